@@ -0,0 +1,20 @@
+#![no_main]
+use std::borrow::Cow;
+
+use libfuzzer_sys::fuzz_target;
+use mezura::{file_parser, io_handler, Language};
+
+// The first byte picks the language, the rest are the contents, e.g. 'cargo +nightly fuzz run parse_str'
+fuzz_target!(|data: &[u8]| {
+    let languages = [include_str!("../../data/languages/Rust.txt"), include_str!("../../data/languages/python.txt"),
+            include_str!("../../data/languages/C.txt"), include_str!("../../data/languages/HTML.txt")];
+    let (first, rest) = match data.split_first() {
+        Some(x) => x,
+        None => return
+    };
+    let mut language: Language = io_handler::parse_string_to_language(Cow::Borrowed(languages[*first as usize % languages.len()]));
+    language.supports_nested_comments = first & 0b1000_0000 != 0;
+    if let Ok(contents) = std::str::from_utf8(rest) {
+        let _ = file_parser::parse_str(contents, &language);
+    }
+});
@@ -0,0 +1,614 @@
+use crate::*;
+
+
+// The finished results of a run, after the faulty files and the languages without files have been removed
+#[derive(Debug)]
+pub struct Aggregate<'a> {
+    pub content_info_map: &'a HashMap<String,LanguageContentInfo>,
+    pub languages_metadata_map: &'a HashMap<String,LanguageMetadata>,
+    // The files that couldn't be parsed, with the reason of each
+    pub faulty_files: &'a [FaultyFile],
+    pub files: usize,
+    pub lines: usize,
+    pub code_lines: usize,
+    pub extra_lines: usize,
+    pub bytes: usize
+}
+
+// A titled list of key-value entries, that is printed after the results and appended to the report
+#[derive(Debug, PartialEq, Clone)]
+pub struct ReportSection {
+    pub title: String,
+    pub entries: Vec<(String, String)>
+}
+
+// Implemented by the users of the library, to derive their own metrics (scores, checks etc) from the results
+// without changing how they are printed. Registered through 'run_with_analyzers'.
+pub trait ReportAnalyzer: Send + Sync {
+    // Returns None if there is nothing to add for this run
+    fn analyze(&self, aggregate: &Aggregate) -> Option<ReportSection>;
+}
+
+impl<'a> Aggregate<'a> {
+    pub fn new(content_info_map: &'a HashMap<String,LanguageContentInfo>, languages_metadata_map: &'a HashMap<String,LanguageMetadata>,
+            faulty_files: &'a [FaultyFile], final_stats: &FinalStats) -> Self
+    {
+        Aggregate {
+            content_info_map,
+            languages_metadata_map,
+            faulty_files,
+            files: final_stats.files,
+            lines: final_stats.lines,
+            code_lines: final_stats.code_lines,
+            extra_lines: final_stats.extra_lines,
+            bytes: final_stats.bytes_size
+        }
+    }
+}
+
+impl ReportSection {
+    pub fn new(title: &str) -> Self {
+        ReportSection {title: title.to_owned(), entries: Vec::new()}
+    }
+
+    pub fn with_entry(mut self, key: &str, value: impl ToString) -> Self {
+        self.entries.push((key.to_owned(), value.to_string()));
+        self
+    }
+}
+
+// Used with '--test-pattern', to compare the test files to the rest, e.g.
+//
+// Tests.
+//
+// Production                 ->  100 files  |  lines 12,500 {8,800 code}  |  1.0 MBs
+// Test                       ->  20 files  |  lines 2,500 {1,200 code}  |  200.0 KBs
+// Test to production ratio   ->  0.14 (code lines)
+#[derive(Debug)]
+pub struct TestRatio;
+
+impl ReportAnalyzer for TestRatio {
+    fn analyze(&self, aggregate: &Aggregate) -> Option<ReportSection> {
+        let mut test = DirStats::default();
+        aggregate.content_info_map.values().for_each(|x| test.add(&x.test_stats));
+        let production = DirStats {
+            files: aggregate.files.saturating_sub(test.files),
+            lines: aggregate.lines.saturating_sub(test.lines),
+            code_lines: aggregate.code_lines.saturating_sub(test.code_lines),
+            bytes: aggregate.bytes.saturating_sub(test.bytes)
+        };
+
+        let section = ReportSection::new("Tests").with_entry("Production", format_stats(&production)).with_entry("Test", format_stats(&test));
+        if production.code_lines == 0 {
+            return Some(section);
+        }
+        Some(section.with_entry("Test to production ratio", format!("{:.2} (code lines)", test.code_lines as f64 / production.code_lines as f64)))
+    }
+}
+
+fn format_stats(stats: &DirStats) -> String {
+    format!("{} files  |  lines {} {{{} code}}  |  {}", with_seperators(stats.files), with_seperators(stats.lines),
+            with_seperators(stats.code_lines), format_size(stats.bytes))
+}
+
+// Used with '--licenses', e.g.
+//
+// Licenses.
+//
+// .py   ->  0 licensed  |  4 unlicensed
+// .rs   ->  30 licensed (MIT 28, Apache-2.0 2)  |  2 unlicensed
+//
+// The extensions are ordered by name and the licenses by their files, the most first
+#[derive(Debug)]
+pub struct LicenseSummary;
+
+impl ReportAnalyzer for LicenseSummary {
+    fn analyze(&self, aggregate: &Aggregate) -> Option<ReportSection> {
+        let mut license_counts = LicenseCounts::new();
+        for content_info in aggregate.content_info_map.values() {
+            for (extension, counts) in content_info.license_counts.iter() {
+                let all_counts = license_counts.entry(extension.clone()).or_default();
+                counts.iter().for_each(|(license, files)| *all_counts.entry(license.clone()).or_insert(0) += files);
+            }
+        }
+        if license_counts.is_empty() {
+            return None;
+        }
+
+        let mut section = ReportSection::new("Licenses");
+        for (extension, counts) in license_counts {
+            let unlicensed = counts.get(UNLICENSED).copied().unwrap_or(0);
+            let mut licenses = counts.into_iter().filter(|(license, _)| license != UNLICENSED).collect::<Vec<_>>();
+            licenses.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            let licensed = licenses.iter().map(|x| x.1).sum::<usize>();
+            let names = if licenses.is_empty() {String::new()} else {
+                format!(" ({})", licenses.iter().map(|(license, files)| format!("{} {}", license, with_seperators(*files))).collect::<Vec<_>>().join(", "))
+            };
+            let key = if extension.is_empty() {"(no extension)".to_owned()} else {format!(".{}", extension)};
+            section = section.with_entry(&key, format!("{} licensed{}  |  {} unlicensed", with_seperators(licensed), names, with_seperators(unlicensed)));
+        }
+        Some(section)
+    }
+}
+
+// Used with '--hygiene', e.g.
+//
+// Hygiene.
+//
+// Byte order mark    ->  3 files
+// Shebang            ->  5 files
+// Line endings       ->  110 LF, 8 CRLF, 2 mixed
+// No final newline   ->  2 files
+#[derive(Debug)]
+pub struct HygieneSummary;
+
+impl ReportAnalyzer for HygieneSummary {
+    fn analyze(&self, aggregate: &Aggregate) -> Option<ReportSection> {
+        if aggregate.files == 0 {
+            return None;
+        }
+        let mut hygiene = HygieneStats::default();
+        aggregate.content_info_map.values().for_each(|x| hygiene.add(&x.hygiene));
+
+        let files = |x: usize| format!("{} files", with_seperators(x));
+        Some(ReportSection::new("Hygiene")
+                .with_entry("Byte order mark", files(hygiene.bom_files))
+                .with_entry("Shebang", files(hygiene.shebang_files))
+                .with_entry("Line endings", format!("{} LF, {} CRLF, {} mixed", with_seperators(hygiene.lf_files), with_seperators(hygiene.crlf_files),
+                        with_seperators(hygiene.mixed_files)))
+                .with_entry("No final newline", files(hygiene.no_final_newline_files)))
+    }
+}
+
+// Used with '--indentation', e.g.
+//
+// Indentation.
+//
+// .go   ->  0.0% spaces, 100.0% tabs, 0.0% mixed
+// .rs   ->  98.5% spaces (4 wide), 1.5% tabs, 0.0% mixed
+//
+// The percentages are of the indented code lines, and the extensions without any are left out
+#[derive(Debug)]
+pub struct IndentationSummary;
+
+impl ReportAnalyzer for IndentationSummary {
+    fn analyze(&self, aggregate: &Aggregate) -> Option<ReportSection> {
+        let mut indentation = IndentationCounts::new();
+        for content_info in aggregate.content_info_map.values() {
+            for (extension, stats) in content_info.indentation.iter() {
+                indentation.entry(extension.clone()).or_default().add(stats);
+            }
+        }
+        indentation.retain(|_, x| x.tab_lines + x.space_lines + x.mixed_lines > 0);
+        if indentation.is_empty() {
+            return None;
+        }
+
+        let mut section = ReportSection::new("Indentation");
+        for (extension, stats) in indentation {
+            let percentage = |x: usize| x as f64 / (stats.tab_lines + stats.space_lines + stats.mixed_lines) as f64 * 100f64;
+            let width = stats.dominant_width().map_or_else(String::new, |x| format!(" ({} wide)", x));
+            let key = if extension.is_empty() {"(no extension)".to_owned()} else {format!(".{}", extension)};
+            section = section.with_entry(&key, format!("{:.1}% spaces{}, {:.1}% tabs, {:.1}% mixed", percentage(stats.space_lines), width,
+                    percentage(stats.tab_lines), percentage(stats.mixed_lines)));
+        }
+        Some(section)
+    }
+}
+
+// Used with '--authors', e.g.
+//
+// Authors.
+//
+// All   ->  Alice 1,200 (60.0%), Bob 600 (30.0%), Carol 150 (7.5%) and 2 others
+// .py   ->  Bob 500 (71.4%), Alice 200 (28.6%)
+// .rs   ->  Alice 1,000 (76.9%), Carol 150 (11.5%), Bob 100 (7.7%) and 2 others
+//
+// The lines are the ones that each author changed last
+#[derive(Debug)]
+pub struct AuthorSummary;
+
+const MAX_LISTED_AUTHORS : usize = 3;
+
+impl ReportAnalyzer for AuthorSummary {
+    fn analyze(&self, aggregate: &Aggregate) -> Option<ReportSection> {
+        let (mut author_counts, mut all_counts) = (AuthorCounts::new(), BTreeMap::<String,usize>::new());
+        for content_info in aggregate.content_info_map.values() {
+            for (extension, counts) in content_info.author_counts.iter() {
+                let extension_counts = author_counts.entry(extension.clone()).or_default();
+                for (author, lines) in counts.iter() {
+                    *extension_counts.entry(author.clone()).or_insert(0) += lines;
+                    *all_counts.entry(author.clone()).or_insert(0) += lines;
+                }
+            }
+        }
+        if all_counts.is_empty() {
+            return None;
+        }
+
+        let mut section = ReportSection::new("Authors").with_entry("All", format_authors(all_counts));
+        for (extension, counts) in author_counts {
+            let key = if extension.is_empty() {"(no extension)".to_owned()} else {format!(".{}", extension)};
+            section = section.with_entry(&key, format_authors(counts));
+        }
+        Some(section)
+    }
+}
+
+fn format_authors(counts: BTreeMap<String,usize>) -> String {
+    let total = counts.values().sum::<usize>().max(1);
+    let mut authors = counts.into_iter().collect::<Vec<_>>();
+    authors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let listed = authors.iter().take(MAX_LISTED_AUTHORS)
+            .map(|(author, lines)| format!("{} {} ({:.1}%)", author, with_seperators(*lines), *lines as f64 / total as f64 * 100f64))
+            .collect::<Vec<_>>().join(", ");
+    match authors.len().saturating_sub(MAX_LISTED_AUTHORS) {
+        0 => listed,
+        1 => format!("{} and 1 other", listed),
+        others => format!("{} and {} others", listed, others)
+    }
+}
+
+// Used with '--estimate', e.g.
+//
+// Estimate.
+//
+// Effort       ->  29.19 person-months
+// Schedule     ->  9.01 months
+// Developers   ->  3.24
+// Cost         ->  328,652
+//
+// With the basic COCOMO model for organic projects, from the code lines of all the languages
+#[derive(Debug)]
+pub struct CostEstimate {
+    // Yearly
+    pub salary: usize,
+    // A percentage of the salary
+    pub overhead: usize
+}
+
+impl ReportAnalyzer for CostEstimate {
+    fn analyze(&self, aggregate: &Aggregate) -> Option<ReportSection> {
+        if aggregate.code_lines == 0 {
+            return None;
+        }
+        let effort = 2.4 * (aggregate.code_lines as f64 / 1000f64).powf(1.05);
+        let schedule = 2.5 * effort.powf(0.38);
+        let cost = effort / 12f64 * self.salary as f64 * self.overhead as f64 / 100f64;
+
+        Some(ReportSection::new("Estimate").with_entry("Effort", format!("{:.2} person-months", effort))
+                .with_entry("Schedule", format!("{:.2} months", schedule))
+                .with_entry("Developers", format!("{:.2}", effort / schedule))
+                .with_entry("Cost", with_seperators(cost.round() as usize)))
+    }
+}
+
+// Shown when generated files are found and '--skip-generated' isn't used, e.g.
+//
+// Generated code.
+//
+// Go     ->  12 files  |  lines 30,000 {28,000 code}  |  1.2 MBs  |  45.2% of the code lines
+// Rust   ->  1 files  |  lines 800 {700 code}  |  30.0 KBs  |  2.1% of the code lines
+#[derive(Debug)]
+pub struct GeneratedCode;
+
+impl ReportAnalyzer for GeneratedCode {
+    fn analyze(&self, aggregate: &Aggregate) -> Option<ReportSection> {
+        make_part_of_code_section(aggregate, "Generated code", |x| &x.generated_stats)
+    }
+}
+
+// Shown when minified files are found and '--skip-minified' isn't used, like the generated code
+#[derive(Debug)]
+pub struct MinifiedCode;
+
+impl ReportAnalyzer for MinifiedCode {
+    fn analyze(&self, aggregate: &Aggregate) -> Option<ReportSection> {
+        make_part_of_code_section(aggregate, "Minified code", |x| &x.minified_stats)
+    }
+}
+
+// The languages that have any files of the part, with how much of their code lines it is
+fn make_part_of_code_section(aggregate: &Aggregate, title: &str, get_stats: impl Fn(&LanguageContentInfo) -> &DirStats) -> Option<ReportSection> {
+    let mut languages = aggregate.content_info_map.iter().filter(|(_, x)| get_stats(x).files > 0).collect::<Vec<_>>();
+    if languages.is_empty() {
+        return None;
+    }
+    languages.sort_by_key(|(name, _)| *name);
+
+    let mut section = ReportSection::new(title);
+    for (name, content_info) in languages {
+        let stats = get_stats(content_info);
+        let percentage = stats.code_lines as f64 / content_info.code_lines.max(1) as f64 * 100f64;
+        section = section.with_entry(name, format!("{}  |  {:.1}% of the code lines", format_stats(stats), percentage));
+    }
+    Some(section)
+}
+
+// Shown when jupyter notebooks are found, e.g.
+//
+// Notebooks.
+//
+// Python   ->  12 notebooks  |  340 code cells  |  120 markdown cells (1,500 lines)
+// R        ->  1 notebooks  |  8 code cells  |  3 markdown cells (20 lines)
+#[derive(Debug)]
+pub struct NotebookSummary;
+
+impl ReportAnalyzer for NotebookSummary {
+    fn analyze(&self, aggregate: &Aggregate) -> Option<ReportSection> {
+        let mut languages = aggregate.content_info_map.iter().filter(|(_, x)| x.notebooks.notebooks > 0).collect::<Vec<_>>();
+        if languages.is_empty() {
+            return None;
+        }
+        languages.sort_by_key(|(name, _)| *name);
+
+        let mut section = ReportSection::new("Notebooks");
+        for (name, content_info) in languages {
+            let notebooks = &content_info.notebooks;
+            section = section.with_entry(name, format!("{} notebooks  |  {} code cells  |  {} markdown cells ({} lines)", with_seperators(notebooks.notebooks),
+                    with_seperators(notebooks.code_cells), with_seperators(notebooks.markdown_cells), with_seperators(notebooks.markdown_lines)));
+        }
+        Some(section)
+    }
+}
+
+// Used with '--embedded', e.g.
+//
+// Embedded code.
+//
+// CSS   ->  14 regions  |  lines 900 {850 code}  |  22.0 KBs  |  60.0% of the code lines
+// JS    ->  20 regions  |  lines 1,200 {1,000 code}  |  35.2 KBs  |  12.5% of the code lines
+#[derive(Debug)]
+pub struct EmbeddedCode;
+
+impl ReportAnalyzer for EmbeddedCode {
+    fn analyze(&self, aggregate: &Aggregate) -> Option<ReportSection> {
+        let mut languages = aggregate.content_info_map.iter().filter(|(_, x)| x.embedded_stats.files > 0).collect::<Vec<_>>();
+        if languages.is_empty() {
+            return None;
+        }
+        languages.sort_by_key(|(name, _)| *name);
+
+        let mut section = ReportSection::new("Embedded code");
+        for (name, content_info) in languages {
+            let stats = &content_info.embedded_stats;
+            let percentage = stats.code_lines as f64 / content_info.code_lines.max(1) as f64 * 100f64;
+            section = section.with_entry(name, format!("{} regions  |  lines {} {{{} code}}  |  {}  |  {:.1}% of the code lines",
+                    with_seperators(stats.files), with_seperators(stats.lines), with_seperators(stats.code_lines), format_size(stats.bytes), percentage));
+        }
+        Some(section)
+    }
+}
+
+// The analyzers that come with the program, which are enabled through the configuration
+pub fn get_builtin_analyzers(config: &Configuration) -> Vec<Box<dyn ReportAnalyzer>> {
+    let mut analyzers : Vec<Box<dyn ReportAnalyzer>> = Vec::new();
+    if config.test_patterns.is_some() {
+        analyzers.push(Box::new(TestRatio));
+    }
+    if config.licenses {
+        analyzers.push(Box::new(LicenseSummary));
+    }
+    if config.hygiene {
+        analyzers.push(Box::new(HygieneSummary));
+    }
+    if config.indentation {
+        analyzers.push(Box::new(IndentationSummary));
+    }
+    if config.authors {
+        analyzers.push(Box::new(AuthorSummary));
+    }
+    if config.estimate {
+        analyzers.push(Box::new(CostEstimate {salary: config.salary, overhead: config.overhead}));
+    }
+    if !config.skip_generated {
+        analyzers.push(Box::new(GeneratedCode));
+    }
+    if !config.skip_minified {
+        analyzers.push(Box::new(MinifiedCode));
+    }
+    if config.embedded {
+        analyzers.push(Box::new(EmbeddedCode));
+    }
+    // Without any notebooks or plugin metrics they add nothing
+    analyzers.push(Box::new(NotebookSummary));
+    analyzers.push(Box::new(plugins::PluginSummary));
+    analyzers
+}
+
+// The sections are kept in the order that the analyzers were registered
+pub fn run_analyzers(analyzers: &[Box<dyn ReportAnalyzer>], aggregate: &Aggregate) -> Vec<ReportSection> {
+    analyzers.iter().filter_map(|x| x.analyze(aggregate)).collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct CommentDensity;
+
+    impl ReportAnalyzer for CommentDensity {
+        fn analyze(&self, aggregate: &Aggregate) -> Option<ReportSection> {
+            if aggregate.lines == 0 {
+                return None;
+            }
+            Some(ReportSection::new("Comment density").with_entry("Extra lines", format!("{:.1}%", aggregate.extra_lines as f64 / aggregate.lines as f64 * 100f64)))
+        }
+    }
+
+    struct LanguageCount;
+
+    impl ReportAnalyzer for LanguageCount {
+        fn analyze(&self, aggregate: &Aggregate) -> Option<ReportSection> {
+            Some(ReportSection::new("Languages").with_entry("Count", aggregate.languages_metadata_map.len()))
+        }
+    }
+
+    #[test]
+    fn test_run_analyzers() {
+        let content_info_map = hashmap!["Rust".to_owned() => LanguageContentInfo::new(200, 150, HashMap::new())];
+        let languages_metadata_map = hashmap!["Rust".to_owned() => LanguageMetadata::new(4, 8000)];
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+        let analyzers : Vec<Box<dyn ReportAnalyzer>> = vec![Box::new(CommentDensity), Box::new(LanguageCount)];
+
+        let sections = run_analyzers(&analyzers, &Aggregate::new(&content_info_map, &languages_metadata_map, &[], &final_stats));
+        assert_eq!(vec![ReportSection::new("Comment density").with_entry("Extra lines", "25.0%"),
+                ReportSection::new("Languages").with_entry("Count", 1)], sections);
+
+        let (empty_content_info_map, empty_metadata_map) = (HashMap::new(), HashMap::new());
+        let empty_stats = FinalStats::calculate(&empty_content_info_map, &empty_metadata_map, false);
+        let sections = run_analyzers(&analyzers, &Aggregate::new(&empty_content_info_map, &empty_metadata_map, &[], &empty_stats));
+        assert_eq!(vec![ReportSection::new("Languages").with_entry("Count", 0)], sections);
+    }
+
+    #[test]
+    fn test_test_ratio() {
+        let mut rust = LanguageContentInfo::new(1000, 800, HashMap::new());
+        rust.test_stats = DirStats {files: 1, lines: 250, code_lines: 200, bytes: 2000};
+        let content_info_map = hashmap!["Rust".to_owned() => rust, "C".to_owned() => LanguageContentInfo::new(500, 400, HashMap::new())];
+        let languages_metadata_map = hashmap!["Rust".to_owned() => LanguageMetadata::new(4, 8000), "C".to_owned() => LanguageMetadata::new(2, 4000)];
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+
+        assert_eq!(Some(ReportSection::new("Tests").with_entry("Production", "5 files  |  lines 1,250 {1,000 code}  |  10.0 KBs")
+                .with_entry("Test", "1 files  |  lines 250 {200 code}  |  2.0 KBs").with_entry("Test to production ratio", "0.20 (code lines)")),
+                TestRatio.analyze(&Aggregate::new(&content_info_map, &languages_metadata_map, &[], &final_stats)));
+    }
+
+    #[test]
+    fn test_indentation_summary() {
+        let mut rust = LanguageContentInfo::new(1000, 800, HashMap::new());
+        rust.indentation = BTreeMap::from([("rs".to_owned(), IndentationStats {tab_lines: 3, space_lines: 194, mixed_lines: 3,
+                width_counts: [0, 10, 0, 40, 0, 0, 0, 0]}), ("txt".to_owned(), IndentationStats::default())]);
+        let mut go = LanguageContentInfo::new(500, 400, HashMap::new());
+        go.indentation = BTreeMap::from([("go".to_owned(), IndentationStats {tab_lines: 50, ..IndentationStats::default()})]);
+        let content_info_map = hashmap!["Rust".to_owned() => rust, "Go".to_owned() => go];
+        let languages_metadata_map = hashmap!["Rust".to_owned() => LanguageMetadata::new(4, 8000), "Go".to_owned() => LanguageMetadata::new(2, 4000)];
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+
+        assert_eq!(Some(ReportSection::new("Indentation").with_entry(".go", "0.0% spaces, 100.0% tabs, 0.0% mixed")
+                .with_entry(".rs", "97.0% spaces (4 wide), 1.5% tabs, 1.5% mixed")),
+                IndentationSummary.analyze(&Aggregate::new(&content_info_map, &languages_metadata_map, &[], &final_stats)));
+    }
+
+    #[test]
+    fn test_author_summary() {
+        let counts = |x: &[(&str, usize)]| x.iter().map(|(author, lines)| ((*author).to_owned(), *lines)).collect::<BTreeMap<_,_>>();
+        let mut rust = LanguageContentInfo::new(1000, 800, HashMap::new());
+        rust.author_counts = BTreeMap::from([("rs".to_owned(), counts(&[("Alice", 600), ("Bob", 300), ("Carol", 60), ("Dan", 40)]))]);
+        let mut python = LanguageContentInfo::new(200, 150, HashMap::new());
+        python.author_counts = BTreeMap::from([("py".to_owned(), counts(&[("Bob", 150), ("Alice", 50)]))]);
+        let content_info_map = hashmap!["Rust".to_owned() => rust, "Python".to_owned() => python];
+        let languages_metadata_map = hashmap!["Rust".to_owned() => LanguageMetadata::new(4, 8000), "Python".to_owned() => LanguageMetadata::new(2, 4000)];
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+
+        assert_eq!(Some(ReportSection::new("Authors").with_entry("All", "Alice 650 (54.2%), Bob 450 (37.5%), Carol 60 (5.0%) and 1 other")
+                .with_entry(".py", "Bob 150 (75.0%), Alice 50 (25.0%)")
+                .with_entry(".rs", "Alice 600 (60.0%), Bob 300 (30.0%), Carol 60 (6.0%) and 1 other")),
+                AuthorSummary.analyze(&Aggregate::new(&content_info_map, &languages_metadata_map, &[], &final_stats)));
+    }
+
+    #[test]
+    fn test_cost_estimate() {
+        let content_info_map = hashmap!["Rust".to_owned() => LanguageContentInfo::new(12_000, 10_000, HashMap::new())];
+        let languages_metadata_map = hashmap!["Rust".to_owned() => LanguageMetadata::new(40, 400_000)];
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+        let estimate = CostEstimate {salary: 56_286, overhead: 240};
+
+        assert_eq!(Some(ReportSection::new("Estimate").with_entry("Effort", "26.93 person-months").with_entry("Schedule", "8.74 months")
+                .with_entry("Developers", "3.08").with_entry("Cost", "303,139")),
+                estimate.analyze(&Aggregate::new(&content_info_map, &languages_metadata_map, &[], &final_stats)));
+        let content_info_map = hashmap!["Rust".to_owned() => LanguageContentInfo::new(0, 0, HashMap::new())];
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+        assert_eq!(None, estimate.analyze(&Aggregate::new(&content_info_map, &languages_metadata_map, &[], &final_stats)));
+    }
+
+    #[test]
+    fn test_generated_code() {
+        let mut go = LanguageContentInfo::new(1000, 800, HashMap::new());
+        go.generated_stats = DirStats {files: 2, lines: 500, code_lines: 400, bytes: 20_000};
+        let content_info_map = hashmap!["Go".to_owned() => go, "Rust".to_owned() => LanguageContentInfo::new(500, 400, HashMap::new())];
+        let languages_metadata_map = hashmap!["Go".to_owned() => LanguageMetadata::new(4, 40_000), "Rust".to_owned() => LanguageMetadata::new(2, 4000)];
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+
+        assert_eq!(Some(ReportSection::new("Generated code").with_entry("Go", "2 files  |  lines 500 {400 code}  |  20.0 KBs  |  50.0% of the code lines")),
+                GeneratedCode.analyze(&Aggregate::new(&content_info_map, &languages_metadata_map, &[], &final_stats)));
+        let content_info_map = hashmap!["Rust".to_owned() => LanguageContentInfo::new(500, 400, HashMap::new())];
+        assert_eq!(None, GeneratedCode.analyze(&Aggregate::new(&content_info_map, &languages_metadata_map, &[], &final_stats)));
+    }
+
+    #[test]
+    fn test_minified_code() {
+        let mut javascript = LanguageContentInfo::new(1000, 1000, HashMap::new());
+        javascript.minified_stats = DirStats {files: 1, lines: 1, code_lines: 1, bytes: 90_000};
+        let content_info_map = hashmap!["JavaScript".to_owned() => javascript];
+        let languages_metadata_map = hashmap!["JavaScript".to_owned() => LanguageMetadata::new(10, 120_000)];
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+
+        assert_eq!(Some(ReportSection::new("Minified code").with_entry("JavaScript", "1 files  |  lines 1 {1 code}  |  90.0 KBs  |  0.1% of the code lines")),
+                MinifiedCode.analyze(&Aggregate::new(&content_info_map, &languages_metadata_map, &[], &final_stats)));
+        assert_eq!(None, GeneratedCode.analyze(&Aggregate::new(&content_info_map, &languages_metadata_map, &[], &final_stats)));
+    }
+
+    #[test]
+    fn test_embedded_code() {
+        let mut js = LanguageContentInfo::new(1000, 800, HashMap::new());
+        js.embedded_stats = DirStats {files: 20, lines: 150, code_lines: 100, bytes: 900};
+        let content_info_map = hashmap!["JS".to_owned() => js, "HTML".to_owned() => LanguageContentInfo::new(500, 400, HashMap::new())];
+        let languages_metadata_map = hashmap!["JS".to_owned() => LanguageMetadata::new(4, 40_000), "HTML".to_owned() => LanguageMetadata::new(2, 4000)];
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+
+        assert_eq!(Some(ReportSection::new("Embedded code").with_entry("JS", "20 regions  |  lines 150 {100 code}  |  900.0 Bytes  |  12.5% of the code lines")),
+                EmbeddedCode.analyze(&Aggregate::new(&content_info_map, &languages_metadata_map, &[], &final_stats)));
+        let content_info_map = hashmap!["HTML".to_owned() => LanguageContentInfo::new(500, 400, HashMap::new())];
+        assert_eq!(None, EmbeddedCode.analyze(&Aggregate::new(&content_info_map, &languages_metadata_map, &[], &final_stats)));
+    }
+
+    #[test]
+    fn test_notebook_summary() {
+        let mut python = LanguageContentInfo::new(1000, 800, HashMap::new());
+        python.notebooks = NotebookStats {notebooks: 12, code_cells: 340, markdown_cells: 120, markdown_lines: 1500};
+        let content_info_map = hashmap!["Python".to_owned() => python, "Rust".to_owned() => LanguageContentInfo::new(500, 400, HashMap::new())];
+        let languages_metadata_map = hashmap!["Python".to_owned() => LanguageMetadata::new(12, 40_000), "Rust".to_owned() => LanguageMetadata::new(2, 4000)];
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+
+        assert_eq!(Some(ReportSection::new("Notebooks").with_entry("Python", "12 notebooks  |  340 code cells  |  120 markdown cells (1,500 lines)")),
+                NotebookSummary.analyze(&Aggregate::new(&content_info_map, &languages_metadata_map, &[], &final_stats)));
+        let content_info_map = hashmap!["Rust".to_owned() => LanguageContentInfo::new(500, 400, HashMap::new())];
+        assert_eq!(None, NotebookSummary.analyze(&Aggregate::new(&content_info_map, &languages_metadata_map, &[], &final_stats)));
+    }
+
+    #[test]
+    fn test_hygiene_summary() {
+        let mut rust = LanguageContentInfo::new(1000, 800, HashMap::new());
+        rust.hygiene = HygieneStats {bom_files: 1, shebang_files: 0, lf_files: 3, crlf_files: 1, mixed_files: 0, no_final_newline_files: 1};
+        let mut python = LanguageContentInfo::new(500, 400, HashMap::new());
+        python.hygiene = HygieneStats {bom_files: 0, shebang_files: 2, lf_files: 1, crlf_files: 0, mixed_files: 1, no_final_newline_files: 0};
+        let content_info_map = hashmap!["Rust".to_owned() => rust, "Python".to_owned() => python];
+        let languages_metadata_map = hashmap!["Rust".to_owned() => LanguageMetadata::new(4, 8000), "Python".to_owned() => LanguageMetadata::new(2, 4000)];
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+
+        assert_eq!(Some(ReportSection::new("Hygiene").with_entry("Byte order mark", "1 files").with_entry("Shebang", "2 files")
+                .with_entry("Line endings", "4 LF, 1 CRLF, 1 mixed").with_entry("No final newline", "1 files")),
+                HygieneSummary.analyze(&Aggregate::new(&content_info_map, &languages_metadata_map, &[], &final_stats)));
+    }
+
+    #[test]
+    fn test_license_summary() {
+        let mut rust = LanguageContentInfo::new(1000, 800, HashMap::new());
+        rust.license_counts = BTreeMap::from([("rs".to_owned(), BTreeMap::from([("MIT".to_owned(), 28), ("Apache-2.0".to_owned(), 2),
+                (UNLICENSED.to_owned(), 2)]))]);
+        let mut python = LanguageContentInfo::new(500, 400, HashMap::new());
+        python.license_counts = BTreeMap::from([("py".to_owned(), BTreeMap::from([(UNLICENSED.to_owned(), 4)])),
+                (String::new(), BTreeMap::from([("MIT".to_owned(), 1)]))]);
+        let content_info_map = hashmap!["Rust".to_owned() => rust, "Python".to_owned() => python];
+        let languages_metadata_map = hashmap!["Rust".to_owned() => LanguageMetadata::new(32, 8000), "Python".to_owned() => LanguageMetadata::new(5, 4000)];
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+
+        assert_eq!(Some(ReportSection::new("Licenses").with_entry("(no extension)", "1 licensed (MIT 1)  |  0 unlicensed")
+                .with_entry(".py", "0 licensed  |  4 unlicensed").with_entry(".rs", "30 licensed (MIT 28, Apache-2.0 2)  |  2 unlicensed")),
+                LicenseSummary.analyze(&Aggregate::new(&content_info_map, &languages_metadata_map, &[], &final_stats)));
+        let content_info_map = hashmap!["Rust".to_owned() => LanguageContentInfo::new(1000, 800, HashMap::new())];
+        assert_eq!(None, LicenseSummary.analyze(&Aggregate::new(&content_info_map, &languages_metadata_map, &[], &final_stats)));
+    }
+}
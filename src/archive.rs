@@ -0,0 +1,480 @@
+// Reads the files of zip and (gzipped) tar archives in memory, so that they can be analyzed without extracting them.
+// Only what the archives of source code releases use is supported: stored or deflated zip entries without zip64
+// or encryption, and ustar tar files with the long names of GNU and pax.
+
+use crate::*;
+
+const ZIP_CENTRAL_HEADER_SIGNATURE : usize = 0x02014b50;
+const ZIP_END_SIGNATURE            : usize = 0x06054b50;
+const ZIP_END_RECORD_LEN           : usize = 22;
+const TAR_BLOCK_SIZE               : usize = 512;
+
+// An archive, or an entry of a zip, may inflate to this many times its compressed size, which the archives of source code
+// don't come close to, unlike a bomb. The small ones that are just very repetitive are allowed up to the minimum
+const MAX_INFLATION_RATIO : usize = 100;
+const MIN_INFLATION_LIMIT : usize = 1024 * 1024;
+
+const TRUNCATED_ARCHIVE : &str = "the archive is truncated";
+const INVALID_DEFLATE   : &str = "the compressed data is invalid";
+const OVER_LIMIT        : &str = "the archive inflates to more than its limit, so the rest of it was not read";
+
+// The lengths and distances of the deflate codes (RFC 1951, 3.2.5)
+const LENGTH_BASES        : [usize; 29] = [3,4,5,6,7,8,9,10,11,13,15,17,19,23,27,31,35,43,51,59,67,83,99,115,131,163,195,227,258];
+const LENGTH_EXTRA_BITS   : [u32; 29] = [0,0,0,0,0,0,0,0,1,1,1,1,2,2,2,2,3,3,3,3,4,4,4,4,5,5,5,5,0];
+const DISTANCE_BASES      : [usize; 30] = [1,2,3,4,5,7,9,13,17,25,33,49,65,97,129,193,257,385,513,769,1025,1537,2049,3073,4097,6145,
+        8193,12289,16385,24577];
+const DISTANCE_EXTRA_BITS : [u32; 30] = [0,0,0,0,1,1,2,2,3,3,4,4,5,5,6,6,7,7,8,8,9,9,10,10,11,11,12,12,13,13];
+// The order in which the lengths of the code length code are stored
+const CODE_LENGTH_ORDER   : [usize; 19] = [16,17,18,0,8,7,9,6,10,5,11,4,12,3,13,2,14,1,15];
+
+
+#[derive(Debug,PartialEq)]
+pub struct ArchiveEntry {
+    // The path inside the archive, with '/' as the separator
+    pub name: String,
+    // As declared by the archive
+    pub size: usize,
+    pub contents: EntryContents
+}
+
+#[derive(Debug,PartialEq)]
+pub enum EntryContents {
+    // The entry is not wanted, so it is not read
+    Unread,
+    Read(Vec<u8>),
+    // It inflates to more than the limit of the archive or than its declared size, so it is not read
+    OverLimit
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    bit_position: usize
+}
+
+// A canonical huffman code, as the number of codes of each length and the symbols ordered by their codes
+struct Huffman {
+    counts: [usize; 16],
+    symbols: Vec<usize>
+}
+
+pub fn is_archive(path: &Path) -> bool {
+    let name = path.file_name().and_then(|x| x.to_str()).unwrap_or("").to_lowercase();
+    [".zip", ".tar", ".tar.gz", ".tgz"].iter().any(|x| name.ends_with(x))
+}
+
+// Passes the regular files of the archive one at a time, with the contents of the ones whose name is wanted.
+// Nothing is inflated past the limit of the compressed data (see MAX_INFLATION_RATIO) or the memory budget in bytes,
+// or past the declared size of a zip entry. The entry that goes over is passed as OverLimit, and the entries
+// that were passed before an error are kept.
+pub fn read_entries(path: &Path, memory_budget: usize, is_wanted: impl Fn(&str) -> bool, on_entry: impl FnMut(ArchiveEntry))
+        -> Result<(), String>
+{
+    let data = fs::read(path).map_err(|x| x.to_string())?;
+    let name = path.to_string_lossy().to_lowercase();
+    if name.ends_with(".zip") {
+        read_zip_entries(&data, memory_budget, is_wanted, on_entry)
+    } else if name.ends_with(".tar") {
+        read_tar_entries(&data, false, is_wanted, on_entry)
+    } else {
+        let (tar, is_cut) = gunzip(&data, get_inflation_limit(data.len(), memory_budget))?;
+        read_tar_entries(&tar, is_cut, is_wanted, on_entry)
+    }
+}
+
+fn get_inflation_limit(compressed_len: usize, memory_budget: usize) -> usize {
+    compressed_len.saturating_mul(MAX_INFLATION_RATIO).max(MIN_INFLATION_LIMIT).min(memory_budget)
+}
+
+fn read_zip_entries(data: &[u8], memory_budget: usize, is_wanted: impl Fn(&str) -> bool, mut on_entry: impl FnMut(ArchiveEntry))
+        -> Result<(), String>
+{
+    // The end record is at the end, unless the archive has a comment
+    let end = (0..=data.len().saturating_sub(ZIP_END_RECORD_LEN)).rev().find(|i| read_le(data, *i, 4) == Ok(ZIP_END_SIGNATURE))
+            .ok_or("not a zip archive")?;
+    let entries_num = read_le(data, end + 10, 2)?;
+    let mut offset = read_le(data, end + 16, 4)?;
+
+    for _ in 0..entries_num {
+        if read_le(data, offset, 4)? != ZIP_CENTRAL_HEADER_SIGNATURE {
+            return Err("the central directory of the archive is invalid".to_owned());
+        }
+        let (flags, method) = (read_le(data, offset + 8, 2)?, read_le(data, offset + 10, 2)?);
+        let (compressed_size, size) = (read_le(data, offset + 20, 4)?, read_le(data, offset + 24, 4)?);
+        let (name_len, extra_len, comment_len) = (read_le(data, offset + 28, 2)?, read_le(data, offset + 30, 2)?, read_le(data, offset + 32, 2)?);
+        let local_offset = read_le(data, offset + 42, 4)?;
+        let name = String::from_utf8_lossy(data.get(offset + 46..offset + 46 + name_len).ok_or(TRUNCATED_ARCHIVE)?).into_owned();
+        offset += 46 + name_len + extra_len + comment_len;
+        if name.ends_with('/') {
+            continue;
+        }
+
+        let contents = if is_wanted(&name) {
+            if flags & 1 != 0 {
+                return Err(format!("'{}' is encrypted", name));
+            }
+            let start = local_offset + 30 + read_le(data, local_offset + 26, 2)? + read_le(data, local_offset + 28, 2)?;
+            let compressed = data.get(start..start + compressed_size).ok_or(TRUNCATED_ARCHIVE)?;
+            let limit = get_inflation_limit(compressed_size, memory_budget);
+            let mut output = Vec::new();
+            match method {
+                _ if size > limit => EntryContents::OverLimit,
+                0 if compressed_size > size => EntryContents::OverLimit,
+                0 => EntryContents::Read(compressed.to_vec()),
+                8 => match inflate(compressed, &mut output, size) {
+                    Ok(_) => EntryContents::Read(output),
+                    Err(x) if x == OVER_LIMIT => EntryContents::OverLimit,
+                    Err(x) => return Err(x)
+                },
+                x => return Err(format!("the compression method {} of '{}' is not supported", x, name))
+            }
+        } else {
+            EntryContents::Unread
+        };
+        on_entry(ArchiveEntry {name, size, contents});
+    }
+    Ok(())
+}
+
+// The data is cut if its inflation was stopped at the limit, in which case the entry that it ends in is passed as OverLimit
+fn read_tar_entries(data: &[u8], is_cut: bool, is_wanted: impl Fn(&str) -> bool, mut on_entry: impl FnMut(ArchiveEntry)) -> Result<(), String> {
+    let mut offset = 0;
+    // Set by the entry before the one it names
+    let mut long_name = None;
+    while offset + TAR_BLOCK_SIZE <= data.len() {
+        let header = &data[offset..offset + TAR_BLOCK_SIZE];
+        // The archive ends with empty blocks
+        if header.iter().all(|x| *x == 0) {
+            return Ok(());
+        }
+        let size = parse_octal(&header[124..136]).ok_or("the header of a tar entry is invalid")?;
+        let start = offset + TAR_BLOCK_SIZE;
+        let is_file = matches!(header[156], b'0' | 0);
+        let contents = match data.get(start..start + size) {
+            Some(x) => x,
+            None if is_cut && is_file => {
+                let name = long_name.take().unwrap_or_else(|| get_tar_name(header));
+                let contents = if is_wanted(&name) {EntryContents::OverLimit} else {EntryContents::Unread};
+                on_entry(ArchiveEntry {name, size, contents});
+                return Err(OVER_LIMIT.to_owned());
+            },
+            None if is_cut => return Err(OVER_LIMIT.to_owned()),
+            None => return Err(TRUNCATED_ARCHIVE.to_owned())
+        };
+        offset = start + size.div_ceil(TAR_BLOCK_SIZE) * TAR_BLOCK_SIZE;
+
+        match header[156] {
+            _ if is_file => {
+                let name = long_name.take().unwrap_or_else(|| get_tar_name(header));
+                let contents = if is_wanted(&name) {EntryContents::Read(contents.to_vec())} else {EntryContents::Unread};
+                on_entry(ArchiveEntry {name, size, contents});
+            },
+            b'L' => long_name = Some(read_tar_string(contents)),
+            b'x' => long_name = get_pax_path(contents).or(long_name),
+            // The global pax headers don't name a single entry
+            b'g' => (),
+            _ => long_name = None
+        }
+    }
+    if is_cut {Err(OVER_LIMIT.to_owned())} else {Ok(())}
+}
+
+// The ustar format keeps the start of the longer paths in a prefix
+fn get_tar_name(header: &[u8]) -> String {
+    let name = read_tar_string(&header[..100]);
+    let prefix = if &header[257..262] == b"ustar" {read_tar_string(&header[345..500])} else {String::new()};
+    if prefix.is_empty() {name} else {format!("{}/{}", prefix, name)}
+}
+
+fn read_tar_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|x| *x == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+fn parse_octal(bytes: &[u8]) -> Option<usize> {
+    let s = read_tar_string(bytes);
+    let s = s.trim();
+    if s.is_empty() {Some(0)} else {usize::from_str_radix(s, 8).ok()}
+}
+
+// The records of pax are in the form '<length> <key>=<value>\n'
+fn get_pax_path(records: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(records).lines().find_map(|x| x.split_once(' ')?.1.strip_prefix("path=").map(|x| x.to_owned()))
+}
+
+// The members of a gzip file (RFC 1952) make up a single stream. Returns the inflated data, and whether it was cut at the
+// maximum length
+fn gunzip(data: &[u8], max_len: usize) -> Result<(Vec<u8>, bool), String> {
+    let mut output = Vec::with_capacity(data.len().saturating_mul(4).min(max_len));
+    let mut offset = 0;
+    // Some tools pad the file with zeros
+    while data[offset..].iter().any(|x| *x != 0) {
+        if data.len() < offset + 10 || data[offset] != 0x1f || data[offset + 1] != 0x8b || data[offset + 2] != 8 {
+            return Err("not a gzip file".to_owned());
+        }
+        let flags = data[offset + 3];
+        let mut i = offset + 10;
+        if flags & 4 != 0 {
+            i += 2 + read_le(data, i, 2)?;
+        }
+        // The name and the comment end with a zero
+        for flag in [8, 16] {
+            if flags & flag != 0 {
+                i += data.get(i..).and_then(|x| x.iter().position(|x| *x == 0)).ok_or(TRUNCATED_ARCHIVE)? + 1;
+            }
+        }
+        if flags & 2 != 0 {
+            i += 2;
+        }
+
+        let consumed = match inflate(data.get(i..).ok_or(TRUNCATED_ARCHIVE)?, &mut output, max_len) {
+            Ok(x) => x,
+            Err(x) if x == OVER_LIMIT => return Ok((output, true)),
+            Err(x) => return Err(x)
+        };
+        // Followed by the CRC-32 and the size
+        offset = (i + consumed + 8).min(data.len());
+    }
+    Ok((output, false))
+}
+
+// Adds the inflated data (RFC 1951) to the output and returns how many of the bytes were used.
+// Stops with OVER_LIMIT as soon as the output would get longer than the maximum length, keeping what was inflated.
+fn inflate(data: &[u8], output: &mut Vec<u8>, max_len: usize) -> Result<usize, String> {
+    let mut reader = BitReader {data, bit_position: 0};
+    output.reserve(data.len().saturating_mul(4).min(max_len.saturating_sub(output.len())));
+    loop {
+        let is_last = reader.read_bits(1)? == 1;
+        match reader.read_bits(2)? {
+            0 => {
+                let start = reader.bit_position.div_ceil(8);
+                let len = read_le(data, start, 2)?;
+                if output.len() + len > max_len {
+                    return Err(OVER_LIMIT.to_owned());
+                }
+                output.extend_from_slice(data.get(start + 4..start + 4 + len).ok_or(TRUNCATED_ARCHIVE)?);
+                reader.bit_position = (start + 4 + len) * 8;
+            },
+            1 => {
+                let (literal_code, distance_code) = get_fixed_codes();
+                inflate_block(&mut reader, output, &literal_code, &distance_code, max_len)?;
+            },
+            2 => {
+                let (literal_code, distance_code) = read_dynamic_codes(&mut reader)?;
+                inflate_block(&mut reader, output, &literal_code, &distance_code, max_len)?;
+            },
+            _ => return Err(INVALID_DEFLATE.to_owned())
+        }
+        if is_last {
+            return Ok(reader.bit_position.div_ceil(8));
+        }
+    }
+}
+
+fn inflate_block(reader: &mut BitReader, output: &mut Vec<u8>, literal_code: &Huffman, distance_code: &Huffman, max_len: usize)
+        -> Result<(), String>
+{
+    loop {
+        let symbol = literal_code.decode(reader)?;
+        if symbol < 256 && output.len() == max_len {
+            return Err(OVER_LIMIT.to_owned());
+        } else if symbol < 256 {
+            output.push(symbol as u8);
+            continue;
+        } else if symbol == 256 {
+            return Ok(());
+        }
+
+        let i = symbol - 257;
+        if i >= LENGTH_BASES.len() {
+            return Err(INVALID_DEFLATE.to_owned());
+        }
+        let length = LENGTH_BASES[i] + reader.read_bits(LENGTH_EXTRA_BITS[i])?;
+        let i = distance_code.decode(reader)?;
+        if i >= DISTANCE_BASES.len() {
+            return Err(INVALID_DEFLATE.to_owned());
+        }
+        let distance = DISTANCE_BASES[i] + reader.read_bits(DISTANCE_EXTRA_BITS[i])?;
+        if distance > output.len() {
+            return Err(INVALID_DEFLATE.to_owned());
+        }
+        if output.len() + length > max_len {
+            return Err(OVER_LIMIT.to_owned());
+        }
+        // The copy may overlap with what it adds
+        let start = output.len() - distance;
+        for j in start..start + length {
+            output.push(output[j]);
+        }
+    }
+}
+
+fn get_fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [8u8; 288];
+    lengths[144..256].iter_mut().for_each(|x| *x = 9);
+    lengths[256..280].iter_mut().for_each(|x| *x = 7);
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+fn read_dynamic_codes(reader: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    let literals_num = reader.read_bits(5)? + 257;
+    let distances_num = reader.read_bits(5)? + 1;
+    let code_lengths_num = reader.read_bits(4)? + 4;
+
+    let mut code_length_lengths = [0u8; 19];
+    for i in CODE_LENGTH_ORDER.iter().take(code_lengths_num) {
+        code_length_lengths[*i] = reader.read_bits(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&code_length_lengths);
+
+    let mut lengths = Vec::with_capacity(literals_num + distances_num);
+    while lengths.len() < literals_num + distances_num {
+        let (length, repeat) = match code_length_code.decode(reader)? {
+            x @ 0..=15 => (x as u8, 1),
+            16 => (*lengths.last().ok_or(INVALID_DEFLATE)?, 3 + reader.read_bits(2)?),
+            17 => (0, 3 + reader.read_bits(3)?),
+            _ => (0, 11 + reader.read_bits(7)?)
+        };
+        lengths.extend(std::iter::repeat_n(length, repeat));
+    }
+    if lengths.len() != literals_num + distances_num {
+        return Err(INVALID_DEFLATE.to_owned());
+    }
+    Ok((Huffman::new(&lengths[..literals_num]), Huffman::new(&lengths[literals_num..])))
+}
+
+// Little endian, as everything in zip and gzip
+fn read_le(data: &[u8], offset: usize, len: usize) -> Result<usize, String> {
+    let bytes = data.get(offset..offset + len).ok_or(TRUNCATED_ARCHIVE)?;
+    Ok(bytes.iter().rev().fold(0, |value, x| value << 8 | *x as usize))
+}
+
+impl<'a> BitReader<'a> {
+    // The bits are packed starting from the least significant bit of each byte
+    fn read_bits(&mut self, num: u32) -> Result<usize, String> {
+        let mut value = 0;
+        for i in 0..num {
+            let byte = *self.data.get(self.bit_position / 8).ok_or(TRUNCATED_ARCHIVE)?;
+            value |= ((byte as usize >> (self.bit_position % 8)) & 1) << i;
+            self.bit_position += 1;
+        }
+        Ok(value)
+    }
+}
+
+impl Huffman {
+    // The lengths of the codes of the symbols, with 0 for the unused ones
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0; 16];
+        lengths.iter().for_each(|x| counts[*x as usize] += 1);
+        counts[0] = 0;
+
+        let mut offsets = [0; 16];
+        for i in 1..15 {
+            offsets[i + 1] = offsets[i] + counts[i];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, length) in lengths.iter().enumerate().filter(|x| *x.1 != 0) {
+            symbols[offsets[*length as usize]] = symbol;
+            offsets[*length as usize] += 1;
+        }
+        Huffman {counts, symbols}
+    }
+
+    // The codes are read one bit at a time, the shorter ones having the smaller values
+    fn decode(&self, reader: &mut BitReader) -> Result<usize, String> {
+        let (mut code, mut first, mut index) = (0, 0, 0);
+        for count in &self.counts[1..] {
+            code |= reader.read_bits(1)?;
+            if code < first + count {
+                return Ok(self.symbols[index + code - first]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(INVALID_DEFLATE.to_owned())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_entries() {
+        let dir = Path::new("test_dir/archives");
+        let expected_main = fs::read(dir.join("main.rs")).unwrap();
+        let is_wanted = |name: &str| name.ends_with(".rs");
+
+        for archive in ["src.zip", "src.tar.gz"] {
+            let mut entries = Vec::new();
+            read_entries(&dir.join(archive), usize::MAX, is_wanted, |x| entries.push(x)).unwrap();
+            entries.sort_by(|a, b| a.name.cmp(&b.name));
+            assert_eq!(vec!["src/README", "src/main.rs", &format!("src/{}/lib.rs", "nested_".repeat(15))],
+                    entries.iter().map(|x| x.name.as_str()).collect::<Vec<_>>(), "{}", archive);
+            assert_eq!(EntryContents::Read(expected_main.clone()), entries[1].contents, "{}", archive);
+            assert_eq!(expected_main.len(), entries[1].size);
+            assert_eq!(EntryContents::Unread, entries[0].contents);
+            assert_eq!(EntryContents::Read(b"pub fn f() {}\n".to_vec()), entries[2].contents);
+        }
+        assert!(read_entries(&dir.join("main.rs"), usize::MAX, is_wanted, |_| ()).is_err());
+
+        // Nothing is inflated past the memory budget
+        let budget = expected_main.len() - 1;
+        let mut entries = Vec::new();
+        read_entries(&dir.join("src.zip"), budget, is_wanted, |x| entries.push(x)).unwrap();
+        assert_eq!(Some(&EntryContents::OverLimit), entries.iter().find(|x| x.name == "src/main.rs").map(|x| &x.contents));
+        // Past the header of 'src/main.rs', which comes after the ones of 'src/' and 'src/README'
+        let budget = 4 * TAR_BLOCK_SIZE + 100;
+        entries.clear();
+        assert_eq!(Err(OVER_LIMIT.to_owned()), read_entries(&dir.join("src.tar.gz"), budget, is_wanted, |x| entries.push(x)));
+        assert_eq!(vec![("src/README", &EntryContents::Unread), ("src/main.rs", &EntryContents::OverLimit)],
+                entries.iter().map(|x| (x.name.as_str(), &x.contents)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_read_entries_of_a_bomb() {
+        // The second entry is 8 MiB of spaces, a thousand times its compressed size
+        let mut entries = Vec::new();
+        assert_eq!(Err(OVER_LIMIT.to_owned()),
+                read_entries(Path::new("test_dir/archives/bomb.tar.gz"), usize::MAX, |_| true, |x| entries.push(x)));
+        assert_eq!(2, entries.len());
+        assert_eq!(EntryContents::Read(b"fn a() {}\n".to_vec()), entries[0].contents);
+        assert_eq!(("bomb/b.rs", 8 * 1024 * 1024, &EntryContents::OverLimit), (entries[1].name.as_str(), entries[1].size, &entries[1].contents));
+
+        assert_eq!(MIN_INFLATION_LIMIT, get_inflation_limit(10, usize::MAX));
+        assert_eq!(200 * 1024 * 1024, get_inflation_limit(2 * 1024 * 1024, usize::MAX));
+        assert_eq!(1000, get_inflation_limit(2 * 1024 * 1024, 1000));
+        assert_eq!(usize::MAX, get_inflation_limit(usize::MAX, usize::MAX));
+    }
+
+    #[test]
+    fn test_inflate() {
+        // A stored block, followed by a block with the fixed codes and a copy that overlaps with itself
+        let data = [0x00, 0x02, 0x00, 0xfd, 0xff, b'a', b'b', 0x4b, 0x04, 0x02, 0x00];
+        let mut output = Vec::new();
+        assert_eq!(Ok(11), inflate(&data, &mut output, 6));
+        assert_eq!(b"abaaaa".to_vec(), output);
+        assert!(inflate(&data[..6], &mut Vec::new(), 6).is_err());
+        assert!(inflate(&[0x07], &mut Vec::new(), 6).is_err());
+        // Stopped in the stored block, the literals and the copy, keeping what was inflated before
+        assert_eq!(Err(OVER_LIMIT.to_owned()), inflate(&data, &mut Vec::new(), 1));
+        let mut output = Vec::new();
+        assert_eq!(Err(OVER_LIMIT.to_owned()), inflate(&data, &mut output, 2));
+        assert_eq!(b"ab".to_vec(), output);
+        assert_eq!(Err(OVER_LIMIT.to_owned()), inflate(&data, &mut Vec::new(), 5));
+        // The maximum length is of the whole output
+        let mut output = b"xyz".to_vec();
+        assert_eq!(Err(OVER_LIMIT.to_owned()), inflate(&data, &mut output, 8));
+    }
+
+    #[test]
+    fn test_is_archive() {
+        assert!(is_archive(Path::new("release-1.0.tar.gz")));
+        assert!(is_archive(Path::new("a/b.TGZ")));
+        assert!(is_archive(Path::new("b.zip")));
+        assert!(!is_archive(Path::new("b.gz")));
+        assert!(!is_archive(Path::new("zip")));
+    }
+}
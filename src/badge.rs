@@ -0,0 +1,185 @@
+use crate::*;
+
+const COLOR : &str = "#007ec6";
+const LABEL_COLOR : &str = "#555";
+const HORIZONTAL_PADDING : usize = 10;
+
+
+// The value that is shown on the badge
+#[derive(Debug,PartialEq,Clone,Copy)]
+pub enum BadgeMetric {
+    Lines,
+    Code,
+    Extra,
+    Files
+}
+
+// Parsed from '--badge <path>, [metric[:language]]'
+#[derive(Debug,PartialEq,Clone)]
+pub struct Badge {
+    pub path: String,
+    pub metric: BadgeMetric,
+    // Lowercase, or None for all the languages
+    pub language: Option<String>
+}
+
+impl BadgeMetric {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "lines" => Some(BadgeMetric::Lines),
+            "code" => Some(BadgeMetric::Code),
+            "extra" => Some(BadgeMetric::Extra),
+            "files" => Some(BadgeMetric::Files),
+            _ => None
+        }
+    }
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            BadgeMetric::Lines => "lines",
+            BadgeMetric::Code => "lines of code",
+            BadgeMetric::Extra => "extra lines",
+            BadgeMetric::Files => "files"
+        }
+    }
+}
+
+// The path has to end with '.svg' and can't be a directory. The metric is 'lines' if it is not provided.
+pub fn parse_badge(s: &str) -> Option<Badge> {
+    let args = utils::parse_paths_to_vec(s);
+    if args.is_empty() || args.len() > 2 || !args[0].to_lowercase().ends_with(".svg") || Path::new(&args[0]).is_dir() {
+        return None;
+    }
+
+    let (metric, language) = match args.get(1) {
+        Some(x) => match x.split_once(':') {
+            Some((metric, language)) => (BadgeMetric::parse(metric)?, Some(utils::get_trimmed_if_not_empty(language)?.to_lowercase())),
+            None => (BadgeMetric::parse(x)?, None)
+        },
+        None => (BadgeMetric::Lines, None)
+    };
+    Some(Badge {path: args[0].to_owned(), metric, language})
+}
+
+pub fn write_badge(badge: &Badge, content_info_map: &HashMap<String,LanguageContentInfo>,
+        languages_metadata_map: &HashMap<String,LanguageMetadata>, final_stats: &FinalStats) -> Result<(),String>
+{
+    let (label, value) = match &badge.language {
+        Some(language) => {
+            let name = languages_metadata_map.keys().find(|x| x.to_lowercase() == *language)
+                    .ok_or_else(|| format!("no '{}' files were found", language))?;
+            let (content_info, metadata) = (&content_info_map[name], &languages_metadata_map[name]);
+            (format!("{} {}", name, badge.metric.label()), get_metric_value(badge.metric, metadata.files, content_info.lines, content_info.code_lines))
+        },
+        None => (badge.metric.label().to_owned(), get_metric_value(badge.metric, final_stats.files, final_stats.lines, final_stats.code_lines))
+    };
+
+    fs::write(&badge.path, make_svg(&label, &format_count(value))).map_err(|x| x.to_string())
+}
+
+pub(crate) fn get_metric_value(metric: BadgeMetric, files: usize, lines: usize, code_lines: usize) -> usize {
+    match metric {
+        BadgeMetric::Lines => lines,
+        BadgeMetric::Code => code_lines,
+        BadgeMetric::Extra => lines - code_lines,
+        BadgeMetric::Files => files
+    }
+}
+
+// e.g. 950 -> "950", 12,345 -> "12.3k", 2,500,000 -> "2.5M"
+pub fn format_count(value: usize) -> String {
+    if value >= 1_000_000 {
+        format!("{}M", round_1(value as f64 / 1_000_000f64))
+    } else if value >= 1000 {
+        format!("{}k", round_1(value as f64 / 1000f64))
+    } else {
+        value.to_string()
+    }
+}
+
+// In the flat style of shields.io. The widths of the texts are estimates for an 11px Verdana,
+// since the font is not available to measure them.
+pub fn make_svg(label: &str, value: &str) -> String {
+    let label_width = get_text_width(label) + 2 * HORIZONTAL_PADDING;
+    let value_width = get_text_width(value) + 2 * HORIZONTAL_PADDING;
+    let (label, value) = (escape_xml(label), escape_xml(value));
+    let width = label_width + value_width;
+
+    format!(concat!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"20\" role=\"img\" aria-label=\"{label}: {value}\">",
+            "<title>{label}: {value}</title>",
+            "<linearGradient id=\"s\" x2=\"0\" y2=\"100%\"><stop offset=\"0\" stop-color=\"#bbb\" stop-opacity=\".1\"/>",
+            "<stop offset=\"1\" stop-opacity=\".1\"/></linearGradient>",
+            "<clipPath id=\"r\"><rect width=\"{width}\" height=\"20\" rx=\"3\" fill=\"#fff\"/></clipPath>",
+            "<g clip-path=\"url(#r)\"><rect width=\"{label_width}\" height=\"20\" fill=\"{label_color}\"/>",
+            "<rect x=\"{label_width}\" width=\"{value_width}\" height=\"20\" fill=\"{color}\"/>",
+            "<rect width=\"{width}\" height=\"20\" fill=\"url(#s)\"/></g>",
+            "<g fill=\"#fff\" text-anchor=\"middle\" font-family=\"Verdana,Geneva,DejaVu Sans,sans-serif\" font-size=\"11\">",
+            "<text x=\"{label_x}\" y=\"15\" fill=\"#010101\" fill-opacity=\".3\">{label}</text><text x=\"{label_x}\" y=\"14\">{label}</text>",
+            "<text x=\"{value_x}\" y=\"15\" fill=\"#010101\" fill-opacity=\".3\">{value}</text><text x=\"{value_x}\" y=\"14\">{value}</text>",
+            "</g></svg>\n"),
+            width = width, label_width = label_width, value_width = value_width, label = label, value = value,
+            label_color = LABEL_COLOR, color = COLOR, label_x = label_width as f64 / 2f64, value_x = label_width as f64 + value_width as f64 / 2f64)
+}
+
+fn get_text_width(s: &str) -> usize {
+    s.chars().map(|c| match c {
+        'i' | 'j' | 'l' | '.' | ',' | ':' | ';' | '\'' | '!' | '|' => 3,
+        'f' | 'r' | 't' | ' ' | '-' | '(' | ')' => 5,
+        'm' | 'w' | 'M' | 'W' => 10,
+        x if x.is_uppercase() || x.is_ascii_digit() => 8,
+        _ => 7
+    }).sum()
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_badge() {
+        assert_eq!(Some(Badge {path: "loc.svg".to_owned(), metric: BadgeMetric::Lines, language: None}), parse_badge(" loc.svg "));
+        assert_eq!(Some(Badge {path: "docs/code.SVG".to_owned(), metric: BadgeMetric::Code, language: Some("rust".to_owned())}),
+                parse_badge("docs\\code.SVG, code:Rust"));
+        assert_eq!(Some(Badge {path: "files.svg".to_owned(), metric: BadgeMetric::Files, language: None}), parse_badge("files.svg, FILES"));
+        assert_eq!(None, parse_badge("loc.png"));
+        assert_eq!(None, parse_badge("loc.svg, words"));
+        assert_eq!(None, parse_badge("loc.svg, code:"));
+        assert_eq!(None, parse_badge("loc.svg, code, files"));
+        assert_eq!(None, parse_badge(""));
+    }
+
+    #[test]
+    fn test_write_badge() {
+        assert_eq!("950", format_count(950));
+        assert_eq!("12.3k", format_count(12_345));
+        assert_eq!("2.5M", format_count(2_500_000));
+
+        let svg = make_svg("lines of code", "12.3k");
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"151\" height=\"20\""));
+        assert!(svg.contains("<text x=\"48.5\" y=\"14\">lines of code</text>"));
+        assert!(svg.contains("<text x=\"124\" y=\"14\">12.3k</text>"));
+        assert!(make_svg("a<b", "1").contains(">a&lt;b</text>"));
+
+        let content_info_map = hashmap!["Rust".to_owned() => LanguageContentInfo::new(2000, 1500, HashMap::new())];
+        let languages_metadata_map = hashmap!["Rust".to_owned() => LanguageMetadata::new(10, 8000)];
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+        let path = std::env::temp_dir().join("mezura-test-badge.svg").to_str().unwrap().to_owned();
+
+        let badge = Badge {path: path.clone(), metric: BadgeMetric::Code, language: Some("rust".to_owned())};
+        write_badge(&badge, &content_info_map, &languages_metadata_map, &final_stats).unwrap();
+        assert!(fs::read_to_string(&path).unwrap().contains(">Rust lines of code</text>"));
+        let badge = Badge {path: path.clone(), metric: BadgeMetric::Extra, language: None};
+        write_badge(&badge, &content_info_map, &languages_metadata_map, &final_stats).unwrap();
+        assert!(fs::read_to_string(&path).unwrap().contains(">500</text>"));
+        let badge = Badge {path: path.clone(), metric: BadgeMetric::Lines, language: Some("java".to_owned())};
+        assert_eq!(Err("no 'java' files were found".to_owned()), write_badge(&badge, &content_info_map, &languages_metadata_map, &final_stats));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
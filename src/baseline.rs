@@ -0,0 +1,157 @@
+use crate::{*, export::{MergedResults, Results}};
+
+// The counter of 'budgets::get_counter' that 'check' compares, if '--metric' is not used
+pub const DEF_CHECK_METRIC : &str = "code";
+
+
+// The growth of the metric that 'check' allows since the baseline, e.g. '5%' of the value of the baseline or '2000'
+#[derive(Debug,PartialEq,Clone,Copy)]
+pub enum MaxGrowth {
+    Percentage(f64),
+    Absolute(usize)
+}
+
+// The metric of 'check' in the baseline and in the dirs. With '--update-baseline' the baseline is the value that was saved
+#[derive(Debug,PartialEq)]
+pub struct CheckResult {
+    pub metric: String,
+    pub baseline: usize,
+    pub value: usize,
+    pub max_growth: MaxGrowth,
+    pub updated: bool
+}
+
+impl MaxGrowth {
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim().replace('_', "");
+        match s.strip_suffix('%') {
+            Some(x) => x.trim().parse::<f64>().ok().filter(|x| x.is_finite() && *x >= 0.0).map(MaxGrowth::Percentage),
+            None => s.parse::<usize>().ok().map(MaxGrowth::Absolute)
+        }
+    }
+
+    // A decrease is always allowed. A percentage of a baseline of 0 allows no growth
+    fn allows(&self, baseline: usize, value: usize) -> bool {
+        let growth = value.saturating_sub(baseline);
+        match self {
+            MaxGrowth::Percentage(x) => growth as f64 <= baseline as f64 * x / 100.0,
+            MaxGrowth::Absolute(x) => growth <= *x
+        }
+    }
+
+    fn as_string(&self) -> String {
+        match self {
+            MaxGrowth::Percentage(x) => format!("{}%", x),
+            MaxGrowth::Absolute(x) => with_seperators(*x)
+        }
+    }
+}
+
+impl CheckResult {
+    pub fn passed(&self) -> bool {
+        self.updated || self.max_growth.allows(self.baseline, self.value)
+    }
+
+    // The one of a condition of '--fail-if' that held, as listed in the README, if the metric grew more than allowed
+    pub fn exit_code(&self) -> i32 {
+        if self.passed() {0} else {4}
+    }
+}
+
+// Analyzes the dirs of the configuration without printing or saving anything, and compares the metric of the results with
+// the one of the baseline, a json file of '--output json'. With 'update' the results are saved as the baseline instead.
+pub fn run_check(mut config: Configuration, mut language_map: HashMap<String, Language>, baseline_path: &str, metric: &str,
+        max_growth: MaxGrowth, update: bool) -> Result<CheckResult, ParseFilesError>
+{
+    config.verbosity = config_manager::Verbosity::Quiet;
+    config.complexity |= split_metric(metric).1 == "complexity";
+    prepare_language_map(&config, &mut language_map)?;
+    let (config, language_map_ref) = (Arc::new(config), Arc::new(language_map));
+
+    let mut analysis = analyze_files(config.clone(), language_map_ref, false, Arc::new(NoObserver), Arc::new(AtomicBool::new(false)),
+            Arc::new(Vec::new()))?;
+    let (final_stats, _) = summarize_analysis(&mut analysis, &config)?;
+    let (language, counter) = split_metric(metric);
+    let value = budgets::get_counter(language.as_deref(), &counter, &analysis.content_info_map, &analysis.languages_metadata_map, &final_stats);
+
+    if update {
+        let json = Results::new(&config.dirs, &analysis.content_info_map, &analysis.languages_metadata_map, &final_stats).to_json();
+        fs::write(baseline_path, json + "\n").map_err(|x| ParseFilesError::UnavailableOutFile(baseline_path.to_owned(), x))?;
+        return Ok(CheckResult {metric: metric.to_owned(), baseline: value, value, max_growth, updated: true});
+    }
+
+    let baseline = MergedResults::read(&[baseline_path.to_owned()]).map_err(ParseFilesError::UnavailableBaseline)?
+            .get_counter(language.as_deref(), &counter);
+    Ok(CheckResult {metric: metric.to_owned(), baseline, value, max_growth, updated: false})
+}
+
+// '[<language>.]<counter>', lowercase
+fn split_metric(metric: &str) -> (Option<String>, String) {
+    let metric = metric.trim().to_lowercase();
+    match metric.rsplit_once('.') {
+        Some((language, counter)) => (Some(language.to_owned()), counter.to_owned()),
+        None => (None, metric)
+    }
+}
+
+// e.g.
+// Check of code against the baseline: 12,000 -> 12,480 (+480, +4.0%), at most +5% allowed
+// The check passed.
+pub fn format_check(result: &CheckResult) -> String {
+    if result.updated {
+        return format!("The baseline was updated, with {} {}.", result.metric, with_seperators(result.value));
+    }
+
+    let change = if result.value >= result.baseline {format!("+{}", with_seperators(result.value - result.baseline))} else {
+        format!("-{}", with_seperators(result.baseline - result.value))
+    };
+    let percentage = if result.baseline == 0 {String::new()} else {
+        format!(", {:+.1}%", (result.value as f64 - result.baseline as f64) / result.baseline as f64 * 100.0)
+    };
+    let text = format!("Check of {} against the baseline: {} -> {} ({}{}), at most +{} allowed\n", result.metric, with_seperators(result.baseline),
+            with_seperators(result.value), change, percentage, result.max_growth.as_string());
+    if result.passed() {
+        format!("{}The check passed.", text)
+    } else {
+        format!("{}The check failed, {} grew more than allowed.", text, result.metric)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_growth() {
+        assert_eq!(Some(MaxGrowth::Percentage(5.0)), MaxGrowth::parse("5%"));
+        assert_eq!(Some(MaxGrowth::Percentage(0.5)), MaxGrowth::parse(" 0.5 % "));
+        assert_eq!(Some(MaxGrowth::Absolute(2000)), MaxGrowth::parse("2_000"));
+        assert_eq!(None, MaxGrowth::parse("-5%"));
+        assert_eq!(None, MaxGrowth::parse("a lot"));
+
+        assert!(MaxGrowth::Percentage(5.0).allows(1000, 1050));
+        assert!(!MaxGrowth::Percentage(5.0).allows(1000, 1051));
+        assert!(MaxGrowth::Percentage(5.0).allows(1000, 10));
+        assert!(!MaxGrowth::Percentage(5.0).allows(0, 1));
+        assert!(MaxGrowth::Absolute(0).allows(1000, 1000));
+        assert!(!MaxGrowth::Absolute(0).allows(1000, 1001));
+    }
+
+    #[test]
+    fn test_format_check() {
+        let result = CheckResult {metric: "code".to_owned(), baseline: 12_000, value: 12_480, max_growth: MaxGrowth::Percentage(5.0), updated: false};
+        assert_eq!("Check of code against the baseline: 12,000 -> 12,480 (+480, +4.0%), at most +5% allowed\nThe check passed.",
+                format_check(&result));
+        assert_eq!(0, result.exit_code());
+
+        let result = CheckResult {metric: "rust.unsafe".to_owned(), baseline: 0, value: 3, max_growth: MaxGrowth::Absolute(2), updated: false};
+        assert_eq!("Check of rust.unsafe against the baseline: 0 -> 3 (+3), at most +2 allowed\nThe check failed, rust.unsafe grew more than allowed.",
+                format_check(&result));
+        assert_eq!(4, result.exit_code());
+
+        let result = CheckResult {metric: "code".to_owned(), baseline: 900, value: 900, max_growth: MaxGrowth::Absolute(0), updated: true};
+        assert_eq!("The baseline was updated, with code 900.", format_check(&result));
+        assert_eq!((Some("rust".to_owned()), "unsafe".to_owned()), split_metric("Rust.unsafe"));
+    }
+}
@@ -0,0 +1,166 @@
+use std::{io, process::Command};
+
+use crate::*;
+
+pub const DEF_BENCH_ITERATIONS : usize = 5;
+pub const MIN_BENCH_ITERATIONS : usize = 1;
+pub const MAX_BENCH_ITERATIONS : usize = 1_000;
+// In the history dir, so that the benches of a project are compared with each other
+const LAST_BENCH_FILE_NAME : &str = "last-bench";
+
+
+// The runs of 'bench'. The metrics have the duration of every run
+#[derive(Debug, PartialEq)]
+pub struct BenchResult {
+    pub files: usize,
+    pub lines: usize,
+    // The caches of the OS were dropped before every run
+    pub cold: bool,
+    pub metrics: Metrics
+}
+
+// What is kept of the last bench, to compare the next one with, e.g.
+// avg_secs 0.152
+// p90_secs 0.18
+// files_per_sec 1000
+// lines_per_sec 200000
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct BenchSummary {
+    pub avg_secs: f64,
+    pub p90_secs: f64,
+    pub files_per_sec: usize,
+    pub lines_per_sec: usize
+}
+
+impl BenchSummary {
+    pub fn new(metrics: &Metrics) -> Self {
+        BenchSummary {avg_secs: metrics.average().as_secs_f64(), p90_secs: metrics.percentile(90).as_secs_f64(),
+                files_per_sec: metrics.files_per_sec, lines_per_sec: metrics.lines_per_sec}
+    }
+
+    fn to_text(self) -> String {
+        format!("avg_secs {}\np90_secs {}\nfiles_per_sec {}\nlines_per_sec {}\n", self.avg_secs, self.p90_secs, self.files_per_sec,
+                self.lines_per_sec)
+    }
+
+    fn parse(contents: &str) -> Option<Self> {
+        let values = contents.lines().filter_map(|x| x.split_once(' ')).collect::<HashMap<_, _>>();
+        Some(BenchSummary {avg_secs: values.get("avg_secs")?.trim().parse().ok()?, p90_secs: values.get("p90_secs")?.trim().parse().ok()?,
+                files_per_sec: values.get("files_per_sec")?.trim().parse().ok()?, lines_per_sec: values.get("lines_per_sec")?.trim().parse().ok()?})
+    }
+}
+
+// Analyzes the dirs of the configuration as many times as the iterations, without printing or saving anything.
+// Unless the caches are dropped, the runs after the first one read the files from the memory.
+pub fn run_bench(mut config: Configuration, mut language_map: HashMap<String, Language>, iterations: usize, cold: bool)
+        -> Result<BenchResult, ParseFilesError>
+{
+    config.verbosity = config_manager::Verbosity::Quiet;
+    prepare_language_map(&config, &mut language_map)?;
+    let (config, language_map_ref) = (Arc::new(config), Arc::new(language_map));
+
+    let (mut files, mut lines, mut bytes) = (0, 0, 0);
+    let mut durations = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        if cold {
+            drop_caches().map_err(ParseFilesError::UnavailableCacheDrop)?;
+        }
+        let started_instant = Instant::now();
+        let mut analysis = analyze_files(config.clone(), language_map_ref.clone(), false, Arc::new(NoObserver), Arc::new(AtomicBool::new(false)),
+                Arc::new(Vec::new()))?;
+        let (final_stats, _) = summarize_analysis(&mut analysis, &config)?;
+        durations.push(started_instant.elapsed());
+        files = final_stats.files;
+        lines = final_stats.lines;
+        bytes = final_stats.bytes_size;
+    }
+    Ok(BenchResult {files, lines, cold, metrics: Metrics::from_durations(files, lines, bytes, durations)})
+}
+
+// Only on linux, and it needs root
+fn drop_caches() -> io::Result<()> {
+    Command::new("sync").status()?;
+    fs::write("/proc/sys/vm/drop_caches", "3")
+}
+
+pub fn read_last_bench() -> Option<BenchSummary> {
+    BenchSummary::parse(&fs::read_to_string(Path::new(history::HISTORY_DIR).join(LAST_BENCH_FILE_NAME)).ok()?)
+}
+
+pub fn save_last_bench(summary: BenchSummary) -> io::Result<()> {
+    fs::create_dir_all(history::HISTORY_DIR)?;
+    fs::write(Path::new(history::HISTORY_DIR).join(LAST_BENCH_FILE_NAME), summary.to_text())
+}
+
+// e.g.
+// Bench of 5 runs with a warm cache: 120 files, 34,000 lines
+//
+// Time  : min 0.120s | avg 0.152s | p50 0.150s | p90 0.180s | max 0.190s
+// Speed : 789 files/s | 223,684 lines/s
+//
+// Against the previous bench:
+//   avg      0.160s -> 0.152s (-5.0%)
+//   p90      0.170s -> 0.180s (+5.9%)
+//   files/s  750 -> 789 (+5.2%)
+//   lines/s  212,500 -> 223,684 (+5.3%)
+pub fn format_bench(result: &BenchResult, previous: Option<BenchSummary>) -> String {
+    let metrics = &result.metrics;
+    let mut text = format!("Bench of {} runs with a {} cache: {} files, {} lines\n\n", metrics.durations.len(), if result.cold {"cold"} else {"warm"},
+            with_seperators(result.files), with_seperators(result.lines));
+    text += &format!("Time  : min {:.3}s | avg {:.3}s | p50 {:.3}s | p90 {:.3}s | max {:.3}s\n", metrics.min().as_secs_f64(),
+            metrics.average().as_secs_f64(), metrics.percentile(50).as_secs_f64(), metrics.percentile(90).as_secs_f64(), metrics.max().as_secs_f64());
+    text += &format!("Speed : {} files/s | {} lines/s\n", with_seperators(metrics.files_per_sec), with_seperators(metrics.lines_per_sec));
+
+    if let Some(previous) = previous {
+        let current = BenchSummary::new(metrics);
+        text += "\nAgainst the previous bench:\n";
+        text += &format!("  avg      {:.3}s -> {:.3}s ({})\n", previous.avg_secs, current.avg_secs, format_change(previous.avg_secs, current.avg_secs));
+        text += &format!("  p90      {:.3}s -> {:.3}s ({})\n", previous.p90_secs, current.p90_secs, format_change(previous.p90_secs, current.p90_secs));
+        text += &format!("  files/s  {} -> {} ({})\n", with_seperators(previous.files_per_sec), with_seperators(current.files_per_sec),
+                format_change(previous.files_per_sec as f64, current.files_per_sec as f64));
+        text += &format!("  lines/s  {} -> {} ({})\n", with_seperators(previous.lines_per_sec), with_seperators(current.lines_per_sec),
+                format_change(previous.lines_per_sec as f64, current.lines_per_sec as f64));
+    }
+    text
+}
+
+fn format_change(previous: f64, current: f64) -> String {
+    if previous == 0f64 {
+        return "-".to_owned();
+    }
+    format!("{:+.1}%", (current - previous) / previous * 100f64)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bench() {
+        let durations = [120, 150, 190, 140, 160].iter().map(|x| Duration::from_millis(*x)).collect();
+        let result = BenchResult {files: 120, lines: 34_000, cold: false, metrics: Metrics::from_durations(120, 34_000, 1_000_000, durations)};
+        assert_eq!((result.metrics.files_per_sec, result.metrics.lines_per_sec), (789, 223_684));
+
+        let previous = BenchSummary {avg_secs: 0.16, p90_secs: 0.17, files_per_sec: 750, lines_per_sec: 212_500};
+        assert_eq!(format_bench(&result, Some(previous)), "Bench of 5 runs with a warm cache: 120 files, 34,000 lines
+
+Time  : min 0.120s | avg 0.152s | p50 0.150s | p90 0.190s | max 0.190s
+Speed : 789 files/s | 223,684 lines/s
+
+Against the previous bench:
+  avg      0.160s -> 0.152s (-5.0%)
+  p90      0.170s -> 0.190s (+11.8%)
+  files/s  750 -> 789 (+5.2%)
+  lines/s  212,500 -> 223,684 (+5.3%)
+");
+        assert!(!format_bench(&result, None).contains("previous"));
+    }
+
+    #[test]
+    fn test_bench_summary() {
+        let summary = BenchSummary {avg_secs: 0.152, p90_secs: 0.19, files_per_sec: 789, lines_per_sec: 223_684};
+        assert_eq!(Some(summary), BenchSummary::parse(&summary.to_text()));
+        assert_eq!(None, BenchSummary::parse("avg_secs 0.1\n"));
+    }
+}
@@ -0,0 +1,154 @@
+use crate::*;
+
+const OPERATORS : [(&str, Comparison); 6] = [(">=", Comparison::GreaterOrEqual), ("<=", Comparison::LessOrEqual), ("==", Comparison::Equal),
+        ("!=", Comparison::NotEqual), (">", Comparison::Greater), ("<", Comparison::Less)];
+
+
+#[derive(Debug,PartialEq,Clone,Copy)]
+pub enum Comparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    Equal,
+    NotEqual
+}
+
+// A condition of '--fail-if' in the form '[<language>.]<metric> <operator> <number>', e.g. 'total_lines > 100000'
+// or 'rust.unsafe > 0'. The metric is one of the counters of 'get_counter', or else the name of a keyword, whose occurrences are summed.
+#[derive(Debug,PartialEq,Clone)]
+pub struct Budget {
+    // As it was provided, to be shown in the violations and saved in configuration files
+    pub expression: String,
+    // Lowercase
+    pub language: Option<String>,
+    pub metric: String,
+    pub comparison: Comparison,
+    pub limit: usize
+}
+
+#[derive(Debug,PartialEq)]
+pub struct BudgetViolation {
+    pub expression: String,
+    pub value: usize
+}
+
+impl Comparison {
+    fn holds(&self, value: usize, limit: usize) -> bool {
+        match self {
+            Comparison::Greater => value > limit,
+            Comparison::GreaterOrEqual => value >= limit,
+            Comparison::Less => value < limit,
+            Comparison::LessOrEqual => value <= limit,
+            Comparison::Equal => value == limit,
+            Comparison::NotEqual => value != limit
+        }
+    }
+}
+
+// One or more conditions separated by commas. None if any of them can't be parsed.
+pub fn parse_budgets(s: &str) -> Option<Vec<Budget>> {
+    let budgets = s.split(',').filter(|x| !x.trim().is_empty()).map(parse_budget).collect::<Option<Vec<_>>>()?;
+    if budgets.is_empty() {None} else {Some(budgets)}
+}
+
+fn parse_budget(s: &str) -> Option<Budget> {
+    let expression = s.split_whitespace().collect::<Vec<_>>().join(" ");
+    let (index, operator, comparison) = OPERATORS.iter().find_map(|(operator, comparison)| {
+        expression.find(operator).map(|i| (i, *operator, *comparison))
+    })?;
+
+    let subject = utils::get_trimmed_if_not_empty(&expression[..index])?.to_lowercase();
+    let limit = expression[index + operator.len()..].trim().replace('_', "").parse::<usize>().ok()?;
+    let (language, metric) = match subject.rsplit_once('.') {
+        Some((language, metric)) => (Some(utils::get_trimmed_if_not_empty(language)?), utils::get_trimmed_if_not_empty(metric)?),
+        None => (None, subject.clone())
+    };
+    Some(Budget {expression, language, metric, comparison, limit})
+}
+
+// The conditions that hold for the final results, which make the run fail
+pub fn find_violations(budgets: &[Budget], content_info_map: &HashMap<String,LanguageContentInfo>,
+        languages_metadata_map: &HashMap<String,LanguageMetadata>, final_stats: &FinalStats) -> Vec<BudgetViolation>
+{
+    budgets.iter().filter_map(|budget| {
+        let value = get_counter(budget.language.as_deref(), &budget.metric, content_info_map, languages_metadata_map, final_stats);
+        if budget.comparison.holds(value, budget.limit) {
+            Some(BudgetViolation {expression: budget.expression.clone(), value})
+        } else {
+            None
+        }
+    }).collect()
+}
+
+// The counter of the language, or of all of them if None, by its lowercase name: files, lines, code, extra, bytes, doc_lines,
+// logical_lines, complexity, blank_files, test_lines and test_code_lines (optionally prefixed with 'total_'), or else the
+// name of a keyword, whose occurrences are summed. Also used for the expressions of '--metrics'
+pub fn get_counter(language: Option<&str>, metric: &str, content_info_map: &HashMap<String,LanguageContentInfo>,
+        languages_metadata_map: &HashMap<String,LanguageMetadata>, final_stats: &FinalStats) -> usize
+{
+    // A language without files has 0 of everything
+    let names = content_info_map.keys().filter(|x| language.iter().all(|language| x.to_lowercase() == *language))
+            .collect::<Vec<_>>();
+    let sum = |f: &dyn Fn(&LanguageContentInfo, &LanguageMetadata) -> usize| -> usize {
+        names.iter().filter_map(|x| Some(f(content_info_map.get(*x)?, languages_metadata_map.get(*x)?))).sum()
+    };
+
+    let metric = metric.strip_prefix("total_").unwrap_or(metric);
+    match metric {
+        "files" if language.is_none() => final_stats.files,
+        "files" => sum(&|_, metadata| metadata.files),
+        "lines" => sum(&|content_info, _| content_info.lines),
+        "code" | "code_lines" => sum(&|content_info, _| content_info.code_lines),
+        "extra" | "extra_lines" => sum(&|content_info, _| content_info.lines - content_info.code_lines),
+        "bytes" | "size" => sum(&|_, metadata| metadata.bytes),
+        "doc_lines" => sum(&|content_info, _| content_info.doc_lines),
+        "logical_lines" => sum(&|content_info, _| content_info.logical_lines),
+        "complexity" => sum(&|content_info, _| content_info.complexity),
+        "blank_files" => sum(&|content_info, _| content_info.blank_files()),
+        "test_lines" => sum(&|content_info, _| content_info.test_stats.lines),
+        "test_code_lines" => sum(&|content_info, _| content_info.test_stats.code_lines),
+        keyword => sum(&|content_info, _| {
+            content_info.keyword_occurences.iter().filter(|(name, _)| name.to_lowercase() == keyword).map(|(_, x)| *x).sum()
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_budgets() {
+        assert_eq!(Some(vec![Budget {expression: "total_lines > 100000".to_owned(), language: None, metric: "total_lines".to_owned(),
+                comparison: Comparison::Greater, limit: 100_000},
+                Budget {expression: "Rust.unsafe >= 1_000".to_owned(), language: Some("rust".to_owned()), metric: "unsafe".to_owned(),
+                comparison: Comparison::GreaterOrEqual, limit: 1000}]),
+                parse_budgets(" total_lines >   100000, Rust.unsafe >= 1_000"));
+        assert_eq!(Comparison::NotEqual, parse_budgets("files!=3").unwrap()[0].comparison);
+        assert_eq!(None, parse_budgets("lines > a lot"));
+        assert_eq!(None, parse_budgets("lines > 10, > 5"));
+        assert_eq!(None, parse_budgets("lines 10"));
+        assert_eq!(None, parse_budgets(".lines < 10"));
+        assert_eq!(None, parse_budgets(""));
+    }
+
+    #[test]
+    fn test_find_violations() {
+        let content_info_map = hashmap!["Rust".to_owned() => LanguageContentInfo::new(1000, 800, hashmap!["unsafe".to_owned() => 3]),
+                "C".to_owned() => LanguageContentInfo::new(500, 300, hashmap!["goto".to_owned() => 1])];
+        let languages_metadata_map = hashmap!["Rust".to_owned() => LanguageMetadata::new(10, 40_000),
+                "C".to_owned() => LanguageMetadata::new(4, 10_000)];
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+
+        let budgets = parse_budgets("total_lines > 1000, code <= 1100, rust.unsafe > 0, c.unsafe > 0, goto == 1, java.files > 0, \
+                c.extra >= 200, bytes < 50000").unwrap();
+        assert_eq!(vec![BudgetViolation {expression: "total_lines > 1000".to_owned(), value: 1500},
+                BudgetViolation {expression: "code <= 1100".to_owned(), value: 1100},
+                BudgetViolation {expression: "rust.unsafe > 0".to_owned(), value: 3},
+                BudgetViolation {expression: "goto == 1".to_owned(), value: 1},
+                BudgetViolation {expression: "c.extra >= 200".to_owned(), value: 200}],
+                find_violations(&budgets, &content_info_map, &languages_metadata_map, &final_stats));
+    }
+}
@@ -0,0 +1,121 @@
+use std::process::Command;
+
+use crate::*;
+
+
+// Directories that the javascript tools commonly generate, next to a package.json
+const JS_OUTPUT_DIRS : [&str; 7] = ["node_modules", "dist", "build", "out", ".next", ".nuxt", ".svelte-kit"];
+
+
+// Returns the directories inside 'dir' that hold the outputs of the build tools whose files are found in it.
+// Only the directories that exist are returned.
+pub fn find_build_output_dirs(dir: &Path) -> Vec<PathBuf> {
+    let mut output_dirs = Vec::new();
+
+    if dir.join("Cargo.toml").is_file() {
+        output_dirs.push(query_cargo_target_dir(dir).unwrap_or_else(|| dir.join("target")));
+    }
+    if dir.join("package.json").is_file() {
+        output_dirs.extend(JS_OUTPUT_DIRS.iter().map(|x| dir.join(x)));
+        if let Some(x) = read_typescript_out_dir(dir) {
+            output_dirs.push(dir.join(x));
+        }
+    }
+    if ["build.gradle", "build.gradle.kts", "settings.gradle", "settings.gradle.kts"].iter().any(|x| dir.join(x).is_file()) {
+        output_dirs.push(dir.join("build"));
+        output_dirs.push(dir.join(".gradle"));
+    }
+    if dir.join("pom.xml").is_file() {
+        output_dirs.push(dir.join("target"));
+    }
+
+    output_dirs.retain(|x| x.is_dir());
+    output_dirs.dedup();
+    output_dirs
+}
+
+// The paths returned by cargo are canonicalized, so they are also compared after resolving symlinks
+pub fn is_build_output_dir(path: &Path, build_output_dirs: &[PathBuf]) -> bool {
+    build_output_dirs.iter().any(|x| x == path || same_file(x, path))
+}
+
+// The target dir can be changed with the CARGO_TARGET_DIR variable or in '.cargo/config.toml', so cargo itself is asked.
+// Returns None if cargo is not installed or fails.
+fn query_cargo_target_dir(dir: &Path) -> Option<PathBuf> {
+    let output = Command::new("cargo").args(["metadata", "--format-version", "1", "--no-deps", "--offline"])
+            .current_dir(dir).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    extract_json_string_value(&String::from_utf8_lossy(&output.stdout), "target_directory").map(PathBuf::from)
+}
+
+fn read_typescript_out_dir(dir: &Path) -> Option<String> {
+    let contents = fs::read_to_string(dir.join("tsconfig.json")).ok()?;
+    extract_json_string_value(&contents, "outDir")
+        .map(|x| x.trim_start_matches("./").trim_end_matches('/').to_owned())
+        .filter(|x| !x.is_empty() && !x.starts_with(".."))
+}
+
+// Finds the first '"key": "value"' in the json and returns the unescaped value
+fn extract_json_string_value(json: &str, key: &str) -> Option<String> {
+    let key = format!("\"{}\"", key);
+    let after_key = &json[json.find(&key)? + key.len()..];
+    let after_colon = after_key.trim_start().strip_prefix(':')?.trim_start().strip_prefix('"')?;
+
+    let mut value = String::new();
+    let mut chars = after_colon.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Some(value),
+            '\\' => match chars.next()? {
+                'n' => value.push('\n'),
+                't' => value.push('\t'),
+                x => value.push(x)
+            },
+            x => value.push(x)
+        }
+    }
+    None
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_json_string_value() {
+        assert_eq!(Some("/a/target".to_owned()), extract_json_string_value(r#"{"packages":[],"target_directory":"/a/target"}"#, "target_directory"));
+        assert_eq!(Some("C:\\a\\target".to_owned()), extract_json_string_value(r#"{"target_directory" : "C:\\a\\target"}"#, "target_directory"));
+        assert_eq!(Some("lib".to_owned()), extract_json_string_value("{\n  \"compilerOptions\": {\n    \"outDir\": \"lib\"\n  }\n}", "outDir"));
+        assert_eq!(None, extract_json_string_value(r#"{"outDir": 5}"#, "outDir"));
+        assert_eq!(None, extract_json_string_value(r#"{"outDir": "unterminated}"#, "outDir"));
+        assert_eq!(None, extract_json_string_value(r#"{"other": "a"}"#, "outDir"));
+    }
+
+    #[test]
+    fn test_find_build_output_dirs() {
+        let dir = std::env::temp_dir().join("mezura-test-build-outputs");
+        fs::create_dir_all(dir.join("node_modules")).unwrap();
+        fs::create_dir_all(dir.join("lib")).unwrap();
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("package.json"), "{}").unwrap();
+        fs::write(dir.join("tsconfig.json"), r#"{"compilerOptions": {"outDir": "./lib/"}}"#).unwrap();
+
+        let output_dirs = find_build_output_dirs(&dir);
+        assert_eq!(vec![dir.join("node_modules"), dir.join("lib")], output_dirs);
+        assert!(is_build_output_dir(&dir.join("lib"), &output_dirs));
+        assert!(!is_build_output_dir(&dir.join("src"), &output_dirs));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
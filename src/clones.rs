@@ -0,0 +1,100 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+
+use crate::*;
+
+// The windows that are found in more files than this are boilerplate (e.g. the same imports everywhere), and comparing all
+// of their pairs would take quadratic time
+const MAX_WINDOW_OCCURRENCES : usize = 100;
+
+
+// A block of code lines of a file that is repeated in another file, found with '--clones'
+#[derive(Debug,PartialEq,Clone)]
+pub struct CodeClone {
+    // Of each copy. The empty lines and the comment lines inside the block are not counted
+    pub code_lines: usize,
+    pub first: CloneLocation,
+    pub second: CloneLocation
+}
+
+#[derive(Debug,PartialEq,Clone)]
+pub struct CloneLocation {
+    pub path: PathBuf,
+    // Starting from 1, both of them included
+    pub start_line: usize,
+    pub end_line: usize
+}
+
+// Without the whitespace, so that the lines that were only reformatted are the same
+pub fn hash_code_line(cleansed: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    cleansed.split_whitespace().for_each(|x| hasher.write(x.as_bytes()));
+    hasher.finish()
+}
+
+// Every window of 'min_lines' code lines is fingerprinted, and the windows that two files have in common are extended for as
+// long as the next lines match too, so that a block is reported once instead of once per window.
+// The longest blocks come first.
+pub fn find_clones(files: &[FileCodeLines], min_lines: usize) -> Vec<CodeClone> {
+    let mut windows_map = HashMap::<u64,Vec<(usize,usize)>>::new();
+    for (file_index, file) in files.iter().enumerate().filter(|(_, x)| min_lines > 0 && x.line_hashes.len() >= min_lines) {
+        for start in 0..=file.line_hashes.len() - min_lines {
+            let mut hasher = DefaultHasher::new();
+            file.line_hashes[start..start + min_lines].iter().for_each(|(hash, _)| hasher.write_u64(*hash));
+            windows_map.entry(hasher.finish()).or_default().push((file_index, start));
+        }
+    }
+
+    let mut clones = Vec::new();
+    for occurrences in windows_map.values().filter(|x| x.len() > 1 && x.len() <= MAX_WINDOW_OCCURRENCES) {
+        for (i, &(a_index, a_start)) in occurrences.iter().enumerate() {
+            for &(b_index, b_start) in occurrences[i + 1..].iter().filter(|(b_index, _)| *b_index != a_index) {
+                let (a, b) = (&files[a_index].line_hashes, &files[b_index].line_hashes);
+                let is_same_line = |a_line: usize, b_line: usize| a[a_line].0 == b[b_line].0;
+                // Not the start of the block, which is reported from its first window
+                if a_start > 0 && b_start > 0 && is_same_line(a_start - 1, b_start - 1) {
+                    continue;
+                }
+                // Different windows with the same hash
+                if (0..min_lines).any(|x| !is_same_line(a_start + x, b_start + x)) {
+                    continue;
+                }
+                let mut code_lines = min_lines;
+                while a_start + code_lines < a.len() && b_start + code_lines < b.len() && is_same_line(a_start + code_lines, b_start + code_lines) {
+                    code_lines += 1;
+                }
+                let location = |file: &FileCodeLines, start: usize| CloneLocation {path: file.path.clone(), start_line: file.line_hashes[start].1,
+                        end_line: file.line_hashes[start + code_lines - 1].1};
+                let (first, second) = (location(&files[a_index], a_start), location(&files[b_index], b_start));
+                clones.push(if first.path <= second.path {CodeClone {code_lines, first, second}} else {CodeClone {code_lines, first: second, second: first}});
+            }
+        }
+    }
+    clones.sort_by(|a, b| b.code_lines.cmp(&a.code_lines).then_with(|| (&a.first.path, a.first.start_line, &a.second.path, a.second.start_line)
+            .cmp(&(&b.first.path, b.first.start_line, &b.second.path, b.second.start_line))));
+    clones
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_clones() {
+        let file = |path: &str, lines: &[&str]| FileCodeLines {path: PathBuf::from(path),
+                line_hashes: lines.iter().enumerate().map(|(i, x)| (hash_code_line(x), i * 2 + 1)).collect()};
+        let block = ["let a = 1;", "let b = 2;", "let c = a + b;", "println!(\"{}\", c);"];
+        let a = file("/p/a.rs", &[&["fn a() {"][..], &block, &["}"]].concat());
+        let b = file("/p/b.rs", &[&["fn b() {", "let x = 0;"][..], &block[..3], &["println!( \"{}\",  c );", "}"]].concat());
+        let c = file("/p/c.rs", &block[..2]);
+
+        assert_eq!(vec![CodeClone {code_lines: 5, first: CloneLocation {path: PathBuf::from("/p/a.rs"), start_line: 3, end_line: 11},
+                second: CloneLocation {path: PathBuf::from("/p/b.rs"), start_line: 5, end_line: 13}}],
+                find_clones(&[b.clone(), c.clone(), a.clone()], 3));
+        assert_eq!(3, find_clones(&[a.clone(), b.clone(), c.clone()], 2).len());
+        assert!(find_clones(&[a.clone(), b.clone()], 6).is_empty());
+        // The same file isn't compared with itself
+        assert!(find_clones(&[file("/p/d.rs", &[&block[..], &block].concat())], 3).is_empty());
+    }
+}
@@ -1,8 +1,8 @@
-use std::{path::Path};
+use std::{collections::HashSet, io::IsTerminal, path::Path};
 
 use colored::{ColoredString, Colorize};
 
-use crate::{Formatted, io_handler, message_printer, utils};
+use crate::{Formatted, KeywordScope, badge::{self, Badge}, budgets::{self, Budget}, derived_metrics::{self, DerivedMetric}, heuristics, io_handler, labels, message_printer, notifier, remote, treemap, utils, warnings::{self, WarningKind}};
 
 // Application version, to be displayed at startup and with --help command
 pub const VERSION_ID : &str = "v1.0.0"; 
@@ -11,6 +11,7 @@ pub const VERSION_ID : &str = "v1.0.0";
 pub const DIRS               :&str   = "dirs";
 pub const EXCLUDE            :&str   = "exclude";
 pub const LANGUAGES          :&str   = "languages";
+pub const EXT                :&str   = "ext";
 pub const THREADS            :&str   = "threads";
 pub const BRACES_AS_CODE     :&str   = "braces-as-code";
 pub const SEARCH_IN_DOTTED   :&str   = "search-in-dotted";
@@ -21,10 +22,130 @@ pub const LOG                :&str   = "log";
 pub const COMPRARE_LEVEL     :&str   = "compare";
 pub const SAVE               :&str   = "save";
 pub const LOAD               :&str   = "load";
+pub const PROFILE            :&str   = "profile";
+pub const DUMP_CONFIG        :&str   = "dump-config";
 pub const HELP               :&str   = "help";
+pub const COMPLETIONS        :&str   = "completions";
 pub const CHANGELOG          :&str   = "changelog";
 pub const SHOW_LANGUAGES     :&str   = "show-languages";
 pub const SHOW_CONFIGS       :&str   = "show-configs";
+pub const VERIFY_REPORT      :&str   = "verify-report";
+pub const SERVE              :&str   = "serve";
+pub const LISTEN             :&str   = "listen";
+pub const SNAPSHOT           :&str   = "snapshot";
+pub const TREND              :&str   = "trend";
+pub const MERGE              :&str   = "merge";
+pub const BENCH              :&str   = "bench";
+pub const ITERATIONS         :&str   = "iterations";
+pub const COLD               :&str   = "cold";
+pub const CHECK              :&str   = "check";
+pub const BASELINE           :&str   = "baseline";
+pub const MAX_GROWTH         :&str   = "max-growth";
+pub const METRIC             :&str   = "metric";
+pub const UPDATE_BASELINE    :&str   = "update-baseline";
+pub const FILE               :&str   = "file";
+pub const RESTRICT_TO        :&str   = "restrict-to";
+pub const LANGS_DIR          :&str   = "langs-dir";
+pub const IGNORE_EMPTY_FILES :&str   = "ignore-empty-files";
+pub const LANG_SCOPE         :&str   = "lang-scope";
+pub const REGEX_KEYWORDS     :&str   = "regex-keywords";
+pub const REPORT             :&str   = "report";
+pub const SIGN_KEY           :&str   = "sign-key";
+pub const FILES_FROM         :&str   = "files-from";
+pub const DETECT_SHEBANGS    :&str   = "detect-shebangs";
+pub const CO_OCCURRENCE      :&str   = "co-occurrence";
+pub const PREFER_SHEBANGS    :&str   = "prefer-shebangs";
+pub const LARGE_FILE_THRESHOLD :&str = "large-file-threshold";
+pub const SPLIT_THRESHOLD    :&str   = "split-threshold";
+pub const SKIP_BUILD_OUTPUTS :&str   = "skip-build-outputs";
+pub const TYPICAL_MARKERS    :&str   = "typical-markers";
+pub const KEYWORD_DENSITY    :&str   = "keyword-density";
+pub const KEYWORD_CATEGORIES :&str   = "keyword-categories";
+pub const STRICT_ENCODING    :&str   = "strict-encoding";
+pub const MAX_OPEN_FILES     :&str   = "max-open-files";
+pub const MAX_MEMORY         :&str   = "max-memory";
+pub const TIMEOUT            :&str   = "timeout";
+pub const FILE_TIMEOUT       :&str   = "file-timeout";
+pub const FOLLOW_LINKS       :&str   = "follow-links";
+pub const HIDDEN             :&str   = "hidden";
+pub const GIT_TRACKED        :&str   = "git-tracked";
+pub const ISOLATE_ROOTS      :&str   = "isolate-roots";
+pub const DOCS               :&str   = "docs";
+pub const DIR_BREAKDOWN      :&str   = "dir-breakdown";
+pub const PATH_STYLE         :&str   = "path-style";
+pub const SIZE_UNITS         :&str   = "size-units";
+pub const SIZE_PRECISION     :&str   = "size-precision";
+pub const NUMBER_FORMAT      :&str   = "number-format";
+pub const OUTPUT             :&str   = "output";
+pub const COLOR              :&str   = "color";
+pub const NO_COLOR           :&str   = "no-color";
+pub const LANG               :&str   = "lang";
+pub const SORT               :&str   = "sort";
+pub const COLUMNS            :&str   = "columns";
+pub const TOP                :&str   = "top";
+pub const TOP_FILES          :&str   = "top-files";
+pub const HISTOGRAM          :&str   = "histogram";
+pub const LIST_ONLY          :&str   = "list-only";
+pub const TIMINGS            :&str   = "timings";
+pub const STREAM             :&str   = "stream";
+pub const LOGICAL_LINES      :&str   = "logical-lines";
+pub const COMPLEXITY         :&str   = "complexity";
+pub const TEST_PATTERN       :&str   = "test-pattern";
+pub const SQLITE             :&str   = "sqlite";
+pub const OUT                :&str   = "out";
+pub const FORCE              :&str   = "force";
+pub const MATCH_CASE         :&str   = "match-case";
+pub const EVENT_LOG          :&str   = "event-log";
+pub const DUPLICATES         :&str   = "duplicates";
+pub const CLONES             :&str   = "clones";
+pub const LICENSES           :&str   = "licenses";
+pub const HYGIENE            :&str   = "hygiene";
+pub const INDENTATION        :&str   = "indentation";
+pub const SKIP_GENERATED     :&str   = "skip-generated";
+pub const SKIP_MINIFIED      :&str   = "skip-minified";
+pub const KEYWORD_HOTSPOTS   :&str   = "keyword-hotspots";
+pub const PRESCAN            :&str   = "prescan";
+pub const LINGUIST_COMPARE   :&str   = "linguist-compare";
+pub const AUTHORS            :&str   = "authors";
+pub const ESTIMATE           :&str   = "estimate";
+pub const SALARY             :&str   = "salary";
+pub const OVERHEAD           :&str   = "overhead";
+pub const STRICT             :&str   = "strict";
+pub const ZERO_KEYWORDS      :&str   = "zero-keywords";
+pub const TOP_KEYWORDS       :&str   = "top-keywords";
+pub const TUI                :&str   = "tui";
+pub const STATS              :&str   = "stats";
+pub const EMBEDDED           :&str   = "embedded";
+pub const IGNORE_TRAILING_EMPTY_LINE :&str = "ignore-trailing-empty-line";
+pub const SUMMARY            :&str   = "summary";
+pub const STYLE              :&str   = "style";
+pub const OVERVIEW           :&str   = "overview";
+pub const SHOW_SKIPPED       :&str   = "show-skipped";
+pub const DISAMBIGUATE       :&str   = "disambiguate";
+pub const KEYWORD_SCOPE      :&str   = "keyword-scope";
+pub const INCLUDE_SUBMODULES :&str   = "include-submodules";
+pub const EXCLUDE_SUBMODULES :&str   = "exclude-submodules";
+pub const MAX_LINE_LENGTH    :&str   = "max-line-length";
+pub const TODOS              :&str   = "todos";
+pub const MAX_DEPTH          :&str   = "max-depth";
+pub const MIN_SIZE           :&str   = "min-size";
+pub const MAX_SIZE           :&str   = "max-size";
+pub const NOTIFY             :&str   = "notify";
+pub const REMOTE             :&str   = "remote";
+pub const CHANGED_SINCE      :&str   = "changed-since";
+pub const NOTIFY_TEMPLATE    :&str   = "notify-template";
+pub const BADGE              :&str   = "badge";
+pub const EXPORT_TREEMAP     :&str   = "export-treemap";
+pub const PR_COMMENT         :&str   = "pr-comment";
+pub const FAIL_IF            :&str   = "fail-if";
+pub const METRICS            :&str   = "metrics";
+pub const QUIET              :&str   = "quiet";
+pub const VERBOSE            :&str   = "verbose";
+
+// The environment variables of the options start with it, e.g. 'MEZURA_TOP_FILES' for '--top-files'
+pub const ENV_PREFIX         :&str   = "MEZURA_";
+// The options that only print messages, or that save a configuration, are not taken from the environment
+const ENV_IGNORED_OPTIONS    : [&str; 7] = [HELP, CHANGELOG, SHOW_LANGUAGES, SHOW_CONFIGS, VERIFY_REPORT, SAVE, DUMP_CONFIG];
 
 pub const MAX_PRODUCERS_VALUE : usize = 4;
 pub const MIN_PRODUCERS_VALUE : usize = 1;
@@ -32,6 +153,36 @@ pub const MAX_CONSUMERS_VALUE : usize = 12;
 pub const MIN_CONSUMERS_VALUE : usize = 1;
 pub const MIN_COMPARE_LEVEL   : usize = 0;
 pub const MAX_COMPARE_LEVEL   : usize = 10;
+pub const MIN_LARGE_FILE_THRESHOLD : usize = 0;
+pub const MAX_LARGE_FILE_THRESHOLD : usize = 100_000;
+pub const MIN_SPLIT_THRESHOLD : usize = 0;
+pub const MAX_SPLIT_THRESHOLD : usize = 100_000;
+pub const MIN_MAX_LINE_LENGTH : usize = 1;
+pub const MAX_MAX_LINE_LENGTH : usize = 100_000;
+pub const MIN_SALARY : usize = 1;
+pub const MAX_SALARY : usize = 100_000_000;
+pub const MIN_OVERHEAD : usize = 100;
+pub const MAX_OVERHEAD : usize = 1000;
+pub const MIN_MAX_OPEN_FILES : usize = 0;
+pub const MAX_MAX_OPEN_FILES : usize = 100_000;
+pub const MIN_MAX_MEMORY : usize = 0;
+pub const MAX_MAX_MEMORY : usize = 100_000;
+pub const MIN_TIMEOUT : usize = 0;
+pub const MAX_TIMEOUT : usize = 86_400;
+pub const MIN_MAX_DEPTH : usize = 0;
+pub const MAX_MAX_DEPTH : usize = 10_000;
+pub const MIN_DIR_BREAKDOWN : usize = 1;
+pub const MAX_DIR_BREAKDOWN : usize = 100;
+pub const MIN_SIZE_PRECISION : usize = 0;
+pub const MAX_SIZE_PRECISION : usize = 3;
+pub const MIN_TOP : usize = 1;
+pub const MAX_TOP : usize = 1_000;
+pub const MIN_TOP_FILES : usize = 1;
+pub const MAX_TOP_FILES : usize = 1_000;
+pub const MIN_TOP_KEYWORDS : usize = 1;
+pub const MAX_TOP_KEYWORDS : usize = 1_000;
+pub const MIN_CLONE_LINES : usize = 3;
+pub const MAX_CLONE_LINES : usize = 1_000;
 
 // default config values
 const DEF_BRACES_AS_CODE    : bool    = false;
@@ -39,7 +190,79 @@ const DEF_SEARCH_IN_DOTTED  : bool    = false;
 const DEF_SHOW_FAULTY_FILES : bool    = false;
 const DEF_NO_VISUAL         : bool    = false;
 const DEF_NO_KEYWORDS       : bool    = false;
+const DEF_IGNORE_EMPTY_FILES: bool    = false;
+const DEF_REGEX_KEYWORDS    : bool    = false;
+const DEF_DETECT_SHEBANGS   : bool    = false;
+const DEF_PREFER_SHEBANGS   : bool    = false;
+const DEF_SKIP_BUILD_OUTPUTS: bool    = false;
+const DEF_TYPICAL_MARKERS   : bool    = false;
+const DEF_KEYWORD_DENSITY   : bool    = false;
+const DEF_KEYWORD_CATEGORIES: bool    = false;
+const DEF_STRICT_ENCODING   : bool    = false;
+// Half of the usual soft limit of open files of every platform, leaving the rest to the shell and the other processes
+#[cfg(target_os = "macos")]
+const DEF_MAX_OPEN_FILES    : usize   = 128;
+#[cfg(target_os = "windows")]
+const DEF_MAX_OPEN_FILES    : usize   = 256;
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const DEF_MAX_OPEN_FILES    : usize   = 512;
+const DEF_MAX_MEMORY        : usize   = 0;
+const DEF_TIMEOUT           : usize   = 0;
+const DEF_FILE_TIMEOUT      : usize   = 0;
+const DEF_FOLLOW_LINKS      : bool    = false;
+const DEF_HIDDEN            : bool    = false;
+const DEF_GIT_TRACKED       : bool    = false;
+const DEF_ISOLATE_ROOTS     : bool    = false;
+const DEF_DOCS              : bool    = false;
+const DEF_HISTOGRAM         : bool    = false;
+const DEF_LIST_ONLY         : bool    = false;
+const DEF_TIMINGS           : bool    = false;
+const DEF_STREAM            : bool    = false;
+const DEF_LOGICAL_LINES     : bool    = false;
+const DEF_COMPLEXITY        : bool    = false;
+const DEF_FORCE             : bool    = false;
+const DEF_MATCH_CASE        : bool    = false;
+const DEF_DUPLICATES        : bool    = false;
+const DEF_LICENSES          : bool    = false;
+const DEF_HYGIENE           : bool    = false;
+const DEF_INDENTATION       : bool    = false;
+const DEF_SKIP_GENERATED    : bool    = false;
+const DEF_SKIP_MINIFIED     : bool    = false;
+const DEF_KEYWORD_HOTSPOTS  : bool    = false;
+const DEF_PRESCAN           : bool    = false;
+const DEF_LINGUIST_COMPARE  : bool    = false;
+const DEF_AUTHORS           : bool    = false;
+const DEF_ESTIMATE          : bool    = false;
+const DEF_STRICT            : bool    = false;
+const DEF_ZERO_KEYWORDS     : bool    = false;
+const DEF_TUI               : bool    = false;
+const DEF_STATS             : bool    = false;
+const DEF_EMBEDDED          : bool    = false;
+const DEF_IGNORE_TRAILING_EMPTY_LINE : bool = false;
+const DEF_SUMMARY           : bool    = false;
+const DEF_STYLE             : Style   = Style::Ascii;
+const DEF_OVERVIEW          : [Column; 3] = [Column::Files, Column::Lines, Column::Size];
+const DEF_SHOW_SKIPPED      : bool    = false;
+const DEF_DIR_BREAKDOWN_DEPTH : usize = 1;
+// How deep the treemap of '--export-treemap' goes without '--dir-breakdown'
+const DEF_TREEMAP_DEPTH     : usize = 3;
+const DEF_PATH_STYLE        : PathStyle = PathStyle::Absolute;
+const DEF_SIZE_UNITS        : SizeUnits = SizeUnits::Si;
+const DEF_SIZE_PRECISION    : usize   = 1;
+const DEF_NUMBER_FORMAT     : NumberFormat = NumberFormat::Comma;
+const DEF_OUTPUT            : OutputFormat = OutputFormat::Text;
+const DEF_COLOR             : ColorPolicy = ColorPolicy::Auto;
+const DEF_LANG              : &str    = "en";
+const DEF_VERBOSITY         : Verbosity = Verbosity::Normal;
+pub const DEF_TODO_MARKERS  : [&str; 3] = ["TODO", "FIXME", "HACK"];
+pub const DEF_TEST_PATTERNS : [&str; 9] = ["tests/", "test/", "__tests__/", "*_test.*", "test_*", "*.test.*", "*.spec.*", "*Test.*", "*Tests.*"];
 const DEF_COMPARE_LEVEL     : usize   = 1;
+const DEF_LARGE_FILE_THRESHOLD : usize = 16;
+const DEF_SPLIT_THRESHOLD : usize = 0;
+const DEF_MAX_LINE_LENGTH : usize = 120;
+// The average yearly salary of a developer in the US and the overhead that the basic COCOMO estimates are usually made with
+const DEF_SALARY : usize = 56_286;
+const DEF_OVERHEAD : usize = 240;
 
 
 #[derive(Debug,PartialEq,Clone)]
@@ -48,6 +271,67 @@ pub struct Configuration {
     pub dirs: Vec<String>,
     pub exclude_dirs: Vec<String>,
     pub languages_of_interest: Vec<String>,
+    // The extensions and groups of '--ext' in their order, e.g. 'all', '-json' and 'all-code'. Resolved against the
+    // extensions of the loaded languages before the search
+    pub extension_filters: Vec<String>,
+    // The extensions only match in the same case, e.g. 'a.C' is not C. By default an exact match is preferred, but the case is ignored otherwise
+    pub match_case: bool,
+    // The contents of the files are hashed, to list the ones that are identical
+    pub duplicates: bool,
+    // The license of the header comment of each file is looked for, and the files with and without one are counted per extension
+    pub licenses: bool,
+    // The byte order marks, the line endings, the final newlines and the shebangs of the files are counted
+    pub hygiene: bool,
+    // The code lines indented with tabs and with spaces, and the width of the indentation, are counted per extension
+    pub indentation: bool,
+    // The files with a marker of generated code near their top (e.g. '@generated') are left out, instead of being
+    // counted and reported apart too
+    pub skip_generated: bool,
+    // The files with a line longer than 16 KiB (e.g. minified javascript) are left out, instead of being counted and
+    // reported apart too
+    pub skip_minified: bool,
+    // The file with the most occurrences of each keyword is shown under the keywords
+    pub keyword_hotspots: bool,
+    // All the files are found and their sizes read before the parsing starts, so that the biggest files are parsed first
+    // and the progress is the one of the bytes
+    pub prescan: bool,
+    // Instead of the analysis, the shares of the languages in bytes are compared with the ones of the language bar of GitHub,
+    // applying the 'linguist-*' attributes of the '.gitattributes' at the root of the dirs
+    pub linguist_compare: bool,
+    // The lines of the files in git repositories are attributed to their authors with git blame, per extension
+    pub authors: bool,
+    // The basic COCOMO estimates of the effort, the schedule and the cost of the code lines are added
+    pub estimate: bool,
+    // Yearly, in any currency, for the cost of '--estimate'
+    pub salary: usize,
+    // A percentage of the salary, with everything else that a developer costs (e.g. equipment, offices), for '--estimate'
+    pub overhead: usize,
+    // A faulty file fails the run, with 'ParseFilesError::FaultyFiles', instead of being left out of the results
+    pub strict: bool,
+    // The keywords that don't occur are listed too, with 0
+    pub zero_keywords: bool,
+    // Only the keywords that occur the most are listed, the most first. None if all of them are listed, by name
+    pub top_keywords: Option<usize>,
+    // The results are browsed in the terminal, after a chart of the lines that is drawn while the files are parsed
+    pub tui: bool,
+    // The durations of the phases, the speeds and how busy the parser threads were are printed after the results
+    pub stats: bool,
+    // The regions of the files in other languages, e.g. the scripts of HTML or the fenced code of markdown, are counted
+    // as the lines of their languages
+    pub embedded: bool,
+    // A blank last line of a file, e.g. the one of an editor that adds an extra newline, is not counted
+    pub ignore_trailing_empty_line: bool,
+    pub summary: bool,
+    pub style: Style,
+    pub overview: Vec<Column>,
+    // Whether the files that were left out for their size are listed, and not only counted
+    pub show_skipped: bool,
+    // The extensions whose files are given the language that their contents point to, e.g. 'h' for C, C++ or Objective-C
+    pub disambiguate: Vec<String>,
+    // Where the occurrences of all the keywords are counted, instead of the scopes of the language files
+    pub keyword_scope: Option<KeywordScope>,
+    // What is done with the git submodules, instead of searching them as any other directory
+    pub submodules: Option<Submodules>,
     pub threads: Threads,
     pub braces_as_code: bool,
     pub should_search_in_dotted: bool,
@@ -57,7 +341,110 @@ pub struct Configuration {
     pub log: LogOption,
     pub compare_level: usize,
     pub config_name_to_save: Option<String>,
-    pub config_name_to_load: Option<String>
+    pub config_name_to_load: Option<String>,
+    pub restrict_to: Option<String>,
+    pub langs_dir: Option<String>,
+    pub ignore_empty_files: bool,
+    pub lang_scopes: Vec<LangScope>,
+    pub regex_keywords: bool,
+    pub report_file: Option<String>,
+    // The SQLite database that the results of each run are appended to
+    pub sqlite: Option<String>,
+    // The file that every file found, skipped, parsed or faulty is written to as a json line
+    pub event_log: Option<String>,
+    // The file that the results are written to instead of stdout. An existing one is only overwritten with '--force'
+    pub out: Option<String>,
+    pub force: bool,
+    pub sign_key: Option<String>,
+    // A file with a list of paths to analyze, one per line, or '-' for the standard input. They are added to the dirs before the run
+    pub files_from: Option<String>,
+    pub detect_shebangs: bool,
+    pub prefer_shebangs: bool,
+    // In MBs, 0 disables reading the large files in blocks
+    pub large_file_threshold: usize,
+    // In MBs, the files of at least this size are parsed in segments by more than one thread. 0 disables it
+    pub split_threshold: usize,
+    // In characters, the lines that are longer are counted in the details
+    pub max_line_length: usize,
+    pub skip_build_outputs: bool,
+    pub typical_markers: bool,
+    // Shows the occurrences of the keywords per 1000 lines of code next to them
+    pub keyword_density: bool,
+    // Sums the occurrences of the keywords of each category of the language files across the languages
+    pub keyword_categories: bool,
+    pub strict_encoding: bool,
+    pub max_open_files: usize,
+    pub max_memory: usize,
+    // In seconds, for the whole search and parsing and for the parsing of each file. 0 means no limit.
+    pub timeout: usize,
+    pub file_timeout: usize,
+    pub follow_links: bool,
+    // The files and directories whose name starts with a dot, like .git or .eslintrc.js, are skipped unless this is set
+    pub hidden: bool,
+    pub git_tracked: bool,
+    pub isolate_roots: bool,
+    pub docs: bool,
+    pub max_depth: Option<usize>,
+    // How many languages are shown individually in the details and the overview, before the rest are grouped as "others".
+    // None for all of them in the details and 3 in the overview
+    pub top: Option<usize>,
+    // How many of the files with the most lines and the biggest size are listed, overall and per language. None if they are not listed
+    pub top_files: Option<usize>,
+    // The fewest code lines that a block repeated in another file needs to be listed as a clone. None if they are not searched for
+    pub clones: Option<usize>,
+    // Whether the files are also shown by how many lines they have, in the overview
+    pub histogram: bool,
+    // Only the search is done and the files that would be parsed are printed
+    pub list_only: bool,
+    // Whether the time spent parsing each extension and the slowest files are shown
+    pub timings: bool,
+    // Whether the stats of each language are printed as soon as all of its files are parsed, before the results
+    pub stream: bool,
+    // Counts the statements of the code as well, which are comparable between languages with different line styles
+    pub logical_lines: bool,
+    // The average complexity per file, from the branch keywords of the languages, is shown in the Details
+    pub complexity: bool,
+    // The number of directory levels of the per-directory breakdown. None if it is not shown
+    pub dir_breakdown: Option<usize>,
+    pub path_style: PathStyle,
+    pub size_units: SizeUnits,
+    // The decimal places of the sizes, when they are not printed in bytes
+    pub size_precision: usize,
+    // The thousands separator of the printed numbers
+    pub number_format: NumberFormat,
+    pub output: OutputFormat,
+    pub color: ColorPolicy,
+    // The language of the printed labels
+    pub lang: String,
+    // None for the default order, by files and size
+    pub sort: Option<SortOrder>,
+    // The stats that are shown in the Details and the sum. None for all of them
+    pub columns: Option<Vec<Column>>,
+    pub verbosity: Verbosity,
+    // In bytes
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    // 'slack:<url>' or 'webhook:<url>'
+    pub notify: Option<String>,
+    pub notify_template: Option<String>,
+    pub badge: Option<Badge>,
+    // The svg or dot file of the directories by their lines
+    pub export_treemap: Option<String>,
+    pub pr_comment: Option<PrComment>,
+    // The run fails if any of them holds for the results
+    pub fail_if: Vec<Budget>,
+    // Evaluated over the totals, to be shown with them, e.g. 'comment_ratio = extra / lines'
+    pub metrics: Vec<DerivedMetric>,
+    // The url of a git repository to clone and analyze instead of the dirs
+    pub remote: Option<String>,
+    // A git revision (e.g. a branch), to only analyze the files that changed since then
+    pub changed_since: Option<String>,
+    // The names of the keywords to show in the co-occurrence matrix, or all of them if empty. None disables it
+    pub co_occurrence: Option<Vec<String>>,
+    // The markers to look for in the comments, or the default ones if empty. None disables it
+    pub todos: Option<Vec<String>>,
+    // The patterns that tell the test files apart from the production ones, or the default ones if empty. None disables it
+    pub test_patterns: Option<Vec<String>>
 }
 
 #[derive(Debug,PartialEq,Clone)]
@@ -66,12 +453,139 @@ pub struct LogOption {
     pub name: Option<String>
 }
 
+// Restricts the files of a language to the given directories, relative to the searched dirs.
+// The directories may contain '*' and '**' wildcards.
+#[derive(Debug,PartialEq,Clone)]
+pub struct LangScope {
+    pub language: String,
+    pub dirs: Vec<String>
+}
+
+// The paths of '--pr-comment'
+#[derive(Debug,PartialEq,Clone)]
+pub struct PrComment {
+    // A report written with '--report'
+    pub baseline_report: String,
+    pub comment_file: String
+}
+
 #[derive(Debug,PartialEq,Clone)]
 pub struct Threads {
     pub producers: usize,
     pub consumers: usize
 }
 
+// How the paths are printed, so that the outputs of different machines can be compared
+#[derive(Debug,PartialEq,Clone,Copy)]
+pub enum PathStyle {
+    // As they were found, which are absolute paths
+    Absolute,
+    // With '/' as the separator
+    Unix,
+    // With the separator of the operating system
+    Os,
+    // Relative to the working directory, with '/' as the separator
+    Relative
+}
+
+// How the sizes are printed
+#[derive(Debug,PartialEq,Clone,Copy)]
+pub enum SizeUnits {
+    // In powers of 1000 (KBs, MBs)
+    Si,
+    // In powers of 1024 (KiBs, MiBs)
+    Binary,
+    // The exact number of bytes, for auditing
+    Bytes
+}
+
+// The thousands separator of the printed numbers. The raw outputs (the logs, the report, json and prometheus) don't have any
+#[derive(Debug,PartialEq,Clone,Copy)]
+pub enum NumberFormat {
+    Comma,
+    Dot,
+    Space,
+    Plain
+}
+
+// The characters of the bars and the separators of the results. Unicode draws them with block characters, ascii is for the
+// terminals and fonts that don't have them
+#[derive(Debug,PartialEq,Clone,Copy)]
+pub enum Style {
+    Ascii,
+    Unicode
+}
+
+// How the tables of the results are printed. Markdown is printed without colors, to be pasted in documents,
+// Prometheus as metrics in the text exposition format, to be pushed to a Pushgateway, and Json, Xml and Yaml as documents
+// for the reporting pipelines that only read those
+#[derive(Debug,PartialEq,Clone,Copy)]
+pub enum OutputFormat {
+    Text,
+    Markdown,
+    Prometheus,
+    Json,
+    Xml,
+    Yaml
+}
+
+// Whether the output has colors. Auto leaves them out when the 'NO_COLOR' environment variable is set
+// or when the output is not a terminal (e.g. redirected to a file).
+#[derive(Debug,PartialEq,Clone,Copy)]
+pub enum ColorPolicy {
+    Auto,
+    Always,
+    Never
+}
+
+// What the languages are sorted by in the Details and the overview
+#[derive(Debug,PartialEq,Clone,Copy)]
+pub enum SortKey {
+    Files,
+    Lines,
+    Code,
+    Size,
+    Name
+}
+
+// The stats of a row of the Details and the sum, that can be left out with '--columns', and the lines of the overview,
+// that are chosen with '--overview'
+#[derive(Debug,PartialEq,Clone,Copy)]
+pub enum Column {
+    Files,
+    Lines,
+    Code,
+    // The extra lines, which are the comments and the blank lines
+    Comments,
+    Size,
+    Keywords,
+    // The part of the total lines and code lines that a row of the Details has. Not a line of the overview
+    Share
+}
+
+#[derive(Debug,PartialEq,Clone,Copy)]
+pub struct SortOrder {
+    pub key: SortKey,
+    pub ascending: bool
+}
+
+// With '--include-submodules' the submodules are searched as roots of their own, so that they get their own rows in the
+// breakdown, while with '--exclude-submodules' they are not searched at all.
+#[derive(Debug,PartialEq,Clone,Copy)]
+pub enum Submodules {
+    Include,
+    Exclude
+}
+
+// How much is printed while running. Quiet prints only the results (a summary line, or the tables in markdown)
+// and the errors, while verbose adds every parsed file and the timings of the threads.
+#[derive(Debug,PartialEq,Clone,Copy)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose
+}
+
 #[derive(Debug, PartialEq)]
 pub enum ArgParsingError {
     NoArgsProvided,
@@ -83,7 +597,16 @@ pub enum ArgParsingError {
     UnrecognisedCommand(String),
     IncorrectCommandArgs(String),
     UnexpectedCommandArgs(String),
-    NonExistantConfig(String)
+    NonExistantConfig(String),
+    // The profile of '--profile' is not in the configuration
+    NonExistantProfile(String),
+    PathOutsideRestrictedRoot(String,String),
+    InvalidSizeRange(u64,u64),
+    DirsWithRemote,
+    // The key file of '--sign-key' doesn't exist or is empty
+    UnreadableSignKey(String),
+    // The producers and the consumers of '--threads', when either is 0
+    InvalidThreads(usize,usize)
 }
 
 // Empty line argument is not supposed to be allowed, since this check is being performed in main
@@ -95,7 +618,8 @@ pub fn create_config_from_args(line: &str) -> Result<Configuration, ArgParsingEr
 }
 
 pub fn create_config_builder_from_args(line: &str) -> Result<ConfigurationBuilder, ArgParsingError> {
-    let mut dirs = None;
+    let line = &add_env_options(line, std::env::vars());
+    let mut config_builder = ConfigurationBuilder::default();
     let mut options = line.split("--");
 
     if line.trim().starts_with("--") {
@@ -105,7 +629,7 @@ pub fn create_config_builder_from_args(line: &str) -> Result<ConfigurationBuilde
         match parse_dirs(options.next().unwrap()) {
             Ok(x) => {
                 if !x.is_empty() {
-                    dirs = Some(x);
+                    config_builder.dirs = Some(x);
                 }
             },
             Err(x) => {
@@ -115,13 +639,10 @@ pub fn create_config_builder_from_args(line: &str) -> Result<ConfigurationBuilde
     }
 
     let mut custom_config = None;
-    let (mut exclude_dirs, mut languages_of_interest, mut threads, mut braces_as_code,
-         mut search_in_dotted, mut show_faulty_files, mut config_name_to_save, mut no_visual,
-         mut log, mut compare_level, mut config_name_to_load, mut no_keywords) 
-         = (None, None, None, None, None, None, None, None, None, None, None, None);
+    let mut profile = None;
     for command in options {
          if let Some(_dirs) = command.strip_prefix(DIRS) {
-            if dirs.is_some() {
+            if config_builder.dirs.is_some() {
                 return Err(ArgParsingError::DoublePath);
             }
 
@@ -131,29 +652,51 @@ pub fn create_config_builder_from_args(line: &str) -> Result<ConfigurationBuilde
                     message_printer::print_help_message_for_command(DIRS);
                     return Err(ArgParsingError::IncorrectCommandArgs(DIRS.to_owned()));
                 }
-                dirs = Some(x)
+                config_builder.dirs = Some(x)
             } else {
                 return Err(parse_result.err().unwrap());
             }
+        } else if command.starts_with(INCLUDE_SUBMODULES) || command.starts_with(EXCLUDE_SUBMODULES) {
+            // Before '--exclude', which it starts with
+            let (name, value) = if command.starts_with(INCLUDE_SUBMODULES) {(INCLUDE_SUBMODULES, Submodules::Include)} else {(EXCLUDE_SUBMODULES, Submodules::Exclude)};
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(name);
+                return Err(ArgParsingError::UnexpectedCommandArgs(name.to_owned()))
+            }
+            // They can't be combined
+            if config_builder.submodules.is_some_and(|x| x != value) {
+                message_printer::print_help_message_for_command(name);
+                return Err(ArgParsingError::IncorrectCommandArgs(name.to_owned()))
+            }
+            config_builder.submodules = Some(value);
         } else if let Some(excluded) = command.strip_prefix(EXCLUDE) {
             let vec = utils::parse_paths_to_vec(excluded);
             if vec.is_empty() {
                 message_printer::print_help_message_for_command(EXCLUDE);
                 return Err(ArgParsingError::IncorrectCommandArgs(EXCLUDE.to_owned()));
             }
-            exclude_dirs = Some(vec);
+            config_builder.exclude_dirs = Some(vec);
         } else if let Some(langs) = command.strip_prefix(LANGUAGES) {
             let vec = utils::parse_languages_to_vec(langs);
             if vec.is_empty() {
                 message_printer::print_help_message_for_command(LANGUAGES);
                 return Err(ArgParsingError::IncorrectCommandArgs(LANGUAGES.to_owned()));
             }    
-            languages_of_interest = Some(vec);
+            config_builder.languages_of_interest = Some(vec);
+        } else if let Some(value) = command.strip_prefix(EXT) {
+            let vec = utils::parse_extension_filters(value);
+            if vec.is_empty() {
+                message_printer::print_help_message_for_command(EXT);
+                return Err(ArgParsingError::IncorrectCommandArgs(EXT.to_owned()));
+            }
+            config_builder.extension_filters = Some(vec);
         } else if let Some(value) = command.strip_prefix(THREADS) {
-            let threads_values = utils::parse_two_usize_values(value,
-                    MIN_PRODUCERS_VALUE, MAX_PRODUCERS_VALUE, MIN_CONSUMERS_VALUE, MAX_CONSUMERS_VALUE);
-            if let Some(_threads) = threads_values {
-                threads = Some(Threads::from(_threads));
+            let threads_values = utils::parse_two_usize_values(value, 0, usize::MAX, 0, usize::MAX);
+            if let Some((producers, consumers)) = threads_values {
+                if producers < MIN_PRODUCERS_VALUE || consumers < MIN_CONSUMERS_VALUE {
+                    return Err(ArgParsingError::InvalidThreads(producers, consumers));
+                }
+                config_builder.threads = Some(Threads::clamped(producers, consumers));
             } else {
                 message_printer::print_help_message_for_command(THREADS);
                 return Err(ArgParsingError::IncorrectCommandArgs(THREADS.to_owned()))
@@ -163,37 +706,74 @@ pub fn create_config_builder_from_args(line: &str) -> Result<ConfigurationBuilde
                 message_printer::print_help_message_for_command(BRACES_AS_CODE);
                 return Err(ArgParsingError::UnexpectedCommandArgs(BRACES_AS_CODE.to_owned()))
             }
-            braces_as_code = Some(true)
+            config_builder.braces_as_code = Some(true)
         } else if command.starts_with(SEARCH_IN_DOTTED) {
             if has_any_args(command) {
                 message_printer::print_help_message_for_command(SEARCH_IN_DOTTED);
                 return Err(ArgParsingError::UnexpectedCommandArgs(SEARCH_IN_DOTTED.to_owned()))
             }
-            search_in_dotted = Some(true)
+            config_builder.should_search_in_dotted = Some(true)
         } else if command.starts_with(SHOW_FAULTY_FILES) {
             if has_any_args(command) {
                 message_printer::print_help_message_for_command(SHOW_FAULTY_FILES);
                 return Err(ArgParsingError::UnexpectedCommandArgs(SHOW_FAULTY_FILES.to_owned()))
             }
-            show_faulty_files = Some(true);
+            config_builder.should_show_faulty_files = Some(true);
         } else if command.starts_with(NO_KEYWORDS) {
             if has_any_args(command) {
                 message_printer::print_help_message_for_command(NO_VISUAL);
                 return Err(ArgParsingError::UnexpectedCommandArgs(NO_KEYWORDS.to_owned()))
             }
-            no_keywords = Some(true);
+            config_builder.no_keywords = Some(true);
         } else if command.starts_with(NO_VISUAL) {
             if has_any_args(command) {
                 message_printer::print_help_message_for_command(NO_VISUAL);
                 return Err(ArgParsingError::UnexpectedCommandArgs(NO_VISUAL.to_owned()))
             }
-            no_visual = Some(true);
+            config_builder.no_visual = Some(true);
+        } else if command.starts_with(LOGICAL_LINES) {
+            // Before '--log', which it starts with
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(LOGICAL_LINES);
+                return Err(ArgParsingError::UnexpectedCommandArgs(LOGICAL_LINES.to_owned()))
+            }
+            config_builder.logical_lines = Some(true);
+        } else if command.starts_with(COMPLEXITY) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(COMPLEXITY);
+                return Err(ArgParsingError::UnexpectedCommandArgs(COMPLEXITY.to_owned()))
+            }
+            config_builder.complexity = Some(true);
         } else if let Some(value) = command.strip_prefix(LOG) {
             let value = value.trim();
             if value.is_empty() {
-                log = Some(LogOption::new(None));
+                config_builder.log = Some(LogOption::new(None));
             } else {
-                log = Some(LogOption::new(Some(value.to_owned())));
+                config_builder.log = Some(LogOption::new(Some(value.to_owned())));
+            }
+        } else if let Some(value) = command.strip_prefix(MAX_LINE_LENGTH) {
+            match utils::parse_usize_value(value, MIN_MAX_LINE_LENGTH, MAX_MAX_LINE_LENGTH) {
+                Some(x) => config_builder.max_line_length = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(MAX_LINE_LENGTH);
+                    return Err(ArgParsingError::IncorrectCommandArgs(MAX_LINE_LENGTH.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(LARGE_FILE_THRESHOLD) {
+            match utils::parse_usize_value(value, MIN_LARGE_FILE_THRESHOLD, MAX_LARGE_FILE_THRESHOLD) {
+                Some(x) => config_builder.large_file_threshold = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(LARGE_FILE_THRESHOLD);
+                    return Err(ArgParsingError::IncorrectCommandArgs(LARGE_FILE_THRESHOLD.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(SPLIT_THRESHOLD) {
+            match utils::parse_usize_value(value, MIN_SPLIT_THRESHOLD, MAX_SPLIT_THRESHOLD) {
+                Some(x) => config_builder.split_threshold = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(SPLIT_THRESHOLD);
+                    return Err(ArgParsingError::IncorrectCommandArgs(SPLIT_THRESHOLD.to_owned()))
+                }
             }
         } else if let Some(value) = command.strip_prefix(COMPRARE_LEVEL) {
             let compare_num = utils::parse_usize_value(value, MIN_COMPARE_LEVEL, MAX_COMPARE_LEVEL);
@@ -201,7 +781,7 @@ pub fn create_config_builder_from_args(line: &str) -> Result<ConfigurationBuilde
                 message_printer::print_help_message_for_command(COMPRARE_LEVEL);
                 return Err(ArgParsingError::IncorrectCommandArgs(COMPRARE_LEVEL.to_owned()))
             } else {
-                compare_level = compare_num
+                config_builder.compare_level = compare_num
             }
         } else if let Some(config_name) = command.strip_prefix(LOAD) {
             let config_name = config_name.trim();
@@ -219,7 +799,7 @@ pub fn create_config_builder_from_args(line: &str) -> Result<ConfigurationBuilde
                     }
                 }
                 custom_config = Some(options);
-                config_name_to_load = Some(config_name.to_owned());
+                config_builder.config_name_to_load = Some(config_name.to_owned());
             } else {
                 return Err(ArgParsingError::NonExistantConfig(config_name.to_owned()))
             }
@@ -229,18 +809,667 @@ pub fn create_config_builder_from_args(line: &str) -> Result<ConfigurationBuilde
                 message_printer::print_help_message_for_command(SAVE);
                 return Err(ArgParsingError::IncorrectCommandArgs(SAVE.to_owned()))
             }
-            config_name_to_save = Some(name.to_owned());
+            config_builder.config_name_to_save = Some(name.to_owned());
+        } else if let Some(name) = command.strip_prefix(PROFILE) {
+            let name = name.trim();
+            if name.is_empty() {
+                message_printer::print_help_message_for_command(PROFILE);
+                return Err(ArgParsingError::IncorrectCommandArgs(PROFILE.to_owned()))
+            }
+            profile = Some(name.to_owned());
+        } else if let Some(source) = command.strip_prefix(FILES_FROM) {
+            match utils::get_trimmed_if_not_empty(source).filter(|x| x == "-" || Path::new(x).is_file()) {
+                Some(x) => config_builder.files_from = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(FILES_FROM);
+                    return Err(ArgParsingError::IncorrectCommandArgs(FILES_FROM.to_owned()))
+                }
+            }
+        } else if let Some(path) = command.strip_prefix(REPORT) {
+            match parse_report_file(path) {
+                Some(x) => config_builder.report_file = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(REPORT);
+                    return Err(ArgParsingError::IncorrectCommandArgs(REPORT.to_owned()))
+                }
+            }
+        } else if let Some(path) = command.strip_prefix(EVENT_LOG) {
+            match parse_report_file(path) {
+                Some(x) => config_builder.event_log = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(EVENT_LOG);
+                    return Err(ArgParsingError::IncorrectCommandArgs(EVENT_LOG.to_owned()))
+                }
+            }
+        } else if let Some(path) = command.strip_prefix(SQLITE) {
+            // Like the report, the database doesn't need to exist
+            match parse_report_file(path) {
+                Some(x) => config_builder.sqlite = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(SQLITE);
+                    return Err(ArgParsingError::IncorrectCommandArgs(SQLITE.to_owned()))
+                }
+            }
+        } else if command.split(' ').next() == Some(OUT) {
+            // Compared as a whole word, since 'output' starts with it
+            match parse_report_file(&command[OUT.len()..]) {
+                Some(x) => config_builder.out = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(OUT);
+                    return Err(ArgParsingError::IncorrectCommandArgs(OUT.to_owned()))
+                }
+            }
+        } else if command.starts_with(FORCE) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(FORCE);
+                return Err(ArgParsingError::UnexpectedCommandArgs(FORCE.to_owned()))
+            }
+            config_builder.force = Some(true);
+        } else if command.starts_with(MATCH_CASE) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(MATCH_CASE);
+                return Err(ArgParsingError::UnexpectedCommandArgs(MATCH_CASE.to_owned()))
+            }
+            config_builder.match_case = Some(true);
+        } else if command.starts_with(DUPLICATES) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(DUPLICATES);
+                return Err(ArgParsingError::UnexpectedCommandArgs(DUPLICATES.to_owned()))
+            }
+            config_builder.duplicates = Some(true);
+        } else if command.starts_with(LICENSES) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(LICENSES);
+                return Err(ArgParsingError::UnexpectedCommandArgs(LICENSES.to_owned()))
+            }
+            config_builder.licenses = Some(true);
+        } else if command.starts_with(HYGIENE) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(HYGIENE);
+                return Err(ArgParsingError::UnexpectedCommandArgs(HYGIENE.to_owned()))
+            }
+            config_builder.hygiene = Some(true);
+        } else if command.starts_with(INDENTATION) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(INDENTATION);
+                return Err(ArgParsingError::UnexpectedCommandArgs(INDENTATION.to_owned()))
+            }
+            config_builder.indentation = Some(true);
+        } else if command.starts_with(SKIP_GENERATED) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(SKIP_GENERATED);
+                return Err(ArgParsingError::UnexpectedCommandArgs(SKIP_GENERATED.to_owned()))
+            }
+            config_builder.skip_generated = Some(true);
+        } else if command.starts_with(SKIP_MINIFIED) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(SKIP_MINIFIED);
+                return Err(ArgParsingError::UnexpectedCommandArgs(SKIP_MINIFIED.to_owned()))
+            }
+            config_builder.skip_minified = Some(true);
+        } else if command.starts_with(KEYWORD_HOTSPOTS) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(KEYWORD_HOTSPOTS);
+                return Err(ArgParsingError::UnexpectedCommandArgs(KEYWORD_HOTSPOTS.to_owned()))
+            }
+            config_builder.keyword_hotspots = Some(true);
+        } else if command.starts_with(PRESCAN) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(PRESCAN);
+                return Err(ArgParsingError::UnexpectedCommandArgs(PRESCAN.to_owned()))
+            }
+            config_builder.prescan = Some(true);
+        } else if command.starts_with(LINGUIST_COMPARE) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(LINGUIST_COMPARE);
+                return Err(ArgParsingError::UnexpectedCommandArgs(LINGUIST_COMPARE.to_owned()))
+            }
+            config_builder.linguist_compare = Some(true);
+        } else if command.starts_with(AUTHORS) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(AUTHORS);
+                return Err(ArgParsingError::UnexpectedCommandArgs(AUTHORS.to_owned()))
+            }
+            config_builder.authors = Some(true);
+        } else if command.starts_with(ESTIMATE) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(ESTIMATE);
+                return Err(ArgParsingError::UnexpectedCommandArgs(ESTIMATE.to_owned()))
+            }
+            config_builder.estimate = Some(true);
+        } else if let Some(value) = command.strip_prefix(SALARY) {
+            match utils::parse_usize_value(value, MIN_SALARY, MAX_SALARY) {
+                Some(x) => config_builder.salary = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(SALARY);
+                    return Err(ArgParsingError::IncorrectCommandArgs(SALARY.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(OVERHEAD) {
+            match utils::parse_usize_value(value, MIN_OVERHEAD, MAX_OVERHEAD) {
+                Some(x) => config_builder.overhead = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(OVERHEAD);
+                    return Err(ArgParsingError::IncorrectCommandArgs(OVERHEAD.to_owned()))
+                }
+            }
+        } else if let Some(template) = command.strip_prefix(NOTIFY_TEMPLATE) {
+            let template = template.trim();
+            if template.is_empty() {
+                message_printer::print_help_message_for_command(NOTIFY_TEMPLATE);
+                return Err(ArgParsingError::IncorrectCommandArgs(NOTIFY_TEMPLATE.to_owned()))
+            }
+            config_builder.notify_template = Some(template.to_owned());
+        } else if let Some(target) = command.strip_prefix(NOTIFY) {
+            match parse_notify_target(target) {
+                Some(x) => config_builder.notify = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(NOTIFY);
+                    return Err(ArgParsingError::IncorrectCommandArgs(NOTIFY.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(BADGE) {
+            match badge::parse_badge(value) {
+                Some(x) => config_builder.badge = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(BADGE);
+                    return Err(ArgParsingError::IncorrectCommandArgs(BADGE.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(EXPORT_TREEMAP) {
+            match treemap::parse_treemap_path(value) {
+                Some(x) => config_builder.export_treemap = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(EXPORT_TREEMAP);
+                    return Err(ArgParsingError::IncorrectCommandArgs(EXPORT_TREEMAP.to_owned()))
+                }
+            }
+        } else if let Some(expressions) = command.strip_prefix(FAIL_IF) {
+            match budgets::parse_budgets(expressions) {
+                Some(x) => config_builder.fail_if = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(FAIL_IF);
+                    return Err(ArgParsingError::IncorrectCommandArgs(FAIL_IF.to_owned()))
+                }
+            }
+        } else if let Some(definitions) = command.strip_prefix(METRICS) {
+            match derived_metrics::parse_derived_metrics(definitions) {
+                Some(x) => config_builder.metrics = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(METRICS);
+                    return Err(ArgParsingError::IncorrectCommandArgs(METRICS.to_owned()))
+                }
+            }
+        } else if let Some(paths) = command.strip_prefix(PR_COMMENT) {
+            match parse_pr_comment(paths) {
+                Some(x) => config_builder.pr_comment = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(PR_COMMENT);
+                    return Err(ArgParsingError::IncorrectCommandArgs(PR_COMMENT.to_owned()))
+                }
+            }
+        } else if let Some(url) = command.strip_prefix(REMOTE) {
+            let url = url.trim();
+            if !remote::is_valid_remote_url(url) {
+                message_printer::print_help_message_for_command(REMOTE);
+                return Err(ArgParsingError::IncorrectCommandArgs(REMOTE.to_owned()))
+            }
+            config_builder.remote = Some(url.to_owned());
+        } else if let Some(revision) = command.strip_prefix(CHANGED_SINCE) {
+            match parse_revision(revision) {
+                Some(x) => config_builder.changed_since = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(CHANGED_SINCE);
+                    return Err(ArgParsingError::IncorrectCommandArgs(CHANGED_SINCE.to_owned()))
+                }
+            }
+        } else if let Some(path) = command.strip_prefix(SIGN_KEY) {
+            let path = path.trim();
+            if path.is_empty() {
+                message_printer::print_help_message_for_command(SIGN_KEY);
+                return Err(ArgParsingError::IncorrectCommandArgs(SIGN_KEY.to_owned()))
+            }
+            match io_handler::read_sign_key(path) {
+                Ok(x) => config_builder.sign_key = Some(x),
+                Err(_) => return Err(ArgParsingError::UnreadableSignKey(path.to_owned()))
+            }
+        } else if let Some(root) = command.strip_prefix(RESTRICT_TO) {
+            match utils::parse_canonical_dir(root) {
+                Some(x) => config_builder.restrict_to = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(RESTRICT_TO);
+                    return Err(ArgParsingError::IncorrectCommandArgs(RESTRICT_TO.to_owned()))
+                }
+            }
+        } else if command.starts_with(REGEX_KEYWORDS) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(REGEX_KEYWORDS);
+                return Err(ArgParsingError::UnexpectedCommandArgs(REGEX_KEYWORDS.to_owned()))
+            }
+            config_builder.regex_keywords = Some(true);
+        } else if let Some(keyword_names) = command.strip_prefix(CO_OCCURRENCE) {
+            config_builder.co_occurrence = Some(utils::parse_keyword_names_to_vec(keyword_names));
+        } else if let Some(markers) = command.strip_prefix(TODOS) {
+            config_builder.todos = Some(utils::parse_todo_markers_to_vec(markers));
+        } else if let Some(patterns) = command.strip_prefix(TEST_PATTERN) {
+            config_builder.test_patterns = Some(utils::parse_paths_to_vec(patterns));
+        } else if command.starts_with(DOCS) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(DOCS);
+                return Err(ArgParsingError::UnexpectedCommandArgs(DOCS.to_owned()))
+            }
+            config_builder.docs = Some(true);
+        } else if command.starts_with(HISTOGRAM) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(HISTOGRAM);
+                return Err(ArgParsingError::UnexpectedCommandArgs(HISTOGRAM.to_owned()))
+            }
+            config_builder.histogram = Some(true);
+        } else if command.starts_with(LIST_ONLY) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(LIST_ONLY);
+                return Err(ArgParsingError::UnexpectedCommandArgs(LIST_ONLY.to_owned()))
+            }
+            config_builder.list_only = Some(true);
+        } else if command.starts_with(TIMINGS) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(TIMINGS);
+                return Err(ArgParsingError::UnexpectedCommandArgs(TIMINGS.to_owned()))
+            }
+            config_builder.timings = Some(true);
+        } else if command.starts_with(STREAM) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(STREAM);
+                return Err(ArgParsingError::UnexpectedCommandArgs(STREAM.to_owned()))
+            }
+            config_builder.stream = Some(true);
+        } else if command.starts_with(ISOLATE_ROOTS) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(ISOLATE_ROOTS);
+                return Err(ArgParsingError::UnexpectedCommandArgs(ISOLATE_ROOTS.to_owned()))
+            }
+            config_builder.isolate_roots = Some(true);
+        } else if command.starts_with(GIT_TRACKED) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(GIT_TRACKED);
+                return Err(ArgParsingError::UnexpectedCommandArgs(GIT_TRACKED.to_owned()))
+            }
+            config_builder.git_tracked = Some(true);
+        } else if command.starts_with(FOLLOW_LINKS) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(FOLLOW_LINKS);
+                return Err(ArgParsingError::UnexpectedCommandArgs(FOLLOW_LINKS.to_owned()))
+            }
+            config_builder.follow_links = Some(true);
+        } else if command.starts_with(HIDDEN) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(HIDDEN);
+                return Err(ArgParsingError::UnexpectedCommandArgs(HIDDEN.to_owned()))
+            }
+            config_builder.hidden = Some(true);
+        } else if command.split(' ').next() == Some(LANG) {
+            // Compared as a whole word, since 'languages' and 'lang-scope' start with it
+            let value = command[LANG.len()..].trim().to_lowercase();
+            if !labels::is_supported_language(&value) {
+                message_printer::print_help_message_for_command(LANG);
+                return Err(ArgParsingError::IncorrectCommandArgs(LANG.to_owned()))
+            }
+            config_builder.lang = Some(value);
+        } else if command.starts_with(QUIET) || command.starts_with(VERBOSE) {
+            let (name, value) = if command.starts_with(QUIET) {(QUIET, Verbosity::Quiet)} else {(VERBOSE, Verbosity::Verbose)};
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(name);
+                return Err(ArgParsingError::UnexpectedCommandArgs(name.to_owned()))
+            }
+            // They can't be combined
+            if config_builder.verbosity.is_some_and(|x| x != value) {
+                message_printer::print_help_message_for_command(name);
+                return Err(ArgParsingError::IncorrectCommandArgs(name.to_owned()))
+            }
+            config_builder.verbosity = Some(value);
+        } else if let Some(value) = command.strip_prefix(SORT) {
+            match SortOrder::parse(value) {
+                Some(x) => config_builder.sort = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(SORT);
+                    return Err(ArgParsingError::IncorrectCommandArgs(SORT.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(COLUMNS) {
+            match Column::parse_list(value) {
+                Some(x) => config_builder.columns = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(COLUMNS);
+                    return Err(ArgParsingError::IncorrectCommandArgs(COLUMNS.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(OUTPUT) {
+            match OutputFormat::parse(value) {
+                Some(x) => config_builder.output = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(OUTPUT);
+                    return Err(ArgParsingError::IncorrectCommandArgs(OUTPUT.to_owned()))
+                }
+            }
+        } else if command.starts_with(NO_COLOR) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(NO_COLOR);
+                return Err(ArgParsingError::UnexpectedCommandArgs(NO_COLOR.to_owned()))
+            }
+            config_builder.color = Some(ColorPolicy::Never);
+        } else if let Some(value) = command.strip_prefix(COLOR) {
+            match ColorPolicy::parse(value) {
+                Some(x) => config_builder.color = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(COLOR);
+                    return Err(ArgParsingError::IncorrectCommandArgs(COLOR.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(PATH_STYLE) {
+            match PathStyle::parse(value) {
+                Some(x) => config_builder.path_style = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(PATH_STYLE);
+                    return Err(ArgParsingError::IncorrectCommandArgs(PATH_STYLE.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(SIZE_UNITS) {
+            match SizeUnits::parse(value) {
+                Some(x) => config_builder.size_units = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(SIZE_UNITS);
+                    return Err(ArgParsingError::IncorrectCommandArgs(SIZE_UNITS.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(SIZE_PRECISION) {
+            match utils::parse_usize_value(value, MIN_SIZE_PRECISION, MAX_SIZE_PRECISION) {
+                Some(x) => config_builder.size_precision = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(SIZE_PRECISION);
+                    return Err(ArgParsingError::IncorrectCommandArgs(SIZE_PRECISION.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(NUMBER_FORMAT) {
+            match NumberFormat::parse(value) {
+                Some(x) => config_builder.number_format = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(NUMBER_FORMAT);
+                    return Err(ArgParsingError::IncorrectCommandArgs(NUMBER_FORMAT.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(DIR_BREAKDOWN) {
+            if !has_any_args(command) {
+                config_builder.dir_breakdown = Some(DEF_DIR_BREAKDOWN_DEPTH);
+            } else {
+                match utils::parse_usize_value(value, MIN_DIR_BREAKDOWN, MAX_DIR_BREAKDOWN) {
+                    Some(x) => config_builder.dir_breakdown = Some(x),
+                    None => {
+                        message_printer::print_help_message_for_command(DIR_BREAKDOWN);
+                        return Err(ArgParsingError::IncorrectCommandArgs(DIR_BREAKDOWN.to_owned()))
+                    }
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(MAX_DEPTH) {
+            match utils::parse_usize_value(value, MIN_MAX_DEPTH, MAX_MAX_DEPTH) {
+                Some(x) => config_builder.max_depth = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(MAX_DEPTH);
+                    return Err(ArgParsingError::IncorrectCommandArgs(MAX_DEPTH.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(CLONES) {
+            match utils::parse_usize_value(value, MIN_CLONE_LINES, MAX_CLONE_LINES) {
+                Some(x) => config_builder.clones = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(CLONES);
+                    return Err(ArgParsingError::IncorrectCommandArgs(CLONES.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(TOP_KEYWORDS) {
+            match utils::parse_usize_value(value, MIN_TOP_KEYWORDS, MAX_TOP_KEYWORDS) {
+                Some(x) => config_builder.top_keywords = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(TOP_KEYWORDS);
+                    return Err(ArgParsingError::IncorrectCommandArgs(TOP_KEYWORDS.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(TOP_FILES) {
+            match utils::parse_usize_value(value, MIN_TOP_FILES, MAX_TOP_FILES) {
+                Some(x) => config_builder.top_files = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(TOP_FILES);
+                    return Err(ArgParsingError::IncorrectCommandArgs(TOP_FILES.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(TOP) {
+            match utils::parse_usize_value(value, MIN_TOP, MAX_TOP) {
+                Some(x) => config_builder.top = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(TOP);
+                    return Err(ArgParsingError::IncorrectCommandArgs(TOP.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(MIN_SIZE) {
+            match utils::parse_size_value(value) {
+                Some(x) => config_builder.min_size = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(MIN_SIZE);
+                    return Err(ArgParsingError::IncorrectCommandArgs(MIN_SIZE.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(MAX_SIZE) {
+            match utils::parse_size_value(value) {
+                Some(x) => config_builder.max_size = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(MAX_SIZE);
+                    return Err(ArgParsingError::IncorrectCommandArgs(MAX_SIZE.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(MAX_MEMORY) {
+            match utils::parse_usize_value(value, MIN_MAX_MEMORY, MAX_MAX_MEMORY) {
+                Some(x) => config_builder.max_memory = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(MAX_MEMORY);
+                    return Err(ArgParsingError::IncorrectCommandArgs(MAX_MEMORY.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(TIMEOUT) {
+            match utils::parse_usize_value(value, MIN_TIMEOUT, MAX_TIMEOUT) {
+                Some(x) => config_builder.timeout = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(TIMEOUT);
+                    return Err(ArgParsingError::IncorrectCommandArgs(TIMEOUT.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(FILE_TIMEOUT) {
+            match utils::parse_usize_value(value, MIN_TIMEOUT, MAX_TIMEOUT) {
+                Some(x) => config_builder.file_timeout = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(FILE_TIMEOUT);
+                    return Err(ArgParsingError::IncorrectCommandArgs(FILE_TIMEOUT.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(MAX_OPEN_FILES) {
+            match utils::parse_usize_value(value, MIN_MAX_OPEN_FILES, MAX_MAX_OPEN_FILES) {
+                Some(x) => config_builder.max_open_files = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(MAX_OPEN_FILES);
+                    return Err(ArgParsingError::IncorrectCommandArgs(MAX_OPEN_FILES.to_owned()))
+                }
+            }
+        } else if command.starts_with(STRICT_ENCODING) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(STRICT_ENCODING);
+                return Err(ArgParsingError::UnexpectedCommandArgs(STRICT_ENCODING.to_owned()))
+            }
+            config_builder.strict_encoding = Some(true);
+        } else if command.starts_with(STRICT) {
+            // Checked after '--strict-encoding', which starts with it
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(STRICT);
+                return Err(ArgParsingError::UnexpectedCommandArgs(STRICT.to_owned()))
+            }
+            config_builder.strict = Some(true);
+        } else if command.starts_with(ZERO_KEYWORDS) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(ZERO_KEYWORDS);
+                return Err(ArgParsingError::UnexpectedCommandArgs(ZERO_KEYWORDS.to_owned()))
+            }
+            config_builder.zero_keywords = Some(true);
+        } else if command.starts_with(TUI) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(TUI);
+                return Err(ArgParsingError::UnexpectedCommandArgs(TUI.to_owned()))
+            }
+            config_builder.tui = Some(true);
+        } else if command.starts_with(STATS) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(STATS);
+                return Err(ArgParsingError::UnexpectedCommandArgs(STATS.to_owned()))
+            }
+            config_builder.stats = Some(true);
+        } else if command.starts_with(EMBEDDED) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(EMBEDDED);
+                return Err(ArgParsingError::UnexpectedCommandArgs(EMBEDDED.to_owned()))
+            }
+            config_builder.embedded = Some(true);
+        } else if command.starts_with(IGNORE_TRAILING_EMPTY_LINE) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(IGNORE_TRAILING_EMPTY_LINE);
+                return Err(ArgParsingError::UnexpectedCommandArgs(IGNORE_TRAILING_EMPTY_LINE.to_owned()))
+            }
+            config_builder.ignore_trailing_empty_line = Some(true);
+        } else if command.starts_with(SUMMARY) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(SUMMARY);
+                return Err(ArgParsingError::UnexpectedCommandArgs(SUMMARY.to_owned()))
+            }
+            config_builder.summary = Some(true);
+        } else if let Some(value) = command.strip_prefix(STYLE) {
+            match Style::parse(value) {
+                Some(x) => config_builder.style = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(STYLE);
+                    return Err(ArgParsingError::IncorrectCommandArgs(STYLE.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(OVERVIEW) {
+            match Column::parse_list(value) {
+                Some(x) if !x.contains(&Column::Share) => config_builder.overview = Some(x),
+                _ => {
+                    message_printer::print_help_message_for_command(OVERVIEW);
+                    return Err(ArgParsingError::IncorrectCommandArgs(OVERVIEW.to_owned()))
+                }
+            }
+        } else if command.starts_with(SHOW_SKIPPED) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(SHOW_SKIPPED);
+                return Err(ArgParsingError::UnexpectedCommandArgs(SHOW_SKIPPED.to_owned()))
+            }
+            config_builder.show_skipped = Some(true);
+        } else if let Some(value) = command.strip_prefix(DISAMBIGUATE) {
+            match heuristics::parse_extensions(value) {
+                Some(x) => config_builder.disambiguate = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(DISAMBIGUATE);
+                    return Err(ArgParsingError::IncorrectCommandArgs(DISAMBIGUATE.to_owned()))
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(KEYWORD_SCOPE) {
+            match KeywordScope::parse(value) {
+                Some(x) => config_builder.keyword_scope = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(KEYWORD_SCOPE);
+                    return Err(ArgParsingError::IncorrectCommandArgs(KEYWORD_SCOPE.to_owned()))
+                }
+            }
+        } else if command.starts_with(TYPICAL_MARKERS) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(TYPICAL_MARKERS);
+                return Err(ArgParsingError::UnexpectedCommandArgs(TYPICAL_MARKERS.to_owned()))
+            }
+            config_builder.typical_markers = Some(true);
+        } else if command.starts_with(KEYWORD_DENSITY) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(KEYWORD_DENSITY);
+                return Err(ArgParsingError::UnexpectedCommandArgs(KEYWORD_DENSITY.to_owned()))
+            }
+            config_builder.keyword_density = Some(true);
+        } else if command.starts_with(KEYWORD_CATEGORIES) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(KEYWORD_CATEGORIES);
+                return Err(ArgParsingError::UnexpectedCommandArgs(KEYWORD_CATEGORIES.to_owned()))
+            }
+            config_builder.keyword_categories = Some(true);
+        } else if command.starts_with(SKIP_BUILD_OUTPUTS) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(SKIP_BUILD_OUTPUTS);
+                return Err(ArgParsingError::UnexpectedCommandArgs(SKIP_BUILD_OUTPUTS.to_owned()))
+            }
+            config_builder.skip_build_outputs = Some(true);
+        } else if command.starts_with(PREFER_SHEBANGS) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(PREFER_SHEBANGS);
+                return Err(ArgParsingError::UnexpectedCommandArgs(PREFER_SHEBANGS.to_owned()))
+            }
+            config_builder.prefer_shebangs = Some(true);
+        } else if command.starts_with(DETECT_SHEBANGS) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(DETECT_SHEBANGS);
+                return Err(ArgParsingError::UnexpectedCommandArgs(DETECT_SHEBANGS.to_owned()))
+            }
+            config_builder.detect_shebangs = Some(true);
+        } else if command.starts_with(IGNORE_EMPTY_FILES) {
+            if has_any_args(command) {
+                message_printer::print_help_message_for_command(IGNORE_EMPTY_FILES);
+                return Err(ArgParsingError::UnexpectedCommandArgs(IGNORE_EMPTY_FILES.to_owned()))
+            }
+            config_builder.ignore_empty_files = Some(true);
+        } else if let Some(scopes) = command.strip_prefix(LANG_SCOPE) {
+            match parse_lang_scopes(scopes) {
+                Some(x) => config_builder.lang_scopes = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(LANG_SCOPE);
+                    return Err(ArgParsingError::IncorrectCommandArgs(LANG_SCOPE.to_owned()))
+                }
+            }
+        } else if let Some(dir) = command.strip_prefix(LANGS_DIR) {
+            match utils::parse_canonical_dir(dir) {
+                Some(x) => config_builder.langs_dir = Some(x),
+                None => {
+                    message_printer::print_help_message_for_command(LANGS_DIR);
+                    return Err(ArgParsingError::IncorrectCommandArgs(LANGS_DIR.to_owned()))
+                }
+            }
         } else {
             return Err(ArgParsingError::UnrecognisedCommand(command.to_owned()));
         }
     }
 
-    print_warnings_for_commands_that_need_a_loaded_configuration(&config_name_to_save, &config_name_to_load, &log, &compare_level);
+    // The dirs are replaced by the clone of the remote repository, so the working dir is not needed
+    if config_builder.remote.is_some() {
+        if config_builder.dirs.is_some() {
+            return Err(ArgParsingError::DirsWithRemote);
+        }
+        config_builder.dirs = Some(Vec::new());
+    }
+    // The listed files take the place of the dirs, unless both are provided
+    if config_builder.files_from.is_some() && config_builder.dirs.is_none() {
+        config_builder.dirs = Some(Vec::new());
+    }
+
+    print_warnings_for_commands_that_need_a_loaded_configuration(&config_builder.config_name_to_save, &config_builder.config_name_to_load,
+            &config_builder.log, &config_builder.compare_level);
     
-    let mut config_builder = ConfigurationBuilder::new(dirs, exclude_dirs, languages_of_interest, threads, braces_as_code,
-        search_in_dotted, show_faulty_files, no_keywords, no_visual, log, compare_level,
-        config_name_to_save, config_name_to_load);
 
+    // The profile is used over the rest of the loaded configuration, or else of the default one, but not over the cmd
+    if let Some(name) = &profile {
+        match io_handler::parse_config_profile(config_builder.config_name_to_load.as_deref(), None, name) {
+            Ok(Some(x)) => {config_builder.add_missing_fields(x);},
+            _ => return Err(ArgParsingError::NonExistantProfile(name.to_owned()))
+        }
+    }
     if let Some(x) = custom_config {
         config_builder.add_missing_fields(x);
     }
@@ -273,6 +1502,7 @@ pub fn create_config_builder_from_args(line: &str) -> Result<ConfigurationBuilde
         }
     }
 
+    config_builder.validate()?;
     Ok(config_builder)
 }
 
@@ -293,6 +1523,37 @@ fn print_warnings_for_commands_that_need_a_loaded_configuration(config_name_to_s
     }
 }
 
+// The 'MEZURA_*' environment variables as options of the line, e.g. 'MEZURA_THREADS=4 4' -> "--threads 4 4", put after its dirs.
+// The options of the line are used over them, and the flags only when their value is 'yes' or 'true'. The variables
+// that are not options are ignored. Since they are options of the line, they are used over the configuration files.
+fn add_env_options(line: &str, vars: impl Iterator<Item = (String, String)>) -> String {
+    let (dirs_str, line_options) = line.split_at(line.find("--").unwrap_or(line.len()));
+    let mut line_names = line_options.split("--").filter_map(|x| x.split_whitespace().next()).collect::<HashSet<_>>();
+    if !dirs_str.trim().is_empty() {
+        line_names.insert(DIRS);
+    }
+
+    let options = message_printer::get_options();
+    let mut env_options = vars.filter_map(|(key, value)| {
+        let name = key.strip_prefix(ENV_PREFIX)?.to_ascii_lowercase().replace('_', "-");
+        let (name, takes_args) = *options.iter().find(|x| x.0 == name)?;
+        if line_names.contains(name) || ENV_IGNORED_OPTIONS.contains(&name) {
+            return None;
+        }
+        match value.trim() {
+            value if takes_args => Some(format!("--{} {}", name, value)),
+            value if value.eq_ignore_ascii_case("yes") || value.eq_ignore_ascii_case("true") => Some(format!("--{}", name)),
+            _ => None
+        }
+    }).collect::<Vec<_>>();
+    if env_options.is_empty() {
+        return line.to_owned();
+    }
+
+    env_options.sort();
+    format!("{} {} {}", dirs_str.trim(), env_options.join(" "), line_options).trim().to_owned()
+}
+
 fn has_any_args(command: &str) -> bool {
     command.split(' ').skip(1).filter_map(|x| utils::get_trimmed_if_not_empty(x)).count() != 0
 }
@@ -312,6 +1573,51 @@ fn parse_dirs(s: &str) -> Result<Vec<String>, ArgParsingError> {
     Ok(_dirs)
 }
 
+// A single word that git can resolve, e.g. 'main' or 'HEAD~3'. It can't start with '-', so that it is not taken as an option of git
+pub fn parse_revision(s: &str) -> Option<String> {
+    utils::get_trimmed_if_not_empty(s).filter(|x| !x.starts_with('-') && !x.contains(char::is_whitespace))
+}
+
+// Expects one or more rules separated by ';', each in the form "<language>: <dir1>, <dir2>"
+pub fn parse_lang_scopes(s: &str) -> Option<Vec<LangScope>> {
+    let mut scopes = Vec::new();
+    for rule in s.split(';').filter(|x| !x.trim().is_empty()) {
+        let mut parts = rule.splitn(2, ':');
+        let language = utils::get_trimmed_if_not_empty(parts.next()?)?.to_lowercase();
+        let dirs = utils::parse_paths_to_vec(parts.next()?);
+        if dirs.is_empty() {
+            return None;
+        }
+        scopes.push(LangScope {language, dirs});
+    }
+
+    if scopes.is_empty() {None} else {Some(scopes)}
+}
+
+// The baseline has to be an existing report, the comment file doesn't need to exist but it can't be a directory
+pub fn parse_pr_comment(s: &str) -> Option<PrComment> {
+    let paths = utils::parse_paths_to_vec(s);
+    if paths.len() != 2 || !Path::new(&paths[0]).is_file() || Path::new(&paths[1]).is_dir() {
+        return None;
+    }
+
+    Some(PrComment {baseline_report: paths[0].to_owned(), comment_file: paths[1].to_owned()})
+}
+
+// The report file doesn't need to exist, but it can't be a directory
+pub fn parse_report_file(s: &str) -> Option<String> {
+    let path = utils::parse_paths_to_vec(s);
+    if path.len() != 1 || Path::new(&path[0]).is_dir() {
+        return None;
+    }
+
+    Some(path[0].to_owned())
+}
+
+pub fn parse_notify_target(s: &str) -> Option<String> {
+    notifier::parse_target(s).map(|_| s.trim().to_owned())
+}
+
 fn parse_working_dir_as_target_dir() -> Result<Vec<String>, ArgParsingError> {
     if let Ok(path_buf) = std::env::current_dir() {
         if let Some(path_str) = path_buf.to_str() {
@@ -341,73 +1647,151 @@ fn convert_to_absolute(s: &str) -> String {
 }
 
 
-#[derive(Debug, PartialEq)]
-pub struct ConfigurationBuilder {
-    pub dirs:                     Option<Vec<String>>,
-    pub exclude_dirs:             Option<Vec<String>>,
-    pub languages_of_interest:    Option<Vec<String>>,
-    pub threads:                  Option<Threads>,
-    pub braces_as_code:           Option<bool>,
-    pub should_search_in_dotted:  Option<bool>,
-    pub should_show_faulty_files: Option<bool>,
-    pub no_keywords:              Option<bool>,
-    pub no_visual:                Option<bool>,
-    pub log:                      Option<LogOption>,
-    pub compare_level:            Option<usize>,
-    pub config_name_to_save:      Option<String>,
-    pub config_name_to_load:      Option<String>
-}
+// The options of the configuration, each of them None if it was not given. The ones of 'configuration_builder!' can be
+// saved in the configuration files and be filled in from them, so that a new option is merged and checked for with the rest.
+macro_rules! configuration_builder {
+    ($($field: ident: $type: ty),* $(,)?) => {
+        #[derive(Debug, PartialEq, Default)]
+        pub struct ConfigurationBuilder {
+            pub dirs:                Option<Vec<String>>,
+            pub config_name_to_save: Option<String>,
+            pub config_name_to_load: Option<String>,
+            // Never saved in configuration files
+            pub sign_key:            Option<String>,
+            // Never saved in configuration files
+            pub files_from:          Option<String>,
+            $(pub $field: Option<$type>),*
+        }
 
-impl ConfigurationBuilder {
-    pub fn new(dirs: Option<Vec<String>>, exclude_dirs: Option<Vec<String>>, languages_of_interest: Option<Vec<String>>, threads: Option<Threads>,
-             braces_as_code: Option<bool>, should_search_in_dotted: Option<bool>, should_show_faulty_files: Option<bool>, no_keywords: Option<bool>,
-             no_visual: Option<bool>, log: Option<LogOption>, compare_level: Option<usize>, config_name_to_save: Option<String>, config_name_to_load: Option<String>) 
-    -> ConfigurationBuilder 
-    {
-        ConfigurationBuilder {
-            dirs,
-            exclude_dirs,
-            languages_of_interest,
-            threads,
-            braces_as_code,
-            should_search_in_dotted,
-            should_show_faulty_files,
-            no_keywords,
-            no_visual,
-            log,
-            compare_level,
-            config_name_to_save,
-            config_name_to_load
-        }
-    }
-
-    pub fn add_missing_fields(&mut self, config: Self) -> &mut Self {
-        if self.dirs.is_none() {self.dirs = config.dirs};
-        if self.exclude_dirs.is_none() {self.exclude_dirs = config.exclude_dirs};
-        if self.languages_of_interest.is_none() {self.languages_of_interest = config.languages_of_interest};
-        if self.threads.is_none() {self.threads = config.threads};
-        if self.braces_as_code.is_none() {self.braces_as_code = config.braces_as_code};
-        if self.should_search_in_dotted.is_none() {self.should_search_in_dotted = config.should_search_in_dotted};
-        if self.should_show_faulty_files.is_none() {self.should_show_faulty_files = config.should_show_faulty_files};
-        if self.no_keywords.is_none() {self.no_keywords = config.no_keywords};
-        if self.no_visual.is_none() {self.no_visual = config.no_visual};
-        if self.compare_level.is_none() {self.compare_level = config.compare_level};
-        if self.log.is_none() {self.log = config.log};
-        self
-    }
-
-    pub fn has_missing_fields(&self) -> bool {
-        self.exclude_dirs.is_none() || self.languages_of_interest.is_none() ||
-        self.threads.is_none() || self.braces_as_code.is_none() || self.should_search_in_dotted.is_none() ||
-        self.should_show_faulty_files.is_none() || self.no_visual.is_none() || self.log.is_none() || self.compare_level.is_none()
-    } 
+        impl ConfigurationBuilder {
+            pub fn add_missing_fields(&mut self, config: Self) -> &mut Self {
+                if self.dirs.is_none() {self.dirs = config.dirs};
+                $(if self.$field.is_none() {self.$field = config.$field};)*
+                self
+            }
+
+            // The dirs are not checked, since the working dir is used without them
+            pub fn has_missing_fields(&self) -> bool {
+                $(self.$field.is_none())||*
+            }
+        }
+    }
+}
+
+configuration_builder! {
+    exclude_dirs:               Vec<String>,
+    languages_of_interest:      Vec<String>,
+    extension_filters:          Vec<String>,
+    threads:                    Threads,
+    braces_as_code:             bool,
+    should_search_in_dotted:    bool,
+    should_show_faulty_files:   bool,
+    no_keywords:                bool,
+    no_visual:                  bool,
+    log:                        LogOption,
+    compare_level:              usize,
+    restrict_to:                String,
+    langs_dir:                  String,
+    ignore_empty_files:         bool,
+    lang_scopes:                Vec<LangScope>,
+    regex_keywords:             bool,
+    report_file:                String,
+    sqlite:                     String,
+    event_log:                  String,
+    out:                        String,
+    force:                      bool,
+    match_case:                 bool,
+    duplicates:                 bool,
+    licenses:                   bool,
+    hygiene:                    bool,
+    indentation:                bool,
+    skip_generated:             bool,
+    skip_minified:              bool,
+    keyword_hotspots:           bool,
+    prescan:                    bool,
+    linguist_compare:           bool,
+    authors:                    bool,
+    estimate:                   bool,
+    salary:                     usize,
+    overhead:                   usize,
+    strict:                     bool,
+    zero_keywords:              bool,
+    top_keywords:               usize,
+    tui:                        bool,
+    stats:                      bool,
+    embedded:                   bool,
+    ignore_trailing_empty_line: bool,
+    summary:                    bool,
+    style:                      Style,
+    overview:                   Vec<Column>,
+    show_skipped:               bool,
+    disambiguate:               Vec<String>,
+    keyword_scope:              KeywordScope,
+    submodules:                 Submodules,
+    detect_shebangs:            bool,
+    prefer_shebangs:            bool,
+    large_file_threshold:       usize,
+    split_threshold:            usize,
+    max_line_length:            usize,
+    skip_build_outputs:         bool,
+    typical_markers:            bool,
+    keyword_density:            bool,
+    keyword_categories:         bool,
+    strict_encoding:            bool,
+    max_open_files:             usize,
+    max_memory:                 usize,
+    timeout:                    usize,
+    file_timeout:               usize,
+    follow_links:               bool,
+    hidden:                     bool,
+    git_tracked:                bool,
+    isolate_roots:              bool,
+    docs:                       bool,
+    max_depth:                  usize,
+    top:                        usize,
+    top_files:                  usize,
+    clones:                     usize,
+    histogram:                  bool,
+    list_only:                  bool,
+    timings:                    bool,
+    stream:                     bool,
+    logical_lines:              bool,
+    complexity:                 bool,
+    dir_breakdown:              usize,
+    path_style:                 PathStyle,
+    size_units:                 SizeUnits,
+    size_precision:             usize,
+    number_format:              NumberFormat,
+    output:                     OutputFormat,
+    color:                      ColorPolicy,
+    lang:                       String,
+    sort:                       SortOrder,
+    columns:                    Vec<Column>,
+    verbosity:                  Verbosity,
+    min_size:                   u64,
+    max_size:                   u64,
+    notify:                     String,
+    notify_template:            String,
+    badge:                      Badge,
+    export_treemap:             String,
+    pr_comment:                 PrComment,
+    fail_if:                    Vec<Budget>,
+    metrics:                    Vec<DerivedMetric>,
+    remote:                     String,
+    changed_since:              String,
+    co_occurrence:              Vec<String>,
+    todos:                      Vec<String>,
+    test_patterns:              Vec<String>
+}
 
+impl ConfigurationBuilder {
     pub fn build(&self) -> Configuration {
         Configuration {
             version: VERSION_ID,
             dirs: self.dirs.clone().unwrap(),
             exclude_dirs: (self.exclude_dirs).clone().unwrap_or_default(),
             languages_of_interest: (self.languages_of_interest).clone().unwrap_or_default(),
+            extension_filters: self.extension_filters.clone().unwrap_or_default(),
             threads: self.threads.clone().unwrap_or_else(Threads::default),
             braces_as_code: self.braces_as_code.unwrap_or(DEF_BRACES_AS_CODE),
             should_search_in_dotted: self.should_search_in_dotted.unwrap_or(DEF_SEARCH_IN_DOTTED),
@@ -417,7 +1801,249 @@ impl ConfigurationBuilder {
             log: self.log.clone().unwrap_or_else(LogOption::default),
             compare_level: self.compare_level.unwrap_or(DEF_COMPARE_LEVEL),
             config_name_to_save: self.config_name_to_save.clone(),
-            config_name_to_load: self.config_name_to_load.clone()
+            config_name_to_load: self.config_name_to_load.clone(),
+            restrict_to: self.restrict_to.clone(),
+            langs_dir: self.langs_dir.clone(),
+            ignore_empty_files: self.ignore_empty_files.unwrap_or(DEF_IGNORE_EMPTY_FILES),
+            lang_scopes: self.lang_scopes.clone().unwrap_or_default(),
+            regex_keywords: self.regex_keywords.unwrap_or(DEF_REGEX_KEYWORDS),
+            report_file: self.report_file.clone(),
+            sqlite: self.sqlite.clone(),
+            event_log: self.event_log.clone(),
+            out: self.out.clone(),
+            force: self.force.unwrap_or(DEF_FORCE),
+            match_case: self.match_case.unwrap_or(DEF_MATCH_CASE),
+            duplicates: self.duplicates.unwrap_or(DEF_DUPLICATES),
+            licenses: self.licenses.unwrap_or(DEF_LICENSES),
+            hygiene: self.hygiene.unwrap_or(DEF_HYGIENE),
+            indentation: self.indentation.unwrap_or(DEF_INDENTATION),
+            skip_generated: self.skip_generated.unwrap_or(DEF_SKIP_GENERATED),
+            skip_minified: self.skip_minified.unwrap_or(DEF_SKIP_MINIFIED),
+            keyword_hotspots: self.keyword_hotspots.unwrap_or(DEF_KEYWORD_HOTSPOTS),
+            prescan: self.prescan.unwrap_or(DEF_PRESCAN),
+            linguist_compare: self.linguist_compare.unwrap_or(DEF_LINGUIST_COMPARE),
+            authors: self.authors.unwrap_or(DEF_AUTHORS),
+            estimate: self.estimate.unwrap_or(DEF_ESTIMATE),
+            salary: self.salary.unwrap_or(DEF_SALARY),
+            overhead: self.overhead.unwrap_or(DEF_OVERHEAD),
+            strict: self.strict.unwrap_or(DEF_STRICT),
+            zero_keywords: self.zero_keywords.unwrap_or(DEF_ZERO_KEYWORDS),
+            top_keywords: self.top_keywords,
+            tui: self.tui.unwrap_or(DEF_TUI),
+            stats: self.stats.unwrap_or(DEF_STATS),
+            embedded: self.embedded.unwrap_or(DEF_EMBEDDED),
+            ignore_trailing_empty_line: self.ignore_trailing_empty_line.unwrap_or(DEF_IGNORE_TRAILING_EMPTY_LINE),
+            summary: self.summary.unwrap_or(DEF_SUMMARY),
+            style: self.style.unwrap_or(DEF_STYLE),
+            overview: self.overview.clone().unwrap_or_else(|| DEF_OVERVIEW.to_vec()),
+            show_skipped: self.show_skipped.unwrap_or(DEF_SHOW_SKIPPED),
+            disambiguate: self.disambiguate.clone().unwrap_or_default(),
+            keyword_scope: self.keyword_scope,
+            submodules: self.submodules,
+            sign_key: self.sign_key.clone(),
+            files_from: self.files_from.clone(),
+            detect_shebangs: self.detect_shebangs.unwrap_or(DEF_DETECT_SHEBANGS),
+            prefer_shebangs: self.prefer_shebangs.unwrap_or(DEF_PREFER_SHEBANGS),
+            large_file_threshold: self.large_file_threshold.unwrap_or(DEF_LARGE_FILE_THRESHOLD),
+            split_threshold: self.split_threshold.unwrap_or(DEF_SPLIT_THRESHOLD),
+            max_line_length: self.max_line_length.unwrap_or(DEF_MAX_LINE_LENGTH),
+            skip_build_outputs: self.skip_build_outputs.unwrap_or(DEF_SKIP_BUILD_OUTPUTS),
+            typical_markers: self.typical_markers.unwrap_or(DEF_TYPICAL_MARKERS),
+            keyword_density: self.keyword_density.unwrap_or(DEF_KEYWORD_DENSITY),
+            keyword_categories: self.keyword_categories.unwrap_or(DEF_KEYWORD_CATEGORIES),
+            strict_encoding: self.strict_encoding.unwrap_or(DEF_STRICT_ENCODING),
+            max_open_files: self.max_open_files.unwrap_or(DEF_MAX_OPEN_FILES),
+            max_memory: self.max_memory.unwrap_or(DEF_MAX_MEMORY),
+            timeout: self.timeout.unwrap_or(DEF_TIMEOUT),
+            file_timeout: self.file_timeout.unwrap_or(DEF_FILE_TIMEOUT),
+            follow_links: self.follow_links.unwrap_or(DEF_FOLLOW_LINKS),
+            hidden: self.hidden.unwrap_or(DEF_HIDDEN),
+            git_tracked: self.git_tracked.unwrap_or(DEF_GIT_TRACKED),
+            isolate_roots: self.isolate_roots.unwrap_or(DEF_ISOLATE_ROOTS),
+            docs: self.docs.unwrap_or(DEF_DOCS),
+            max_depth: self.max_depth,
+            top: self.top,
+            top_files: self.top_files,
+            clones: self.clones,
+            histogram: self.histogram.unwrap_or(DEF_HISTOGRAM),
+            list_only: self.list_only.unwrap_or(DEF_LIST_ONLY),
+            timings: self.timings.unwrap_or(DEF_TIMINGS),
+            stream: self.stream.unwrap_or(DEF_STREAM),
+            logical_lines: self.logical_lines.unwrap_or(DEF_LOGICAL_LINES),
+            complexity: self.complexity.unwrap_or(DEF_COMPLEXITY),
+            dir_breakdown: self.dir_breakdown,
+            path_style: self.path_style.unwrap_or(DEF_PATH_STYLE),
+            size_units: self.size_units.unwrap_or(DEF_SIZE_UNITS),
+            size_precision: self.size_precision.unwrap_or(DEF_SIZE_PRECISION),
+            number_format: self.number_format.unwrap_or(DEF_NUMBER_FORMAT),
+            output: self.output.unwrap_or(DEF_OUTPUT),
+            color: self.color.unwrap_or(DEF_COLOR),
+            lang: self.lang.clone().unwrap_or_else(|| DEF_LANG.to_owned()),
+            sort: self.sort,
+            columns: self.columns.clone(),
+            verbosity: self.verbosity.unwrap_or(DEF_VERBOSITY),
+            min_size: self.min_size,
+            max_size: self.max_size,
+            notify: self.notify.clone(),
+            notify_template: self.notify_template.clone(),
+            badge: self.badge.clone(),
+            export_treemap: self.export_treemap.clone(),
+            pr_comment: self.pr_comment.clone(),
+            fail_if: self.fail_if.clone().unwrap_or_default(),
+            metrics: self.metrics.clone().unwrap_or_default(),
+            remote: self.remote.clone(),
+            changed_since: self.changed_since.clone(),
+            co_occurrence: self.co_occurrence.clone(),
+            todos: self.todos.clone(),
+            test_patterns: self.test_patterns.clone()
+        }
+    }
+
+    // The same as 'build', but with the checks of the command line instead of panicking on the missing dirs,
+    // for the users of the library that fill in the builder themselves
+    pub fn try_build(&self) -> Result<Configuration, ArgParsingError> {
+        self.validate()?;
+        Ok(self.build())
+    }
+
+    pub fn validate(&self) -> Result<(), ArgParsingError> {
+        // Empty with '--files-from'
+        let dirs = self.dirs.as_ref().ok_or(ArgParsingError::MissingTargetDirs)?;
+
+        if let Some(threads) = &self.threads {
+            if threads.producers < MIN_PRODUCERS_VALUE || threads.consumers < MIN_CONSUMERS_VALUE {
+                return Err(ArgParsingError::InvalidThreads(threads.producers, threads.consumers));
+            }
+        }
+
+        if let Some(root) = &self.restrict_to {
+            if let Some(dir) = dirs.iter().find(|x| !utils::is_path_inside_root(Path::new(x), root)) {
+                return Err(ArgParsingError::PathOutsideRestrictedRoot(dir.to_owned(), root.to_owned()));
+            }
+        }
+
+        if let (Some(min_size), Some(max_size)) = (self.min_size, self.max_size) {
+            if min_size > max_size {
+                return Err(ArgParsingError::InvalidSizeRange(min_size, max_size));
+            }
+        }
+        Ok(())
+    }
+}
+
+// Every option of the configuration is set, so that it can be written with all its values resolved.
+// The empty lists are left unset, since they are the defaults and an empty option is not valid in a file.
+impl From<&Configuration> for ConfigurationBuilder {
+    fn from(config: &Configuration) -> Self {
+        ConfigurationBuilder {
+            dirs: Some(config.dirs.clone()),
+            exclude_dirs: Some(config.exclude_dirs.clone()).filter(|x| !x.is_empty()),
+            languages_of_interest: Some(config.languages_of_interest.clone()).filter(|x| !x.is_empty()),
+            extension_filters: Some(config.extension_filters.clone()).filter(|x| !x.is_empty()),
+            threads: Some(config.threads.clone()),
+            braces_as_code: Some(config.braces_as_code),
+            should_search_in_dotted: Some(config.should_search_in_dotted),
+            should_show_faulty_files: Some(config.should_show_faulty_files),
+            no_keywords: Some(config.no_keywords),
+            no_visual: Some(config.no_visual),
+            log: Some(config.log.clone()),
+            compare_level: Some(config.compare_level),
+            config_name_to_save: None,
+            config_name_to_load: None,
+            restrict_to: config.restrict_to.clone(),
+            langs_dir: config.langs_dir.clone(),
+            ignore_empty_files: Some(config.ignore_empty_files),
+            lang_scopes: Some(config.lang_scopes.clone()).filter(|x| !x.is_empty()),
+            regex_keywords: Some(config.regex_keywords),
+            report_file: config.report_file.clone(),
+            sqlite: config.sqlite.clone(),
+            event_log: config.event_log.clone(),
+            out: config.out.clone(),
+            force: Some(config.force),
+            match_case: Some(config.match_case),
+            duplicates: Some(config.duplicates),
+            licenses: Some(config.licenses),
+            hygiene: Some(config.hygiene),
+            indentation: Some(config.indentation),
+            skip_generated: Some(config.skip_generated),
+            skip_minified: Some(config.skip_minified),
+            keyword_hotspots: Some(config.keyword_hotspots),
+            prescan: Some(config.prescan),
+            linguist_compare: Some(config.linguist_compare),
+            authors: Some(config.authors),
+            estimate: Some(config.estimate),
+            salary: Some(config.salary),
+            overhead: Some(config.overhead),
+            strict: Some(config.strict),
+            zero_keywords: Some(config.zero_keywords),
+            top_keywords: config.top_keywords,
+            tui: Some(config.tui),
+            stats: Some(config.stats),
+            embedded: Some(config.embedded),
+            ignore_trailing_empty_line: Some(config.ignore_trailing_empty_line),
+            summary: Some(config.summary),
+            style: Some(config.style),
+            overview: Some(config.overview.clone()),
+            show_skipped: Some(config.show_skipped),
+            disambiguate: Some(config.disambiguate.clone()).filter(|x| !x.is_empty()),
+            keyword_scope: config.keyword_scope,
+            submodules: config.submodules,
+            sign_key: config.sign_key.clone(),
+            files_from: config.files_from.clone(),
+            detect_shebangs: Some(config.detect_shebangs),
+            prefer_shebangs: Some(config.prefer_shebangs),
+            large_file_threshold: Some(config.large_file_threshold),
+            split_threshold: Some(config.split_threshold),
+            max_line_length: Some(config.max_line_length),
+            skip_build_outputs: Some(config.skip_build_outputs),
+            typical_markers: Some(config.typical_markers),
+            keyword_density: Some(config.keyword_density),
+            keyword_categories: Some(config.keyword_categories),
+            strict_encoding: Some(config.strict_encoding),
+            max_open_files: Some(config.max_open_files),
+            max_memory: Some(config.max_memory),
+            timeout: Some(config.timeout),
+            file_timeout: Some(config.file_timeout),
+            follow_links: Some(config.follow_links),
+            hidden: Some(config.hidden),
+            git_tracked: Some(config.git_tracked),
+            isolate_roots: Some(config.isolate_roots),
+            docs: Some(config.docs),
+            max_depth: config.max_depth,
+            top: config.top,
+            top_files: config.top_files,
+            clones: config.clones,
+            histogram: Some(config.histogram),
+            list_only: Some(config.list_only),
+            timings: Some(config.timings),
+            stream: Some(config.stream),
+            logical_lines: Some(config.logical_lines),
+            complexity: Some(config.complexity),
+            dir_breakdown: config.dir_breakdown,
+            path_style: Some(config.path_style),
+            size_units: Some(config.size_units),
+            size_precision: Some(config.size_precision),
+            number_format: Some(config.number_format),
+            output: Some(config.output),
+            color: Some(config.color),
+            lang: Some(config.lang.clone()),
+            sort: config.sort,
+            columns: config.columns.clone(),
+            verbosity: Some(config.verbosity),
+            min_size: config.min_size,
+            max_size: config.max_size,
+            notify: config.notify.clone(),
+            notify_template: config.notify_template.clone(),
+            badge: config.badge.clone(),
+            export_treemap: config.export_treemap.clone(),
+            pr_comment: config.pr_comment.clone(),
+            fail_if: Some(config.fail_if.clone()).filter(|x| !x.is_empty()),
+            metrics: Some(config.metrics.clone()).filter(|x| !x.is_empty()),
+            remote: config.remote.clone(),
+            changed_since: config.changed_since.clone(),
+            co_occurrence: config.co_occurrence.clone(),
+            todos: config.todos.clone(),
+            test_patterns: config.test_patterns.clone()
         }
     }
 }
@@ -429,6 +2055,7 @@ impl Configuration {
             dirs,
             exclude_dirs: Vec::new(),
             languages_of_interest: Vec::new(),
+            extension_filters: Vec::new(),
             threads: Threads::default(),
             braces_as_code: DEF_BRACES_AS_CODE,
             should_search_in_dotted: DEF_SEARCH_IN_DOTTED,
@@ -438,8 +2065,140 @@ impl Configuration {
             log: LogOption::default(),
             compare_level: DEF_COMPARE_LEVEL,
             config_name_to_save: None,
-            config_name_to_load: None
+            config_name_to_load: None,
+            restrict_to: None,
+            langs_dir: None,
+            ignore_empty_files: DEF_IGNORE_EMPTY_FILES,
+            lang_scopes: Vec::new(),
+            regex_keywords: DEF_REGEX_KEYWORDS,
+            report_file: None,
+            sqlite: None,
+            event_log: None,
+            out: None,
+            force: DEF_FORCE,
+            match_case: DEF_MATCH_CASE,
+            duplicates: DEF_DUPLICATES,
+            licenses: DEF_LICENSES,
+            hygiene: DEF_HYGIENE,
+            indentation: DEF_INDENTATION,
+            skip_generated: DEF_SKIP_GENERATED,
+            skip_minified: DEF_SKIP_MINIFIED,
+            keyword_hotspots: DEF_KEYWORD_HOTSPOTS,
+            prescan: DEF_PRESCAN,
+            linguist_compare: DEF_LINGUIST_COMPARE,
+            authors: DEF_AUTHORS,
+            estimate: DEF_ESTIMATE,
+            salary: DEF_SALARY,
+            overhead: DEF_OVERHEAD,
+            strict: DEF_STRICT,
+            zero_keywords: DEF_ZERO_KEYWORDS,
+            top_keywords: None,
+            tui: DEF_TUI,
+            stats: DEF_STATS,
+            embedded: DEF_EMBEDDED,
+            ignore_trailing_empty_line: DEF_IGNORE_TRAILING_EMPTY_LINE,
+            summary: DEF_SUMMARY,
+            style: DEF_STYLE,
+            overview: DEF_OVERVIEW.to_vec(),
+            show_skipped: DEF_SHOW_SKIPPED,
+            disambiguate: Vec::new(),
+            keyword_scope: None,
+            submodules: None,
+            sign_key: None,
+            files_from: None,
+            detect_shebangs: DEF_DETECT_SHEBANGS,
+            prefer_shebangs: DEF_PREFER_SHEBANGS,
+            large_file_threshold: DEF_LARGE_FILE_THRESHOLD,
+            split_threshold: DEF_SPLIT_THRESHOLD,
+            max_line_length: DEF_MAX_LINE_LENGTH,
+            skip_build_outputs: DEF_SKIP_BUILD_OUTPUTS,
+            typical_markers: DEF_TYPICAL_MARKERS,
+            keyword_density: DEF_KEYWORD_DENSITY,
+            keyword_categories: DEF_KEYWORD_CATEGORIES,
+            strict_encoding: DEF_STRICT_ENCODING,
+            max_open_files: DEF_MAX_OPEN_FILES,
+            max_memory: DEF_MAX_MEMORY,
+            timeout: DEF_TIMEOUT,
+            file_timeout: DEF_FILE_TIMEOUT,
+            follow_links: DEF_FOLLOW_LINKS,
+            hidden: DEF_HIDDEN,
+            git_tracked: DEF_GIT_TRACKED,
+            isolate_roots: DEF_ISOLATE_ROOTS,
+            docs: DEF_DOCS,
+            max_depth: None,
+            top: None,
+            top_files: None,
+            clones: None,
+            histogram: DEF_HISTOGRAM,
+            list_only: DEF_LIST_ONLY,
+            timings: DEF_TIMINGS,
+            stream: DEF_STREAM,
+            logical_lines: DEF_LOGICAL_LINES,
+            complexity: DEF_COMPLEXITY,
+            dir_breakdown: None,
+            path_style: DEF_PATH_STYLE,
+            size_units: DEF_SIZE_UNITS,
+            size_precision: DEF_SIZE_PRECISION,
+            number_format: DEF_NUMBER_FORMAT,
+            output: DEF_OUTPUT,
+            color: DEF_COLOR,
+            lang: DEF_LANG.to_owned(),
+            sort: None,
+            columns: None,
+            verbosity: DEF_VERBOSITY,
+            min_size: None,
+            max_size: None,
+            notify: None,
+            notify_template: None,
+            badge: None,
+            export_treemap: None,
+            pr_comment: None,
+            fail_if: Vec::new(),
+            metrics: Vec::new(),
+            remote: None,
+            changed_since: None,
+            co_occurrence: None,
+            todos: None,
+            test_patterns: None
+        }
+    }
+
+    // Only the text has colors, since the other formats are meant to be read by other programs
+    pub fn uses_colors(&self) -> bool {
+        if self.output != OutputFormat::Text {
+            return false;
         }
+        match self.color {
+            ColorPolicy::Always => true,
+            ColorPolicy::Never => false,
+            ColorPolicy::Auto => std::env::var_os("NO_COLOR").is_none_or(|x| x.is_empty()) && std::io::stdout().is_terminal() && self.out.is_none()
+        }
+    }
+
+    pub fn shows_column(&self, column: Column) -> bool {
+        self.columns.as_ref().is_none_or(|x| x.contains(&column))
+    }
+
+    // How deep the stats of the directories are kept, if they are needed for '--dir-breakdown' or '--export-treemap'
+    pub fn breakdown_depth(&self) -> Option<usize> {
+        self.dir_breakdown.or(self.export_treemap.as_ref().map(|_| DEF_TREEMAP_DEPTH))
+    }
+
+    // Whether the branch keywords are counted while parsing, since they are shown with '--complexity'
+    // or compared by '--fail-if' and '--metrics'
+    pub fn counts_complexity(&self) -> bool {
+        self.complexity || self.fail_if.iter().any(|x| x.metric.trim_start_matches("total_") == "complexity")
+                || self.metrics.iter().any(|x| x.definition.to_lowercase().contains("complexity"))
+    }
+
+    // Whether the progress messages (banners, counts, saved files etc) are printed
+    pub fn is_quiet(&self) -> bool {
+        self.verbosity == Verbosity::Quiet
+    }
+
+    // Whether every parsed file and the timings of the threads are printed
+    pub fn is_verbose(&self) -> bool {
+        self.verbosity == Verbosity::Verbose
     }
 
     //Setters used mainly in tests, for the ability to chain many config changes
@@ -460,6 +2219,11 @@ impl Configuration {
         self
     }
 
+    pub fn set_extension_filters(&mut self, extension_filters: Vec<String>) -> &mut Self {
+        self.extension_filters = extension_filters;
+        self
+    }
+
     pub fn set_threads(&mut self, producers: usize, consumers: usize) -> &mut Self {
         self.threads = Threads::new(producers, consumers);
         self
@@ -494,6 +2258,496 @@ impl Configuration {
         self.log = log;
         self
     }
+
+    pub fn set_restrict_to(&mut self, restrict_to: Option<String>) -> &mut Self {
+        self.restrict_to = restrict_to;
+        self
+    }
+
+    pub fn set_langs_dir(&mut self, langs_dir: Option<String>) -> &mut Self {
+        self.langs_dir = langs_dir;
+        self
+    }
+
+    pub fn set_ignore_empty_files(&mut self, ignore_empty_files: bool) -> &mut Self {
+        self.ignore_empty_files = ignore_empty_files;
+        self
+    }
+
+    pub fn set_lang_scopes(&mut self, lang_scopes: Vec<LangScope>) -> &mut Self {
+        self.lang_scopes = lang_scopes;
+        self
+    }
+
+    pub fn set_regex_keywords(&mut self, regex_keywords: bool) -> &mut Self {
+        self.regex_keywords = regex_keywords;
+        self
+    }
+
+    pub fn set_detect_shebangs(&mut self, detect_shebangs: bool) -> &mut Self {
+        self.detect_shebangs = detect_shebangs;
+        self
+    }
+
+    pub fn set_prefer_shebangs(&mut self, prefer_shebangs: bool) -> &mut Self {
+        self.prefer_shebangs = prefer_shebangs;
+        self
+    }
+
+    pub fn set_max_line_length(&mut self, max_line_length: usize) -> &mut Self {
+        self.max_line_length = max_line_length;
+        self
+    }
+
+    pub fn set_large_file_threshold(&mut self, large_file_threshold: usize) -> &mut Self {
+        self.large_file_threshold = large_file_threshold;
+        self
+    }
+
+    pub fn set_split_threshold(&mut self, split_threshold: usize) -> &mut Self {
+        self.split_threshold = split_threshold;
+        self
+    }
+
+    pub fn set_skip_build_outputs(&mut self, skip_build_outputs: bool) -> &mut Self {
+        self.skip_build_outputs = skip_build_outputs;
+        self
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) -> &mut Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn set_top(&mut self, top: Option<usize>) -> &mut Self {
+        self.top = top;
+        self
+    }
+
+    pub fn set_clones(&mut self, clones: Option<usize>) -> &mut Self {
+        self.clones = clones;
+        self
+    }
+
+    pub fn set_top_files(&mut self, top_files: Option<usize>) -> &mut Self {
+        self.top_files = top_files;
+        self
+    }
+
+    pub fn set_dir_breakdown(&mut self, dir_breakdown: Option<usize>) -> &mut Self {
+        self.dir_breakdown = dir_breakdown;
+        self
+    }
+
+    pub fn set_path_style(&mut self, path_style: PathStyle) -> &mut Self {
+        self.path_style = path_style;
+        self
+    }
+
+    pub fn set_size_units(&mut self, size_units: SizeUnits) -> &mut Self {
+        self.size_units = size_units;
+        self
+    }
+
+    pub fn set_size_precision(&mut self, size_precision: usize) -> &mut Self {
+        self.size_precision = size_precision;
+        self
+    }
+
+    pub fn set_number_format(&mut self, number_format: NumberFormat) -> &mut Self {
+        self.number_format = number_format;
+        self
+    }
+
+    pub fn set_output(&mut self, output: OutputFormat) -> &mut Self {
+        self.output = output;
+        self
+    }
+
+    pub fn set_color(&mut self, color: ColorPolicy) -> &mut Self {
+        self.color = color;
+        self
+    }
+
+    pub fn set_lang(&mut self, lang: &str) -> &mut Self {
+        self.lang = lang.to_owned();
+        self
+    }
+
+    pub fn set_min_size(&mut self, min_size: Option<u64>) -> &mut Self {
+        self.min_size = min_size;
+        self
+    }
+
+    pub fn set_max_size(&mut self, max_size: Option<u64>) -> &mut Self {
+        self.max_size = max_size;
+        self
+    }
+
+    pub fn set_remote(&mut self, remote: Option<String>) -> &mut Self {
+        self.remote = remote;
+        self
+    }
+
+    pub fn set_changed_since(&mut self, changed_since: Option<String>) -> &mut Self {
+        self.changed_since = changed_since;
+        self
+    }
+
+    pub fn set_notify(&mut self, notify: Option<String>, notify_template: Option<String>) -> &mut Self {
+        self.notify = notify;
+        self.notify_template = notify_template;
+        self
+    }
+
+    pub fn set_badge(&mut self, badge: Option<Badge>) -> &mut Self {
+        self.badge = badge;
+        self
+    }
+
+    pub fn set_export_treemap(&mut self, export_treemap: Option<String>) -> &mut Self {
+        self.export_treemap = export_treemap;
+        self
+    }
+
+    pub fn set_verbosity(&mut self, verbosity: Verbosity) -> &mut Self {
+        self.verbosity = verbosity;
+        self
+    }
+
+    pub fn set_sort(&mut self, sort: Option<SortOrder>) -> &mut Self {
+        self.sort = sort;
+        self
+    }
+
+    pub fn set_columns(&mut self, columns: Option<Vec<Column>>) -> &mut Self {
+        self.columns = columns;
+        self
+    }
+
+    pub fn set_fail_if(&mut self, fail_if: Vec<Budget>) -> &mut Self {
+        self.fail_if = fail_if;
+        self
+    }
+
+    pub fn set_metrics(&mut self, metrics: Vec<DerivedMetric>) -> &mut Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn set_pr_comment(&mut self, baseline_report: &str, comment_file: &str) -> &mut Self {
+        self.pr_comment = Some(PrComment {baseline_report: baseline_report.to_owned(), comment_file: comment_file.to_owned()});
+        self
+    }
+
+    pub fn set_docs(&mut self, docs: bool) -> &mut Self {
+        self.docs = docs;
+        self
+    }
+
+    pub fn set_histogram(&mut self, histogram: bool) -> &mut Self {
+        self.histogram = histogram;
+        self
+    }
+
+    pub fn set_list_only(&mut self, list_only: bool) -> &mut Self {
+        self.list_only = list_only;
+        self
+    }
+
+    pub fn set_timings(&mut self, timings: bool) -> &mut Self {
+        self.timings = timings;
+        self
+    }
+
+    pub fn set_stream(&mut self, stream: bool) -> &mut Self {
+        self.stream = stream;
+        self
+    }
+
+    pub fn set_logical_lines(&mut self, logical_lines: bool) -> &mut Self {
+        self.logical_lines = logical_lines;
+        self
+    }
+
+    pub fn set_complexity(&mut self, complexity: bool) -> &mut Self {
+        self.complexity = complexity;
+        self
+    }
+
+    pub fn set_isolate_roots(&mut self, isolate_roots: bool) -> &mut Self {
+        self.isolate_roots = isolate_roots;
+        self
+    }
+
+    pub fn set_git_tracked(&mut self, git_tracked: bool) -> &mut Self {
+        self.git_tracked = git_tracked;
+        self
+    }
+
+    pub fn set_follow_links(&mut self, follow_links: bool) -> &mut Self {
+        self.follow_links = follow_links;
+        self
+    }
+
+    pub fn set_hidden(&mut self, hidden: bool) -> &mut Self {
+        self.hidden = hidden;
+        self
+    }
+
+    pub fn set_max_memory(&mut self, max_memory: usize) -> &mut Self {
+        self.max_memory = max_memory;
+        self
+    }
+
+    pub fn set_timeout(&mut self, timeout: usize) -> &mut Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn set_file_timeout(&mut self, file_timeout: usize) -> &mut Self {
+        self.file_timeout = file_timeout;
+        self
+    }
+
+    pub fn set_max_open_files(&mut self, max_open_files: usize) -> &mut Self {
+        self.max_open_files = max_open_files;
+        self
+    }
+
+    pub fn set_strict_encoding(&mut self, strict_encoding: bool) -> &mut Self {
+        self.strict_encoding = strict_encoding;
+        self
+    }
+
+    pub fn set_typical_markers(&mut self, typical_markers: bool) -> &mut Self {
+        self.typical_markers = typical_markers;
+        self
+    }
+
+    pub fn set_keyword_density(&mut self, keyword_density: bool) -> &mut Self {
+        self.keyword_density = keyword_density;
+        self
+    }
+
+    pub fn set_keyword_categories(&mut self, keyword_categories: bool) -> &mut Self {
+        self.keyword_categories = keyword_categories;
+        self
+    }
+
+    pub fn set_co_occurrence(&mut self, co_occurrence: Option<Vec<String>>) -> &mut Self {
+        self.co_occurrence = co_occurrence;
+        self
+    }
+
+    pub fn set_todos(&mut self, todos: Option<Vec<String>>) -> &mut Self {
+        self.todos = todos;
+        self
+    }
+
+    // The markers of '--todos', with the default ones in place of an empty list
+    pub fn get_todo_markers(&self) -> Vec<String> {
+        match &self.todos {
+            Some(x) if !x.is_empty() => x.clone(),
+            Some(_) => DEF_TODO_MARKERS.iter().map(|x| x.to_string()).collect(),
+            None => Vec::new()
+        }
+    }
+
+    pub fn set_test_patterns(&mut self, test_patterns: Option<Vec<String>>) -> &mut Self {
+        self.test_patterns = test_patterns;
+        self
+    }
+
+    // The patterns of '--test-pattern', with the default ones in place of an empty list
+    pub fn get_test_patterns(&self) -> Vec<String> {
+        match &self.test_patterns {
+            Some(x) if !x.is_empty() => x.clone(),
+            Some(_) => DEF_TEST_PATTERNS.iter().map(|x| x.to_string()).collect(),
+            None => Vec::new()
+        }
+    }
+
+    pub fn set_sqlite(&mut self, sqlite: Option<String>) -> &mut Self {
+        self.sqlite = sqlite;
+        self
+    }
+
+    pub fn set_out(&mut self, out: Option<String>, force: bool) -> &mut Self {
+        self.out = out;
+        self.force = force;
+        self
+    }
+
+    pub fn set_event_log(&mut self, event_log: Option<String>) -> &mut Self {
+        self.event_log = event_log;
+        self
+    }
+
+    pub fn set_indentation(&mut self, indentation: bool) -> &mut Self {
+        self.indentation = indentation;
+        self
+    }
+
+    pub fn set_skip_generated(&mut self, skip_generated: bool) -> &mut Self {
+        self.skip_generated = skip_generated;
+        self
+    }
+
+    pub fn set_skip_minified(&mut self, skip_minified: bool) -> &mut Self {
+        self.skip_minified = skip_minified;
+        self
+    }
+
+    pub fn set_keyword_hotspots(&mut self, keyword_hotspots: bool) -> &mut Self {
+        self.keyword_hotspots = keyword_hotspots;
+        self
+    }
+
+    pub fn set_prescan(&mut self, prescan: bool) -> &mut Self {
+        self.prescan = prescan;
+        self
+    }
+
+    pub fn set_linguist_compare(&mut self, linguist_compare: bool) -> &mut Self {
+        self.linguist_compare = linguist_compare;
+        self
+    }
+
+    pub fn set_authors(&mut self, authors: bool) -> &mut Self {
+        self.authors = authors;
+        self
+    }
+
+    pub fn set_estimate(&mut self, estimate: bool) -> &mut Self {
+        self.estimate = estimate;
+        self
+    }
+
+    pub fn set_salary(&mut self, salary: usize) -> &mut Self {
+        self.salary = salary;
+        self
+    }
+
+    pub fn set_overhead(&mut self, overhead: usize) -> &mut Self {
+        self.overhead = overhead;
+        self
+    }
+
+    pub fn set_strict(&mut self, strict: bool) -> &mut Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn set_zero_keywords(&mut self, zero_keywords: bool) -> &mut Self {
+        self.zero_keywords = zero_keywords;
+        self
+    }
+
+    pub fn set_top_keywords(&mut self, top_keywords: Option<usize>) -> &mut Self {
+        self.top_keywords = top_keywords;
+        self
+    }
+
+    pub fn set_tui(&mut self, tui: bool) -> &mut Self {
+        self.tui = tui;
+        self
+    }
+
+    pub fn set_stats(&mut self, stats: bool) -> &mut Self {
+        self.stats = stats;
+        self
+    }
+
+    pub fn set_embedded(&mut self, embedded: bool) -> &mut Self {
+        self.embedded = embedded;
+        self
+    }
+
+    pub fn set_ignore_trailing_empty_line(&mut self, ignore_trailing_empty_line: bool) -> &mut Self {
+        self.ignore_trailing_empty_line = ignore_trailing_empty_line;
+        self
+    }
+
+    pub fn set_summary(&mut self, summary: bool) -> &mut Self {
+        self.summary = summary;
+        self
+    }
+
+    pub fn set_style(&mut self, style: Style) -> &mut Self {
+        self.style = style;
+        self
+    }
+
+    pub fn set_overview(&mut self, overview: Vec<Column>) -> &mut Self {
+        self.overview = overview;
+        self
+    }
+
+    pub fn set_show_skipped(&mut self, show_skipped: bool) -> &mut Self {
+        self.show_skipped = show_skipped;
+        self
+    }
+
+    pub fn set_disambiguate(&mut self, disambiguate: Vec<String>) -> &mut Self {
+        self.disambiguate = disambiguate;
+        self
+    }
+
+    pub fn set_keyword_scope(&mut self, keyword_scope: Option<KeywordScope>) -> &mut Self {
+        self.keyword_scope = keyword_scope;
+        self
+    }
+
+    pub fn set_submodules(&mut self, submodules: Option<Submodules>) -> &mut Self {
+        self.submodules = submodules;
+        self
+    }
+
+    pub fn set_hygiene(&mut self, hygiene: bool) -> &mut Self {
+        self.hygiene = hygiene;
+        self
+    }
+
+    pub fn set_licenses(&mut self, licenses: bool) -> &mut Self {
+        self.licenses = licenses;
+        self
+    }
+
+    pub fn set_duplicates(&mut self, duplicates: bool) -> &mut Self {
+        self.duplicates = duplicates;
+        self
+    }
+
+    pub fn set_match_case(&mut self, match_case: bool) -> &mut Self {
+        self.match_case = match_case;
+        self
+    }
+
+    pub fn set_report(&mut self, report_file: Option<String>, sign_key: Option<String>) -> &mut Self {
+        self.report_file = report_file;
+        self.sign_key = sign_key;
+        self
+    }
+
+    pub fn set_files_from(&mut self, files_from: Option<String>) -> &mut Self {
+        self.files_from = files_from;
+        self
+    }
+}
+
+impl LangScope {
+    pub fn new(language: &str, dirs: &[&str]) -> Self {
+        LangScope {
+            language: language.to_lowercase(),
+            dirs: dirs.iter().map(|x| x.to_string()).collect()
+        }
+    }
+
+    // 'relative_dir' is the dir of the file, relative to the searched dir that it was found in
+    pub fn allows(&self, relative_dir: &str) -> bool {
+        self.dirs.iter().any(|x| utils::is_dir_matching_pattern(relative_dir, x))
+    }
 }
 
 impl Threads {
@@ -511,25 +2765,252 @@ impl Threads {
         }
     }
 
+    // The values above the maximums are lowered to them, with a warning of the run
+    pub fn clamped(producers: usize, consumers: usize) -> Self {
+        let threads = Threads::new(producers.min(MAX_PRODUCERS_VALUE), consumers.min(MAX_CONSUMERS_VALUE));
+        if threads.producers != producers || threads.consumers != consumers {
+            warnings::add(WarningKind::ClampedThreads, format!("'--{} {} {}' is more than the maximum of {} producers and {} consumers, '--{} {} {}' was used.",
+                    THREADS, producers, consumers, MAX_PRODUCERS_VALUE, MAX_CONSUMERS_VALUE, THREADS, threads.producers, threads.consumers));
+        }
+        threads
+    }
+
+    // Based on the available parallelism, which takes into account the cpu limits of containers and the affinity of the process
     pub fn default() -> Self {
-        let threads = num_cpus::get();
-        // We may actually use one more thread than the available ones, it seems to help a bit
-        if threads <= 4 {
-            Threads {
-                producers: 2,
-                consumers: 3
-            }
-        } else if threads <= 8 {
-            Threads {
-                producers: 3,
-                consumers: 6
+        let available = std::thread::available_parallelism().map_or_else(|_| num_cpus::get(), |x| x.get());
+        Self::for_available_parallelism(available)
+    }
+
+    // A producer for every 4 threads, and the rest for the consumers. We may actually use one more thread than the available ones,
+    // it seems to help a bit. The search mostly waits on the file system, so there are at least 2 producers to walk the dirs in parallel.
+    fn for_available_parallelism(available: usize) -> Self {
+        let producers = available.div_ceil(4).clamp(2, MAX_PRODUCERS_VALUE);
+        Threads {
+            producers,
+            consumers: (available + 1).saturating_sub(producers).clamp(MIN_CONSUMERS_VALUE, MAX_CONSUMERS_VALUE)
+        }
+    }
+}
+
+impl ColorPolicy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "auto" => Some(ColorPolicy::Auto),
+            "always" => Some(ColorPolicy::Always),
+            "never" => Some(ColorPolicy::Never),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ColorPolicy::Auto => "auto",
+            ColorPolicy::Always => "always",
+            ColorPolicy::Never => "never"
+        }
+    }
+}
+
+impl PathStyle {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "absolute" => Some(PathStyle::Absolute),
+            "unix" => Some(PathStyle::Unix),
+            "os" => Some(PathStyle::Os),
+            "relative" => Some(PathStyle::Relative),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PathStyle::Absolute => "absolute",
+            PathStyle::Unix => "unix",
+            PathStyle::Os => "os",
+            PathStyle::Relative => "relative"
+        }
+    }
+
+    // A path that is not under the working directory is made relative with '..' segments. If that is not possible
+    // (e.g. it is on another drive), it is only printed with '/' as the separator.
+    pub fn format(&self, path: &str) -> String {
+        match self {
+            PathStyle::Absolute => path.to_owned(),
+            PathStyle::Unix => path.replace('\\', "/"),
+            PathStyle::Os => path.replace(['/', '\\'], std::path::MAIN_SEPARATOR_STR),
+            PathStyle::Relative => {
+                let relative_path = std::env::current_dir().ok().and_then(|x| std::fs::canonicalize(x).ok())
+                        .and_then(|x| utils::get_relative_path(Path::new(path), &x));
+                match relative_path {
+                    Some(x) => x.to_string_lossy().replace('\\', "/"),
+                    None => path.replace('\\', "/")
+                }
             }
-        } else {
-            Threads {
-                producers: 3,
-                consumers: 8
+        }
+    }
+}
+
+impl SizeUnits {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "si" => Some(SizeUnits::Si),
+            "binary" => Some(SizeUnits::Binary),
+            "bytes" => Some(SizeUnits::Bytes),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SizeUnits::Si => "si",
+            SizeUnits::Binary => "binary",
+            SizeUnits::Bytes => "bytes"
+        }
+    }
+}
+
+impl NumberFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "comma" => Some(NumberFormat::Comma),
+            "dot" => Some(NumberFormat::Dot),
+            "space" => Some(NumberFormat::Space),
+            "none" => Some(NumberFormat::Plain),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NumberFormat::Comma => "comma",
+            NumberFormat::Dot => "dot",
+            NumberFormat::Space => "space",
+            NumberFormat::Plain => "none"
+        }
+    }
+
+    pub fn separator(&self) -> Option<char> {
+        match self {
+            NumberFormat::Comma => Some(','),
+            NumberFormat::Dot => Some('.'),
+            NumberFormat::Space => Some(' '),
+            NumberFormat::Plain => None
+        }
+    }
+}
+
+impl Style {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "ascii" => Some(Style::Ascii),
+            "unicode" => Some(Style::Unicode),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Style::Ascii => "ascii",
+            Style::Unicode => "unicode"
+        }
+    }
+}
+
+impl OutputFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "text" => Some(OutputFormat::Text),
+            "markdown" | "md" => Some(OutputFormat::Markdown),
+            "prometheus" | "openmetrics" => Some(OutputFormat::Prometheus),
+            "json" => Some(OutputFormat::Json),
+            "xml" => Some(OutputFormat::Xml),
+            "yaml" | "yml" => Some(OutputFormat::Yaml),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Markdown => "markdown",
+            OutputFormat::Prometheus => "prometheus",
+            OutputFormat::Json => "json",
+            OutputFormat::Xml => "xml",
+            OutputFormat::Yaml => "yaml"
+        }
+    }
+}
+
+impl Column {
+    // Separated by commas, e.g. 'files,lines,code'. None if any of them is unknown or if there are none.
+    pub fn parse_list(s: &str) -> Option<Vec<Self>> {
+        let mut columns = Vec::new();
+        for name in s.split(',').map(|x| x.trim().to_lowercase()).filter(|x| !x.is_empty()) {
+            let column = match name.as_str() {
+                "files" => Column::Files,
+                "lines" => Column::Lines,
+                "code" => Column::Code,
+                "comments" | "extra" => Column::Comments,
+                "size" => Column::Size,
+                "keywords" => Column::Keywords,
+                "share" => Column::Share,
+                _ => return None
+            };
+            if !columns.contains(&column) {
+                columns.push(column);
             }
         }
+        if columns.is_empty() {None} else {Some(columns)}
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Column::Files => "files",
+            Column::Lines => "lines",
+            Column::Code => "code",
+            Column::Comments => "comments",
+            Column::Size => "size",
+            Column::Keywords => "keywords",
+            Column::Share => "share"
+        }
+    }
+}
+
+impl SortOrder {
+    // '<key> [asc|desc]'. The numbers are descending and the names ascending by default.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim().to_lowercase();
+        let mut words = s.split_whitespace();
+        let key = match words.next()? {
+            "files" => SortKey::Files,
+            "lines" => SortKey::Lines,
+            "code" => SortKey::Code,
+            "size" => SortKey::Size,
+            "name" => SortKey::Name,
+            _ => return None
+        };
+        let ascending = match words.next() {
+            Some("asc") => true,
+            Some("desc") => false,
+            None => key == SortKey::Name,
+            _ => return None
+        };
+        if words.next().is_some() {
+            return None;
+        }
+
+        Some(SortOrder {key, ascending})
+    }
+
+    pub fn as_string(&self) -> String {
+        let key = match self.key {
+            SortKey::Files => "files",
+            SortKey::Lines => "lines",
+            SortKey::Code => "code",
+            SortKey::Size => "size",
+            SortKey::Name => "name"
+        };
+        format!("{} {}", key, if self.ascending {"asc"} else {"desc"})
     }
 }
 
@@ -561,17 +3042,39 @@ impl Formatted for ArgParsingError {
             Self::UnrecognisedCommand(p) => format!("--{} is not recognised as a command.",p).red(),
             Self::IncorrectCommandArgs(p) => format!("Incorrect arguments provided for the command '--{}'.",p).red(),
             Self::UnexpectedCommandArgs(p) => format!("Command '--{}' does not expect any arguments.",p).red(),
-            Self::NonExistantConfig(p) => format!("Configuration '{}' does not exist.",p).red()
+            Self::NonExistantConfig(p) => format!("Configuration '{}' does not exist.",p).red(),
+            Self::NonExistantProfile(p) => format!("Profile '{}' does not exist in the configuration.",p).red(),
+            Self::PathOutsideRestrictedRoot(p,root) => format!("Path '{}' is outside of the restricted root '{}'.",p,root).red(),
+            Self::InvalidSizeRange(min,max) => format!("The min size ({} bytes) is bigger than the max size ({} bytes).",
+                    utils::with_seperators(*min as usize), utils::with_seperators(*max as usize)).red(),
+            Self::DirsWithRemote => format!("Target directories can't be provided along with '--{}'.", REMOTE).red(),
+            Self::UnreadableSignKey(p) => format!("Unable to read a key from the file '{}' of '--{}'.", p, SIGN_KEY).red(),
+            Self::InvalidThreads(producers,consumers) => format!("At least {} producer and {} consumer are needed, but '--{} {} {}' was given.",
+                    MIN_PRODUCERS_VALUE, MIN_CONSUMERS_VALUE, THREADS, producers, consumers).red()
         }
     }
 }
 
+impl ArgParsingError {
+    // The code that the program exits with, as listed in the README, so that a script can tell a wrong invocation apart
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::UnreadableSignKey(_) => 5,
+            _ => 6
+        }
+    }
+}
+
+impl_display_from_formatted!(ArgParsingError);
+
+impl std::error::Error for ArgParsingError {}
+
 
 #[cfg(test)]
 mod tests {
     use std::ops::Add;
 
-    use crate::PERSISTENT_APP_PATHS;
+    use crate::{PERSISTENT_APP_PATHS, badge::BadgeMetric};
 
     use super::*;
 
@@ -586,8 +3089,8 @@ mod tests {
         assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("dirs".to_owned())), create_config_from_args("--dirs"));
         assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("dirs".to_owned())), create_config_from_args("--dirs   "));
         assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("threads".to_owned())), create_config_from_args("./ --threads"));
-        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("threads".to_owned())), create_config_from_args("./ --threads 5 10"));
-        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("threads".to_owned())), create_config_from_args("./ --threads 2 13"));
+        assert_eq!(Err(ArgParsingError::InvalidThreads(0, 4)), create_config_from_args("./ --threads 0 4"));
+        assert_eq!(Err(ArgParsingError::InvalidThreads(2, 0)), create_config_from_args("./ --threads 2 0"));
         assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("threads".to_owned())), create_config_from_args("./ --threads 9"));
         assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("threads".to_owned())), create_config_from_args("./ --threads A"));
         assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("show-faulty-files".to_owned())), create_config_from_args("./ --threads 1 1 --show-faulty-files 1"));
@@ -595,10 +3098,15 @@ mod tests {
         assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("search-in-dotted".to_owned())), create_config_from_args("./ --threads 1 1 --search-in-dotted a"));
         assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("no-visual".to_owned())), create_config_from_args("./ --no-visual a"));
         assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("braces-as-code".to_owned())), create_config_from_args("./ --braces-as-code a"));
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("ignore-empty-files".to_owned())), create_config_from_args("./ --ignore-empty-files a"));
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("regex-keywords".to_owned())), create_config_from_args("./ --regex-keywords a"));
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("detect-shebangs".to_owned())), create_config_from_args("./ --detect-shebangs a"));
         assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("exclude".to_owned())), create_config_from_args("./ --exclude"));
         assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("exclude".to_owned())), create_config_from_args("./ --exclude   --threads 4"));
         assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("load".to_owned())), create_config_from_args("./ --load"));
         assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("load".to_owned())), create_config_from_args("./ --load   "));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("profile".to_owned())), create_config_from_args("./ --profile"));
+        assert_eq!(Err(ArgParsingError::NonExistantProfile("non_existant".to_owned())), create_config_from_args("./ --profile non_existant"));
         assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("save".to_owned())), create_config_from_args("./ --save"));
         assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("save".to_owned())), create_config_from_args("./ --save   "));
 
@@ -609,6 +3117,8 @@ mod tests {
         assert_eq!(Configuration::new(vec![convert_to_absolute("./")]), create_config_from_args("--dirs ./").unwrap());
         assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_threads(1,1), create_config_from_args("./ --threads 1 1").unwrap());
         assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_threads(1,1), create_config_from_args("./ --threads   1   1 ").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_threads(4,10), create_config_from_args("./ --threads 5 10").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_threads(2,12), create_config_from_args("./ --threads 2 13").unwrap());
         assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_threads(1,1).set_braces_as_code(true),
                 create_config_from_args("./ --threads 1 1 --braces-as-code").unwrap());
         assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_should_search_in_dotted(true),
@@ -617,6 +3127,205 @@ mod tests {
                 create_config_from_args("./ --no-visual").unwrap());
         assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_should_show_faulty_files(true),
                 create_config_from_args("./ --show-faulty-files").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_ignore_empty_files(true),
+                create_config_from_args("./ --ignore-empty-files").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_regex_keywords(true),
+                create_config_from_args("./ --regex-keywords").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_detect_shebangs(true),
+                create_config_from_args("./ --detect-shebangs").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("large-file-threshold".to_owned())), create_config_from_args("./ --large-file-threshold"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("large-file-threshold".to_owned())), create_config_from_args("./ --large-file-threshold -1"));
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("skip-build-outputs".to_owned())), create_config_from_args("./ --skip-build-outputs a"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("max-open-files".to_owned())), create_config_from_args("./ --max-open-files a"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("max-memory".to_owned())), create_config_from_args("./ --max-memory a"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("max-depth".to_owned())), create_config_from_args("./ --max-depth -1"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("min-size".to_owned())), create_config_from_args("./ --min-size 1TB"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("max-size".to_owned())), create_config_from_args("./ --max-size"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("notify".to_owned())), create_config_from_args("./ --notify https://a.b/c"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("notify".to_owned())), create_config_from_args("./ --notify slack:a.b/c"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("notify-template".to_owned())), create_config_from_args("./ --notify-template"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_notify(Some("webhook:https://a.b/c".to_owned()),
+                Some("{\"n\": {files}}".to_owned())),
+                create_config_from_args("./ --notify webhook:https://a.b/c --notify-template {\"n\": {files}}").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_badge(Some(Badge {path: "loc.svg".to_owned(),
+                metric: BadgeMetric::Code, language: Some("rust".to_owned())})),
+                create_config_from_args("./ --badge loc.svg, code:rust").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("badge".to_owned())), create_config_from_args("./ --badge loc.svg, words"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_export_treemap(Some("map.dot".to_owned())),
+                create_config_from_args("./ --export-treemap map.dot").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("export-treemap".to_owned())), create_config_from_args("./ --export-treemap map.png"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_pr_comment("Cargo.toml", "comment.md"),
+                create_config_from_args("./ --pr-comment Cargo.toml, comment.md").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("pr-comment".to_owned())), create_config_from_args("./ --pr-comment missing.txt, comment.md"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("pr-comment".to_owned())), create_config_from_args("./ --pr-comment Cargo.toml"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_fail_if(budgets::parse_budgets("total_lines > 100000, todo > 50").unwrap()),
+                create_config_from_args("./ --fail-if total_lines > 100000, todo > 50").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("fail-if".to_owned())), create_config_from_args("./ --fail-if lines"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_metrics(derived_metrics::parse_derived_metrics("ratio = extra / lines").unwrap()),
+                create_config_from_args("./ --metrics ratio = extra / lines").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("metrics".to_owned())), create_config_from_args("./ --metrics ratio = extra /"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_sort(Some(SortOrder {key: SortKey::Code, ascending: true})),
+                create_config_from_args("./ --sort Code ASC").unwrap());
+        assert_eq!(Some(SortOrder {key: SortKey::Name, ascending: true}), SortOrder::parse("name"));
+        assert_eq!("size desc", SortOrder::parse(" size ").unwrap().as_string());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("sort".to_owned())), create_config_from_args("./ --sort bytes"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("sort".to_owned())), create_config_from_args("./ --sort lines up"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_columns(Some(vec![Column::Files, Column::Lines, Column::Comments])),
+                create_config_from_args("./ --columns Files, lines,extra,files").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("columns".to_owned())), create_config_from_args("./ --columns files,bytes"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("columns".to_owned())), create_config_from_args("./ --columns"));
+        assert!(!Configuration::new(vec![]).set_columns(Some(vec![Column::Files])).shows_column(Column::Size));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_overview(vec![Column::Code, Column::Files, Column::Keywords]),
+                create_config_from_args("./ --overview code,files, Keywords").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("overview".to_owned())), create_config_from_args("./ --overview files,bytes"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_columns(Some(vec![Column::Lines, Column::Share])),
+                create_config_from_args("./ --columns lines,share").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("overview".to_owned())), create_config_from_args("./ --overview files,share"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_show_skipped(true),
+                create_config_from_args("./ --show-skipped").unwrap());
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("show-skipped".to_owned())), create_config_from_args("./ --show-skipped yes"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_disambiguate(vec!["h".to_owned(), "pl".to_owned()]),
+                create_config_from_args("./ --disambiguate h,.pl").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("disambiguate".to_owned())), create_config_from_args("./ --disambiguate h,rs"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_keyword_scope(Some(KeywordScope::Comments)),
+                create_config_from_args("./ --keyword-scope Comments").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_submodules(Some(Submodules::Include)),
+                create_config_from_args("./ --include-submodules").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_submodules(Some(Submodules::Exclude)),
+                create_config_from_args("./ --exclude-submodules --exclude-submodules").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("exclude-submodules".to_owned())),
+                create_config_from_args("./ --include-submodules --exclude-submodules"));
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("include-submodules".to_owned())), create_config_from_args("./ --include-submodules yes"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("keyword-scope".to_owned())), create_config_from_args("./ --keyword-scope strings"));
+        assert!(Configuration::new(vec![]).shows_column(Column::Size));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_verbosity(Verbosity::Quiet),
+                create_config_from_args("./ --quiet").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_verbosity(Verbosity::Verbose),
+                create_config_from_args("./ --verbose --verbose").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("verbose".to_owned())), create_config_from_args("./ --quiet --verbose"));
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("quiet".to_owned())), create_config_from_args("./ --quiet 2"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("remote".to_owned())), create_config_from_args("--remote --upload-pack=x"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("remote".to_owned())), create_config_from_args("--remote github.com/a/b"));
+        assert_eq!(Err(ArgParsingError::DirsWithRemote), create_config_from_args("./ --remote https://github.com/a/b"));
+        assert_eq!(*Configuration::new(vec![]).set_files_from(Some("-".to_owned())), create_config_from_args("--files-from -").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_changed_since(Some("origin/main".to_owned())),
+                create_config_from_args("./ --changed-since origin/main ").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("changed-since".to_owned())), create_config_from_args("./ --changed-since -p"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("changed-since".to_owned())), create_config_from_args("./ --changed-since a b"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_files_from(Some("Cargo.toml".to_owned())),
+                create_config_from_args("./ --files-from Cargo.toml").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("files-from".to_owned())), create_config_from_args("--files-from missing.txt"));
+        assert_eq!(*Configuration::new(vec![]).set_remote(Some("https://github.com/a/b".to_owned())),
+                create_config_from_args("--remote https://github.com/a/b").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_top(Some(6)), create_config_from_args("./ --top 6").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_top_files(Some(5)).set_top(Some(2)),
+                create_config_from_args("./ --top-files 5 --top 2").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("top".to_owned())), create_config_from_args("./ --top 0"));
+        assert_eq!(Err(ArgParsingError::InvalidSizeRange(2048, 1024)), create_config_from_args("./ --min-size 2KB --max-size 1024"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_max_depth(Some(0)).set_min_size(Some(10))
+                .set_max_size(Some(3 * 1024 * 1024)), create_config_from_args("./ --max-depth 0 --min-size 10 --max-size 3mb").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_lang("de").set_languages_of_interest(vec!["rust".to_owned()]),
+                create_config_from_args("./ --lang DE --languages rust").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("lang".to_owned())), create_config_from_args("./ --lang it"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_output(OutputFormat::Markdown),
+                create_config_from_args("./ --output markdown").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_output(OutputFormat::Prometheus),
+                create_config_from_args("./ --output Prometheus").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_output(OutputFormat::Json),
+                create_config_from_args("./ --output JSON").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_output(OutputFormat::Xml),
+                create_config_from_args("./ --output xml").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_output(OutputFormat::Yaml),
+                create_config_from_args("./ --output yml").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("output".to_owned())), create_config_from_args("./ --output html"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_color(ColorPolicy::Always),
+                create_config_from_args("./ --color Always").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_color(ColorPolicy::Never),
+                create_config_from_args("./ --no-color").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("color".to_owned())), create_config_from_args("./ --color sometimes"));
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("no-color".to_owned())), create_config_from_args("./ --no-color always"));
+        assert!(!Configuration::new(vec![]).set_color(ColorPolicy::Always).set_output(OutputFormat::Markdown).uses_colors());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_path_style(PathStyle::Relative),
+                create_config_from_args("./ --path-style Relative").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("path-style".to_owned())), create_config_from_args("./ --path-style windows"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_size_units(SizeUnits::Binary).set_size_precision(2),
+                create_config_from_args("./ --size-units Binary --size-precision 2").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("size-units".to_owned())), create_config_from_args("./ --size-units kb"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("size-precision".to_owned())), create_config_from_args("./ --size-precision 4"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_number_format(NumberFormat::Plain),
+                create_config_from_args("./ --number-format None").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("number-format".to_owned())), create_config_from_args("./ --number-format apostrophe"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_dir_breakdown(Some(1)),
+                create_config_from_args("./ --dir-breakdown").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_dir_breakdown(Some(3)),
+                create_config_from_args("./ --dir-breakdown 3").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("dir-breakdown".to_owned())), create_config_from_args("./ --dir-breakdown 0"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_docs(true),
+                create_config_from_args("./ --docs").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_histogram(true),
+                create_config_from_args("./ --histogram").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_list_only(true),
+                create_config_from_args("./ --list-only").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_timings(true),
+                create_config_from_args("./ --timings").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_stream(true),
+                create_config_from_args("./ --stream").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_logical_lines(true),
+                create_config_from_args("./ --logical-lines").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_complexity(true),
+                create_config_from_args("./ --complexity").unwrap());
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("complexity".to_owned())), create_config_from_args("./ --complexity yes"));
+        assert!(!create_config_from_args("./").unwrap().counts_complexity());
+        assert!(create_config_from_args("./ --fail-if rust.complexity > 100").unwrap().counts_complexity());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_isolate_roots(true),
+                create_config_from_args("./ --isolate-roots").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_git_tracked(true),
+                create_config_from_args("./ --git-tracked").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_follow_links(true),
+                create_config_from_args("./ --follow-links").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_hidden(true),
+                create_config_from_args("./ --hidden").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_max_memory(512),
+                create_config_from_args("./ --max-memory 512").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_timeout(600).set_file_timeout(10),
+                create_config_from_args("./ --timeout 600 --file-timeout 10").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_max_open_files(64),
+                create_config_from_args("./ --max-open-files 64").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_max_open_files(0),
+                create_config_from_args("./ --max-open-files 0").unwrap());
+        assert_ne!(0, Configuration::new(vec![convert_to_absolute("./")]).max_open_files);
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_strict_encoding(true),
+                create_config_from_args("./ --strict-encoding").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_typical_markers(true),
+                create_config_from_args("./ --typical-markers").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_keyword_density(true),
+                create_config_from_args("./ --keyword-density").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_keyword_categories(true),
+                create_config_from_args("./ --keyword-categories").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_skip_build_outputs(true),
+                create_config_from_args("./ --skip-build-outputs").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_large_file_threshold(0),
+                create_config_from_args("./ --large-file-threshold 0").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_split_threshold(64),
+                create_config_from_args("./ --split-threshold 64").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("split-threshold".to_owned())), create_config_from_args("./ --split-threshold big"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_max_line_length(80),
+                create_config_from_args("./ --max-line-length 80").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("max-line-length".to_owned())), create_config_from_args("./ --max-line-length 0"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_detect_shebangs(true).set_prefer_shebangs(true),
+                create_config_from_args("./ --detect-shebangs --prefer-shebangs").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_co_occurrence(Some(vec![])),
+                create_config_from_args("./ --co-occurrence").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_co_occurrence(Some(vec!["unsafe".to_owned(), "transmute".to_owned()])),
+                create_config_from_args("./ --co-occurrence Unsafe , transmute,").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_todos(Some(vec![])),
+                create_config_from_args("./ --todos").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_todos(Some(vec!["TODO".to_owned(), "XXX".to_owned()])),
+                create_config_from_args("./ --todos TODO , XXX,").unwrap());
+        assert_eq!(vec!["TODO", "FIXME", "HACK"], Configuration::new(vec![]).set_todos(Some(vec![])).get_todo_markers());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_test_patterns(Some(vec!["spec/".to_owned(), "*_spec.rb".to_owned()])),
+                create_config_from_args("./ --test-pattern spec\\ , *_spec.rb").unwrap());
+        assert_eq!(DEF_TEST_PATTERNS.len(), Configuration::new(vec![]).set_test_patterns(Some(vec![])).get_test_patterns().len());
         assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_exclude_dirs(vec!["a".to_owned(),"b".to_owned(),"c".to_owned()]),
                 create_config_from_args("./ --exclude a,b ,  c ").unwrap());
         assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_exclude_dirs(vec!["a/path".to_owned(),"b/path".to_owned()]),
@@ -625,10 +3334,118 @@ mod tests {
                 create_config_from_args("./ --languages a,b,c").unwrap());
         assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_languages_of_interest(vec!["a".to_owned()]),
                 create_config_from_args("./ --languages a, ").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_extension_filters(vec!["all".to_owned(),"-json".to_owned(),"-md".to_owned()]),
+                create_config_from_args("./ --ext all, -JSON,-.md").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("ext".to_owned())), create_config_from_args("./ --ext - , "));
         assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_log_option(LogOption::new(Some("this is a test".to_owned()))),
                 create_config_from_args("./ --log   this is a test ").unwrap());
         assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_log_option(LogOption::new(None)),
                 create_config_from_args("./ --log  ").unwrap());
+        
+        let src_root = utils::canonicalize_to_str(Path::new("./src"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("restrict-to".to_owned())), create_config_from_args("./ --restrict-to"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("restrict-to".to_owned())), create_config_from_args("./ --restrict-to non_existant"));
+        assert_eq!(Err(ArgParsingError::PathOutsideRestrictedRoot(convert_to_absolute("./"), src_root.clone().unwrap())),
+                create_config_from_args("./ --restrict-to ./src"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./src")]).set_restrict_to(src_root),
+                create_config_from_args("./src --restrict-to \"./src\"").unwrap());
+
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("lang-scope".to_owned())), create_config_from_args("./ --lang-scope"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("lang-scope".to_owned())), create_config_from_args("./ --lang-scope rust src"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("lang-scope".to_owned())), create_config_from_args("./ --lang-scope rust: ; java: app"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_lang_scopes(
+                vec![LangScope::new("rust", &["src", "crates/**"]), LangScope::new("java", &["app/src"])]),
+                create_config_from_args("./ --lang-scope Rust: src, crates/** ; java: app\\src").unwrap());
+
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("report".to_owned())), create_config_from_args("./ --report"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("report".to_owned())), create_config_from_args("./ --report ./src"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("sign-key".to_owned())), create_config_from_args("./ --sign-key  "));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_report(Some("a/report.txt".to_owned()), Some("my key".to_owned())),
+                create_config_from_args("./ --report a\\report.txt --sign-key test_dir/keys/sign.key ").unwrap());
+        assert_eq!(Err(ArgParsingError::UnreadableSignKey("test_dir/keys/missing.key".to_owned())),
+                create_config_from_args("./ --report a\\report.txt --sign-key test_dir/keys/missing.key"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("sqlite".to_owned())), create_config_from_args("./ --sqlite ./src"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_sqlite(Some("history.db".to_owned())),
+                create_config_from_args("./ --sqlite history.db").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_event_log(Some("logs/events.ndjson".to_owned())),
+                create_config_from_args("./ --event-log logs\\events.ndjson").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("event-log".to_owned())), create_config_from_args("./ --event-log"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_out(Some("results.txt".to_owned()), false).set_output(OutputFormat::Xml),
+                create_config_from_args("./ --out results.txt --output xml").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_out(Some("results.txt".to_owned()), true),
+                create_config_from_args("./ --force --out results.txt").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("out".to_owned())), create_config_from_args("./ --out"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_match_case(true),
+                create_config_from_args("./ --match-case").unwrap());
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("match-case".to_owned())), create_config_from_args("./ --match-case yes"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_duplicates(true).set_top_files(Some(3)),
+                create_config_from_args("./ --duplicates --top-files 3").unwrap());
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("duplicates".to_owned())), create_config_from_args("./ --duplicates 3"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_clones(Some(6)), create_config_from_args("./ --clones 6").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("clones".to_owned())), create_config_from_args("./ --clones 2"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("clones".to_owned())), create_config_from_args("./ --clones"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_licenses(true), create_config_from_args("./ --licenses").unwrap());
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("licenses".to_owned())), create_config_from_args("./ --licenses MIT"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_hygiene(true).set_licenses(true),
+                create_config_from_args("./ --hygiene --licenses").unwrap());
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("hygiene".to_owned())), create_config_from_args("./ --hygiene no"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_indentation(true), create_config_from_args("./ --indentation").unwrap());
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("indentation".to_owned())), create_config_from_args("./ --indentation 4"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_skip_generated(true), create_config_from_args("./ --skip-generated").unwrap());
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("skip-generated".to_owned())), create_config_from_args("./ --skip-generated yes"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_skip_minified(true), create_config_from_args("./ --skip-minified").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_keyword_hotspots(true), create_config_from_args("./ --keyword-hotspots").unwrap());
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("keyword-hotspots".to_owned())), create_config_from_args("./ --keyword-hotspots 3"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_prescan(true), create_config_from_args("./ --prescan").unwrap());
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("prescan".to_owned())), create_config_from_args("./ --prescan yes"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_linguist_compare(true), create_config_from_args("./ --linguist-compare").unwrap());
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("linguist-compare".to_owned())), create_config_from_args("./ --linguist-compare yes"));
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("skip-minified".to_owned())), create_config_from_args("./ --skip-minified yes"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_authors(true), create_config_from_args("./ --authors").unwrap());
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("authors".to_owned())), create_config_from_args("./ --authors 5"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_estimate(true).set_salary(80_000).set_overhead(150),
+                create_config_from_args("./ --estimate --salary 80000 --overhead 150").unwrap());
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("estimate".to_owned())), create_config_from_args("./ --estimate 80000"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("salary".to_owned())), create_config_from_args("./ --salary 0"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("overhead".to_owned())), create_config_from_args("./ --overhead 50"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_strict(true), create_config_from_args("./ --strict").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_strict_encoding(true), create_config_from_args("./ --strict-encoding").unwrap());
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("strict".to_owned())), create_config_from_args("./ --strict yes"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_zero_keywords(true).set_top_keywords(Some(3)).set_top(Some(2)),
+                create_config_from_args("./ --zero-keywords --top-keywords 3 --top 2").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("top-keywords".to_owned())), create_config_from_args("./ --top-keywords 0"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_tui(true), create_config_from_args("./ --tui").unwrap());
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("tui".to_owned())), create_config_from_args("./ --tui yes"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_stats(true), create_config_from_args("./ --stats").unwrap());
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("stats".to_owned())), create_config_from_args("./ --stats yes"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_embedded(true), create_config_from_args("./ --embedded").unwrap());
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("embedded".to_owned())), create_config_from_args("./ --embedded yes"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_ignore_trailing_empty_line(true),
+                create_config_from_args("./ --ignore-trailing-empty-line").unwrap());
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_summary(true), create_config_from_args("./ --summary").unwrap());
+        assert_eq!(Err(ArgParsingError::UnexpectedCommandArgs("summary".to_owned())), create_config_from_args("./ --summary yes"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_style(Style::Unicode), create_config_from_args("./ --style Unicode").unwrap());
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("style".to_owned())), create_config_from_args("./ --style emoji"));
+
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("langs-dir".to_owned())), create_config_from_args("./ --langs-dir"));
+        assert_eq!(Err(ArgParsingError::IncorrectCommandArgs("langs-dir".to_owned())), create_config_from_args("./ --langs-dir non_existant"));
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_langs_dir(utils::canonicalize_to_str(Path::new("./test_dir/languages"))),
+                create_config_from_args("./ --langs-dir ./test_dir/languages").unwrap());
+    }
+
+    #[test]
+    fn test_add_env_options() {
+        let vars = || vec![("MEZURA_THREADS".to_owned(), "2 4".to_owned()), ("MEZURA_HYGIENE".to_owned(), "yes".to_owned()),
+                ("MEZURA_TUI".to_owned(), "no".to_owned()), ("MEZURA_DIRS".to_owned(), "/a".to_owned()), ("MEZURA_HOME".to_owned(), "/b".to_owned()),
+                ("MEZURA_HELP".to_owned(), "yes".to_owned()), ("THREADS".to_owned(), "1 1".to_owned())].into_iter();
+        assert_eq!("./ --hygiene --threads 2 4 --exclude a", add_env_options("./ --exclude a", vars()));
+        // The options of the line are used over the environment
+        assert_eq!("./ --hygiene --threads 1 1", add_env_options("./ --threads 1 1", vars()));
+        assert_eq!("--dirs /a --hygiene --threads 2 4 --strict-encoding", add_env_options("--strict-encoding", vars()));
+        assert_eq!("./ --exclude a", add_env_options("./ --exclude a", std::iter::empty()));
+
+        assert_eq!(*Configuration::new(vec![convert_to_absolute("./")]).set_threads(2, 4).set_hygiene(true),
+                create_config_builder_from_args(&add_env_options("./", vars())).unwrap().build());
     }
 
     #[test]
@@ -642,6 +3459,49 @@ mod tests {
         assert!(!has_any_args("cmnd    "));
     }
 
+    #[test]
+    fn test_add_missing_fields() {
+        let mut config_builder = ConfigurationBuilder {no_keywords: Some(true), ..ConfigurationBuilder::default()};
+        assert!(config_builder.has_missing_fields());
+        let loaded = ConfigurationBuilder {dirs: Some(vec!["./src".to_owned()]), no_keywords: Some(false), threads: Some(Threads::new(2, 3)),
+                sign_key: Some("key".to_owned()), ..ConfigurationBuilder::default()};
+        config_builder.add_missing_fields(loaded);
+
+        assert_eq!((Some(vec!["./src".to_owned()]), Some(true), Some(Threads::new(2, 3)), None),
+                (config_builder.dirs.clone(), config_builder.no_keywords, config_builder.threads.clone(), config_builder.sign_key.clone()));
+        assert!(config_builder.has_missing_fields());
+    }
+
+    #[test]
+    fn test_try_build() {
+        let mut config_builder = ConfigurationBuilder {threads: Some(Threads::new(0, 2)), ..ConfigurationBuilder::default()};
+        assert_eq!(Err(ArgParsingError::MissingTargetDirs), config_builder.try_build());
+        config_builder.dirs = Some(vec!["./src".to_owned()]);
+        assert_eq!(Err(ArgParsingError::InvalidThreads(0, 2)), config_builder.try_build());
+        config_builder.threads = Some(Threads::new(1, 2));
+        config_builder.min_size = Some(10);
+        config_builder.max_size = Some(5);
+        assert_eq!(Err(ArgParsingError::InvalidSizeRange(10, 5)), config_builder.try_build());
+        config_builder.max_size = None;
+        config_builder.restrict_to = Some("./tests".to_owned());
+        assert_eq!(Err(ArgParsingError::PathOutsideRestrictedRoot("./src".to_owned(), "./tests".to_owned())), config_builder.try_build());
+        config_builder.restrict_to = None;
+
+        let config = config_builder.try_build().unwrap();
+        assert_eq!(vec!["./src".to_owned()], config.dirs);
+        assert_eq!(Threads::new(1, 2), config.threads);
+        assert_eq!(Some(10), config.min_size);
+    }
+
+    #[test]
+    fn test_default_threads() {
+        assert_eq!(Threads::new(2, 1), Threads::for_available_parallelism(1));
+        assert_eq!(Threads::new(2, 3), Threads::for_available_parallelism(4));
+        assert_eq!(Threads::new(2, 7), Threads::for_available_parallelism(8));
+        assert_eq!(Threads::new(3, 10), Threads::for_available_parallelism(12));
+        assert_eq!(Threads::new(4, 12), Threads::for_available_parallelism(64));
+    }
+
     #[test]
     fn test_absolute_conversion() {
         let path = "./";
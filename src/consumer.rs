@@ -1,37 +1,185 @@
-use std::{sync::atomic::Ordering, thread, time::Duration};
+use std::{panic::{self, AssertUnwindSafe}, sync::mpsc::Sender, thread};
 
 use crossbeam_deque::Steal;
 
 use crate::*;
 
-pub fn start_parser_thread(id: usize, files_injector: Arc<Injector<ParsableFile>>, faulty_files: FaultyFilesListMut, finish_condition: Arc<AtomicBool>,
-        languages_content_info: ContentInfoMapMut, language_map: Arc<HashMap<String,Language>>, config: Arc<Configuration>) -> JoinHandle<()>
-{
-    thread::Builder::new().name(id.to_string()).spawn(move || {
-        start_parsing_files(id, files_injector, faulty_files, finish_condition, languages_content_info, language_map, config);
-    }).unwrap()
+// What the parser threads share, which each of them gets a clone of
+#[derive(Clone)]
+pub struct ParserContext {
+    pub files_injector: Arc<Injector<ParsableFile>>,
+    pub faulty_files: FaultyFilesListMut,
+    pub files_signal: Arc<FilesSignal>,
+    pub dirs_stats: DirStatsMapMut,
+    pub parse_timings: ParseTimingsMut,
+    pub language_map: Arc<HashMap<String,Language>>,
+    pub config: Arc<Configuration>,
+    // Gets the bytes of every file that is parsed, if there is a progress bar
+    pub progress_sender: Option<Sender<usize>>,
+    pub resource_limits: Arc<ResourceLimits>,
+    pub observer: ObserverRef,
+    pub plugins: PluginsRef
 }
 
-pub fn start_parsing_files(_id: usize, files_injector: Arc<Injector<ParsableFile>>, faulty_files: FaultyFilesListMut, finish_condition: Arc<AtomicBool>,
-    languages_content_info: ContentInfoMapMut, language_map: Arc<HashMap<String,Language>>, config: Arc<Configuration>) 
-{
+pub fn start_parser_thread(id: usize, context: ParserContext) -> JoinHandle<HashMap<String,LanguageContentInfo>> {
+    thread::Builder::new().name(id.to_string()).spawn(move || start_parsing_files(id, &context)).unwrap()
+}
+
+// The stats of the languages are accumulated by each thread on its own, without any locking, and are returned to be merged
+// with the ones of the other threads after it is joined.
+pub fn start_parsing_files(id: usize, context: &ParserContext) -> HashMap<String,LanguageContentInfo> {
+    let ParserContext {files_injector, faulty_files, files_signal, dirs_stats, parse_timings, language_map, config, progress_sender,
+            resource_limits, observer, plugins} = context;
+    let mut progress_sender = progress_sender.clone();
+    let mut content_info_map = make_language_stats(language_map.clone());
     let mut buf = String::with_capacity(150);
-    // let mut share = 0;
+    let (started_instant, mut parsed_files, mut busy_duration) = (Instant::now(), 0, Duration::ZERO);
+    let test_patterns = config.get_test_patterns();
     loop {
+        if files_signal.should_stop() {
+            break;
+        }
         if let Steal::Success(parsable_file) = &files_injector.steal() 
         {
-            match file_parser::parse_file(&parsable_file.path, &parsable_file.language_name, &mut buf, language_map.clone(), &config) {
-                Ok(x) => languages_content_info.lock().unwrap().get_mut(&parsable_file.language_name).unwrap().add_file_stats(x),
-                Err(x) => faulty_files.lock().unwrap().push(FaultyFileDetails::new(
-                        parsable_file.path.to_str().unwrap().to_owned(),x,parsable_file.path.metadata().map_or(0, |m| m.len())))
+            let file_started_instant = Instant::now();
+            // The wait for an open file slot is not part of the parse time
+            let open_file_slot = parsable_file.contents.is_none().then(|| resource_limits.acquire_open_file());
+            let buffered_bytes = (resource_limits.max_buffered_bytes != 0)
+                    .then(|| resource_limits.reserve_buffer(estimate_parse_memory(parsable_file, config)));
+            let parse_started_instant = Instant::now();
+            // With '--embedded', the stats of the regions of the file in other languages
+            let mut regions_stats = Vec::new();
+            // A panic of the parser would leave the thread unjoinable, so it only makes the file faulty
+            let parse_result = panic::catch_unwind(AssertUnwindSafe(|| match &parsable_file.contents {
+                contents if config.embedded && embedded::has_embedded_regions(language_map, &parsable_file.language_name) =>
+                        embedded::parse_file_with_regions(&parsable_file.path, contents.as_deref(), &parsable_file.language_name, &mut buf,
+                                language_map.clone(), config, plugins, &mut regions_stats),
+                Some(contents) => file_parser::parse_archive_entry(contents, &parsable_file.path, &parsable_file.language_name, &mut buf,
+                        language_map.clone(), config, plugins),
+                None if language_map.get(&parsable_file.language_name).is_some_and(|x| notebook::is_parsed_as_notebook(&parsable_file.path, x)) =>
+                        notebook::parse_notebook_file(&parsable_file.path, &parsable_file.language_name, &mut buf, language_map.clone(), config, plugins),
+                None => file_parser::parse_file(&parsable_file.path, &parsable_file.language_name, &mut buf, language_map.clone(), config, plugins)
+            })).unwrap_or_else(|x| {
+                buf.clear();
+                Err(FaultyReason::Panicked(get_panic_message(&*x)))
+            });
+            let parse_duration = parse_started_instant.elapsed();
+            drop(open_file_slot);
+            drop(buffered_bytes);
+            parsed_files += 1;
+            if config.timings {
+                parse_timings.lock().unwrap().add_file(&parsable_file.path, &parsable_file.language_name, parse_duration);
             }
-        } else {
-            if finish_condition.load(Ordering::Relaxed) {
-                break;
-            } 
-
-            thread::sleep(Duration::from_millis(2));
+            match parse_result {
+                Ok(x) if x.is_generated && config.skip_generated => {
+                    // Checked before the message is formatted, since it is done for every file
+                    if config.is_verbose() {
+                        reporter::verbose(config, &format!("-- {} ({}, generated, left out)", config.path_style.format(&parsable_file.path.to_string_lossy()),
+                                parsable_file.language_name));
+                    }
+                    observer.on_event(&AnalysisEvent::FileSkipped {path: &parsable_file.path, reason: SkipReason::Generated});
+                    get_content_info(&mut content_info_map, &parsable_file.language_name).generated_stats.add_file_stats(&x, parsable_file.size() as usize);
+                },
+                Ok(x) if x.is_minified && config.skip_minified => {
+                    if config.is_verbose() {
+                        reporter::verbose(config, &format!("-- {} ({}, minified, left out)", config.path_style.format(&parsable_file.path.to_string_lossy()),
+                                parsable_file.language_name));
+                    }
+                    observer.on_event(&AnalysisEvent::FileSkipped {path: &parsable_file.path, reason: SkipReason::Minified});
+                    get_content_info(&mut content_info_map, &parsable_file.language_name).minified_stats.add_file_stats(&x, parsable_file.size() as usize);
+                },
+                Ok(mut x) => {
+                    if config.is_verbose() {
+                        reporter::verbose(config, &format!("-- {} ({}, {} lines)", config.path_style.format(&parsable_file.path.to_string_lossy()),
+                                parsable_file.language_name, with_seperators(x.lines)));
+                    }
+                    let bytes = parsable_file.size();
+                    observer.on_event(&AnalysisEvent::FileParsed {path: &parsable_file.path, language: &parsable_file.language_name, stats: &x, bytes});
+                    // The documentation files are not part of the code stats, so they are left out of the breakdown too
+                    let bytes = bytes as usize;
+                    if let Some(depth) = config.breakdown_depth().filter(|_| !language_map[&parsable_file.language_name].is_documentation()) {
+                        dirs_stats.lock().unwrap().entry(get_breakdown_dir(&parsable_file.path, &config.dirs, depth)).or_default()
+                                .add_file_stats(&x, bytes);
+                    }
+                    let is_test = !test_patterns.is_empty() && producer::is_test_file(&parsable_file.path, &test_patterns, &config.dirs);
+                    let content_info = get_content_info(&mut content_info_map, &parsable_file.language_name);
+                    if let Some(keyword_names) = &config.co_occurrence {
+                        content_info.add_keyword_co_occurrences(&x.keyword_occurences, keyword_names);
+                    }
+                    if config.keyword_hotspots {
+                        content_info.add_keyword_hotspots(&x.keyword_occurences, &parsable_file.path);
+                    }
+                    if config.top_files.is_some() || config.tui {
+                        content_info.file_summaries.push(FileSummary {path: parsable_file.path.clone(), lines: x.lines, bytes});
+                    }
+                    if config.clones.is_some() {
+                        let line_hashes = std::mem::take(&mut x.code_line_hashes);
+                        content_info.file_code_lines.push(FileCodeLines {path: parsable_file.path.clone(), line_hashes});
+                    }
+                    if config.licenses {
+                        let extension = utils::get_file_extension(&parsable_file.path).unwrap_or_default();
+                        let license = x.license.take().unwrap_or_else(|| UNLICENSED.to_owned());
+                        *content_info.license_counts.entry(extension).or_default().entry(license).or_insert(0) += 1;
+                    }
+                    if config.authors && parsable_file.contents.is_none() {
+                        // The files that git doesn't know are left out
+                        if let Ok(author_lines) = git::blame_authors(&parsable_file.path) {
+                            let extension = utils::get_file_extension(&parsable_file.path).unwrap_or_default();
+                            let counts = content_info.author_counts.entry(extension).or_default();
+                            author_lines.into_iter().for_each(|(author, lines)| *counts.entry(author).or_insert(0) += lines);
+                        }
+                    }
+                    if let Some(indentation) = x.indentation.take() {
+                        let extension = utils::get_file_extension(&parsable_file.path).unwrap_or_default();
+                        content_info.indentation.entry(extension).or_default().add(&indentation);
+                    }
+                    if let Some(hash) = x.content_hash {
+                        content_info.file_hashes.push(FileHash {hash, summary: FileSummary {path: parsable_file.path.clone(), lines: x.lines, bytes}});
+                    }
+                    if is_test {
+                        content_info.test_stats.add_file_stats(&x, bytes);
+                    }
+                    if x.is_generated {
+                        content_info.generated_stats.add_file_stats(&x, bytes);
+                    }
+                    if x.is_minified {
+                        content_info.minified_stats.add_file_stats(&x, bytes);
+                    }
+                    content_info.add_file_stats(x);
+                    for region in regions_stats {
+                        if let Some(region_content_info) = content_info_map.get_mut(&region.language_name) {
+                            region_content_info.add_region_stats(region.stats, region.bytes);
+                        }
+                    }
+                },
+                Err(x) => {
+                    let faulty_file = FaultyFile::new(parsable_file.path.clone(), parsable_file.language_name.to_owned(), x, parsable_file.size());
+                    observer.on_event(&AnalysisEvent::FileFaulty(&faulty_file));
+                    faulty_files.lock().unwrap().push(faulty_file)
+                }
+            }
+            // The progress bar may have stopped already, which doesn't stop the parsing
+            if progress_sender.as_ref().is_some_and(|x| x.send(parsable_file.size() as usize).is_err()) {
+                progress_sender = None;
+            }
+            busy_duration += file_started_instant.elapsed();
+        } else if !files_signal.wait_for_files(files_injector) {
+            break;
         }
     }
-    // println!("Thread {} finished, having done {} files.",_id,share);
+    producer::print_thread_colored_msg(config, id, format!("Parser thread {} finished in {} ms, having parsed {} files.", id,
+            with_seperators(started_instant.elapsed().as_millis() as usize), with_seperators(parsed_files)));
+    parse_timings.lock().unwrap().parser_threads.push((id, busy_duration, started_instant.elapsed()));
+    content_info_map
+}
+
+// The stats are made for every language of the language map, and a file of any other language is faulty before it gets here
+fn get_content_info<'a>(content_info_map: &'a mut HashMap<String,LanguageContentInfo>, language_name: &str) -> &'a mut LanguageContentInfo {
+    content_info_map.get_mut(language_name).expect("the stats are made for every language of the language map")
+}
+
+fn get_panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload.downcast_ref::<&str>().map(|x| (*x).to_owned())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_owned())
 }
@@ -0,0 +1,210 @@
+use crate::*;
+
+
+// A metric of '--metrics' in the form '<name> = <expression>', e.g. 'comment_ratio = extra / lines', evaluated after the
+// analysis. The expression combines numbers and the counters of 'budgets::get_counter', optionally as '<language>.<counter>',
+// with +, -, *, / and parentheses.
+#[derive(Debug,PartialEq,Clone)]
+pub struct DerivedMetric {
+    // As it was provided, to be saved in configuration files
+    pub definition: String,
+    pub name: String,
+    expression: Expression
+}
+
+#[derive(Debug,PartialEq,Clone)]
+enum Expression {
+    Number(f64),
+    // Lowercase, with the language if any
+    Counter(Option<String>, String),
+    Negative(Box<Expression>),
+    Binary(Box<Expression>, Operator, Box<Expression>)
+}
+
+#[derive(Debug,PartialEq,Clone,Copy)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide
+}
+
+// The tokens of an expression, of which the operators and the parentheses are single characters
+struct ExpressionParser<'a> {
+    tokens: Vec<&'a str>,
+    index: usize
+}
+
+impl DerivedMetric {
+    // None if the counters are divided by 0, or the value is too big to be a number
+    pub fn evaluate(&self, content_info_map: &HashMap<String,LanguageContentInfo>, languages_metadata_map: &HashMap<String,LanguageMetadata>,
+            final_stats: &FinalStats) -> Option<f64>
+    {
+        self.expression.evaluate(&|language, counter| {
+            budgets::get_counter(language, counter, content_info_map, languages_metadata_map, final_stats) as f64
+        }).filter(|x| x.is_finite())
+    }
+}
+
+impl Expression {
+    fn evaluate(&self, get_counter: &dyn Fn(Option<&str>, &str) -> f64) -> Option<f64> {
+        match self {
+            Expression::Number(x) => Some(*x),
+            Expression::Counter(language, counter) => Some(get_counter(language.as_deref(), counter)),
+            Expression::Negative(x) => x.evaluate(get_counter).map(|x| -x),
+            Expression::Binary(left, operator, right) => {
+                let (left, right) = (left.evaluate(get_counter)?, right.evaluate(get_counter)?);
+                match operator {
+                    Operator::Add => Some(left + right),
+                    Operator::Subtract => Some(left - right),
+                    Operator::Multiply => Some(left * right),
+                    Operator::Divide => if right == 0.0 {None} else {Some(left / right)}
+                }
+            }
+        }
+    }
+}
+
+impl<'a> ExpressionParser<'a> {
+    fn new(s: &'a str) -> Self {
+        let mut tokens = Vec::new();
+        let mut start = None;
+        for (i, c) in s.char_indices() {
+            let is_symbol = "+-*/()".contains(c);
+            if is_symbol || c.is_whitespace() {
+                if let Some(x) = start.take() {
+                    tokens.push(&s[x..i]);
+                }
+                if is_symbol {
+                    tokens.push(&s[i..i + 1]);
+                }
+            } else if start.is_none() {
+                start = Some(i);
+            }
+        }
+        if let Some(x) = start {
+            tokens.push(&s[x..]);
+        }
+        ExpressionParser {tokens, index: 0}
+    }
+
+    // All of the tokens have to be part of the expression
+    fn parse(mut self) -> Option<Expression> {
+        let expression = self.parse_sum()?;
+        if self.index == self.tokens.len() {Some(expression)} else {None}
+    }
+
+    fn parse_sum(&mut self) -> Option<Expression> {
+        let mut expression = self.parse_product()?;
+        while let Some(operator) = self.next_operator(&[("+", Operator::Add), ("-", Operator::Subtract)]) {
+            expression = Expression::Binary(Box::new(expression), operator, Box::new(self.parse_product()?));
+        }
+        Some(expression)
+    }
+
+    fn parse_product(&mut self) -> Option<Expression> {
+        let mut expression = self.parse_factor()?;
+        while let Some(operator) = self.next_operator(&[("*", Operator::Multiply), ("/", Operator::Divide)]) {
+            expression = Expression::Binary(Box::new(expression), operator, Box::new(self.parse_factor()?));
+        }
+        Some(expression)
+    }
+
+    fn parse_factor(&mut self) -> Option<Expression> {
+        let token = *self.tokens.get(self.index)?;
+        self.index += 1;
+        match token {
+            "-" => Some(Expression::Negative(Box::new(self.parse_factor()?))),
+            "(" => {
+                let expression = self.parse_sum()?;
+                if self.tokens.get(self.index) != Some(&")") {
+                    return None;
+                }
+                self.index += 1;
+                Some(expression)
+            },
+            x if x.starts_with(|c: char| c.is_ascii_digit()) => x.replace('_', "").parse::<f64>().ok().map(Expression::Number),
+            x if x.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '.') => {
+                let x = x.to_lowercase();
+                match x.rsplit_once('.') {
+                    Some((language, counter)) => Some(Expression::Counter(Some(utils::get_trimmed_if_not_empty(language)?),
+                            utils::get_trimmed_if_not_empty(counter)?)),
+                    None => Some(Expression::Counter(None, x))
+                }
+            },
+            _ => None
+        }
+    }
+
+    fn next_operator(&mut self, operators: &[(&str, Operator)]) -> Option<Operator> {
+        let token = self.tokens.get(self.index)?;
+        let operator = operators.iter().find(|x| x.0 == *token)?.1;
+        self.index += 1;
+        Some(operator)
+    }
+}
+
+// One or more metrics separated by commas. None if any of them can't be parsed or two of them have the same name.
+pub fn parse_derived_metrics(s: &str) -> Option<Vec<DerivedMetric>> {
+    let metrics = s.split(',').filter(|x| !x.trim().is_empty()).map(parse_derived_metric).collect::<Option<Vec<_>>>()?;
+    let has_repeated_names = metrics.iter().enumerate().any(|(i, x)| metrics[..i].iter().any(|y| y.name == x.name));
+    if metrics.is_empty() || has_repeated_names {None} else {Some(metrics)}
+}
+
+fn parse_derived_metric(s: &str) -> Option<DerivedMetric> {
+    let definition = s.split_whitespace().collect::<Vec<_>>().join(" ");
+    let (name, expression) = definition.split_once('=')?;
+    let name = utils::get_trimmed_if_not_empty(name)?;
+    if !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    let expression = ExpressionParser::new(expression).parse()?;
+    Some(DerivedMetric {definition, name, expression})
+}
+
+// The value of each metric by name, in the order they were defined
+pub fn evaluate_derived_metrics(metrics: &[DerivedMetric], content_info_map: &HashMap<String,LanguageContentInfo>,
+        languages_metadata_map: &HashMap<String,LanguageMetadata>, final_stats: &FinalStats) -> Vec<(String, Option<f64>)>
+{
+    metrics.iter().map(|x| (x.name.clone(), x.evaluate(content_info_map, languages_metadata_map, final_stats))).collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_derived_metrics() {
+        let metrics = parse_derived_metrics(" comment_ratio =  extra / lines, unsafe_per_file = Rust.unsafe/(files - 1_0)").unwrap();
+        assert_eq!(vec!["comment_ratio = extra / lines", "unsafe_per_file = Rust.unsafe/(files - 1_0)"],
+                metrics.iter().map(|x| x.definition.as_str()).collect::<Vec<_>>());
+        assert_eq!("comment_ratio", metrics[0].name);
+        assert_eq!(Expression::Binary(Box::new(Expression::Counter(Some("rust".to_owned()), "unsafe".to_owned())), Operator::Divide,
+                Box::new(Expression::Binary(Box::new(Expression::Counter(None, "files".to_owned())), Operator::Subtract,
+                Box::new(Expression::Number(10.0))))), metrics[1].expression);
+
+        assert_eq!(None, parse_derived_metrics("ratio = code /"));
+        assert_eq!(None, parse_derived_metrics("ratio = (code / lines"));
+        assert_eq!(None, parse_derived_metrics("ratio = code lines"));
+        assert_eq!(None, parse_derived_metrics("code / lines"));
+        assert_eq!(None, parse_derived_metrics("my ratio = code / lines"));
+        assert_eq!(None, parse_derived_metrics("ratio = code / lines, ratio = extra / lines"));
+        assert_eq!(None, parse_derived_metrics(""));
+    }
+
+    #[test]
+    fn test_evaluate_derived_metrics() {
+        let content_info_map = hashmap!["Rust".to_owned() => LanguageContentInfo::new(1000, 800, hashmap!["unsafe".to_owned() => 3]),
+                "C".to_owned() => LanguageContentInfo::new(500, 300, hashmap!["goto".to_owned() => 1])];
+        let languages_metadata_map = hashmap!["Rust".to_owned() => LanguageMetadata::new(10, 40_000),
+                "C".to_owned() => LanguageMetadata::new(4, 10_000)];
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+
+        let metrics = parse_derived_metrics("extra_ratio = extra / (lines - 500), rust_share = 100 * rust.code / code, \
+                weighted = -(unsafe + goto * 2) + 1.5, per_test_line = lines / test_lines").unwrap();
+        assert_eq!(vec![("extra_ratio".to_owned(), Some(0.4)), ("rust_share".to_owned(), Some(72.72727272727273)),
+                ("weighted".to_owned(), Some(-3.5)), ("per_test_line".to_owned(), None)],
+                evaluate_derived_metrics(&metrics, &content_info_map, &languages_metadata_map, &final_stats));
+    }
+}
@@ -0,0 +1,195 @@
+// Splits the files of the languages with embedded regions, e.g. HTML, Vue or markdown with fenced code blocks, so that
+// the lines of every region are counted as the language it is written in. The lines that start and end a region stay
+// in the file, along with the rest of its lines. Only used with '--embedded'.
+
+use std::{borrow::Cow, str};
+
+use crate::*;
+
+// The attribute of the start line that picks the language of a region, e.g. '<script lang="ts">'
+const LANG_ATTRIBUTE : &str = "lang=";
+
+// The lines of a region, in the language that was found for it
+#[derive(Debug,PartialEq)]
+pub struct Region {
+    pub language_name: String,
+    pub contents: String
+}
+
+// The stats of a region of a file, with the size of its lines
+#[derive(Debug,PartialEq)]
+pub struct RegionStats {
+    pub language_name: String,
+    pub stats: FileStats,
+    pub bytes: usize
+}
+
+pub fn has_embedded_regions(language_map: &HashMap<String,Language>, lang_name: &str) -> bool {
+    language_map.get(lang_name).is_some_and(|x| !x.embedded_regions.is_empty())
+}
+
+// The file is parsed without the lines of its regions, and every region on its own, as a file of its language with the
+// path of the file. The files that are not valid UTF-8 are parsed whole. With '--duplicates' the hash is the one of the
+// whole file.
+#[allow(clippy::too_many_arguments)]
+pub fn parse_file_with_regions(path: &Path, contents: Option<&[u8]>, lang_name: &str, buf: &mut String, language_map: Arc<HashMap<String,Language>>,
+        config: &Configuration, plugins: &[Box<dyn MetricPlugin>], regions_stats: &mut Vec<RegionStats>) -> Result<FileStats,FaultyReason>
+{
+    let language = language_map.get(lang_name).ok_or_else(|| FaultyReason::UnknownLanguage(lang_name.to_owned()))?;
+    let contents = match contents {
+        Some(x) => Cow::Borrowed(x),
+        None => Cow::Owned(fs::read(path)?)
+    };
+    let (container, regions) = match str::from_utf8(&contents) {
+        Ok(text) => split_regions(text, &language.embedded_regions, |x| find_region_language(&language_map, x)),
+        Err(_) => (String::new(), Vec::new())
+    };
+    if regions.is_empty() {
+        return file_parser::parse_archive_entry(&contents, path, lang_name, buf, language_map.clone(), config, plugins);
+    }
+
+    let mut file_stats = file_parser::parse_archive_entry(container.as_bytes(), path, lang_name, buf, language_map.clone(), config, plugins)?;
+    if config.duplicates {
+        file_stats.content_hash = Some(hashing::sha256(&contents));
+    }
+    for region in regions {
+        let stats = file_parser::parse_archive_entry(region.contents.as_bytes(), path, &region.language_name, buf, language_map.clone(),
+                config, plugins)?;
+        regions_stats.push(RegionStats {bytes: region.contents.len(), language_name: region.language_name, stats});
+    }
+    Ok(file_stats)
+}
+
+// The language of an identifier, by the extensions of the languages or else by their names, in any case.
+// The documentation formats are left out, since a block of e.g. '```text' is not code.
+fn find_region_language(language_map: &Arc<HashMap<String,Language>>, identifier: &str) -> Option<String> {
+    find_lang_with_this_identifier(language_map, identifier, false)
+            .or_else(|| language_map.keys().filter(|x| x.eq_ignore_ascii_case(identifier)).min().cloned())
+            .filter(|x| !language_map[x].is_documentation())
+}
+
+// Returns the lines that are left in the file and the regions whose language was found, in the order of the file.
+// The regions of an unknown language are left in the file. A start line that also has the end symbol, e.g.
+// '<script src="a.js"></script>', doesn't start a region, and a region that is not closed lasts until the end of the file.
+pub fn split_regions(contents: &str, regions: &[EmbeddedRegion], find_language: impl Fn(&str) -> Option<String>) -> (String, Vec<Region>) {
+    let mut container = String::with_capacity(contents.len());
+    let mut found_regions = Vec::new();
+    // The region that is open, with the language that was found for it and how many of its start symbols were opened
+    // inside it, e.g. the nested templates of Vue
+    let mut open_region : Option<(&EmbeddedRegion, Option<String>, usize)> = None;
+    let mut region_contents = String::new();
+
+    for line in contents.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let (region, language, depth) = match &mut open_region {
+            Some(x) => x,
+            None => {
+                container.push_str(line);
+                if let Some(region) = regions.iter().find(|x| starts_with_ignore_case(trimmed, &x.start)) {
+                    let rest = &trimmed[region.start.len()..];
+                    if region.start == region.end || !rest.to_ascii_lowercase().contains(&region.end.to_ascii_lowercase()) {
+                        let language = get_region_identifier(region, rest).and_then(|x| find_language(&x));
+                        open_region = Some((region, language, 0));
+                    }
+                }
+                continue;
+            }
+        };
+
+        if starts_with_ignore_case(trimmed, &region.end) {
+            if *depth == 0 {
+                container.push_str(line);
+                if let Some(language_name) = language.take() {
+                    found_regions.push(Region {language_name, contents: std::mem::take(&mut region_contents)});
+                }
+                open_region = None;
+                continue;
+            }
+            *depth -= 1;
+        } else if region.start != region.end && starts_with_ignore_case(trimmed, &region.start) {
+            *depth += 1;
+        }
+        if language.is_some() {
+            region_contents.push_str(line);
+        } else {
+            container.push_str(line);
+        }
+    }
+    if let Some((_, Some(language_name), _)) = open_region {
+        found_regions.push(Region {language_name, contents: region_contents});
+    }
+    (container, found_regions)
+}
+
+// The value of the 'lang' attribute, or else the identifier of the region, or else the word after the start symbol,
+// e.g. 'rust' of '```rust,ignore' or 'r' of '```{r}'
+fn get_region_identifier(region: &EmbeddedRegion, rest: &str) -> Option<String> {
+    let lowercase_rest = rest.to_ascii_lowercase();
+    if let Some(index) = lowercase_rest.find(LANG_ATTRIBUTE) {
+        let value = rest[index + LANG_ATTRIBUTE.len()..].trim_start_matches(['"', '\'']);
+        let value = value.split(|x: char| x == '"' || x == '\'' || x == '>' || x.is_whitespace()).next().unwrap_or_default();
+        if !value.is_empty() {
+            return Some(value.to_owned());
+        }
+    }
+    if let Some(identifier) = &region.identifier {
+        return Some(identifier.to_owned());
+    }
+    rest.trim().trim_start_matches('{').split(|x: char| x == ',' || x == '}' || x.is_whitespace()).next()
+            .filter(|x| !x.is_empty()).map(|x| x.to_owned())
+}
+
+fn starts_with_ignore_case(line: &str, symbol: &str) -> bool {
+    line.get(..symbol.len()).is_some_and(|x| x.eq_ignore_ascii_case(symbol))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(start: &str, end: &str, identifier: Option<&str>) -> EmbeddedRegion {
+        EmbeddedRegion {start: start.to_owned(), end: end.to_owned(), identifier: identifier.map(|x| x.to_owned())}
+    }
+
+    fn find_language(identifier: &str) -> Option<String> {
+        match identifier {
+            "js" => Some("JS".to_owned()),
+            "ts" => Some("TS".to_owned()),
+            "css" => Some("CSS".to_owned()),
+            "rust" => Some("Rust".to_owned()),
+            _ => None
+        }
+    }
+
+    fn found(language_name: &str, contents: &str) -> Region {
+        Region {language_name: language_name.to_owned(), contents: contents.to_owned()}
+    }
+
+    #[test]
+    fn test_split_regions_of_html() {
+        let regions = [region("<script", "</script>", Some("js")), region("<style", "</style>", Some("css"))];
+        let html = "<html>\n  <SCRIPT src=\"a.js\"></script>\n  <script lang=\"ts\">\n    let a = 1;\n  </script>\n\
+                <style>\nbody {}\n</style>\n<script type=\"module\">\nf();\n";
+        let (container, found_regions) = split_regions(html, &regions, find_language);
+        assert_eq!("<html>\n  <SCRIPT src=\"a.js\"></script>\n  <script lang=\"ts\">\n  </script>\n<style>\n</style>\n<script type=\"module\">\n",
+                container);
+        assert_eq!(vec![found("TS", "    let a = 1;\n"), found("CSS", "body {}\n"), found("JS", "f();\n")], found_regions);
+    }
+
+    #[test]
+    fn test_split_regions_of_vue_and_markdown() {
+        let regions = [region("<template", "</template>", Some("html")), region("<script", "</script>", Some("js"))];
+        let vue = "<template>\n  <template v-if=\"a\">\n  </template>\n</template>\n<script>\nexport default {}\n</script>\n";
+        let (container, found_regions) = split_regions(vue, &regions, find_language);
+        // The template is html, which is unknown here, so it stays in the file, up to its own end
+        assert_eq!("<template>\n  <template v-if=\"a\">\n  </template>\n</template>\n<script>\n</script>\n", container);
+        assert_eq!(vec![found("JS", "export default {}\n")], found_regions);
+
+        let regions = [region("```", "```", None), region("~~~", "~~~", None)];
+        let markdown = "# Title\n```rust,ignore\nfn main() {}\n```\n~~~\nplain\n~~~\n```python\nprint()\n```\n";
+        let (container, found_regions) = split_regions(markdown, &regions, find_language);
+        assert_eq!("# Title\n```rust,ignore\n```\n~~~\nplain\n~~~\n```python\nprint()\n```\n", container);
+        assert_eq!(vec![found("Rust", "fn main() {}\n")], found_regions);
+    }
+}
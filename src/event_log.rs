@@ -0,0 +1,82 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use crate::*;
+use crate::notifier::escape_json_string;
+
+
+// Used with '--event-log', to write every event of a run as a line of json (NDJSON), so that it can be seen afterwards
+// why a file was or wasn't counted. The events are passed on to the observer of the run.
+pub struct EventLogObserver {
+    inner: ObserverRef,
+    writer: Mutex<BufWriter<File>>
+}
+
+impl EventLogObserver {
+    pub fn new(file: File, inner: ObserverRef) -> Self {
+        EventLogObserver {inner, writer: Mutex::new(BufWriter::new(file))}
+    }
+}
+
+impl AnalysisObserver for EventLogObserver {
+    fn on_event(&self, event: &AnalysisEvent) {
+        {
+            let mut writer = self.writer.lock().unwrap();
+            // A log that can't be written to shouldn't fail the run
+            let _ = writeln!(writer, "{}", to_json_line(event));
+            if *event == AnalysisEvent::PhaseChanged(AnalysisPhase::Finished) {
+                let _ = writer.flush();
+            }
+        }
+        self.inner.on_event(event);
+    }
+}
+
+fn to_json_line(event: &AnalysisEvent) -> String {
+    let path = |x: &Path| escape_json_string(&x.to_string_lossy());
+    match event {
+        AnalysisEvent::PhaseChanged(phase) => format!("{{\"event\":\"phase\",\"phase\":\"{}\"}}", get_phase_name(*phase)),
+        AnalysisEvent::FileDiscovered {path: p, language} =>
+            format!("{{\"event\":\"discovered\",\"path\":\"{}\",\"language\":\"{}\"}}", path(p), escape_json_string(language)),
+        AnalysisEvent::FileSkipped {path: p, reason} =>
+            format!("{{\"event\":\"skipped\",\"kind\":\"file\",\"path\":\"{}\",\"reason\":\"{}\"}}", path(p), reason.as_str()),
+        AnalysisEvent::DirSkipped {path: p, reason} =>
+            format!("{{\"event\":\"skipped\",\"kind\":\"dir\",\"path\":\"{}\",\"reason\":\"{}\"}}", path(p), reason.as_str()),
+        AnalysisEvent::FileParsed {path: p, language, stats, bytes} =>
+            format!("{{\"event\":\"parsed\",\"path\":\"{}\",\"language\":\"{}\",\"lines\":{},\"code_lines\":{},\"bytes\":{}}}",
+                    path(p), escape_json_string(language), stats.lines, stats.code_lines, bytes),
+        AnalysisEvent::FileFaulty(x) =>
+            format!("{{\"event\":\"faulty\",\"path\":\"{}\",\"language\":\"{}\",\"error\":\"{}\"}}", path(&x.path),
+                    escape_json_string(&x.language_name), escape_json_string(&x.error.message()))
+    }
+}
+
+fn get_phase_name(phase: AnalysisPhase) -> &'static str {
+    match phase {
+        AnalysisPhase::Searching => "searching",
+        AnalysisPhase::Parsing => "parsing",
+        AnalysisPhase::Summarizing => "summarizing",
+        AnalysisPhase::Finished => "finished"
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_json_line() {
+        assert_eq!("{\"event\":\"phase\",\"phase\":\"searching\"}", to_json_line(&AnalysisEvent::PhaseChanged(AnalysisPhase::Searching)));
+        assert_eq!("{\"event\":\"skipped\",\"kind\":\"dir\",\"path\":\"/p/target\",\"reason\":\"build output\"}",
+                to_json_line(&AnalysisEvent::DirSkipped {path: Path::new("/p/target"), reason: SkipReason::BuildOutput}));
+        assert_eq!("{\"event\":\"discovered\",\"path\":\"/p/a \\\"b\\\".rs\",\"language\":\"Rust\"}",
+                to_json_line(&AnalysisEvent::FileDiscovered {path: Path::new("/p/a \"b\".rs"), language: "Rust"}));
+
+        let mut stats = FileStats::default();
+        stats.lines = 10;
+        stats.code_lines = 7;
+        assert_eq!("{\"event\":\"parsed\",\"path\":\"/p/a.rs\",\"language\":\"Rust\",\"lines\":10,\"code_lines\":7,\"bytes\":120}",
+                to_json_line(&AnalysisEvent::FileParsed {path: Path::new("/p/a.rs"), language: "Rust", stats: &stats, bytes: 120}));
+    }
+}
@@ -0,0 +1,488 @@
+use crate::{*, json::Json, warnings::RunWarning};
+
+
+// The totals and the languages of the results, for the formats that are read by other programs: json for '/analyze'
+// of 'serve', and xml and yaml for '--output'. The languages are in order of name, each with its keywords in order of name.
+#[derive(Debug,PartialEq)]
+pub struct Results<'a> {
+    dirs: &'a [String],
+    totals: Counts,
+    languages: Vec<LanguageResults<'a>>,
+    // The tree of '--dir-breakdown', only in json. Empty without it
+    directories: Vec<DirectoryResults>,
+    // Of the run, only in json
+    warnings: &'a [RunWarning],
+    // The values of '--metrics' by name, only in json. None for a metric without a value, e.g. of a division by 0
+    metrics: &'a [(String, Option<f64>)]
+}
+
+// A directory with the counts of its files and of its subdirectories, which are its children. The files in directories
+// deeper than '--dir-breakdown' are counted in the one at that depth, and with '--top' the children after the biggest ones
+// are summed up in 'others', so that the tree can be drawn as a treemap without thousands of tiny directories.
+#[derive(Debug,PartialEq)]
+struct DirectoryResults {
+    name: String,
+    counts: Counts,
+    children: Vec<DirectoryResults>
+}
+
+#[derive(Debug,PartialEq)]
+struct LanguageResults<'a> {
+    name: &'a str,
+    counts: Counts,
+    keywords: Vec<(&'a str, usize)>
+}
+
+// The results of several runs combined by 'merge', e.g. of the shards of a monorepo that were analyzed on different machines.
+// The counts of the same language are summed, and so are the occurrences of the same keyword of it.
+#[derive(Debug,Default)]
+pub struct MergedResults {
+    dirs: Vec<String>,
+    content_info_map: HashMap<String,LanguageContentInfo>,
+    languages_metadata_map: HashMap<String,LanguageMetadata>
+}
+
+#[derive(Debug,PartialEq)]
+pub enum MergeError {
+    // The path of the file, with the message of the io error
+    Unreadable(String, String),
+    // The path of the file, with what was wrong with its json
+    Invalid(String, String)
+}
+
+#[derive(Debug,PartialEq,Default)]
+struct Counts {
+    files: usize,
+    lines: usize,
+    code_lines: usize,
+    extra_lines: usize,
+    bytes: usize
+}
+
+impl<'a> Results<'a> {
+    pub fn new(dirs: &'a [String], content_info_map: &'a HashMap<String,LanguageContentInfo>,
+            languages_metadata_map: &'a HashMap<String,LanguageMetadata>, final_stats: &FinalStats) -> Self
+    {
+        let mut names = languages_metadata_map.keys().filter(|x| content_info_map.contains_key(*x)).collect::<Vec<_>>();
+        names.sort();
+        let languages = names.into_iter().map(|name| {
+            let (content_info, metadata) = (&content_info_map[name], &languages_metadata_map[name]);
+            let mut keywords = content_info.keyword_occurences.iter().map(|(x, occurrences)| (x.as_str(), *occurrences)).collect::<Vec<_>>();
+            keywords.sort();
+            LanguageResults {name, keywords, counts: Counts {files: metadata.files, lines: content_info.lines, code_lines: content_info.code_lines,
+                    extra_lines: content_info.lines - content_info.code_lines, bytes: metadata.bytes}}
+        }).collect();
+
+        Results {dirs, languages, totals: Counts {files: final_stats.files, lines: final_stats.lines, code_lines: final_stats.code_lines,
+                extra_lines: final_stats.extra_lines, bytes: final_stats.bytes_size}, directories: Vec::new(), warnings: &[], metrics: &[]}
+    }
+
+    // The rows of 'make_dir_tree' as a tree, with at most 'top' children per directory, apart from 'others'
+    pub fn with_directories(mut self, dir_tree: &[(usize, String, DirStats)], top: Option<usize>) -> Self {
+        self.directories = make_directories(&make_dir_nodes(dir_tree), 0, top);
+        self
+    }
+
+    pub fn with_warnings(mut self, warnings: &'a [RunWarning]) -> Self {
+        self.warnings = warnings;
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: &'a [(String, Option<f64>)]) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    // On a single line, e.g. {"dirs": ["/project"], "files": 14, ..., "languages": [{"name": "Rust", ..., "keywords": {"unsafe": 3}}]}
+    pub fn to_json(&self) -> String {
+        let languages = self.languages.iter().map(|x| {
+            format!("{{\"name\": \"{}\", {}, \"keywords\": {{{}}}}}", notifier::escape_json_string(x.name), x.counts.to_json(),
+                    x.keywords.iter().map(|(keyword, occurrences)| format!("\"{}\": {}", notifier::escape_json_string(keyword), occurrences))
+                    .collect::<Vec<_>>().join(", "))
+        }).collect::<Vec<_>>();
+
+        let directories = if self.directories.is_empty() {String::new()} else {
+            format!(", \"by_directory\": [{}]", self.directories.iter().map(DirectoryResults::to_json).collect::<Vec<_>>().join(", "))
+        };
+        let warnings = if self.warnings.is_empty() {String::new()} else {
+            format!(", \"warnings\": [{}]", self.warnings.iter().map(RunWarning::to_json).collect::<Vec<_>>().join(", "))
+        };
+        let metrics = if self.metrics.is_empty() {String::new()} else {
+            format!(", \"metrics\": {{{}}}", self.metrics.iter().map(|(name, value)| format!("\"{}\": {}", notifier::escape_json_string(name),
+                    value.map_or("null".to_owned(), |x| x.to_string()))).collect::<Vec<_>>().join(", "))
+        };
+        format!("{{\"dirs\": [{}], {}{}, \"languages\": [{}]{}{}}}",
+                self.dirs.iter().map(|x| format!("\"{}\"", notifier::escape_json_string(x))).collect::<Vec<_>>().join(", "),
+                self.totals.to_json(), metrics, languages.join(", "), directories, warnings)
+    }
+
+    // The counts are attributes of the results and of each language, e.g.
+    //
+    // <?xml version="1.0" encoding="UTF-8"?>
+    // <results files="14" lines="1500" code_lines="1100" extra_lines="400" bytes="50000">
+    //   <dirs>
+    //     <dir>/project</dir>
+    //   </dirs>
+    //   <languages>
+    //     <language name="Rust" files="10" lines="1000" code_lines="800" extra_lines="200" bytes="40000">
+    //       <keyword name="unsafe">3</keyword>
+    //     </language>
+    //   </languages>
+    // </results>
+    pub fn to_xml(&self) -> String {
+        let mut xml = format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<results {}>\n  <dirs>\n", self.totals.to_xml_attributes());
+        for dir in self.dirs {
+            xml += &format!("    <dir>{}</dir>\n", escape_xml(dir));
+        }
+        xml += "  </dirs>\n  <languages>\n";
+        for language in &self.languages {
+            let start = format!("    <language name=\"{}\" {}", escape_xml(language.name), language.counts.to_xml_attributes());
+            if language.keywords.is_empty() {
+                xml += &format!("{}/>\n", start);
+                continue;
+            }
+            xml += &format!("{}>\n", start);
+            for (keyword, occurrences) in &language.keywords {
+                xml += &format!("      <keyword name=\"{}\">{}</keyword>\n", escape_xml(keyword), occurrences);
+            }
+            xml += "    </language>\n";
+        }
+        xml + "  </languages>\n</results>\n"
+    }
+
+    // The strings are double-quoted with the escapes of json, which are valid in yaml too, e.g.
+    //
+    // dirs:
+    //   - "/project"
+    // files: 14
+    // ...
+    // languages:
+    //   - name: "Rust"
+    //     files: 10
+    //     ...
+    //     keywords:
+    //       "unsafe": 3
+    pub fn to_yaml(&self) -> String {
+        let mut yaml = String::from("dirs:\n");
+        for dir in self.dirs {
+            yaml += &format!("  - \"{}\"\n", notifier::escape_json_string(dir));
+        }
+        yaml += &self.totals.to_yaml("");
+        yaml += if self.languages.is_empty() {"languages: []\n"} else {"languages:\n"};
+        for language in &self.languages {
+            yaml += &format!("  - name: \"{}\"\n", notifier::escape_json_string(language.name));
+            yaml += &language.counts.to_yaml("    ");
+            yaml += if language.keywords.is_empty() {"    keywords: {}\n"} else {"    keywords:\n"};
+            for (keyword, occurrences) in &language.keywords {
+                yaml += &format!("      \"{}\": {}\n", notifier::escape_json_string(keyword), occurrences);
+            }
+        }
+        yaml
+    }
+}
+
+impl MergedResults {
+    pub fn read(paths: &[String]) -> Result<Self, MergeError> {
+        let mut merged = MergedResults::default();
+        for path in paths {
+            let contents = fs::read_to_string(path).map_err(|x| MergeError::Unreadable(path.to_owned(), x.to_string()))?;
+            merged.add_json(&contents).map_err(|x| MergeError::Invalid(path.to_owned(), x))?;
+        }
+        Ok(merged)
+    }
+
+    // The json of '--output json' or of 'serve'. Nothing is added if it is not valid.
+    pub fn add_json(&mut self, contents: &str) -> Result<(), String> {
+        let json = json::parse(contents)?;
+        let dirs = match json.get("dirs") {
+            Some(Json::Array(x)) => x.iter().filter_map(Json::as_str).map(|x| x.to_owned()).collect::<Vec<_>>(),
+            _ => return Err("there are no dirs".to_owned())
+        };
+        let languages = match json.get("languages") {
+            Some(Json::Array(x)) => x,
+            _ => return Err("there are no languages".to_owned())
+        };
+
+        let mut parsed_languages = Vec::with_capacity(languages.len());
+        for language in languages {
+            let name = language.get("name").and_then(Json::as_str).ok_or_else(|| "a language has no name".to_owned())?;
+            let count = |key: &str| language.get(key).and_then(Json::as_usize).ok_or_else(|| format!("the {} of {} are not valid", key, name));
+            let keywords = match language.get("keywords") {
+                Some(Json::Object(x)) => x.iter().map(|(keyword, occurrences)| occurrences.as_usize().map(|x| (keyword.to_owned(), x))
+                        .ok_or_else(|| format!("the occurrences of '{}' of {} are not valid", keyword, name))).collect::<Result<Vec<_>,_>>()?,
+                _ => Vec::new()
+            };
+            parsed_languages.push((name, LanguageMetadata::new(count("files")?, count("bytes")?), count("lines")?, count("code_lines")?, keywords));
+        }
+
+        for dir in dirs {
+            if !self.dirs.contains(&dir) {
+                self.dirs.push(dir);
+            }
+        }
+        for (name, metadata, lines, code_lines, keywords) in parsed_languages {
+            self.languages_metadata_map.entry(name.to_owned()).or_default().add_metadata(&metadata);
+            let content_info = self.content_info_map.entry(name.to_owned()).or_insert_with(|| LanguageContentInfo::new(0, 0, Vec::new()));
+            content_info.lines += lines;
+            content_info.code_lines += code_lines;
+            for (keyword, occurrences) in keywords {
+                *content_info.keyword_occurences.entry(keyword).or_insert(0) += occurrences;
+            }
+        }
+        Ok(())
+    }
+
+    // As for '--fail-if', of the totals of the results
+    pub fn get_counter(&self, language: Option<&str>, metric: &str) -> usize {
+        let final_stats = FinalStats::calculate(&self.content_info_map, &self.languages_metadata_map, false);
+        budgets::get_counter(language, metric, &self.content_info_map, &self.languages_metadata_map, &final_stats)
+    }
+
+    // In the same format as the results that were merged
+    pub fn to_json(&self) -> String {
+        let final_stats = FinalStats::calculate(&self.content_info_map, &self.languages_metadata_map, false);
+        Results::new(&self.dirs, &self.content_info_map, &self.languages_metadata_map, &final_stats).to_json()
+    }
+}
+
+impl DirectoryResults {
+    // e.g. {"name": "src", "files": 10, ..., "children": [{"name": "parser", ..., "children": []}]}
+    fn to_json(&self) -> String {
+        format!("{{\"name\": \"{}\", {}, \"children\": [{}]}}", notifier::escape_json_string(&self.name), self.counts.to_json(),
+                self.children.iter().map(DirectoryResults::to_json).collect::<Vec<_>>().join(", "))
+    }
+}
+
+fn make_directories(nodes: &[DirNode], level: usize, top: Option<usize>) -> Vec<DirectoryResults> {
+    let mut directories = nodes.iter().map(|x| DirectoryResults {name: x.name.to_owned(), counts: Counts::from(&x.stats),
+            children: make_directories(&x.children, level + 1, top)}).collect::<Vec<_>>();
+
+    // A single directory is not grouped as "others"
+    match top {
+        Some(top) if level != 0 && directories.len() > top + 1 => {
+            let mut others = DirectoryResults {name: "others".to_owned(), counts: Counts::default(), children: Vec::new()};
+            for directory in directories.split_off(top) {
+                others.counts.add(&directory.counts);
+            }
+            directories.push(others);
+            directories
+        },
+        _ => directories
+    }
+}
+
+impl From<&DirStats> for Counts {
+    fn from(stats: &DirStats) -> Self {
+        Counts {files: stats.files, lines: stats.lines, code_lines: stats.code_lines, extra_lines: stats.lines - stats.code_lines, bytes: stats.bytes}
+    }
+}
+
+impl Counts {
+    fn add(&mut self, other: &Counts) {
+        self.files += other.files;
+        self.lines += other.lines;
+        self.code_lines += other.code_lines;
+        self.extra_lines += other.extra_lines;
+        self.bytes += other.bytes;
+    }
+
+    fn named(&self) -> [(&'static str, usize); 5] {
+        [("files", self.files), ("lines", self.lines), ("code_lines", self.code_lines), ("extra_lines", self.extra_lines), ("bytes", self.bytes)]
+    }
+
+    fn to_json(&self) -> String {
+        self.named().iter().map(|(name, value)| format!("\"{}\": {}", name, value)).collect::<Vec<_>>().join(", ")
+    }
+
+    fn to_xml_attributes(&self) -> String {
+        self.named().iter().map(|(name, value)| format!("{}=\"{}\"", name, value)).collect::<Vec<_>>().join(" ")
+    }
+
+    fn to_yaml(&self, indentation: &str) -> String {
+        self.named().iter().map(|(name, value)| format!("{}{}: {}\n", indentation, name, value)).collect()
+    }
+}
+
+impl Formatted for MergeError {
+    fn formatted(&self) -> ColoredString {
+        match self {
+            Self::Unreadable(path, x) => format!("Unable to read '{}': {}", path, x).red(),
+            Self::Invalid(path, x) => format!("'{}' is not json of the results: {}", path, x).red()
+        }
+    }
+}
+
+impl MergeError {
+    // The code that the program exits with, as listed in the README, like the one of a failed run
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Unreadable(..) => 5,
+            Self::Invalid(..) => 6
+        }
+    }
+}
+
+impl_display_from_formatted!(MergeError);
+
+impl std::error::Error for MergeError {}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").replace('\'', "&apos;")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_maps() -> (HashMap<String,LanguageContentInfo>, HashMap<String,LanguageMetadata>) {
+        (hashmap!["Rust".to_owned() => LanguageContentInfo::new(1000, 800, hashmap!["unsafe".to_owned() => 3]),
+                "C".to_owned() => LanguageContentInfo::new(500, 300, HashMap::new())],
+        hashmap!["Rust".to_owned() => LanguageMetadata::new(10, 40_000), "C".to_owned() => LanguageMetadata::new(4, 10_000)])
+    }
+
+    #[test]
+    fn test_to_json() {
+        let (content_info_map, languages_metadata_map) = get_maps();
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+        let dirs = ["/home/user/\"a\"".to_owned()];
+
+        assert_eq!("{\"dirs\": [\"/home/user/\\\"a\\\"\"], \"files\": 14, \"lines\": 1500, \"code_lines\": 1100, \"extra_lines\": 400, \"bytes\": 50000, \
+                \"languages\": [{\"name\": \"C\", \"files\": 4, \"lines\": 500, \"code_lines\": 300, \"extra_lines\": 200, \"bytes\": 10000, \"keywords\": {}}, \
+                {\"name\": \"Rust\", \"files\": 10, \"lines\": 1000, \"code_lines\": 800, \"extra_lines\": 200, \"bytes\": 40000, \"keywords\": {\"unsafe\": 3}}]}",
+                Results::new(&dirs, &content_info_map, &languages_metadata_map, &final_stats).to_json());
+    }
+
+    #[test]
+    fn test_with_directories() {
+        let (content_info_map, languages_metadata_map) = (HashMap::new(), HashMap::new());
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+        let dirs = ["/p".to_owned()];
+        let stats = |files: usize, lines: usize| DirStats {files, lines, code_lines: lines / 2, bytes: lines * 10};
+        let dir_tree = [(0, "/p".to_owned(), stats(7, 700)), (1, "src".to_owned(), stats(4, 400)), (2, "parser".to_owned(), stats(2, 300)),
+                (1, "tests".to_owned(), stats(2, 200)), (1, "docs".to_owned(), stats(1, 100))];
+        let json = |top: Option<usize>| Results::new(&dirs, &content_info_map, &languages_metadata_map, &final_stats)
+                .with_directories(&dir_tree, top).to_json();
+        let directory = |name: &str, files: usize, lines: usize, children: &str| format!("{{\"name\": \"{}\", \"files\": {}, \"lines\": {}, \
+                \"code_lines\": {}, \"extra_lines\": {}, \"bytes\": {}, \"children\": [{}]}}", name, files, lines, lines / 2, lines / 2, lines * 10, children);
+
+        let src = directory("src", 4, 400, &directory("parser", 2, 300, ""));
+        assert!(json(None).ends_with(&format!("\"languages\": [], \"by_directory\": [{}]}}", directory("/p", 7, 700,
+                &[src.as_str(), &directory("tests", 2, 200, ""), &directory("docs", 1, 100, "")].join(", ")))));
+        assert!(json(Some(1)).ends_with(&format!("\"by_directory\": [{}]}}", directory("/p", 7, 700,
+                &[src.as_str(), &directory("others", 3, 300, "")].join(", ")))));
+        // A single directory is not grouped as "others"
+        assert_eq!(json(None), json(Some(2)));
+        assert!(!Results::new(&dirs, &content_info_map, &languages_metadata_map, &final_stats).with_directories(&[], None).to_json()
+                .contains("by_directory"));
+    }
+
+    #[test]
+    fn test_with_warnings() {
+        let (content_info_map, languages_metadata_map) = (HashMap::new(), HashMap::new());
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+        let dirs = ["/p".to_owned()];
+        let warnings = [RunWarning {kind: warnings::WarningKind::UnreadableDir, message: "'/p/a': gone".to_owned()},
+                RunWarning {kind: warnings::WarningKind::ClampedThreads, message: "too many".to_owned()}];
+        assert!(Results::new(&dirs, &content_info_map, &languages_metadata_map, &final_stats).with_warnings(&warnings).to_json()
+                .ends_with("\"languages\": [], \"warnings\": [{\"kind\": \"unreadable directory\", \"message\": \"'/p/a': gone\"}, \
+                {\"kind\": \"clamped threads\", \"message\": \"too many\"}]}"));
+        assert!(!Results::new(&dirs, &content_info_map, &languages_metadata_map, &final_stats).to_json().contains("warnings"));
+    }
+
+    #[test]
+    fn test_with_metrics() {
+        let (content_info_map, languages_metadata_map) = (HashMap::new(), HashMap::new());
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+        let dirs = ["/p".to_owned()];
+        let metrics = [("extra_ratio".to_owned(), Some(0.25)), ("per_test_line".to_owned(), None), ("files".to_owned(), Some(3.0))];
+        assert!(Results::new(&dirs, &content_info_map, &languages_metadata_map, &final_stats).with_metrics(&metrics).to_json()
+                .ends_with("\"bytes\": 0, \"metrics\": {\"extra_ratio\": 0.25, \"per_test_line\": null, \"files\": 3}, \"languages\": []}"));
+        assert!(!Results::new(&dirs, &content_info_map, &languages_metadata_map, &final_stats).to_json().contains("metrics"));
+    }
+
+    #[test]
+    fn test_to_xml() {
+        let (content_info_map, languages_metadata_map) = get_maps();
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+        let dirs = ["/home/<a> & 'b'".to_owned()];
+
+        assert_eq!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<results files=\"14\" lines=\"1500\" code_lines=\"1100\" extra_lines=\"400\" bytes=\"50000\">
+  <dirs>
+    <dir>/home/&lt;a&gt; &amp; &apos;b&apos;</dir>
+  </dirs>
+  <languages>
+    <language name=\"C\" files=\"4\" lines=\"500\" code_lines=\"300\" extra_lines=\"200\" bytes=\"10000\"/>
+    <language name=\"Rust\" files=\"10\" lines=\"1000\" code_lines=\"800\" extra_lines=\"200\" bytes=\"40000\">
+      <keyword name=\"unsafe\">3</keyword>
+    </language>
+  </languages>
+</results>
+", Results::new(&dirs, &content_info_map, &languages_metadata_map, &final_stats).to_xml());
+    }
+
+    #[test]
+    fn test_to_yaml() {
+        let (content_info_map, languages_metadata_map) = get_maps();
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+        let dirs = ["/home/\"a\": b".to_owned()];
+
+        assert_eq!("dirs:
+  - \"/home/\\\"a\\\": b\"
+files: 14
+lines: 1500
+code_lines: 1100
+extra_lines: 400
+bytes: 50000
+languages:
+  - name: \"C\"
+    files: 4
+    lines: 500
+    code_lines: 300
+    extra_lines: 200
+    bytes: 10000
+    keywords: {}
+  - name: \"Rust\"
+    files: 10
+    lines: 1000
+    code_lines: 800
+    extra_lines: 200
+    bytes: 40000
+    keywords:
+      \"unsafe\": 3
+", Results::new(&dirs, &content_info_map, &languages_metadata_map, &final_stats).to_yaml());
+
+        let (content_info_map, languages_metadata_map) = (HashMap::new(), HashMap::new());
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+        assert!(Results::new(&dirs, &content_info_map, &languages_metadata_map, &final_stats).to_yaml().ends_with("bytes: 0\nlanguages: []\n"));
+    }
+
+    #[test]
+    fn test_merge() {
+        let (content_info_map, languages_metadata_map) = get_maps();
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+        let dirs = ["/shard/a".to_owned()];
+        let first = Results::new(&dirs, &content_info_map, &languages_metadata_map, &final_stats).to_json();
+        let second = "{\"dirs\": [\"/shard/b\"], \"files\": 2, \"lines\": 100, \"code_lines\": 60, \"extra_lines\": 40, \"bytes\": 3000, \
+                \"languages\": [{\"name\": \"Rust\", \"files\": 2, \"lines\": 100, \"code_lines\": 60, \"extra_lines\": 40, \"bytes\": 3000, \
+                \"keywords\": {\"unsafe\": 1, \"async\": 5}}]}";
+
+        let mut merged = MergedResults::default();
+        merged.add_json(&first).unwrap();
+        merged.add_json(second).unwrap();
+        assert_eq!("{\"dirs\": [\"/shard/a\", \"/shard/b\"], \"files\": 16, \"lines\": 1600, \"code_lines\": 1160, \"extra_lines\": 440, \"bytes\": 53000, \
+                \"languages\": [{\"name\": \"C\", \"files\": 4, \"lines\": 500, \"code_lines\": 300, \"extra_lines\": 200, \"bytes\": 10000, \"keywords\": {}}, \
+                {\"name\": \"Rust\", \"files\": 12, \"lines\": 1100, \"code_lines\": 860, \"extra_lines\": 240, \"bytes\": 43000, \
+                \"keywords\": {\"async\": 5, \"unsafe\": 4}}]}", merged.to_json());
+
+        // A file that is not valid leaves the merged results as they were
+        assert!(merged.add_json("{\"dirs\": [], \"languages\": [{\"name\": \"Go\", \"files\": -1}]}").is_err());
+        assert!(merged.add_json("[]").is_err());
+        assert_eq!(2, merged.languages_metadata_map.len());
+        assert_eq!(Err(MergeError::Unreadable("test_dir/missing.json".to_owned(), "No such file or directory (os error 2)".to_owned())),
+                MergedResults::read(&["test_dir/missing.json".to_owned()]).map(|x| x.to_json()));
+        assert_eq!(5, MergeError::Unreadable(String::new(), String::new()).exit_code());
+        assert_eq!(6, MergeError::Invalid(String::new(), String::new()).exit_code());
+    }
+}
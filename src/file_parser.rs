@@ -1,65 +1,1154 @@
-use std::{io::{BufRead, BufReader}, str::{self, MatchIndices}};
+use std::{borrow::Cow, io::{self, BufRead, BufReader, Read, Seek, SeekFrom}, mem, panic, str::{self, MatchIndices}, thread};
 
 use crate::*;
 
 
-pub fn parse_file(path: &Path, lang_name: &str, buf: &mut String, language_map: Arc<HashMap<String,Language>>, config: &Configuration)
--> Result<FileStats,String> 
+// How many lines are parsed between the checks of '--file-timeout'
+const TIMEOUT_CHECK_LINES : usize = 1024;
+// The comments of a file after this many lines are not looked at for a license
+const LICENSE_HEADER_MAX_LINES : usize = 40;
+const SPDX_IDENTIFIER : &str = "SPDX-License-Identifier:";
+// Looked for in the lowercase text of the header, the first that is found is taken. The GPL ones are told apart by
+// their version afterwards
+const LICENSE_PHRASES : [(&str, &str); 5] = [("permission is hereby granted, free of charge", "MIT"), ("apache license, version 2.0", "Apache-2.0"),
+        ("gnu affero general public license", "AGPL-3.0"), ("gnu lesser general public license", "LGPL"), ("gnu general public license", "GPL")];
+// The comments before the first code line, up to this many lines, are looked at for a marker of generated code
+const GENERATED_HEADER_MAX_LINES : usize = 40;
+// 'DO NOT EDIT' is in the headers of Go ('Code generated ... DO NOT EDIT.') and of the protocol buffer compiler
+const GENERATED_MARKERS : [&str; 3] = ["@generated", "DO NOT EDIT", "<auto-generated"];
+// Files bigger than this are read in blocks of bytes, to avoid a read call and a String copy for every line
+const LARGE_FILE_BLOCK_SIZE : usize = 1 << 20;
+// The segments of '--split-threshold' are at least this big, so that a thread is not started for a few lines
+const MIN_SEGMENT_BYTES : usize = 4 << 20;
+// A line longer than this makes its file minified, and is parsed in chunks of about this size instead of being read whole
+const MINIFIED_LINE_BYTES : usize = 16 * 1024;
+
+const UTF8_BOM : [u8; 3] = [0xEF, 0xBB, 0xBF];
+const UTF16_LE_BOM : [u8; 2] = [0xFF, 0xFE];
+const UTF16_BE_BOM : [u8; 2] = [0xFE, 0xFF];
+
+// How a line ended. A '\r\n' is a newline, with the '\r' left in the line
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum LineEnd {
+    Newline,
+    // Of the old Mac files
+    LoneCr,
+    EndOfFile
+}
+
+#[derive(Debug, PartialEq)]
+enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be
+}
+
+// Hashes the bytes as the parser consumes them, so that the files of '--duplicates' are not read twice
+struct HashingReader<R> {
+    inner: R,
+    hasher: hashing::Sha256
+}
+
+pub fn parse_file(path: &Path, lang_name: &str, buf: &mut String, language_map: Arc<HashMap<String,Language>>, config: &Configuration,
+        plugins: &[Box<dyn MetricPlugin>]) -> Result<FileStats,FaultyReason> 
+{
+    let language = get_language(&language_map, lang_name)?;
+    let file = File::open(path)?;
+    let file_size = file.metadata().map_or(0, |m| m.len() as usize);
+    let segments = get_segment_count(file_size, language, config, plugins);
+    if segments > 1 {
+        return with_path(parse_in_segments(path, file, file_size, segments, language, config), path, language, plugins);
+    }
+    if !config.duplicates {
+        let file_stats = parse_contents(BufReader::new(file), file_size, language, buf, config, plugins);
+        return with_path(file_stats, path, language, plugins);
+    }
+
+    let mut reader = HashingReader::new(BufReader::new(file));
+    let mut file_stats = parse_contents(&mut reader, file_size, language, buf, config, plugins)?;
+    file_stats.content_hash = Some(reader.finish()?);
+    with_path(Ok(file_stats), path, language, plugins)
+}
+
+// For the golden files of 'tests/fixtures', with the default configuration, so that the counts of a language can be
+// verified without setting up the producers and the consumers.
+pub fn parse_file_for_tests(path: &Path, language: &Language) -> Result<FileStats,FaultyReason> {
+    let file = File::open(path)?;
+    let file_size = file.metadata().map_or(0, |m| m.len() as usize);
+    let file_stats = parse_contents(BufReader::new(file), file_size, language, &mut String::new(), &Configuration::new(Vec::new()), &[]);
+    with_path(file_stats, path, language, &[])
+}
+
+// The contents of a buffer in memory, like an unsaved document of an editor, with the default configuration.
+// The language of an extension is the one of 'find_lang_with_this_identifier'.
+pub fn parse_str(contents: &str, language: &Language) -> Result<FileStats,FaultyReason> {
+    parse_contents(contents.as_bytes(), contents.len(), language, &mut String::new(), &Configuration::new(Vec::new()), &[])
+}
+
+// The contents of a file inside an archive, that were read in memory. The path is the one of the archive joined with the
+// path of the entry.
+pub fn parse_archive_entry(contents: &[u8], path: &Path, lang_name: &str, buf: &mut String, language_map: Arc<HashMap<String,Language>>,
+        config: &Configuration, plugins: &[Box<dyn MetricPlugin>]) -> Result<FileStats,FaultyReason>
+{
+    let language = get_language(&language_map, lang_name)?;
+    let mut file_stats = parse_contents(contents, contents.len(), language, buf, config, plugins);
+    if let (true, Ok(x)) = (config.duplicates, &mut file_stats) {
+        x.content_hash = Some(hashing::sha256(contents));
+    }
+    with_path(file_stats, path, language, plugins)
+}
+
+fn get_language<'a>(language_map: &'a HashMap<String,Language>, lang_name: &str) -> Result<&'a Language,FaultyReason> {
+    language_map.get(lang_name).ok_or_else(|| FaultyReason::UnknownLanguage(lang_name.to_owned()))
+}
+
+// The line parser doesn't know the path of the file, which the todos and the plugins of the files need
+fn with_path(file_stats: Result<FileStats,FaultyReason>, path: &Path, language: &Language, plugins: &[Box<dyn MetricPlugin>])
+-> Result<FileStats,FaultyReason>
+{
+    let mut file_stats = file_stats?;
+    for todo in file_stats.todos.iter_mut() {
+        todo.path = path.to_path_buf();
+    }
+    plugins::run_file_plugins(plugins, path, language, &mut file_stats);
+    Ok(file_stats)
+}
+
+fn parse_contents(mut reader: impl BufRead, file_size: usize, language: &Language, buf: &mut String, config: &Configuration,
+        plugins: &[Box<dyn MetricPlugin>]) -> Result<FileStats,FaultyReason> 
+{
+    if !config.hygiene {
+        return parse_by_encoding_and_size(reader, file_size, language, buf, config, plugins);
+    }
+    // Looked for before it is consumed, whatever the encoding
+    let start = reader.fill_buf()?;
+    let has_bom = start.starts_with(&UTF8_BOM) || start.starts_with(&UTF16_LE_BOM) || start.starts_with(&UTF16_BE_BOM);
+    let mut file_stats = parse_by_encoding_and_size(reader, file_size, language, buf, config, plugins)?;
+    if let Some(hygiene) = &mut file_stats.hygiene {
+        hygiene.has_bom = has_bom;
+    }
+    Ok(file_stats)
+}
+
+fn parse_by_encoding_and_size(mut reader: impl BufRead, file_size: usize, language: &Language, buf: &mut String, config: &Configuration,
+        plugins: &[Box<dyn MetricPlugin>]) -> Result<FileStats,FaultyReason> 
 {
-    let reader = BufReader::new(match File::open(path){
-        Ok(f) => f,
-        Err(x) => return Err(x.to_string())
+    // In strict mode the bytes are read as they are, so anything that is not valid UTF-8 makes the file faulty
+    if !config.strict_encoding {
+        match sniff_bom(&mut reader)? {
+            Encoding::Utf8 => (),
+            x => return parse_utf16(reader, x, language, config, plugins)
+        }
+    }
+
+    if let Some(budget) = get_file_memory_budget(config) {
+        if file_size > budget {
+            return parse_blocks(reader, LARGE_FILE_BLOCK_SIZE.min(budget), language, config, plugins);
+        }
+    }
+    if config.large_file_threshold != 0 && file_size >= config.large_file_threshold * 1024 * 1024 {
+        return parse_blocks(reader, LARGE_FILE_BLOCK_SIZE, language, config, plugins);
+    }
+
+    parse_lines(reader, buf, language, config, plugins)
+}
+
+// The segments of '--split-threshold', one for every parser thread but none smaller than MIN_SEGMENT_BYTES, or 1 if the file
+// is parsed whole. The hash of '--duplicates' is the one of the whole file, the prose and the plugins depend on all the lines
+// before, and each segment would take a block of the memory budget.
+fn get_segment_count(file_size: usize, language: &Language, config: &Configuration, plugins: &[Box<dyn MetricPlugin>]) -> usize {
+    if config.split_threshold == 0 || file_size < config.split_threshold * 1024 * 1024 || config.duplicates || language.is_documentation()
+            || !plugins.is_empty() || get_file_memory_budget(config).is_some() {
+        return 1;
+    }
+    config.threads.consumers.min(file_size / MIN_SEGMENT_BYTES).max(1)
+}
+
+// The segments after the first are parsed by threads of their own, each as if it was the rest of the file after a line outside
+// of comments and strings. If the segment before one ended inside a comment or a string, or still in the header of the file,
+// it is parsed again after it instead, by the parser of the previous segments.
+fn parse_in_segments(path: &Path, file: File, file_size: usize, segments: usize, language: &Language, config: &Configuration)
+-> Result<FileStats,FaultyReason>
+{
+    let mut reader = BufReader::new(file);
+    let start = reader.fill_buf()?;
+    let has_bom = start.starts_with(&UTF8_BOM) || start.starts_with(&UTF16_LE_BOM) || start.starts_with(&UTF16_BE_BOM);
+    if !config.strict_encoding && (start.starts_with(&UTF16_LE_BOM) || start.starts_with(&UTF16_BE_BOM)) {
+        return parse_contents(reader, file_size, language, &mut String::new(), config, &[]);
+    }
+    let first_byte = if !config.strict_encoding && start.starts_with(&UTF8_BOM) {UTF8_BOM.len() as u64} else {0};
+    let bounds = find_segment_bounds(reader.get_mut(), first_byte, file_size as u64, segments)?;
+
+    // There is always the first one
+    let (first_bounds, other_bounds) = (bounds[0], &bounds[1..]);
+    let (first_segment, other_segments) = thread::scope(|scope| {
+        let handles = other_bounds.iter()
+                .map(|x| scope.spawn(move || parse_segment(path, *x, LineParser::for_segment(language, config))))
+                .collect::<Vec<_>>();
+        let first_segment = parse_segment(path, first_bounds, LineParser::new(language, config, &[]));
+        // A panic of the parser is left to the consumer, as if the file wasn't split
+        (first_segment, handles.into_iter().map(|x| x.join().unwrap_or_else(|x| panic::resume_unwind(x))).collect::<Vec<_>>())
     });
 
-    parse_lines(reader, buf, language_map.get(lang_name).unwrap(), config)
+    let mut line_parser = first_segment?;
+    for (bounds, segment) in other_bounds.iter().zip(other_segments) {
+        let segment = segment?;
+        if line_parser.is_at_segment_start() {
+            line_parser.append_segment(segment);
+        } else {
+            line_parser = parse_segment(path, *bounds, line_parser)?;
+        }
+    }
+    let mut file_stats = line_parser.finish();
+    if let Some(hygiene) = &mut file_stats.hygiene {
+        hygiene.has_bom = has_bom;
+    }
+    Ok(file_stats)
+}
+
+// The start and the end of each segment, all of them ending after a '\n' except the last one, which ends with the file.
+// There are fewer segments than asked for if the lines are too long to cut the file in that many.
+fn find_segment_bounds(file: &mut File, first_byte: u64, file_size: u64, segments: usize) -> io::Result<Vec<(u64, u64)>> {
+    let segment_size = file_size.saturating_sub(first_byte) / segments as u64;
+    let mut starts = vec![first_byte];
+    let mut line = Vec::new();
+    for i in 1..segments as u64 {
+        let target = first_byte + i * segment_size;
+        if target < *starts.last().unwrap_or(&0) {
+            continue;
+        }
+        file.seek(SeekFrom::Start(target))?;
+        line.clear();
+        let read = BufReader::new(&mut *file).read_until(b'\n', &mut line)? as u64;
+        if !line.ends_with(b"\n") || target + read >= file_size {
+            break;
+        }
+        starts.push(target + read);
+    }
+    let ends = starts.iter().skip(1).copied().chain(std::iter::once(u64::MAX));
+    Ok(starts.iter().copied().zip(ends).collect())
+}
+
+fn parse_segment<'a>(path: &Path, (start, end): (u64, u64), mut line_parser: LineParser<'a>) -> Result<LineParser<'a>,FaultyReason> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(start))?;
+    parse_blocks_with(file.take(end - start), LARGE_FILE_BLOCK_SIZE, &mut line_parser)?;
+    Ok(line_parser)
+}
+
+impl<R: BufRead> HashingReader<R> {
+    fn new(inner: R) -> Self {
+        HashingReader {inner, hasher: hashing::Sha256::new()}
+    }
+
+    // The parsing may stop before the end of the file, so the rest is read too
+    fn finish(mut self) -> io::Result<[u8; 32]> {
+        io::copy(&mut self, &mut io::sink())?;
+        Ok(self.hasher.finalize())
+    }
+}
+
+impl<R: BufRead> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hasher.update(&buf[..read]);
+        Ok(read)
+    }
+}
+
+impl<R: BufRead> BufRead for HashingReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    // The consumed bytes are still in the buffer, so getting them doesn't read again
+    fn consume(&mut self, amt: usize) {
+        if let Ok(buf) = self.inner.fill_buf() {
+            self.hasher.update(&buf[..amt.min(buf.len())]);
+        }
+        self.inner.consume(amt);
+    }
+}
+
+// Consumes the byte order mark, if there is one, and returns the encoding that it signifies.
+// Files without one are treated as UTF-8.
+fn sniff_bom(reader: &mut impl BufRead) -> std::io::Result<Encoding> {
+    let start = reader.fill_buf()?;
+    let (encoding, bom_len) = if start.starts_with(&UTF8_BOM) {
+        (Encoding::Utf8, UTF8_BOM.len())
+    } else if start.starts_with(&UTF16_LE_BOM) {
+        (Encoding::Utf16Le, UTF16_LE_BOM.len())
+    } else if start.starts_with(&UTF16_BE_BOM) {
+        (Encoding::Utf16Be, UTF16_BE_BOM.len())
+    } else {
+        (Encoding::Utf8, 0)
+    };
+    reader.consume(bom_len);
+
+    Ok(encoding)
+}
+
+// Invalid UTF-8 (e.g. Latin-1 files) is replaced with U+FFFD, unless the encoding is strict.
+// The symbols that the parsing depends on are all ASCII, so the replaced characters don't affect the stats.
+fn decode_line<'b>(bytes: &'b [u8], config: &Configuration) -> Result<Cow<'b, str>,FaultyReason> {
+    match str::from_utf8(bytes) {
+        Ok(x) => Ok(Cow::Borrowed(x)),
+        Err(_) if config.strict_encoding => Err(FaultyReason::InvalidEncoding),
+        Err(_) => Ok(String::from_utf8_lossy(bytes))
+    }
 }
 
-fn parse_lines(mut reader: BufReader<File>, buf: &mut String, language: &Language, config: &Configuration)
--> Result<FileStats,String>
+fn parse_lines(mut reader: impl BufRead, buf: &mut String, language: &Language, config: &Configuration, plugins: &[Box<dyn MetricPlugin>])
+-> Result<FileStats,FaultyReason>
 {
-    let mut file_stats = match config.no_keywords {
-        true => FileStats::default(),
-        false => FileStats::with_keywords(&language.keywords)
+    // The lines are read as bytes so that they can be decoded leniently, reusing the allocation of the buffer
+    let mut bytes = mem::take(buf).into_bytes();
+    bytes.clear();
+    let mut line_parser = LineParser::new(language, config, plugins);
+    let result = loop {
+        // The rest of the previous chunk of a long line is still in the bytes
+        let carried_bytes = bytes.len();
+        match read_line_up_to(&mut reader, &mut bytes, MINIFIED_LINE_BYTES) {
+            Ok(0) if carried_bytes == 0 => break Ok(line_parser.finish()),
+            Ok(_) => {
+                let has_newline = bytes.ends_with(b"\n");
+                let parsed = if !has_newline && bytes.len() >= MINIFIED_LINE_BYTES {
+                    let chunk_end = find_chunk_end(&bytes);
+                    let parsed = line_parser.parse_line_chunk(&bytes[..chunk_end]);
+                    bytes.drain(..chunk_end);
+                    parsed
+                } else {
+                    let parsed = for_each_line(&bytes[..bytes.len() - usize::from(has_newline)], has_newline,
+                            |line, end| line_parser.parse_line_bytes(line, end));
+                    bytes.clear();
+                    parsed
+                };
+                if let Err(x) = parsed {
+                    break Err(x);
+                }
+            },
+            Err(x) => break Err(x.into())
+        }
+        if let Err(x) = line_parser.check_timeout() {
+            break Err(x);
+        }
     };
-    let mut is_comment_closed = true;
-    let mut open_str_symbol = None;
+    bytes.clear();
+    *buf = String::from_utf8(bytes).unwrap_or_default();
+
+    result
+}
+
+// The lines are sliced directly out of the block, only a line that continues into the next block is copied
+fn parse_blocks(reader: impl Read, block_size: usize, language: &Language, config: &Configuration, plugins: &[Box<dyn MetricPlugin>])
+-> Result<FileStats,FaultyReason>
+{
+    let mut line_parser = LineParser::new(language, config, plugins);
+    parse_blocks_with(reader, block_size, &mut line_parser)?;
+    Ok(line_parser.finish())
+}
+
+fn parse_blocks_with(mut reader: impl Read, block_size: usize, line_parser: &mut LineParser) -> Result<(),FaultyReason> {
+    let mut block = vec![0u8; block_size];
+    let mut unfinished_line = Vec::new();
     loop {
-        buf.clear();
-        match reader.read_line(buf) {
-            Ok(u) => if u == 0 {return Ok(file_stats)},
-            Err(x) => return Err(x.to_string())
+        let read_bytes = match reader.read(&mut block) {
+            Ok(0) => break,
+            Ok(x) => x,
+            Err(x) if x.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(x) => return Err(x.into())
+        };
+
+        let mut line_start = 0;
+        for (i, byte) in block[..read_bytes].iter().enumerate() {
+            if *byte != b'\n' {continue;}
+
+            if unfinished_line.is_empty() {
+                for_each_line(&block[line_start..i], true, |line, end| line_parser.parse_line_bytes(line, end))?;
+            } else {
+                unfinished_line.extend_from_slice(&block[line_start..i]);
+                for_each_line(&unfinished_line, true, |line, end| line_parser.parse_line_bytes(line, end))?;
+                unfinished_line.clear();
+            }
+            line_parser.check_timeout()?;
+            line_start = i + 1;
         }
-        file_stats.incr_lines();
+        unfinished_line.extend_from_slice(&block[line_start..read_bytes]);
+        while unfinished_line.len() >= MINIFIED_LINE_BYTES {
+            let chunk_end = find_chunk_end(&unfinished_line);
+            line_parser.parse_line_chunk(&unfinished_line[..chunk_end])?;
+            unfinished_line.drain(..chunk_end);
+        }
+    }
+    if !unfinished_line.is_empty() {
+        for_each_line(&unfinished_line, false, |line, end| line_parser.parse_line_bytes(line, end))?;
+    }
+
+    Ok(())
+}
+
+// UTF-16 files are rare enough in source trees that they are simply decoded whole
+fn parse_utf16(mut reader: impl Read, encoding: Encoding, language: &Language, config: &Configuration, plugins: &[Box<dyn MetricPlugin>])
+-> Result<FileStats,FaultyReason>
+{
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+
+    let units = bytes.chunks_exact(2).map(|x| match encoding {
+        Encoding::Utf16Be => u16::from_be_bytes([x[0], x[1]]),
+        _ => u16::from_le_bytes([x[0], x[1]])
+    });
+    let contents = char::decode_utf16(units).map(|x| x.unwrap_or(char::REPLACEMENT_CHARACTER)).collect::<String>();
+
+    let mut line_parser = LineParser::new(language, config, plugins);
+    for line in contents.split_inclusive('\n') {
+        let has_newline = line.ends_with('\n');
+        for_each_line(line.trim_end_matches('\n').as_bytes(), has_newline, |line, end| line_parser.parse_line_bytes(line, end))?;
+        line_parser.check_timeout()?;
+    }
+
+    Ok(line_parser.finish())
+}
+
+// Like 'read_until' a '\n', but stops once the bytes reach the limit, so that the single line of a minified file
+// is not read whole
+fn read_line_up_to(reader: &mut impl BufRead, bytes: &mut Vec<u8>, limit: usize) -> io::Result<usize> {
+    let mut read = 0;
+    while bytes.len() < limit {
+        let available = match reader.fill_buf() {
+            Ok(x) => x,
+            Err(x) if x.kind() == io::ErrorKind::Interrupted => continue,
+            Err(x) => return Err(x)
+        };
+        if available.is_empty() {
+            break;
+        }
+        let available = &available[..available.len().min(limit - bytes.len())];
+        let (used, has_newline) = match available.iter().position(|x| *x == b'\n') {
+            Some(i) => (i + 1, true),
+            None => (available.len(), false)
+        };
+        bytes.extend_from_slice(&available[..used]);
+        reader.consume(used);
+        read += used;
+        if has_newline {
+            break;
+        }
+    }
+    Ok(read)
+}
+
+// Where a chunk of a long line is cut: after the last space or punctuation of its second half, so that the words aren't
+// split, or else before the last character if it isn't ascii, since it may not have all of its bytes yet
+fn find_chunk_end(bytes: &[u8]) -> usize {
+    match bytes.iter().rposition(|x| matches!(x, b' ' | b'\t' | b';' | b',' | b'{' | b'}' | b'(' | b')')) {
+        Some(i) if i + 1 >= bytes.len() / 2 => i + 1,
+        _ if bytes.last().is_some_and(u8::is_ascii) => bytes.len(),
+        _ => bytes.iter().rposition(|x| x & 0xC0 != 0x80).filter(|x| *x > 0).unwrap_or(bytes.len())
+    }
+}
+
+// The bytes up to a '\n' or the end of the file are split at the lone '\r' of the old Mac files, so that the same lines
+// are counted with any line ending. The '\r' of a '\r\n' is left in the line. Nothing comes after a line ending at the end
+// of the file, so it doesn't add an empty line.
+fn for_each_line(bytes: &[u8], has_newline: bool, mut on_line: impl FnMut(&[u8], LineEnd) -> Result<(),FaultyReason>)
+-> Result<(),FaultyReason>
+{
+    let last_end = if has_newline {LineEnd::Newline} else {LineEnd::EndOfFile};
+    let crlf_len = usize::from(has_newline && bytes.ends_with(b"\r"));
+    if !bytes[..bytes.len() - crlf_len].contains(&b'\r') {
+        return on_line(bytes, last_end);
+    }
+
+    let mut line_start = 0;
+    for (i, byte) in bytes[..bytes.len() - crlf_len].iter().enumerate() {
+        if *byte == b'\r' {
+            on_line(&bytes[line_start..i], LineEnd::LoneCr)?;
+            line_start = i + 1;
+        }
+    }
+    if line_start < bytes.len() || has_newline {
+        on_line(&bytes[line_start..], last_end)?;
+    }
+    Ok(())
+}
+
+// Keeps the state that carries over from one line to the next
+struct LineParser<'a> {
+    language: &'a Language,
+    config: &'a Configuration,
+    file_stats: FileStats,
+    is_comment_closed: bool,
+    open_str_symbol: Option<String>,
+    // The strings that the symbol by symbol scan keeps track of apart from the open string
+    open_strings: OpenStrings,
+    // How many multiline comments are open, for the languages that nest them
+    comment_depth: usize,
+    // The index of the kind of the open multiline comment, for the languages with more than one kind
+    open_comment_kind: usize,
+    // Whether the open multiline comment or string was started by a documentation comment symbol
+    is_doc_open: bool,
+    // The markers of '--todos', empty if it is not used
+    todo_markers: Vec<String>,
+    // Whether the branch keywords are counted, which costs a search for each of them in every code line
+    counts_complexity: bool,
+    // Only for documentation files
+    prose_parser: Option<ProseParser>,
+    // When the parsing of the file has to end with '--file-timeout'
+    deadline: Option<Instant>,
+    parsed_lines: usize,
+    plugins: &'a [Box<dyn MetricPlugin>],
+    // The comment lines before the first code line, with '--licenses'. None once the license was looked for
+    license_header: Option<String>,
+    // The spaces before the previous code line, with '--indentation'. None before the first one of a segment of a split file
+    last_indent: Option<usize>,
+    // The spaces before the first code line of a segment, whose growth is only known once the segment is appended
+    first_indent: Option<usize>,
+    // Whether the comments of the header are still looked at for a marker of generated code
+    in_header: bool,
+    // For '--ignore-trailing-empty-line'
+    is_last_line_blank: bool,
+    // While a line longer than MINIFIED_LINE_BYTES is parsed in chunks
+    long_line: Option<LongLine>
+}
+
+// The counts from before a line that is parsed in chunks, so that it is counted as one line in the end
+struct LongLine {
+    lines: usize,
+    code_lines: usize,
+    doc_lines: usize,
+    logical_lines: usize,
+    // In characters, of the chunks so far
+    length: usize
+}
+
+impl<'a> LineParser<'a> {
+    fn new(language: &'a Language, config: &'a Configuration, plugins: &'a [Box<dyn MetricPlugin>]) -> Self {
+        LineParser {
+            language,
+            config,
+            file_stats: FileStats {
+                hygiene: if config.hygiene {Some(FileHygiene::default())} else {None},
+                indentation: if config.indentation && !language.is_documentation() {Some(IndentationStats::default())} else {None},
+                ..match config.no_keywords {
+                    true => FileStats::default(),
+                    false => FileStats::with_keywords(&language.keywords)
+                }
+            },
+            is_comment_closed: true,
+            open_str_symbol: None,
+            open_strings: OpenStrings::default(),
+            comment_depth: 0,
+            open_comment_kind: 0,
+            is_doc_open: false,
+            todo_markers: config.get_todo_markers(),
+            counts_complexity: config.counts_complexity() && !language.branch_keywords.is_empty(),
+            prose_parser: if language.is_documentation() {Some(ProseParser::new(&language.name))} else {None},
+            deadline: if config.file_timeout == 0 {None} else {Some(Instant::now() + Duration::from_secs(config.file_timeout as u64))},
+            parsed_lines: 0,
+            plugins,
+            license_header: if config.licenses && !language.is_documentation() {Some(String::new())} else {None},
+            last_indent: Some(0),
+            first_indent: None,
+            in_header: !language.is_documentation(),
+            is_last_line_blank: false,
+            long_line: None
+        }
+    }
+
+    // For a segment of a split file after the first one, which starts after the header
+    fn for_segment(language: &'a Language, config: &'a Configuration) -> Self {
+        LineParser {license_header: None, in_header: false, last_indent: None, ..LineParser::new(language, config, &[])}
+    }
+
+    // Whether the next line would be parsed as the first line of a segment parsed on its own
+    fn is_at_segment_start(&self) -> bool {
+        self.is_comment_closed && self.comment_depth == 0 && self.open_str_symbol.is_none() && self.open_strings == OpenStrings::default()
+                && !self.is_doc_open && !self.in_header && self.license_header.is_none() && self.long_line.is_none()
+    }
+
+    // Adds the stats of the segment that follows, with its line numbers after the lines so far, and takes over its state
+    fn append_segment(&mut self, segment: LineParser) {
+        let (stats, segment_stats) = (&mut self.file_stats, segment.file_stats);
+        let line_offset = stats.lines;
+        stats.lines += segment_stats.lines;
+        stats.code_lines += segment_stats.code_lines;
+        stats.doc_lines += segment_stats.doc_lines;
+        stats.complexity += segment_stats.complexity;
+        stats.logical_lines += segment_stats.logical_lines;
+        stats.long_lines += segment_stats.long_lines;
+        stats.max_line_length = stats.max_line_length.max(segment_stats.max_line_length);
+        stats.has_content |= segment_stats.has_content;
+        stats.is_minified |= segment_stats.is_minified;
+        for (name, occurrences) in segment_stats.keyword_occurences {
+            *stats.keyword_occurences.entry(name).or_insert(0) += occurrences;
+        }
+        stats.todos.extend(segment_stats.todos.into_iter().map(|x| TodoItem {line: x.line + line_offset, ..x}));
+        stats.code_line_hashes.extend(segment_stats.code_line_hashes.into_iter().map(|(hash, line)| (hash, line + line_offset)));
+        if let (Some(hygiene), Some(segment_hygiene)) = (&mut stats.hygiene, segment_stats.hygiene) {
+            hygiene.lf_lines += segment_hygiene.lf_lines;
+            hygiene.crlf_lines += segment_hygiene.crlf_lines;
+            hygiene.ends_with_newline = segment_hygiene.ends_with_newline;
+        }
+        if let (Some(indentation), Some(segment_indentation)) = (&mut stats.indentation, segment_stats.indentation) {
+            indentation.tab_lines += segment_indentation.tab_lines;
+            indentation.space_lines += segment_indentation.space_lines;
+            indentation.mixed_lines += segment_indentation.mixed_lines;
+            for (count, segment_count) in indentation.width_counts.iter_mut().zip(segment_indentation.width_counts) {
+                *count += segment_count;
+            }
+            if let (Some(last_indent), Some(spaces)) = (self.last_indent, segment.first_indent) {
+                add_indent_growth(indentation, last_indent, spaces);
+            }
+        }
+
+        self.is_comment_closed = segment.is_comment_closed;
+        self.open_str_symbol = segment.open_str_symbol;
+        self.open_strings = segment.open_strings;
+        self.comment_depth = segment.comment_depth;
+        self.open_comment_kind = segment.open_comment_kind;
+        self.is_doc_open = segment.is_doc_open;
+        self.is_last_line_blank = segment.is_last_line_blank;
+        self.parsed_lines += segment.parsed_lines;
+        self.last_indent = segment.last_indent.or(self.last_indent);
+    }
+
+    // The characters are only counted for the lines that have enough bytes to matter
+    fn add_line_length(&mut self, line: &str) {
+        let line = line.trim_end_matches(['\n', '\r']);
+        if line.len() <= self.file_stats.max_line_length && line.len() <= self.config.max_line_length {
+            return;
+        }
+        self.add_length(line.chars().count());
+    }
+
+    fn add_length(&mut self, length: usize) {
+        self.file_stats.max_line_length = self.file_stats.max_line_length.max(length);
+        if length > self.config.max_line_length {
+            self.file_stats.long_lines += 1;
+        }
+    }
+
+    fn parse_line_bytes(&mut self, line: &[u8], end: LineEnd) -> Result<(),FaultyReason> {
+        if self.long_line.is_some() {
+            return self.end_long_line(line, end);
+        }
+        self.add_line_end(line, end);
+        self.parse_line(&decode_line(line, self.config)?);
+        Ok(())
+    }
+
+    // A part of a line that goes on after it. The comments and the strings that are open at its end carry over to the
+    // next chunk as they would to the next line, and the single line comments end with it.
+    fn parse_line_chunk(&mut self, chunk: &[u8]) -> Result<(),FaultyReason> {
+        if self.long_line.is_none() {
+            // For the shebang of the first line, the end is set by the last chunk
+            self.add_line_end(chunk, LineEnd::EndOfFile);
+            let stats = &self.file_stats;
+            self.long_line = Some(LongLine {lines: stats.lines, code_lines: stats.code_lines, doc_lines: stats.doc_lines,
+                    logical_lines: stats.logical_lines, length: 0});
+            self.file_stats.is_minified = true;
+        }
+        let chunk = decode_line(chunk, self.config)?;
+        if let Some(long_line) = &mut self.long_line {
+            long_line.length += chunk.chars().count();
+        }
+        self.parse_line_contents(&chunk);
+        Ok(())
+    }
+
+    // After the last chunk, the chunks are counted as a single line
+    fn end_long_line(&mut self, chunk: &[u8], end: LineEnd) -> Result<(),FaultyReason> {
+        self.add_line_end(chunk, end);
+        self.is_last_line_blank = false;
+        let chunk = decode_line(chunk, self.config)?;
+        self.parse_line_contents(&chunk);
+        let long_line = match self.long_line.take() {
+            Some(x) => x,
+            None => return Ok(())
+        };
+        let stats = &mut self.file_stats;
+        stats.lines = long_line.lines + 1;
+        stats.code_lines = long_line.code_lines + usize::from(stats.code_lines > long_line.code_lines);
+        stats.doc_lines = long_line.doc_lines + usize::from(stats.doc_lines > long_line.doc_lines);
+        // Without statement symbols a logical line is a code line
+        if self.language.statement_symbols.is_empty() {
+            stats.logical_lines = long_line.logical_lines + usize::from(stats.logical_lines > long_line.logical_lines);
+        }
+        self.add_length(long_line.length + chunk.trim_end_matches('\r').chars().count());
+        Ok(())
+    }
+
+    // Called before the line is parsed, with the line without its '\n'. The lone '\r' endings are neither LF nor CRLF
+    fn add_line_end(&mut self, line: &[u8], end: LineEnd) {
+        self.is_last_line_blank = line.iter().all(u8::is_ascii_whitespace);
+        let is_first_line = self.file_stats.lines == 0;
+        let hygiene = match &mut self.file_stats.hygiene {
+            Some(x) => x,
+            None => return
+        };
+        if is_first_line {
+            hygiene.has_shebang = line.strip_prefix(&UTF8_BOM[..]).unwrap_or(line).starts_with(b"#!");
+        }
+        if end == LineEnd::Newline {
+            if line.ends_with(b"\r") {hygiene.crlf_lines += 1} else {hygiene.lf_lines += 1}
+        }
+        hygiene.ends_with_newline = end != LineEnd::EndOfFile;
+    }
+
+    fn finish(mut self) -> FileStats {
+        if self.long_line.is_some() {
+            // An empty chunk is valid in any encoding
+            let _ = self.end_long_line(&[], LineEnd::EndOfFile);
+        }
+        if let Some(header) = self.license_header.take() {
+            self.file_stats.license = detect_license(&header);
+        }
+        // A blank line doesn't add anything else, unless it is inside a comment or a string, which the blank lines don't
+        // change either
+        if self.config.ignore_trailing_empty_line && self.is_last_line_blank && self.file_stats.lines > 0 {
+            self.file_stats.lines -= 1;
+        }
+        self.file_stats
+    }
+
+    // The clock is read only every few lines, since most lines take far less time to parse than reading it
+    fn check_timeout(&mut self) -> Result<(),FaultyReason> {
+        self.parsed_lines += 1;
+        match self.deadline {
+            Some(deadline) if self.parsed_lines.is_multiple_of(TIMEOUT_CHECK_LINES) && Instant::now() >= deadline => {
+                Err(FaultyReason::TimedOut(self.config.file_timeout))
+            },
+            _ => Ok(())
+        }
+    }
+
+    fn parse_line(&mut self, line: &str) {
+        self.add_line_length(line);
+        if line.len() > MINIFIED_LINE_BYTES {
+            self.file_stats.is_minified = true;
+        }
+        self.parse_line_contents(line);
+    }
+
+    fn parse_line_contents(&mut self, line: &str) {
+        if (self.plugins.is_empty() && self.license_header.is_none() && self.file_stats.indentation.is_none() && !self.in_header)
+                || self.prose_parser.is_some() {
+            self.parse_line_stats(line);
+            return;
+        }
+        let (code_lines, doc_lines) = (self.file_stats.code_lines, self.file_stats.doc_lines);
+        self.parse_line_stats(line);
+        let is_code = self.file_stats.code_lines != code_lines;
+        if self.license_header.is_some() {
+            self.add_to_license_header(line.trim(), is_code);
+        }
+        if self.in_header {
+            self.look_for_generated_marker(line, is_code);
+        }
+        if is_code {
+            self.add_indentation(line);
+        }
+        if self.plugins.is_empty() {
+            return;
+        }
+        let context = LineContext {line: line.trim(), language: self.language, is_code, is_doc: self.file_stats.doc_lines != doc_lines};
+        for plugin in self.plugins {
+            plugin.on_line(&context, &mut self.file_stats.plugin_metrics);
+        }
+    }
+
+    // The width is the growth of the indentation with spaces from the previous code line, so that the alignment of
+    // the continuation lines doesn't count for much
+    fn add_indentation(&mut self, line: &str) {
+        let indentation = match &mut self.file_stats.indentation {
+            Some(x) => x,
+            None => return
+        };
+        let indent = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+        let (has_tabs, spaces) = (indent.contains('\t'), indent.bytes().filter(|x| *x == b' ').count());
+        match (has_tabs, spaces > 0) {
+            (true, true) => indentation.mixed_lines += 1,
+            (true, false) => indentation.tab_lines += 1,
+            (false, true) => indentation.space_lines += 1,
+            (false, false) => ()
+        }
+        if has_tabs {
+            return;
+        }
+        match self.last_indent {
+            Some(last_indent) => add_indent_growth(indentation, last_indent, spaces),
+            None => self.first_indent = Some(spaces)
+        }
+        self.last_indent = Some(spaces);
+    }
+
+    fn look_for_generated_marker(&mut self, line: &str, is_code: bool) {
+        if !is_code && GENERATED_MARKERS.iter().any(|x| line.contains(x)) {
+            self.file_stats.is_generated = true;
+        }
+        if is_code || self.file_stats.is_generated || self.file_stats.lines >= GENERATED_HEADER_MAX_LINES {
+            self.in_header = false;
+        }
+    }
+
+    fn add_to_license_header(&mut self, line: &str, is_code: bool) {
+        if is_code || self.file_stats.lines > LICENSE_HEADER_MAX_LINES {
+            if let Some(header) = self.license_header.take() {
+                self.file_stats.license = detect_license(&header);
+            }
+        } else if let Some(header) = &mut self.license_header {
+            header.push_str(line);
+            header.push('\n');
+        }
+    }
+
+    fn parse_line_stats(&mut self, line: &str) {
+        if let Some(prose_parser) = &mut self.prose_parser {
+            prose_parser.parse_line(line, &mut self.file_stats);
+            return;
+        }
+
+        let (language, config) = (self.language, self.config);
+        self.file_stats.incr_lines();
 
-        let line = buf.trim();
-        if line.is_empty() { continue; }
+        let line = line.trim();
+        if line.is_empty() { return; }
+        self.file_stats.has_content = true;
 
-        // Two different parsing functions to skip the unnecessary checks for langs that don't support multiline comments
-        // for performance reasons
+        let is_inside_comment_or_str = !self.is_comment_closed || self.comment_depth > 0 || self.open_str_symbol.is_some();
+        let starts_with_doc_symbol = !is_inside_comment_or_str && language.doc_comment_symbols.iter().any(|x| line.starts_with(x.as_str()));
+        if !self.todo_markers.is_empty() {
+            self.add_todo_if_any(line, !self.is_comment_closed || self.comment_depth > 0);
+        }
+        if !config.no_keywords && self.open_str_symbol.is_none() && language.keywords.iter().any(|x| x.scope.includes_comments()) {
+            if let Some(comment) = get_comment_part(line, language, !self.is_comment_closed || self.comment_depth > 0) {
+                add_keywords_if_any(comment, language, &mut self.file_stats, true);
+            }
+        }
+
+        // Different parsing functions to skip the unnecessary checks for langs that don't support multiline comments
+        // for performance reasons. The symbol by symbol scan is only needed for nesting, more than one kind of them
+        // or the lines with strings of their own delimiters, heredocs or char literals.
         let line_info = 
-        if language.supports_multiline_comments() { 
-            get_bounds_w_multiline_comments(line, language, is_comment_closed, &open_str_symbol)
+        if self.has_no_symbols(line) {
+            LineInfo::with_str(line.to_owned(), false)
+        } else if self.needs_scanning(line) {
+            let open_comment = (self.comment_depth, self.open_comment_kind);
+            let (line_info, (comment_depth, open_comment_kind)) = get_bounds_by_scanning(line, language, open_comment, &self.open_str_symbol,
+                    &mut self.open_strings);
+            self.comment_depth = comment_depth;
+            self.open_comment_kind = open_comment_kind;
+            line_info
+        } else if language.supports_multiline_comments() { 
+            let line_info = get_bounds_w_multiline_comments(line, language, self.is_comment_closed, &self.open_str_symbol);
+            // Kept for the scan of a later line, which is only of the single kind of the comments here
+            self.comment_depth = usize::from(line_info.is_comment_open_after);
+            line_info
         } else {
-            get_bounds_only_single_line_comments(line, language, &open_str_symbol)
+            get_bounds_only_single_line_comments(line, language, &self.open_str_symbol)
         };
 
-        is_comment_closed = !line_info.is_comment_open_after;
-        open_str_symbol = line_info.open_str_sybol_after;
+        self.is_comment_closed = !line_info.is_comment_open_after;
+        self.open_str_symbol = line_info.open_str_sybol_after;
+        // The doc lines are a part of the extra lines, so a line with code too (e.g. '/**/ i = 0;') is not documentation,
+        // while the lines of a docstring are documentation instead of code
+        let is_doc_line = (self.is_doc_open || starts_with_doc_symbol) && line_info.cleansed_string.is_none();
+        if self.is_doc_open || is_doc_line {
+            self.is_doc_open = line_info.is_comment_open_after || self.open_str_symbol.is_some();
+        }
+        if is_doc_line {
+            self.file_stats.incr_doc_lines();
+        }
 
         if let Some(x) = line_info.cleansed_string {
             let cleansed = x.trim();
-            if config.braces_as_code || cleansed.len() > 2 || (cleansed != "{" && cleansed != "}" && cleansed != "};") {
-                file_stats.incr_code_lines();
+            let is_code_line = config.braces_as_code || cleansed.len() > 2 || (cleansed != "{" && cleansed != "}" && cleansed != "};");
+            if is_code_line {
+                self.file_stats.incr_code_lines();
+                if config.clones.is_some() {
+                    let line_number = self.file_stats.lines;
+                    self.file_stats.code_line_hashes.push((clones::hash_code_line(cleansed), line_number));
+                }
                 if !config.no_keywords {
-                    add_keywords_if_any(cleansed, language, &mut file_stats);
+                    add_keywords_if_any(cleansed, language, &mut self.file_stats, false);
+                }
+                if self.counts_complexity {
+                    self.file_stats.complexity += count_branches(cleansed, language);
+                }
+            }
+            // Regardless of '--braces-as-code', since a brace on its own line still opens a statement
+            if config.logical_lines {
+                self.file_stats.logical_lines += if language.statement_symbols.is_empty() {
+                    usize::from(is_code_line)
+                } else {
+                    count_statements(cleansed, language)
+                };
+            }
+        } else {
+            if line_info.has_string_literal && !is_doc_line {self.file_stats.incr_code_lines();}
+        }
+    }
+
+    // A line of plain code, outside of comments and strings, is all code. Most of the lines are, so they are found
+    // with a search for each symbol instead of being parsed
+    fn has_no_symbols(&self, line: &str) -> bool {
+        let language = self.language;
+        self.is_comment_closed && self.comment_depth == 0 && self.open_str_symbol.is_none() && self.open_strings == OpenStrings::default()
+                && !language.comment_symbols.iter().any(|x| line.contains(x.as_str()))
+                && !language.multiline_comment_symbols.iter().any(|(start, end)| line.contains(start.as_str()) || line.contains(end.as_str()))
+                && !language.string_symbols.iter().any(|x| line.contains(x.as_str()))
+                && !language.string_delimiters.iter().any(|x| line.contains(x.start.as_str()))
+                && !language.heredoc_symbols.iter().any(|x| line.contains(x.as_str()))
+                && !(language.supports_char_literals && line.contains('\''))
+    }
+
+    // A string of a delimiter, a heredoc or an interpolation that goes on from the previous line is scanned too,
+    // while a line without any of their start symbols is parsed like in the languages without them
+    fn needs_scanning(&self, line: &str) -> bool {
+        let language = self.language;
+        if language.supports_multiline_comments() && (language.supports_nested_comments || language.multiline_comment_symbols.len() > 1) {
+            return true;
+        }
+        self.open_strings != OpenStrings::default() || self.open_str_symbol.as_ref().is_some_and(|x| !language.string_symbols.contains(x))
+                || language.string_delimiters.iter().any(|x| line.contains(x.start.as_str()))
+                || language.heredoc_symbols.iter().any(|x| line.contains(x.as_str()))
+                || (language.supports_char_literals && line.contains('\''))
+    }
+
+    // Only the first marker of the line is taken. It counts if the line is inside a multiline comment
+    // or a comment symbol comes before it, so it may be picked up from a string that contains a comment symbol.
+    fn add_todo_if_any(&mut self, line: &str, is_inside_comment: bool) {
+        let found = self.todo_markers.iter()
+                .filter_map(|marker| find_marker(line, marker).map(|i| (i, marker)))
+                .min_by_key(|(i, _)| *i);
+        let (index, marker) = match found {
+            Some(x) => x,
+            None => return
+        };
+
+        let language = self.language;
+        let before = &line[..index];
+        if !is_inside_comment && !language.comment_symbols.iter().any(|x| before.contains(x.as_str()))
+                && !language.multiline_comment_symbols.iter().any(|(start, _)| before.contains(start.as_str())) {
+            return;
+        }
+
+        let mut text = line[index + marker.len()..].trim_start_matches(':').trim();
+        for (_, end) in &language.multiline_comment_symbols {
+            text = text.strip_suffix(end.as_str()).unwrap_or(text).trim_end();
+        }
+        self.file_stats.todos.push(TodoItem {marker: marker.clone(), path: PathBuf::new(), line: self.file_stats.lines, text: text.to_owned()});
+    }
+}
+
+fn add_indent_growth(indentation: &mut IndentationStats, last_indent: usize, spaces: usize) {
+    if spaces > last_indent && spaces - last_indent <= MAX_INDENT_WIDTH {
+        indentation.width_counts[spaces - last_indent - 1] += 1;
+    }
+}
+
+// An SPDX identifier is taken as it is (e.g. 'MIT OR Apache-2.0'), otherwise the text is compared without the comment symbols
+// and the line breaks, since the usual headers are wrapped differently by each project
+fn detect_license(header: &str) -> Option<String> {
+    if let Some(index) = header.find(SPDX_IDENTIFIER) {
+        let rest = header[index + SPDX_IDENTIFIER.len()..].lines().next().unwrap_or("");
+        let identifier = rest.trim().trim_end_matches("-->").trim_end_matches("*/").trim();
+        if !identifier.is_empty() {
+            return Some(identifier.to_owned());
+        }
+    }
+
+    let text = header.lines().map(|x| x.trim_start_matches(|c: char| !c.is_alphanumeric())).collect::<Vec<_>>().join(" ");
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    let (_, name) = LICENSE_PHRASES.iter().find(|(phrase, _)| text.contains(phrase))?;
+    let version = if text.contains("version 3") {"-3.0"} else if text.contains("version 2.1") {"-2.1"} else if text.contains("version 2") {"-2.0"} else {""};
+    Some(match *name {
+        "GPL" | "LGPL" => format!("{}{}", name, version),
+        _ => (*name).to_owned()
+    })
+}
+
+// The index of the first occurrence of the marker as a whole word, e.g. 'TODO' but not 'TODOS' or 'MY_TODO'
+fn find_marker(line: &str, marker: &str) -> Option<usize> {
+    line.match_indices(marker).map(|(i, _)| i).find(|i| is_whole_word(line, *i, marker.len()))
+}
+
+// Operators like '&&' are counted wherever they are, the words only if they are whole, e.g. 'if' but not 'elif' or 'if_x'
+fn count_branches(cleansed: &str, language: &Language) -> usize {
+    language.branch_keywords.iter().map(|keyword| {
+        let is_word = keyword.chars().all(|c| c.is_alphanumeric() || c == '_');
+        cleansed.match_indices(keyword.as_str()).filter(|(i, _)| !is_word || is_whole_word(cleansed, *i, keyword.len())).count()
+    }).sum()
+}
+
+fn count_statements(cleansed: &str, language: &Language) -> usize {
+    language.statement_symbols.iter().map(|symbol| cleansed.matches(symbol.as_str()).count()).sum()
+}
+
+fn is_whole_word(line: &str, start: usize, len: usize) -> bool {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    !line[..start].chars().next_back().is_some_and(is_word_char) && !line[start + len..].chars().next().is_some_and(is_word_char)
+}
+
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum ProseFormat {
+    Markdown,
+    ReStructuredText,
+    AsciiDoc,
+    Text
+}
+
+// Counts the words, headings and code blocks of a documentation file. The lines of the code blocks are not counted as words,
+// and neither are the lines that only decorate the text (e.g. the underlines of the headings)
+struct ProseParser {
+    format: ProseFormat,
+    // The fence (e.g. "```") or delimiter (e.g. "----") that opened the current code block
+    open_fence: Option<String>,
+    // The indentation of the line that started the current indented block of reStructuredText
+    literal_block_indent: Option<usize>,
+    // The length of the previous line if it was text, since an underline below it makes it a heading
+    previous_text_len: Option<usize>
+}
+
+impl ProseParser {
+    fn new(language_name: &str) -> Self {
+        let format = match language_name {
+            "Markdown" => ProseFormat::Markdown,
+            "reStructuredText" => ProseFormat::ReStructuredText,
+            "AsciiDoc" => ProseFormat::AsciiDoc,
+            _ => ProseFormat::Text
+        };
+        ProseParser {format, open_fence: None, literal_block_indent: None, previous_text_len: None}
+    }
+
+    fn parse_line(&mut self, line: &str, file_stats: &mut FileStats) {
+        file_stats.incr_lines();
+        let trimmed = line.trim();
+        if !trimmed.is_empty() {
+            file_stats.has_content = true;
+        }
+
+        let previous_text_len = self.previous_text_len.take();
+        if self.is_inside_code_block(line, trimmed) || trimmed.is_empty() {
+            return;
+        }
+
+        if let Some(fence) = self.get_opening_fence(trimmed) {
+            self.open_fence = Some(fence);
+            file_stats.prose.code_blocks += 1;
+            return;
+        }
+        if is_adornment(trimmed) {
+            if self.is_underline(trimmed, previous_text_len) {
+                file_stats.prose.headings += 1;
+            }
+            return;
+        }
+
+        if self.format == ProseFormat::ReStructuredText {
+            if let Some(directive) = trimmed.strip_prefix(".. ") {
+                if ["code-block::", "code::", "sourcecode::"].iter().any(|x| directive.starts_with(x)) {
+                    self.start_literal_block(line, file_stats);
                 }
+                return;
             }
+        }
+
+        let (text, is_heading) = self.strip_heading_marker(trimmed);
+        if is_heading {
+            file_stats.prose.headings += 1;
         } else {
-            if line_info.has_string_literal {file_stats.incr_code_lines();}
+            self.previous_text_len = Some(trimmed.chars().count());
+        }
+        file_stats.prose.words += text.split_whitespace().filter(|x| x.chars().any(char::is_alphanumeric)).count();
+
+        if self.format == ProseFormat::ReStructuredText && trimmed.ends_with("::") {
+            self.start_literal_block(line, file_stats);
+        }
+    }
+
+    // Also closes the fenced block, if the line is its closing fence
+    fn is_inside_code_block(&mut self, line: &str, trimmed: &str) -> bool {
+        if let Some(fence) = &self.open_fence {
+            let fence_char = fence.chars().next().unwrap();
+            if trimmed.len() >= fence.len() && trimmed.chars().all(|x| x == fence_char) {
+                self.open_fence = None;
+            }
+            return true;
         }
+
+        if let Some(indent) = self.literal_block_indent {
+            if trimmed.is_empty() || get_indentation(line) > indent {
+                return true;
+            }
+            self.literal_block_indent = None;
+        }
+        false
+    }
+
+    fn start_literal_block(&mut self, line: &str, file_stats: &mut FileStats) {
+        self.literal_block_indent = Some(get_indentation(line));
+        self.previous_text_len = None;
+        file_stats.prose.code_blocks += 1;
+    }
+
+    fn get_opening_fence(&self, trimmed: &str) -> Option<String> {
+        match self.format {
+            ProseFormat::Markdown | ProseFormat::AsciiDoc => (),
+            _ => return None
+        }
+        for fence_char in ['`', '~'] {
+            let fence_len = trimmed.chars().take_while(|x| *x == fence_char).count();
+            if fence_len >= 3 {
+                return Some(fence_char.to_string().repeat(fence_len));
+            }
+        }
+        // The listing and literal blocks of AsciiDoc
+        if self.format == ProseFormat::AsciiDoc && trimmed.len() >= 4 && (trimmed.chars().all(|x| x == '-') || trimmed.chars().all(|x| x == '.')) {
+            return Some(trimmed.to_owned());
+        }
+        None
+    }
+
+    fn is_underline(&self, trimmed: &str, previous_text_len: Option<usize>) -> bool {
+        match (self.format, previous_text_len) {
+            (ProseFormat::Markdown, Some(_)) => trimmed.starts_with('=') || trimmed.starts_with('-'),
+            (ProseFormat::ReStructuredText, Some(x)) => trimmed.chars().count() >= x,
+            _ => false
+        }
+    }
+
+    // e.g. '## Usage' in Markdown or '== Usage' in AsciiDoc
+    fn strip_heading_marker<'t>(&self, trimmed: &'t str) -> (&'t str, bool) {
+        let marker = match self.format {
+            ProseFormat::Markdown => '#',
+            ProseFormat::AsciiDoc => '=',
+            _ => return (trimmed, false)
+        };
+        let level = trimmed.chars().take_while(|x| *x == marker).count();
+        let text = &trimmed[level..];
+        if (1..=6).contains(&level) && (text.is_empty() || text.starts_with(' ')) {
+            (text.trim_end_matches(marker), true)
+        } else {
+            (trimmed, false)
+        }
+    }
+}
+
+// A line of the same punctuation character repeated, like the underlines of the headings or the horizontal rules
+fn is_adornment(trimmed: &str) -> bool {
+    let mut chars = trimmed.chars();
+    match chars.next() {
+        Some(first) => first.is_ascii_punctuation() && trimmed.len() >= 2 && chars.all(|x| x == first),
+        None => false
     }
 }
 
+fn get_indentation(line: &str) -> usize {
+    line.chars().take_while(|x| x.is_whitespace()).map(|x| if x == '\t' {4} else {1}).sum()
+}
 
 // cleansed_string can contain normal code string or curly braces or strings
 #[derive(Debug, PartialEq)]
@@ -70,6 +1159,16 @@ struct LineInfo {
     open_str_sybol_after: Option<String>
 }
 
+// The strings that last for more than one line without an open string symbol
+#[derive(Debug, PartialEq, Default)]
+struct OpenStrings {
+    // The strings whose interpolation is open, e.g. '`a ${', with the start symbol of the string and how many braces
+    // are open inside the interpolation. The innermost is the last one
+    interpolations: Vec<(String, usize)>,
+    // The label of the line that ends the open heredoc, e.g. 'EOF'
+    heredoc: Option<String>
+}
+
 
 fn get_bounds_only_single_line_comments(line: &str, language: &Language, open_str_symbol: &Option<String>) -> LineInfo {
     let (str_indices, str_symbols) = get_str_indices_and_symbols(line, language, open_str_symbol);
@@ -324,10 +1423,176 @@ fn get_bounds_w_multiline_comments(line: &str, language: &Language, is_comment_c
     }
 }
 
+// Scans the line symbol by symbol, since a comment end only closes the innermost comment, e.g. in '/* a /* b */ c */',
+// and only a comment of its own kind, e.g. '-}' doesn't close '(*'. The open comment is the number of comments that are open
+// (more than 1 only if they nest) and the index of their kind. Returns the info of the line and the open comment after it.
+// The code of an interpolation is scanned like the rest of the code, and the lines of a heredoc are a string up to the
+// one that starts with its label.
+fn get_bounds_by_scanning(line: &str, language: &Language, open_comment: (usize, usize), open_str_symbol: &Option<String>,
+        open_strings: &mut OpenStrings) -> (LineInfo, (usize, usize))
+{
+    let symbols = &language.multiline_comment_symbols;
+    let mut relevant = String::with_capacity(line.len());
+    let ((mut depth, mut kind), mut open_str_symbol) = (open_comment, open_str_symbol.to_owned());
+    let mut has_string_literal = open_str_symbol.is_some() || open_strings.heredoc.is_some();
+
+    let mut i = 0;
+    if let Some(label) = &open_strings.heredoc {
+        if !line.starts_with(label.as_str()) || line[label.len()..].starts_with(is_label_char) {
+            return (LineInfo::none_str(false, true, None), open_comment);
+        }
+        i = label.len();
+        open_strings.heredoc = None;
+    }
+    while i < line.len() {
+        let rest = &line[i..];
+        let next_char_len = rest.chars().next().map_or(1, |x| x.len_utf8());
+        if open_str_symbol.is_none() && depth == 0 {
+            if let Some((symbol, end_len)) = open_strings.close_interpolation_if_any(rest, language) {
+                open_str_symbol = Some(symbol);
+                has_string_literal = true;
+                i += end_len;
+                continue;
+            }
+        }
+        if let Some(symbol) = &open_str_symbol {
+            let (end_symbol, is_raw) = get_string_end(symbol, language);
+            let escaped = if is_raw {None} else {rest.strip_prefix('\\')};
+            if let Some(escaped) = escaped {
+                i += 1 + escaped.chars().next().map_or(0, |x| x.len_utf8());
+                continue;
+            } else if rest.starts_with(end_symbol) {
+                i += end_symbol.len();
+                open_str_symbol = None;
+                continue;
+            } else if let Some((interpolation_start, _)) = get_interpolation(symbol, language).filter(|x| rest.starts_with(x.0.as_str())) {
+                open_strings.interpolations.push((symbol.to_owned(), 0));
+                i += interpolation_start.len();
+                open_str_symbol = None;
+                continue;
+            }
+        } else if depth > 0 {
+            let (start_symbol, end_symbol) = &symbols[kind];
+            if rest.starts_with(end_symbol.as_str()) {
+                depth -= 1;
+                i += end_symbol.len();
+                continue;
+            } else if language.supports_nested_comments && rest.starts_with(start_symbol.as_str()) {
+                depth += 1;
+                i += start_symbol.len();
+                continue;
+            }
+        } else if let Some(index) = symbols.iter().position(|x| rest.starts_with(x.0.as_str())) {
+            depth = 1;
+            kind = index;
+            i += symbols[index].0.len();
+            continue;
+        } else if language.comment_symbols.iter().any(|x| rest.starts_with(x.as_str())) {
+            break;
+        } else if let Some((label, len)) = find_heredoc_start(rest, language) {
+            open_strings.heredoc = Some(label);
+            has_string_literal = true;
+            i += len;
+            continue;
+        } else if let Some(len) = language.supports_char_literals.then(|| char_literal_len(rest)).flatten() {
+            relevant.push_str(&rest[..len]);
+            i += len;
+            continue;
+        } else if let Some(delimiter) = language.string_delimiters.iter().find(|x| rest.starts_with(x.start.as_str())) {
+            open_str_symbol = Some(delimiter.start.to_owned());
+            has_string_literal = true;
+            i += delimiter.start.len();
+            continue;
+        } else if let Some(symbol) = language.string_symbols.iter().find(|x| rest.starts_with(x.as_str())) {
+            open_str_symbol = Some(symbol.to_owned());
+            has_string_literal = true;
+            i += symbol.len();
+            continue;
+        } else {
+            if let (Some((_, braces)), true) = (open_strings.interpolations.last_mut(), rest.starts_with('{')) {
+                *braces += 1;
+            }
+            relevant.push_str(&rest[..next_char_len]);
+        }
+        i += next_char_len;
+    }
+
+    let cleansed_string = if relevant.trim().is_empty() {None} else {Some(relevant)};
+    (LineInfo::new(cleansed_string, has_string_literal, depth > 0, open_str_symbol), (depth, kind))
+}
+
+impl OpenStrings {
+    // The start symbol of the string that goes on and the length of the end symbol, if the innermost interpolation ends at
+    // the start of the text. The end symbol of an interpolation only ends it after the braces that were opened in it are closed.
+    fn close_interpolation_if_any(&mut self, text: &str, language: &Language) -> Option<(String, usize)> {
+        let (symbol, braces) = self.interpolations.last_mut()?;
+        let (_, end_symbol) = get_interpolation(symbol, language)?;
+        if !text.starts_with(end_symbol.as_str()) {
+            return None;
+        }
+        if *braces > 0 {
+            *braces -= 1;
+            return None;
+        }
+        self.interpolations.pop().map(|(symbol, _)| (symbol, end_symbol.len()))
+    }
+}
+
+fn get_interpolation<'l>(start_symbol: &str, language: &'l Language) -> Option<&'l (String,String)> {
+    language.string_delimiters.iter().find(|x| x.start == start_symbol)?.interpolation.as_ref()
+}
+
+// The label of the heredoc that starts at the start of the text and the length of the start, e.g. 'EOF' and 7 of "<<'EOF'".
+// A bare label has to follow the symbol, e.g. '<<EOF' or '<<\EOF', so that a shift like 'a << b' is not taken for a heredoc,
+// while a quoted one can be after spaces.
+fn find_heredoc_start(text: &str, language: &Language) -> Option<(String, usize)> {
+    let symbol = language.heredoc_symbols.iter().find(|x| text.starts_with(x.as_str()))?;
+    let after_symbol = &text[symbol.len()..];
+    let trimmed = after_symbol.trim_start();
+    let (quote, label_start) = match trimmed.strip_prefix(['\'', '"']) {
+        Some(x) => (&trimmed[..1], x),
+        None if trimmed.len() == after_symbol.len() => ("", trimmed.strip_prefix('\\').unwrap_or(trimmed)),
+        None => return None
+    };
+    let label_len = label_start.find(|x: char| !is_label_char(x)).unwrap_or(label_start.len());
+    let label = &label_start[..label_len];
+    if !label.starts_with(|x: char| x.is_alphabetic() || x == '_') || !label_start[label_len..].starts_with(quote) {
+        return None;
+    }
+    Some((label.to_owned(), text.len() - label_start.len() + label_len + quote.len()))
+}
+
+// The length of the char literal at the start of the text, e.g. '"', '\'' or '\u{22}'. A single quote that is not closed
+// right after one char or escape is not a char literal
+fn char_literal_len(text: &str) -> Option<usize> {
+    let rest = text.strip_prefix('\'')?;
+    let body_len = match rest.strip_prefix('\\') {
+        Some(escaped) => {
+            let escaped_len = escaped.chars().next()?.len_utf8();
+            // The longest escape is the one of a unicode code point, e.g. '\u{10FFFF}'
+            let close = escaped[escaped_len..].find('\'').filter(|x| *x <= 8)?;
+            1 + escaped_len + close
+        },
+        None => rest.chars().next().filter(|x| *x != '\'')?.len_utf8()
+    };
+    rest[body_len..].starts_with('\'').then_some(body_len + 2)
+}
+
+fn is_label_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// The end symbol of the open string and whether it is raw. The open string is kept by its start symbol.
+fn get_string_end<'l>(start_symbol: &'l str, language: &'l Language) -> (&'l str, bool) {
+    match language.string_delimiters.iter().find(|x| x.start == start_symbol) {
+        Some(x) => (&x.end, x.is_raw),
+        None => (start_symbol, false)
+    }
+}
+
 fn find_comment_indicies_without_multiline(line: &str, language: &Language) -> Vec<usize> {
     if language.comment_symbols.len() > 1 {
-        let mut matches = line.match_indices(&language.comment_symbols[0]).map(|x| x.0)
-            .chain(line.match_indices(&language.comment_symbols[1]).map(|x| x.0))
+        let mut matches = language.comment_symbols.iter().flat_map(|symbol| line.match_indices(symbol.as_str()).map(|x| x.0))
             .collect::<Vec<usize>>();
         matches.sort_unstable();
         matches
@@ -338,13 +1603,9 @@ fn find_comment_indicies_without_multiline(line: &str, language: &Language) -> V
 
 fn find_comment_indicies_w_multiline(line: &str, language: &Language, com_end_indices: &[usize]) -> Vec<usize> {
     if language.comment_symbols.len() > 1 {
-        line.match_indices(&language.comment_symbols[0])
-        .filter_map(|x| filter_comment_end_indicies(x.0, language, com_end_indices))
-            .chain(
-                line.match_indices(&language.comment_symbols[1])
-                .filter_map(|x|  filter_comment_end_indicies(x.0, language, com_end_indices))
-            )
-        .collect::<Vec<_>>()
+        language.comment_symbols.iter().flat_map(|symbol| line.match_indices(symbol.as_str()))
+            .filter_map(|x| filter_comment_end_indicies(x.0, language, com_end_indices))
+            .collect::<Vec<_>>()
     } else {
         line.match_indices(&language.comment_symbols[0])
             .filter_map(|x| {
@@ -371,11 +1632,15 @@ fn get_LineInfo_with_str_symbol(relevant: String, str_symbol: &str) -> LineInfo
 }
 
 fn get_com_end_indices(line: &str, language: &Language) -> Vec<usize> {
-    line.match_indices(language.multiline_comment_end_symbol.as_ref().unwrap()).map(|x| x.0).collect::<Vec<usize>>()
+    language.multiline_end_symbol().map_or_else(Vec::new, |symbol| line.match_indices(symbol).map(|x| x.0).collect::<Vec<usize>>())
 }
 
 fn get_com_start_indices(line: &str, language: &Language, comment_indices: &[usize]) -> Vec<usize> {
-    line.match_indices(language.multiline_comment_start_symbol.as_ref().unwrap())
+    let symbol = match language.multiline_start_symbol() {
+        Some(x) => x,
+        None => return Vec::new()
+    };
+    line.match_indices(symbol)
     .filter_map(|x|{
         if !is_intersecting_with_comment_symbol(x.0, comment_indices) {
             Some(x.0)
@@ -447,7 +1712,26 @@ fn resolve_double_counting_of_adjacent_start_and_end_symbols(start_indices: &mut
 }
 
 
-fn add_keywords_if_any(cleansed: &str, language: &Language, file_stats: &mut FileStats) {
+// The text of the comment of the line, from its start symbol (or the start of the line if it is inside a comment) to the end
+// symbol of a multiline comment if there is one. A comment symbol after an odd number of string symbols is taken to be in a string.
+fn get_comment_part<'a>(line: &'a str, language: &Language, is_inside_comment: bool) -> Option<&'a str> {
+    let start = if is_inside_comment {
+        0
+    } else {
+        let (index, len) = language.comment_symbols.iter().map(|x| x.as_str())
+                .chain(language.multiline_comment_symbols.iter().map(|(start, _)| start.as_str()))
+                .filter_map(|x| line.find(x).map(|i| (i, x.len())))
+                .filter(|(i, _)| language.string_symbols.iter().all(|x| line[..*i].matches(x.as_str()).count().is_multiple_of(2)))
+                .min_by_key(|(i, _)| *i)?;
+        index + len
+    };
+    let comment = &line[start..];
+    let end = language.multiline_comment_symbols.iter().filter_map(|(_, end)| comment.find(end.as_str())).min().unwrap_or(comment.len());
+    Some(comment[..end].trim()).filter(|x| !x.is_empty())
+}
+
+// 'is_comment' tells if the text is from a comment, so that only the keywords of that scope are counted
+fn add_keywords_if_any(cleansed: &str, language: &Language, file_stats: &mut FileStats, is_comment: bool) {
     fn is_acceptable_prefix(prefix: &str) -> bool {
         prefix.is_empty() || prefix.ends_with(' ') || prefix.ends_with('}') || prefix.ends_with('{') || prefix.ends_with(',')
     }
@@ -456,9 +1740,27 @@ fn add_keywords_if_any(cleansed: &str, language: &Language, file_stats: &mut Fil
         suffix.is_empty() || suffix.starts_with(' ') || suffix.starts_with('}') || suffix.starts_with('{') || suffix.starts_with(',')
     }
 
+    // Lowercased only for the ascii letters, so that the byte indices stay the same as the ones of the line
+    let lowercased = if language.keywords.iter().any(|x| x.case_insensitive && x.patterns.is_empty()) {cleansed.to_ascii_lowercase()} else {String::new()};
+
     for keyword in &language.keywords {
+        if !(if is_comment {keyword.scope.includes_comments()} else {keyword.scope.includes_code()}) {
+            continue;
+        }
+        // If the aliases are compiled as patterns (--regex-keywords), they replace the literal matching
+        if !keyword.patterns.is_empty() {
+            for pattern in &keyword.patterns {
+                for _ in 0..pattern.count_matches(cleansed) {
+                    file_stats.incr_keyword(&keyword.descriptive_name);
+                }
+            }
+            continue;
+        }
+
+        let cleansed = if keyword.case_insensitive {lowercased.as_str()} else {cleansed};
         for alias in &keyword.aliases {
-            let mut indices = cleansed.match_indices(alias).map(|x| x.0).collect::<Vec<usize>>();
+            let alias : Cow<str> = if keyword.case_insensitive {Cow::Owned(alias.to_ascii_lowercase())} else {Cow::Borrowed(alias)};
+            let mut indices = cleansed.match_indices(alias.as_ref()).map(|x| x.0).collect::<Vec<usize>>();
             if indices.is_empty() {continue;}
             let alias_len = alias.len();
 
@@ -937,27 +2239,47 @@ mod tests {
     lazy_static! {
         static ref CLASS : Keyword = Keyword {
             descriptive_name : "classes".to_owned(),
-            aliases : vec!["class".to_owned()]
+            aliases : vec!["class".to_owned()],
+            patterns : vec![],
+            category : None,
+            case_insensitive : false,
+            scope : KeywordScope::Code
         };
 
         static ref INTERFACE : Keyword = Keyword {
             descriptive_name : "interfaces".to_owned(),
-            aliases : vec!["interface".to_owned()]
+            aliases : vec!["interface".to_owned()],
+            patterns : vec![],
+            category : None,
+            case_insensitive : false,
+            scope : KeywordScope::Code
         };
 
         static ref ENUM : Keyword = Keyword {
             descriptive_name : "enums".to_owned(),
-            aliases : vec!["enum".to_owned()]
+            aliases : vec!["enum".to_owned()],
+            patterns : vec![],
+            category : None,
+            case_insensitive : false,
+            scope : KeywordScope::Code
         };
 
         static ref STRUCT : Keyword = Keyword {
             descriptive_name : "structs".to_owned(),
-            aliases : vec!["struct".to_owned()]
+            aliases : vec!["struct".to_owned()],
+            patterns : vec![],
+            category : None,
+            case_insensitive : false,
+            scope : KeywordScope::Code
         };
 
         static ref TRAIT : Keyword = Keyword {
             descriptive_name : "traits".to_owned(),
-            aliases : vec!["trait".to_owned()]
+            aliases : vec!["trait".to_owned()],
+            patterns : vec![],
+            category : None,
+            case_insensitive : false,
+            scope : KeywordScope::Code
         };
 
         static ref JAVA : Language = Language {
@@ -965,8 +2287,16 @@ mod tests {
             extensions : vec!["java".to_owned()],
             string_symbols : vec!["\"".to_owned()],
             comment_symbols : vec!["//".to_owned()],
-            multiline_comment_start_symbol : Some("/*".to_owned()),
-            multiline_comment_end_symbol : Some("*/".to_owned()),
+            multiline_comment_symbols : vec![("/*".to_owned(), "*/".to_owned())],
+            supports_nested_comments : false,
+            supports_char_literals : false,
+            string_delimiters : vec![],
+            doc_comment_symbols : vec![],
+            branch_keywords : vec![],
+            statement_symbols : vec![],
+            heredoc_symbols : vec![],
+            embedded_regions : vec![],
+            category : FileCategory::Code,
             keywords : vec![CLASS.clone(),INTERFACE.clone()]
         };
 
@@ -975,8 +2305,16 @@ mod tests {
             extensions : vec!["php".to_owned()],
             string_symbols : vec!["\"".to_owned(),"'".to_owned()],
             comment_symbols : vec!["//".to_owned(),"#".to_owned()],
-            multiline_comment_start_symbol : Some("/*".to_owned()),
-            multiline_comment_end_symbol : Some("*/".to_owned()),
+            multiline_comment_symbols : vec![("/*".to_owned(), "*/".to_owned())],
+            supports_nested_comments : false,
+            supports_char_literals : false,
+            string_delimiters : vec![],
+            doc_comment_symbols : vec![],
+            branch_keywords : vec![],
+            statement_symbols : vec![],
+            heredoc_symbols : vec![],
+            embedded_regions : vec![],
+            category : FileCategory::Code,
             keywords : vec![CLASS.clone()]
         };
 
@@ -985,8 +2323,16 @@ mod tests {
             extensions : vec!["py".to_owned()],
             string_symbols : vec!["\"".to_owned(),"'".to_owned()],
             comment_symbols : vec!["#".to_owned()],
-            multiline_comment_start_symbol : None,
-            multiline_comment_end_symbol : None,
+            multiline_comment_symbols : vec![],
+            supports_nested_comments : false,
+            supports_char_literals : false,
+            string_delimiters : vec![],
+            doc_comment_symbols : vec![],
+            branch_keywords : vec![],
+            statement_symbols : vec![],
+            heredoc_symbols : vec![],
+            embedded_regions : vec![],
+            category : FileCategory::Code,
             keywords : vec![CLASS.clone()]
         };
 
@@ -995,8 +2341,16 @@ mod tests {
             extensions : vec!["rs".to_owned()],
             string_symbols : vec!["\"".to_owned()],
             comment_symbols : vec!["//".to_owned()],
-            multiline_comment_start_symbol : Some("/*".to_owned()),
-            multiline_comment_end_symbol : Some("*/".to_owned()),
+            multiline_comment_symbols : vec![("/*".to_owned(), "*/".to_owned())],
+            supports_nested_comments : false,
+            supports_char_literals : false,
+            string_delimiters : vec![],
+            doc_comment_symbols : vec![],
+            branch_keywords : vec![],
+            statement_symbols : vec![],
+            heredoc_symbols : vec![],
+            embedded_regions : vec![],
+            category : FileCategory::Code,
             keywords : vec![STRUCT.clone(),ENUM.clone(),TRAIT.clone()]
         };
 
@@ -1004,107 +2358,669 @@ mod tests {
                 Arc::new(io_handler::parse_supported_languages_to_map(&LOCAL_APP_PATHS.languages_dir).unwrap().0);
     }
 
+    fn single_file_content_info(lines: usize, code_lines: usize, keyword_occurences: HashMap<String,usize>) -> LanguageContentInfo {
+        let mut content_info = LanguageContentInfo::new(lines, code_lines, keyword_occurences);
+        content_info.lines_distribution.add_file(lines, code_lines, lines == 0);
+        content_info
+    }
+
     #[test]
     fn test_correct_parsing_of_test_dir() {
         let mut buf = String::with_capacity(150);
 
         let mut config = Configuration::new(vec!["a".to_owned()]);
-        let result = parse_file(Path::new("test_dir/lang_files/a.txt"), "Java", &mut buf, LANGUAGE_MAP_REF.clone(), &config);
+        let result = parse_file(Path::new("test_dir/lang_files/a.txt"), "Java", &mut buf, LANGUAGE_MAP_REF.clone(), &config, &[]);
         let result = LanguageContentInfo::from(result.unwrap());
-        assert_eq!(LanguageContentInfo::new(44, 13, hashmap!("classes".to_owned()=>3,"interfaces".to_owned()=>0)), result);
+        assert_eq!(single_file_content_info(44, 13, hashmap!("classes".to_owned()=>3,"interfaces".to_owned()=>0)), result);
         buf.clear();
         config.set_should_not_count_keywords(true);
-        let result = parse_file(Path::new("test_dir/lang_files/a.txt"), "Java", &mut buf, LANGUAGE_MAP_REF.clone(), &config);
+        let result = parse_file(Path::new("test_dir/lang_files/a.txt"), "Java", &mut buf, LANGUAGE_MAP_REF.clone(), &config, &[]);
         let result = LanguageContentInfo::from(result.unwrap());
-        assert_eq!(LanguageContentInfo::new(44, 13, hashmap!()), result);
+        assert_eq!(single_file_content_info(44, 13, hashmap!()), result);
         buf.clear();
         config.set_should_not_count_keywords(false);
-        let result = parse_file(Path::new("test_dir/lang_files/a.txt"), "C#", &mut buf, LANGUAGE_MAP_REF.clone(), &Configuration::new(vec!["a".to_owned()]));
+        let result = parse_file(Path::new("test_dir/lang_files/a.txt"), "C#", &mut buf, LANGUAGE_MAP_REF.clone(), &Configuration::new(vec!["a".to_owned()]), &[]);
         let result = LanguageContentInfo::from(result.unwrap());
-        assert_eq!(LanguageContentInfo::new(44, 13, hashmap!("structs".to_owned()=>0,"classes".to_owned()=>3,"interfaces".to_owned()=>0)), result);
+        assert_eq!(single_file_content_info(44, 13, hashmap!("structs".to_owned()=>0,"classes".to_owned()=>3,"interfaces".to_owned()=>0)), result);
         buf.clear();
         
-        let result = parse_file(Path::new("test_dir/lang_files/d.txt"), "C#", &mut buf, LANGUAGE_MAP_REF.clone(), &Configuration::new(vec!["a".to_owned()]));
+        let result = parse_file(Path::new("test_dir/lang_files/d.txt"), "C#", &mut buf, LANGUAGE_MAP_REF.clone(), &Configuration::new(vec!["a".to_owned()]), &[]);
         let result = LanguageContentInfo::from(result.unwrap());
-        assert_eq!(LanguageContentInfo::new(19, 7, hashmap!("structs".to_owned()=>0,"classes".to_owned()=>5,"interfaces".to_owned()=>0)), result);
+        assert_eq!(single_file_content_info(19, 7, hashmap!("structs".to_owned()=>0,"classes".to_owned()=>5,"interfaces".to_owned()=>0)), result);
         buf.clear();
-        let result = parse_file(Path::new("test_dir/lang_files/d.txt"), "Java", &mut buf, LANGUAGE_MAP_REF.clone(), &Configuration::new(vec!["a".to_owned()]));
+        let result = parse_file(Path::new("test_dir/lang_files/d.txt"), "Java", &mut buf, LANGUAGE_MAP_REF.clone(), &Configuration::new(vec!["a".to_owned()]), &[]);
         let result = LanguageContentInfo::from(result.unwrap());
-        assert_eq!(LanguageContentInfo::new(19, 7, hashmap!("classes".to_owned()=>5,"interfaces".to_owned()=>0)), result);
+        assert_eq!(single_file_content_info(19, 7, hashmap!("classes".to_owned()=>5,"interfaces".to_owned()=>0)), result);
         buf.clear();
 
-        let result = parse_file(Path::new("test_dir/lang_files/b.txt"), "Java", &mut buf, LANGUAGE_MAP_REF.clone(), &Configuration::new(vec!["a".to_owned()]));
+        let result = parse_file(Path::new("test_dir/lang_files/b.txt"), "Java", &mut buf, LANGUAGE_MAP_REF.clone(), &Configuration::new(vec!["a".to_owned()]), &[]);
         let result = LanguageContentInfo::from(result.unwrap());
-        assert_eq!(LanguageContentInfo::new(19, 11, hashmap!("classes".to_owned()=>7,"interfaces".to_owned()=>0)), result);
+        assert_eq!(single_file_content_info(19, 11, hashmap!("classes".to_owned()=>7,"interfaces".to_owned()=>0)), result);
         buf.clear();
 
-        let result = parse_file(Path::new("test_dir/lang_files/c.txt"), "Python", &mut buf, LANGUAGE_MAP_REF.clone(), &Configuration::new(vec!["a".to_owned()]));
+        let result = parse_file(Path::new("test_dir/lang_files/c.txt"), "Python", &mut buf, LANGUAGE_MAP_REF.clone(), &Configuration::new(vec!["a".to_owned()]), &[]);
         let result = LanguageContentInfo::from(result.unwrap());
-        assert_eq!(LanguageContentInfo::new(11, 6, hashmap!("classes".to_owned()=>2)), result);
+        assert_eq!(single_file_content_info(11, 6, hashmap!("classes".to_owned()=>2)), result);
         buf.clear();
     }
 
+    #[test]
+    fn test_parsing_in_blocks() {
+        let mut buf = String::with_capacity(150);
+        let config = Configuration::new(vec!["a".to_owned()]);
+        for (file, lang_name) in [("a.txt", "Java"), ("b.txt", "Java"), ("c.txt", "Python"), ("d.txt", "C#")].iter() {
+            let path = format!("test_dir/lang_files/{}", file);
+            let language = LANGUAGE_MAP_REF.get(*lang_name).unwrap();
+            let expected = parse_lines(BufReader::new(File::open(&path).unwrap()), &mut buf, language, &config, &[]).unwrap();
+            for block_size in [1, 7, 64, LARGE_FILE_BLOCK_SIZE].iter() {
+                assert_eq!(expected, parse_blocks(File::open(&path).unwrap(), *block_size, language, &config, &[]).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_parsing_in_segments() {
+        let mut config = Configuration::new(Vec::new());
+        config.set_hygiene(true).set_indentation(true).set_licenses(true).set_logical_lines(true).set_clones(Some(3))
+                .set_todos(Some(vec!["TODO".to_owned()]));
+        let mut contents = "\u{feff}// SPDX-License-Identifier: MIT\n".to_owned();
+        for i in 0..200 {
+            contents += &format!("struct S{};\n    let s = \"struct\";\n        // TODO: {}\n/* a comment\n\n that goes on */\r\n", i, i);
+        }
+        let path = std::env::temp_dir().join("mezura-test-segments.rs");
+        fs::write(&path, &contents).unwrap();
+
+        let expected = parse_contents(contents.as_bytes(), contents.len(), &RUST, &mut String::new(), &config, &[]).unwrap();
+        assert_eq!((1201, 200, Some("MIT".to_owned())), (expected.lines, expected.todos.len(), expected.license.clone()));
+        // With many segments, most of them start inside a comment of the previous one
+        for segments in [2, 3, 7, 100] {
+            assert_eq!(expected, parse_in_segments(&path, File::open(&path).unwrap(), contents.len(), segments, &RUST, &config).unwrap());
+        }
+        assert_eq!(1, get_segment_count(contents.len(), &RUST, &config, &[]));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_prose_parsing() {
+        let mut buf = String::with_capacity(150);
+        let config = Configuration::new(vec!["a".to_owned()]);
+        let parse_prose = |lang_name: &str, contents: &str, buf: &mut String| {
+            let language = Language::new(lang_name.to_owned(), vec![], vec![], vec![], vec![], vec![]);
+            let file_stats = parse_lines(BufReader::new(contents.as_bytes()), buf, &language, &config, &[]).unwrap();
+            (file_stats.lines, file_stats.code_lines, file_stats.prose)
+        };
+        let prose = |words, headings, code_blocks| ProseStats {words, headings, code_blocks};
+
+        let markdown = "# Title #\n\nSome *text* - here.\n\n```rust\nfn main() {}\n```\n\nSubtitle\n---\n\n---\n~~~\nnot counted\n~~~\n";
+        assert_eq!((15, 0, prose(5, 2, 2)), parse_prose("Markdown", markdown, &mut buf));
+
+        let rst = "=====\nTitle\n=====\n\nSection\n-------\n\nAn example::\n\n    not counted\n\nBack to text.\n\n.. code-block:: python\n\n    x = 1\n";
+        assert_eq!((16, 0, prose(7, 2, 2)), parse_prose("reStructuredText", rst, &mut buf));
+
+        let asciidoc = "= Title\n\n== Section\nText here.\n\n----\nnot counted\n----\n";
+        assert_eq!((8, 0, prose(4, 2, 1)), parse_prose("AsciiDoc", asciidoc, &mut buf));
+
+        assert_eq!((2, 0, prose(5, 0, 0)), parse_prose("Text", "# Not a heading\n```but words```\n", &mut buf));
+    }
+
+    #[test]
+    fn test_nested_comments() {
+        let mut buf = String::with_capacity(150);
+        let config = Configuration::new(vec!["a".to_owned()]);
+        let mut nested_rust = RUST.clone();
+        nested_rust.supports_nested_comments = true;
+        let count = |language: &Language, contents: &str, buf: &mut String| {
+            let file_stats = parse_lines(BufReader::new(contents.as_bytes()), buf, language, &config, &[]).unwrap();
+            (file_stats.lines, file_stats.code_lines)
+        };
+
+        let contents = "/* a /* b */\nlet x = 1;\n*/\nlet y = 2; /* c */ let z = 3;\n";
+        assert_eq!((4, 1), count(&nested_rust, contents, &mut buf));
+        assert_eq!((4, 3), count(&RUST, contents, &mut buf));
+
+        assert_eq!((3, 3), count(&nested_rust, "let s = \"/* not a comment\";\n/* /* */ */ let t = 4; // /*\nlet u = 5;\n", &mut buf));
+        assert_eq!((3, 2), count(&nested_rust, "let s = \"a \\\" /*\n*/\";\n/*/**/*/\n", &mut buf));
+    }
+
+    #[test]
+    fn test_parse_str() {
+        let file_stats = parse_str("\u{feff}struct A; // struct B\n\n/* struct C */ let s = \"struct D\";\n", &RUST).unwrap();
+        assert_eq!((3, 2), (file_stats.lines, file_stats.code_lines));
+        assert_eq!(1, file_stats.keyword_occurences["structs"]);
+
+        assert_eq!(0, parse_str("", &RUST).unwrap().lines);
+    }
+
+    #[test]
+    fn test_line_plugins() {
+        let mut buf = String::new();
+        let config = Configuration::new(vec!["a".to_owned()]);
+        let plugins : Vec<Box<dyn MetricPlugin>> = vec![
+            Box::new(LineMetric::new("queries", |x: &LineContext| x.is_code && x.line.contains("SELECT"))),
+            Box::new(LineMetric::new("documented", |x: &LineContext| x.is_doc))
+        ];
+        let mut rust = RUST.clone();
+        rust.doc_comment_symbols = vec!["///".to_owned()];
+        let contents = "/// SELECT the rows\nlet q = \"SELECT 1\";\n// SELECT 2\n/* SELECT 3\n*/ run(\"SELECT 4\");\n";
+        let file_stats = parse_lines(BufReader::new(contents.as_bytes()), &mut buf, &rust, &config, &plugins).unwrap();
+        assert_eq!(BTreeMap::from([("queries".to_owned(), 2), ("documented".to_owned(), 1)]), file_stats.plugin_metrics);
+        assert_eq!(PluginMetrics::new(), parse_lines(BufReader::new(contents.as_bytes()), &mut buf, &RUST, &config, &[]).unwrap().plugin_metrics);
+    }
+
+    #[test]
+    fn test_parse_str_of_malformed_input() {
+        let mut no_multiline_rust = RUST.clone();
+        no_multiline_rust.multiline_comment_symbols.clear();
+        let mut nested_rust = RUST.clone();
+        nested_rust.supports_nested_comments = true;
+        let inputs = ["/*", "*/*/*", "\"", "\\\"\\", "r#\"/*\"#*/", "/*/**/", "é/*é*/é\"é", "\r\n\r", "\u{feff}\u{feff}/*", "'''\"\"\"#//"];
+        for language in [&*RUST, &*PYTHON, &*PHP, &no_multiline_rust, &nested_rust] {
+            for contents in inputs {
+                assert!(parse_str(contents, language).unwrap().lines <= 3);
+            }
+        }
+
+        let mut buf = String::new();
+        assert_eq!(Err(FaultyReason::UnknownLanguage("Zig".to_owned())),
+                parse_file(Path::new("test_dir/a.rs"), "Zig", &mut buf, LANGUAGE_MAP_REF.clone(), &Configuration::new(Vec::new()), &[]));
+    }
+
+    #[test]
+    fn test_detect_license() {
+        assert_eq!(Some("MIT OR Apache-2.0".to_owned()), detect_license("// SPDX-License-Identifier: MIT OR Apache-2.0\n"));
+        assert_eq!(Some("GPL-2.0-only".to_owned()), detect_license("/* SPDX-License-Identifier: GPL-2.0-only */\n"));
+        assert_eq!(Some("Apache-2.0".to_owned()), detect_license("/*\n * Licensed under the Apache License,\n * Version 2.0 (the \"License\");\n */\n"));
+        assert_eq!(Some("MIT".to_owned()), detect_license("# Copyright (c) 2020 A\n#\n# Permission is hereby granted, free of\n# charge, to any person\n"));
+        assert_eq!(Some("GPL-3.0".to_owned()), detect_license("// under the terms of the GNU General Public License as published by\n\
+                // the Free Software Foundation, either version 3 of the License\n"));
+        assert_eq!(Some("LGPL-2.1".to_owned()), detect_license("// GNU Lesser General Public License, version 2.1\n"));
+        assert_eq!(None, detect_license("// Parses the arguments\n"));
+        assert_eq!(None, detect_license(""));
+    }
+
+    #[test]
+    fn test_license_header() {
+        let mut config = Configuration::new(Vec::new());
+        config.set_licenses(true);
+        let parse = |contents: &str| parse_contents(contents.as_bytes(), contents.len(), &RUST, &mut String::new(), &config, &[]).unwrap().license;
+
+        assert_eq!(Some("MIT".to_owned()), parse("// Copyright 2024 A\n\n// SPDX-License-Identifier: MIT\n\nfn main() {}\n"));
+        assert_eq!(Some("MIT".to_owned()), parse("/*\n Permission is hereby granted,\n free of charge\n*/\n"));
+        // After the first code line it is not a header anymore
+        assert_eq!(None, parse("fn main() {}\n// SPDX-License-Identifier: MIT\n"));
+        assert_eq!(None, parse(&format!("{}// SPDX-License-Identifier: MIT\n", "//\n".repeat(LICENSE_HEADER_MAX_LINES))));
+    }
+
+    #[test]
+    fn test_line_lengths() {
+        let mut config = Configuration::new(Vec::new());
+        config.set_max_line_length(10);
+        let contents = "let a = 1;\r\nlet b = \"ééé\";\n\nlet long_name = 1;\n    // a long comment\r\n";
+        let file_stats = parse_contents(contents.as_bytes(), contents.len(), &RUST, &mut String::new(), &config, &[]).unwrap();
+        assert_eq!((21, 3), (file_stats.max_line_length, file_stats.long_lines));
+
+        let file_stats = parse_blocks(contents.as_bytes(), 4, &RUST, &config, &[]).unwrap();
+        assert_eq!((21, 3), (file_stats.max_line_length, file_stats.long_lines));
+    }
+
+    #[test]
+    fn test_indentation() {
+        let mut config = Configuration::new(Vec::new());
+        config.set_indentation(true);
+        let contents = "fn main() {\n    let a = 1;\n    if a > 0 {\n        // a comment\n        run(a,\n            a);\n    }\n\tlet b = 2;\n\t  let c = 3;\n}\n";
+        let indentation = parse_contents(contents.as_bytes(), contents.len(), &RUST, &mut String::new(), &config, &[]).unwrap().indentation.unwrap();
+
+        assert_eq!(IndentationStats {tab_lines: 1, space_lines: 4, mixed_lines: 1, width_counts: [0, 0, 0, 3, 0, 0, 0, 0]}, indentation);
+        assert_eq!(Some(4), indentation.dominant_width());
+        assert_eq!(None, IndentationStats::default().dominant_width());
+    }
+
+    #[test]
+    fn test_generated_marker() {
+        let config = Configuration::new(Vec::new());
+        let is_generated = |contents: &str| parse_contents(contents.as_bytes(), contents.len(), &RUST, &mut String::new(), &config, &[])
+                .unwrap().is_generated;
+
+        assert!(is_generated("// Copyright 2024\n\n// Code generated by protoc-gen-go. DO NOT EDIT.\n\nfn main() {}\n"));
+        assert!(is_generated("/*\n * @generated\n */\nfn main() {}\n"));
+        assert!(!is_generated("fn main() {}\n// DO NOT EDIT\n"));
+        assert!(!is_generated("fn main() {\n    println!(\"@generated\");\n}\n"));
+        assert!(!is_generated(&format!("{}// @generated\n", "//\n".repeat(GENERATED_HEADER_MAX_LINES))));
+    }
+
+    #[test]
+    fn test_minified_lines() {
+        let config = Configuration::new(Vec::new());
+        let long_line = format!("{}// class", "class A{} ".repeat(5000));
+        for contents in [format!("int i;\n{}\nint j;\n", long_line), format!("int i;\n{}", long_line)].iter() {
+            let by_lines = parse_lines(contents.as_bytes(), &mut String::new(), &JAVA, &config, &[]).unwrap();
+            let by_blocks = parse_blocks(contents.as_bytes(), 4096, &JAVA, &config, &[]).unwrap();
+            assert_eq!(by_lines, by_blocks);
+            assert!(by_lines.is_minified);
+            assert_eq!(contents.lines().count(), by_lines.lines);
+            assert_eq!(contents.lines().count(), by_lines.code_lines);
+            assert_eq!(Some(&5000), by_lines.keyword_occurences.get("classes"));
+            assert_eq!(long_line.len(), by_lines.max_line_length);
+        }
+        assert!(!parse_lines("int i;\n".as_bytes(), &mut String::new(), &JAVA, &config, &[]).unwrap().is_minified);
+
+        assert_eq!(6, find_chunk_end(b"ab;cd;ef"));
+        assert_eq!(7, find_chunk_end("a;bcdef\u{e9}".as_bytes()));
+        assert_eq!(4, find_chunk_end(b"abcd"));
+    }
+
+    // The same lines with every line ending, with and without a final one, by every way of reading a file
+    #[test]
+    fn test_line_endings() {
+        let config = Configuration::new(Vec::new());
+        let contents = "// a comment\nclass A {\n\n  int a; /* a\n  comment */\n}\n";
+        let expected = parse_lines(contents.as_bytes(), &mut String::new(), &JAVA, &config, &[]).unwrap();
+        assert_eq!((6, 2, 1), (expected.lines, expected.code_lines, expected.keyword_occurences["classes"]));
+
+        for ending in ["\n", "\r\n", "\r"] {
+            for has_final_ending in [true, false] {
+                let mut text = contents.replace('\n', ending);
+                if !has_final_ending {
+                    text.truncate(text.len() - ending.len());
+                }
+                let case = format!("{:?}, final line ending: {}", ending, has_final_ending);
+                assert_eq!(expected, parse_lines(text.as_bytes(), &mut String::new(), &JAVA, &config, &[]).unwrap(), "{}", case);
+                for block_size in [1, 2, 7, 4096] {
+                    assert_eq!(expected, parse_blocks(text.as_bytes(), block_size, &JAVA, &config, &[]).unwrap(), "{}, block {}", case, block_size);
+                }
+                let utf16 = UTF16_LE_BOM.iter().copied().chain(text.encode_utf16().flat_map(|x| x.to_le_bytes())).collect::<Vec<_>>();
+                assert_eq!(expected, parse_contents(&utf16[..], utf16.len(), &JAVA, &mut String::new(), &config, &[]).unwrap(), "{}, UTF-16", case);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ignore_trailing_empty_line() {
+        let mut config = Configuration::new(Vec::new());
+        let lines = |contents: &str, config: &Configuration| parse_lines(contents.as_bytes(), &mut String::new(), &JAVA, config, &[]).unwrap().lines;
+        assert_eq!(vec![1, 2, 2, 2, 3, 3], ["int a;\n", "int a;\n\n", "int a;\r\n\r\n", "int a;\r\r", "int a;\n\n  \n", "int a;\n\nint b;"]
+                .iter().map(|x| lines(x, &config)).collect::<Vec<_>>());
+        config.set_ignore_trailing_empty_line(true);
+        assert_eq!(vec![1, 1, 1, 1, 2, 3], ["int a;\n", "int a;\n\n", "int a;\r\n\r\n", "int a;\r\r", "int a;\n\n  \n", "int a;\n\nint b;"]
+                .iter().map(|x| lines(x, &config)).collect::<Vec<_>>());
+        assert_eq!(0, lines("\n", &config));
+    }
+
+    #[test]
+    fn test_hygiene() {
+        let mut config = Configuration::new(Vec::new());
+        config.set_hygiene(true);
+        let parse = |contents: &[u8], config: &Configuration| parse_contents(contents, contents.len(), &PYTHON, &mut String::new(), config, &[]).unwrap().hygiene;
+        let hygiene = |has_bom, has_shebang, lf_lines, crlf_lines, ends_with_newline| Some(FileHygiene {has_bom, has_shebang, lf_lines, crlf_lines,
+                ends_with_newline});
+
+        assert_eq!(hygiene(false, true, 2, 0, true), parse(b"#!/usr/bin/env python\nx = 1\n", &config));
+        assert_eq!(hygiene(true, true, 1, 1, false), parse(b"\xEF\xBB\xBF#!/usr/bin/env python\r\nx = 1\ny = 2", &config));
+        assert_eq!(hygiene(true, false, 0, 2, true), parse(b"\xFF\xFEx\0 \0=\0 \x001\0\r\0\n\0y\0\r\0\n\0", &config));
+        assert_eq!(hygiene(false, false, 0, 0, false), parse(b"", &config));
+        assert_eq!(hygiene(false, true, 1, 1, false), parse_blocks(&b"#!/bin/python\r\nx = 1\ny = 2"[..], 4, &PYTHON, &config, &[]).unwrap().hygiene);
+        config.set_strict_encoding(true);
+        assert_eq!(hygiene(true, true, 0, 1, true), parse(b"\xEF\xBB\xBF#!/bin/python\r\n", &config));
+        assert_eq!(None, parse(b"x = 1\n", &Configuration::new(Vec::new())));
+    }
+
+    #[test]
+    fn test_hashing_reader() {
+        let mut config = Configuration::new(Vec::new());
+        config.set_duplicates(true);
+        let contents = "\u{feff}fn main() {\n    // a comment\n    println!(\"hello\");\n}\n".repeat(20);
+        for capacity in [1, 16, 4096] {
+            let mut reader = HashingReader::new(BufReader::with_capacity(capacity, contents.as_bytes()));
+            parse_contents(&mut reader, contents.len(), &RUST, &mut String::new(), &config, &[]).unwrap();
+            assert_eq!(hashing::sha256(contents.as_bytes()), reader.finish().unwrap());
+        }
+
+        let file_stats = parse_archive_entry(contents.as_bytes(), Path::new("a.zip/a.rs"), "Rust", &mut String::new(), LANGUAGE_MAP_REF.clone(), &config, &[]);
+        assert_eq!(Some(hashing::sha256(contents.as_bytes())), file_stats.unwrap().content_hash);
+    }
+
+    #[test]
+    fn test_string_delimiters() {
+        let mut buf = String::with_capacity(150);
+        let config = Configuration::new(vec!["a".to_owned()]);
+        let delimiter = |start: &str, end: &str, is_raw| StringDelimiter {start: start.to_owned(), end: end.to_owned(), is_raw, interpolation: None};
+        let keywords = |language: &Language, contents: &str, keyword: &str, buf: &mut String| {
+            let file_stats = parse_lines(BufReader::new(contents.as_bytes()), buf, language, &config, &[]).unwrap();
+            (file_stats.code_lines, file_stats.keyword_occurences[keyword])
+        };
+
+        let mut rust = RUST.clone();
+        rust.string_delimiters = vec![delimiter("r#\"", "\"#", true), delimiter("r\"", "\"", true)];
+        let contents = "let s = r\"C:\\dir\\\"; struct A;\n";
+        assert_eq!((1, 1), keywords(&rust, contents, "structs", &mut buf));
+        assert_eq!((1, 0), keywords(&RUST, contents, "structs", &mut buf));
+        let contents = "let s = r#\"a \"struct\" /* \"#; struct A;\nlet t = r#\"\n// struct\n\"#;\n";
+        assert_eq!((4, 1), keywords(&rust, contents, "structs", &mut buf));
+
+        let mut python = PYTHON.clone();
+        python.string_delimiters = vec![delimiter("\"\"\"", "\"\"\"", false), delimiter("'''", "'''", false)];
+        let contents = "x = \"\"\"a \" # b\nclass A\n\\\"\"\" '''\n\"\"\" # class\n";
+        assert_eq!((4, 0), keywords(&python, contents, "classes", &mut buf));
+        assert_eq!((4, 1), keywords(&PYTHON, contents, "classes", &mut buf));
+    }
+
+    #[test]
+    fn test_char_literals() {
+        let mut buf = String::with_capacity(150);
+        let config = Configuration::new(vec!["a".to_owned()]);
+        let count = |language: &Language, contents: &str, buf: &mut String| {
+            let file_stats = parse_lines(BufReader::new(contents.as_bytes()), buf, language, &config, &[]).unwrap();
+            (file_stats.code_lines, file_stats.keyword_occurences["structs"])
+        };
+
+        let mut rust = RUST.clone();
+        rust.supports_char_literals = true;
+        let contents = "let q = '\"'; // comment\n// struct A;\n";
+        assert_eq!((1, 0), count(&rust, contents, &mut buf));
+        assert_eq!((2, 0), count(&RUST, contents, &mut buf));
+        let contents = "let q = b'\"'; let e = '\\''; let u = '\\u{22}';\n// struct A;\nfn a<'a>(x: &'a str) -> char {'\"'}\nstruct B;\n";
+        assert_eq!((3, 1), count(&rust, contents, &mut buf));
+        // The string and the comment that a scanned line opens are closed by lines without char literals, and the other way around
+        let contents = "let q = '\"'; let s = \"a\n// struct A;\n\"; /* struct B;\nstruct C; */ let c = '*';\nstruct D;\n";
+        assert_eq!((5, 1), count(&rust, contents, &mut buf));
+    }
+
+    #[test]
+    fn test_interpolations_and_heredocs() {
+        let mut buf = String::with_capacity(150);
+        let config = Configuration::new(vec!["a".to_owned()]);
+        let classes = |language: &Language, contents: &str, buf: &mut String| {
+            let file_stats = parse_lines(BufReader::new(contents.as_bytes()), buf, language, &config, &[]).unwrap();
+            (file_stats.code_lines, file_stats.keyword_occurences["classes"])
+        };
+        let template = |interpolation: Option<(&str, &str)>| StringDelimiter {start: "`".to_owned(), end: "`".to_owned(), is_raw: false,
+                interpolation: interpolation.map(|(start, end)| (start.to_owned(), end.to_owned()))};
+
+        let mut js = JAVA.clone();
+        js.string_delimiters = vec![template(Some(("${", "}")))];
+        let contents = "let s = `a ${x + `b ${ {a: 1}.a } class`} // class\nclass A\n`; class B {}\n\
+                let t = `${\n  f(/* class */)\n}` // class\n";
+        assert_eq!((6, 1), classes(&js, contents, &mut buf));
+        js.string_delimiters = vec![template(None)];
+        assert_eq!((6, 2), classes(&js, contents, &mut buf));
+
+        let mut php = JAVA.clone();
+        php.heredoc_symbols = vec!["<<<".to_owned()];
+        let contents = "$a = <<<EOT\n// class\nclass A\nEOT;\nclass B {}\n$b = <<<'EOT' . f(); // x\nclass C\n  EOTS\n  EOT\n$c = 1 <<< 2;\n";
+        assert_eq!((10, 1), classes(&php, contents, &mut buf));
+        assert_eq!((9, 3), classes(&JAVA, contents, &mut buf));
+    }
+
+    #[test]
+    fn test_doc_comments() {
+        let mut buf = String::with_capacity(150);
+        let config = Configuration::new(vec!["a".to_owned()]);
+        let doc_lines = |language: &Language, contents: &str, buf: &mut String| {
+            parse_lines(BufReader::new(contents.as_bytes()), buf, language, &config, &[]).unwrap().doc_lines
+        };
+
+        let mut rust = RUST.clone();
+        rust.doc_comment_symbols = vec!["///".to_owned(), "//!".to_owned(), "/**".to_owned()];
+        let contents = "//! Crate docs\n/// A struct\n/** Block\n\n docs */\nstruct A; // not docs\n/* plain\n comment */\n\
+                let s = \"\n/// inside a string\n\";\n/**/ let i = 0;\n";
+        assert_eq!(4, doc_lines(&rust, contents, &mut buf));
+        assert_eq!(0, doc_lines(&RUST, contents, &mut buf));
+
+        let mut python = PYTHON.clone();
+        python.string_delimiters = vec![StringDelimiter {start: "\"\"\"".to_owned(), end: "\"\"\"".to_owned(), is_raw: false, interpolation: None}];
+        python.doc_comment_symbols = vec!["\"\"\"".to_owned()];
+        let contents = "def f():\n    \"\"\"Docs\n    more docs\n    \"\"\"\n    x = \"\"\"not docs\"\"\"\n    return x\n";
+        assert_eq!(3, doc_lines(&python, contents, &mut buf));
+
+        // The doc lines are extra lines in every language, whether the docs are comments or strings
+        let code_lines = |language: &Language, contents: &str, buf: &mut String| {
+            parse_lines(BufReader::new(contents.as_bytes()), buf, language, &config, &[]).unwrap().code_lines
+        };
+        assert_eq!(3, code_lines(&python, contents, &mut buf));
+        assert_eq!(1, code_lines(&python, "\"\"\"Module docs\"\"\"\nx = 1\n", &mut buf));
+        assert_eq!(1, code_lines(&rust, "/// Docs\n/** more\n docs */\nstruct A;\n", &mut buf));
+    }
+
+    #[test]
+    fn test_keyword_scopes() {
+        let mut buf = String::with_capacity(150);
+        let config = Configuration::new(vec!["a".to_owned()]);
+        let mut interface = INTERFACE.clone();
+        interface.scope = KeywordScope::Comments;
+        let mut class = CLASS.clone();
+        class.scope = KeywordScope::All;
+        let mut java = JAVA.clone();
+        java.keywords = vec![class, interface];
+        let contents = "class A implements B { // the interface of class B
+/* interface
+   interface */ interface C {}
+String s = \"// class interface\";
+";
+        let stats = parse_lines(BufReader::new(contents.as_bytes()), &mut buf, &java, &config, &[]).unwrap();
+        assert_eq!(Some(&2), stats.keyword_occurences.get("classes"));
+        assert_eq!(Some(&3), stats.keyword_occurences.get("interfaces"));
+        assert_eq!(get_comment_part("i++; /* a */ j++;", &JAVA, false), Some("a"));
+        assert_eq!(get_comment_part("a */ j++;", &JAVA, true), Some("a"));
+        assert_eq!(get_comment_part("j++;", &JAVA, false), None);
+    }
+
+    #[test]
+    fn test_todos() {
+        let mut buf = String::with_capacity(150);
+        let mut config = Configuration::new(vec!["a".to_owned()]);
+        config.set_todos(Some(vec![]));
+        let contents = "// TODO: split this
+fn main() { // FIXME the loop below
+/* HACK */
+/*
+  TODO(later) \n*/
+                let s = \"TODO not a comment\";
+// TODOS and MY_TODO are not markers
+";
+        let todos = parse_lines(BufReader::new(contents.as_bytes()), &mut buf, &RUST, &config, &[]).unwrap().todos;
+        let expected = [("TODO", 1, "split this"), ("FIXME", 2, "the loop below"), ("HACK", 3, ""), ("TODO", 5, "(later)")]
+                .iter().map(|(marker, line, text)| TodoItem {marker: (*marker).to_owned(), path: PathBuf::new(), line: *line, text: (*text).to_owned()})
+                .collect::<Vec<_>>();
+        assert_eq!(expected, todos);
+
+        config.set_todos(Some(vec!["XXX".to_owned()]));
+        let todos = parse_lines(BufReader::new(contents.as_bytes()), &mut buf, &RUST, &config, &[]).unwrap().todos;
+        assert!(todos.is_empty());
+        config.set_todos(None);
+        assert!(parse_lines(BufReader::new("// TODO\n".as_bytes()), &mut buf, &RUST, &config, &[]).unwrap().todos.is_empty());
+    }
+
+    #[test]
+    fn test_logical_lines() {
+        let mut buf = String::with_capacity(150);
+        let mut config = Configuration::new(vec!["a".to_owned()]);
+        let mut rust = RUST.clone();
+        rust.statement_symbols = vec![";".to_owned(), "{".to_owned()];
+        let contents = "fn main()
+{
+    let s = \"a; b {\"; // c; d
+    for i in 0..3 { x(i); }
+    /* e; */
+}
+";
+        assert_eq!(0, parse_lines(BufReader::new(contents.as_bytes()), &mut buf, &rust, &config, &[]).unwrap().logical_lines);
+
+        config.set_logical_lines(true);
+        assert_eq!(4, parse_lines(BufReader::new(contents.as_bytes()), &mut buf, &rust, &config, &[]).unwrap().logical_lines);
+        // Without statement symbols every code line is a logical line
+        let file_stats = parse_lines(BufReader::new("if x:\n    # y\n    pass\n".as_bytes()), &mut buf, &PYTHON, &config, &[]).unwrap();
+        assert_eq!(2, file_stats.logical_lines);
+    }
+
+    #[test]
+    fn test_branch_keywords() {
+        let mut buf = String::with_capacity(150);
+        let mut config = Configuration::new(vec!["a".to_owned()]);
+        let mut python = PYTHON.clone();
+        python.branch_keywords = vec!["if".to_owned(), "elif".to_owned(), "and".to_owned(), "or".to_owned(), "&&".to_owned()];
+        let contents = "if x and y:
+    pass
+elif order or x&&y: # if and or
+    s = 'if'
+if_x = android
+";
+        // Nothing is counted without '--complexity'
+        assert_eq!(0, parse_lines(BufReader::new(contents.as_bytes()), &mut buf, &python, &config, &[]).unwrap().complexity);
+        config.set_complexity(true);
+        let file_stats = parse_lines(BufReader::new(contents.as_bytes()), &mut buf, &python, &config, &[]).unwrap();
+        assert_eq!(5, file_stats.complexity);
+        assert_eq!(0, parse_lines(BufReader::new(contents.as_bytes()), &mut buf, &PYTHON, &config, &[]).unwrap().complexity);
+    }
+
+    #[test]
+    fn test_multiple_comment_kinds() {
+        let mut buf = String::with_capacity(150);
+        let config = Configuration::new(vec!["a".to_owned()]);
+        let mut language = PYTHON.clone();
+        language.comment_symbols = vec!["--".to_owned(), "//".to_owned(), "#".to_owned()];
+        language.multiline_comment_symbols = vec![("{-".to_owned(), "-}".to_owned()), ("(*".to_owned(), "*)".to_owned())];
+        let count = |language: &Language, contents: &str, buf: &mut String| {
+            let file_stats = parse_lines(BufReader::new(contents.as_bytes()), buf, language, &config, &[]).unwrap();
+            (file_stats.lines, file_stats.code_lines)
+        };
+
+        let contents = "x = 1 {- a (* -}\n(* b -} still\n*) y = 2\nz = 3 -- c\n# d\n// e\n";
+        assert_eq!((6, 3), count(&language, contents, &mut buf));
+
+        language.supports_nested_comments = true;
+        assert_eq!((3, 1), count(&language, "{- a {- b (* -}\nx = 1\n-} y\n", &mut buf));
+        assert_eq!((1, 1), count(&language, "(* a {- *) x = \"(*\"\n", &mut buf));
+    }
+
+    #[test]
+    fn test_file_timeout() {
+        let language = LANGUAGE_MAP_REF.get("Java").unwrap();
+        let mut config = Configuration::new(vec!["a".to_owned()]);
+        let mut line_parser = LineParser::new(language, &config, &[]);
+        assert!((0..TIMEOUT_CHECK_LINES * 2).all(|_| line_parser.check_timeout().is_ok()));
+
+        config.set_file_timeout(3);
+        let mut line_parser = LineParser::new(language, &config, &[]);
+        line_parser.deadline = Some(Instant::now());
+        // The clock is not read until enough lines are parsed
+        assert!((1..TIMEOUT_CHECK_LINES).all(|_| line_parser.check_timeout().is_ok()));
+        assert_eq!(Err(FaultyReason::TimedOut(3)), line_parser.check_timeout());
+    }
+
+    #[test]
+    fn test_encoding_tolerant_parsing() {
+        let mut buf = String::with_capacity(150);
+        let mut config = Configuration::new(vec!["a".to_owned()]);
+        let contents = fs::read_to_string("test_dir/lang_files/a.txt").unwrap();
+        let expected = parse_file(Path::new("test_dir/lang_files/a.txt"), "Java", &mut buf, LANGUAGE_MAP_REF.clone(), &config, &[]).unwrap();
+
+        let dir = std::env::temp_dir().join("mezura-test-encodings");
+        fs::create_dir_all(&dir).unwrap();
+        let utf8_bom = [&UTF8_BOM[..], contents.as_bytes()].concat();
+        let latin1 = contents.replacen("class", "class \u{e9}", 1).chars().map(|x| x as u8).collect::<Vec<u8>>();
+        let utf16_le = [UTF16_LE_BOM.to_vec(), contents.encode_utf16().flat_map(|x| x.to_le_bytes().to_vec()).collect()].concat();
+        let utf16_be = [UTF16_BE_BOM.to_vec(), contents.encode_utf16().flat_map(|x| x.to_be_bytes().to_vec()).collect()].concat();
+        
+        for (name, bytes) in [("utf8_bom", utf8_bom), ("latin1", latin1), ("utf16_le", utf16_le), ("utf16_be", utf16_be)].iter() {
+            let path = dir.join(name);
+            fs::write(&path, bytes).unwrap();
+            assert_eq!(expected, parse_file(&path, "Java", &mut buf, LANGUAGE_MAP_REF.clone(), &config, &[]).unwrap(), "{}", name);
+        }
+
+        let latin1_path = dir.join("latin1");
+        let language = LANGUAGE_MAP_REF.get("Java").unwrap();
+        assert_eq!(expected, parse_blocks(File::open(&latin1_path).unwrap(), 7, language, &config, &[]).unwrap());
+
+        config.set_strict_encoding(true);
+        assert_eq!(Err(FaultyReason::InvalidEncoding), parse_file(&latin1_path, "Java", &mut buf, LANGUAGE_MAP_REF.clone(), &config, &[]));
+        assert_eq!(Err(FaultyReason::InvalidEncoding), parse_blocks(File::open(&latin1_path).unwrap(), 7, language, &config, &[]));
+        assert!(parse_file(&dir.join("utf16_le"), "Java", &mut buf, LANGUAGE_MAP_REF.clone(), &config, &[]).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn finds_keywords_correctly() {
         let line = String::from("Hello world!");
         let mut file_stats =  FileStats::with_keywords(&[CLASS.clone(),INTERFACE.clone()]);
-        add_keywords_if_any(&line, &JAVA, &mut file_stats);
+        add_keywords_if_any(&line, &JAVA, &mut file_stats, false);
         assert_eq!(make_file_stats(0,0), file_stats);
 
         let line = String::from("class");
         let mut file_stats =  FileStats::with_keywords(&[CLASS.clone(),INTERFACE.clone()]);
-        add_keywords_if_any(&line, &JAVA, &mut file_stats);
+        add_keywords_if_any(&line, &JAVA, &mut file_stats, false);
         assert_eq!(make_file_stats(1,0), file_stats);
 
         let line = String::from("1class");
         let mut file_stats =  FileStats::with_keywords(&[CLASS.clone(),INTERFACE.clone()]);
-        add_keywords_if_any(&line, &JAVA, &mut file_stats);
+        add_keywords_if_any(&line, &JAVA, &mut file_stats, false);
         assert_eq!(make_file_stats(0,0), file_stats);
 
         let line = String::from("hello class word!");
         let mut file_stats =  FileStats::with_keywords(&[CLASS.clone(),INTERFACE.clone()]);
-        add_keywords_if_any(&line, &JAVA, &mut file_stats);
+        add_keywords_if_any(&line, &JAVA, &mut file_stats, false);
         assert_eq!(make_file_stats(1,0), file_stats);
 
         let line = String::from("class class class");
         let mut file_stats =  FileStats::with_keywords(&[CLASS.clone(),INTERFACE.clone()]);
-        add_keywords_if_any(&line, &JAVA, &mut file_stats);
+        add_keywords_if_any(&line, &JAVA, &mut file_stats, false);
         assert_eq!(make_file_stats(3,0), file_stats);
 
         let line = String::from("classclass");
         let mut file_stats =  FileStats::with_keywords(&[CLASS.clone(),INTERFACE.clone()]);
-        add_keywords_if_any(&line, &JAVA, &mut file_stats);
+        add_keywords_if_any(&line, &JAVA, &mut file_stats, false);
         assert_eq!(make_file_stats(0,0), file_stats);
 
         let line = String::from("hello,class{word!");
         let mut file_stats =  FileStats::with_keywords(&[CLASS.clone(),INTERFACE.clone()]);
-        add_keywords_if_any(&line, &JAVA, &mut file_stats);
+        add_keywords_if_any(&line, &JAVA, &mut file_stats, false);
         assert_eq!(make_file_stats(1,0), file_stats);
         
         let line = String::from("classe,");
         let mut file_stats =  FileStats::with_keywords(&[CLASS.clone(),INTERFACE.clone()]);
-        add_keywords_if_any(&line, &JAVA, &mut file_stats);
+        add_keywords_if_any(&line, &JAVA, &mut file_stats, false);
         assert_eq!(make_file_stats(0,0), file_stats);
         
         let line = String::from("class interfaceclass classinterface interface");
         let mut file_stats =  FileStats::with_keywords(&[CLASS.clone(),INTERFACE.clone()]);
-        add_keywords_if_any(&line, &JAVA, &mut file_stats);
+        add_keywords_if_any(&line, &JAVA, &mut file_stats, false);
         assert_eq!(make_file_stats(1,1), file_stats);
         
         let line = String::from("{class,interface}");
         let mut file_stats =  FileStats::with_keywords(&[CLASS.clone(),INTERFACE.clone()]);
-        add_keywords_if_any(&line, &JAVA, &mut file_stats);
+        add_keywords_if_any(&line, &JAVA, &mut file_stats, false);
         assert_eq!(make_file_stats(1,1), file_stats);
         
         let line = String::from("{class.interface}");
         let mut file_stats =  FileStats::with_keywords(&[CLASS.clone(),INTERFACE.clone()]);
-        add_keywords_if_any(&line, &JAVA, &mut file_stats);
+        add_keywords_if_any(&line, &JAVA, &mut file_stats, false);
         assert_eq!(make_file_stats(0,0), file_stats);
     }
 
+    #[test]
+    fn finds_case_insensitive_keywords() {
+        let mut class = CLASS.clone();
+        class.aliases = vec!["Class".to_owned()];
+        class.case_insensitive = true;
+        let mut java = JAVA.clone();
+        java.keywords = vec![class.clone(), INTERFACE.clone()];
+
+        let line = String::from("CLASS class Class interface INTERFACE classes");
+        let mut file_stats = FileStats::with_keywords(&[class.clone(), INTERFACE.clone()]);
+        add_keywords_if_any(&line, &java, &mut file_stats, false);
+        assert_eq!(make_file_stats(3,1), file_stats);
+
+        // The patterns have no word boundaries, so 'classes' counts too
+        java.compile_keyword_patterns().unwrap();
+        let mut file_stats = FileStats::with_keywords(&[class, INTERFACE.clone()]);
+        add_keywords_if_any(&line, &java, &mut file_stats, false);
+        assert_eq!(make_file_stats(4,1), file_stats);
+    }
+
     fn make_file_stats(class_occurances: usize, interface_occurances: usize) -> FileStats {
-        fn get_keyword_map(class_occurances: usize, interface_occurances: usize) -> HashMap<String,usize> {
-            let mut map = HashMap::<String,usize>::new();
+        fn get_keyword_map(class_occurances: usize, interface_occurances: usize) -> KeywordOccurences {
+            let mut map = KeywordOccurences::new();
             map.insert(CLASS.descriptive_name.clone(), class_occurances);
             map.insert(INTERFACE.descriptive_name.clone(), interface_occurances);
             map
@@ -1113,7 +3029,24 @@ mod tests {
         FileStats {
             lines: 0,
             code_lines: 0,
-            keyword_occurences : get_keyword_map(class_occurances, interface_occurances)
+            keyword_occurences : get_keyword_map(class_occurances, interface_occurances),
+            has_content: false,
+            doc_lines: 0,
+            complexity: 0,
+            logical_lines: 0,
+            todos: Vec::new(),
+            prose: ProseStats::default(),
+            plugin_metrics: PluginMetrics::new(),
+            content_hash: None,
+            code_line_hashes: Vec::new(),
+            license: None,
+            hygiene: None,
+            max_line_length: 0,
+            long_lines: 0,
+            indentation: None,
+            is_generated: false,
+            is_minified: false,
+            notebook: None
         }
     }
 
@@ -0,0 +1,264 @@
+use std::{ffi::OsStr, process::Command};
+
+use crate::*;
+
+
+// The files that are tracked by git (or changed since a revision) inside the provided directories, along with the
+// directories that lead to them, so that the search doesn't descend into directories without any of them.
+#[derive(Debug,Default)]
+pub struct TrackedFiles {
+    files: HashSet<PathBuf>,
+    dirs: HashSet<PathBuf>
+}
+
+impl TrackedFiles {
+    pub fn contains_file(&self, path: &Path) -> bool {
+        self.files.contains(path)
+    }
+
+    pub fn contains_dir(&self, path: &Path) -> bool {
+        self.dirs.contains(path)
+    }
+}
+
+// Asks git for the files in the index of the repository that contains each directory. The paths are joined
+// to the provided directories, so that they match the paths of the search. Files given directly are ignored.
+pub fn find_tracked_files(dirs: &[String]) -> Result<TrackedFiles, String> {
+    collect_files(dirs, list_tracked_files)
+}
+
+// Like 'find_tracked_files', but only with the files that changed since the common ancestor of the revision and HEAD,
+// including the uncommitted changes. Untracked files are not included.
+pub fn find_changed_files(dirs: &[String], revision: &str) -> Result<TrackedFiles, String> {
+    collect_files(dirs, |dir| list_changed_files(dir, revision))
+}
+
+fn collect_files(dirs: &[String], list_files: impl Fn(&Path) -> Result<Vec<String>, String>) -> Result<TrackedFiles, String> {
+    let mut tracked_files = TrackedFiles::default();
+    for dir in dirs.iter().map(Path::new).filter(|x| x.is_dir()) {
+        for relative_path in list_files(dir)? {
+            let path = dir.join(relative_path);
+            let mut parent = path.parent();
+            while let Some(x) = parent {
+                if !x.starts_with(dir) || !tracked_files.dirs.insert(x.to_path_buf()) {
+                    break;
+                }
+                parent = x.parent();
+            }
+            tracked_files.files.insert(path);
+        }
+    }
+    Ok(tracked_files)
+}
+
+fn list_tracked_files(dir: &Path) -> Result<Vec<String>, String> {
+    run_git(dir, &["ls-files", "-z", "--recurse-submodules"])
+}
+
+// The deleted files are left out, since there is nothing to count in them
+fn list_changed_files(dir: &Path, revision: &str) -> Result<Vec<String>, String> {
+    run_git(dir, &["diff", "--name-only", "-z", "--relative", "--diff-filter=d", "--merge-base", revision, "--"])
+}
+
+// The submodules inside the provided directories, as listed in the '.gitmodules' of the repository that contains each one.
+// The file is read instead of asking git, so that git doesn't have to be installed. With 'nested' the submodules of the
+// submodules are included too. Only the ones that are checked out are returned.
+pub fn find_submodule_dirs(dirs: &[String], nested: bool) -> Vec<PathBuf> {
+    let mut repositories = dirs.iter().map(Path::new).filter(|x| x.is_dir())
+            .filter_map(|dir| find_repository_root(dir).map(|root| (root.to_path_buf(), dir.to_path_buf())))
+            .collect::<Vec<_>>();
+    let mut submodule_dirs = Vec::new();
+    while let Some((root, dir)) = repositories.pop() {
+        let contents = fs::read_to_string(root.join(".gitmodules")).unwrap_or_default();
+        for submodule_dir in parse_submodule_paths(&contents).iter().map(|x| root.join(x)) {
+            if submodule_dir == dir || !submodule_dir.starts_with(&dir) || !submodule_dir.is_dir() || submodule_dirs.contains(&submodule_dir) {
+                continue;
+            }
+            if nested {
+                repositories.push((submodule_dir.clone(), submodule_dir.clone()));
+            }
+            submodule_dirs.push(submodule_dir);
+        }
+    }
+    submodule_dirs.sort();
+    submodule_dirs
+}
+
+// The working tree of a submodule has a '.git' file instead of a directory
+fn find_repository_root(dir: &Path) -> Option<&Path> {
+    dir.ancestors().find(|x| x.join(".git").exists())
+}
+
+fn parse_submodule_paths(gitmodules: &str) -> Vec<String> {
+    gitmodules.lines().filter_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        if key.trim() == "path" {Some(value.trim().trim_matches('"').to_owned())} else {None}
+    }).collect()
+}
+
+// The lines of the file by the author of the commit that last changed them. The uncommitted lines are attributed
+// by git to 'Not Committed Yet', and the untracked files are an error.
+pub fn blame_authors(path: &Path) -> Result<BTreeMap<String,usize>, String> {
+    let (dir, name) = match (path.parent(), path.file_name()) {
+        (Some(dir), Some(name)) => (dir, name),
+        _ => return Err(format!("{} is not a file", path.display()))
+    };
+    let output = get_git_output(dir, &[OsStr::new("blame"), OsStr::new("--porcelain"), OsStr::new("--"), name])?;
+    Ok(parse_blame_porcelain(&output))
+}
+
+// Every line of the file is preceded by a header that starts with the hash of its commit, and the details of a commit,
+// like 'author', are only given with its first line
+fn parse_blame_porcelain(output: &str) -> BTreeMap<String,usize> {
+    let (mut commit_authors, mut author_lines) = (HashMap::<&str,&str>::new(), BTreeMap::new());
+    let mut commit = "";
+    for line in output.lines() {
+        if line.starts_with('\t') {
+            if let Some(author) = commit_authors.get(commit) {
+                *author_lines.entry((*author).to_owned()).or_insert(0) += 1;
+            }
+        } else if let Some(author) = line.strip_prefix("author ") {
+            commit_authors.insert(commit, author);
+        } else if let Some(hash) = line.split(' ').next().filter(|x| x.len() >= 40 && x.bytes().all(|x| x.is_ascii_hexdigit())) {
+            commit = hash;
+        }
+    }
+    author_lines
+}
+
+// The paths that git prints, separated by null characters
+fn run_git(dir: &Path, args: &[&str]) -> Result<Vec<String>, String> {
+    Ok(get_git_output(dir, args)?.split('\0').filter(|x| !x.is_empty()).map(|x| x.to_owned()).collect())
+}
+
+fn get_git_output(dir: &Path, args: &[impl AsRef<OsStr>]) -> Result<String, String> {
+    let output = Command::new("git").args(args).current_dir(dir).output()
+            .map_err(|x| format!("could not run git ({})", x))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_owned());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_tracked_files() {
+        let dir = std::env::temp_dir().join("mezura-test-git-tracked");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src/nested")).unwrap();
+        fs::create_dir_all(dir.join("target")).unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(dir.join("src/nested/lib.rs"), "").unwrap();
+        fs::write(dir.join("src/untracked.rs"), "").unwrap();
+        fs::write(dir.join("target/out.rs"), "").unwrap();
+
+        let dir_str = dir.to_str().unwrap().to_owned();
+        assert!(find_tracked_files(&[dir_str.clone()]).is_err());
+
+        let git = |args: &[&str]| Command::new("git").args(args).current_dir(&dir).output().map(|x| x.status.success()).unwrap_or(false);
+        if !git(&["init", "-q"]) {
+            // git is not installed
+            fs::remove_dir_all(&dir).unwrap();
+            return;
+        }
+        assert!(git(&["add", "src/main.rs", "src/nested/lib.rs"]));
+
+        let tracked_files = find_tracked_files(&[dir_str]).unwrap();
+        assert!(tracked_files.contains_file(&dir.join("src/main.rs")));
+        assert!(tracked_files.contains_file(&dir.join("src/nested/lib.rs")));
+        assert!(!tracked_files.contains_file(&dir.join("src/untracked.rs")));
+        assert!(!tracked_files.contains_file(&dir.join("target/out.rs")));
+        assert!(tracked_files.contains_dir(&dir.join("src")));
+        assert!(tracked_files.contains_dir(&dir.join("src/nested")));
+        assert!(!tracked_files.contains_dir(&dir.join("target")));
+
+        let tracked_in_subdir = find_tracked_files(&[dir.join("src/nested").to_str().unwrap().to_owned()]).unwrap();
+        assert!(tracked_in_subdir.contains_file(&dir.join("src/nested/lib.rs")));
+        assert!(!tracked_in_subdir.contains_file(&dir.join("src/main.rs")));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_blame_porcelain() {
+        let output = "\
+1111111111111111111111111111111111111111 1 1 2
+author Alice
+author-mail <alice@example.com>
+summary first
+filename src/main.rs
+\tfn main() {
+1111111111111111111111111111111111111111 2 2
+\t}
+2222222222222222222222222222222222222222 3 3 1
+author Bob Smith
+previous 1111111111111111111111111111111111111111 src/main.rs
+filename src/main.rs
+\t// author Carol
+";
+        assert_eq!(BTreeMap::from([("Alice".to_owned(), 2), ("Bob Smith".to_owned(), 1)]), parse_blame_porcelain(output));
+        assert!(parse_blame_porcelain("").is_empty());
+    }
+
+    #[test]
+    fn test_find_changed_files() {
+        let dir = std::env::temp_dir().join("mezura-test-git-changed");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(dir.join("src/old.rs"), "").unwrap();
+        fs::write(dir.join("src/removed.rs"), "").unwrap();
+
+        let git = |args: &[&str]| Command::new("git").args(["-c", "user.name=a", "-c", "user.email=a@a", "-c", "commit.gpgsign=false"]).args(args)
+                .current_dir(&dir).output().map(|x| x.status.success()).unwrap_or(false);
+        if !git(&["init", "-q"]) {
+            // git is not installed
+            fs::remove_dir_all(&dir).unwrap();
+            return;
+        }
+        assert!(git(&["add", "."]) && git(&["commit", "-q", "-m", "first"]));
+        fs::write(dir.join("src/new.rs"), "").unwrap();
+        assert!(git(&["add", "src/new.rs"]) && git(&["rm", "-q", "src/removed.rs"]) && git(&["commit", "-q", "-m", "second"]));
+        fs::write(dir.join("src/main.rs"), "fn main() {println!();}").unwrap();
+
+        let dir_str = dir.to_str().unwrap().to_owned();
+        let changed_files = find_changed_files(&[dir_str.clone()], "HEAD~1").unwrap();
+        assert!(changed_files.contains_file(&dir.join("src/new.rs")));
+        assert!(changed_files.contains_file(&dir.join("src/main.rs")));
+        assert!(!changed_files.contains_file(&dir.join("src/old.rs")));
+        assert!(!changed_files.contains_file(&dir.join("src/removed.rs")));
+        assert!(changed_files.contains_dir(&dir.join("src")));
+        assert!(find_changed_files(&[dir_str], "missing").is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_find_submodule_dirs() {
+        let dir = std::env::temp_dir().join("mezura-test-git-submodules");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join(".git")).unwrap();
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::create_dir_all(dir.join("vendor/lib/deps/inner")).unwrap();
+        fs::write(dir.join(".gitmodules"), "[submodule \"lib\"]\n\tpath = vendor/lib\n\turl = ../lib.git\n\
+                [submodule \"missing\"]\n\tpath = vendor/missing\n").unwrap();
+        fs::write(dir.join("vendor/lib/.git"), "gitdir: ../../.git/modules/lib").unwrap();
+        fs::write(dir.join("vendor/lib/.gitmodules"), "[submodule \"inner\"]\n\tpath = \"deps/inner\"\n").unwrap();
+
+        let dir_str = dir.to_str().unwrap().to_owned();
+        assert_eq!(vec![dir.join("vendor/lib")], find_submodule_dirs(&[dir_str.clone()], false));
+        assert_eq!(vec![dir.join("vendor/lib"), dir.join("vendor/lib/deps/inner")], find_submodule_dirs(&[dir_str], true));
+        // The '.gitmodules' of the repository is found from a directory inside it
+        assert_eq!(vec![dir.join("vendor/lib")], find_submodule_dirs(&[dir.join("vendor").to_str().unwrap().to_owned()], false));
+        assert!(find_submodule_dirs(&[dir.join("src").to_str().unwrap().to_owned()], true).is_empty());
+        // A submodule that is provided is searched as the repository of its own submodules
+        assert_eq!(vec![dir.join("vendor/lib/deps/inner")], find_submodule_dirs(&[dir.join("vendor/lib").to_str().unwrap().to_owned()], false));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
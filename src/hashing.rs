@@ -0,0 +1,212 @@
+// SHA-256 and HMAC-SHA256 (FIPS 180-4, RFC 2104), used to make the saved reports verifiable and to find the duplicate files.
+
+const BLOCK_SIZE : usize = 64;
+
+const K : [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2
+];
+
+const H0 : [u32; 8] = [0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19];
+
+
+// For the data that is read in parts, like the files that are parsed line by line
+#[derive(Debug, Clone)]
+pub struct Sha256 {
+    state: [u32; 8],
+    // The bytes that don't make a whole block yet
+    pending: Vec<u8>,
+    len: u64
+}
+
+impl Sha256 {
+    pub fn new() -> Self {
+        Sha256 {state: H0, pending: Vec::with_capacity(BLOCK_SIZE), len: 0}
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.len += data.len() as u64;
+        if !self.pending.is_empty() {
+            let needed = (BLOCK_SIZE - self.pending.len()).min(data.len());
+            self.pending.extend_from_slice(&data[..needed]);
+            data = &data[needed..];
+            if self.pending.len() < BLOCK_SIZE {
+                return;
+            }
+            compress(&mut self.state, &self.pending);
+            self.pending.clear();
+        }
+        let mut blocks = data.chunks_exact(BLOCK_SIZE);
+        for block in &mut blocks {
+            compress(&mut self.state, block);
+        }
+        self.pending.extend_from_slice(blocks.remainder());
+    }
+
+    pub fn finalize(mut self) -> [u8; 32] {
+        let bits_len = self.len * 8;
+        let mut padding = vec![0x80];
+        while (self.pending.len() + padding.len()) % BLOCK_SIZE != 56 {
+            padding.push(0);
+        }
+        padding.extend_from_slice(&bits_len.to_be_bytes());
+        self.update(&padding);
+
+        let mut digest = [0u8; 32];
+        for (i, word) in self.state.iter().enumerate() {
+            digest[i*4..i*4+4].copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.finalize()
+}
+
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut padded_key = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        padded_key[..32].copy_from_slice(&sha256(key));
+    } else {
+        padded_key[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = padded_key.iter().map(|x| x ^ 0x36).collect::<Vec<u8>>();
+    inner.extend_from_slice(message);
+    let mut outer = padded_key.iter().map(|x| x ^ 0x5c).collect::<Vec<u8>>();
+    outer.extend_from_slice(&sha256(&inner));
+
+    sha256(&outer)
+}
+
+// Whether the signature is the HMAC-SHA256 of the message. Every byte is compared, so the time taken
+// doesn't tell how much of a forged signature was right.
+pub fn verify_hmac_sha256(key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let expected = hmac_sha256(key, message);
+    signature.len() == expected.len() && signature.iter().zip(expected.iter()).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|x| format!("{:02x}", x)).collect()
+}
+
+pub fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.bytes().all(|x| x.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i+2], 16).ok()).collect()
+}
+
+fn compress(state: &mut [u32; 8], block: &[u8]) {
+    let mut w = [0u32; 64];
+    for i in 0..16 {
+        w[i] = u32::from_be_bytes([block[i*4], block[i*4+1], block[i*4+2], block[i*4+3]]);
+    }
+    for i in 16..64 {
+        let s0 = w[i-15].rotate_right(7) ^ w[i-15].rotate_right(18) ^ (w[i-15] >> 3);
+        let s1 = w[i-2].rotate_right(17) ^ w[i-2].rotate_right(19) ^ (w[i-2] >> 10);
+        w[i] = w[i-16].wrapping_add(s0).wrapping_add(w[i-7]).wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for i in 0..64 {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let temp1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(w[i]);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let temp2 = s0.wrapping_add(maj);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(temp1);
+        d = c;
+        c = b;
+        b = a;
+        a = temp1.wrapping_add(temp2);
+    }
+
+    for (x, y) in state.iter_mut().zip([a, b, c, d, e, f, g, h].iter()) {
+        *x = x.wrapping_add(*y);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855", to_hex(&sha256(b"")));
+        assert_eq!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad", to_hex(&sha256(b"abc")));
+        assert_eq!("248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+                to_hex(&sha256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")));
+    }
+
+    #[test]
+    fn test_sha256_in_parts() {
+        let data = (0..1000).map(|x| (x % 251) as u8).collect::<Vec<_>>();
+        for part_len in [1, 7, 63, 64, 65, 200, 1000] {
+            let mut hasher = Sha256::new();
+            data.chunks(part_len).for_each(|x| hasher.update(x));
+            assert_eq!(sha256(&data), hasher.finalize());
+        }
+        assert_eq!(sha256(b""), Sha256::new().finalize());
+    }
+
+    // The test cases of RFC 4231
+    #[test]
+    fn test_hmac_sha256() {
+        assert_eq!("b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7", to_hex(&hmac_sha256(&[0x0b; 20], b"Hi There")));
+        assert_eq!("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+                to_hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")));
+        assert_eq!("773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe", to_hex(&hmac_sha256(&[0xaa; 20], &[0xdd; 50])));
+        assert_eq!("82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b",
+                to_hex(&hmac_sha256(&(1..=25).collect::<Vec<u8>>(), &[0xcd; 50])));
+        // Truncated to 128 bits
+        assert_eq!("a3b6167473100ee06e0c796c2955552b", to_hex(&hmac_sha256(&[0x0c; 20], b"Test With Truncation")[..16]));
+        assert_eq!("60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+                to_hex(&hmac_sha256(&[0xaa; 131], b"Test Using Larger Than Block-Size Key - Hash Key First")));
+        assert_eq!("9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2",
+                to_hex(&hmac_sha256(&[0xaa; 131], b"This is a test using a larger than block-size key and a larger than block-size data. \
+                The key needs to be hashed before being used by the HMAC algorithm.")));
+    }
+
+    #[test]
+    fn test_verify_hmac_sha256() {
+        let signature = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        assert!(verify_hmac_sha256(b"Jefe", b"what do ya want for nothing?", &signature));
+        assert!(!verify_hmac_sha256(b"Jeff", b"what do ya want for nothing?", &signature));
+        assert!(!verify_hmac_sha256(b"Jefe", b"what do ya want for nothing?", &signature[..31]));
+        let mut forged = signature;
+        forged[31] ^= 1;
+        assert!(!verify_hmac_sha256(b"Jefe", b"what do ya want for nothing?", &forged));
+    }
+
+    #[test]
+    fn test_from_hex() {
+        assert_eq!(Some(vec![0x00, 0xab, 0xff]), from_hex("00abFF"));
+        assert_eq!(Some(Vec::new()), from_hex(""));
+        assert_eq!(None, from_hex("abc"));
+        assert_eq!(None, from_hex("+f"));
+        assert_eq!(None, from_hex("zz"));
+        assert_eq!(None, from_hex("é0"));
+    }
+}
@@ -0,0 +1,82 @@
+// With '--disambiguate', the language of a file with an extension that several languages share, e.g. '.h' of C, C++ and
+// Objective-C, is found by sniffing its first KB for the markers of each of them, instead of by the extension alone.
+
+use crate::*;
+
+// How much of the start of a file is sniffed
+const SNIFFED_BYTES : u64 = 1024;
+
+// A language with the markers that point to it
+type Rule = (&'static str, &'static [&'static str]);
+
+// For each extension, the languages in the order that they are checked, each with its markers. The first language
+// that is supported and that has any of its markers in the file is chosen, and one without markers is chosen if none
+// of the ones before it matched. If none is chosen, the language of the extension is kept.
+const RULES : [(&str, &[Rule]); 3] = [
+    ("h", &[
+        ("Objective-C", &["@interface", "@implementation", "@protocol", "@property", "@end", "#import "]),
+        ("C++", &["namespace ", "template <", "template<", "class ", "std::", "public:", "private:", "virtual ", "nullptr", "#include <iostream>"]),
+        ("C", &[])
+    ]),
+    ("m", &[
+        ("Objective-C", &["@interface", "@implementation", "@protocol", "@property", "@end", "#import "]),
+        ("Matlab", &[])
+    ]),
+    ("pl", &[
+        ("Perl", &["use strict", "use warnings", "my $", "my @", "my %", "sub ", "=~", "#!/usr/bin/perl", "#!/usr/bin/env perl"]),
+        ("Prolog", &[":- ", ":-\n", "?- "])
+    ])
+];
+
+
+// Separated by commas, without the dots and case-insensitive, e.g. 'h,m'. None if any of them has no heuristics or if there are none.
+pub fn parse_extensions(s: &str) -> Option<Vec<String>> {
+    let extensions = s.split(',').map(|x| x.trim().trim_start_matches('.').to_lowercase()).filter(|x| !x.is_empty()).collect::<Vec<_>>();
+    if extensions.is_empty() || extensions.iter().any(|x| !RULES.iter().any(|(extension, _)| extension == x)) {
+        return None;
+    }
+    Some(extensions)
+}
+
+// None if the extension has no heuristics, the file can't be read or none of its languages is chosen
+pub fn find_language_by_content(path: &Path, extension: &str, languages: &HashMap<String,Language>) -> Option<String> {
+    let rules = RULES.iter().find(|(x, _)| x.eq_ignore_ascii_case(extension))?.1;
+    let mut bytes = Vec::with_capacity(SNIFFED_BYTES as usize);
+    File::open(path).ok()?.take(SNIFFED_BYTES).read_to_end(&mut bytes).ok()?;
+    choose_language(&String::from_utf8_lossy(&bytes), rules, languages)
+}
+
+fn choose_language(contents: &str, rules: &[Rule], languages: &HashMap<String,Language>) -> Option<String> {
+    rules.iter().filter_map(|(name, markers)| languages.keys().find(|x| x.eq_ignore_ascii_case(name)).map(|x| (x, markers)))
+            .find(|(_, markers)| markers.is_empty() || markers.iter().any(|x| contents.contains(x)))
+            .map(|(name, _)| name.to_owned())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose_language() {
+        let language = |name: &str| (name.to_owned(), Language::new(name.to_owned(), vec![], vec![], vec![], vec![], vec![]));
+        let languages = vec![language("C"), language("C++"), language("Objective-C"), language("Perl")].into_iter().collect::<HashMap<_,_>>();
+        let choose = |extension: &str, contents: &str| choose_language(contents, RULES.iter().find(|(x, _)| *x == extension).unwrap().1, &languages);
+
+        assert_eq!(Some("Objective-C".to_owned()), choose("h", "#import <Foundation/Foundation.h>\n@interface A : NSObject\n@end\n"));
+        assert_eq!(Some("C++".to_owned()), choose("h", "#pragma once\nnamespace a {\nclass B;\n}\n"));
+        assert_eq!(Some("C".to_owned()), choose("h", "#include <stdio.h>\ntypedef struct point point;\n"));
+        // Matlab is not supported, so the language of the extension is kept
+        assert_eq!(Some("Objective-C".to_owned()), choose("m", "@implementation A\n@end\n"));
+        assert_eq!(None, choose("m", "function y = f(x)\n  y = x;\nend\n"));
+        assert_eq!(Some("Perl".to_owned()), choose("pl", "use strict;\nmy $a = 1;\n"));
+        assert_eq!(None, choose("pl", "parent(a, b).\n"));
+    }
+
+    #[test]
+    fn test_parse_extensions() {
+        assert_eq!(Some(vec!["h".to_owned(), "pl".to_owned()]), parse_extensions("h, .PL"));
+        assert_eq!(None, parse_extensions("h,rs"));
+        assert_eq!(None, parse_extensions(" , "));
+    }
+}
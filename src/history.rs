@@ -0,0 +1,221 @@
+use chrono::FixedOffset;
+
+use crate::{*, badge::BadgeMetric, io_handler::ReportStats, pr_comment::{format_size_with_change, format_with_change, get_changed_languages, has_same_stats}};
+
+// Relative to the current dir, so that the snapshots stay with the project that they were taken of
+pub const HISTORY_DIR : &str = ".mezura";
+const SNAPSHOT_EXTENSION : &str = "txt";
+// The format of the second line of a report
+const REPORT_DATETIME_FORMAT : &str = "%Y-%m-%d %H:%M:%S %z";
+
+
+// The named snapshots of 'snapshot save', which are reports in the format of '--report', e.g. '.mezura/v1.0.txt'
+#[derive(Debug,PartialEq)]
+pub enum HistoryError {
+    // Empty, or with characters that don't belong in a file name
+    InvalidName(String),
+    NotFound(String),
+    // The file exists, but it can't be read as a report
+    Unreadable(String),
+    Unwritable(String),
+    // 'trend' needs at least one snapshot
+    NoSnapshots
+}
+
+#[derive(Debug,PartialEq)]
+pub struct Snapshot {
+    pub name: String,
+    // When the report was created
+    pub datetime: DateTime<FixedOffset>,
+    pub stats: ReportStats
+}
+
+// Letters, digits and '.', '-', '_', without starting with a '.', e.g. 'v1.0' or 'before_refactor'
+pub fn is_valid_snapshot_name(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('.') && name.chars().all(|x| x.is_alphanumeric() || x == '.' || x == '-' || x == '_')
+}
+
+pub fn get_snapshot_path(name: &str) -> PathBuf {
+    Path::new(HISTORY_DIR).join(format!("{}.{}", name, SNAPSHOT_EXTENSION))
+}
+
+// The path that the report of 'snapshot save' is written to. An existing snapshot of the same name is replaced.
+pub fn prepare_snapshot_path(name: &str) -> Result<String, HistoryError> {
+    if !is_valid_snapshot_name(name) {
+        return Err(HistoryError::InvalidName(name.to_owned()));
+    }
+    fs::create_dir_all(HISTORY_DIR).map_err(|x| HistoryError::Unwritable(format!("{} ({})", HISTORY_DIR, x)))?;
+    Ok(get_snapshot_path(name).to_string_lossy().into_owned())
+}
+
+pub fn read_snapshot(name: &str) -> Result<ReportStats, HistoryError> {
+    if !is_valid_snapshot_name(name) {
+        return Err(HistoryError::InvalidName(name.to_owned()));
+    }
+    let contents = fs::read_to_string(get_snapshot_path(name)).map_err(|_| HistoryError::NotFound(name.to_owned()))?;
+    io_handler::parse_report_stats(&contents).ok_or_else(|| HistoryError::Unreadable(name.to_owned()))
+}
+
+// All the snapshots of the history dir, the oldest first. The files that are not snapshots are skipped.
+pub fn read_snapshots() -> Vec<Snapshot> {
+    let entries = match fs::read_dir(HISTORY_DIR) {
+        Ok(x) => x,
+        Err(_) => return Vec::new()
+    };
+    let mut snapshots = entries.flatten().filter_map(|entry| {
+        let path = entry.path();
+        if path.extension().and_then(|x| x.to_str()) != Some(SNAPSHOT_EXTENSION) {
+            return None;
+        }
+        let name = path.file_stem()?.to_str()?.to_owned();
+        parse_snapshot(name, &fs::read_to_string(&path).ok()?)
+    }).collect::<Vec<_>>();
+    snapshots.sort_by(|a, b| a.datetime.cmp(&b.datetime).then_with(|| a.name.cmp(&b.name)));
+    snapshots
+}
+
+pub fn parse_snapshot(name: String, contents: &str) -> Option<Snapshot> {
+    let datetime = DateTime::parse_from_str(contents.lines().nth(1)?.trim(), REPORT_DATETIME_FORMAT).ok()?;
+    Some(Snapshot {name, datetime, stats: io_handler::parse_report_stats(contents)?})
+}
+
+// The metric of the totals of every snapshot over time, as bars in the style of the overview
+pub fn print_trend(metric: BadgeMetric) -> Result<(), HistoryError> {
+    let snapshots = read_snapshots();
+    if snapshots.is_empty() {
+        return Err(HistoryError::NoSnapshots);
+    }
+    result_printer::print_trend(&snapshots, metric);
+    Ok(())
+}
+
+pub fn get_metric_value(metric: BadgeMetric, stats: &FinalStats) -> usize {
+    badge::get_metric_value(metric, stats.files, stats.lines, stats.code_lines)
+}
+
+pub fn diff_snapshots(older_name: &str, newer_name: &str) -> Result<String, HistoryError> {
+    Ok(render_diff(older_name, &read_snapshot(older_name)?, newer_name, &read_snapshot(newer_name)?))
+}
+
+// e.g.
+//
+// v1.0 -> v2.0
+//
+// Total              files 120 (+3)  |  lines 15,000 (+250)  |  code 10,000 (+200)  |  1.2 MBs (+2.5%)
+// Rust               files 100 (+3)  |  lines 12,500 (+250)  |  code 8,800 (+200)  |  1.0 MBs (+3.1%)
+// Python (removed)   files 0 (-2)  |  lines 0 (-40)  |  code 0 (-30)  |  0.0 Bytes (-100.0%)
+//
+// Like '--pr-comment', only the languages that changed are listed, the ones with the biggest change in code lines first.
+pub fn render_diff(older_name: &str, older: &ReportStats, newer_name: &str, newer: &ReportStats) -> String {
+    let empty_stats = FinalStats::new_extended(0, 0, 0, 0, 0, 0);
+    let changed = get_changed_languages(&older.languages, &newer.languages, &empty_stats);
+
+    let mut text = format!("{} -> {}\n\n", older_name, newer_name);
+    if changed.is_empty() && has_same_stats(&older.total, &newer.total) {
+        return text + "No changes between the snapshots.\n";
+    }
+
+    let titles = changed.iter().map(|(name, _, _)| {
+        if !older.languages.contains_key(*name) {
+            format!("{} (new)", name)
+        } else if !newer.languages.contains_key(*name) {
+            format!("{} (removed)", name)
+        } else {
+            (*name).to_owned()
+        }
+    }).collect::<Vec<_>>();
+    let title_width = titles.iter().map(|x| x.chars().count()).max().unwrap_or(0).max("Total".len());
+
+    text += &format_row("Total", title_width, &older.total, &newer.total);
+    for (title, (_, older, newer)) in titles.iter().zip(changed) {
+        text += &format_row(title, title_width, older, newer);
+    }
+    text
+}
+
+fn format_row(title: &str, title_width: usize, older: &FinalStats, newer: &FinalStats) -> String {
+    format!("{:<width$}   files {}  |  lines {}  |  code {}  |  {}\n", title, format_with_change(older.files, newer.files),
+            format_with_change(older.lines, newer.lines), format_with_change(older.code_lines, newer.code_lines),
+            format_size_with_change(older.bytes_size, newer.bytes_size), width = title_width)
+}
+
+impl Formatted for HistoryError {
+    fn formatted(&self) -> ColoredString {
+        match self {
+            Self::InvalidName(x) => format!("'{}' is not a valid snapshot name (use letters, digits, '.', '-' and '_').", x).red(),
+            Self::NotFound(x) => format!("Snapshot '{}' not found in '{}'.", x, HISTORY_DIR).red(),
+            Self::Unreadable(x) => format!("Snapshot '{}' is not a report.", x).red(),
+            Self::Unwritable(x) => format!("Unable to create the dir of the snapshots: {}", x).red(),
+            Self::NoSnapshots => format!("There are no snapshots in '{}' yet, save one with 'snapshot save <name>'.", HISTORY_DIR).yellow()
+        }
+    }
+}
+
+impl HistoryError {
+    // The code that the program exits with, as listed in the README
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::InvalidName(_) => 6,
+            Self::NotFound(_) | Self::Unreadable(_) | Self::Unwritable(_) => 5,
+            Self::NoSnapshots => 1
+        }
+    }
+}
+
+impl_display_from_formatted!(HistoryError);
+
+impl std::error::Error for HistoryError {}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_snapshot_name() {
+        assert!(is_valid_snapshot_name("v1.0"));
+        assert!(is_valid_snapshot_name("before_refactor-2"));
+        assert!(!is_valid_snapshot_name(""));
+        assert!(!is_valid_snapshot_name(".hidden"));
+        assert!(!is_valid_snapshot_name("../v1"));
+        assert!(!is_valid_snapshot_name("a b"));
+    }
+
+    #[test]
+    fn test_parse_snapshot() {
+        let contents = "Mezura report v1.0.0\n2021-10-17 09:30:00 +0200\nConfiguration:\n    dirs: ./\n\
+                Stats:\n    Files: 2\n    Lines: 30\n        Code: 20\n        Extra: 10\n    Total Size: 900\n\
+                Rust:\n    Files: 2\n    Lines: 30\n        Code: 20\n        Extra: 10\n    Total Size: 900\n";
+        let snapshot = parse_snapshot("v1.0".to_owned(), contents).unwrap();
+        assert_eq!("v1.0", snapshot.name);
+        assert_eq!("2021-10-17 07:30:00 UTC", snapshot.datetime.naive_utc().to_string() + " UTC");
+        assert_eq!(30, get_metric_value(BadgeMetric::Lines, &snapshot.stats.total));
+        assert_eq!(10, get_metric_value(BadgeMetric::Extra, &snapshot.stats.total));
+
+        assert_eq!(None, parse_snapshot("v1.0".to_owned(), &contents.replace("2021-10-17 09:30:00 +0200", "yesterday")));
+        assert_eq!(None, parse_snapshot("v1.0".to_owned(), "some notes\n"));
+    }
+
+    #[test]
+    fn test_render_diff() {
+        let older = ReportStats {
+            total: FinalStats::new_extended(6, 1200, 900, 300, 50_000, 8_333),
+            languages: hashmap!["Rust".to_owned() => FinalStats::new_extended(4, 1000, 800, 200, 40_000, 10_000),
+                    "Python".to_owned() => FinalStats::new_extended(2, 200, 100, 100, 10_000, 5_000)]
+        };
+        let newer = ReportStats {
+            total: FinalStats::new_extended(6, 1350, 1080, 270, 54_000, 9_000),
+            languages: hashmap!["Rust".to_owned() => FinalStats::new_extended(5, 1250, 1000, 250, 50_000, 10_000),
+                    "C".to_owned() => FinalStats::new_extended(1, 100, 80, 20, 4_000, 4_000)]
+        };
+
+        assert_eq!("v1.0 -> v2.0\n\n\
+                Total              files 6  |  lines 1,350 (+150)  |  code 1,080 (+180)  |  54.0 KBs (+8.0%)\n\
+                Rust               files 5 (+1)  |  lines 1,250 (+250)  |  code 1,000 (+200)  |  50.0 KBs (+25.0%)\n\
+                Python (removed)   files 0 (-2)  |  lines 0 (-200)  |  code 0 (-100)  |  0.0 Bytes (-100.0%)\n\
+                C (new)            files 1 (+1)  |  lines 100 (+100)  |  code 80 (+80)  |  4.0 KBs\n",
+                render_diff("v1.0", &older, "v2.0", &newer));
+
+        assert_eq!("v2.0 -> v2.0\n\nNo changes between the snapshots.\n", render_diff("v2.0", &newer, "v2.0", &newer));
+    }
+}
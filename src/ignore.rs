@@ -0,0 +1,178 @@
+// Reads the '.mezuraignore' file at the root of every provided directory, which lists the paths that are left out of the
+// analysis with the syntax of '.gitignore', so that the exclusions of a project can be committed along with it, apart
+// from what git ignores. Unlike git, only the file at the root is read, not the ones of the subdirectories.
+
+use crate::*;
+
+pub const IGNORE_FILE_NAME : &str = ".mezuraignore";
+
+#[derive(Debug,PartialEq)]
+struct IgnorePattern {
+    // Relative to the root, the patterns without a '/' start with '**'
+    segments: Vec<String>,
+    // Starts with '!', bringing back what an earlier pattern left out
+    negated: bool,
+    // Ends with '/'
+    only_dirs: bool
+}
+
+// The patterns of the provided directories that have a '.mezuraignore'
+#[derive(Debug,Default)]
+pub struct IgnoreRules {
+    roots: Vec<(PathBuf, Vec<IgnorePattern>)>
+}
+
+impl IgnoreRules {
+    pub fn read(dirs: &[String]) -> Self {
+        let roots = dirs.iter().map(Path::new).filter(|x| x.is_dir())
+                .filter_map(|dir| fs::read_to_string(dir.join(IGNORE_FILE_NAME)).ok().map(|x| (dir.to_path_buf(), parse_patterns(&x))))
+                .filter(|(_, patterns)| !patterns.is_empty())
+                .collect();
+        IgnoreRules {roots}
+    }
+
+    // The last pattern that matches the path decides. Since the search doesn't enter an ignored directory,
+    // its files can't be brought back, like with git.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        self.roots.iter().any(|(root, patterns)| match path.strip_prefix(root) {
+            Ok(relative_path) => {
+                let segments = relative_path.components().map(|x| x.as_os_str().to_string_lossy()).collect::<Vec<_>>();
+                let segments = segments.iter().map(|x| x.as_ref()).collect::<Vec<_>>();
+                patterns.iter().rev().find(|x| x.matches(&segments, is_dir)).is_some_and(|x| !x.negated)
+            },
+            Err(_) => false
+        })
+    }
+}
+
+impl IgnorePattern {
+    fn matches(&self, path_segments: &[&str], is_dir: bool) -> bool {
+        (is_dir || !self.only_dirs) && matches_segments(path_segments, &self.segments)
+    }
+}
+
+// Empty lines and the ones starting with '#' are skipped. A '\' escapes a leading '#' or '!' and a trailing space.
+fn parse_patterns(contents: &str) -> Vec<IgnorePattern> {
+    contents.lines().filter_map(|line| {
+        let line = if line.ends_with("\\ ") {line} else {line.trim_end()};
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(x) => (true, x),
+            None => (false, line)
+        };
+        let line = if line.starts_with("\\#") || line.starts_with("\\!") {&line[1..]} else {line};
+        let (only_dirs, line) = match line.strip_suffix('/') {
+            Some(x) => (true, x),
+            None => (false, line)
+        };
+
+        // A pattern with a '/' at the start or in the middle is relative to the root, the rest match at any level
+        let is_anchored = line.contains('/');
+        let mut segments = line.split('/').filter(|x| !x.is_empty()).map(|x| x.to_owned()).collect::<Vec<_>>();
+        if segments.is_empty() {
+            return None;
+        }
+        if !is_anchored {
+            segments.insert(0, "**".to_owned());
+        }
+        Some(IgnorePattern {segments, negated, only_dirs})
+    }).collect()
+}
+
+// A '**' matches any number of directories, the rest of the segments match a single one
+pub(crate) fn matches_segments(path_segments: &[&str], pattern_segments: &[String]) -> bool {
+    match pattern_segments.split_first() {
+        None => path_segments.is_empty(),
+        Some((first, rest)) if first == "**" => (0..=path_segments.len()).any(|i| matches_segments(&path_segments[i..], rest)),
+        Some((first, rest)) => match path_segments.split_first() {
+            Some((path_first, path_rest)) => matches_name(path_first, first) && matches_segments(path_rest, rest),
+            None => false
+        }
+    }
+}
+
+// A '*' matches any number of characters, a '?' a single one and a '[a-z]' one of a set, negated with a leading '!' or '^'.
+// A '\' makes the next character literal.
+fn matches_name(name: &str, pattern: &str) -> bool {
+    matches_chars(&name.chars().collect::<Vec<_>>(), &pattern.chars().collect::<Vec<_>>())
+}
+
+fn matches_chars(name: &[char], pattern: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((&'*', rest)) => (0..=name.len()).any(|i| matches_chars(&name[i..], rest)),
+        Some((&'?', rest)) => !name.is_empty() && matches_chars(&name[1..], rest),
+        Some((&'[', rest)) => match name.split_first() {
+            Some((c, name_rest)) => match match_char_class(*c, rest) {
+                Some((is_in_class, pattern_rest)) => is_in_class && matches_chars(name_rest, pattern_rest),
+                // A '[' that is not closed is literal
+                None => *c == '[' && matches_chars(name_rest, rest)
+            },
+            None => false
+        },
+        Some((&'\\', rest)) if !rest.is_empty() => name.first() == rest.first() && matches_chars(&name[1..], &rest[1..]),
+        Some((x, rest)) => name.first() == Some(x) && matches_chars(&name[1..], rest)
+    }
+}
+
+// Gets the pattern after a '[', returns whether the character is in the set and the pattern after the closing ']'
+fn match_char_class(c: char, pattern: &[char]) -> Option<(bool, &[char])> {
+    let negated = matches!(pattern.first(), Some('!') | Some('^'));
+    let start = if negated {1} else {0};
+    let (mut i, mut is_in_class) = (start, false);
+    while i < pattern.len() {
+        // A ']' right after the '[' is part of the set
+        if pattern[i] == ']' && i > start {
+            return Some((is_in_class != negated, &pattern[i + 1..]));
+        }
+        if i + 2 < pattern.len() && pattern[i + 1] == '-' && pattern[i + 2] != ']' {
+            is_in_class |= pattern[i] <= c && c <= pattern[i + 2];
+            i += 3;
+        } else {
+            is_in_class |= pattern[i] == c;
+            i += 1;
+        }
+    }
+    None
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_ignored() {
+        let patterns = parse_patterns("# generated code\n\n*.pb.go\n/vendor/\ndocs/**/*.md\n!docs/**/README.md\nfixture?.[jt]s\n\
+                \\#notes.txt\nbuild/ \n");
+        let rules = IgnoreRules {roots: vec![(PathBuf::from("/p"), patterns)]};
+        let is_ignored = |path: &str, is_dir: bool| rules.is_ignored(Path::new(path), is_dir);
+
+        assert!(is_ignored("/p/api/service.pb.go", false));
+        assert!(!is_ignored("/p/api/service.go", false));
+        assert!(is_ignored("/p/vendor", true));
+        assert!(!is_ignored("/p/vendor", false));
+        assert!(!is_ignored("/p/src/vendor", true));
+        assert!(is_ignored("/p/docs/guide/intro.md", false));
+        assert!(is_ignored("/p/docs/intro.md", false));
+        assert!(!is_ignored("/p/docs/guide/README.md", false));
+        assert!(!is_ignored("/p/src/intro.md", false));
+        assert!(is_ignored("/p/test/fixture1.ts", false));
+        assert!(!is_ignored("/p/test/fixture1.rs", false));
+        assert!(!is_ignored("/p/test/fixture10.js", false));
+        assert!(is_ignored("/p/#notes.txt", false));
+        assert!(is_ignored("/p/app/build", true));
+        assert!(!is_ignored("/other/a.pb.go", false));
+    }
+
+    #[test]
+    fn test_match_char_class() {
+        assert_eq!(Some((true, &['x'][..])), match_char_class('b', &['a', '-', 'c', ']', 'x']));
+        assert_eq!(Some((false, &[][..])), match_char_class('b', &['!', 'a', '-', 'c', ']']));
+        assert_eq!(Some((true, &[][..])), match_char_class(']', &[']', ']']));
+        assert_eq!(Some((true, &[][..])), match_char_class('-', &['a', '-', ']']));
+        assert_eq!(None, match_char_class('a', &['a', 'b']));
+    }
+}
@@ -1,21 +1,37 @@
-use std::{borrow::Cow, collections::HashMap, fs::{self, DirEntry, File}, io::{self, BufRead, BufReader, BufWriter, Write}, path::Path};
+use std::{borrow::Cow, collections::HashMap, fs::{self, DirEntry, File}, io::{self, BufRead, BufWriter, Write}, path::Path};
 
 use chrono::{DateTime, Local};
 use colored::*;
 
-use crate::{Configuration, DEFAULT_CONFIG_NAME, FinalStats, Formatted, PERSISTENT_APP_PATHS, config_manager::{self, ConfigurationBuilder, LogOption,
-     MAX_COMPARE_LEVEL, MAX_CONSUMERS_VALUE, MAX_PRODUCERS_VALUE, MIN_COMPARE_LEVEL, MIN_CONSUMERS_VALUE, MIN_PRODUCERS_VALUE, Threads}, domain::*, split_line_on_whitespace, utils};
+use crate::{Configuration, DEFAULT_CONFIG_NAME, FinalStats, Formatted, PERSISTENT_APP_PATHS, ReportSection, budgets, derived_metrics, hashing, heuristics, labels, remote, treemap, warnings::{self, WarningKind}, config_manager::{self, ConfigurationBuilder, LogOption,
+     MAX_COMPARE_LEVEL, MIN_COMPARE_LEVEL, MIN_CONSUMERS_VALUE, MIN_PRODUCERS_VALUE, Submodules, Threads}, domain::*, split_line_on_whitespace, utils};
 
 
+// The start of the line that starts a profile of a configuration file, e.g. '[profile.ci]'
+const PROFILE_PREFIX           : &str = "[profile.";
+
 const LANGUAGE                 : &str = "Language";     
 const EXTENSIONS               : &str = "Extensions";     
 const STRING_SYMBOLS           : &str = "String symbols";     
 const COMMENT_SYMBOLS          : &str = "Comment symbols";     
 const MULTILINE_COMMENT_START  : &str = "Multi line comment start";     
 const MULTILINE_COMMENT_END    : &str = "Multi line comment end";     
+const NESTED_COMMENTS          : &str = "Nested multi line comments";     
+const DOC_COMMENT_SYMBOLS      : &str = "Doc comment symbols";     
+const BRANCH_KEYWORDS          : &str = "Branch keywords";     
+const STATEMENT_SYMBOLS        : &str = "Statement symbols";     
+const HEREDOC_SYMBOLS          : &str = "Heredoc symbols";     
+const CATEGORY                 : &str = "Category";     
+const CHAR_LITERALS            : &str = "Char literals";
+const STRING_DELIMITERS        : &str = "String delimiters";     
+const RAW_STRING               : &str = "raw";     
+const EMBEDDED_REGIONS         : &str = "Embedded regions";     
 const KEYWORD                  : &str = "Keyword";     
 const KEYWORD_NAME             : &str = "NAME";     
 const KEYWORD_ALIASES          : &str = "ALIASES";     
+const KEYWORD_CATEGORY         : &str = "CATEGORY";     
+const KEYWORD_CASE_INSENSITIVE : &str = "CASE INSENSITIVE";
+const KEYWORD_SCOPE            : &str = "SCOPE";     
 
 
 #[derive(Debug)]
@@ -38,13 +54,27 @@ pub enum ConfigFileParseError {
     IOError
 }
 
+#[derive(Debug, PartialEq)]
+pub enum ReportVerificationError {
+    FileNotFound(String),
+    MissingChecksum,
+    ChecksumMismatch,
+    MissingSignature,
+    SignatureMismatch,
+    UnreadableKey(String)
+}
+
 
 // --------------------- Languages handling -------------------------
 
 pub fn parse_supported_languages_to_map(target_path: &str) -> Result<(HashMap<String, Language>, Vec<String>), LanguageDirParseError> {
-    fn add_file_name_to_faulty_files(entry: &DirEntry, faulty_files: &mut Vec<String>) {
+    fn add_file_name_to_faulty_files(entry: &DirEntry, faulty_files: &mut Vec<String>, faulty_section: Option<&str>) {
         let file_name = entry.file_name().to_str().map_or(String::new(), |x| x.to_owned());
-        if !file_name.is_empty() {faulty_files.push(file_name.to_lowercase())}
+        if file_name.is_empty() {return;}
+        match faulty_section {
+            Some(section) => faulty_files.push(format!("{} (at '{}')", file_name.to_lowercase(), section)),
+            None => faulty_files.push(file_name.to_lowercase())
+        }
     }
 
     let mut language_map = HashMap::with_capacity(30);
@@ -67,15 +97,15 @@ pub fn parse_supported_languages_to_map(target_path: &str) -> Result<(HashMap<St
         let reader = match my_reader::BufReader::open(path) {
             Ok(x) => x,
             Err(_) => {
-                add_file_name_to_faulty_files(&entry, &mut faulty_files);
+                add_file_name_to_faulty_files(&entry, &mut faulty_files, None);
                 continue;
             }
         } ;
         
         let language = match parse_file_to_language(reader, &mut buffer) {
             Ok(x) => x,
-            Err(_) => {
-                add_file_name_to_faulty_files(&entry, &mut faulty_files);
+            Err(section) => {
+                add_file_name_to_faulty_files(&entry, &mut faulty_files, Some(section));
                 continue;
             }
         };
@@ -92,63 +122,174 @@ pub fn parse_supported_languages_to_map(target_path: &str) -> Result<(HashMap<St
     }
 }
 
-fn parse_file_to_language(mut reader :my_reader::BufReader, buffer :&mut String) -> Result<Language,()> {
-    if !reader.read_line_and_compare(buffer, LANGUAGE) {return Err(());}
-    if !reader.read_line_exists(buffer) {return Err(());}
+fn parse_file_to_language(mut reader :my_reader::BufReader, buffer :&mut String) -> Result<Language,&'static str> {
+    if !reader.read_line_and_compare(buffer, LANGUAGE) {return Err(LANGUAGE);}
+    if !reader.read_line_exists(buffer) {return Err(LANGUAGE);}
     let lang_name = buffer.trim_end().to_owned();
-    if !reader.read_line_exists(buffer) {return Err(());}
+    if !reader.read_line_exists(buffer) {return Err(LANGUAGE);}
 
-    if !reader.read_line_and_compare(buffer, EXTENSIONS) {return Err(());}
+    if !reader.read_line_and_compare(buffer, EXTENSIONS) {return Err(EXTENSIONS);}
     let identifiers = match reader.get_line_sliced(buffer) {
         Ok(x) => x,
-        Err(_) => return Err(())
+        Err(_) => return Err(EXTENSIONS)
     };
-    if !reader.read_line_exists(buffer) {return Err(());}
+    if !reader.read_line_exists(buffer) {return Err(EXTENSIONS);}
 
-    if !reader.read_line_and_compare(buffer, STRING_SYMBOLS) {return Err(());}
+    if !reader.read_line_and_compare(buffer, STRING_SYMBOLS) {return Err(STRING_SYMBOLS);}
     let string_symbols = match reader.get_line_sliced(buffer) {
         Ok(x) => x,
-        Err(_) => return Err(())
+        Err(_) => return Err(STRING_SYMBOLS)
     };
-    if string_symbols.is_empty() {return Err(());}
+    if string_symbols.is_empty() {return Err(STRING_SYMBOLS);}
 
-    if !reader.read_line_exists(buffer) {return Err(());}
-    if !reader.read_line_and_compare(buffer, COMMENT_SYMBOLS) {return Err(());} 
+    if !reader.read_line_exists(buffer) {return Err(STRING_SYMBOLS);}
+    if !reader.read_line_and_compare(buffer, COMMENT_SYMBOLS) {return Err(COMMENT_SYMBOLS);} 
     let comment_symbols = match reader.get_line_sliced(buffer) {
         Ok(x) => x,
-        Err(_) => return Err(())
+        Err(_) => return Err(COMMENT_SYMBOLS)
     };
     
-    let mut multi_start :Option<String> = None;
-    let mut multi_end :Option<String> = None;
+    // The start and end symbols are separated by whitespace, and are paired by their order, e.g. '/* {-' with '*/ -}'
+    let mut multiline_comment_symbols = Vec::new();
+    let mut nested_comments = false;
     if reader.read_line_and_compare(buffer, MULTILINE_COMMENT_START) {
-        if !reader.read_line_exists(buffer) {return Err(());}
-        let symbol = buffer.trim_end().to_owned();
-        if symbol.is_empty() {return Err(());}
-        multi_start = Some(symbol);
-        if !reader.read_line_and_compare(buffer, MULTILINE_COMMENT_END) {return Err(());}
-        if !reader.read_line_exists(buffer) {return Err(());}
-        let symbol = buffer.trim_end().to_owned();
-        if symbol.is_empty() {return Err(());}
-        multi_end = Some(symbol);
-        if !reader.read_line_exists(buffer) {return Err(())}
+        if !reader.read_line_exists(buffer) {return Err(MULTILINE_COMMENT_START);}
+        let start_symbols = split_line_on_whitespace(buffer);
+        if start_symbols.is_empty() {return Err(MULTILINE_COMMENT_START);}
+        if !reader.read_line_and_compare(buffer, MULTILINE_COMMENT_END) {return Err(MULTILINE_COMMENT_END);}
+        if !reader.read_line_exists(buffer) {return Err(MULTILINE_COMMENT_END);}
+        let end_symbols = split_line_on_whitespace(buffer);
+        if end_symbols.len() != start_symbols.len() {return Err(MULTILINE_COMMENT_END);}
+        multiline_comment_symbols = start_symbols.into_iter().zip(end_symbols).collect();
+        if !reader.read_line_exists(buffer) {return Err(MULTILINE_COMMENT_END)}
+        // Optional, 'yes' or 'no'
+        if buffer.trim_end() == NESTED_COMMENTS {
+            if !reader.read_line_exists(buffer) {return Err(NESTED_COMMENTS);}
+            nested_comments = match buffer.trim() {
+                "yes" => true,
+                "no" => false,
+                _ => return Err(NESTED_COMMENTS)
+            };
+            if !reader.read_line_exists(buffer) {return Err(NESTED_COMMENTS)}
+        }
+    }
+
+    // Optional
+    let mut doc_comment_symbols = Vec::new();
+    if buffer.trim_end() == DOC_COMMENT_SYMBOLS {
+        if !reader.read_line_exists(buffer) {return Err(DOC_COMMENT_SYMBOLS);}
+        doc_comment_symbols = split_line_on_whitespace(buffer);
+        if doc_comment_symbols.is_empty() {return Err(DOC_COMMENT_SYMBOLS);}
+        if !reader.read_line_exists(buffer) {return Err(DOC_COMMENT_SYMBOLS)}
+    }
+
+    // Optional
+    let mut branch_keywords = Vec::new();
+    if buffer.trim_end() == BRANCH_KEYWORDS {
+        if !reader.read_line_exists(buffer) {return Err(BRANCH_KEYWORDS);}
+        branch_keywords = split_line_on_whitespace(buffer);
+        if branch_keywords.is_empty() {return Err(BRANCH_KEYWORDS);}
+        if !reader.read_line_exists(buffer) {return Err(BRANCH_KEYWORDS)}
+    }
+
+    // Optional
+    let mut statement_symbols = Vec::new();
+    if buffer.trim_end() == STATEMENT_SYMBOLS {
+        if !reader.read_line_exists(buffer) {return Err(STATEMENT_SYMBOLS);}
+        statement_symbols = split_line_on_whitespace(buffer);
+        if statement_symbols.is_empty() {return Err(STATEMENT_SYMBOLS);}
+        if !reader.read_line_exists(buffer) {return Err(STATEMENT_SYMBOLS)}
+    }
+
+    // Optional
+    let mut heredoc_symbols = Vec::new();
+    if buffer.trim_end() == HEREDOC_SYMBOLS {
+        if !reader.read_line_exists(buffer) {return Err(HEREDOC_SYMBOLS);}
+        heredoc_symbols = split_line_on_whitespace(buffer);
+        if heredoc_symbols.is_empty() {return Err(HEREDOC_SYMBOLS);}
+        if !reader.read_line_exists(buffer) {return Err(HEREDOC_SYMBOLS)}
+    }
+
+    // Optional, e.g. 'markup' or 'config'
+    let mut category = FileCategory::Code;
+    if buffer.trim_end() == CATEGORY {
+        if !reader.read_line_exists(buffer) {return Err(CATEGORY);}
+        category = FileCategory::parse(buffer).ok_or(CATEGORY)?;
+        if !reader.read_line_exists(buffer) {return Err(CATEGORY)}
+    }
+
+    // Optional, 'yes' or 'no'
+    let mut char_literals = false;
+    if buffer.trim_end() == CHAR_LITERALS {
+        if !reader.read_line_exists(buffer) {return Err(CHAR_LITERALS);}
+        char_literals = match buffer.trim() {
+            "yes" => true,
+            "no" => false,
+            _ => return Err(CHAR_LITERALS)
+        };
+        if !reader.read_line_exists(buffer) {return Err(CHAR_LITERALS)}
+    }
+
+    // Optional, one per line until an empty line, e.g. 'r#" "# raw' or '` ` ${ }'
+    let mut string_delimiters = Vec::new();
+    if buffer.trim_end() == STRING_DELIMITERS {
+        while reader.read_line_exists(buffer) && !buffer.trim().is_empty() && buffer.trim_end() != EMBEDDED_REGIONS {
+            string_delimiters.push(parse_string_delimiter(buffer).ok_or(STRING_DELIMITERS)?);
+        }
+    }
+
+    // Optional, one per line until an empty line, e.g. '<script </script> js'
+    let mut embedded_regions = Vec::new();
+    if buffer.trim_end() == EMBEDDED_REGIONS {
+        while reader.read_line_exists(buffer) && !buffer.trim().is_empty() {
+            embedded_regions.push(parse_embedded_region(buffer).ok_or(EMBEDDED_REGIONS)?);
+        }
     }
     
     let mut keywords = Vec::new();
-    while reader.read_line_exists(buffer) {
-        if !reader.read_lines_exist(2, buffer) {return Err(());}
+    let mut has_next_line = reader.read_line_exists(buffer);
+    while has_next_line {
+        if !reader.read_lines_exist(2, buffer) {return Err(KEYWORD);}
         let name = buffer.trim().to_string().clone();
-        if name.is_empty() {return Err(());}
-        if !reader.read_line_exists(buffer) {return Err(());}
+        if name.is_empty() {return Err(KEYWORD);}
+        if !reader.read_line_exists(buffer) {return Err(KEYWORD);}
         let aliases = match reader.get_line_sliced(buffer) {
             Ok(x) => x,
-            Err(_) => return Err(())
+            Err(_) => return Err(KEYWORD)
         };
-        if aliases.is_empty() {return Err(());}
+        if aliases.is_empty() {return Err(KEYWORD);}
+
+        // Optional, in any order: the category, e.g. 'types', whether the case is ignored, 'yes' or 'no',
+        // and where the keyword is counted, 'code', 'comments' or 'all'
+        let (mut category, mut case_insensitive, mut scope) = (None, false, KeywordScope::Code);
+        has_next_line = reader.read_line_exists(buffer);
+        while has_next_line {
+            let header = match buffer.trim() {
+                x @ (KEYWORD_CATEGORY | KEYWORD_CASE_INSENSITIVE | KEYWORD_SCOPE) => x.to_owned(),
+                _ => break
+            };
+            if !reader.read_line_exists(buffer) {return Err(KEYWORD);}
+            if header == KEYWORD_CATEGORY {
+                category = Some(utils::get_trimmed_if_not_empty(buffer).ok_or(KEYWORD)?);
+            } else if header == KEYWORD_SCOPE {
+                scope = KeywordScope::parse(buffer).ok_or(KEYWORD)?;
+            } else {
+                case_insensitive = match buffer.trim() {
+                    "yes" => true,
+                    "no" => false,
+                    _ => return Err(KEYWORD)
+                };
+            }
+            has_next_line = reader.read_line_exists(buffer);
+        }
         
         let keyword = Keyword {
             descriptive_name : name,
-            aliases
+            aliases,
+            patterns : Vec::new(),
+            category,
+            case_insensitive,
+            scope
         };
         keywords.push(keyword);
     }
@@ -158,15 +299,50 @@ fn parse_file_to_language(mut reader :my_reader::BufReader, buffer :&mut String)
         extensions: identifiers,
         string_symbols,
         comment_symbols,
-        multiline_comment_start_symbol : multi_start,
-        multiline_comment_end_symbol : multi_end,
+        multiline_comment_symbols,
+        supports_nested_comments : nested_comments,
+        supports_char_literals : char_literals,
+        string_delimiters,
+        doc_comment_symbols,
+        branch_keywords,
+        statement_symbols,
+        heredoc_symbols,
+        embedded_regions,
+        category,
         keywords
     })
 }
 
+// The start and end symbols, then 'raw' if the backslashes don't escape, then the start and end symbols of the interpolation if any
+fn parse_string_delimiter(line: &str) -> Option<StringDelimiter> {
+    let words = split_line_on_whitespace(line);
+    let (start, end, rest) = match words.as_slice() {
+        [start, end, rest @ ..] => (start, end, rest),
+        _ => return None
+    };
+    let (is_raw, rest) = match rest {
+        [raw, rest @ ..] if raw == RAW_STRING => (true, rest),
+        _ => (false, rest)
+    };
+    let interpolation = match rest {
+        [] => None,
+        [interpolation_start, interpolation_end] => Some((interpolation_start.to_owned(), interpolation_end.to_owned())),
+        _ => return None
+    };
+    Some(StringDelimiter {start: start.to_owned(), end: end.to_owned(), is_raw, interpolation})
+}
+
+fn parse_embedded_region(line: &str) -> Option<EmbeddedRegion> {
+    match split_line_on_whitespace(line).as_slice() {
+        [start, end] => Some(EmbeddedRegion {start: start.to_owned(), end: end.to_owned(), identifier: None}),
+        [start, end, identifier] => Some(EmbeddedRegion {start: start.to_owned(), end: end.to_owned(), identifier: Some(identifier.to_owned())}),
+        _ => None
+    }
+}
+
 pub fn parse_string_to_language(contents: Cow<str>) -> Language {
-    let mut lines = (&contents).lines();
-    let (mut mult_start, mut mult_end) = (None, None);
+    let mut lines = (&contents).lines().peekable();
+    let (mut multiline_comment_symbols, mut nested_comments) = (Vec::new(), false);
 
     lines.next();
     let lang_name = lines.next().unwrap().trim().to_owned();
@@ -179,15 +355,58 @@ pub fn parse_string_to_language(contents: Cow<str>) -> Language {
     lines.next();
     lines.next();
     let comment_symbols = split_line_on_whitespace(lines.next().unwrap());
-    let next_line = lines.next();
-    if let Some(line) = next_line {
-        if line == MULTILINE_COMMENT_START {
-            mult_start = Some(lines.next().unwrap().trim().to_owned());
-            lines.next();
-            mult_end = Some(lines.next().unwrap().trim().to_owned());
-            lines.next();
+    let mut next_line = lines.next();
+    if next_line == Some(MULTILINE_COMMENT_START) {
+        let start_symbols = split_line_on_whitespace(lines.next().unwrap());
+        lines.next();
+        let end_symbols = split_line_on_whitespace(lines.next().unwrap());
+        multiline_comment_symbols = start_symbols.into_iter().zip(end_symbols).collect();
+        next_line = lines.next();
+        if next_line == Some(NESTED_COMMENTS) {
+            nested_comments = lines.next().unwrap().trim() == "yes";
+            next_line = lines.next();
         }
     }
+    let mut doc_comment_symbols = Vec::new();
+    if next_line == Some(DOC_COMMENT_SYMBOLS) {
+        doc_comment_symbols = split_line_on_whitespace(lines.next().unwrap());
+        next_line = lines.next();
+    }
+    let mut branch_keywords = Vec::new();
+    if next_line == Some(BRANCH_KEYWORDS) {
+        branch_keywords = split_line_on_whitespace(lines.next().unwrap());
+        next_line = lines.next();
+    }
+    let mut statement_symbols = Vec::new();
+    if next_line == Some(STATEMENT_SYMBOLS) {
+        statement_symbols = split_line_on_whitespace(lines.next().unwrap());
+        next_line = lines.next();
+    }
+    let mut heredoc_symbols = Vec::new();
+    if next_line == Some(HEREDOC_SYMBOLS) {
+        heredoc_symbols = split_line_on_whitespace(lines.next().unwrap());
+        next_line = lines.next();
+    }
+    let mut category = FileCategory::Code;
+    if next_line == Some(CATEGORY) {
+        category = lines.next().and_then(FileCategory::parse).unwrap_or(FileCategory::Code);
+        next_line = lines.next();
+    }
+    let mut char_literals = false;
+    if next_line == Some(CHAR_LITERALS) {
+        char_literals = lines.next().unwrap().trim() == "yes";
+        next_line = lines.next();
+    }
+    let mut string_delimiters = Vec::new();
+    if next_line == Some(STRING_DELIMITERS) {
+        string_delimiters = std::iter::from_fn(|| lines.next_if(|x| !x.trim().is_empty() && *x != EMBEDDED_REGIONS))
+                .filter_map(parse_string_delimiter).collect();
+        next_line = lines.next();
+    }
+    let mut embedded_regions = Vec::new();
+    if next_line == Some(EMBEDDED_REGIONS) {
+        embedded_regions = lines.by_ref().take_while(|x| !x.trim().is_empty()).filter_map(parse_embedded_region).collect();
+    }
 
     let mut keywords = Vec::new();
     while let Some(x) = lines.next() {
@@ -197,13 +416,68 @@ pub fn parse_string_to_language(contents: Cow<str>) -> Language {
         let k_name = lines.next().unwrap().trim().to_owned();
         lines.next();
         let k_aliases = split_line_on_whitespace(lines.next().unwrap());
+        let (mut k_category, mut k_case_insensitive, mut k_scope) = (None, false, KeywordScope::Code);
+        while let Some(header) = lines.next_if(|x| [KEYWORD_CATEGORY, KEYWORD_CASE_INSENSITIVE, KEYWORD_SCOPE].contains(&x.trim())) {
+            let value = lines.next().unwrap_or("").trim();
+            if header.trim() == KEYWORD_CATEGORY {
+                k_category = Some(value.to_owned());
+            } else if header.trim() == KEYWORD_SCOPE {
+                k_scope = KeywordScope::parse(value).unwrap_or(KeywordScope::Code);
+            } else {
+                k_case_insensitive = value == "yes";
+            }
+        }
         keywords.push(Keyword{
             descriptive_name: k_name,
-            aliases: k_aliases
+            aliases: k_aliases,
+            patterns: Vec::new(),
+            category: k_category,
+            case_insensitive: k_case_insensitive,
+            scope: k_scope
         });
     }
 
-    Language::new(lang_name, extensions, string_symbols, comment_symbols, mult_start, mult_end, keywords)
+    let mut language = Language::new(lang_name, extensions, string_symbols, comment_symbols, multiline_comment_symbols, keywords);
+    language.supports_nested_comments = nested_comments;
+    language.supports_char_literals = char_literals;
+    language.string_delimiters = string_delimiters;
+    language.doc_comment_symbols = doc_comment_symbols;
+    language.branch_keywords = branch_keywords;
+    language.statement_symbols = statement_symbols;
+    language.heredoc_symbols = heredoc_symbols;
+    language.embedded_regions = embedded_regions;
+    language.category = category;
+    language
+}
+
+// Every relevant line has the name of the language, the percentage of extra lines and the lines per file.
+// The rest of the lines are ignored. The names of the languages are lowercased.
+pub fn parse_language_references(contents: &str) -> HashMap<String, LanguageReference> {
+    contents.lines().filter_map(|line| {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        if words.len() != 3 {
+            return None;
+        }
+        let extra_lines_percentage = words[1].parse::<f64>().ok()?;
+        let lines_per_file = words[2].parse::<usize>().ok()?;
+        Some((words[0].to_lowercase(), LanguageReference::new(extra_lines_percentage, lines_per_file)))
+    })
+    .collect()
+}
+
+// For '--out'. The missing parent dirs are created, and an existing file is only truncated if it can be overwritten
+pub fn create_out_file(path: &str, can_overwrite: bool) -> Result<File, io::Error> {
+    if let Some(parent) = Path::new(path).parent().filter(|x| !x.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true);
+    if can_overwrite {
+        options.create(true).truncate(true);
+    } else {
+        options.create_new(true);
+    }
+    options.open(path)
 }
 
 pub fn serialize_language(lang: &Language, path: &str) -> Result<(), io::Error> {
@@ -226,13 +500,50 @@ pub fn serialize_language(lang: &Language, path: &str) -> Result<(), io::Error>
     writer.write(lang.comment_symbols.join(" ").as_bytes());
     writer.write(b"\n");
     
-    if let Some(symbol) = &lang.multiline_comment_start_symbol {
+    if lang.supports_multiline_comments() {
+        let (start_symbols, end_symbols) : (Vec<_>,Vec<_>) = lang.multiline_comment_symbols.iter().cloned().unzip();
         writer.write(format!("{}\n",MULTILINE_COMMENT_START).as_bytes());
-        writer.write(symbol.as_bytes());
+        writer.write(start_symbols.join(" ").as_bytes());
         writer.write(b"\n");
         writer.write(format!("{}\n",MULTILINE_COMMENT_END).as_bytes());
-        writer.write(lang.multiline_comment_end_symbol.as_ref().unwrap().as_bytes());
+        writer.write(end_symbols.join(" ").as_bytes());
         writer.write(b"\n");
+        if lang.supports_nested_comments {
+            writer.write_all(format!("{}\nyes\n",NESTED_COMMENTS).as_bytes())?;
+        }
+    }
+    if !lang.doc_comment_symbols.is_empty() {
+        writer.write_all(format!("{}\n{}\n",DOC_COMMENT_SYMBOLS, lang.doc_comment_symbols.join(" ")).as_bytes())?;
+    }
+    if !lang.branch_keywords.is_empty() {
+        writer.write_all(format!("{}\n{}\n",BRANCH_KEYWORDS, lang.branch_keywords.join(" ")).as_bytes())?;
+    }
+    if !lang.statement_symbols.is_empty() {
+        writer.write_all(format!("{}\n{}\n",STATEMENT_SYMBOLS, lang.statement_symbols.join(" ")).as_bytes())?;
+    }
+    if !lang.heredoc_symbols.is_empty() {
+        writer.write_all(format!("{}\n{}\n",HEREDOC_SYMBOLS, lang.heredoc_symbols.join(" ")).as_bytes())?;
+    }
+    if lang.category != FileCategory::Code {
+        writer.write_all(format!("{}\n{}\n",CATEGORY, lang.category.as_str()).as_bytes())?;
+    }
+    if lang.supports_char_literals {
+        writer.write_all(format!("{}\nyes\n",CHAR_LITERALS).as_bytes())?;
+    }
+    if !lang.string_delimiters.is_empty() {
+        writer.write_all(format!("{}\n",STRING_DELIMITERS).as_bytes())?;
+        for delimiter in lang.string_delimiters.iter() {
+            let raw = if delimiter.is_raw {format!(" {}", RAW_STRING)} else {String::new()};
+            let interpolation = delimiter.interpolation.as_ref().map_or(String::new(), |(start, end)| format!(" {} {}", start, end));
+            writer.write_all(format!("{} {}{}{}\n", delimiter.start, delimiter.end, raw, interpolation).as_bytes())?;
+        }
+    }
+    if !lang.embedded_regions.is_empty() {
+        writer.write_all(format!("{}\n",EMBEDDED_REGIONS).as_bytes())?;
+        for region in lang.embedded_regions.iter() {
+            let identifier = region.identifier.as_ref().map_or(String::new(), |x| format!(" {}", x));
+            writer.write_all(format!("{} {}{}\n", region.start, region.end, identifier).as_bytes())?;
+        }
     }
     writer.write(b"\n");
     
@@ -244,6 +555,15 @@ pub fn serialize_language(lang: &Language, path: &str) -> Result<(), io::Error>
         writer.write(format!("{}\n",KEYWORD_ALIASES).as_bytes());
         writer.write(keyword.aliases.join(" ").as_bytes());
         writer.write(b"\n");
+        if let Some(category) = &keyword.category {
+            writer.write_all(format!("{}\n{}\n", KEYWORD_CATEGORY, category).as_bytes())?;
+        }
+        if keyword.case_insensitive {
+            writer.write_all(format!("{}\nyes\n", KEYWORD_CASE_INSENSITIVE).as_bytes())?;
+        }
+        if keyword.scope != KeywordScope::Code {
+            writer.write_all(format!("{}\n{}\n", KEYWORD_SCOPE, keyword.scope.as_str()).as_bytes())?;
+        }
     }
 
     Ok(())
@@ -253,17 +573,52 @@ pub fn serialize_language(lang: &Language, path: &str) -> Result<(), io::Error>
 // ------------------------------ Config handling ------------------------------
 
 pub fn parse_config_file(file_name: Option<&str>, config_dir_path: Option<String>) -> Result<ConfigurationBuilder,ConfigFileParseError> {
+    let contents = read_config_file(file_name, config_dir_path)?;
+    Ok(parse_config_contents(split_config_profiles(&contents).0))
+}
+
+// The options of the '[profile.<name>]' of the file, that are meant to override the rest of the file.
+// None if the file has no such profile.
+pub fn parse_config_profile(file_name: Option<&str>, config_dir_path: Option<String>, profile: &str)
+        -> Result<Option<ConfigurationBuilder>,ConfigFileParseError>
+{
+    let contents = read_config_file(file_name, config_dir_path)?;
+    Ok(split_config_profiles(&contents).1.into_iter().find(|(name, _)| *name == profile).map(|(_, x)| parse_config_contents(x)))
+}
+
+fn read_config_file(file_name: Option<&str>, config_dir_path: Option<String>) -> Result<String,ConfigFileParseError> {
     let config_path = if let Some(dir) = config_dir_path {dir} else {PERSISTENT_APP_PATHS.config_dir.clone()};
     let file_name = if let Some(x) = file_name {x} else {DEFAULT_CONFIG_NAME};
     let file_path = (config_path + file_name + ".txt").replace("\\", "/");
-    let mut reader = BufReader::new(match fs::File::open(file_path){
-        Ok(f) => f,
-        Err(_) => return Err(ConfigFileParseError::FileNotFound(file_name.to_owned()))
-    });
-
-    let (mut dirs, mut braces_as_code, mut should_search_in_dotted, mut threads, mut exclude_dirs,
-         mut languages_of_interest, mut should_show_faulty_files, mut no_keywords, mut no_visual,
-         mut log, mut compare_level) = (None,None,None,None,None,None,None,None,None,None,None);
+    fs::read_to_string(file_path).map_err(|_| ConfigFileParseError::FileNotFound(file_name.to_owned()))
+}
+
+// The options before the first profile, and the options of every profile as (name, options). A profile starts
+// with a '[profile.<name>]' line and lasts until the next one, e.g.
+// [profile.ci]
+// ===> threads
+// 4 4
+fn split_config_profiles(contents: &str) -> (&str, Vec<(&str, &str)>) {
+    let mut starts = Vec::new();
+    let mut offset = 0;
+    for line in contents.split_inclusive('\n') {
+        if let Some(name) = line.trim().strip_prefix(PROFILE_PREFIX).and_then(|x| x.strip_suffix(']')) {
+            starts.push((name.trim(), offset, offset + line.len()));
+        }
+        offset += line.len();
+    }
+
+    let base = &contents[..starts.first().map(|x| x.1).unwrap_or(contents.len())];
+    let profiles = starts.iter().enumerate().map(|(i, (name, _, options_start))| {
+        let options_end = starts.get(i + 1).map(|x| x.1).unwrap_or(contents.len());
+        (*name, &contents[*options_start..options_end])
+    }).collect();
+    (base, profiles)
+}
+
+fn parse_config_contents(contents: &str) -> ConfigurationBuilder {
+    let mut reader = contents.as_bytes();
+    let mut config_builder = ConfigurationBuilder::default();
     let mut buf = String::with_capacity(150); 
 
     while let Ok(size) = reader.read_line(&mut buf) {
@@ -274,53 +629,348 @@ pub fn parse_config_file(file_name: Option<&str>, config_dir_path: Option<String
             if id == config_manager::DIRS {
                 let paths = read_lines_from_file_to_vec(&mut reader, &mut buf, utils::parse_paths_to_vec);
                 if !paths.is_empty() {
-                    dirs = Some(paths);
+                    config_builder.dirs = Some(paths);
                 }
             } else if id == config_manager::EXCLUDE {
                 let paths = read_lines_from_file_to_vec(&mut reader, &mut buf, utils::parse_paths_to_vec);
                 if !paths.is_empty() {
-                    exclude_dirs = Some(paths);
+                    config_builder.exclude_dirs = Some(paths);
                 }
             } else if id == config_manager::LANGUAGES {
                 let langs = read_lines_from_file_to_vec(&mut reader, &mut buf, utils::parse_languages_to_vec);
                 if !langs.is_empty() {
-                    languages_of_interest = Some(langs);
+                    config_builder.languages_of_interest = Some(langs);
+                }
+            } else if id == config_manager::EXT {
+                let filters = read_lines_from_file_to_vec(&mut reader, &mut buf, utils::parse_extension_filters);
+                if !filters.is_empty() {
+                    config_builder.extension_filters = Some(filters);
                 }
             } else if id == config_manager::THREADS {
                 buf.clear();
                 reader.read_line(&mut buf);
-                threads = Some(Threads::from(utils::parse_two_usize_values(&buf,MIN_PRODUCERS_VALUE, MAX_PRODUCERS_VALUE,
-                        MIN_CONSUMERS_VALUE, MAX_CONSUMERS_VALUE).unwrap()));
+                config_builder.threads = utils::parse_two_usize_values(&buf, MIN_PRODUCERS_VALUE, usize::MAX, MIN_CONSUMERS_VALUE, usize::MAX)
+                        .map(|(producers, consumers)| Threads::clamped(producers, consumers));
             }else if id == config_manager::BRACES_AS_CODE {
-                braces_as_code = read_bool_value_from_file(&mut reader, &mut buf);
+                config_builder.braces_as_code = read_bool_value_from_file(&mut reader, &mut buf);
             } else if id == config_manager::SHOW_FAULTY_FILES {
-                should_show_faulty_files = read_bool_value_from_file(&mut reader, &mut buf);
+                config_builder.should_show_faulty_files = read_bool_value_from_file(&mut reader, &mut buf);
             } else if id == config_manager::SEARCH_IN_DOTTED {
-                should_search_in_dotted = read_bool_value_from_file(&mut reader, &mut buf);
+                config_builder.should_search_in_dotted = read_bool_value_from_file(&mut reader, &mut buf);
             } else if id == config_manager::NO_KEYWORDS {
-                no_keywords = read_bool_value_from_file(&mut reader, &mut buf);
+                config_builder.no_keywords = read_bool_value_from_file(&mut reader, &mut buf);
             } else if id == config_manager::NO_VISUAL {
-                no_visual = read_bool_value_from_file(&mut reader, &mut buf);
+                config_builder.no_visual = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::LANG_SCOPE {
+                let rules = read_lines_from_file_to_vec(&mut reader, &mut buf, |x| utils::get_trimmed_if_not_empty(x).into_iter().collect());
+                config_builder.lang_scopes = config_manager::parse_lang_scopes(&rules.join(";"));
+            } else if id == config_manager::SQLITE {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.sqlite = config_manager::parse_report_file(&buf);
+            } else if id == config_manager::EVENT_LOG {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.event_log = config_manager::parse_report_file(&buf);
+            } else if id == config_manager::OUT {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.out = config_manager::parse_report_file(&buf);
+            } else if id == config_manager::FORCE {
+                config_builder.force = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::MATCH_CASE {
+                config_builder.match_case = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::DUPLICATES {
+                config_builder.duplicates = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::LICENSES {
+                config_builder.licenses = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::HYGIENE {
+                config_builder.hygiene = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::INDENTATION {
+                config_builder.indentation = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::SKIP_GENERATED {
+                config_builder.skip_generated = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::SKIP_MINIFIED {
+                config_builder.skip_minified = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::KEYWORD_HOTSPOTS {
+                config_builder.keyword_hotspots = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::PRESCAN {
+                config_builder.prescan = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::LINGUIST_COMPARE {
+                config_builder.linguist_compare = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::AUTHORS {
+                config_builder.authors = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::ESTIMATE {
+                config_builder.estimate = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::SALARY {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.salary = utils::parse_usize_value(&buf, config_manager::MIN_SALARY, config_manager::MAX_SALARY);
+            } else if id == config_manager::OVERHEAD {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.overhead = utils::parse_usize_value(&buf, config_manager::MIN_OVERHEAD, config_manager::MAX_OVERHEAD);
+            } else if id == config_manager::STRICT {
+                config_builder.strict = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::ZERO_KEYWORDS {
+                config_builder.zero_keywords = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::TOP_KEYWORDS {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.top_keywords = utils::parse_usize_value(&buf, config_manager::MIN_TOP_KEYWORDS, config_manager::MAX_TOP_KEYWORDS);
+            } else if id == config_manager::TUI {
+                config_builder.tui = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::STATS {
+                config_builder.stats = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::EMBEDDED {
+                config_builder.embedded = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::IGNORE_TRAILING_EMPTY_LINE {
+                config_builder.ignore_trailing_empty_line = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::SUMMARY {
+                config_builder.summary = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::STYLE {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.style = config_manager::Style::parse(&buf);
+            } else if id == config_manager::OVERVIEW {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.overview = config_manager::Column::parse_list(&buf).filter(|x| !x.contains(&config_manager::Column::Share));
+            } else if id == config_manager::SHOW_SKIPPED {
+                config_builder.show_skipped = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::DISAMBIGUATE {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.disambiguate = heuristics::parse_extensions(&buf);
+            } else if id == config_manager::KEYWORD_SCOPE {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.keyword_scope = KeywordScope::parse(&buf);
+            } else if id == config_manager::INCLUDE_SUBMODULES || id == config_manager::EXCLUDE_SUBMODULES {
+                let value = if id == config_manager::INCLUDE_SUBMODULES {Submodules::Include} else {Submodules::Exclude};
+                if read_bool_value_from_file(&mut reader, &mut buf) == Some(true) {
+                    config_builder.submodules = Some(value);
+                }
+            } else if id == config_manager::EXPORT_TREEMAP {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.export_treemap = treemap::parse_treemap_path(&buf);
+            } else if id == config_manager::REPORT {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.report_file = config_manager::parse_report_file(&buf);
+            } else if id == config_manager::NOTIFY {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.notify = config_manager::parse_notify_target(&buf);
+            } else if id == config_manager::REMOTE {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.remote = Some(buf.trim().to_owned()).filter(|x| remote::is_valid_remote_url(x));
+            } else if id == config_manager::CHANGED_SINCE {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.changed_since = config_manager::parse_revision(&buf);
+            } else if id == config_manager::NOTIFY_TEMPLATE {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.notify_template = utils::get_trimmed_if_not_empty(&buf);
+            } else if id == config_manager::REGEX_KEYWORDS {
+                config_builder.regex_keywords = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::CO_OCCURRENCE {
+                buf.clear();
+                reader.read_line(&mut buf);
+                let value = buf.trim().to_lowercase();
+                if value == "yes" || value == "true" {
+                    config_builder.co_occurrence = Some(Vec::new());
+                } else if value != "no" && value != "false" {
+                    config_builder.co_occurrence = Some(utils::parse_keyword_names_to_vec(&value));
+                }
+            } else if id == config_manager::TODOS {
+                buf.clear();
+                reader.read_line(&mut buf);
+                let value = buf.trim().to_lowercase();
+                if value == "yes" || value == "true" {
+                    config_builder.todos = Some(Vec::new());
+                } else if value != "no" && value != "false" {
+                    config_builder.todos = Some(utils::parse_todo_markers_to_vec(&buf));
+                }
+            } else if id == config_manager::TEST_PATTERN {
+                buf.clear();
+                reader.read_line(&mut buf);
+                let value = buf.trim().to_lowercase();
+                if value == "yes" || value == "true" {
+                    config_builder.test_patterns = Some(Vec::new());
+                } else if value != "no" && value != "false" {
+                    config_builder.test_patterns = Some(utils::parse_paths_to_vec(&buf));
+                }
+            } else if id == config_manager::SORT {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.sort = config_manager::SortOrder::parse(&buf);
+            } else if id == config_manager::COLUMNS {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.columns = config_manager::Column::parse_list(&buf);
+            } else if id == config_manager::FAIL_IF {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.fail_if = budgets::parse_budgets(&buf);
+            } else if id == config_manager::METRICS {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.metrics = derived_metrics::parse_derived_metrics(&buf);
+            } else if id == config_manager::LANG {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.lang = Some(buf.trim().to_lowercase()).filter(|x| labels::is_supported_language(x));
+            } else if id == config_manager::OUTPUT {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.output = config_manager::OutputFormat::parse(&buf);
+            } else if id == config_manager::COLOR {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.color = config_manager::ColorPolicy::parse(&buf);
+            } else if id == config_manager::PATH_STYLE {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.path_style = config_manager::PathStyle::parse(&buf);
+            } else if id == config_manager::SIZE_UNITS {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.size_units = config_manager::SizeUnits::parse(&buf);
+            } else if id == config_manager::SIZE_PRECISION {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.size_precision = utils::parse_usize_value(&buf, config_manager::MIN_SIZE_PRECISION, config_manager::MAX_SIZE_PRECISION);
+            } else if id == config_manager::NUMBER_FORMAT {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.number_format = config_manager::NumberFormat::parse(&buf);
+            } else if id == config_manager::DIR_BREAKDOWN {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.dir_breakdown = utils::parse_usize_value(&buf, config_manager::MIN_DIR_BREAKDOWN, config_manager::MAX_DIR_BREAKDOWN);
+            } else if id == config_manager::MAX_DEPTH {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.max_depth = utils::parse_usize_value(&buf, config_manager::MIN_MAX_DEPTH, config_manager::MAX_MAX_DEPTH);
+            } else if id == config_manager::TOP_FILES {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.top_files = utils::parse_usize_value(&buf, config_manager::MIN_TOP_FILES, config_manager::MAX_TOP_FILES);
+            } else if id == config_manager::CLONES {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.clones = utils::parse_usize_value(&buf, config_manager::MIN_CLONE_LINES, config_manager::MAX_CLONE_LINES);
+            } else if id == config_manager::TOP {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.top = utils::parse_usize_value(&buf, config_manager::MIN_TOP, config_manager::MAX_TOP);
+            } else if id == config_manager::MIN_SIZE {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.min_size = utils::parse_size_value(&buf);
+            } else if id == config_manager::MAX_SIZE {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.max_size = utils::parse_size_value(&buf);
+            } else if id == config_manager::DOCS {
+                config_builder.docs = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::HISTOGRAM {
+                config_builder.histogram = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::LIST_ONLY {
+                config_builder.list_only = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::TIMINGS {
+                config_builder.timings = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::STREAM {
+                config_builder.stream = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::LOGICAL_LINES {
+                config_builder.logical_lines = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::COMPLEXITY {
+                config_builder.complexity = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::ISOLATE_ROOTS {
+                config_builder.isolate_roots = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::GIT_TRACKED {
+                config_builder.git_tracked = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::FOLLOW_LINKS {
+                config_builder.follow_links = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::HIDDEN {
+                config_builder.hidden = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::MAX_MEMORY {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.max_memory = utils::parse_usize_value(&buf, config_manager::MIN_MAX_MEMORY, config_manager::MAX_MAX_MEMORY);
+            } else if id == config_manager::TIMEOUT {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.timeout = utils::parse_usize_value(&buf, config_manager::MIN_TIMEOUT, config_manager::MAX_TIMEOUT);
+            } else if id == config_manager::FILE_TIMEOUT {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.file_timeout = utils::parse_usize_value(&buf, config_manager::MIN_TIMEOUT, config_manager::MAX_TIMEOUT);
+            } else if id == config_manager::MAX_OPEN_FILES {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.max_open_files = utils::parse_usize_value(&buf, config_manager::MIN_MAX_OPEN_FILES, config_manager::MAX_MAX_OPEN_FILES);
+            } else if id == config_manager::STRICT_ENCODING {
+                config_builder.strict_encoding = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::TYPICAL_MARKERS {
+                config_builder.typical_markers = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::KEYWORD_DENSITY {
+                config_builder.keyword_density = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::KEYWORD_CATEGORIES {
+                config_builder.keyword_categories = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::SKIP_BUILD_OUTPUTS {
+                config_builder.skip_build_outputs = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::LARGE_FILE_THRESHOLD {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.large_file_threshold = utils::parse_usize_value(&buf, config_manager::MIN_LARGE_FILE_THRESHOLD, config_manager::MAX_LARGE_FILE_THRESHOLD);
+            } else if id == config_manager::SPLIT_THRESHOLD {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.split_threshold = utils::parse_usize_value(&buf, config_manager::MIN_SPLIT_THRESHOLD, config_manager::MAX_SPLIT_THRESHOLD);
+            } else if id == config_manager::MAX_LINE_LENGTH {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.max_line_length = utils::parse_usize_value(&buf, config_manager::MIN_MAX_LINE_LENGTH, config_manager::MAX_MAX_LINE_LENGTH);
+            } else if id == config_manager::PREFER_SHEBANGS {
+                config_builder.prefer_shebangs = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::DETECT_SHEBANGS {
+                config_builder.detect_shebangs = read_bool_value_from_file(&mut reader, &mut buf);
+            } else if id == config_manager::IGNORE_EMPTY_FILES {
+                config_builder.ignore_empty_files = read_bool_value_from_file(&mut reader, &mut buf);
             } else if id == config_manager::LOG {
                 buf.clear();
                 reader.read_line(&mut buf);
                 let name = &buf.trim().to_lowercase();
                 if name == "yes" || name == "true" {
-                    log = Some(LogOption::new(None));
+                    config_builder.log = Some(LogOption::new(None));
                 } else if name != "no" && name != "false"{
-                    log = Some(LogOption::new(Some(name.to_owned())));
+                    config_builder.log = Some(LogOption::new(Some(name.to_owned())));
                 }
             } else if id == config_manager::COMPRARE_LEVEL {
                 buf.clear();
                 reader.read_line(&mut buf);
-                compare_level = utils::parse_usize_value(&buf,MIN_COMPARE_LEVEL, MAX_COMPARE_LEVEL);
+                config_builder.compare_level = utils::parse_usize_value(&buf,MIN_COMPARE_LEVEL, MAX_COMPARE_LEVEL);
+            } else if id == config_manager::RESTRICT_TO {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.restrict_to = utils::parse_canonical_dir(&buf);
+            } else if id == config_manager::LANGS_DIR {
+                buf.clear();
+                reader.read_line(&mut buf);
+                config_builder.langs_dir = utils::parse_canonical_dir(&buf);
+            } else {
+                warnings::add(WarningKind::UnknownConfigKey, format!("'{}' is not an option of the configuration files, it is ignored.", id));
             }
         }
         buf.clear();
     }
 
-    Ok(ConfigurationBuilder::new(dirs,exclude_dirs, languages_of_interest, threads, braces_as_code,should_search_in_dotted,
-             should_show_faulty_files, no_keywords, no_visual, log, compare_level, None, None))
+    config_builder
 }
 
 // Dirs must be specified (is checked before calling this function)
@@ -331,8 +981,21 @@ pub fn save_existing_commands_from_config_builder_to_file(config_path: Option<St
     let file_name = config_dir + config_name + ".txt";
 
     let mut writer = BufWriter::new(std::fs::OpenOptions::new().write(true).create(true).truncate(true).open(file_name)?);
-
     writer.write(b"Auto-generated config file.");
+    write_config_builder(&mut writer, config_builder)?;
+    writer.flush();
+
+    Ok(())
+}
+
+// The options of the configuration in the format of the config files, so that it can be saved as one
+pub fn config_builder_to_string(config_builder: &ConfigurationBuilder) -> String {
+    let mut contents = Vec::new();
+    write_config_builder(&mut contents, config_builder).ok();
+    String::from_utf8_lossy(&contents).trim_start().to_owned()
+}
+
+fn write_config_builder(writer: &mut impl Write, config_builder: &ConfigurationBuilder) -> std::io::Result<()> {
 
     writer.write(&[b"\n\n===> ",config_manager::DIRS.as_bytes(),b"\n"].concat());
     writer.write(config_builder.dirs.as_ref().unwrap().join(",").as_bytes());
@@ -369,6 +1032,354 @@ pub fn save_existing_commands_from_config_builder_to_file(config_path: Option<St
         writer.write(&[b"\n\n===> ",config_manager::NO_VISUAL.as_bytes(),b"\n"].concat());
         writer.write(if *no_visual {b"yes"} else {b"no"});
     }
+    if let Some(sqlite) = &config_builder.sqlite {
+        writer.write_all(&[b"\n\n===> ",config_manager::SQLITE.as_bytes(),b"\n"].concat())?;
+        writer.write_all(sqlite.as_bytes())?;
+    }
+    if let Some(event_log) = &config_builder.event_log {
+        writer.write_all(&[b"\n\n===> ",config_manager::EVENT_LOG.as_bytes(),b"\n"].concat())?;
+        writer.write_all(event_log.as_bytes())?;
+    }
+    if let Some(out) = &config_builder.out {
+        writer.write_all(&[b"\n\n===> ",config_manager::OUT.as_bytes(),b"\n"].concat())?;
+        writer.write_all(out.as_bytes())?;
+    }
+    if let Some(force) = &config_builder.force {
+        writer.write_all(&[b"\n\n===> ",config_manager::FORCE.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *force {b"yes"} else {b"no"})?;
+    }
+    if let Some(match_case) = &config_builder.match_case {
+        writer.write_all(&[b"\n\n===> ",config_manager::MATCH_CASE.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *match_case {b"yes"} else {b"no"})?;
+    }
+    if let Some(duplicates) = &config_builder.duplicates {
+        writer.write_all(&[b"\n\n===> ",config_manager::DUPLICATES.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *duplicates {b"yes"} else {b"no"})?;
+    }
+    if let Some(licenses) = &config_builder.licenses {
+        writer.write_all(&[b"\n\n===> ",config_manager::LICENSES.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *licenses {b"yes"} else {b"no"})?;
+    }
+    if let Some(hygiene) = &config_builder.hygiene {
+        writer.write_all(&[b"\n\n===> ",config_manager::HYGIENE.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *hygiene {b"yes"} else {b"no"})?;
+    }
+    if let Some(indentation) = &config_builder.indentation {
+        writer.write_all(&[b"\n\n===> ",config_manager::INDENTATION.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *indentation {b"yes"} else {b"no"})?;
+    }
+    if let Some(skip_generated) = &config_builder.skip_generated {
+        writer.write_all(&[b"\n\n===> ",config_manager::SKIP_GENERATED.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *skip_generated {b"yes"} else {b"no"})?;
+    }
+    if let Some(skip_minified) = &config_builder.skip_minified {
+        writer.write_all(&[b"\n\n===> ",config_manager::SKIP_MINIFIED.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *skip_minified {b"yes"} else {b"no"})?;
+    }
+    if let Some(keyword_hotspots) = &config_builder.keyword_hotspots {
+        writer.write_all(&[b"\n\n===> ",config_manager::KEYWORD_HOTSPOTS.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *keyword_hotspots {b"yes"} else {b"no"})?;
+    }
+    if let Some(prescan) = &config_builder.prescan {
+        writer.write_all(&[b"\n\n===> ",config_manager::PRESCAN.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *prescan {b"yes"} else {b"no"})?;
+    }
+    if let Some(linguist_compare) = &config_builder.linguist_compare {
+        writer.write_all(&[b"\n\n===> ",config_manager::LINGUIST_COMPARE.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *linguist_compare {b"yes"} else {b"no"})?;
+    }
+    if let Some(authors) = &config_builder.authors {
+        writer.write_all(&[b"\n\n===> ",config_manager::AUTHORS.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *authors {b"yes"} else {b"no"})?;
+    }
+    if let Some(estimate) = &config_builder.estimate {
+        writer.write_all(&[b"\n\n===> ",config_manager::ESTIMATE.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *estimate {b"yes"} else {b"no"})?;
+    }
+    if let Some(salary) = &config_builder.salary {
+        writer.write_all(&[b"\n\n===> ",config_manager::SALARY.as_bytes(),b"\n"].concat())?;
+        writer.write_all(salary.to_string().as_bytes())?;
+    }
+    if let Some(overhead) = &config_builder.overhead {
+        writer.write_all(&[b"\n\n===> ",config_manager::OVERHEAD.as_bytes(),b"\n"].concat())?;
+        writer.write_all(overhead.to_string().as_bytes())?;
+    }
+    if let Some(strict) = &config_builder.strict {
+        writer.write_all(&[b"\n\n===> ",config_manager::STRICT.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *strict {b"yes"} else {b"no"})?;
+    }
+    if let Some(zero_keywords) = &config_builder.zero_keywords {
+        writer.write_all(&[b"\n\n===> ",config_manager::ZERO_KEYWORDS.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *zero_keywords {b"yes"} else {b"no"})?;
+    }
+    if let Some(top_keywords) = &config_builder.top_keywords {
+        writer.write_all(&[b"\n\n===> ",config_manager::TOP_KEYWORDS.as_bytes(),b"\n"].concat())?;
+        writer.write_all(top_keywords.to_string().as_bytes())?;
+    }
+    if let Some(tui) = &config_builder.tui {
+        writer.write_all(&[b"\n\n===> ",config_manager::TUI.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *tui {b"yes"} else {b"no"})?;
+    }
+    if let Some(stats) = &config_builder.stats {
+        writer.write_all(&[b"\n\n===> ",config_manager::STATS.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *stats {b"yes"} else {b"no"})?;
+    }
+    if let Some(embedded) = &config_builder.embedded {
+        writer.write_all(&[b"\n\n===> ",config_manager::EMBEDDED.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *embedded {b"yes"} else {b"no"})?;
+    }
+    if let Some(ignore_trailing_empty_line) = &config_builder.ignore_trailing_empty_line {
+        writer.write_all(&[b"\n\n===> ",config_manager::IGNORE_TRAILING_EMPTY_LINE.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *ignore_trailing_empty_line {b"yes"} else {b"no"})?;
+    }
+    if let Some(summary) = &config_builder.summary {
+        writer.write_all(&[b"\n\n===> ",config_manager::SUMMARY.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *summary {b"yes"} else {b"no"})?;
+    }
+    if let Some(style) = &config_builder.style {
+        writer.write_all(&[b"\n\n===> ",config_manager::STYLE.as_bytes(),b"\n"].concat())?;
+        writer.write_all(style.as_str().as_bytes())?;
+    }
+    if let Some(overview) = &config_builder.overview {
+        writer.write_all(&[b"\n\n===> ",config_manager::OVERVIEW.as_bytes(),b"\n"].concat())?;
+        writer.write_all(overview.iter().map(|x| x.as_str()).collect::<Vec<_>>().join(",").as_bytes())?;
+    }
+    if let Some(show_skipped) = &config_builder.show_skipped {
+        writer.write_all(&[b"\n\n===> ",config_manager::SHOW_SKIPPED.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *show_skipped {b"yes"} else {b"no"})?;
+    }
+    if let Some(disambiguate) = &config_builder.disambiguate {
+        writer.write_all(&[b"\n\n===> ",config_manager::DISAMBIGUATE.as_bytes(),b"\n"].concat())?;
+        writer.write_all(disambiguate.join(",").as_bytes())?;
+    }
+    if let Some(keyword_scope) = &config_builder.keyword_scope {
+        writer.write_all(&[b"\n\n===> ",config_manager::KEYWORD_SCOPE.as_bytes(),b"\n"].concat())?;
+        writer.write_all(keyword_scope.as_str().as_bytes())?;
+    }
+    if let Some(submodules) = &config_builder.submodules {
+        let id = if *submodules == Submodules::Include {config_manager::INCLUDE_SUBMODULES} else {config_manager::EXCLUDE_SUBMODULES};
+        writer.write_all(&[b"\n\n===> ",id.as_bytes(),b"\nyes"].concat())?;
+    }
+    if let Some(export_treemap) = &config_builder.export_treemap {
+        writer.write_all(&[b"\n\n===> ",config_manager::EXPORT_TREEMAP.as_bytes(),b"\n"].concat())?;
+        writer.write_all(export_treemap.as_bytes())?;
+    }
+    if let Some(report_file) = &config_builder.report_file {
+        writer.write_all(&[b"\n\n===> ",config_manager::REPORT.as_bytes(),b"\n"].concat())?;
+        writer.write_all(report_file.as_bytes())?;
+    }
+    if let Some(remote) = &config_builder.remote {
+        writer.write_all(&[b"\n\n===> ",config_manager::REMOTE.as_bytes(),b"\n"].concat())?;
+        writer.write_all(remote.as_bytes())?;
+    }
+    if let Some(changed_since) = &config_builder.changed_since {
+        writer.write_all(&[b"\n\n===> ",config_manager::CHANGED_SINCE.as_bytes(),b"\n"].concat())?;
+        writer.write_all(changed_since.as_bytes())?;
+    }
+    if let Some(notify) = &config_builder.notify {
+        writer.write_all(&[b"\n\n===> ",config_manager::NOTIFY.as_bytes(),b"\n"].concat())?;
+        writer.write_all(notify.as_bytes())?;
+    }
+    if let Some(notify_template) = &config_builder.notify_template {
+        writer.write_all(&[b"\n\n===> ",config_manager::NOTIFY_TEMPLATE.as_bytes(),b"\n"].concat())?;
+        writer.write_all(notify_template.as_bytes())?;
+    }
+    if let Some(regex_keywords) = &config_builder.regex_keywords {
+        writer.write_all(&[b"\n\n===> ",config_manager::REGEX_KEYWORDS.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *regex_keywords {b"yes"} else {b"no"})?;
+    }
+    if let Some(co_occurrence) = &config_builder.co_occurrence {
+        writer.write_all(&[b"\n\n===> ",config_manager::CO_OCCURRENCE.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if co_occurrence.is_empty() {"yes".to_owned()} else {co_occurrence.join(", ")}.as_bytes())?;
+    }
+    if let Some(todos) = &config_builder.todos {
+        writer.write_all(&[b"\n\n===> ",config_manager::TODOS.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if todos.is_empty() {"yes".to_owned()} else {todos.join(", ")}.as_bytes())?;
+    }
+    if let Some(test_patterns) = &config_builder.test_patterns {
+        writer.write_all(&[b"\n\n===> ",config_manager::TEST_PATTERN.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if test_patterns.is_empty() {"yes".to_owned()} else {test_patterns.join(", ")}.as_bytes())?;
+    }
+    if let Some(detect_shebangs) = &config_builder.detect_shebangs {
+        writer.write_all(&[b"\n\n===> ",config_manager::DETECT_SHEBANGS.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *detect_shebangs {b"yes"} else {b"no"})?;
+    }
+    if let Some(max_depth) = &config_builder.max_depth {
+        writer.write_all(&[b"\n\n===> ",config_manager::MAX_DEPTH.as_bytes(),b"\n"].concat())?;
+        writer.write_all(max_depth.to_string().as_bytes())?;
+    }
+    if let Some(top) = &config_builder.top {
+        writer.write_all(&[b"\n\n===> ",config_manager::TOP.as_bytes(),b"\n"].concat())?;
+        writer.write_all(top.to_string().as_bytes())?;
+    }
+    if let Some(top_files) = &config_builder.top_files {
+        writer.write_all(&[b"\n\n===> ",config_manager::TOP_FILES.as_bytes(),b"\n"].concat())?;
+        writer.write_all(top_files.to_string().as_bytes())?;
+    }
+    if let Some(clones) = &config_builder.clones {
+        writer.write_all(&[b"\n\n===> ",config_manager::CLONES.as_bytes(),b"\n"].concat())?;
+        writer.write_all(clones.to_string().as_bytes())?;
+    }
+    if let Some(histogram) = &config_builder.histogram {
+        writer.write_all(&[b"\n\n===> ",config_manager::HISTOGRAM.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *histogram {b"yes"} else {b"no"})?;
+    }
+    if let Some(list_only) = &config_builder.list_only {
+        writer.write_all(&[b"\n\n===> ",config_manager::LIST_ONLY.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *list_only {b"yes"} else {b"no"})?;
+    }
+    if let Some(timings) = &config_builder.timings {
+        writer.write_all(&[b"\n\n===> ",config_manager::TIMINGS.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *timings {b"yes"} else {b"no"})?;
+    }
+    if let Some(stream) = &config_builder.stream {
+        writer.write_all(&[b"\n\n===> ",config_manager::STREAM.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *stream {b"yes"} else {b"no"})?;
+    }
+    if let Some(logical_lines) = &config_builder.logical_lines {
+        writer.write_all(&[b"\n\n===> ",config_manager::LOGICAL_LINES.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *logical_lines {b"yes"} else {b"no"})?;
+    }
+    if let Some(complexity) = &config_builder.complexity {
+        writer.write_all(&[b"\n\n===> ",config_manager::COMPLEXITY.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *complexity {b"yes"} else {b"no"})?;
+    }
+    if let Some(sort) = &config_builder.sort {
+        writer.write_all(&[b"\n\n===> ",config_manager::SORT.as_bytes(),b"\n"].concat())?;
+        writer.write_all(sort.as_string().as_bytes())?;
+    }
+    if let Some(columns) = &config_builder.columns {
+        writer.write_all(&[b"\n\n===> ",config_manager::COLUMNS.as_bytes(),b"\n"].concat())?;
+        writer.write_all(columns.iter().map(|x| x.as_str()).collect::<Vec<_>>().join(",").as_bytes())?;
+    }
+    if let Some(fail_if) = &config_builder.fail_if {
+        writer.write_all(&[b"\n\n===> ",config_manager::FAIL_IF.as_bytes(),b"\n"].concat())?;
+        writer.write_all(fail_if.iter().map(|x| x.expression.as_str()).collect::<Vec<_>>().join(", ").as_bytes())?;
+    }
+    if let Some(metrics) = &config_builder.metrics {
+        writer.write_all(&[b"\n\n===> ",config_manager::METRICS.as_bytes(),b"\n"].concat())?;
+        writer.write_all(metrics.iter().map(|x| x.definition.as_str()).collect::<Vec<_>>().join(", ").as_bytes())?;
+    }
+    if let Some(lang) = &config_builder.lang {
+        writer.write_all(&[b"\n\n===> ",config_manager::LANG.as_bytes(),b"\n"].concat())?;
+        writer.write_all(lang.as_bytes())?;
+    }
+    if let Some(output) = &config_builder.output {
+        writer.write_all(&[b"\n\n===> ",config_manager::OUTPUT.as_bytes(),b"\n"].concat())?;
+        writer.write_all(output.as_str().as_bytes())?;
+    }
+    if let Some(color) = &config_builder.color {
+        writer.write_all(&[b"\n\n===> ",config_manager::COLOR.as_bytes(),b"\n"].concat())?;
+        writer.write_all(color.as_str().as_bytes())?;
+    }
+    if let Some(path_style) = &config_builder.path_style {
+        writer.write_all(&[b"\n\n===> ",config_manager::PATH_STYLE.as_bytes(),b"\n"].concat())?;
+        writer.write_all(path_style.as_str().as_bytes())?;
+    }
+    if let Some(size_units) = &config_builder.size_units {
+        writer.write_all(&[b"\n\n===> ",config_manager::SIZE_UNITS.as_bytes(),b"\n"].concat())?;
+        writer.write_all(size_units.as_str().as_bytes())?;
+    }
+    if let Some(size_precision) = &config_builder.size_precision {
+        writer.write_all(&[b"\n\n===> ",config_manager::SIZE_PRECISION.as_bytes(),b"\n"].concat())?;
+        writer.write_all(size_precision.to_string().as_bytes())?;
+    }
+    if let Some(number_format) = &config_builder.number_format {
+        writer.write_all(&[b"\n\n===> ",config_manager::NUMBER_FORMAT.as_bytes(),b"\n"].concat())?;
+        writer.write_all(number_format.as_str().as_bytes())?;
+    }
+    if let Some(dir_breakdown) = &config_builder.dir_breakdown {
+        writer.write_all(&[b"\n\n===> ",config_manager::DIR_BREAKDOWN.as_bytes(),b"\n"].concat())?;
+        writer.write_all(dir_breakdown.to_string().as_bytes())?;
+    }
+    if let Some(min_size) = &config_builder.min_size {
+        writer.write_all(&[b"\n\n===> ",config_manager::MIN_SIZE.as_bytes(),b"\n"].concat())?;
+        writer.write_all(min_size.to_string().as_bytes())?;
+    }
+    if let Some(max_size) = &config_builder.max_size {
+        writer.write_all(&[b"\n\n===> ",config_manager::MAX_SIZE.as_bytes(),b"\n"].concat())?;
+        writer.write_all(max_size.to_string().as_bytes())?;
+    }
+    if let Some(docs) = &config_builder.docs {
+        writer.write_all(&[b"\n\n===> ",config_manager::DOCS.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *docs {b"yes"} else {b"no"})?;
+    }
+    if let Some(isolate_roots) = &config_builder.isolate_roots {
+        writer.write_all(&[b"\n\n===> ",config_manager::ISOLATE_ROOTS.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *isolate_roots {b"yes"} else {b"no"})?;
+    }
+    if let Some(git_tracked) = &config_builder.git_tracked {
+        writer.write_all(&[b"\n\n===> ",config_manager::GIT_TRACKED.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *git_tracked {b"yes"} else {b"no"})?;
+    }
+    if let Some(follow_links) = &config_builder.follow_links {
+        writer.write_all(&[b"\n\n===> ",config_manager::FOLLOW_LINKS.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *follow_links {b"yes"} else {b"no"})?;
+    }
+    if let Some(hidden) = &config_builder.hidden {
+        writer.write_all(&[b"\n\n===> ",config_manager::HIDDEN.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *hidden {b"yes"} else {b"no"})?;
+    }
+    if let Some(extension_filters) = &config_builder.extension_filters {
+        writer.write_all(&[b"\n\n===> ",config_manager::EXT.as_bytes(),b"\n"].concat())?;
+        writer.write_all(extension_filters.join(",").as_bytes())?;
+    }
+    if let Some(max_memory) = &config_builder.max_memory {
+        writer.write_all(&[b"\n\n===> ",config_manager::MAX_MEMORY.as_bytes(),b"\n"].concat())?;
+        writer.write_all(max_memory.to_string().as_bytes())?;
+    }
+    if let Some(timeout) = &config_builder.timeout {
+        writer.write_all(&[b"\n\n===> ",config_manager::TIMEOUT.as_bytes(),b"\n"].concat())?;
+        writer.write_all(timeout.to_string().as_bytes())?;
+    }
+    if let Some(file_timeout) = &config_builder.file_timeout {
+        writer.write_all(&[b"\n\n===> ",config_manager::FILE_TIMEOUT.as_bytes(),b"\n"].concat())?;
+        writer.write_all(file_timeout.to_string().as_bytes())?;
+    }
+    if let Some(max_open_files) = &config_builder.max_open_files {
+        writer.write_all(&[b"\n\n===> ",config_manager::MAX_OPEN_FILES.as_bytes(),b"\n"].concat())?;
+        writer.write_all(max_open_files.to_string().as_bytes())?;
+    }
+    if let Some(strict_encoding) = &config_builder.strict_encoding {
+        writer.write_all(&[b"\n\n===> ",config_manager::STRICT_ENCODING.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *strict_encoding {b"yes"} else {b"no"})?;
+    }
+    if let Some(typical_markers) = &config_builder.typical_markers {
+        writer.write_all(&[b"\n\n===> ",config_manager::TYPICAL_MARKERS.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *typical_markers {b"yes"} else {b"no"})?;
+    }
+    if let Some(keyword_density) = &config_builder.keyword_density {
+        writer.write_all(&[b"\n\n===> ",config_manager::KEYWORD_DENSITY.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *keyword_density {b"yes"} else {b"no"})?;
+    }
+    if let Some(keyword_categories) = &config_builder.keyword_categories {
+        writer.write_all(&[b"\n\n===> ",config_manager::KEYWORD_CATEGORIES.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *keyword_categories {b"yes"} else {b"no"})?;
+    }
+    if let Some(skip_build_outputs) = &config_builder.skip_build_outputs {
+        writer.write_all(&[b"\n\n===> ",config_manager::SKIP_BUILD_OUTPUTS.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *skip_build_outputs {b"yes"} else {b"no"})?;
+    }
+    if let Some(large_file_threshold) = &config_builder.large_file_threshold {
+        writer.write_all(&[b"\n\n===> ",config_manager::LARGE_FILE_THRESHOLD.as_bytes(),b"\n"].concat())?;
+        writer.write_all(large_file_threshold.to_string().as_bytes())?;
+    }
+    if let Some(split_threshold) = &config_builder.split_threshold {
+        writer.write_all(&[b"\n\n===> ",config_manager::SPLIT_THRESHOLD.as_bytes(),b"\n"].concat())?;
+        writer.write_all(split_threshold.to_string().as_bytes())?;
+    }
+    if let Some(max_line_length) = &config_builder.max_line_length {
+        writer.write_all(&[b"\n\n===> ",config_manager::MAX_LINE_LENGTH.as_bytes(),b"\n"].concat())?;
+        writer.write_all(max_line_length.to_string().as_bytes())?;
+    }
+    if let Some(prefer_shebangs) = &config_builder.prefer_shebangs {
+        writer.write_all(&[b"\n\n===> ",config_manager::PREFER_SHEBANGS.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *prefer_shebangs {b"yes"} else {b"no"})?;
+    }
+    if let Some(ignore_empty_files) = &config_builder.ignore_empty_files {
+        writer.write_all(&[b"\n\n===> ",config_manager::IGNORE_EMPTY_FILES.as_bytes(),b"\n"].concat())?;
+        writer.write_all(if *ignore_empty_files {b"yes"} else {b"no"})?;
+    }
     if let Some(log) = &config_builder.log {
         writer.write(&[b"\n\n===> ",config_manager::LOG.as_bytes(),b"\n"].concat());
         if log.should_log {
@@ -385,9 +1396,21 @@ pub fn save_existing_commands_from_config_builder_to_file(config_path: Option<St
         writer.write(&[b"\n\n===> ",config_manager::COMPRARE_LEVEL.as_bytes(),b"\n"].concat());
         writer.write(compare_level.to_string().as_bytes());
     }
+    if let Some(restrict_to) = &config_builder.restrict_to {
+        writer.write_all(&[b"\n\n===> ",config_manager::RESTRICT_TO.as_bytes(),b"\n"].concat())?;
+        writer.write_all(restrict_to.as_bytes())?;
+    }
+    if let Some(lang_scopes) = &config_builder.lang_scopes {
+        writer.write_all(&[b"\n\n===> ",config_manager::LANG_SCOPE.as_bytes(),b"\n"].concat())?;
+        let rules = lang_scopes.iter().map(|x| format!("{}: {}", x.language, x.dirs.join(", "))).collect::<Vec<_>>();
+        writer.write_all(rules.join("\n").as_bytes())?;
+    }
+    if let Some(langs_dir) = &config_builder.langs_dir {
+        writer.write_all(&[b"\n\n===> ",config_manager::LANGS_DIR.as_bytes(),b"\n"].concat())?;
+        writer.write_all(langs_dir.as_bytes())?;
+    }
 
     writer.write(b"\n");    
-    writer.flush();
 
     Ok(())
 }
@@ -437,7 +1460,176 @@ fn write_current_log(writer: &mut BufWriter<File>, config: &Configuration, datet
 }
 
 
-fn read_bool_value_from_file(reader: &mut BufReader<File>, mut buf: &mut String) -> Option<bool> {
+// ----------------------------------- Report handling ------------------------------------------
+
+const REPORT_CHECKSUM_SECTION : &str = "===> checksum\n";
+const SHA256_PREFIX           : &str = "SHA-256: ";
+const HMAC_SHA256_PREFIX      : &str = "HMAC-SHA256: ";
+
+// The report is serialized in a canonical way (sorted languages and keywords, '\n' line endings), so that
+// its checksum only depends on the stats.
+pub fn serialize_report(content_info_map: &HashMap<String, LanguageContentInfo>, languages_metadata_map: &HashMap<String, LanguageMetadata>,
+        final_stats: &FinalStats, report_sections: &[ReportSection], datetime_now: &DateTime<Local>, config: &Configuration) -> String
+{
+    let mut report = String::with_capacity(1000);
+    report += &format!("Mezura report {}\n", config.version);
+    report += &format!("{}\n", datetime_now.format("%Y-%m-%d %H:%M:%S %z"));
+    report += "Configuration:\n";
+    report += &format!("    dirs: {}\n", config.dirs.iter().map(|x| config.path_style.format(x)).collect::<Vec<_>>().join(","));
+    report += &format!("    exclude: {}\n", config.exclude_dirs.join(","));
+    report += &format!("    languages: {}\n", config.languages_of_interest.join(","));
+    report += &format!("    braces-as-code: {}\n", if config.braces_as_code {"yes"} else {"no"});
+    report += &format!("    search-in-dotted: {}\n", if config.should_search_in_dotted {"yes"} else {"no"});
+    // Only when it is used, so that the reports without it stay the same
+    if let Some(revision) = &config.changed_since {
+        report += &format!("    changed-since: {} (up to the working tree)\n", revision);
+    }
+    report += "Stats:\n";
+    report += &format!("    Files: {}\n", final_stats.files);
+    report += &format!("    Lines: {}\n", final_stats.lines);
+    report += &format!("        Code: {}\n", final_stats.code_lines);
+    report += &format!("        Extra: {}\n", final_stats.extra_lines);
+    report += &format!("        Docs: {}\n", content_info_map.values().map(|x| x.doc_lines).sum::<usize>());
+    report += &format!("    Total Size: {}\n", final_stats.bytes_size);
+    report += &format!("        Average Size: {}\n", final_stats.bytes_average_size);
+
+    let mut language_names = languages_metadata_map.keys().collect::<Vec<_>>();
+    language_names.sort();
+    for name in language_names {
+        let metadata = &languages_metadata_map[name];
+        let content_info = &content_info_map[name];
+        report += &format!("{}:\n", name);
+        report += &format!("    Files: {}\n", metadata.files);
+        report += &format!("    Lines: {}\n", content_info.lines);
+        report += &format!("        Code: {}\n", content_info.code_lines);
+        report += &format!("        Extra: {}\n", content_info.lines - content_info.code_lines);
+        report += &format!("        Docs: {}\n", content_info.doc_lines);
+        report += &format!("    Total Size: {}\n", metadata.bytes);
+
+        for (keyword, occurences) in content_info.keyword_occurences.iter() {
+            report += &format!("    {}: {}\n", keyword, occurences);
+        }
+    }
+
+    for section in report_sections {
+        report += &format!("Section {}:\n", section.title);
+        for (key, value) in section.entries.iter() {
+            report += &format!("    {}: {}\n", key, value);
+        }
+    }
+
+    report
+}
+
+// The paths of '--files-from', one per line, from the standard input if the source is '-'. The empty lines are skipped
+pub fn read_file_list(source: &str) -> io::Result<Vec<String>> {
+    let contents = if source == "-" {io::read_to_string(io::stdin())?} else {fs::read_to_string(source)?};
+    Ok(contents.lines().filter_map(utils::get_trimmed_if_not_empty).collect())
+}
+
+// The key is read from a file, so that it doesn't show up in the arguments of the process or the history of the shell.
+// The line ending of the file is not part of the key
+pub fn read_sign_key(path: &str) -> io::Result<String> {
+    let key = fs::read_to_string(path)?.trim_end_matches(['\n', '\r']).to_owned();
+    if key.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "the key file is empty"));
+    }
+    Ok(key)
+}
+
+pub fn write_report(path: &str, report: &str, sign_key: &Option<String>) -> io::Result<()> {
+    let mut writer = BufWriter::new(fs::OpenOptions::new().write(true).create(true).truncate(true).open(path)?);
+
+    writer.write_all(report.as_bytes())?;
+    writer.write_all(b"\n")?;
+    writer.write_all(REPORT_CHECKSUM_SECTION.as_bytes())?;
+    writer.write_all(format!("{}{}\n", SHA256_PREFIX, hashing::to_hex(&hashing::sha256(report.as_bytes()))).as_bytes())?;
+    if let Some(key) = sign_key {
+        let signature = hashing::hmac_sha256(key.as_bytes(), report.as_bytes());
+        writer.write_all(format!("{}{}\n", HMAC_SHA256_PREFIX, hashing::to_hex(&signature)).as_bytes())?;
+    }
+    writer.flush()
+}
+
+// Returns whether the signature was verified too, which only happens if a key is provided
+pub fn verify_report(path: &str, sign_key: Option<&str>) -> Result<bool, ReportVerificationError> {
+    let contents = match fs::read_to_string(path) {
+        Ok(x) => x,
+        Err(_) => return Err(ReportVerificationError::FileNotFound(path.to_owned()))
+    };
+    let (report, checksum_section) = match contents.rfind(&(String::from("\n") + REPORT_CHECKSUM_SECTION)) {
+        Some(i) => (&contents[..i], &contents[i+1+REPORT_CHECKSUM_SECTION.len()..]),
+        None => return Err(ReportVerificationError::MissingChecksum)
+    };
+
+    let find_value = |prefix: &str| checksum_section.lines().find_map(|x| x.strip_prefix(prefix)).map(|x| x.trim().to_owned());
+    match find_value(SHA256_PREFIX) {
+        Some(x) => if x != hashing::to_hex(&hashing::sha256(report.as_bytes())) {return Err(ReportVerificationError::ChecksumMismatch)},
+        None => return Err(ReportVerificationError::MissingChecksum)
+    }
+
+    if let Some(key) = sign_key {
+        match find_value(HMAC_SHA256_PREFIX) {
+            Some(x) => if !hashing::from_hex(&x).is_some_and(|x| hashing::verify_hmac_sha256(key.as_bytes(), report.as_bytes(), &x)) {
+                return Err(ReportVerificationError::SignatureMismatch)
+            },
+            None => return Err(ReportVerificationError::MissingSignature)
+        }
+    }
+
+    Ok(sign_key.is_some())
+}
+
+// The totals and the stats of each language of a report, e.g. to be used as the baseline of '--pr-comment'
+#[derive(Debug, PartialEq)]
+pub struct ReportStats {
+    pub total: FinalStats,
+    pub languages: HashMap<String, FinalStats>
+}
+
+// Reads back the stats of a report written by 'serialize_report'. The configuration, the keywords and the sections are skipped.
+pub fn parse_report_stats(contents: &str) -> Option<ReportStats> {
+    if !contents.starts_with("Mezura report") {
+        return None;
+    }
+
+    // files, lines, code lines, bytes
+    let mut blocks : Vec<(String, [usize; 4])> = Vec::new();
+    for line in contents.lines().skip(2).filter(|x| !x.trim().is_empty()) {
+        if !line.starts_with(' ') {
+            if line.starts_with("Section ") || line.starts_with("===>") {
+                break;
+            }
+            blocks.push((line.strip_suffix(':')?.to_owned(), [0; 4]));
+            continue;
+        }
+        let (key, value) = match line.trim().split_once(':') {
+            Some(x) => x,
+            None => continue
+        };
+        let index = match key {
+            "Files" => 0,
+            "Lines" => 1,
+            "Code" => 2,
+            "Total Size" => 3,
+            _ => continue
+        };
+        if let Some((_, values)) = blocks.last_mut() {
+            values[index] = value.trim().parse().ok()?;
+        }
+    }
+
+    let make_stats = |[files, lines, code_lines, bytes]: [usize; 4]| {
+        FinalStats::new_extended(files, lines, code_lines, lines.saturating_sub(code_lines), bytes, bytes / files.max(1))
+    };
+    let total = make_stats(blocks.iter().find(|(name, _)| name == "Stats")?.1);
+    let languages = blocks.into_iter().filter(|(name, _)| name != "Configuration" && name != "Stats")
+            .map(|(name, values)| (name, make_stats(values))).collect();
+    Some(ReportStats {total, languages})
+}
+
+
+fn read_bool_value_from_file(reader: &mut impl BufRead, mut buf: &mut String) -> Option<bool> {
     buf.clear();
     reader.read_line(&mut buf);
     let buf = buf.trim();
@@ -453,7 +1645,7 @@ fn read_bool_value_from_file(reader: &mut BufReader<File>, mut buf: &mut String)
 }
 
 //Keep parsing new lines as relevant, until an empty one appears.
-fn read_lines_from_file_to_vec(reader: &mut BufReader<File>, mut buf: &mut String, parser_func: fn(&str) -> Vec<String>) -> Vec<String> {
+fn read_lines_from_file_to_vec(reader: &mut impl BufRead, mut buf: &mut String, parser_func: fn(&str) -> Vec<String>) -> Vec<String> {
     let mut vec = Vec::new();
     loop {
         buf.clear();
@@ -499,6 +1691,44 @@ impl Formatted for ConfigFileParseError {
     }
 }
 
+impl Formatted for ReportVerificationError {
+    fn formatted(&self) -> ColoredString {
+        match self {
+            Self::FileNotFound(x) => format!("Report '{}' not found.", x).red(),
+            Self::MissingChecksum => "The report doesn't contain a checksum.".red(),
+            Self::ChecksumMismatch => "The checksum doesn't match, the report has been modified.".red(),
+            Self::MissingSignature => "The report is not signed.".red(),
+            Self::SignatureMismatch => "The signature doesn't match, the report has been modified or the key is wrong.".red(),
+            Self::UnreadableKey(x) => format!("Unable to read a key from the file '{}'.", x).red()
+        }
+    }
+}
+
+impl LanguageDirParseError {
+    // The code that the program exits with, as listed in the README, since nothing can be counted without the languages
+    pub fn exit_code(&self) -> i32 {
+        5
+    }
+}
+
+impl ReportVerificationError {
+    // The code that the program exits with, as listed in the README, so that a verification step of a CI job fails
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::FileNotFound(_) | Self::UnreadableKey(_) => 5,
+            Self::MissingChecksum | Self::ChecksumMismatch | Self::MissingSignature | Self::SignatureMismatch => 4
+        }
+    }
+}
+
+impl_display_from_formatted!(LanguageDirParseError);
+impl_display_from_formatted!(ConfigFileParseError);
+impl_display_from_formatted!(ReportVerificationError);
+
+impl std::error::Error for LanguageDirParseError {}
+impl std::error::Error for ConfigFileParseError {}
+impl std::error::Error for ReportVerificationError {}
+
 
 mod my_reader {
     use std::{fs::File, io::{self, prelude::*}};
@@ -564,6 +1794,8 @@ mod my_reader {
 
 #[cfg(test)]
 mod tests {
+    use std::io::{self, Write};
+
     use crate::*;
 
     #[test]
@@ -607,11 +1839,172 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_read_config_profile() {
+        let profile = io_handler::parse_config_profile(Some("test"), Some(LOCAL_APP_PATHS.test_config_dir.clone()), "ci").unwrap().unwrap();
+        assert_eq!(Some(config_manager::Threads::new(4, 4)), profile.threads);
+        assert_eq!(Some(true), profile.no_visual);
+        assert_eq!(None, profile.dirs);
+        assert_eq!(None, profile.exclude_dirs);
+
+        let profile = io_handler::parse_config_profile(Some("test"), Some(LOCAL_APP_PATHS.test_config_dir.clone()), "quick").unwrap().unwrap();
+        assert_eq!(Some(vec!["target".to_owned()]), profile.exclude_dirs);
+        assert_eq!(None, profile.threads);
+        assert_eq!(None, io_handler::parse_config_profile(Some("test"), Some(LOCAL_APP_PATHS.test_config_dir.clone()), "audit").unwrap());
+    }
+
+    #[test]
+    fn test_config_to_string_and_back() {
+        let command = "./src,./tests --exclude target --languages rust,c --threads 2 3 --top-files 5 --style unicode \
+                --overview lines,files --show-skipped --disambiguate h,m --keyword-scope all --skip-minified --keyword-hotspots --complexity --prescan --split-threshold 64 --linguist-compare --exclude-submodules --export-treemap map.svg --summary --log run --compare 3 --fail-if code>100 --metrics ratio=extra/lines --lang-scope Rust: src --ext rs";
+        let config = config_manager::create_config_from_args(command).unwrap();
+
+        let contents = io_handler::config_builder_to_string(&config_manager::ConfigurationBuilder::from(&config));
+        assert!(contents.starts_with("===> dirs\n"));
+        assert_eq!(config, io_handler::parse_config_contents(&contents).build());
+
+        let default_config = Configuration::new(vec!["./".to_owned()]);
+        let contents = io_handler::config_builder_to_string(&config_manager::ConfigurationBuilder::from(&default_config));
+        assert_eq!(default_config, io_handler::parse_config_contents(&contents).build());
+    }
+
     #[test]
     fn test_parse_supported_languages_to_map() {
         let (lang_map, faulty_files) = io_handler::parse_supported_languages_to_map(
                 &(LOCAL_APP_PATHS.test_dir.clone() + "languages/")).unwrap();
         assert!(lang_map.len() == 2);
         assert!(faulty_files.len() == 1);
+        assert_eq!("c++.txt (at 'Extensions')", faulty_files[0]);
+        assert!(lang_map["Rust"].supports_nested_comments);
+        assert!(!lang_map["Java"].supports_nested_comments);
+        assert_eq!(vec![StringDelimiter {start: "r#\"".to_owned(), end: "\"#".to_owned(), is_raw: true, interpolation: None},
+                StringDelimiter {start: "r\"".to_owned(), end: "\"".to_owned(), is_raw: true, interpolation: None}], lang_map["Rust"].string_delimiters);
+        assert!(lang_map["Java"].string_delimiters.is_empty());
+        assert_eq!(vec!["if", "for", "while", "match", "&&", "||", "?"], lang_map["Rust"].branch_keywords);
+        assert!(lang_map["Java"].branch_keywords.is_empty());
+        assert_eq!(vec![";", "{"], lang_map["Rust"].statement_symbols);
+        assert!(lang_map["Java"].statement_symbols.is_empty());
+        assert_eq!(vec![Some("types".to_owned()), None, None], lang_map["Rust"].keywords.iter().map(|x| x.category.clone()).collect::<Vec<_>>());
+
+        let rust = io_handler::parse_string_to_language(fs::read_to_string(LOCAL_APP_PATHS.test_dir.clone() + "languages/Rust.txt").unwrap().into());
+        assert_eq!(lang_map["Rust"], rust);
+        assert_eq!(vec![("/*".to_owned(), "*/".to_owned())], rust.multiline_comment_symbols);
+
+        let ocaml = io_handler::parse_string_to_language("Language\nOCaml\n\nExtensions\nml\n\nString symbols\n\"\n\n\
+                Comment symbols\n\nMulti line comment start\n(* {|\nMulti line comment end\n*) |}\n\n".into());
+        assert_eq!(vec![("(*".to_owned(), "*)".to_owned()), ("{|".to_owned(), "|}".to_owned())], ocaml.multiline_comment_symbols);
+    }
+
+    #[test]
+    fn test_serialize_language_and_then_parse_it() -> std::io::Result<()> {
+        let dir = std::env::temp_dir().join("mezura-test-serialized-languages");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir)?;
+        let keyword = |name: &str, alias: &str, category: Option<&str>, case_insensitive: bool| Keyword {descriptive_name: name.to_owned(),
+                aliases: vec![alias.to_owned()], patterns: vec![], category: category.map(|x| x.to_owned()), case_insensitive,
+                scope: KeywordScope::Code};
+        let mut sql = Language::new("SQL".to_owned(), vec!["sql".to_owned()], vec!["'".to_owned()], vec!["--".to_owned()], vec![],
+                vec![keyword("selects", "SELECT", Some("queries"), true), keyword("tables", "TABLE", None, true), keyword("views", "VIEW", None, false)]);
+        sql.string_delimiters = vec![StringDelimiter {start: "$$".to_owned(), end: "$$".to_owned(), is_raw: true, interpolation: None},
+                StringDelimiter {start: "`".to_owned(), end: "`".to_owned(), is_raw: false, interpolation: Some(("${".to_owned(), "}".to_owned()))}];
+        sql.heredoc_symbols = vec!["<<-".to_owned(), "<<".to_owned()];
+        sql.supports_char_literals = true;
+        sql.category = FileCategory::Data;
+        sql.embedded_regions = vec![EmbeddedRegion {start: "BEGIN".to_owned(), end: "END".to_owned(), identifier: Some("plsql".to_owned())},
+                EmbeddedRegion {start: "```".to_owned(), end: "```".to_owned(), identifier: None}];
+
+        io_handler::serialize_language(&sql, dir.to_str().unwrap())?;
+        let (lang_map, faulty_files) = io_handler::parse_supported_languages_to_map(dir.to_str().unwrap()).unwrap();
+        assert!(faulty_files.is_empty());
+        assert_eq!(sql, lang_map["SQL"]);
+        assert_eq!(sql, io_handler::parse_string_to_language(fs::read_to_string(dir.join("SQL.txt"))?.into()));
+
+        fs::remove_dir_all(&dir)
+    }
+
+    #[test]
+    fn test_read_file_list() -> std::io::Result<()> {
+        let path = std::env::temp_dir().join("mezura-test-file-list.txt");
+        fs::write(&path, "src/lib.rs\n\n  src/main.rs \r\nmissing.rs")?;
+        assert_eq!(vec!["src/lib.rs", "src/main.rs", "missing.rs"], io_handler::read_file_list(path.to_str().unwrap())?);
+        fs::remove_file(&path)?;
+        assert!(io_handler::read_file_list(path.to_str().unwrap()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_language_references() {
+        let references = io_handler::parse_language_references("Some description\n\nRust   24  200\nC++ 27.5 220\nBad 1 a\n");
+        assert_eq!(hashmap!["rust".to_owned() => LanguageReference::new(24.0, 200), "c++".to_owned() => LanguageReference::new(27.5, 220)],
+                references);
+
+        let shipped_references = io_handler::parse_language_references(&String::from_utf8_lossy(&LANGUAGE_REFERENCES_BYTES));
+        assert!(shipped_references.contains_key("rust"));
+        assert!(shipped_references.contains_key("python"));
+    }
+
+    #[test]
+    fn test_write_and_verify_report() -> std::io::Result<()> {
+        let path = std::env::temp_dir().join("mezura-test-report.txt");
+        let path = path.to_str().unwrap();
+        let report = "Mezura report\nStats:\n    Files: 2\n";
+
+        io_handler::write_report(path, report, &Some("key".to_owned()))?;
+        assert_eq!(Ok(true), io_handler::verify_report(path, Some("key")));
+        assert_eq!(Ok(false), io_handler::verify_report(path, None));
+        assert_eq!(Err(io_handler::ReportVerificationError::SignatureMismatch), io_handler::verify_report(path, Some("other key")));
+
+        let tampered = fs::read_to_string(path)?.replace("Files: 2", "Files: 3");
+        fs::write(path, tampered)?;
+        assert_eq!(Err(io_handler::ReportVerificationError::ChecksumMismatch), io_handler::verify_report(path, Some("key")));
+
+        io_handler::write_report(path, report, &None)?;
+        assert_eq!(Err(io_handler::ReportVerificationError::MissingSignature), io_handler::verify_report(path, Some("key")));
+        fs::remove_file(path)?;
+
+        assert_eq!("my key", io_handler::read_sign_key("test_dir/keys/sign.key")?);
+        assert!(io_handler::read_sign_key("test_dir/keys/missing.key").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_out_file() -> std::io::Result<()> {
+        let dir = std::env::temp_dir().join("mezura-test-out");
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("a/b/results.txt");
+        let path = path.to_str().unwrap();
+
+        io_handler::create_out_file(path, false)?.write_all(b"first")?;
+        assert_eq!(io::ErrorKind::AlreadyExists, io_handler::create_out_file(path, false).unwrap_err().kind());
+        assert_eq!("first", fs::read_to_string(path)?);
+
+        io_handler::create_out_file(path, true)?.write_all(b"new")?;
+        assert_eq!("new", fs::read_to_string(path)?);
+
+        fs::remove_dir_all(&dir)
+    }
+
+    #[test]
+    fn test_serialize_report_with_sections() {
+        let content_info_map = hashmap!["Rust".to_owned() => LanguageContentInfo::new(10, 8, HashMap::new())];
+        let languages_metadata_map = hashmap!["Rust".to_owned() => LanguageMetadata::new(1, 300)];
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+        let sections = [ReportSection::new("Maintainability").with_entry("Score", "B").with_entry("Large files", 0)];
+
+        let report = io_handler::serialize_report(&content_info_map, &languages_metadata_map, &final_stats, &sections, &chrono::Local::now(),
+                &Configuration::new(vec![".".to_owned()]));
+        assert!(report.ends_with("Rust:\n    Files: 1\n    Lines: 10\n        Code: 8\n        Extra: 2\n        Docs: 0\n    Total Size: 300\n\
+                Section Maintainability:\n    Score: B\n    Large files: 0\n"));
+
+        let report_stats = io_handler::parse_report_stats(&(report + "\n===> checksum\n")).unwrap();
+        assert_eq!(FinalStats::new_extended(1, 10, 8, 2, 300, 300), report_stats.total);
+        assert_eq!(hashmap!["Rust".to_owned() => FinalStats::new_extended(1, 10, 8, 2, 300, 300)], report_stats.languages);
+        assert_eq!(None, io_handler::parse_report_stats("Files: 1"));
+
+        let mut config = Configuration::new(vec![".".to_owned()]);
+        config.set_changed_since(Some("main".to_owned()));
+        let report = io_handler::serialize_report(&content_info_map, &languages_metadata_map, &final_stats, &[], &chrono::Local::now(), &config);
+        assert!(report.contains("    search-in-dotted: no\n    changed-since: main (up to the working tree)\nStats:\n"));
+        assert_eq!(FinalStats::new_extended(1, 10, 8, 2, 300, 300), io_handler::parse_report_stats(&(report + "\n===> checksum\n")).unwrap().total);
     }
 }
@@ -0,0 +1,198 @@
+// A small json parser, used to read the jupyter notebooks and the json results of 'merge'. The numbers are read as f64.
+
+#[derive(Debug,PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    // In the order of the file
+    Object(Vec<(String, Json)>)
+}
+
+struct JsonParser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>
+}
+
+impl Json {
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(x) => x.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(x) => Some(x),
+            _ => None
+        }
+    }
+
+    // None unless it is a whole number that is not negative
+    pub fn as_usize(&self) -> Option<usize> {
+        match self {
+            Json::Number(x) if *x >= 0f64 && x.fract() == 0f64 => Some(*x as usize),
+            _ => None
+        }
+    }
+}
+
+pub fn parse(contents: &str) -> Result<Json, String> {
+    JsonParser::new(contents).parse_document()
+}
+
+impl<'a> JsonParser<'a> {
+    fn new(contents: &'a str) -> Self {
+        JsonParser {chars: contents.chars().peekable()}
+    }
+
+    fn parse_document(&mut self) -> Result<Json, String> {
+        let json = self.parse_value()?;
+        self.skip_whitespace();
+        match self.chars.next() {
+            None => Ok(json),
+            Some(x) => Err(format!("unexpected '{}' after the end", x))
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some('{') => self.parse_object(),
+            Some('[') => self.parse_array(),
+            Some('"') => self.parse_string().map(Json::String),
+            Some('t') => self.parse_literal("true", Json::Bool(true)),
+            Some('f') => self.parse_literal("false", Json::Bool(false)),
+            Some('n') => self.parse_literal("null", Json::Null),
+            Some(x) if *x == '-' || x.is_ascii_digit() => self.parse_number(),
+            Some(x) => Err(format!("unexpected '{}'", x)),
+            None => Err("unexpected end".to_owned())
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Json, String> {
+        self.chars.next();
+        let mut entries = Vec::new();
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&'}').is_some() {
+            return Ok(Json::Object(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            entries.push((key, self.parse_value()?));
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Json::Object(entries)),
+                _ => return Err("expected ',' or '}' in an object".to_owned())
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Json, String> {
+        self.chars.next();
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&']').is_some() {
+            return Ok(Json::Array(values));
+        }
+        loop {
+            values.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Json::Array(values)),
+                _ => return Err("expected ',' or ']' in an array".to_owned())
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut string = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(string),
+                Some('\\') => match self.chars.next() {
+                    Some('n') => string.push('\n'),
+                    Some('t') => string.push('\t'),
+                    Some('r') => string.push('\r'),
+                    Some('b') => string.push('\u{8}'),
+                    Some('f') => string.push('\u{c}'),
+                    Some('u') => string.push(self.parse_unicode_escape()?),
+                    Some(x) => string.push(x),
+                    None => return Err("unterminated string".to_owned())
+                },
+                Some(x) => string.push(x),
+                None => return Err("unterminated string".to_owned())
+            }
+        }
+    }
+
+    // After '\u', with the surrogate pairs of the characters outside the basic plane
+    fn parse_unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.parse_hex4()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return Ok(char::from_u32(high).unwrap_or(char::REPLACEMENT_CHARACTER));
+        }
+        if self.chars.next_if_eq(&'\\').is_none() || self.chars.next_if_eq(&'u').is_none() {
+            return Ok(char::REPLACEMENT_CHARACTER);
+        }
+        let low = self.parse_hex4()?;
+        Ok(char::from_u32(0x10000 + ((high - 0xD800) << 10) + low.wrapping_sub(0xDC00)).unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, String> {
+        let hex = (0..4).filter_map(|_| self.chars.next()).collect::<String>();
+        u32::from_str_radix(&hex, 16).map_err(|_| format!("invalid unicode escape '{}'", hex))
+    }
+
+    fn parse_number(&mut self) -> Result<Json, String> {
+        let mut number = String::new();
+        while let Some(x) = self.chars.next_if(|x| x.is_ascii_digit() || "+-.eE".contains(*x)) {
+            number.push(x);
+        }
+        number.parse().map(Json::Number).map_err(|_| format!("invalid number '{}'", number))
+    }
+
+    fn parse_literal(&mut self, literal: &str, json: Json) -> Result<Json, String> {
+        for expected in literal.chars() {
+            self.expect(expected)?;
+        }
+        Ok(json)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.chars.next() {
+            Some(x) if x == expected => Ok(()),
+            Some(x) => Err(format!("expected '{}' but found '{}'", expected, x)),
+            None => Err(format!("expected '{}' but the end was found", expected))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|x| x.is_whitespace()).is_some() {}
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Ok(Json::Array(vec![Json::Number(-1.5e3), Json::String("😀".to_owned()), Json::Bool(false), Json::Object(vec![])])),
+                parse(r#"[-1.5e3, "😀", false, {}]"#));
+        assert_eq!(Some(14), parse("{\"files\": 14}").unwrap().get("files").and_then(Json::as_usize));
+        assert_eq!(None, parse("-1").unwrap().as_usize());
+        assert_eq!(None, parse("1.5").unwrap().as_usize());
+        assert!(parse("{\"a\": }").is_err());
+        assert!(parse("[] []").is_err());
+    }
+}
@@ -0,0 +1,360 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+
+// The index of the catalog in use. It is set once at startup from '--lang'
+static CURRENT_CATALOG : AtomicUsize = AtomicUsize::new(0);
+
+static CATALOGS : [(&str, Labels); 4] = [("en", EN), ("de", DE), ("fr", FR), ("es", ES)];
+
+
+// The words of the printed results. The keys of the logs and the reports are not translated, since they are parsed back.
+#[derive(Debug)]
+pub struct Labels {
+    pub files: &'static str,
+    pub lines: &'static str,
+    pub code: &'static str,
+    pub extra: &'static str,
+    pub keywords: &'static str,
+    pub total: &'static str,
+    pub average: &'static str,
+    pub size: &'static str,
+    pub total_size: &'static str,
+    pub average_size: &'static str,
+    pub empty: &'static str,
+    pub whitespace_only: &'static str,
+    pub doc_lines: &'static str,
+    pub logical_lines: &'static str,
+    pub complexity_per_file: &'static str,
+    pub per_file: &'static str,
+    pub median: &'static str,
+    pub words: &'static str,
+    pub headings: &'static str,
+    pub code_blocks: &'static str,
+    pub language: &'static str,
+    pub analyzing_directories: &'static str,
+    pub parsing_files: &'static str,
+    pub details: &'static str,
+    pub keyword_co_occurrence: &'static str,
+    pub overview: &'static str,
+    pub progress: &'static str,
+    pub combined_total: &'static str,
+    pub documentation: &'static str,
+    pub directories: &'static str,
+    pub todos: &'static str,
+    pub keyword_categories: &'static str,
+    pub top_files: &'static str,
+    pub all_languages: &'static str,
+    pub most_lines: &'static str,
+    pub biggest_size: &'static str,
+    pub timings: &'static str,
+    pub by_extension: &'static str,
+    pub slowest_files: &'static str,
+    pub finished_languages: &'static str,
+    pub duplicate_files: &'static str,
+    pub copies: &'static str,
+    pub wasted: &'static str,
+    pub groups: &'static str,
+    pub clones: &'static str,
+    pub code_lines: &'static str,
+    pub lines_over: &'static str,
+    pub longest: &'static str,
+    pub performance: &'static str,
+    pub discovery: &'static str,
+    pub parsing: &'static str,
+    pub speed: &'static str,
+    pub threads: &'static str,
+    pub lines_by_category: &'static str,
+    pub warnings: &'static str,
+    pub share: &'static str,
+    pub hotspots: &'static str,
+    pub metrics: &'static str,
+    pub complexity: &'static str
+}
+
+const EN : Labels = Labels {
+    files: "files",
+    lines: "lines",
+    code: "code",
+    extra: "extra",
+    keywords: "keywords",
+    total: "total",
+    average: "average",
+    size: "Size",
+    total_size: "Total size",
+    average_size: "Average size",
+    empty: "empty",
+    whitespace_only: "whitespace-only",
+    doc_lines: "doc lines",
+    logical_lines: "logical lines",
+    complexity_per_file: "avg complexity per file",
+    per_file: "per file",
+    median: "median",
+    words: "words",
+    headings: "headings",
+    code_blocks: "code blocks",
+    language: "Language",
+    analyzing_directories: "Analyzing directories",
+    parsing_files: "Parsing files",
+    details: "Details",
+    keyword_co_occurrence: "Keyword co-occurrence (files containing both keywords)",
+    overview: "Overview",
+    progress: "Progress",
+    combined_total: "Combined total",
+    documentation: "Documentation",
+    directories: "Directories",
+    todos: "TODOs",
+    keyword_categories: "Keyword categories",
+    top_files: "Top files",
+    all_languages: "All languages",
+    most_lines: "Most lines",
+    biggest_size: "Biggest size",
+    timings: "Timings",
+    by_extension: "By extension",
+    slowest_files: "Slowest files",
+    finished_languages: "Finished languages",
+    duplicate_files: "Duplicate files",
+    copies: "copies",
+    wasted: "wasted",
+    groups: "groups",
+    clones: "Clones",
+    code_lines: "code lines",
+    lines_over: "lines over",
+    longest: "longest",
+    performance: "Performance",
+    discovery: "Discovery",
+    parsing: "Parsing",
+    speed: "Speed",
+    threads: "Threads",
+    lines_by_category: "lines by category",
+    warnings: "Warnings",
+    share: "share",
+    hotspots: "hotspots",
+    metrics: "metrics",
+    complexity: "complexity"
+};
+
+const DE : Labels = Labels {
+    files: "Dateien",
+    lines: "Zeilen",
+    code: "Code",
+    extra: "sonstige",
+    keywords: "Schlüsselwörter",
+    total: "gesamt",
+    average: "Durchschnitt",
+    size: "Größe",
+    total_size: "Gesamtgröße",
+    average_size: "Durchschnittsgröße",
+    empty: "leer",
+    whitespace_only: "nur Leerraum",
+    doc_lines: "Dokuzeilen",
+    logical_lines: "logische Zeilen",
+    complexity_per_file: "Ø Komplexität pro Datei",
+    per_file: "pro Datei",
+    median: "Median",
+    words: "Wörter",
+    headings: "Überschriften",
+    code_blocks: "Codeblöcke",
+    language: "Sprache",
+    analyzing_directories: "Verzeichnisse werden analysiert",
+    parsing_files: "Dateien werden gelesen",
+    details: "Details",
+    keyword_co_occurrence: "Gemeinsames Auftreten von Schlüsselwörtern (Dateien mit beiden Schlüsselwörtern)",
+    overview: "Übersicht",
+    progress: "Fortschritt",
+    combined_total: "Gesamtsumme",
+    documentation: "Dokumentation",
+    directories: "Verzeichnisse",
+    todos: "Offene Punkte",
+    keyword_categories: "Schlüsselwort-Kategorien",
+    top_files: "Größte Dateien",
+    all_languages: "Alle Sprachen",
+    most_lines: "Meiste Zeilen",
+    biggest_size: "Größter Umfang",
+    timings: "Zeiten",
+    by_extension: "Nach Endung",
+    slowest_files: "Langsamste Dateien",
+    finished_languages: "Fertige Sprachen",
+    duplicate_files: "Doppelte Dateien",
+    copies: "Kopien",
+    wasted: "verschwendet",
+    groups: "Gruppen",
+    clones: "Klone",
+    code_lines: "Codezeilen",
+    lines_over: "Zeilen über",
+    longest: "längste",
+    performance: "Leistung",
+    discovery: "Suche",
+    parsing: "Parsen",
+    speed: "Tempo",
+    threads: "Threads",
+    lines_by_category: "Zeilen nach Kategorie",
+    warnings: "Warnungen",
+    share: "Anteil",
+    hotspots: "Hotspots",
+    metrics: "Kennzahlen",
+    complexity: "Komplexität"
+};
+
+const FR : Labels = Labels {
+    files: "fichiers",
+    lines: "lignes",
+    code: "code",
+    extra: "autres",
+    keywords: "mots-clés",
+    total: "total",
+    average: "moyenne",
+    size: "Taille",
+    total_size: "Taille totale",
+    average_size: "Taille moyenne",
+    empty: "vides",
+    whitespace_only: "blancs uniquement",
+    doc_lines: "lignes de doc",
+    logical_lines: "lignes logiques",
+    complexity_per_file: "complexité moy. par fichier",
+    per_file: "par fichier",
+    median: "médiane",
+    words: "mots",
+    headings: "titres",
+    code_blocks: "blocs de code",
+    language: "Langage",
+    analyzing_directories: "Analyse des répertoires",
+    parsing_files: "Lecture des fichiers",
+    details: "Détails",
+    keyword_co_occurrence: "Cooccurrence des mots-clés (fichiers contenant les deux mots-clés)",
+    overview: "Vue d'ensemble",
+    progress: "Progression",
+    combined_total: "Total combiné",
+    documentation: "Documentation",
+    directories: "Répertoires",
+    todos: "Points en suspens",
+    keyword_categories: "Catégories de mots-clés",
+    top_files: "Plus gros fichiers",
+    all_languages: "Tous les langages",
+    most_lines: "Le plus de lignes",
+    biggest_size: "Plus grande taille",
+    timings: "Durées",
+    by_extension: "Par extension",
+    slowest_files: "Fichiers les plus lents",
+    finished_languages: "Langages terminés",
+    duplicate_files: "Fichiers en double",
+    copies: "copies",
+    wasted: "gaspillé",
+    groups: "groupes",
+    clones: "Clones",
+    code_lines: "lignes de code",
+    lines_over: "lignes de plus de",
+    longest: "la plus longue",
+    performance: "Performances",
+    discovery: "Recherche",
+    parsing: "Analyse",
+    speed: "Vitesse",
+    threads: "Threads",
+    lines_by_category: "lignes par catégorie",
+    warnings: "Avertissements",
+    share: "part",
+    hotspots: "points chauds",
+    metrics: "métriques",
+    complexity: "complexité"
+};
+
+const ES : Labels = Labels {
+    files: "archivos",
+    lines: "líneas",
+    code: "código",
+    extra: "otras",
+    keywords: "palabras clave",
+    total: "total",
+    average: "promedio",
+    size: "Tamaño",
+    total_size: "Tamaño total",
+    average_size: "Tamaño promedio",
+    empty: "vacíos",
+    whitespace_only: "solo espacios",
+    doc_lines: "líneas de doc",
+    logical_lines: "líneas lógicas",
+    complexity_per_file: "complejidad media por archivo",
+    per_file: "por archivo",
+    median: "mediana",
+    words: "palabras",
+    headings: "encabezados",
+    code_blocks: "bloques de código",
+    language: "Lenguaje",
+    analyzing_directories: "Analizando directorios",
+    parsing_files: "Leyendo archivos",
+    details: "Detalles",
+    keyword_co_occurrence: "Coocurrencia de palabras clave (archivos que contienen ambas palabras clave)",
+    overview: "Resumen",
+    progress: "Progreso",
+    combined_total: "Total combinado",
+    documentation: "Documentación",
+    directories: "Directorios",
+    todos: "Pendientes",
+    keyword_categories: "Categorías de palabras clave",
+    top_files: "Archivos más grandes",
+    all_languages: "Todos los lenguajes",
+    most_lines: "Más líneas",
+    biggest_size: "Mayor tamaño",
+    timings: "Tiempos",
+    by_extension: "Por extensión",
+    slowest_files: "Archivos más lentos",
+    finished_languages: "Lenguajes terminados",
+    duplicate_files: "Archivos duplicados",
+    copies: "copias",
+    wasted: "desperdiciado",
+    groups: "grupos",
+    clones: "Clones",
+    code_lines: "líneas de código",
+    lines_over: "líneas de más de",
+    longest: "la más larga",
+    performance: "Rendimiento",
+    discovery: "Búsqueda",
+    parsing: "Análisis",
+    speed: "Velocidad",
+    threads: "Hilos",
+    lines_by_category: "líneas por categoría",
+    warnings: "Advertencias",
+    share: "proporción",
+    hotspots: "puntos calientes",
+    metrics: "métricas",
+    complexity: "complejidad"
+};
+
+pub fn is_supported_language(code: &str) -> bool {
+    CATALOGS.iter().any(|(x, _)| *x == code)
+}
+
+// An unsupported code leaves the current catalog in place
+pub fn set_language(code: &str) {
+    if let Some(i) = CATALOGS.iter().position(|(x, _)| *x == code) {
+        CURRENT_CATALOG.store(i, Ordering::Relaxed);
+    }
+}
+
+pub fn get() -> &'static Labels {
+    &CATALOGS[CURRENT_CATALOG.load(Ordering::Relaxed)].1
+}
+
+// e.g. "total" -> "Total", for the titles of the rows
+pub fn capitalized(label: &str) -> String {
+    let mut chars = label.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalogs() {
+        assert!(is_supported_language("de"));
+        assert!(!is_supported_language("it"));
+        assert_eq!("Gesamt", capitalized(DE.total));
+        assert_eq!("Étapes", capitalized("étapes"));
+        // The tests print in english, so the catalog in use is not changed here
+        assert_eq!("files", get().files);
+    }
+}
@@ -4,6 +4,18 @@
 #![allow(dead_code)]
 #![allow(non_snake_case)]
 
+// The errors are printed with their colors through 'Formatted'. Their Display is the same message without the colors,
+// so that the users of the library can treat them as any other error. Defined before the modules, to be usable in them.
+macro_rules! impl_display_from_formatted {
+    ($error:ty) => {
+        impl std::fmt::Display for $error {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str(crate::Formatted::formatted(self).trim())
+            }
+        }
+    };
+}
+
 pub mod config_manager;
 pub mod io_handler;
 pub mod utils;
@@ -11,24 +23,73 @@ pub mod consumer;
 pub mod producer;
 pub mod message_printer;
 pub mod file_parser;
+pub mod regex;
+pub mod hashing;
+pub mod build_outputs;
+pub mod git;
+pub mod ignore;
+pub mod linguist;
+pub mod archive;
+pub mod notebook;
+pub mod embedded;
+pub mod remote;
+pub mod analyzers;
+pub mod labels;
+pub mod badge;
+pub mod budgets;
+pub mod derived_metrics;
+pub mod clones;
+pub mod observer;
+pub mod history;
+pub mod plugins;
+pub mod bench;
+pub mod baseline;
+pub mod single_file;
+pub mod warnings;
 
 mod result_printer;
+mod notifier;
+mod storage;
+mod server;
+mod pr_comment;
+mod progress_bar;
+mod streaming;
+mod export;
+mod event_log;
+mod tui;
+mod reporter;
+mod json;
+mod heuristics;
+mod treemap;
 
 pub use colored::{Colorize,ColoredString};
 pub use config_manager::Configuration;
 pub use utils::*;
-pub use domain::{Language, LanguageContentInfo, LanguageMetadata, FileStats, Keyword};
-
-pub type FaultyFilesListMut = Arc<Mutex<Vec<FaultyFileDetails>>>;
-pub type ContentInfoMapMut  = Arc<Mutex<HashMap<String,LanguageContentInfo>>>;
+pub use domain::{Language, LanguageBuilder, LanguageBuildError, FileCategory, LanguageContentInfo, LanguageMetadata, FileStats, Keyword, KeywordScope, LanguageReference, ProseStats, NotebookStats, DirStats, FaultyFile, FaultyReason, SkippedFile, SkippedReason, UnreadableDir, StringDelimiter, EmbeddedRegion,
+        TodoItem, KeywordOccurences, KeywordHotspots, PluginMetrics, LicenseCounts, UNLICENSED, AuthorCounts, FileHygiene, HygieneStats,
+        IndentationCounts, IndentationStats, MAX_INDENT_WIDTH,
+        LinesDistribution, FileSummary, FileHash, FileCodeLines};
+pub use regex::{Regex, RegexError};
+pub use analyzers::{Aggregate, ReportAnalyzer, ReportSection};
+pub use budgets::BudgetViolation;
+pub use result_printer::format_footer;
+pub use server::LanguagesReloader;
+pub use observer::{AnalysisEvent, AnalysisObserver, AnalysisPhase, NoObserver, ObserverRef, SkipReason};
+pub use plugins::{FileContext, FileMetric, LineContext, LineMetric, MetricPlugin, PluginsRef};
+pub use export::MergeError;
+
+pub type FaultyFilesListMut = Arc<Mutex<Vec<FaultyFile>>>;
 pub type MetadataMapMut     = Arc<Mutex<HashMap<String,LanguageMetadata>>>;
+pub type VisitedDirsMut     = Arc<Mutex<HashSet<PathBuf>>>;
+pub type DirStatsMapMut     = Arc<Mutex<HashMap<PathBuf,DirStats>>>;
+pub type ParseTimingsMut    = Arc<Mutex<ParseTimings>>;
 
 use lazy_static::lazy_static;
 use directories::{BaseDirs,ProjectDirs};
-use crossbeam_deque::{Worker,Injector};
+use crossbeam_deque::{Worker,Injector,Steal};
 use chrono::{DateTime, Local};
-use std::{collections::HashMap, fs::{self, File}, io::Read, path::{Path, PathBuf}, sync::atomic::{AtomicBool, Ordering}, time::{Duration, Instant}};
-use std::{sync::{Arc, Mutex}, thread::JoinHandle};
+use std::{collections::{BTreeMap, HashMap, HashSet}, fs::{self, File}, io::{IsTerminal, Read, Write}, path::{Path, PathBuf}, time::{Duration, Instant}};
+use std::{sync::{Arc, Condvar, Mutex, mpsc, atomic::{AtomicBool, AtomicUsize, Ordering}}, thread::JoinHandle};
 
 
 pub const APP_NAME : &str = "mezura";
@@ -37,129 +98,712 @@ pub const CONFIG_DIR_NAME : &str = "config";
 pub const LOGS_DIR_NAME : &str = "logs";
 pub const TEST_DIR_NAME : &str = "test_dir";
 pub const DEFAULT_CONFIG_NAME : &str = "default.txt";
+const SHEBANG_MAX_LEN : usize = 128;
+// A rough estimate of the memory that a queued file takes, along with its path and language name
+const QUEUED_FILE_MEMORY_ESTIMATE : usize = 512;
+// How many of the slowest files are kept with '--timings'
+const SLOWEST_FILES_NUM : usize = 10;
+// Another consumer is started for every this many files that are waiting to be parsed, up to the ones of '--threads'
+const QUEUED_FILES_PER_CONSUMER : usize = 32;
+// How often the queued files are checked while the producers search
+const CONSUMERS_CHECK_INTERVAL : Duration = Duration::from_millis(5);
 
 lazy_static! {
     pub static ref PERSISTENT_APP_PATHS : PersistentAppPaths = PersistentAppPaths::get();
     pub static ref LOCAL_APP_PATHS : LocalAppPaths = LocalAppPaths::get();
     pub static ref CHANGELOG_BYTES : &'static [u8] = include_bytes!("../Changelog");
+    pub static ref LANGUAGE_REFERENCES_BYTES : &'static [u8] = include_bytes!("../data/references.txt");
 }
 
 
+// The metrics of the parsing are returned however long it took, and are None only with '--list-only', which parses nothing
 pub fn run(config: Configuration, language_map: HashMap<String, Language>) -> Result<Option<Metrics>, ParseFilesError> {
+    run_with_analyzers(config, language_map, &[])
+}
+
+// Like 'run', but the analyzers get the final results and their sections are printed after them and added to the report
+pub fn run_with_analyzers(config: Configuration, language_map: HashMap<String, Language>, analyzers: &[Box<dyn ReportAnalyzer>])
+-> Result<Option<Metrics>, ParseFilesError>
+{
+    run_observed(config, language_map, analyzers, Arc::new(NoObserver), Arc::new(AtomicBool::new(false)), Arc::new(Vec::new()))
+}
+
+// Like 'run', but the plugins count their metrics while the files are parsed, which are printed in their own section
+// and added to the report
+pub fn run_with_plugins(config: Configuration, language_map: HashMap<String, Language>, plugins: Vec<Box<dyn MetricPlugin>>)
+-> Result<Option<Metrics>, ParseFilesError>
+{
+    run_observed(config, language_map, &[], Arc::new(NoObserver), Arc::new(AtomicBool::new(false)), Arc::new(plugins))
+}
+
+// Like 'run', but the observer is told about the phases of the run and about every file, as they happen
+pub fn run_with_observer(config: Configuration, language_map: HashMap<String, Language>, observer: impl AnalysisObserver + 'static)
+-> Result<Option<Metrics>, ParseFilesError>
+{
+    run_observed(config, language_map, &[], Arc::new(observer), Arc::new(AtomicBool::new(false)), Arc::new(Vec::new()))
+}
+
+// Like 'run_with_observer', but the run stops as soon as the flag is set (e.g. by another thread) with 'ParseFilesError::Cancelled'.
+// The files that are being parsed are finished first, and nothing is printed or saved after that.
+pub fn run_cancellable(config: Configuration, language_map: HashMap<String, Language>, observer: impl AnalysisObserver + 'static,
+        cancel_flag: Arc<AtomicBool>) -> Result<Option<Metrics>, ParseFilesError>
+{
+    run_observed(config, language_map, &[], Arc::new(observer), cancel_flag, Arc::new(Vec::new()))
+}
+
+fn run_observed(config: Configuration, mut language_map: HashMap<String, Language>, analyzers: &[Box<dyn ReportAnalyzer>], observer: ObserverRef,
+        cancel_flag: Arc<AtomicBool>, plugins: PluginsRef) -> Result<Option<Metrics>, ParseFilesError>
+{
+    utils::set_size_format(config.size_units, config.size_precision);
+    utils::set_number_format(config.number_format);
+    if let Some(path) = &config.out {
+        let file = io_handler::create_out_file(path, config.force).map_err(|x| ParseFilesError::UnavailableOutFile(path.clone(), x))?;
+        result_printer::set_out_file(file);
+    }
+    if config.list_only {
+        result_printer::print_discovered_files(&discover(config.clone(), language_map), &config);
+        return Ok(None);
+    }
+    if config.linguist_compare {
+        let rules = linguist::LinguistRules::read(&config.dirs);
+        let comparison = linguist::compare(&discover(config.clone(), language_map.clone()), &language_map, &rules);
+        result_printer::print_linguist_comparison(&comparison);
+        return Ok(None);
+    }
+    prepare_language_map(&config, &mut language_map)?;
+    let observer : ObserverRef = match &config.event_log {
+        Some(path) => {
+            let file = io_handler::create_out_file(path, true).map_err(|x| ParseFilesError::UnavailableEventLog(path.clone(), x))?;
+            Arc::new(event_log::EventLogObserver::new(file, observer))
+        },
+        None => observer
+    };
+
     let config = Arc::new(config);
-    let faulty_files_ref : FaultyFilesListMut  = Arc::new(Mutex::new(Vec::with_capacity(10)));
-    let finish_condition_ref = Arc::new(AtomicBool::new(false));
     let language_map_ref = Arc::new(language_map);
-    let languages_content_info_ref : ContentInfoMapMut = Arc::new(Mutex::new(make_language_stats(language_map_ref.clone())));
+    if config.isolate_roots && config.dirs.len() > 1 {
+        return run_isolated_roots(config, language_map_ref, analyzers, observer, cancel_flag, plugins);
+    }
+
+    // The chart of the lines is drawn on the terminal instead of the progress bar
+    let mut terminal = if config.tui {Some(tui::Terminal::enter().map_err(ParseFilesError::UnavailableTerminal)?)} else {None};
+    if terminal.is_none() {
+        reporter::info(&config, &format!("\n{}...",labels::get().analyzing_directories.underline().bold()));
+    }
+    // The finished languages are printed instead of the progress bar
+    let stream = config.stream && !config.is_quiet() && terminal.is_none();
+    let observer : ObserverRef = match &terminal {
+        Some(x) => Arc::new(tui::LiveChartObserver::new(observer, x.writer().map_err(ParseFilesError::UnavailableTerminal)?)),
+        None if stream => Arc::new(streaming::StreamingObserver::new(observer)),
+        None => observer
+    };
+    let mut analysis = analyze_files(config.clone(), language_map_ref.clone(), !stream && terminal.is_none(), observer.clone(), cancel_flag, plugins)?;
+    observer.on_event(&AnalysisEvent::PhaseChanged(AnalysisPhase::Summarizing));
+    let (final_stats, metrics) = summarize_analysis(&mut analysis, &config)?;
+
+    let existing_log_contents = get_specified_config_file_path(&config).and_then(|x| extract_file_contents(&x));
+    let datetime_now = chrono::Local::now();
+
+    remove_languages_with_0_files(&mut analysis.content_info_map, &mut analysis.languages_metadata_map);
+    let aggregate = Aggregate::new(&analysis.content_info_map, &analysis.languages_metadata_map, &analysis.faulty_files, &final_stats);
+    let mut report_sections = analyzers::run_analyzers(&analyzers::get_builtin_analyzers(&config), &aggregate);
+    report_sections.extend(analyzers::run_analyzers(analyzers, &aggregate));
+    // Taken before the overview puts the less relevant languages in 'others'
+    let file_summaries_map = result_printer::take_file_summaries(&mut analysis.content_info_map);
+    let file_hashes = result_printer::take_file_hashes(&mut analysis.content_info_map);
+    let file_code_lines = result_printer::take_file_code_lines(&mut analysis.content_info_map);
+    let keyword_categories_map = if config.keyword_categories && !config.summary {make_keyword_categories_map(&analysis.content_info_map, &language_map_ref)} else {BTreeMap::new()};
+    let dir_tree = make_dir_tree(&analysis.dirs_stats_map, &analysis.breakdown_roots);
+    let run_warnings = warnings::take();
+    // Also made for '--export-treemap', but only shown with '--dir-breakdown'
+    let shown_dir_tree = if config.dir_breakdown.is_some() {dir_tree.as_slice()} else {&[]};
+    if let Some(mut terminal) = terminal.take() {
+        tui::browse(&mut terminal, &analysis.content_info_map, &analysis.languages_metadata_map, &file_summaries_map, &config)
+                .map_err(ParseFilesError::UnavailableTerminal)?;
+    } else {
+        let file_categories_map = make_file_categories_map(&analysis, &language_map_ref);
+        result_printer::format_and_print_results(&mut analysis.content_info_map, &mut analysis.languages_metadata_map, &final_stats, 
+            &file_categories_map, shown_dir_tree, &run_warnings, &existing_log_contents, &datetime_now, &config);
+    }
+    if !config.is_quiet() && !config.tui {
+        result_printer::print_keyword_categories(&keyword_categories_map);
+        print_documentation_if_any(&analysis, &final_stats);
+        if !shown_dir_tree.is_empty() {
+            result_printer::print_dir_breakdown(shown_dir_tree, &config);
+        }
+        result_printer::print_report_sections(&report_sections);
+        result_printer::print_todos(&analysis.content_info_map, &config);
+        result_printer::print_top_files(&file_summaries_map, &config);
+        result_printer::print_duplicate_files(&file_hashes, &config);
+        result_printer::print_clones(&file_code_lines, &config);
+        if config.timings {
+            result_printer::print_parse_timings(&analysis.parse_timings, &config);
+        }
+        if config.stats {
+            result_printer::print_performance_stats(&metrics);
+        }
+    }
+    result_printer::print_warnings(&run_warnings, &config);
+
+    let violations = budgets::find_violations(&config.fail_if, &analysis.content_info_map, &analysis.languages_metadata_map, &final_stats);
+    save_and_send_results(&analysis.content_info_map, &analysis.languages_metadata_map, &final_stats, &report_sections, &violations,
+            &dir_tree, &existing_log_contents, &datetime_now, &config);
+    observer.on_event(&AnalysisEvent::PhaseChanged(AnalysisPhase::Finished));
+
+    if violations.is_empty() {Ok(Some(metrics))} else {Err(ParseFilesError::BudgetsExceeded(violations))}
+}
+
+// Combines the json results of '--output json' of the files into results of the same format, e.g. of the shards of a monorepo
+// that were analyzed on different CI workers
+pub fn merge_json_results(paths: &[String]) -> Result<String, MergeError> {
+    export::MergedResults::read(paths).map(|x| x.to_json())
+}
+
+// Keeps the configuration and the languages loaded and answers http requests on the address, until the process is stopped:
+// '/analyze?path=<dir>' analyzes the dir (or else the dirs of the configuration) and returns the results as json,
+// and '/metrics' analyzes the dirs of the configuration and returns them in the format of '--output prometheus'.
+pub fn serve(listen: &str, config: Configuration, language_map: HashMap<String, Language>) -> Result<(), ParseFilesError> {
+    serve_with_reloader(listen, config, language_map, None)
+}
+
+// Like 'serve', but the languages are made again by the reloader when the files of its dirs change, before the next request
+pub fn serve_with_reloader(listen: &str, mut config: Configuration, mut language_map: HashMap<String, Language>, reloader: Option<LanguagesReloader>)
+-> Result<(), ParseFilesError>
+{
+    prepare_language_map(&config, &mut language_map)?;
+    // Nothing is printed for the requests, and the errors are sent without colors
+    config.verbosity = config_manager::Verbosity::Quiet;
+    colored::control::set_override(false);
+    server::serve(listen, Arc::new(config), Arc::new(language_map), reloader)
+}
+
+fn prepare_language_map(config: &Configuration, language_map: &mut HashMap<String, Language>) -> Result<(), ParseFilesError> {
+    labels::set_language(&config.lang);
+    if let Some(scope) = config.keyword_scope {
+        for keyword in language_map.values_mut().flat_map(|x| x.keywords.iter_mut()) {
+            keyword.scope = scope;
+        }
+    }
+    if config.regex_keywords {
+        for language in language_map.values_mut() {
+            if let Err(x) = language.compile_keyword_patterns() {
+                return Err(ParseFilesError::InvalidKeywordPattern(x));
+            }
+        }
+    }
+
+    if config.docs {
+        add_documentation_languages(language_map);
+    }
+    Ok(())
+}
+
+// With '--isolate-roots' each root is analyzed concurrently by its own pipeline and gets its own section in the results,
+// followed by a combined total. The log, the report and the notification get the combined results.
+fn run_isolated_roots(config: Arc<Configuration>, language_map_ref: Arc<HashMap<String, Language>>, analyzers: &[Box<dyn ReportAnalyzer>],
+        observer: ObserverRef, cancel_flag: Arc<AtomicBool>, plugins: PluginsRef)
+-> Result<Option<Metrics>, ParseFilesError>
+{
+    reporter::info(&config, &format!("\n{}...",labels::get().analyzing_directories.underline().bold()));
+    let handles = config.dirs.iter().map(|root| {
+        let mut root_config = (*config).clone();
+        root_config.dirs = vec![root.to_owned()];
+        let (language_map_ref, observer, cancel_flag, plugins) = (language_map_ref.clone(), observer.clone(), cancel_flag.clone(), plugins.clone());
+        std::thread::spawn(move || analyze_files(Arc::new(root_config), language_map_ref, false, observer, cancel_flag, plugins))
+    }).collect::<Vec<_>>();
+    let analyses = handles.into_iter().map(|x| x.join().unwrap()).collect::<Vec<_>>();
+    // Not even the roots that were finished are printed
+    if cancel_flag.load(Ordering::Relaxed) {
+        return Err(ParseFilesError::Cancelled);
+    }
+    observer.on_event(&AnalysisEvent::PhaseChanged(AnalysisPhase::Summarizing));
+
+    let existing_log_contents = get_specified_config_file_path(&config).and_then(|x| extract_file_contents(&x));
+    let datetime_now = chrono::Local::now();
+
+    let mut combined_content_info_map = make_language_stats(language_map_ref.clone());
+    let mut combined_languages_metadata_map = make_language_metadata(&language_map_ref);
+    let mut combined_faulty_files = Vec::new();
+    let mut combined_parse_timings = ParseTimings::default();
+    let mut combined_dirs_stats_map = HashMap::new();
+    let mut combined_breakdown_roots = Vec::new();
+    let mut roots_final_stats = Vec::with_capacity(config.dirs.len());
+    let (mut relevant_files, mut max_parsing_duration, mut max_discovery_duration) = (0, Duration::ZERO, Duration::ZERO);
+    let mut first_error = None;
+    // Of all the roots, in the json results of each one and after the combined total
+    let run_warnings = warnings::take();
+    for (root, analysis) in config.dirs.iter().zip(analyses) {
+        let root = config.path_style.format(root);
+        reporter::info(&config, &format!("\n{} {}", "Root:".underline().bold(), root.underline().bold()));
+        let (mut analysis, final_stats) = match analysis.and_then(|mut x| summarize_analysis(&mut x, &config).map(|(final_stats, _)| (x, final_stats))) {
+            Ok(x) => x,
+            Err(x) => {
+                reporter::warning(&config, &x.formatted().to_string());
+                first_error.get_or_insert(x);
+                continue;
+            }
+        };
+
+        for (name, content_info) in analysis.content_info_map.iter() {
+            combined_content_info_map.get_mut(name).unwrap().add_content_info(content_info);
+        }
+        for (name, metadata) in analysis.languages_metadata_map.iter() {
+            combined_languages_metadata_map.get_mut(name).unwrap().add_metadata(metadata);
+        }
+        combined_faulty_files.append(&mut analysis.faulty_files);
+        combined_parse_timings.add(&analysis.parse_timings);
+        relevant_files += analysis.files_present.relevant_files;
+        max_parsing_duration = max_parsing_duration.max(analysis.parsing_duration);
+        max_discovery_duration = max_discovery_duration.max(analysis.discovery_duration);
+
+        // When quiet, only the combined total is printed
+        if !config.is_quiet() {
+            remove_languages_with_0_files(&mut analysis.content_info_map, &mut analysis.languages_metadata_map);
+            let file_categories_map = make_file_categories_map(&analysis, &language_map_ref);
+            let dir_tree = if config.dir_breakdown.is_some() {make_dir_tree(&analysis.dirs_stats_map, &analysis.breakdown_roots)} else {Vec::new()};
+            result_printer::format_and_print_results(&mut analysis.content_info_map, &mut analysis.languages_metadata_map, &final_stats, 
+                &file_categories_map, &dir_tree, &run_warnings, &None, &datetime_now, &config);
+            print_documentation_if_any(&analysis, &final_stats);
+            if !dir_tree.is_empty() {
+                result_printer::print_dir_breakdown(&dir_tree, &config);
+            }
+        }
+        combined_dirs_stats_map.extend(analysis.dirs_stats_map);
+        combined_breakdown_roots.extend(analysis.breakdown_roots);
+        roots_final_stats.push((root, final_stats));
+    }
+    if roots_final_stats.is_empty() {
+        return Err(first_error.unwrap());
+    }
+
+    let metrics = generate_metrics(max_parsing_duration, max_discovery_duration, relevant_files, &combined_content_info_map,
+            &combined_languages_metadata_map, &combined_parse_timings);
+    let final_stats = FinalStats::calculate(&combined_content_info_map, &combined_languages_metadata_map, config.ignore_empty_files);
+    remove_languages_with_0_files(&mut combined_content_info_map, &mut combined_languages_metadata_map);
+    let aggregate = Aggregate::new(&combined_content_info_map, &combined_languages_metadata_map, &combined_faulty_files, &final_stats);
+    let mut report_sections = analyzers::run_analyzers(&analyzers::get_builtin_analyzers(&config), &aggregate);
+    report_sections.extend(analyzers::run_analyzers(analyzers, &aggregate));
+    if config.is_quiet() {
+        result_printer::print_summary_line(&final_stats);
+    } else {
+        result_printer::print_combined_total(&roots_final_stats, &final_stats, &existing_log_contents, &datetime_now, &config);
+        result_printer::print_report_sections(&report_sections);
+        result_printer::print_todos(&combined_content_info_map, &config);
+        result_printer::print_top_files(&result_printer::take_file_summaries(&mut combined_content_info_map), &config);
+        result_printer::print_duplicate_files(&result_printer::take_file_hashes(&mut combined_content_info_map), &config);
+        result_printer::print_clones(&result_printer::take_file_code_lines(&mut combined_content_info_map), &config);
+        if config.timings {
+            result_printer::print_parse_timings(&combined_parse_timings, &config);
+        }
+        if config.stats {
+            result_printer::print_performance_stats(&metrics);
+        }
+    }
+    result_printer::print_warnings(&run_warnings, &config);
+
+    let violations = budgets::find_violations(&config.fail_if, &combined_content_info_map, &combined_languages_metadata_map, &final_stats);
+    save_and_send_results(&combined_content_info_map, &combined_languages_metadata_map, &final_stats, &report_sections, &violations,
+            &make_dir_tree(&combined_dirs_stats_map, &combined_breakdown_roots), &existing_log_contents, &datetime_now, &config);
+    observer.on_event(&AnalysisEvent::PhaseChanged(AnalysisPhase::Finished));
+
+    if violations.is_empty() {Ok(Some(metrics))} else {Err(ParseFilesError::BudgetsExceeded(violations))}
+}
+
+// Searches and parses the files of the configured dirs, printing a progress bar if asked and possible.
+// It is not printed when quiet, nor when verbose, where it would be mixed with the parsed files.
+fn analyze_files(config: Arc<Configuration>, language_map_ref: Arc<HashMap<String, Language>>, show_progress: bool, observer: ObserverRef,
+        cancel_flag: Arc<AtomicBool>, plugins: PluginsRef) -> Result<Analysis, ParseFilesError>
+{
+    observer.on_event(&AnalysisEvent::PhaseChanged(AnalysisPhase::Searching));
+    let (config, submodule_dirs) = find_submodules(config);
+    let faulty_files_ref : FaultyFilesListMut  = Arc::new(Mutex::new(Vec::with_capacity(10)));
+    let files_signal_ref = Arc::new(FilesSignal::with_cancel_flag(cancel_flag).with_timeout(config.timeout));
+    let resource_limits_ref = Arc::new(ResourceLimits::from_config(&config));
+    let visited_dirs_ref : VisitedDirsMut = Arc::new(Mutex::new(HashSet::new()));
+    // The changed files are tracked as well, so they take the place of the tracked ones
+    let tracked_files_ref = if let Some(revision) = &config.changed_since {
+        Arc::new(Some(git::find_changed_files(&config.dirs, revision).map_err(ParseFilesError::UnavailableGitChanges)?))
+    } else if config.git_tracked {
+        Arc::new(Some(git::find_tracked_files(&config.dirs).map_err(ParseFilesError::UnavailableGitIndex)?))
+    } else {
+        Arc::new(None)
+    };
+    let ignore_rules_ref = Arc::new(ignore::IgnoreRules::read(&config.dirs));
+    let dirs_stats_ref : DirStatsMapMut = Arc::new(Mutex::new(HashMap::new()));
+    let parse_timings_ref : ParseTimingsMut = Arc::new(Mutex::new(ParseTimings::default()));
     let global_languages_metadata_map = Arc::new(Mutex::new(make_language_metadata(&language_map_ref)));
     
-    let mut files_present = FilesPresent::default();
-    let producer_termination_states = Arc::new(Mutex::new(vec![false; config.threads.producers]));
+    let mut files_present = FilesPresent {submodule_dirs: submodule_dirs.clone(), ..FilesPresent::default()};
+    let submodule_dirs_ref = Arc::new(submodule_dirs);
     let files_injector = Arc::new(Injector::<ParsableFile>::new());
     let dirs_injector = Arc::new(Injector::<PathBuf>::new());
     calculate_single_file_stats_or_add_to_injector(&config, &dirs_injector, &files_injector, &mut files_present, 
-            &language_map_ref, &global_languages_metadata_map);
+            &language_map_ref, &global_languages_metadata_map, &*observer);
+    // When only files were given there is nothing to search
+    let producers = if dirs_injector.is_empty() {1} else {config.threads.producers};
+    let producer_termination_states = Arc::new(Mutex::new(vec![false; producers]));
 
     let files_stats = Arc::new(Mutex::new(files_present));
 
-    let mut producer_handles = Vec::with_capacity(config.threads.producers);
+    let mut producer_handles = Vec::with_capacity(producers);
     let mut consumer_handles = Vec::with_capacity(config.threads.consumers);
 
-    println!("\n{}...","Analyzing directories".underline().bold());
+    // The progress bar is only useful in a terminal, it would just pollute redirected output
+    let (progress_sender, mut progress_receiver) = if show_progress && config.verbosity == config_manager::Verbosity::Normal && std::io::stdout().is_terminal() {
+        let (sender, receiver) = mpsc::channel();
+        (Some(sender), Some(receiver))
+    } else {
+        (None, None)
+    };
+    // With '--prescan' the progress thread waits for the total bytes, which are known once all the files are found
+    let mut progress_handle = if config.prescan {None} else {
+        progress_receiver.take().map(|x| progress_bar::start_progress_thread(x, files_injector.clone(), None))
+    };
 
     let parsing_started_instant = Instant::now();
-    for i in 0..config.threads.producers {
+    for i in 0..producers {
         producer_handles.push(producer::start_producer_thread(i, files_injector.clone(), dirs_injector.clone(), Worker::new_fifo(),
-            global_languages_metadata_map.clone(), producer_termination_states.clone(),language_map_ref.clone(), config.clone(), files_stats.clone()));
+            global_languages_metadata_map.clone(), producer_termination_states.clone(),language_map_ref.clone(), config.clone(), files_stats.clone(),
+            files_signal_ref.clone(), resource_limits_ref.clone(), visited_dirs_ref.clone(), tracked_files_ref.clone(), ignore_rules_ref.clone(),
+            submodule_dirs_ref.clone(), observer.clone()));
     }
-    for i in 0..config.threads.consumers {
-        consumer_handles.push(consumer::start_parser_thread(i, files_injector.clone(), faulty_files_ref.clone(), finish_condition_ref.clone(),
-        languages_content_info_ref.clone(), language_map_ref.clone(), config.clone()));
+    let parser_context = consumer::ParserContext {files_injector: files_injector.clone(), faulty_files: faulty_files_ref.clone(),
+        files_signal: files_signal_ref.clone(), dirs_stats: dirs_stats_ref.clone(), parse_timings: parse_timings_ref.clone(),
+        language_map: language_map_ref.clone(), config: config.clone(), progress_sender: progress_sender.clone(),
+        resource_limits: resource_limits_ref.clone(), observer: observer.clone(), plugins: plugins.clone()};
+    let start_consumer = |id: usize| consumer::start_parser_thread(id, parser_context.clone());
+
+    if config.prescan {
+        for handle in producer_handles {
+            handle.join();
+        }
+        let total_bytes = queue_biggest_first(&files_injector);
+        reporter::info(&config, &format!("{} files to parse, {} in total.", with_seperators(files_injector.len()), utils::format_size(total_bytes)));
+        progress_handle = progress_receiver.take().map(|x| progress_bar::start_progress_thread(x, files_injector.clone(), Some(total_bytes)));
+        for i in 0..get_needed_consumers(files_injector.len(), 0, config.threads.consumers) {
+            consumer_handles.push(start_consumer(i));
+        }
+    } else {
+        // The consumers are started as the files are queued, so that a few files are not parsed by a lot of threads
+        consumer_handles.push(start_consumer(0));
+        loop {
+            // Checked first, so that the files that were queued last are taken into account
+            let producers_finished = producer_handles.iter().all(|x| x.is_finished());
+            let needed_consumers = get_needed_consumers(files_injector.len(), resource_limits_ref.max_queued_files, config.threads.consumers);
+            while consumer_handles.len() < needed_consumers {
+                consumer_handles.push(start_consumer(consumer_handles.len()));
+            }
+            if producers_finished {
+                break;
+            }
+            std::thread::sleep(CONSUMERS_CHECK_INTERVAL);
+        }
+        for handle in producer_handles {
+            handle.join();
+        }
+        //If there are a lot of files remaining after producers finish, it makes sense to start another consumer.
+        let len = files_injector.len();
+        if len > 1200 {
+            consumer_handles.push(start_consumer(consumer_handles.len()));
+        }
     }
+    let discovery_duration = parsing_started_instant.elapsed();
+    observer.on_event(&AnalysisEvent::PhaseChanged(AnalysisPhase::Parsing));
+    reporter::verbose(&config, &format!("Started {} parser threads.", consumer_handles.len()));
+    drop(progress_sender);
 
-    for handle in producer_handles {
+    files_signal_ref.finish();
+    let mut content_info_map = make_language_stats(language_map_ref.clone());
+    for handle in consumer_handles {
+        for (name, content_info) in handle.join().unwrap() {
+            content_info_map.get_mut(&name).unwrap().add_content_info(&content_info);
+        }
+    }
+    if let Some(handle) = progress_handle {
         handle.join();
     }
+    if files_signal_ref.was_stopped() {
+        return Err(if files_signal_ref.is_cancelled() {ParseFilesError::Cancelled} else {ParseFilesError::TimedOut(config.timeout)});
+    }
+    let parsing_duration = parsing_started_instant.elapsed();
+    reporter::verbose(&config, &format!("Searched and parsed in {} ms.", with_seperators(parsing_duration.as_millis() as usize)));
+
+    let mut files_present = std::mem::take(&mut *files_stats.lock().unwrap());
+    let mut faulty_files = std::mem::take(&mut *faulty_files_ref.lock().unwrap());
+    faulty_files.append(&mut files_present.faulty_archive_entries);
+    // In the order of their paths instead of the one that the threads happened to finish them in
+    faulty_files.sort_by(|a, b| a.path.cmp(&b.path));
+    files_present.skipped_files.sort_by(|a, b| a.path.cmp(&b.path));
+    files_present.unreadable_dirs.sort_by(|a, b| a.path.cmp(&b.path));
+    warnings::add_unreadable_dirs(&files_present.unreadable_dirs);
+    let languages_metadata_map = std::mem::take(&mut *global_languages_metadata_map.lock().unwrap());
+    let dirs_stats_map = std::mem::take(&mut *dirs_stats_ref.lock().unwrap());
+    let parse_timings = std::mem::take(&mut *parse_timings_ref.lock().unwrap());
+    let documentation_languages = language_map_ref.values().filter(|x| x.is_documentation()).map(|x| x.name.to_owned()).collect();
+    Ok(Analysis {files_present, faulty_files, content_info_map, languages_metadata_map, parsing_duration, discovery_duration, documentation_languages,
+            documentation_content_info_map: HashMap::new(), documentation_metadata_map: HashMap::new(), dirs_stats_map, parse_timings,
+            breakdown_roots: config.dirs.clone()})
+}
 
-    //If there are a lot of files remaining after producers finish, it makes sense to start another consumer.
-    let len = files_injector.len();
-    if len > 1200 {
-        consumer_handles.push(consumer::start_parser_thread(config.threads.consumers, files_injector, faulty_files_ref.clone(), finish_condition_ref.clone(),
-        languages_content_info_ref.clone(), language_map_ref.clone(), config.clone()));
+// The submodules of '--include-submodules' or '--exclude-submodules', which the search doesn't descend into from their repository.
+// The included ones are added to the provided directories of the returned configuration, so that they are searched as roots of their own.
+fn find_submodules(config: Arc<Configuration>) -> (Arc<Configuration>, Vec<PathBuf>) {
+    let include = match config.submodules {
+        Some(x) => x == config_manager::Submodules::Include,
+        None => return (config, Vec::new())
+    };
+    let submodule_dirs = git::find_submodule_dirs(&config.dirs, include);
+    if !include || submodule_dirs.is_empty() {
+        return (config, submodule_dirs);
     }
+    let mut submodules_config = (*config).clone();
+    for dir in submodule_dirs.iter().map(|x| x.to_string_lossy().into_owned()) {
+        if !submodules_config.dirs.contains(&dir) {
+            submodules_config.dirs.push(dir);
+        }
+    }
+    (Arc::new(submodules_config), submodule_dirs)
+}
 
-    finish_condition_ref.store(true,Ordering::Relaxed);
-    for handle in consumer_handles {
-        handle.join();
+// Requeues all the queued files from the biggest to the smallest, so that the big files are spread among the consumers
+// instead of being left to the ones that happen to be free last. Returns the total bytes of the files.
+fn queue_biggest_first(files_injector: &Injector<ParsableFile>) -> usize {
+    let mut files = Vec::with_capacity(files_injector.len());
+    while let Steal::Success(file) = files_injector.steal() {
+        let size = file.size() as usize;
+        files.push((size, file));
     }
-    let parsing_duration_millis = parsing_started_instant.elapsed().as_millis();
+    files.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.path.cmp(&b.1.path)));
+    let total_bytes = files.iter().map(|x| x.0).sum();
+    for (_, file) in files {
+        files_injector.push(file);
+    }
+    total_bytes
+}
+
+// One consumer for every QUEUED_FILES_PER_CONSUMER queued files, up to the maximum. All of them are needed when the queue
+// of '--max-memory' is full, since the producers are waiting for it to have space.
+fn get_needed_consumers(queued_files: usize, max_queued_files: usize, max_consumers: usize) -> usize {
+    if max_queued_files != 0 && queued_files >= max_queued_files {
+        return max_consumers;
+    }
+    (1 + queued_files / QUEUED_FILES_PER_CONSUMER).min(max_consumers)
+}
 
-    let file_stats_guard = files_stats.lock().unwrap();
+// Analyzes the dirs of a quiet configuration without printing, saving or sending anything
+fn analyze_silently(config: Arc<Configuration>, language_map_ref: Arc<HashMap<String, Language>>) -> Result<(Analysis, FinalStats), ParseFilesError> {
+    let mut analysis = analyze_files(config.clone(), language_map_ref, false, Arc::new(NoObserver), Arc::new(AtomicBool::new(false)), Arc::new(Vec::new()))?;
+    let (final_stats, _) = summarize_analysis(&mut analysis, &config)?;
+    remove_languages_with_0_files(&mut analysis.content_info_map, &mut analysis.languages_metadata_map);
+    Ok((analysis, final_stats))
+}
+
+// Prints what was found and which files were faulty, and calculates the final stats without the faulty files.
+// The documentation files are moved to their own maps, so that the final stats are about the code only.
+fn summarize_analysis(analysis: &mut Analysis, config: &Configuration) -> Result<(FinalStats, Metrics), ParseFilesError> {
+    let files_present = &analysis.files_present;
     let (total_files_num, relevant_files_num, excluded_files_num) = 
-            (file_stats_guard.total_files, file_stats_guard.relevant_files, file_stats_guard.excluded_files);
+            (files_present.total_files, files_present.relevant_files, files_present.excluded_files);
     if relevant_files_num == 0 {
-        return Err(ParseFilesError::NoRelevantFiles(get_activated_languages_as_str(&config)));
+        return Err(ParseFilesError::NoRelevantFiles(get_activated_languages_as_str(config)));
+    }
+    reporter::report_with(config, reporter::Level::Info, |out| {
+        writeln!(out, "{} files found. {} of interest. {} excluded.\n",with_seperators(total_files_num), with_seperators(relevant_files_num), with_seperators(excluded_files_num));
+        write_detection_conflicts(out, &files_present.detection_conflicts, config);
+        write_skipped_files(out, &files_present.skipped_files, config);
+        write_unreadable_dirs(out, &files_present.unreadable_dirs, config);
+        write_submodules(out, &files_present.submodule_dirs, config);
+
+        writeln!(out, "{}...",labels::get().parsing_files.underline().bold());
+
+        write_faulty_files_or_ok(out, &analysis.faulty_files, config);
+    });
+    if analysis.faulty_files.len() == relevant_files_num {
+        return Err(ParseFilesError::AllAreFaultyFiles);
+    }
+    if config.strict && !analysis.faulty_files.is_empty() {
+        return Err(ParseFilesError::FaultyFiles(analysis.faulty_files.len()));
     }
-    println!("{} files found. {} of interest. {} excluded.\n",with_seperators(total_files_num), with_seperators(relevant_files_num), with_seperators(excluded_files_num));
 
-    println!("{}...","Parsing files".underline().bold());
+    let mut reconciliation = Reconciliation::new(files_present, &analysis.languages_metadata_map, &analysis.faulty_files);
+    let unaccounted_files = remove_faulty_files_stats(&analysis.faulty_files, &mut analysis.languages_metadata_map);
+    if config.skip_generated {
+        let generated = remove_left_out_files_stats(&analysis.content_info_map, &mut analysis.languages_metadata_map, |x| &x.generated_stats);
+        reconciliation.generated_files = generated.files;
+        reconciliation.parsed_files += generated.files;
+        reconciliation.parsed_bytes += generated.bytes;
+    }
+    if config.skip_minified {
+        let minified = remove_left_out_files_stats(&analysis.content_info_map, &mut analysis.languages_metadata_map, |x| &x.minified_stats);
+        reconciliation.minified_files = minified.files;
+        reconciliation.parsed_files += minified.files;
+        reconciliation.parsed_bytes += minified.bytes;
+    }
+    for file in unaccounted_files {
+        reporter::info(config, &format!("Warning: the faulty file '{}' was not among the found files of {}.",
+                config.path_style.format(&file.path.to_string_lossy()), file.language_name).yellow().to_string());
+    }
 
-    print_faulty_files_or_ok(&faulty_files_ref, &config);
-    if faulty_files_ref.lock().unwrap().len() == relevant_files_num {
-        return Err(ParseFilesError::AllAreFaultyFiles);
+    let metrics = generate_metrics(analysis.parsing_duration, analysis.discovery_duration, relevant_files_num, &analysis.content_info_map,
+            &analysis.languages_metadata_map, &analysis.parse_timings);
+
+    for name in analysis.documentation_languages.iter() {
+        if let (Some(content_info), Some(metadata)) = (analysis.content_info_map.remove(name), analysis.languages_metadata_map.remove(name)) {
+            reconciliation.parsed_files += metadata.files;
+            reconciliation.parsed_bytes += metadata.bytes;
+            analysis.documentation_content_info_map.insert(name.to_owned(), content_info);
+            analysis.documentation_metadata_map.insert(name.to_owned(), metadata);
+        }
     }
 
-    let mut global_languages_metadata_map_guard = global_languages_metadata_map.lock();
-    let mut languages_metadata_map = global_languages_metadata_map_guard.as_deref_mut().unwrap();
-    
-    remove_faulty_files_stats(&faulty_files_ref, &mut languages_metadata_map, &language_map_ref);
+    let final_stats = FinalStats::calculate(&analysis.content_info_map, &analysis.languages_metadata_map, config.ignore_empty_files);
+    reconciliation.parsed_files += final_stats.files;
+    reconciliation.parsed_bytes += final_stats.bytes_size;
+    reporter::report_with(config, reporter::Level::Info, |out| write_reconciliation(out, &reconciliation));
+
+    Ok((final_stats, metrics))
+}
 
-    let mut content_info_map_guard = languages_content_info_ref.lock();
-    let mut content_info_map = content_info_map_guard.as_deref_mut().unwrap();
+#[allow(clippy::too_many_arguments)]
+fn save_and_send_results(content_info_map: &HashMap<String,LanguageContentInfo>, languages_metadata_map: &HashMap<String,LanguageMetadata>,
+        final_stats: &FinalStats, report_sections: &[ReportSection], violations: &[BudgetViolation], dir_tree: &[(usize, String, DirStats)],
+        existing_log_contents: &Option<String>, datetime_now: &DateTime<Local>, config: &Configuration)
+{
+    if config.log.should_log {
+        if let Some(path) = get_specified_config_file_path(config) {
+            io_handler::log_stats(&path, existing_log_contents, final_stats, datetime_now, config);
+        }
+    }
 
-    let metrics = generate_metrics_if_parsing_took_more_than_one_sec(parsing_duration_millis, relevant_files_num, content_info_map);
+    if let Some(path) = &config.report_file {
+        let report = io_handler::serialize_report(content_info_map, languages_metadata_map, final_stats, report_sections, datetime_now, config);
+        match io_handler::write_report(path, &report, &config.sign_key) {
+            Ok(_) => reporter::info(config, &format!("\nReport saved to '{}'.", path)),
+            Err(x) => reporter::warning(config, &format!("\n{}", format!("Unable to save the report: {}", x).yellow()))
+        }
+    }
 
-    let final_stats = FinalStats::calculate(content_info_map, languages_metadata_map);
-    let log_file_path = get_specified_config_file_path(&config);
-    let existing_log_contents = {
-        if let Some(path) = &log_file_path {
-            extract_file_contents(path)
-        } else {
-            None
+    if let Some(path) = &config.sqlite {
+        match storage::append_run(path, content_info_map, languages_metadata_map, final_stats, datetime_now, config) {
+            Ok(_) => reporter::info(config, &format!("\nRun appended to '{}'.", path)),
+            Err(x) => reporter::warning(config, &format!("\n{}", format!("Unable to append the run to the database: {}", x).yellow()))
         }
-    };
-    let datetime_now = chrono::Local::now();
+    }
 
-    remove_languages_with_0_files(content_info_map, languages_metadata_map);
-    result_printer::format_and_print_results(&mut content_info_map, &mut languages_metadata_map, &final_stats, 
-        &existing_log_contents, &datetime_now, &config);
+    if let Some(pr_comment) = &config.pr_comment {
+        match write_pr_comment(&pr_comment.baseline_report, &pr_comment.comment_file, violations, content_info_map, languages_metadata_map,
+                final_stats) {
+            Ok(_) => reporter::info(config, &format!("\nPull request comment saved to '{}'.", pr_comment.comment_file)),
+            Err(x) => reporter::warning(config, &format!("\n{}", format!("Unable to save the pull request comment: {}", x).yellow()))
+        }
+    }
 
-    if config.log.should_log {
-        if let Some(path) = log_file_path {
-            io_handler::log_stats(&path, &existing_log_contents, &final_stats, &datetime_now, &config);
+    if let Some(badge) = &config.badge {
+        match badge::write_badge(badge, content_info_map, languages_metadata_map, final_stats) {
+            Ok(_) => reporter::info(config, &format!("\nBadge saved to '{}'.", badge.path)),
+            Err(x) => reporter::warning(config, &format!("\n{}", format!("Unable to save the badge: {}", x).yellow()))
         }
     }
 
-    Ok(metrics)
+    if let Some(path) = &config.export_treemap {
+        match treemap::write_treemap(path, dir_tree) {
+            Ok(_) => reporter::info(config, &format!("\nTreemap saved to '{}'.", path)),
+            Err(x) => reporter::warning(config, &format!("\n{}", format!("Unable to save the treemap: {}", x).yellow()))
+        }
+    }
+
+    if let Some(target) = config.notify.as_deref().and_then(notifier::parse_target) {
+        match notifier::send_notification(&target, final_stats, existing_log_contents, config) {
+            Ok(_) => reporter::info(config, "\nNotification sent."),
+            Err(x) => reporter::warning(config, &format!("\n{}", format!("Unable to send the notification: {}", x).yellow()))
+        }
+    }
+}
+
+fn write_pr_comment(baseline_report: &str, comment_file: &str, violations: &[BudgetViolation], content_info_map: &HashMap<String,LanguageContentInfo>,
+        languages_metadata_map: &HashMap<String,LanguageMetadata>, final_stats: &FinalStats) -> Result<(),String>
+{
+    let contents = fs::read_to_string(baseline_report).map_err(|x| x.to_string())?;
+    let baseline = io_handler::parse_report_stats(&contents).ok_or_else(|| format!("'{}' is not a report", baseline_report))?;
+    let comment = pr_comment::render_pr_comment(&baseline, violations, content_info_map, languages_metadata_map, final_stats);
+    fs::write(comment_file, comment).map_err(|x| x.to_string())
+}
+
+fn print_documentation_if_any(analysis: &Analysis, final_stats: &FinalStats) {
+    if analysis.documentation_metadata_map.values().any(|x| x.files > 0) {
+        result_printer::print_documentation(&analysis.documentation_content_info_map, &analysis.documentation_metadata_map, final_stats);
+    }
+}
+
+// Runs only the file search of the 'run' function, applying the same filtering (excluded dirs, dotted dirs, languages,
+// scopes etc), so that other tools can process the relevant files themselves. The files are sorted by path.
+pub fn discover(config: Configuration, mut language_map: HashMap<String, Language>) -> Vec<DiscoveredFile> {
+    if config.docs {
+        add_documentation_languages(&mut language_map);
+    }
+    let (config, submodule_dirs) = find_submodules(Arc::new(config));
+    let language_map_ref = Arc::new(language_map);
+    let global_languages_metadata_map = Arc::new(Mutex::new(make_language_metadata(&language_map_ref)));
+
+    let mut files_present = FilesPresent::default();
+    let producer_termination_states = Arc::new(Mutex::new(vec![false; config.threads.producers]));
+    let files_injector = Arc::new(Injector::<ParsableFile>::new());
+    let dirs_injector = Arc::new(Injector::<PathBuf>::new());
+    calculate_single_file_stats_or_add_to_injector(&config, &dirs_injector, &files_injector, &mut files_present, 
+            &language_map_ref, &global_languages_metadata_map, &NoObserver);
+    let files_stats = Arc::new(Mutex::new(files_present));
+    // There are no consumers here to drain the queue, so it can't be throttled
+    let resource_limits_ref = Arc::new(ResourceLimits::new(config.max_open_files, 0));
+    let visited_dirs_ref : VisitedDirsMut = Arc::new(Mutex::new(HashSet::new()));
+    // If git can't list the files, none of them is considered tracked
+    let tracked_files_ref = Arc::new(match &config.changed_since {
+        Some(revision) => Some(git::find_changed_files(&config.dirs, revision).unwrap_or_default()),
+        None if config.git_tracked => Some(git::find_tracked_files(&config.dirs).unwrap_or_default()),
+        None => None
+    });
+    let ignore_rules_ref = Arc::new(ignore::IgnoreRules::read(&config.dirs));
+    let submodule_dirs_ref = Arc::new(submodule_dirs);
+
+    let mut producer_handles = Vec::with_capacity(config.threads.producers);
+    for i in 0..config.threads.producers {
+        producer_handles.push(producer::start_producer_thread(i, files_injector.clone(), dirs_injector.clone(), Worker::new_fifo(),
+            global_languages_metadata_map.clone(), producer_termination_states.clone(),language_map_ref.clone(), config.clone(), files_stats.clone(),
+            Arc::new(FilesSignal::new()), resource_limits_ref.clone(), visited_dirs_ref.clone(), tracked_files_ref.clone(), ignore_rules_ref.clone(),
+            submodule_dirs_ref.clone(), Arc::new(NoObserver)));
+    }
+    for handle in producer_handles {
+        handle.join();
+    }
+
+    let mut discovered_files = Vec::with_capacity(files_injector.len());
+    while let Steal::Success(file) = files_injector.steal() {
+        discovered_files.push(DiscoveredFile::from(file));
+    }
+    discovered_files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    discovered_files
 }
 
 //pub for integration tests
 pub fn calculate_single_file_stats_or_add_to_injector(config: &Configuration, dirs_injector: &Arc<Injector<PathBuf>>, files_injector: &Arc<Injector<ParsableFile>>,
-        files_present: &mut FilesPresent, languages: &Arc<HashMap<String,Language>>, languages_metadata_map: &MetadataMapMut)
+        files_present: &mut FilesPresent, languages: &Arc<HashMap<String,Language>>, languages_metadata_map: &MetadataMapMut, observer: &dyn AnalysisObserver)
 {
     config.dirs.iter().for_each(|dir| {
         let dir_path = Path::new(dir);
-        if dir_path.is_file() {
-            if let Some(x) = dir_path.extension() {
-                if let Some(extension) = x.to_str() {
-                    if let Some(lang_name) = find_lang_with_this_identifier(languages, extension) {
-                        languages_metadata_map.lock().unwrap().get_mut(&lang_name).unwrap().add_file_meta(
-                                dir_path.metadata().map_or(0, |m| m.len() as usize));
-                        files_injector.push(ParsableFile::new(dir_path.to_path_buf(),lang_name));
-                        files_present.total_files += 1;
-                        files_present.relevant_files += 1;
-                    }
-                }
+        if dir_path.is_file() && archive::is_archive(dir_path) {
+            add_archive_entries(dir_path, config, files_injector, files_present, languages, languages_metadata_map, observer);
+        } else if dir_path.is_file() {
+            let (lang_name, conflict) = find_lang_of_file(dir_path, languages, config);
+            files_present.detection_conflicts.extend(conflict);
+            if let Some(lang_name) = lang_name {
+                languages_metadata_map.lock().unwrap().get_mut(&lang_name).unwrap().add_file_meta(
+                        dir_path.metadata().map_or(0, |m| m.len() as usize));
+                observer.on_event(&AnalysisEvent::FileDiscovered {path: dir_path, language: &lang_name});
+                files_injector.push(ParsableFile::new(dir_path.to_path_buf(),lang_name));
+                files_present.total_files += 1;
+                files_present.relevant_files += 1;
+            } else {
+                observer.on_event(&AnalysisEvent::FileSkipped {path: dir_path, reason: SkipReason::Unsupported});
             }
         } else if dir_path.is_dir() {
             dirs_injector.push(dir_path.to_path_buf());
@@ -167,13 +811,46 @@ pub fn calculate_single_file_stats_or_add_to_injector(config: &Configuration, di
     })
 }
 
+// The files of the archive are counted as if it was a directory, with paths that start with the path of the archive
+fn add_archive_entries(path: &Path, config: &Configuration, files_injector: &Arc<Injector<ParsableFile>>, files_present: &mut FilesPresent,
+        languages: &Arc<HashMap<String,Language>>, languages_metadata_map: &MetadataMapMut, observer: &dyn AnalysisObserver)
+{
+    let get_entry_path = |name: &str| path.join(name.trim_start_matches("./"));
+    // Even without a budget, an archive or an entry of a zip is not inflated past a ratio of its compressed size
+    let memory_budget = if config.max_memory == 0 {usize::MAX} else {config.max_memory * 1024 * 1024};
+    let is_wanted = |name: &str| find_lang_of_file(&get_entry_path(name), languages, config).0.is_some();
+
+    let result = archive::read_entries(path, memory_budget, is_wanted, |entry| {
+        files_present.total_files += 1;
+        if entry.contents == archive::EntryContents::Unread {
+            return;
+        }
+        let entry_path = get_entry_path(&entry.name);
+        if let (Some(lang_name), _) = find_lang_of_file(&entry_path, languages, config) {
+            languages_metadata_map.lock().unwrap().get_mut(&lang_name).unwrap().add_file_meta(entry.size);
+            observer.on_event(&AnalysisEvent::FileDiscovered {path: &entry_path, language: &lang_name});
+            match entry.contents {
+                archive::EntryContents::Read(x) => files_injector.push(ParsableFile::from_archive_entry(entry_path, lang_name, x)),
+                _ => files_present.faulty_archive_entries.push(FaultyFile::new(entry_path, lang_name, FaultyReason::OverInflated,
+                        entry.size as u64))
+            }
+            files_present.relevant_files += 1;
+        }
+    });
+    if let Err(x) = result {
+        reporter::info(config, &format!("Unable to read the archive '{}': {}", path.display(), x).yellow().to_string());
+    }
+}
+
 //pub for integration tests
 pub fn remove_languages_with_0_files(content_info_map: &mut HashMap<String,LanguageContentInfo>,
     languages_metadata_map: &mut HashMap<String, LanguageMetadata>) 
 {
    let mut empty_languages = Vec::new();
    for element in languages_metadata_map.iter() {
-       if element.1.files == 0 {
+       // A language that is only in the regions of the files of other languages is kept
+       let has_embedded_lines = content_info_map.get(element.0).is_some_and(|x| x.embedded_stats.files > 0);
+       if element.1.files == 0 && !has_embedded_lines {
            empty_languages.push(element.0.to_owned());
        }
    }
@@ -184,67 +861,379 @@ pub fn remove_languages_with_0_files(content_info_map: &mut HashMap<String,Langu
    }
 }
 
-pub fn find_lang_with_this_identifier(languages: &Arc<HashMap<String,Language>>, wanted_identifier: &str) -> Option<String> {
-    for lang in languages.iter() {
-        if lang.1.extensions.iter().any(|x| x == wanted_identifier) {
-            return Some(lang.0.to_owned());
+// The identifier of a file is its extension. If '--detect-shebangs' is used, a file without an extension is identified
+// by its name if it is well-known, or else by its shebang line.
+pub fn get_file_identifier(path: &Path, config: &Configuration) -> Option<String> {
+    if let Some(x) = path.extension() {
+        return x.to_str().map(|x| x.to_owned());
+    }
+    if !config.detect_shebangs {
+        return None;
+    }
+
+    if let Some(x) = path.file_name().and_then(|x| x.to_str()).and_then(domain::identifier_from_file_name) {
+        return Some(x.to_owned());
+    }
+    utils::read_first_line(path, SHEBANG_MAX_LEN).and_then(|x| domain::identifier_from_shebang(&x)).map(|x| x.to_owned())
+}
+
+// With '--disambiguate', the contents of a file with one of its extensions may point to another language than the extension.
+// If '--detect-shebangs' is used, the shebang line of a file with an extension is checked too, and if it points to a
+// different language the conflict is returned. The shebang wins only if '--prefer-shebangs' is used and its language is supported.
+pub fn find_lang_of_file(path: &Path, languages: &Arc<HashMap<String,Language>>, config: &Configuration) -> (Option<String>, Option<DetectionConflict>) {
+    let lang_name = match get_file_identifier(path, config).and_then(|x| find_lang_with_this_identifier(languages, &x, config.match_case)) {
+        Some(x) => x,
+        // The notebooks are counted as the language of their kernel, unless a language claims them
+        None if notebook::is_notebook(path) => return (notebook::find_kernel_language(path, languages, config.match_case), None),
+        None => return (None, None)
+    };
+    let lang_name = match path.extension().and_then(|x| x.to_str()).filter(|x| config.disambiguate.iter().any(|y| y.eq_ignore_ascii_case(x))) {
+        Some(extension) => heuristics::find_language_by_content(path, extension, languages).unwrap_or(lang_name),
+        None => lang_name
+    };
+    if !config.detect_shebangs || path.extension().is_none() {
+        return (Some(lang_name), None);
+    }
+
+    let shebang_identifier = match utils::read_first_line(path, SHEBANG_MAX_LEN).and_then(|x| domain::identifier_from_shebang(&x)) {
+        Some(x) => x,
+        None => return (Some(lang_name), None)
+    };
+    if languages.get(&lang_name).unwrap().extensions.iter().any(|x| x == shebang_identifier) {
+        return (Some(lang_name), None);
+    }
+
+    let shebang_language = find_lang_with_this_identifier(languages, shebang_identifier, config.match_case);
+    let chosen_language = match &shebang_language {
+        Some(x) if config.prefer_shebangs => x.to_owned(),
+        _ => lang_name.to_owned()
+    };
+    let conflict = DetectionConflict {
+        path: path.to_path_buf(),
+        extension_language: lang_name,
+        shebang_identifier: shebang_identifier.to_owned(),
+        shebang_language,
+        chosen_language: chosen_language.to_owned()
+    };
+    (Some(chosen_language), Some(conflict))
+}
+
+// If more than one language claims the identifier, the first by name is chosen, so that it is the same on every run.
+// An exact match is preferred, otherwise the case is ignored unless '--match-case' is used, so that e.g. 'main.RS' is Rust.
+pub fn find_lang_with_this_identifier(languages: &Arc<HashMap<String,Language>>, wanted_identifier: &str, match_case: bool) -> Option<String> {
+    let find = |is_match: &dyn Fn(&String) -> bool| {
+        languages.iter().filter(|(_, lang)| lang.extensions.iter().any(is_match)).map(|(name, _)| name).min().cloned()
+    };
+    find(&|x| x == wanted_identifier).or_else(|| if match_case {None} else {find(&|x| x.eq_ignore_ascii_case(wanted_identifier))})
+}
+
+// Custom languages take precedence over the existing ones, both by name and by the extensions they claim.
+// An existing language that is left without any extensions is removed.
+// The directory that a file is counted under with '--dir-breakdown': the provided directory that it was found in,
+// followed by at most 'depth' of the directories that lead to the file. A file that was provided directly counts under its parent.
+pub fn get_breakdown_dir(path: &Path, dirs: &[String], depth: usize) -> PathBuf {
+    let parent = path.parent().unwrap_or(path);
+    match find_breakdown_root(parent, dirs) {
+        Some(root) => {
+            let mut dir = root.to_path_buf();
+            dir.extend(parent.strip_prefix(root).unwrap().components().take(depth));
+            dir
+        },
+        None => parent.to_path_buf()
+    }
+}
+
+// The most specific of the provided directories that contains the directory
+fn find_breakdown_root<'a>(dir: &Path, dirs: &'a [String]) -> Option<&'a Path> {
+    dirs.iter().map(Path::new).filter(|x| dir.starts_with(x)).max_by_key(|x| x.as_os_str().len())
+}
+
+// The rows of the '--dir-breakdown' table as (level, name, stats): every root followed by its directories, depth first
+// and the biggest (in lines) first. The stats of a directory include the ones of its subdirectories.
+pub fn make_dir_tree(dirs_stats_map: &HashMap<PathBuf,DirStats>, dirs: &[String]) -> Vec<(usize, String, DirStats)> {
+    let mut totals : HashMap<PathBuf,DirStats> = HashMap::new();
+    let mut roots = HashSet::new();
+    for (dir, stats) in dirs_stats_map.iter() {
+        let root = find_breakdown_root(dir, dirs).unwrap_or(dir);
+        for ancestor in dir.ancestors().take_while(|x| x.starts_with(root)) {
+            totals.entry(ancestor.to_path_buf()).or_default().add(stats);
         }
+        roots.insert(root.to_path_buf());
+    }
+
+    let mut rows = Vec::with_capacity(totals.len());
+    for root in sort_dirs_by_lines(roots.iter().collect(), &totals) {
+        add_dir_tree_rows(root, 0, &totals, &roots, &mut rows);
     }
-    None
+    rows
 }
 
+fn add_dir_tree_rows(dir: &Path, level: usize, totals: &HashMap<PathBuf,DirStats>, roots: &HashSet<PathBuf>,
+        rows: &mut Vec<(usize, String, DirStats)>)
+{
+    let name = match dir.file_name() {
+        Some(x) if level != 0 => x.to_string_lossy().into_owned(),
+        _ => dir.to_string_lossy().into_owned()
+    };
+    rows.push((level, name, totals[dir]));
 
-fn generate_metrics_if_parsing_took_more_than_one_sec(parsing_duration_millis: u128, relevant_files: usize,
-        content_info_map: &HashMap<String, LanguageContentInfo>) -> Option<Metrics> 
+    let children = totals.keys().filter(|x| x.parent() == Some(dir) && !roots.contains(*x)).collect();
+    for child in sort_dirs_by_lines(children, totals) {
+        add_dir_tree_rows(child, level + 1, totals, roots, rows);
+    }
+}
+
+// A directory of the rows of 'make_dir_tree' with its subdirectories, biggest first, for the outputs that are trees
+#[derive(Debug,PartialEq)]
+pub struct DirNode {
+    pub name: String,
+    pub stats: DirStats,
+    pub children: Vec<DirNode>
+}
+
+pub fn make_dir_nodes(dir_tree: &[(usize, String, DirStats)]) -> Vec<DirNode> {
+    add_dir_nodes(&mut dir_tree.iter().peekable(), 0)
+}
+
+// The rows of a level are followed by the ones of their children
+fn add_dir_nodes<'a>(rows: &mut std::iter::Peekable<impl Iterator<Item = &'a (usize, String, DirStats)>>, level: usize) -> Vec<DirNode> {
+    let mut nodes = Vec::new();
+    while let Some((_, name, stats)) = rows.next_if(|(x, _, _)| *x == level) {
+        let children = add_dir_nodes(rows, level + 1);
+        nodes.push(DirNode {name: name.to_owned(), stats: *stats, children});
+    }
+    nodes
+}
+
+fn sort_dirs_by_lines<'a>(mut dirs: Vec<&'a PathBuf>, totals: &HashMap<PathBuf,DirStats>) -> Vec<&'a PathBuf> {
+    dirs.sort_by(|a, b| totals[*b].lines.cmp(&totals[*a].lines).then(a.cmp(b)));
+    dirs
+}
+
+// The stats of the languages summed by their category, e.g. how many of the lines are code and how many config.
+// The documentation formats of '--docs' are included, as docs, though they are not part of the total.
+fn make_file_categories_map(analysis: &Analysis, language_map: &HashMap<String,Language>) -> BTreeMap<FileCategory,DirStats> {
+    let mut categories_map = BTreeMap::<FileCategory,DirStats>::new();
+    let content_info_maps = [(&analysis.content_info_map, &analysis.languages_metadata_map),
+            (&analysis.documentation_content_info_map, &analysis.documentation_metadata_map)];
+    for (content_info_map, metadata_map) in content_info_maps {
+        for (name, content_info) in content_info_map.iter() {
+            let (language, metadata) = match (language_map.get(name), metadata_map.get(name)) {
+                (Some(language), Some(metadata)) if metadata.files > 0 => (language, metadata),
+                _ => continue
+            };
+            categories_map.entry(language.category).or_default().add(&DirStats {files: metadata.files, lines: content_info.lines,
+                    code_lines: content_info.code_lines, bytes: metadata.bytes});
+        }
+    }
+    categories_map
+}
+
+// With '--docs' the documentation formats are parsed as prose. A format is left out if one of the languages
+// already has its name or one of its extensions.
+// The occurrences of the keywords of each category of the language files, per language, e.g. "types" -> {"C" -> 15, "Rust" -> 120}.
+// The keywords without a category and the languages without any occurrences are left out.
+pub fn make_keyword_categories_map(content_info_map: &HashMap<String,LanguageContentInfo>, language_map: &HashMap<String,Language>)
+        -> BTreeMap<String,BTreeMap<String,usize>>
 {
-    if parsing_duration_millis <= 1000 {
-        return None;
+    let mut categories_map = BTreeMap::<String,BTreeMap<String,usize>>::new();
+    for (name, content_info) in content_info_map {
+        let language = match language_map.get(name) {
+            Some(x) => x,
+            None => continue
+        };
+        for keyword in language.keywords.iter() {
+            let (category, occurrences) = match (&keyword.category, content_info.keyword_occurences.get(&keyword.descriptive_name)) {
+                (Some(category), Some(occurrences)) if *occurrences > 0 => (category, *occurrences),
+                _ => continue
+            };
+            *categories_map.entry(category.to_owned()).or_default().entry(name.to_owned()).or_insert(0) += occurrences;
+        }
+    }
+    categories_map
+}
+
+pub fn add_documentation_languages(language_map: &mut HashMap<String,Language>) {
+    for (name, extensions) in domain::DOCUMENTATION_FORMATS.iter() {
+        let is_claimed = language_map.values().any(|lang| lang.name == *name || lang.extensions.iter().any(|x| extensions.contains(&x.as_str())));
+        if !is_claimed {
+            let extensions = extensions.iter().map(|x| x.to_string()).collect();
+            let mut language = Language::new(name.to_string(), extensions, vec![], vec![], vec![], vec![]);
+            language.category = FileCategory::Docs;
+            if *name == "Markdown" {
+                language.embedded_regions = domain::MARKDOWN_FENCES.iter()
+                        .map(|x| EmbeddedRegion {start: x.to_string(), end: x.to_string(), identifier: None}).collect();
+            }
+            language_map.insert(name.to_string(), language);
+        }
+    }
+}
+
+// A custom language takes the extensions that it claims from the other languages. One with the name of an existing language
+// replaces its definition, but keeps the extensions of it that no custom language claims, e.g. 'h' of C when only 'c' is given.
+pub fn merge_custom_languages(language_map: &mut HashMap<String,Language>, custom_languages: HashMap<String,Language>) {
+    for custom_lang in custom_languages.values() {
+        for lang in language_map.values_mut() {
+            lang.extensions.retain(|x| !custom_lang.extensions.contains(x));
+        }
     }
+    language_map.retain(|_, lang| !lang.extensions.is_empty());
+    for (name, mut custom_lang) in custom_languages {
+        if let Some(lang) = language_map.remove(&name) {
+            custom_lang.extensions.extend(lang.extensions);
+        }
+        language_map.insert(name, custom_lang);
+    }
+}
+
+// Even for the fastest runs, since the speeds are calculated from the fractions of a second
+fn generate_metrics(parsing_duration: Duration, discovery_duration: Duration, relevant_files: usize,
+        content_info_map: &HashMap<String, LanguageContentInfo>, languages_metadata_map: &HashMap<String, LanguageMetadata>,
+        parse_timings: &ParseTimings) -> Metrics
+{
+    let total_lines = content_info_map.values().map(|x| x.lines).sum();
+    let total_bytes = languages_metadata_map.values().map(|x| x.bytes).sum();
+    let mut metrics = Metrics::from_durations(relevant_files, total_lines, total_bytes, vec![parsing_duration]);
+    metrics.discovery_duration = discovery_duration;
+    metrics.parse_duration = parsing_duration.saturating_sub(discovery_duration);
+    let mut parser_threads = parse_timings.parser_threads.clone();
+    parser_threads.sort_by_key(|x| x.0);
+    metrics.thread_utilizations = parser_threads.iter()
+            .map(|(_, busy, alive)| if alive.is_zero() {0f64} else {(busy.as_secs_f64() / alive.as_secs_f64()).min(1f64)}).collect();
+    metrics
+}
+
 
-    let duration_secs = parsing_duration_millis as f32/ 1000f32;
-    let mut total_lines = 0;
-    content_info_map.iter().for_each(|x| total_lines += x.1.lines);
-    let lines_per_sec = (total_lines as f32 / duration_secs) as usize;
-    let files_per_sec = (relevant_files as f32 / duration_secs) as usize;
+fn write_detection_conflicts(out: &mut dyn Write, conflicts: &[DetectionConflict], config: &Configuration) {
+    if conflicts.is_empty() {
+        return;
+    }
 
-    Some(
-        Metrics {
-            files_per_sec,
-            lines_per_sec
+    let mut conflicts = conflicts.iter().collect::<Vec<_>>();
+    conflicts.sort_by(|a, b| a.path.cmp(&b.path));
+    writeln!(out, "{} {}", conflicts.len().to_string().yellow(), "files have a shebang that disagrees with their extension:".yellow());
+    for c in conflicts {
+        let shebang_language = match &c.shebang_language {
+            Some(x) => x.to_owned(),
+            None => format!("'{}' (not supported)", c.shebang_identifier)
+        };
+        writeln!(out, "-- {}
+   extension: {}, shebang: {} -> counted as {}", config.path_style.format(&c.path.to_string_lossy()), c.extension_language, shebang_language, c.chosen_language);
+    }
+    writeln!(out);
+}
+
+fn write_skipped_files(out: &mut dyn Write, skipped_files: &[SkippedFile], config: &Configuration) {
+    if skipped_files.is_empty() {
+        return;
+    }
+
+    writeln!(out, "{} {}", skipped_files.len().to_string().yellow(), "files were left out for their size.".yellow());
+    if config.show_skipped || config.is_verbose() {
+        for f in skipped_files {
+            writeln!(out, "-- {} ({}, {} bytes)\n   {}", config.path_style.format(&f.path.to_string_lossy()), f.language_name,
+                    with_seperators(f.bytes as usize), f.reason.message());
         }
-    )
+    } else {
+        writeln!(out, "Run with command '--{}' to list them.", config_manager::SHOW_SKIPPED);
+    }
+    writeln!(out);
 }
 
+// Only the paths with '--verbose', since their number is among the warnings after the results
+fn write_unreadable_dirs(out: &mut dyn Write, unreadable_dirs: &[UnreadableDir], config: &Configuration) {
+    if unreadable_dirs.is_empty() || !config.is_verbose() {
+        return;
+    }
 
-fn print_faulty_files_or_ok(faulty_files_ref: &FaultyFilesListMut, config: &Configuration) {
-    let faulty_files = &*faulty_files_ref.as_ref().lock().unwrap();
+    writeln!(out, "{}", "Directories that couldn't be read:".yellow());
+    for dir in unreadable_dirs {
+        writeln!(out, "-- {}\n   {}", config.path_style.format(&dir.path.to_string_lossy()), dir.error);
+    }
+    writeln!(out);
+}
+
+fn write_submodules(out: &mut dyn Write, submodule_dirs: &[PathBuf], config: &Configuration) {
+    if submodule_dirs.is_empty() {
+        return;
+    }
+
+    let done = if config.submodules == Some(config_manager::Submodules::Include) {"searched as directories of their own"} else {"left out"};
+    writeln!(out, "{} git submodules were {}.", with_seperators(submodule_dirs.len()), done);
+    if config.is_verbose() {
+        for dir in submodule_dirs {
+            writeln!(out, "-- {}", config.path_style.format(&dir.to_string_lossy()));
+        }
+    }
+    writeln!(out);
+}
+
+fn write_faulty_files_or_ok(out: &mut dyn Write, faulty_files: &[FaultyFile], config: &Configuration) {
     if faulty_files.is_empty() {
-        println!("{}\n","ok".bright_green());
+        writeln!(out, "{}\n","ok".bright_green());
     } else {
-        println!("{} {}",format!("{}",faulty_files.len()).red(), "faulty files detected. They will be ignored in stat calculation.".red());
-        if config.should_show_faulty_files {
+        writeln!(out, "{} {}",format!("{}",faulty_files.len()).red(), "faulty files detected. They will be ignored in stat calculation.".red());
+        if config.should_show_faulty_files || config.is_verbose() {
             for f in faulty_files {
-                println!("-- Error: {} \n   for file: {}\n",f.error_msg,f.path);
+                writeln!(out, "-- Error: {} \n   for file: {}\n",f.error.message(),config.path_style.format(&f.path.to_string_lossy()));
             }
         } else {
-            println!("Run with command '--{}' to get detailed info.",config_manager::SHOW_FAULTY_FILES)
+            writeln!(out, "Run with command '--{}' to get detailed info.",config_manager::SHOW_FAULTY_FILES);
         }
-        println!();
+        writeln!(out);
     }
 }
 
-fn remove_faulty_files_stats(faulty_files_ref: &FaultyFilesListMut, languages_metadata_map: &mut HashMap<String,LanguageMetadata>,
-        language_map: &Arc<HashMap<String,Language>>) {
-    let faulty_files = &*faulty_files_ref.as_ref().lock().unwrap();
+fn write_reconciliation(out: &mut dyn Write, reconciliation: &Reconciliation) {
+    let left_out = [(reconciliation.generated_files, "generated"), (reconciliation.minified_files, "minified")].iter()
+            .filter(|(files, _)| *files != 0)
+            .map(|(files, kind)| format!("{} {}", with_seperators(*files), kind))
+            .collect::<Vec<_>>();
+    let left_out = if left_out.is_empty() {String::new()} else {format!(" ({} left out)", left_out.join(", "))};
+    let skipped = match reconciliation.skipped_files {
+        0 => String::new(),
+        x => format!(" ({} for their size)", with_seperators(x))
+    };
+    writeln!(out, "{} files accounted for: {} parsed{}, {} faulty, {} excluded{}, {} not supported.", with_seperators(reconciliation.found_files),
+            with_seperators(reconciliation.parsed_files), left_out, with_seperators(reconciliation.faulty_files),
+            with_seperators(reconciliation.excluded_files), skipped, with_seperators(reconciliation.unsupported_files()));
+    for discrepancy in reconciliation.discrepancies() {
+        writeln!(out, "{}", format!("Discrepancy: {}", discrepancy).yellow());
+    }
+    writeln!(out);
+}
+
+// The language of a faulty file is the one it was queued with, since it may have been detected by its name or shebang.
+// The faulty files that are more than the found files of their language are returned, instead of the stats underflowing.
+fn remove_faulty_files_stats<'a>(faulty_files: &'a [FaultyFile], languages_metadata_map: &mut HashMap<String,LanguageMetadata>) -> Vec<&'a FaultyFile> {
+    let mut unaccounted_files = Vec::new();
     for file in faulty_files {
-        let extension = utils::get_file_extension(Path::new(&file.path));
-        if let Some(x) = extension {
-            let lang_name = find_lang_with_this_identifier(language_map, x).unwrap();
-            let language_metadata = languages_metadata_map.get_mut(&lang_name).unwrap();
-            language_metadata.files -= 1;
-            language_metadata.bytes -= file.size as usize;
+        match languages_metadata_map.get_mut(&file.language_name) {
+            Some(language_metadata) if language_metadata.files != 0 => {
+                language_metadata.files -= 1;
+                language_metadata.bytes = language_metadata.bytes.saturating_sub(file.bytes as usize);
+            },
+            _ => unaccounted_files.push(file)
         }
     }
+    unaccounted_files
+}
+
+// The generated or minified files were found by the search before they were parsed, so they are in the metadata of their
+// language. Returns all of them.
+fn remove_left_out_files_stats(content_info_map: &HashMap<String,LanguageContentInfo>,
+        languages_metadata_map: &mut HashMap<String,LanguageMetadata>, get_stats: impl Fn(&LanguageContentInfo) -> &DirStats) -> DirStats
+{
+    let mut left_out = DirStats::default();
+    for (name, content_info) in content_info_map.iter() {
+        let stats = get_stats(content_info);
+        if let Some(language_metadata) = languages_metadata_map.get_mut(name) {
+            language_metadata.files = language_metadata.files.saturating_sub(stats.files);
+            language_metadata.bytes = language_metadata.bytes.saturating_sub(stats.bytes);
+        }
+        left_out.add(stats);
+    }
+    left_out
 }
 
 fn get_activated_languages_as_str(config: &Configuration) -> String {
@@ -306,10 +1295,46 @@ pub struct LocalAppPaths {
     pub test_log_dir: String,
 }
 
+// What the search and the parsing of the files of a run produced
 #[derive(Debug)]
+struct Analysis {
+    files_present: FilesPresent,
+    faulty_files: Vec<FaultyFile>,
+    content_info_map: HashMap<String,LanguageContentInfo>,
+    languages_metadata_map: HashMap<String,LanguageMetadata>,
+    parsing_duration: Duration,
+    // Until the search for the files was finished, which is part of the parsing duration
+    discovery_duration: Duration,
+    documentation_languages: Vec<String>,
+    documentation_content_info_map: HashMap<String,LanguageContentInfo>,
+    documentation_metadata_map: HashMap<String,LanguageMetadata>,
+    // Empty unless '--dir-breakdown' is used
+    dirs_stats_map: HashMap<PathBuf,DirStats>,
+    // Only the parser threads unless '--timings' is used
+    parse_timings: ParseTimings,
+    // The provided directories, followed by the submodules of '--include-submodules'
+    breakdown_roots: Vec<String>
+}
+
+#[derive(Debug, PartialEq)]
 pub struct Metrics {
+    // Of a single run, like the bytes
+    pub files: usize,
+    pub lines: usize,
     pub files_per_sec: usize,
-    pub lines_per_sec: usize
+    pub lines_per_sec: usize,
+    // Of every run, in the order that they were made. A single one, unless they are the metrics of 'bench'
+    pub durations: Vec<Duration>,
+    // Of all the runs
+    pub total_duration: Duration,
+    // Of a single run
+    pub total_bytes: usize,
+    pub bytes_per_sec: usize,
+    // The search for the files, while the first ones were being parsed too, and the parsing that went on after it
+    pub discovery_duration: Duration,
+    pub parse_duration: Duration,
+    // The part of its lifetime that each parser thread spent on the files, from 0 to 1, by id
+    pub thread_utilizations: Vec<f64>
 }
 
 #[derive(Debug, PartialEq)]
@@ -326,30 +1351,150 @@ pub struct FinalStats {
     average_size_measurement: String
 }
 
-#[derive(Debug)]
-pub struct FaultyFileDetails {
-    path: String,
-    error_msg: String,
-    size: u64
-}
-
 #[derive(Debug)]
 pub enum ParseFilesError {
     NoRelevantFiles(String),
-    AllAreFaultyFiles
+    AllAreFaultyFiles,
+    InvalidKeywordPattern(RegexError),
+    UnavailableGitIndex(String),
+    UnavailableGitChanges(String),
+    // The address of 'serve' couldn't be listened on
+    UnavailableAddress(String),
+    // The file of '--out' couldn't be created, or it exists without '--force'
+    UnavailableOutFile(String, std::io::Error),
+    // The file of '--event-log' couldn't be created
+    UnavailableEventLog(String, std::io::Error),
+    // The terminal of '--tui' couldn't be set up or read
+    UnavailableTerminal(std::io::Error),
+    // The caches of the OS couldn't be dropped before a run of 'bench --cold'
+    UnavailableCacheDrop(std::io::Error),
+    // The baseline of 'check' couldn't be read, or it is not json of '--output json'
+    UnavailableBaseline(MergeError),
+    // The flag of 'run_cancellable' was set before the run was finished
+    Cancelled,
+    // The search and the parsing took longer than the seconds of '--timeout'
+    TimedOut(usize),
+    // The conditions of '--fail-if' that held. The results were printed and saved before
+    BudgetsExceeded(Vec<BudgetViolation>),
+    // With '--strict', the number of the files that couldn't be parsed. The results are not printed
+    FaultyFiles(usize),
+    // The path of 'file' is not a file, or not of any of the languages
+    UnsupportedFile(String),
+    // The file of 'file' couldn't be parsed
+    UnparsableFile(String, FaultyReason)
 } 
 
+// Checks that every file that was found ends up in the results, or is counted as faulty, excluded or not supported,
+// as a guard against the filters disagreeing with each other
+#[derive(Debug,Default,PartialEq)]
+pub struct Reconciliation {
+    pub found_files: usize,
+    pub relevant_files: usize,
+    pub excluded_files: usize,
+    pub discovered_files: usize,
+    pub discovered_bytes: usize,
+    pub parsed_files: usize,
+    pub parsed_bytes: usize,
+    pub faulty_files: usize,
+    pub faulty_bytes: usize,
+    // Of the excluded files, the ones left out for their size
+    pub skipped_files: usize,
+    // Of the parsed files, the ones left out with '--skip-generated'
+    pub generated_files: usize,
+    // Of the parsed files, the ones left out with '--skip-minified'
+    pub minified_files: usize
+}
+
 #[derive(Debug,Default,Clone)]
 pub struct FilesPresent {
     pub total_files: usize,
     pub relevant_files: usize,
-    pub excluded_files: usize
+    pub excluded_files: usize,
+    pub detection_conflicts: Vec<DetectionConflict>,
+    // Part of the excluded files, with '--min-size' and '--max-size'
+    pub skipped_files: Vec<SkippedFile>,
+    // The directories whose files couldn't be counted, e.g. for their permissions
+    pub unreadable_dirs: Vec<UnreadableDir>,
+    // The git submodules of '--include-submodules' or '--exclude-submodules'
+    pub submodule_dirs: Vec<PathBuf>,
+    // The relevant entries of the archives that inflate to more than their limit, which are added to the faulty files
+    pub faulty_archive_entries: Vec<FaultyFile>
+}
+
+// The time that the parser threads spent on the files, with '--timings'. The faulty files are included,
+// since a file that timed out or failed to be read may well be what made the run slow. The parser threads are always included.
+#[derive(Debug,Default,Clone,PartialEq)]
+pub struct ParseTimings {
+    // By extension, with the number of files and the time spent on them. The files without an extension are under ""
+    pub extensions: HashMap<String,(usize, Duration)>,
+    // The path, the language and the duration of the slowest files, the slowest first
+    pub slowest_files: Vec<(PathBuf, String, Duration)>,
+    // The id of every parser thread, the time it spent on the files and the time it was alive
+    pub parser_threads: Vec<(usize, Duration, Duration)>
+}
+
+// A file whose shebang line points to a different language than its extension
+#[derive(Debug,Clone,PartialEq)]
+pub struct DetectionConflict {
+    pub path: PathBuf,
+    pub extension_language: String,
+    pub shebang_identifier: String,
+    // None if no language supports the extension of the shebang
+    pub shebang_language: Option<String>,
+    pub chosen_language: String
+}
+
+// Lets the consumers block until files are queued, instead of polling the files injector.
+// The producers notify it after pushing files, and it is finished when no more files will be pushed.
+// The producers and the consumers also stop early when the flag of 'run_cancellable' is set or the '--timeout' is reached.
+#[derive(Debug,Default)]
+pub struct FilesSignal {
+    finished: Mutex<bool>,
+    condvar: Condvar,
+    cancel_flag: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+    // Whether any of the threads stopped before the search and the parsing were over
+    stopped: AtomicBool
+}
+
+// The limits of '--max-open-files' and '--max-memory' that the producers and the consumers share.
+// A limit of 0 means no limit.
+#[derive(Debug,Default)]
+pub struct ResourceLimits {
+    max_open_files: usize,
+    open_files: Mutex<usize>,
+    condvar: Condvar,
+    pub max_queued_files: usize,
+    // Roughly, of the files that the consumers are parsing, against the half of '--max-memory' that is given to them
+    buffered_bytes: AtomicUsize,
+    pub max_buffered_bytes: usize
+}
+
+// Releases its open file slot when dropped
+pub struct OpenFileSlot<'a> {
+    limits: &'a ResourceLimits
+}
+
+// Gives its bytes back to the memory budget when dropped
+pub struct BufferedBytes<'a> {
+    limits: &'a ResourceLimits,
+    bytes: usize
 }
 
 #[derive(Debug,Clone)]
 pub struct ParsableFile {
     pub path: PathBuf,
-    pub language_name: String 
+    pub language_name: String,
+    // The contents of a file inside an archive, which has no path of its own to be read from
+    pub contents: Option<Arc<[u8]>>
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct DiscoveredFile {
+    pub path: PathBuf,
+    pub extension: String,
+    pub language_name: String,
+    pub size: u64
 }
 
 
@@ -405,8 +1550,95 @@ impl Formatted for ParseFilesError {
     fn formatted(&self) -> ColoredString {
         match self {
             Self::NoRelevantFiles(x) => format!("{} {}","No relevant files found in the given directory.", x).yellow(),
-            Self::AllAreFaultyFiles => "None of the files were able to be parsed".yellow()
+            Self::AllAreFaultyFiles => "None of the files were able to be parsed".yellow(),
+            Self::InvalidKeywordPattern(x) => x.formatted(),
+            Self::UnavailableGitIndex(x) => format!("Unable to list the files tracked by git: {}", x).yellow(),
+            Self::UnavailableGitChanges(x) => format!("Unable to list the files changed in git: {}", x).yellow(),
+            Self::UnavailableAddress(x) => format!("Unable to listen on the address: {}", x).red(),
+            Self::UnavailableOutFile(x, error) if error.kind() == std::io::ErrorKind::AlreadyExists => {
+                format!("'{}' already exists. Use '--force' to overwrite it", x).red()
+            },
+            Self::UnavailableOutFile(x, error) => format!("Unable to create '{}': {}", x, error.kind()).red(),
+            Self::UnavailableEventLog(x, error) => format!("Unable to create the event log '{}': {}", x, error.kind()).red(),
+            Self::UnavailableTerminal(x) => format!("Unable to use the terminal: {}", x).red(),
+            Self::UnavailableCacheDrop(x) => format!("Unable to drop the caches of the OS, which needs root on linux: {}", x).red(),
+            Self::UnavailableBaseline(x) => x.formatted(),
+            Self::Cancelled => "The analysis was cancelled".yellow(),
+            Self::TimedOut(x) => format!("The analysis took longer than the timeout of {} secs", x).yellow(),
+            Self::BudgetsExceeded(x) => format!("\nBudgets exceeded:\n{}", x.iter().map(|x| format!("  {} (actual: {})", x.expression,
+                    with_seperators(x.value))).collect::<Vec<_>>().join("\n")).red(),
+            Self::FaultyFiles(x) => format!("{} faulty files with '--strict'", with_seperators(*x)).red(),
+            Self::UnsupportedFile(x) => format!("'{}' is not a file of the supported languages", x).yellow(),
+            Self::UnparsableFile(x, reason) => format!("Unable to parse '{}': {}", x, reason.message()).red()
+        }
+    }
+}
+
+impl ParseFilesError {
+    // The code that the program exits with, as listed in the README, so that scripts can tell the outcomes apart
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::NoRelevantFiles(_) | Self::UnsupportedFile(_) => 2,
+            Self::AllAreFaultyFiles | Self::FaultyFiles(_) | Self::UnparsableFile(..) => 3,
+            Self::BudgetsExceeded(_) => 4,
+            Self::UnavailableGitIndex(_) | Self::UnavailableGitChanges(_) | Self::UnavailableAddress(_) | Self::UnavailableOutFile(..)
+                    | Self::UnavailableEventLog(..) | Self::UnavailableTerminal(_)
+                    | Self::UnavailableCacheDrop(_) | Self::UnavailableBaseline(_) => 5,
+            Self::InvalidKeywordPattern(_) | Self::Cancelled | Self::TimedOut(_) => 1
+        }
+    }
+}
+
+impl_display_from_formatted!(ParseFilesError);
+
+impl std::error::Error for ParseFilesError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidKeywordPattern(x) => Some(x),
+            Self::UnavailableOutFile(_, x) | Self::UnavailableEventLog(_, x) | Self::UnavailableTerminal(x)
+                    | Self::UnavailableCacheDrop(x) => Some(x),
+            Self::UnavailableBaseline(x) => Some(x),
+            _ => None
+        }
+    }
+}
+
+impl Metrics {
+    // The speeds are of the average duration
+    pub fn from_durations(files: usize, lines: usize, bytes: usize, durations: Vec<Duration>) -> Self {
+        let total_duration = durations.iter().sum();
+        let mut metrics = Metrics {files, lines, files_per_sec: 0, lines_per_sec: 0, durations, total_duration, total_bytes: bytes, bytes_per_sec: 0,
+                discovery_duration: Duration::ZERO, parse_duration: Duration::ZERO, thread_utilizations: Vec::new()};
+        let average_secs = metrics.average().as_secs_f64();
+        if average_secs > 0f64 {
+            metrics.files_per_sec = (files as f64 / average_secs) as usize;
+            metrics.lines_per_sec = (lines as f64 / average_secs) as usize;
+            metrics.bytes_per_sec = (bytes as f64 / average_secs) as usize;
+        }
+        metrics
+    }
+
+    pub fn min(&self) -> Duration {
+        self.durations.iter().min().copied().unwrap_or_default()
+    }
+
+    pub fn max(&self) -> Duration {
+        self.durations.iter().max().copied().unwrap_or_default()
+    }
+
+    pub fn average(&self) -> Duration {
+        if self.durations.is_empty() {
+            return Duration::ZERO;
         }
+        self.durations.iter().sum::<Duration>() / self.durations.len() as u32
+    }
+
+    // The duration that the given percent of the runs took at most, e.g. 90 for the p90, by the nearest rank
+    pub fn percentile(&self, percent: usize) -> Duration {
+        let mut durations = self.durations.clone();
+        durations.sort();
+        let rank = (percent * durations.len()).div_ceil(100).max(1);
+        durations.get(rank - 1).copied().unwrap_or_default()
     }
 }
 
@@ -414,10 +1646,10 @@ impl FinalStats {
     pub fn new(files: usize, lines: usize, code_lines: usize, bytes_size: usize) -> Self
     {
         let bytes_average_size = bytes_size / files;
-        let (size, size_measurement) = FinalStats::get_formatted_size_and_measurement(bytes_size);
-        let size = round_1(size);
-        let (average_size, average_size_measurement) = Self::get_formatted_size_and_measurement(bytes_average_size);
-        let average_size = round_1(average_size);
+        let (size, size_measurement) = get_size_and_measurement(bytes_size);
+        let size = round_size(size);
+        let (average_size, average_size_measurement) = get_size_and_measurement(bytes_average_size);
+        let average_size = round_size(average_size);
         FinalStats {
             files,
             lines,
@@ -433,10 +1665,10 @@ impl FinalStats {
     }
 
     pub fn new_extended(files: usize, lines: usize, code_lines: usize, extra_lines: usize, bytes_size: usize, bytes_average_size: usize) -> Self {
-        let (size, size_measurement) = FinalStats::get_formatted_size_and_measurement(bytes_size);
-        let size = round_1(size);
-        let (average_size, average_size_measurement) = Self::get_formatted_size_and_measurement(bytes_average_size);
-        let average_size = round_1(average_size);
+        let (size, size_measurement) = get_size_and_measurement(bytes_size);
+        let size = round_size(size);
+        let (average_size, average_size_measurement) = get_size_and_measurement(bytes_average_size);
+        let average_size = round_size(average_size);
 
         FinalStats {
             files,
@@ -452,16 +1684,21 @@ impl FinalStats {
         }
     }
 
-    pub fn calculate(content_info_map: &HashMap<String,LanguageContentInfo>, languages_metadata_map: &HashMap<String,LanguageMetadata>) -> Self {
-        let (mut total_files, mut total_lines, mut total_code_lines, mut total_bytes) = (0, 0, 0,0);
+    // If 'ignore_empty_files' is set, the empty and whitespace-only files are not taken into account in the average size,
+    // like in the lines and the code lines per file of each language (see LinesDistribution::without_blank_files)
+    pub fn calculate(content_info_map: &HashMap<String,LanguageContentInfo>, languages_metadata_map: &HashMap<String,LanguageMetadata>,
+            ignore_empty_files: bool) -> Self 
+    {
+        let (mut total_files, mut total_lines, mut total_code_lines, mut total_bytes, mut blank_files) = (0, 0, 0, 0, 0);
         languages_metadata_map.values().for_each(|e| {total_files += e.files; total_bytes += e.bytes});
-        content_info_map.values().for_each(|c| {total_lines += c.lines; total_code_lines += c.code_lines});
+        content_info_map.values().for_each(|c| {total_lines += c.lines; total_code_lines += c.code_lines; blank_files += c.blank_files()});
         let bytes_size = total_bytes;
-        let bytes_average_size = total_bytes / total_files;
-        let (total_size, size_measurement) = Self::get_formatted_size_and_measurement(total_bytes);
-        let (average_size, average_size_measurement) = Self::get_formatted_size_and_measurement(bytes_average_size);
-        let total_size = round_1(total_size);
-        let average_size = round_1(average_size);
+        let files_for_average = if ignore_empty_files {total_files.saturating_sub(blank_files)} else {total_files}.max(1);
+        let bytes_average_size = total_bytes / files_for_average;
+        let (total_size, size_measurement) = get_size_and_measurement(total_bytes);
+        let (average_size, average_size_measurement) = get_size_and_measurement(bytes_average_size);
+        let total_size = round_size(total_size);
+        let average_size = round_size(average_size);
 
 
         FinalStats {
@@ -478,20 +1715,48 @@ impl FinalStats {
         }
     }
 
-    fn get_formatted_size_and_measurement(value: usize) -> (f64, String) {
-        if value >= 1000000 {(value as f64 / 1000000f64, "MBs".to_owned())}
-        else if value >= 1000 {(value as f64 / 1000f64, "KBs".to_owned())}
-        else {(value as f64, "Bytes".to_owned())}
-    }
 }
 
-impl FaultyFileDetails {
-    pub fn new(path: String, error_msg: String, size: u64) -> Self {
-        FaultyFileDetails {
-            path,
-            error_msg,
-            size
+impl Reconciliation {
+    // The discovered counts are taken from the metadata before the faulty files are removed from it.
+    // The parsed counts are filled in after the final stats are calculated.
+    pub fn new(files_present: &FilesPresent, languages_metadata_map: &HashMap<String,LanguageMetadata>, faulty_files: &[FaultyFile]) -> Self {
+        Reconciliation {
+            found_files: files_present.total_files,
+            relevant_files: files_present.relevant_files,
+            excluded_files: files_present.excluded_files,
+            discovered_files: languages_metadata_map.values().map(|x| x.files).sum(),
+            discovered_bytes: languages_metadata_map.values().map(|x| x.bytes).sum(),
+            faulty_files: faulty_files.len(),
+            faulty_bytes: faulty_files.iter().map(|x| x.bytes as usize).sum(),
+            skipped_files: files_present.skipped_files.len(),
+            ..Reconciliation::default()
+        }
+    }
+
+    pub fn unsupported_files(&self) -> usize {
+        self.found_files.saturating_sub(self.relevant_files + self.excluded_files)
+    }
+
+    pub fn discrepancies(&self) -> Vec<String> {
+        let mut discrepancies = Vec::new();
+        if self.relevant_files + self.excluded_files > self.found_files {
+            discrepancies.push(format!("{} files of interest and {} excluded, but only {} found", with_seperators(self.relevant_files),
+                    with_seperators(self.excluded_files), with_seperators(self.found_files)));
         }
+        if self.discovered_files != self.relevant_files {
+            discrepancies.push(format!("{} files of interest, but {} attributed to languages", with_seperators(self.relevant_files),
+                    with_seperators(self.discovered_files)));
+        }
+        if self.parsed_files + self.faulty_files != self.relevant_files {
+            discrepancies.push(format!("{} files of interest, but {} parsed and {} faulty", with_seperators(self.relevant_files),
+                    with_seperators(self.parsed_files), with_seperators(self.faulty_files)));
+        }
+        if self.parsed_bytes + self.faulty_bytes != self.discovered_bytes {
+            discrepancies.push(format!("{} bytes found, but {} parsed and {} faulty", with_seperators(self.discovered_bytes),
+                    with_seperators(self.parsed_bytes), with_seperators(self.faulty_bytes)));
+        }
+        discrepancies
     }
 }
 
@@ -500,7 +1765,203 @@ impl FilesPresent {
         FilesPresent {
             total_files,
             relevant_files,
-            excluded_files
+            excluded_files,
+            detection_conflicts: Vec::new(),
+            skipped_files: Vec::new(),
+            unreadable_dirs: Vec::new(),
+            submodule_dirs: Vec::new(),
+            faulty_archive_entries: Vec::new()
+        }
+    }
+}
+
+impl ParseTimings {
+    pub fn add_file(&mut self, path: &Path, language_name: &str, duration: Duration) {
+        let extension_timing = self.extensions.entry(utils::get_file_extension(path).unwrap_or_default()).or_default();
+        extension_timing.0 += 1;
+        extension_timing.1 += duration;
+        self.add_slowest_file(path, language_name, duration);
+    }
+
+    pub fn add(&mut self, other: &ParseTimings) {
+        for (extension, (files, duration)) in other.extensions.iter() {
+            let extension_timing = self.extensions.entry(extension.to_owned()).or_default();
+            extension_timing.0 += files;
+            extension_timing.1 += *duration;
+        }
+        for (path, language_name, duration) in other.slowest_files.iter() {
+            self.add_slowest_file(path, language_name, *duration);
+        }
+        self.parser_threads.extend_from_slice(&other.parser_threads);
+    }
+
+    fn add_slowest_file(&mut self, path: &Path, language_name: &str, duration: Duration) {
+        if self.slowest_files.len() == SLOWEST_FILES_NUM && self.slowest_files.last().is_some_and(|x| x.2 >= duration) {
+            return;
+        }
+        let index = self.slowest_files.iter().position(|x| x.2 < duration).unwrap_or(self.slowest_files.len());
+        self.slowest_files.insert(index, (path.to_path_buf(), language_name.to_owned(), duration));
+        self.slowest_files.truncate(SLOWEST_FILES_NUM);
+    }
+
+    pub fn total_duration(&self) -> Duration {
+        self.extensions.values().map(|x| x.1).sum()
+    }
+}
+
+impl FilesSignal {
+    pub fn new() -> Self {
+        Self::with_cancel_flag(Arc::new(AtomicBool::new(false)))
+    }
+
+    pub fn with_cancel_flag(cancel_flag: Arc<AtomicBool>) -> Self {
+        FilesSignal {
+            finished: Mutex::new(false),
+            condvar: Condvar::new(),
+            cancel_flag,
+            deadline: None,
+            stopped: AtomicBool::new(false)
+        }
+    }
+
+    // 0 means no limit
+    pub fn with_timeout(mut self, timeout_secs: usize) -> Self {
+        self.deadline = if timeout_secs == 0 {None} else {Some(Instant::now() + Duration::from_secs(timeout_secs as u64))};
+        self
+    }
+
+    // Checked by the threads before they take more work
+    pub fn should_stop(&self) -> bool {
+        let should_stop = self.cancel_flag.load(Ordering::Relaxed) || self.deadline.is_some_and(|x| Instant::now() >= x);
+        if should_stop {
+            self.stopped.store(true, Ordering::Relaxed);
+        }
+        should_stop
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_flag.load(Ordering::Relaxed)
+    }
+
+    pub fn was_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    // The lock is taken so that the notification can't be missed by a consumer that just found the injector empty
+    pub fn notify_new_files(&self) {
+        let _guard = self.finished.lock().unwrap();
+        self.condvar.notify_all();
+    }
+
+    pub fn finish(&self) {
+        *self.finished.lock().unwrap() = true;
+        self.condvar.notify_all();
+    }
+
+    // Blocks until there are files in the injector, or until it is finished.
+    // Returns false if the injector is empty and no more files will be pushed.
+    pub fn wait_for_files(&self, files_injector: &Injector<ParsableFile>) -> bool {
+        let mut finished = self.finished.lock().unwrap();
+        while files_injector.is_empty() && !*finished {
+            finished = self.condvar.wait(finished).unwrap();
+        }
+        !files_injector.is_empty()
+    }
+}
+
+impl ResourceLimits {
+    pub fn new(max_open_files: usize, max_queued_files: usize) -> Self {
+        ResourceLimits {
+            max_open_files,
+            open_files: Mutex::new(0),
+            condvar: Condvar::new(),
+            max_queued_files,
+            buffered_bytes: AtomicUsize::new(0),
+            max_buffered_bytes: 0
+        }
+    }
+
+    // Half of the memory budget is given to the queued files, the other half to the files being parsed
+    pub fn from_config(config: &Configuration) -> Self {
+        // With '--prescan' nothing is parsed until all the files are queued, so the producers can't wait for the queue to have space
+        let max_queued_files = if config.max_memory == 0 || config.prescan {
+            0
+        } else {
+            (config.max_memory * 1024 * 1024 / 2 / QUEUED_FILE_MEMORY_ESTIMATE).max(1)
+        };
+        ResourceLimits {
+            max_buffered_bytes: config.max_memory * 1024 * 1024 / 2,
+            ..ResourceLimits::new(config.max_open_files, max_queued_files)
+        }
+    }
+
+    // Blocks until a file or directory can be opened without exceeding the limit.
+    // The slot has to be kept for as long as the file is open.
+    pub fn acquire_open_file(&self) -> OpenFileSlot<'_> {
+        if self.max_open_files != 0 {
+            let mut open_files = self.open_files.lock().unwrap();
+            while *open_files >= self.max_open_files {
+                open_files = self.condvar.wait(open_files).unwrap();
+            }
+            *open_files += 1;
+        }
+        OpenFileSlot {limits: self}
+    }
+
+    // Counts the memory that the parsing of a file holds until the returned bytes are dropped. The producers wait while
+    // it is over the budget, instead of queueing more files.
+    pub fn reserve_buffer(&self, bytes: usize) -> BufferedBytes<'_> {
+        if self.max_buffered_bytes != 0 {
+            self.buffered_bytes.fetch_add(bytes, Ordering::Relaxed);
+        }
+        BufferedBytes {limits: self, bytes}
+    }
+
+    pub fn is_over_buffer_budget(&self) -> bool {
+        self.max_buffered_bytes != 0 && self.buffered_bytes.load(Ordering::Relaxed) >= self.max_buffered_bytes
+    }
+}
+
+impl Drop for BufferedBytes<'_> {
+    fn drop(&mut self) {
+        if self.limits.max_buffered_bytes != 0 {
+            self.limits.buffered_bytes.fetch_sub(self.bytes, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Drop for OpenFileSlot<'_> {
+    fn drop(&mut self) {
+        if self.limits.max_open_files != 0 {
+            *self.limits.open_files.lock().unwrap() -= 1;
+            self.limits.condvar.notify_one();
+        }
+    }
+}
+
+// The memory that a single consumer can use for the file it parses, if '--max-memory' is used.
+// Files bigger than this are read in blocks that fit in it.
+pub fn get_file_memory_budget(config: &Configuration) -> Option<usize> {
+    if config.max_memory == 0 {
+        return None;
+    }
+    Some(config.max_memory * 1024 * 1024 / 2 / config.threads.consumers.max(1))
+}
+
+// The size of the file, since the buffers and the stats of its parsing grow with it, up to the budget of a consumer
+// for the files that are read in blocks
+pub fn estimate_parse_memory(file: &ParsableFile, config: &Configuration) -> usize {
+    let size = file.size() as usize;
+    get_file_memory_budget(config).map_or(size, |x| size.min(x))
+}
+
+impl From<ParsableFile> for DiscoveredFile {
+    fn from(file: ParsableFile) -> Self {
+        DiscoveredFile {
+            extension: utils::get_file_extension(&file.path).unwrap_or_default(),
+            size: file.size(),
+            path: file.path,
+            language_name: file.language_name
         }
     }
 }
@@ -509,7 +1970,23 @@ impl ParsableFile {
     pub fn new(path: PathBuf, language_name: String) -> Self {
         ParsableFile {
             path,
-            language_name
+            language_name,
+            contents: None
+        }
+    }
+
+    pub fn from_archive_entry(path: PathBuf, language_name: String, contents: Vec<u8>) -> Self {
+        ParsableFile {
+            path,
+            language_name,
+            contents: Some(Arc::from(contents))
+        }
+    }
+
+    pub fn size(&self) -> u64 {
+        match &self.contents {
+            Some(x) => x.len() as u64,
+            None => self.path.metadata().map_or(0, |m| m.len())
         }
     }
 }
@@ -517,6 +1994,22 @@ impl ParsableFile {
 
 pub mod domain {
     use super::*;
+
+    // The occurrences of each keyword, ordered by name so that they are always printed and saved in the same order
+    pub type KeywordOccurences = BTreeMap<String,usize>;
+    // The file with the most occurrences of each keyword, with their number. Empty unless '--keyword-hotspots' is used
+    pub type KeywordHotspots = BTreeMap<String,(usize,PathBuf)>;
+    // The metrics of the plugins by name, e.g. "SQL queries". Empty unless plugins are registered with 'run_with_plugins'
+    pub type PluginMetrics = BTreeMap<String,usize>;
+    // The files of each extension by license, e.g. "MIT", with the unlicensed ones under UNLICENSED. Empty unless '--licenses' is used
+    pub type LicenseCounts = BTreeMap<String,BTreeMap<String,usize>>;
+    pub const UNLICENSED : &str = "unlicensed";
+    // The lines of each extension by the author that last changed them according to git blame. Empty unless '--authors' is used
+    pub type AuthorCounts = BTreeMap<String,BTreeMap<String,usize>>;
+    // The indentation of the code lines of each extension. Empty unless '--indentation' is used
+    pub type IndentationCounts = BTreeMap<String,IndentationStats>;
+    // The widths of the indentation with spaces that are counted, from 1
+    pub const MAX_INDENT_WIDTH : usize = 8;
     
     #[derive(Debug,PartialEq, Clone)]
     pub struct Language {
@@ -524,22 +2017,219 @@ pub mod domain {
         pub extensions : Vec<String>,
         pub string_symbols : Vec<String>,
         pub comment_symbols : Vec<String>,
-        pub multiline_comment_start_symbol : Option<String>,
-        pub multiline_comment_end_symbol : Option<String>,
+        // The start and end symbols of each kind of multiline comment, e.g. '{-' and '-}'. An end symbol only closes
+        // a comment that was started by its own start symbol
+        pub multiline_comment_symbols : Vec<(String,String)>,
+        // Whether a multiline comment can contain others (e.g. Rust), so that it only ends when they have all ended
+        pub supports_nested_comments : bool,
+        // Whether a single quote that is closed after one char or escape starts a char literal, e.g. '"' in Rust and C, so that
+        // the string symbol in it doesn't start a string. Other single quotes are left as they are, e.g. the lifetimes of Rust
+        pub supports_char_literals : bool,
+        // The strings that don't start and end with a string symbol, matched before the string symbols
+        pub string_delimiters : Vec<StringDelimiter>,
+        // The symbols that start a documentation comment at the start of a line, e.g. '///' or '/**'. If the symbol
+        // opens a multiline comment or a string, the documentation lasts until it is closed
+        pub doc_comment_symbols : Vec<String>,
+        // The keywords and operators that branch the control flow (e.g. 'if' or '&&'), whose occurrences in the code make up
+        // its complexity. Counted as whole words, apart from the keywords of the results
+        pub branch_keywords : Vec<String>,
+        // The symbols that end or open a statement, e.g. ';' and '{', whose occurrences in the code are its logical lines.
+        // Without them every code line is a logical line, as in Python
+        pub statement_symbols : Vec<String>,
+        // The symbols that start a heredoc, e.g. '<<' or '<<<', followed by the label (bare or in quotes) of the line that
+        // ends it. The lines in between are a string. Matched in the order they are given, e.g. '<<-' before '<<'
+        pub heredoc_symbols : Vec<String>,
+        // The regions of the files that are in other languages, e.g. the scripts of HTML. Only split with '--embedded'
+        pub embedded_regions : Vec<EmbeddedRegion>,
+        // What kind of files they are, for the lines per category of the sum. Code unless the language file says otherwise
+        pub category : FileCategory,
         pub keywords : Vec<Keyword>
     }
+
+    // A string with its own start and end symbols, e.g. 'r#"' and '"#' for the raw strings of Rust or '"""' for the
+    // multiline strings of Python. Backslashes don't escape the end symbol in raw strings.
+    #[derive(Debug,PartialEq,Clone)]
+    pub struct StringDelimiter {
+        pub start : String,
+        pub end : String,
+        pub is_raw : bool,
+        // The start and end symbols of the code inside the string, e.g. '${' and '}' for the template literals of JavaScript
+        pub interpolation : Option<(String,String)>
+    }
+
+    // The lines between a line that starts with the start symbol and one that starts with the end symbol, e.g. '<script'
+    // and '</script>', are in the language of the identifier, that is an extension or the name of a language. Without an
+    // identifier it is the word after the start symbol, as in the fenced code blocks of markdown ('```rust').
+    #[derive(Debug,PartialEq,Clone)]
+    pub struct EmbeddedRegion {
+        pub start : String,
+        pub end : String,
+        pub identifier : Option<String>
+    }
     
+    // The kind of the files of a language, e.g. the YAML files are config and the HTML ones markup. The documentation
+    // formats of '--docs' are docs.
+    #[derive(Debug,PartialEq,Eq,PartialOrd,Ord,Clone,Copy)]
+    pub enum FileCategory {
+        Code,
+        Markup,
+        Config,
+        Data,
+        Docs
+    }
+
+    // Builds a language with the checks of a language file, so that the users of the library don't have to fill in
+    // the fields of 'Language' themselves. The multiline comment symbols are given as the starts and the ends, as in the files.
+    #[derive(Debug,Clone)]
+    pub struct LanguageBuilder {
+        language : Language,
+        multiline_start_symbols : Vec<String>,
+        multiline_end_symbols : Vec<String>
+    }
+
+    #[derive(Debug,PartialEq)]
+    pub enum LanguageBuildError {
+        EmptyName,
+        NoStringSymbols,
+        // A symbol that is empty, with the kind of symbols that it is in, e.g. "comment"
+        EmptySymbol(&'static str),
+        // The number of the start and the end symbols of the multiline comments
+        UnmatchedMultilineSymbols(usize,usize),
+        DuplicateKeyword(String)
+    }
+
     #[derive(Debug,PartialEq)]
     pub struct Keyword{
         pub descriptive_name : String,
-        pub aliases : Vec<String>
+        pub aliases : Vec<String>,
+        // The aliases compiled as regular expressions, empty unless '--regex-keywords' is used
+        pub patterns : Vec<Regex>,
+        // e.g. "types" or "concurrency", so that the occurrences can be summed across the languages with '--keyword-categories'
+        pub category : Option<String>,
+        // The ascii letters of the aliases match both of their cases, e.g. 'SELECT', 'Select' and 'select' in SQL
+        pub case_insensitive : bool,
+        pub scope : KeywordScope
+    }
+
+    // Where the occurrences of a keyword are counted. The code leaves out the comments and the strings, and the comments
+    // are found as for '--todos', e.g. for keywords like 'FIXME' or 'SAFETY:'
+    #[derive(Debug,PartialEq,Eq,Clone,Copy)]
+    pub enum KeywordScope {
+        Code,
+        Comments,
+        All
     }
     
-    #[derive(Debug,PartialEq)]
+    #[derive(Debug,PartialEq,Clone)]
     pub struct LanguageContentInfo {
         pub lines : usize,
         pub code_lines : usize,
-        pub keyword_occurences : HashMap<String,usize>
+        pub keyword_occurences : KeywordOccurences,
+        pub empty_files : usize,
+        pub whitespace_only_files : usize,
+        // The number of files that contain both keywords of a pair, with the pair ordered by name.
+        // A pair of the same keyword holds the number of files that contain it. Empty unless '--co-occurrence' is used
+        pub keyword_co_occurrences : HashMap<(String,String),usize>,
+        pub keyword_hotspots : KeywordHotspots,
+        // The lines of the documentation comments, e.g. '///' or Python docstrings. They are a part of the extra lines in every
+        // language, so a docstring line is not a code line and a line with both docs and code is not a doc line
+        pub doc_lines : usize,
+        // The sum of the branch keywords of the files, for a rough idea of the complexity of the code
+        pub complexity : usize,
+        // The statements of the code, according to the statement symbols of the language
+        pub logical_lines : usize,
+        // The markers found in the comments, in the order the files were parsed by each thread. Empty unless '--todos' is used
+        pub todos : Vec<TodoItem>,
+        pub prose : ProseStats,
+        pub lines_distribution : LinesDistribution,
+        // The lines and the size of each file, to list the biggest ones. Empty unless '--top-files' is used
+        pub file_summaries : Vec<FileSummary>,
+        // The hash of the contents of each file, to find the identical ones. Empty unless '--duplicates' is used
+        pub file_hashes : Vec<FileHash>,
+        // The code lines of each file, to find the blocks that are repeated in other files. Empty unless '--clones' is used
+        pub file_code_lines : Vec<FileCodeLines>,
+        pub license_counts : LicenseCounts,
+        pub author_counts : AuthorCounts,
+        // Zero unless '--hygiene' is used
+        pub hygiene : HygieneStats,
+        // In characters, of the longest line of the files
+        pub max_line_length : usize,
+        // The lines longer than '--max-line-length'
+        pub long_lines : usize,
+        pub indentation : IndentationCounts,
+        // The part of the stats that comes from the test files. Empty unless '--test-pattern' is used
+        pub test_stats : DirStats,
+        // The part of the stats that comes from the generated files, or with '--skip-generated' the generated files that were left out
+        pub generated_stats : DirStats,
+        // The same for the minified files and '--skip-minified'
+        pub minified_stats : DirStats,
+        // Of the jupyter notebooks that were counted as the language
+        pub notebooks : NotebookStats,
+        // The part of the stats that comes from the regions of the files of other languages, with every region counted
+        // as a file. Empty unless '--embedded' is used
+        pub embedded_stats : DirStats,
+        pub plugin_metrics : PluginMetrics
+    }
+
+    // The lines and the code lines of each parsed file, in the order they were parsed by each thread, for the medians per file
+    #[derive(Debug,PartialEq,Default,Clone)]
+    pub struct LinesDistribution {
+        pub lines : Vec<usize>,
+        pub code_lines : Vec<usize>,
+        // Whether each file is empty or whitespace-only, for '--ignore-empty-files'
+        pub blank : Vec<bool>
+    }
+
+    // How the bytes of a file start and how its lines end, for '--hygiene'
+    #[derive(Debug,PartialEq,Default,Clone,Copy)]
+    pub struct FileHygiene {
+        pub has_bom : bool,
+        pub has_shebang : bool,
+        pub lf_lines : usize,
+        pub crlf_lines : usize,
+        pub ends_with_newline : bool
+    }
+
+    // Of the indented code lines
+    #[derive(Debug,PartialEq,Default,Clone,Copy)]
+    pub struct IndentationStats {
+        pub tab_lines : usize,
+        pub space_lines : usize,
+        // With both tabs and spaces before the code
+        pub mixed_lines : usize,
+        // How many times the indentation with spaces grew by each width from one code line to the next, from 1 to MAX_INDENT_WIDTH
+        pub width_counts : [usize; MAX_INDENT_WIDTH]
+    }
+
+    // The files of a language by the hygiene of 'FileHygiene'
+    #[derive(Debug,PartialEq,Default,Clone,Copy)]
+    pub struct HygieneStats {
+        pub bom_files : usize,
+        pub shebang_files : usize,
+        pub lf_files : usize,
+        pub crlf_files : usize,
+        // With both LF and CRLF line endings
+        pub mixed_files : usize,
+        // Not counting the empty files
+        pub no_final_newline_files : usize
+    }
+
+    // The cells of the jupyter notebooks. Only the code cells are counted as the lines of the language of the kernel,
+    // so the markdown cells are counted here on their own
+    #[derive(Debug,PartialEq,Default,Clone,Copy)]
+    pub struct NotebookStats {
+        pub notebooks : usize,
+        pub code_cells : usize,
+        pub markdown_cells : usize,
+        pub markdown_lines : usize
+    }
+
+    // The counts of the documentation files, that are parsed as prose instead of code. Zero for the other languages
+    #[derive(Debug,PartialEq,Default,Clone,Copy)]
+    pub struct ProseStats {
+        pub words : usize,
+        pub headings : usize,
+        pub code_blocks : usize
     }
 
     #[derive(Debug,PartialEq,Default,Clone)]
@@ -548,65 +2238,433 @@ pub mod domain {
         pub bytes: usize
     }
 
+    // The stats of a group of files, e.g. of a directory for '--dir-breakdown' or of the test files for '--test-pattern'
+    #[derive(Debug,PartialEq,Default,Clone,Copy)]
+    pub struct DirStats {
+        pub files: usize,
+        pub lines: usize,
+        pub code_lines: usize,
+        pub bytes: usize
+    }
+
+    // Typical values of a language, to compare the results against
+    #[derive(Debug,PartialEq,Clone)]
+    pub struct LanguageReference {
+        pub extra_lines_percentage: f64,
+        pub lines_per_file: usize
+    }
+
     #[derive(Debug,PartialEq)]
     pub struct FileStats {
         pub lines : usize,
         pub code_lines : usize,
-        pub keyword_occurences : HashMap<String,usize> 
+        pub keyword_occurences : KeywordOccurences,
+        pub has_content : bool,
+        // A part of the extra lines, as in LanguageContentInfo
+        pub doc_lines : usize,
+        pub complexity : usize,
+        pub logical_lines : usize,
+        pub todos : Vec<TodoItem>,
+        pub prose : ProseStats,
+        pub plugin_metrics : PluginMetrics,
+        // The SHA-256 of the bytes of the file. Only with '--duplicates'
+        pub content_hash : Option<[u8; 32]>,
+        // The hash of each code line without its comments and whitespace, with the number of the line. Only with '--clones'
+        pub code_line_hashes : Vec<(u64, usize)>,
+        // Of the comments before the first code line. Only with '--licenses'
+        pub license : Option<String>,
+        // Only with '--hygiene'
+        pub hygiene : Option<FileHygiene>,
+        // In characters, without the line ending
+        pub max_line_length : usize,
+        // The lines longer than '--max-line-length'
+        pub long_lines : usize,
+        // Only with '--indentation'
+        pub indentation : Option<IndentationStats>,
+        // A marker of generated code was found in its first lines
+        pub is_generated : bool,
+        // It has a line longer than 16 KiB, like the minified javascript and css files
+        pub is_minified : bool,
+        // Only for the jupyter notebooks, whose code cells are the contents that were parsed
+        pub notebook : Option<NotebookStats>
+    }
+
+    // Why a file couldn't be parsed, so that the users of the library can tell the categories apart
+    #[derive(Debug,PartialEq,Clone)]
+    pub enum FaultyReason {
+        // It couldn't be opened or read, with the message of the io error
+        Io(std::io::ErrorKind, String),
+        // It is not valid UTF-8, with '--strict-encoding'
+        InvalidEncoding,
+        // Its parsing took longer than the seconds of '--file-timeout'
+        TimedOut(usize),
+        // There is no language with its name in the language map
+        UnknownLanguage(String),
+        // The parser panicked on it, with the message of the panic. The file is left out instead of the parser thread dying.
+        Panicked(String),
+        // It is a jupyter notebook that isn't valid json, with what was wrong
+        InvalidNotebook(String),
+        // It is an entry of an archive that inflates to more than its limit
+        OverInflated
+    }
+
+    // A file that is left out of the results, because it couldn't be parsed
+    #[derive(Debug,PartialEq,Clone)]
+    pub struct FaultyFile {
+        pub path: PathBuf,
+        pub language_name: String,
+        pub error: FaultyReason,
+        pub bytes: u64
+    }
+
+    #[derive(Debug,PartialEq,Clone,Copy)]
+    pub enum SkippedReason {
+        // Smaller than the bytes of '--min-size'
+        BelowMinSize(u64),
+        // Larger than the bytes of '--max-size'
+        AboveMaxSize(u64)
+    }
+
+    // A file of a language of interest that is left out before it is parsed, because of its size
+    #[derive(Debug,PartialEq,Clone)]
+    pub struct SkippedFile {
+        pub path: PathBuf,
+        pub language_name: String,
+        pub reason: SkippedReason,
+        pub bytes: u64
+    }
+
+    // A directory of the search that couldn't be listed, so that none of its files are in the results
+    #[derive(Debug,PartialEq,Clone)]
+    pub struct UnreadableDir {
+        pub path: PathBuf,
+        pub permission_denied: bool,
+        pub error: String
+    }
+
+    impl UnreadableDir {
+        pub fn new(path: &Path, error: &std::io::Error) -> Self {
+            UnreadableDir {path: path.to_path_buf(), permission_denied: error.kind() == std::io::ErrorKind::PermissionDenied, error: error.to_string()}
+        }
+    }
+
+    #[derive(Debug,PartialEq,Clone)]
+    pub struct FileSummary {
+        pub path: PathBuf,
+        pub lines: usize,
+        pub bytes: usize
+    }
+
+    #[derive(Debug,PartialEq,Clone)]
+    pub struct FileHash {
+        pub hash: [u8; 32],
+        pub summary: FileSummary
+    }
+
+    #[derive(Debug,PartialEq,Clone)]
+    pub struct FileCodeLines {
+        pub path: PathBuf,
+        // As in 'FileStats::code_line_hashes'
+        pub line_hashes: Vec<(u64, usize)>
+    }
+
+    // A marker of '--todos' found in a comment, e.g. 'FIXME: the cache is never cleared'
+    #[derive(Debug,PartialEq,Clone)]
+    pub struct TodoItem {
+        pub marker: String,
+        pub path: PathBuf,
+        // Starting from 1
+        pub line: usize,
+        // What follows the marker in the comment, without a leading colon. May be empty
+        pub text: String
     }
 
     impl Clone for Keyword {
         fn clone(&self) -> Self {
             Keyword {
                 descriptive_name : self.descriptive_name.to_owned(),
-                aliases : self.aliases.to_owned()
+                aliases : self.aliases.to_owned(),
+                patterns : self.patterns.to_owned(),
+                category : self.category.to_owned(),
+                case_insensitive : self.case_insensitive,
+                scope : self.scope
+            }
+        }
+    }
+
+    impl Language {
+        pub fn new(name: String, extensions: Vec<String>, string_symbols: Vec<String>, comment_symbols: Vec<String>,
+            multiline_comment_symbols: Vec<(String,String)>, keywords: Vec<Keyword>) -> Self 
+        {
+            Language {
+                name,
+                extensions,
+                string_symbols,
+                comment_symbols,
+                multiline_comment_symbols,
+                supports_nested_comments: false,
+                supports_char_literals: false,
+                string_delimiters: Vec::new(),
+                doc_comment_symbols: Vec::new(),
+                branch_keywords: Vec::new(),
+                statement_symbols: Vec::new(),
+                heredoc_symbols: Vec::new(),
+                embedded_regions: Vec::new(),
+                category: FileCategory::Code,
+                keywords 
+            }
+        }
+
+        // The symbols of the first kind of multiline comment, the only one for most languages
+        pub fn multiline_start_symbol(&self) -> Option<&str> {
+            self.multiline_comment_symbols.first().map(|x| x.0.as_str())
+        }
+
+        pub fn multiline_end_symbol(&self) -> Option<&str> {
+            self.multiline_comment_symbols.first().map(|x| x.1.as_str())
+        }
+
+        pub fn multiline_start_len(&self) -> usize {
+            self.multiline_start_symbol().map_or(0, str::len)
+        }
+
+        pub fn multiline_end_len(&self) -> usize {
+            self.multiline_end_symbol().map_or(0, str::len)
+        }
+
+        pub fn supports_multiline_comments(&self) -> bool {
+            !self.multiline_comment_symbols.is_empty()
+        }
+
+        // The languages of the documentation formats are the only ones without string symbols, which the language files require,
+        // so a language file with the same name is not mistaken for one
+        pub fn is_documentation(&self) -> bool {
+            self.string_symbols.is_empty() && is_documentation_language(&self.name)
+        }
+
+        pub fn compile_keyword_patterns(&mut self) -> Result<(), RegexError> {
+            for keyword in self.keywords.iter_mut() {
+                let compile = if keyword.case_insensitive {Regex::new_case_insensitive} else {Regex::new};
+                keyword.patterns = keyword.aliases.iter().map(|x| compile(x)).collect::<Result<Vec<_>,_>>()?;
+            }
+            Ok(())
+        }
+    }
+
+    impl LanguageBuilder {
+        pub fn new(name: &str) -> Self {
+            LanguageBuilder {
+                language: Language::new(name.to_owned(), Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new()),
+                multiline_start_symbols: Vec::new(),
+                multiline_end_symbols: Vec::new()
+            }
+        }
+
+        pub fn set_extensions(&mut self, extensions: Vec<String>) -> &mut Self {
+            self.language.extensions = extensions;
+            self
+        }
+
+        pub fn set_string_symbols(&mut self, string_symbols: Vec<String>) -> &mut Self {
+            self.language.string_symbols = string_symbols;
+            self
+        }
+
+        pub fn set_comment_symbols(&mut self, comment_symbols: Vec<String>) -> &mut Self {
+            self.language.comment_symbols = comment_symbols;
+            self
+        }
+
+        // The start and the end symbol of each kind of multiline comment are at the same index
+        pub fn set_multiline_comment_symbols(&mut self, start_symbols: Vec<String>, end_symbols: Vec<String>) -> &mut Self {
+            self.multiline_start_symbols = start_symbols;
+            self.multiline_end_symbols = end_symbols;
+            self
+        }
+
+        pub fn set_nested_comments(&mut self, supports_nested_comments: bool) -> &mut Self {
+            self.language.supports_nested_comments = supports_nested_comments;
+            self
+        }
+
+        pub fn set_char_literals(&mut self, supports_char_literals: bool) -> &mut Self {
+            self.language.supports_char_literals = supports_char_literals;
+            self
+        }
+
+        pub fn set_string_delimiters(&mut self, string_delimiters: Vec<StringDelimiter>) -> &mut Self {
+            self.language.string_delimiters = string_delimiters;
+            self
+        }
+
+        pub fn set_doc_comment_symbols(&mut self, doc_comment_symbols: Vec<String>) -> &mut Self {
+            self.language.doc_comment_symbols = doc_comment_symbols;
+            self
+        }
+
+        pub fn set_branch_keywords(&mut self, branch_keywords: Vec<String>) -> &mut Self {
+            self.language.branch_keywords = branch_keywords;
+            self
+        }
+
+        pub fn set_statement_symbols(&mut self, statement_symbols: Vec<String>) -> &mut Self {
+            self.language.statement_symbols = statement_symbols;
+            self
+        }
+
+        pub fn set_heredoc_symbols(&mut self, heredoc_symbols: Vec<String>) -> &mut Self {
+            self.language.heredoc_symbols = heredoc_symbols;
+            self
+        }
+
+        pub fn set_embedded_regions(&mut self, embedded_regions: Vec<EmbeddedRegion>) -> &mut Self {
+            self.language.embedded_regions = embedded_regions;
+            self
+        }
+
+        pub fn set_category(&mut self, category: FileCategory) -> &mut Self {
+            self.language.category = category;
+            self
+        }
+
+        pub fn set_keywords(&mut self, keywords: Vec<Keyword>) -> &mut Self {
+            self.language.keywords = keywords;
+            self
+        }
+
+        pub fn build(&self) -> Result<Language, LanguageBuildError> {
+            let language = &self.language;
+            if language.name.trim().is_empty() {
+                return Err(LanguageBuildError::EmptyName);
+            }
+            if language.string_symbols.is_empty() {
+                return Err(LanguageBuildError::NoStringSymbols);
+            }
+            if self.multiline_start_symbols.len() != self.multiline_end_symbols.len() {
+                return Err(LanguageBuildError::UnmatchedMultilineSymbols(self.multiline_start_symbols.len(), self.multiline_end_symbols.len()));
+            }
+            let symbols : [(&'static str, &[String]); 8] = [("string", &language.string_symbols), ("comment", &language.comment_symbols),
+                    ("multiline comment", &self.multiline_start_symbols), ("multiline comment", &self.multiline_end_symbols),
+                    ("doc comment", &language.doc_comment_symbols), ("branch keyword", &language.branch_keywords),
+                    ("statement", &language.statement_symbols), ("heredoc", &language.heredoc_symbols)];
+            if let Some((kind, _)) = symbols.iter().find(|(_, x)| x.iter().any(|x| x.trim().is_empty())) {
+                return Err(LanguageBuildError::EmptySymbol(kind));
+            }
+            if language.extensions.iter().any(|x| x.trim().is_empty()) {
+                return Err(LanguageBuildError::EmptySymbol("extension"));
+            }
+            if language.keywords.iter().any(|x| x.aliases.is_empty() || x.aliases.iter().any(|x| x.trim().is_empty())) {
+                return Err(LanguageBuildError::EmptySymbol("keyword alias"));
+            }
+            let mut names = HashSet::new();
+            if let Some(keyword) = language.keywords.iter().find(|x| !names.insert(x.descriptive_name.as_str())) {
+                return Err(LanguageBuildError::DuplicateKeyword(keyword.descriptive_name.to_owned()));
+            }
+
+            let mut language = language.clone();
+            language.multiline_comment_symbols = self.multiline_start_symbols.iter().cloned().zip(self.multiline_end_symbols.iter().cloned()).collect();
+            Ok(language)
+        }
+    }
+
+    impl Formatted for LanguageBuildError {
+        fn formatted(&self) -> ColoredString {
+            match self {
+                Self::EmptyName => "The name of the language is empty.".red(),
+                Self::NoStringSymbols => "The language has no string symbols.".red(),
+                Self::EmptySymbol(kind) => format!("The language has an empty {} symbol.", kind).red(),
+                Self::UnmatchedMultilineSymbols(starts, ends) => format!("The language has {} start and {} end symbols of multiline comments.",
+                        starts, ends).red(),
+                Self::DuplicateKeyword(name) => format!("The language has more than one keyword named '{}'.", name).red()
+            }
+        }
+    }
+
+    impl_display_from_formatted!(LanguageBuildError);
+
+    impl std::error::Error for LanguageBuildError {}
+
+    impl KeywordScope {
+        pub fn parse(s: &str) -> Option<Self> {
+            match s.trim().to_lowercase().as_str() {
+                "code" => Some(KeywordScope::Code),
+                "comments" => Some(KeywordScope::Comments),
+                "all" => Some(KeywordScope::All),
+                _ => None
             }
         }
-    }
 
-    impl Language {
-        pub fn new(name: String, extensions: Vec<String>, string_symbols: Vec<String>, comment_symbols: Vec<String>,
-            multiline_comment_start_symbol: Option<String>, multiline_comment_end_symbol: Option<String>,
-            keywords: Vec<Keyword>) -> Self 
-        {
-            Language {
-                name,
-                extensions,
-                string_symbols,
-                comment_symbols,
-                multiline_comment_start_symbol,
-                multiline_comment_end_symbol,
-                keywords 
+        pub fn as_str(&self) -> &'static str {
+            match self {
+                KeywordScope::Code => "code",
+                KeywordScope::Comments => "comments",
+                KeywordScope::All => "all"
             }
         }
 
-        pub fn multiline_start_len(&self) -> usize {
-            if let Some(x) = &self.multiline_comment_start_symbol {
-                x.len()
-            } else {
-                0
-            }
+        pub fn includes_code(&self) -> bool {
+            *self != KeywordScope::Comments
         }
 
-        pub fn multiline_end_len(&self) -> usize {
-            if let Some(x) = &self.multiline_comment_end_symbol {
-                x.len()
-            } else {
-                0
+        pub fn includes_comments(&self) -> bool {
+            *self != KeywordScope::Code
+        }
+    }
+
+    impl FileCategory {
+        pub fn parse(s: &str) -> Option<Self> {
+            match s.trim().to_lowercase().as_str() {
+                "code" => Some(FileCategory::Code),
+                "markup" => Some(FileCategory::Markup),
+                "config" => Some(FileCategory::Config),
+                "data" => Some(FileCategory::Data),
+                "docs" => Some(FileCategory::Docs),
+                _ => None
             }
         }
 
-        pub fn supports_multiline_comments(&self) -> bool {
-            self.multiline_comment_start_symbol.is_some()
+        pub fn as_str(&self) -> &'static str {
+            match self {
+                FileCategory::Code => "code",
+                FileCategory::Markup => "markup",
+                FileCategory::Config => "config",
+                FileCategory::Data => "data",
+                FileCategory::Docs => "docs"
+            }
         }
     }
 
     impl LanguageContentInfo {
-        pub fn new(lines: usize, code_lines: usize, keyword_occurences: HashMap<String,usize>) -> Self {
+        pub fn new(lines: usize, code_lines: usize, keyword_occurences: impl IntoIterator<Item = (String,usize)>) -> Self {
             LanguageContentInfo {
                 lines,
                 code_lines,
-                keyword_occurences
+                keyword_occurences: keyword_occurences.into_iter().collect(),
+                empty_files: 0,
+                whitespace_only_files: 0,
+                keyword_co_occurrences: HashMap::new(),
+                keyword_hotspots: KeywordHotspots::new(),
+                doc_lines: 0,
+                complexity: 0,
+                logical_lines: 0,
+                todos: Vec::new(),
+                prose: ProseStats::default(),
+                lines_distribution: LinesDistribution::default(),
+                file_summaries: Vec::new(),
+                file_hashes: Vec::new(),
+                file_code_lines: Vec::new(),
+                license_counts: LicenseCounts::new(),
+                author_counts: AuthorCounts::new(),
+                hygiene: HygieneStats::default(),
+                max_line_length: 0,
+                long_lines: 0,
+                indentation: IndentationCounts::new(),
+                test_stats: DirStats::default(),
+                generated_stats: DirStats::default(),
+                minified_stats: DirStats::default(),
+                notebooks: NotebookStats::default(),
+                embedded_stats: DirStats::default(),
+                plugin_metrics: PluginMetrics::new()
             }
         }
 
@@ -614,24 +2672,149 @@ pub mod domain {
             LanguageContentInfo {
                 lines,
                 code_lines: 0,
-                keyword_occurences: HashMap::new()
+                keyword_occurences: KeywordOccurences::new(),
+                empty_files: 0,
+                whitespace_only_files: 0,
+                keyword_co_occurrences: HashMap::new(),
+                keyword_hotspots: KeywordHotspots::new(),
+                doc_lines: 0,
+                complexity: 0,
+                logical_lines: 0,
+                todos: Vec::new(),
+                prose: ProseStats::default(),
+                lines_distribution: LinesDistribution::default(),
+                file_summaries: Vec::new(),
+                file_hashes: Vec::new(),
+                file_code_lines: Vec::new(),
+                license_counts: LicenseCounts::new(),
+                author_counts: AuthorCounts::new(),
+                hygiene: HygieneStats::default(),
+                max_line_length: 0,
+                long_lines: 0,
+                indentation: IndentationCounts::new(),
+                test_stats: DirStats::default(),
+                generated_stats: DirStats::default(),
+                minified_stats: DirStats::default(),
+                notebooks: NotebookStats::default(),
+                embedded_stats: DirStats::default(),
+                plugin_metrics: PluginMetrics::new()
             }
         }
         
-        pub fn add_file_stats(&mut self, other: FileStats) {
+        pub fn add_file_stats(&mut self, mut other: FileStats) {
+            self.lines_distribution.add_file(other.lines, other.code_lines, other.lines == 0 || !other.has_content);
+            if other.lines == 0 {
+                self.empty_files += 1;
+            } else if !other.has_content {
+                self.whitespace_only_files += 1;
+            }
+            if let Some(hygiene) = other.hygiene.take() {
+                self.hygiene.add_file(&hygiene, other.lines);
+            }
+            if let Some(notebook) = other.notebook.take() {
+                self.notebooks.add(&notebook);
+            }
+            self.add_lines_of(other);
+        }
+
+        // A region of a file of another language, whose lines are counted as the ones of this language but not as a file of it
+        pub fn add_region_stats(&mut self, other: FileStats, bytes: usize) {
+            self.embedded_stats.add_file_stats(&other, bytes);
+            self.add_lines_of(other);
+        }
+
+        fn add_lines_of(&mut self, other: FileStats) {
             self.lines += other.lines;
             self.code_lines += other.code_lines;
+            self.doc_lines += other.doc_lines;
+            self.complexity += other.complexity;
+            self.logical_lines += other.logical_lines;
+            self.prose.add(&other.prose);
+            self.todos.extend(other.todos);
             for (k,v) in other.keyword_occurences.iter() {
                 *self.keyword_occurences.get_mut(k).unwrap() += *v;
             }
+            for (k,v) in other.plugin_metrics {
+                *self.plugin_metrics.entry(k).or_insert(0) += v;
+            }
+            self.max_line_length = self.max_line_length.max(other.max_line_length);
+            self.long_lines += other.long_lines;
         }
         
         pub fn add_content_info(&mut self, other: &LanguageContentInfo) {
             self.lines += other.lines;
             self.code_lines += other.code_lines;
+            self.doc_lines += other.doc_lines;
+            self.complexity += other.complexity;
+            self.logical_lines += other.logical_lines;
+            self.prose.add(&other.prose);
+            self.lines_distribution.add(&other.lines_distribution);
+            self.file_summaries.extend(other.file_summaries.iter().cloned());
+            self.file_hashes.extend(other.file_hashes.iter().cloned());
+            self.file_code_lines.extend(other.file_code_lines.iter().cloned());
+            self.hygiene.add(&other.hygiene);
+            self.max_line_length = self.max_line_length.max(other.max_line_length);
+            self.long_lines += other.long_lines;
+            for (extension, stats) in other.indentation.iter() {
+                self.indentation.entry(extension.clone()).or_default().add(stats);
+            }
+            for (extension, counts) in other.license_counts.iter() {
+                let own_counts = self.license_counts.entry(extension.clone()).or_default();
+                for (license, files) in counts.iter() {
+                    *own_counts.entry(license.clone()).or_insert(0) += *files;
+                }
+            }
+            for (extension, counts) in other.author_counts.iter() {
+                let own_counts = self.author_counts.entry(extension.clone()).or_default();
+                for (author, lines) in counts.iter() {
+                    *own_counts.entry(author.clone()).or_insert(0) += *lines;
+                }
+            }
+            self.test_stats.add(&other.test_stats);
+            self.generated_stats.add(&other.generated_stats);
+            self.minified_stats.add(&other.minified_stats);
+            self.notebooks.add(&other.notebooks);
+            self.embedded_stats.add(&other.embedded_stats);
+            self.todos.extend(other.todos.iter().cloned());
+            self.empty_files += other.empty_files;
+            self.whitespace_only_files += other.whitespace_only_files;
             for (k,v) in other.keyword_occurences.iter() {
                 *self.keyword_occurences.get_mut(k).unwrap() += *v;
             }
+            for (k,v) in other.keyword_co_occurrences.iter() {
+                *self.keyword_co_occurrences.entry(k.clone()).or_insert(0) += *v;
+            }
+            for (k,(v,path)) in other.keyword_hotspots.iter() {
+                add_keyword_hotspot(&mut self.keyword_hotspots, k, *v, path);
+            }
+            for (k,v) in other.plugin_metrics.iter() {
+                *self.plugin_metrics.entry(k.clone()).or_insert(0) += *v;
+            }
+        }
+
+        // Only the keywords named in 'keyword_names' are taken into account, or all of them if it is empty
+        pub fn add_keyword_co_occurrences(&mut self, file_keyword_occurences: &KeywordOccurences, keyword_names: &[String]) {
+            let mut present_keywords = file_keyword_occurences.iter()
+                    .filter(|(name, num)| **num > 0 && (keyword_names.is_empty() || keyword_names.contains(&name.to_lowercase())))
+                    .map(|x| x.0)
+                    .collect::<Vec<_>>();
+            present_keywords.sort();
+
+            for (i, first) in present_keywords.iter().enumerate() {
+                for second in &present_keywords[i..] {
+                    *self.keyword_co_occurrences.entry(((*first).to_owned(), (*second).to_owned())).or_insert(0) += 1;
+                }
+            }
+        }
+
+        pub fn add_keyword_hotspots(&mut self, file_keyword_occurences: &KeywordOccurences, path: &Path) {
+            for (name, num) in file_keyword_occurences.iter().filter(|x| *x.1 > 0) {
+                add_keyword_hotspot(&mut self.keyword_hotspots, name, *num, path);
+            }
+        }
+
+        pub fn blank_files(&self) -> usize {
+            self.empty_files + self.whitespace_only_files
         }
     }
 
@@ -640,7 +2823,32 @@ pub mod domain {
             LanguageContentInfo {
                 lines : 0,
                 code_lines : 0,
-                keyword_occurences : get_keyword_stats_map(ext)
+                keyword_occurences : get_keyword_stats_map(ext),
+                empty_files : 0,
+                whitespace_only_files : 0,
+                keyword_co_occurrences : HashMap::new(),
+                keyword_hotspots : KeywordHotspots::new(),
+                doc_lines : 0,
+                complexity : 0,
+                logical_lines : 0,
+                todos : Vec::new(),
+                prose : ProseStats::default(),
+                lines_distribution : LinesDistribution::default(),
+                file_summaries : Vec::new(),
+                file_hashes : Vec::new(),
+                file_code_lines : Vec::new(),
+                license_counts : LicenseCounts::new(),
+                author_counts : AuthorCounts::new(),
+                hygiene : HygieneStats::default(),
+                max_line_length : 0,
+                long_lines : 0,
+                indentation : IndentationCounts::new(),
+                test_stats : DirStats::default(),
+                generated_stats : DirStats::default(),
+                minified_stats : DirStats::default(),
+                notebooks : NotebookStats::default(),
+                embedded_stats : DirStats::default(),
+                plugin_metrics : PluginMetrics::new()
             }
         }
     }
@@ -650,7 +2858,218 @@ pub mod domain {
             LanguageContentInfo {
                 lines : stats.lines,
                 code_lines : stats.code_lines,
-                keyword_occurences : stats.keyword_occurences
+                empty_files : if stats.lines == 0 {1} else {0},
+                whitespace_only_files : if stats.lines != 0 && !stats.has_content {1} else {0},
+                keyword_occurences : stats.keyword_occurences,
+                keyword_co_occurrences : HashMap::new(),
+                keyword_hotspots : KeywordHotspots::new(),
+                doc_lines : stats.doc_lines,
+                complexity : stats.complexity,
+                logical_lines : stats.logical_lines,
+                lines_distribution : LinesDistribution {lines: vec![stats.lines], code_lines: vec![stats.code_lines],
+                        blank: vec![stats.lines == 0 || !stats.has_content]},
+                file_summaries : Vec::new(),
+                file_hashes : Vec::new(),
+                file_code_lines : Vec::new(),
+                license_counts : LicenseCounts::new(),
+                author_counts : AuthorCounts::new(),
+                hygiene : HygieneStats::default(),
+                max_line_length : 0,
+                long_lines : 0,
+                indentation : IndentationCounts::new(),
+                test_stats : DirStats::default(),
+                generated_stats : DirStats::default(),
+                minified_stats : DirStats::default(),
+                notebooks : stats.notebook.unwrap_or_default(),
+                embedded_stats : DirStats::default(),
+                todos : stats.todos,
+                prose : stats.prose,
+                plugin_metrics : stats.plugin_metrics
+            }
+        }
+    }
+
+    impl IndentationStats {
+        pub fn add(&mut self, other: &IndentationStats) {
+            self.tab_lines += other.tab_lines;
+            self.space_lines += other.space_lines;
+            self.mixed_lines += other.mixed_lines;
+            for (count, other_count) in self.width_counts.iter_mut().zip(other.width_counts.iter()) {
+                *count += other_count;
+            }
+        }
+
+        // The most common width, the smallest of the ties. None without any indentation with spaces
+        pub fn dominant_width(&self) -> Option<usize> {
+            let (index, count) = self.width_counts.iter().enumerate().fold((0, 0), |max, (i, x)| if *x > max.1 {(i, *x)} else {max});
+            if count == 0 {None} else {Some(index + 1)}
+        }
+    }
+
+    impl HygieneStats {
+        pub fn add_file(&mut self, hygiene: &FileHygiene, lines: usize) {
+            self.bom_files += usize::from(hygiene.has_bom);
+            self.shebang_files += usize::from(hygiene.has_shebang);
+            match (hygiene.lf_lines > 0, hygiene.crlf_lines > 0) {
+                (true, true) => self.mixed_files += 1,
+                (true, false) => self.lf_files += 1,
+                (false, true) => self.crlf_files += 1,
+                (false, false) => ()
+            }
+            self.no_final_newline_files += usize::from(lines > 0 && !hygiene.ends_with_newline);
+        }
+
+        pub fn add(&mut self, other: &HygieneStats) {
+            self.bom_files += other.bom_files;
+            self.shebang_files += other.shebang_files;
+            self.lf_files += other.lf_files;
+            self.crlf_files += other.crlf_files;
+            self.mixed_files += other.mixed_files;
+            self.no_final_newline_files += other.no_final_newline_files;
+        }
+    }
+
+    impl ProseStats {
+        pub fn add(&mut self, other: &ProseStats) {
+            self.words += other.words;
+            self.headings += other.headings;
+            self.code_blocks += other.code_blocks;
+        }
+    }
+
+    impl LinesDistribution {
+        pub fn add_file(&mut self, lines: usize, code_lines: usize, is_blank: bool) {
+            self.lines.push(lines);
+            self.code_lines.push(code_lines);
+            self.blank.push(is_blank);
+        }
+
+        pub fn add(&mut self, other: &LinesDistribution) {
+            self.lines.extend(&other.lines);
+            self.code_lines.extend(&other.code_lines);
+            self.blank.extend(&other.blank);
+        }
+
+        pub fn without_blank_files(&self) -> LinesDistribution {
+            let mut distribution = LinesDistribution::default();
+            for i in (0..self.lines.len()).filter(|x| !self.blank[*x]) {
+                distribution.add_file(self.lines[i], self.code_lines[i], false);
+            }
+            distribution
+        }
+
+        // None if no files were parsed
+        pub fn average_lines(&self) -> Option<f64> {
+            average(&self.lines)
+        }
+
+        pub fn average_code_lines(&self) -> Option<f64> {
+            average(&self.code_lines)
+        }
+
+        pub fn median_lines(&self) -> Option<f64> {
+            median(&self.lines)
+        }
+
+        pub fn median_code_lines(&self) -> Option<f64> {
+            median(&self.code_lines)
+        }
+    }
+
+    fn average(values: &[usize]) -> Option<f64> {
+        if values.is_empty() {None} else {Some(values.iter().sum::<usize>() as f64 / values.len() as f64)}
+    }
+
+    // The mean of the 2 middle values, if there is an even number of them
+    fn median(values: &[usize]) -> Option<f64> {
+        let mut sorted = values.to_vec();
+        sorted.sort_unstable();
+        let middle = sorted.len() / 2;
+        match sorted.len() {
+            0 => None,
+            x if x % 2 == 0 => Some((sorted[middle - 1] + sorted[middle]) as f64 / 2.0),
+            _ => Some(sorted[middle] as f64)
+        }
+    }
+
+    // Of the files with as many occurrences the first by path is kept, so that the order the threads parsed them in doesn't matter
+    pub fn add_keyword_hotspot(hotspots: &mut KeywordHotspots, name: &str, occurrences: usize, path: &Path) {
+        match hotspots.get_mut(name) {
+            Some((num, hotspot_path)) => {
+                if occurrences > *num || (occurrences == *num && path < hotspot_path.as_path()) {
+                    *num = occurrences;
+                    *hotspot_path = path.to_owned();
+                }
+            },
+            None => {hotspots.insert(name.to_owned(), (occurrences, path.to_owned()));}
+        }
+    }
+
+    impl FaultyReason {
+        pub fn message(&self) -> String {
+            match self {
+                FaultyReason::Io(_, message) => message.to_owned(),
+                FaultyReason::InvalidEncoding => "stream did not contain valid UTF-8".to_owned(),
+                FaultyReason::TimedOut(x) => format!("parsing took longer than {} secs", x),
+                FaultyReason::UnknownLanguage(x) => format!("there is no language named '{}'", x),
+                FaultyReason::Panicked(x) => format!("the parser failed: {}", x),
+                FaultyReason::InvalidNotebook(x) => format!("the notebook is not valid: {}", x),
+                FaultyReason::OverInflated => "the archive inflates to more than its limit".to_owned()
+            }
+        }
+    }
+
+    impl From<std::io::Error> for FaultyReason {
+        fn from(error: std::io::Error) -> Self {
+            FaultyReason::Io(error.kind(), error.to_string())
+        }
+    }
+
+    impl FaultyFile {
+        pub fn new(path: PathBuf, language_name: String, error: FaultyReason, bytes: u64) -> Self {
+            FaultyFile {path, language_name, error, bytes}
+        }
+    }
+
+    impl SkippedReason {
+        pub fn message(&self) -> String {
+            match self {
+                SkippedReason::BelowMinSize(x) => format!("smaller than the minimum size of {} bytes", x),
+                SkippedReason::AboveMaxSize(x) => format!("larger than the maximum size of {} bytes", x)
+            }
+        }
+    }
+
+    impl DirStats {
+        pub fn add_file_stats(&mut self, file_stats: &FileStats, bytes: usize) {
+            self.files += 1;
+            self.lines += file_stats.lines;
+            self.code_lines += file_stats.code_lines;
+            self.bytes += bytes;
+        }
+
+        pub fn add(&mut self, other: &DirStats) {
+            self.files += other.files;
+            self.lines += other.lines;
+            self.code_lines += other.code_lines;
+            self.bytes += other.bytes;
+        }
+    }
+
+    impl NotebookStats {
+        pub fn add(&mut self, other: &NotebookStats) {
+            self.notebooks += other.notebooks;
+            self.code_cells += other.code_cells;
+            self.markdown_cells += other.markdown_cells;
+            self.markdown_lines += other.markdown_lines;
+        }
+    }
+
+    impl LanguageReference {
+        pub fn new(extra_lines_percentage: f64, lines_per_file: usize) -> Self {
+            LanguageReference {
+                extra_lines_percentage,
+                lines_per_file
             }
         }
     }
@@ -679,7 +3098,24 @@ pub mod domain {
             FileStats {
                 lines : 0,
                 code_lines : 0,
-                keyword_occurences : hashmap![]
+                keyword_occurences : KeywordOccurences::new(),
+                has_content : false,
+                doc_lines : 0,
+                complexity : 0,
+                logical_lines : 0,
+                todos : Vec::new(),
+                prose : ProseStats::default(),
+                plugin_metrics : PluginMetrics::new(),
+                content_hash : None,
+                code_line_hashes : Vec::new(),
+                license : None,
+                hygiene : None,
+                max_line_length : 0,
+                long_lines : 0,
+                indentation : None,
+                is_generated : false,
+                is_minified : false,
+                notebook : None
             }
         }
 
@@ -687,7 +3123,24 @@ pub mod domain {
             FileStats {
                 lines : 0,
                 code_lines : 0,
-                keyword_occurences : get_stats_map(keywords)
+                keyword_occurences : get_stats_map(keywords),
+                has_content : false,
+                doc_lines : 0,
+                complexity : 0,
+                logical_lines : 0,
+                todos : Vec::new(),
+                prose : ProseStats::default(),
+                plugin_metrics : PluginMetrics::new(),
+                content_hash : None,
+                code_line_hashes : Vec::new(),
+                license : None,
+                hygiene : None,
+                max_line_length : 0,
+                long_lines : 0,
+                indentation : None,
+                is_generated : false,
+                is_minified : false,
+                notebook : None
             }
         }
 
@@ -699,21 +3152,79 @@ pub mod domain {
             self.code_lines += 1;
         }
 
+        pub fn incr_doc_lines(&mut self) {
+            self.doc_lines += 1;
+        }
+
         pub fn incr_keyword(&mut self, keyword_name:&str) {
-            *self.keyword_occurences.get_mut(keyword_name).unwrap() += 1;
+            match self.keyword_occurences.get_mut(keyword_name) {
+                Some(x) => *x += 1,
+                None => {self.keyword_occurences.insert(keyword_name.to_owned(), 1);}
+            }
         }
     }
     
-    fn get_keyword_stats_map(extension: &Language) -> HashMap<String,usize> {
-        let mut map = HashMap::<String,usize>::new();
+    // The documentation formats that are added to the languages with '--docs', along with their extensions
+    pub const DOCUMENTATION_FORMATS : [(&str, &[&str]); 4] = [
+        ("Markdown", &["md", "markdown"]), ("reStructuredText", &["rst"]), ("AsciiDoc", &["adoc", "asciidoc"]), ("Text", &["txt"])
+    ];
+
+    // The fenced code blocks of markdown are in the language of the word after the fence, e.g. '```rust'
+    pub const MARKDOWN_FENCES : [&str; 2] = ["```", "~~~"];
+
+    // The markup, data and documentation formats, which are left out of the 'all-code' group of '--ext'
+    pub const MARKUP_EXTENSIONS : [&str; 19] = [
+        "md", "markdown", "rst", "adoc", "asciidoc", "txt", "html", "htm", "xhtml", "xml", "svg", "json", "jsonc", "yaml", "yml",
+        "toml", "ini", "csv", "tsv"
+    ];
+
+    pub fn is_documentation_language(name: &str) -> bool {
+        DOCUMENTATION_FORMATS.iter().any(|x| x.0 == name)
+    }
+
+    // Well-known names of files without an extension, mapped to the extension of the language they are written in.
+    // The names are compared case-insensitively.
+    pub const FILE_NAME_IDENTIFIERS : [(&str, &str); 10] = [
+        ("makefile", "mk"), ("gnumakefile", "mk"), ("dockerfile", "dockerfile"), ("containerfile", "dockerfile"),
+        ("rakefile", "rb"), ("gemfile", "rb"), ("podfile", "rb"), ("vagrantfile", "rb"), ("jenkinsfile", "groovy"),
+        ("justfile", "just")
+    ];
+
+    // Interpreters of shebang lines (without their version), mapped to the extension of the language they run.
+    pub const SHEBANG_IDENTIFIERS : [(&str, &str); 20] = [
+        ("python", "py"), ("pypy", "py"), ("node", "js"), ("nodejs", "js"), ("deno", "ts"), ("ts-node", "ts"),
+        ("php", "php"), ("rscript", "r"), ("swift", "swift"), ("kotlin", "kts"), ("sh", "sh"), ("bash", "sh"),
+        ("zsh", "sh"), ("dash", "sh"), ("ksh", "sh"), ("ruby", "rb"), ("perl", "pl"), ("lua", "lua"),
+        ("groovy", "groovy"), ("tclsh", "tcl")
+    ];
+
+    pub fn identifier_from_file_name(file_name: &str) -> Option<&'static str> {
+        let file_name = file_name.to_lowercase();
+        FILE_NAME_IDENTIFIERS.iter().find(|x| x.0 == file_name).map(|x| x.1)
+    }
+
+    // Supports both '#!/usr/bin/python3' and '#!/usr/bin/env python3', with or without arguments
+    pub fn identifier_from_shebang(line: &str) -> Option<&'static str> {
+        let mut words = line.strip_prefix("#!")?.split_whitespace();
+        let mut interpreter = words.next()?.rsplit('/').next()?;
+        if interpreter == "env" {
+            interpreter = words.find(|x| !x.starts_with('-'))?;
+        }
+        let interpreter = interpreter.to_lowercase();
+        let interpreter = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+        SHEBANG_IDENTIFIERS.iter().find(|x| x.0 == interpreter).map(|x| x.1)
+    }
+
+    fn get_keyword_stats_map(extension: &Language) -> KeywordOccurences {
+        let mut map = KeywordOccurences::new();
         for k in &extension.keywords {
             map.insert(k.descriptive_name.to_owned(), 0);
         }
         map
     }
 
-    fn get_stats_map(keywords: &[Keyword]) -> HashMap<String,usize> {
-        let mut map = HashMap::<String,usize>::new();
+    fn get_stats_map(keywords: &[Keyword]) -> KeywordOccurences {
+        let mut map = KeywordOccurences::new();
         for k in keywords {
             map.insert(k.descriptive_name.to_owned(), 0);
         }
@@ -739,7 +3250,7 @@ mod tests {
         ];
         let f = FinalStats::new(40, 4000, 3000, 200000);
         let ef = FinalStats::new_extended(40, 4000, 3000, 1000, 200000, 5000);
-        let cf = FinalStats::calculate(&content_info_map, &languages_metadata_map);
+        let cf = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
         let customf = FinalStats {
             files: 40,
             lines: 4000,
@@ -769,7 +3280,7 @@ mod tests {
         ];
         let f = FinalStats::new(49, 4000, 3000, 2417403);
         let ef = FinalStats::new_extended(49, 4000, 3000, 1000, 2417403, 49334);
-        let cf = FinalStats::calculate(&content_info_map, &languages_metadata_map);
+        let cf = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
         let customf = FinalStats {
             files: 49,
             lines: 4000,
@@ -786,4 +3297,675 @@ mod tests {
         assert_eq!(customf, ef);
         assert_eq!(customf, cf);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_FinalStats_ignoring_empty_files() {
+        let mut content_info = LanguageContentInfo::new(2000, 1400, hashmap![]);
+        content_info.add_file_stats(FileStats::default());
+        let mut whitespace_only_stats = FileStats::default();
+        whitespace_only_stats.incr_lines();
+        content_info.add_file_stats(whitespace_only_stats);
+        assert_eq!(1, content_info.empty_files);
+        assert_eq!(1, content_info.whitespace_only_files);
+
+        let content_info_map = hashmap!["a".to_owned() => content_info];
+        let languages_metadata_map = hashmap!["a".to_owned() => LanguageMetadata::new(10, 80000)];
+        assert_eq!(8000, FinalStats::calculate(&content_info_map, &languages_metadata_map, false).bytes_average_size);
+        assert_eq!(10000, FinalStats::calculate(&content_info_map, &languages_metadata_map, true).bytes_average_size);
+    }
+
+    #[test]
+    fn test_lines_distribution_ignoring_empty_files() {
+        // A file of 2 lines, an empty one and a whitespace-only one
+        let mut content_info = LanguageContentInfo::new(0, 0, hashmap![]);
+        let mut file_stats = FileStats::default();
+        file_stats.lines = 2;
+        file_stats.code_lines = 2;
+        file_stats.has_content = true;
+        content_info.add_file_stats(file_stats);
+        content_info.add_file_stats(FileStats::default());
+        let mut whitespace_only_stats = FileStats::default();
+        whitespace_only_stats.incr_lines();
+        content_info.add_file_stats(whitespace_only_stats);
+
+        let distribution = &content_info.lines_distribution;
+        assert_eq!((Some(1.0), Some(1.0)), (distribution.average_lines(), distribution.median_lines()));
+        assert_eq!((Some(2.0 / 3.0), Some(0.0)), (distribution.average_code_lines(), distribution.median_code_lines()));
+        let distribution = content_info.lines_distribution.without_blank_files();
+        assert_eq!((Some(2.0), Some(2.0)), (distribution.average_lines(), distribution.median_lines()));
+        assert_eq!((Some(2.0), Some(2.0)), (distribution.average_code_lines(), distribution.median_code_lines()));
+
+        let content_info_map = hashmap!["Rust".to_owned() => content_info];
+        let languages_metadata_map = hashmap!["Rust".to_owned() => LanguageMetadata::new(3, 30)];
+        assert_eq!(10, FinalStats::calculate(&content_info_map, &languages_metadata_map, false).bytes_average_size);
+        assert_eq!(30, FinalStats::calculate(&content_info_map, &languages_metadata_map, true).bytes_average_size);
+    }
+
+    #[test]
+    fn test_keyword_co_occurrences() {
+        let mut content_info = LanguageContentInfo::new(0, 0, hashmap![]);
+        let pair = |a: &str, b: &str| (a.to_owned(), b.to_owned());
+
+        content_info.add_keyword_co_occurrences(&KeywordOccurences::from([("unsafe".to_owned(), 2), ("transmute".to_owned(), 1), ("traits".to_owned(), 0)]), &[]);
+        content_info.add_keyword_co_occurrences(&KeywordOccurences::from([("unsafe".to_owned(), 1), ("transmute".to_owned(), 0), ("traits".to_owned(), 3)]), &[]);
+        assert_eq!(hashmap![pair("unsafe","unsafe") => 2, pair("transmute","transmute") => 1, pair("traits","traits") => 1,
+                pair("transmute","unsafe") => 1, pair("traits","unsafe") => 1], content_info.keyword_co_occurrences);
+
+        let mut content_info = LanguageContentInfo::new(0, 0, hashmap![]);
+        content_info.add_keyword_co_occurrences(&KeywordOccurences::from([("Unsafe".to_owned(), 2), ("transmute".to_owned(), 1), ("traits".to_owned(), 1)]),
+                &["unsafe".to_owned(), "transmute".to_owned()]);
+        assert_eq!(hashmap![pair("Unsafe","Unsafe") => 1, pair("transmute","transmute") => 1, pair("Unsafe","transmute") => 1],
+                content_info.keyword_co_occurrences);
+    }
+
+    #[test]
+    fn test_keyword_hotspots() {
+        let mut content_info = LanguageContentInfo::new(0, 0, hashmap![]);
+        let hotspot = |num: usize, path: &str| (num, PathBuf::from(path));
+
+        content_info.add_keyword_hotspots(&KeywordOccurences::from([("unsafe".to_owned(), 2), ("TODO".to_owned(), 0)]), Path::new("/p/b.rs"));
+        content_info.add_keyword_hotspots(&KeywordOccurences::from([("unsafe".to_owned(), 1), ("TODO".to_owned(), 3)]), Path::new("/p/c.rs"));
+        assert_eq!(KeywordHotspots::from([("unsafe".to_owned(), hotspot(2, "/p/b.rs")), ("TODO".to_owned(), hotspot(3, "/p/c.rs"))]),
+                content_info.keyword_hotspots);
+
+        let mut other = LanguageContentInfo::new(0, 0, hashmap![]);
+        other.add_keyword_hotspots(&KeywordOccurences::from([("unsafe".to_owned(), 2), ("TODO".to_owned(), 4)]), Path::new("/p/a.rs"));
+        content_info.add_content_info(&other);
+        assert_eq!(KeywordHotspots::from([("unsafe".to_owned(), hotspot(2, "/p/a.rs")), ("TODO".to_owned(), hotspot(4, "/p/a.rs"))]),
+                content_info.keyword_hotspots);
+    }
+
+    #[test]
+    fn test_lines_distribution() {
+        let mut content_info = LanguageContentInfo::new(0, 0, hashmap![]);
+        assert_eq!(None, content_info.lines_distribution.median_lines());
+
+        for (lines, code_lines) in [(10, 8), (300, 200), (20, 12)] {
+            let mut file_stats = FileStats::default();
+            file_stats.lines = lines;
+            file_stats.code_lines = code_lines;
+            content_info.add_file_stats(file_stats);
+        }
+        assert_eq!((Some(110.0), Some(20.0)), (content_info.lines_distribution.average_lines(), content_info.lines_distribution.median_lines()));
+        assert_eq!((Some(220.0 / 3.0), Some(12.0)),
+                (content_info.lines_distribution.average_code_lines(), content_info.lines_distribution.median_code_lines()));
+
+        let mut others = LanguageContentInfo::new(0, 0, hashmap![]);
+        others.lines_distribution.add_file(40, 30, false);
+        content_info.add_content_info(&others);
+        assert_eq!(Some(30.0), content_info.lines_distribution.median_lines());
+    }
+
+    #[test]
+    fn test_file_identifiers() {
+        assert_eq!(Some("mk"), domain::identifier_from_file_name("Makefile"));
+        assert_eq!(Some("dockerfile"), domain::identifier_from_file_name("Dockerfile"));
+        assert_eq!(None, domain::identifier_from_file_name("README"));
+
+        assert_eq!(Some("py"), domain::identifier_from_shebang("#!/usr/bin/env python3"));
+        assert_eq!(Some("py"), domain::identifier_from_shebang("#!/usr/bin/python3.11 -u"));
+        assert_eq!(Some("js"), domain::identifier_from_shebang("#! /usr/bin/env -S node --harmony"));
+        assert_eq!(Some("sh"), domain::identifier_from_shebang("#!/bin/bash"));
+        assert_eq!(None, domain::identifier_from_shebang("#!/usr/bin/env"));
+        assert_eq!(None, domain::identifier_from_shebang("#!/usr/bin/unknown"));
+        assert_eq!(None, domain::identifier_from_shebang("# python"));
+    }
+
+    #[test]
+    fn test_dir_breakdown() {
+        let dirs = vec!["/project".to_owned(), "/other/lib.rs".to_owned()];
+        assert_eq!(PathBuf::from("/project"), get_breakdown_dir(Path::new("/project/main.rs"), &dirs, 1));
+        assert_eq!(PathBuf::from("/project/src"), get_breakdown_dir(Path::new("/project/src/a/b/lib.rs"), &dirs, 1));
+        assert_eq!(PathBuf::from("/project/src/a"), get_breakdown_dir(Path::new("/project/src/a/b/lib.rs"), &dirs, 2));
+        assert_eq!(PathBuf::from("/other"), get_breakdown_dir(Path::new("/other/lib.rs"), &dirs, 1));
+
+        let stats = |files, lines| DirStats {files, lines, code_lines: lines / 2, bytes: lines * 10};
+        let dirs_stats_map = hashmap![PathBuf::from("/project") => stats(1, 10), PathBuf::from("/project/src/a") => stats(2, 100),
+                PathBuf::from("/project/src") => stats(1, 40), PathBuf::from("/project/tests") => stats(3, 300),
+                PathBuf::from("/other") => stats(1, 5)];
+        assert_eq!(vec![(0, "/project".to_owned(), stats(7, 450)), (1, "tests".to_owned(), stats(3, 300)), (1, "src".to_owned(), stats(3, 140)),
+                (2, "a".to_owned(), stats(2, 100)), (0, "/other".to_owned(), stats(1, 5))], make_dir_tree(&dirs_stats_map, &dirs));
+    }
+
+    #[test]
+    fn test_add_documentation_languages() {
+        let lang = |name: &str, extension: &str| Language::new(name.to_owned(), vec![extension.to_owned()], vec!["\"".to_owned()], vec![],
+                vec![], vec![]);
+        let mut languages = hashmap!["Rust".to_owned() => lang("Rust", "rs"), "Plain".to_owned() => lang("Plain", "txt")];
+        add_documentation_languages(&mut languages);
+
+        assert!(!languages["Rust"].is_documentation());
+        assert!(languages["Markdown"].is_documentation());
+        assert_eq!(vec!["md".to_owned(), "markdown".to_owned()], languages["Markdown"].extensions);
+        assert_eq!(2, languages["Markdown"].embedded_regions.len());
+        assert!(languages.contains_key("reStructuredText") && languages.contains_key("AsciiDoc"));
+        // The extension is already claimed
+        assert!(!languages.contains_key("Text"));
+        assert_eq!(5, languages.len());
+    }
+
+    #[test]
+    fn test_language_builder() {
+        let keyword = |name: &str| Keyword {descriptive_name: name.to_owned(), aliases: vec![name.to_owned()], patterns: vec![],
+                category: None, case_insensitive: false, scope: KeywordScope::Code};
+        let strings = |x: &[&str]| x.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        let mut builder = LanguageBuilder::new("OCaml");
+        builder.set_extensions(strings(&["ml"])).set_string_symbols(strings(&["\""]))
+                .set_multiline_comment_symbols(strings(&["(*", "{|"]), strings(&["*)", "|}"])).set_nested_comments(true)
+                .set_keywords(vec![keyword("match"), keyword("let")]);
+
+        let language = builder.build().unwrap();
+        assert_eq!(vec![("(*".to_owned(), "*)".to_owned()), ("{|".to_owned(), "|}".to_owned())], language.multiline_comment_symbols);
+        assert!(language.supports_nested_comments);
+        assert_eq!(2, language.keywords.len());
+
+        assert_eq!(Err(LanguageBuildError::UnmatchedMultilineSymbols(2, 1)),
+                builder.clone().set_multiline_comment_symbols(strings(&["(*", "{|"]), strings(&["*)"])).build());
+        assert_eq!(Err(LanguageBuildError::DuplicateKeyword("let".to_owned())),
+                builder.clone().set_keywords(vec![keyword("let"), keyword("match"), keyword("let")]).build());
+        assert_eq!(Err(LanguageBuildError::EmptySymbol("comment")), builder.clone().set_comment_symbols(strings(&["//", " "])).build());
+        assert_eq!(Err(LanguageBuildError::NoStringSymbols), builder.clone().set_string_symbols(vec![]).build());
+        assert_eq!(Err(LanguageBuildError::EmptyName), LanguageBuilder::new(" ").set_string_symbols(strings(&["\""])).build());
+    }
+
+    #[test]
+    fn test_make_keyword_categories_map() {
+        let keyword = |name: &str, category: Option<&str>| Keyword {descriptive_name: name.to_owned(), aliases: vec![name.to_owned()],
+                patterns: vec![], category: category.map(|x| x.to_owned()), case_insensitive: false, scope: KeywordScope::Code};
+        let lang = |name: &str, keywords: Vec<Keyword>| Language::new(name.to_owned(), vec![], vec![], vec![], vec![], keywords);
+        let languages = hashmap!["Rust".to_owned() => lang("Rust", vec![keyword("structs", Some("types")), keyword("enums", Some("types")),
+                keyword("unsafe", None), keyword("spawns", Some("concurrency"))]), "C".to_owned() => lang("C", vec![keyword("structs", Some("types"))])];
+        let content_info_map = hashmap![
+                "Rust".to_owned() => LanguageContentInfo::new(100, 80, KeywordOccurences::from([("structs".to_owned(), 4), ("enums".to_owned(), 2),
+                        ("unsafe".to_owned(), 7), ("spawns".to_owned(), 0)])),
+                "C".to_owned() => LanguageContentInfo::new(50, 40, KeywordOccurences::from([("structs".to_owned(), 3)])),
+                "Markdown".to_owned() => LanguageContentInfo::new(10, 10, KeywordOccurences::new())];
+
+        assert_eq!(BTreeMap::from([("types".to_owned(), BTreeMap::from([("C".to_owned(), 3), ("Rust".to_owned(), 6)]))]),
+                make_keyword_categories_map(&content_info_map, &languages));
+    }
+
+    #[test]
+    fn test_find_lang_of_file() {
+        let lang = |name: &str, extension: &str| Language::new(name.to_owned(), vec![extension.to_owned()], vec![], vec![], vec![], vec![]);
+        let languages = Arc::new(hashmap!["Rust".to_owned() => lang("Rust", "rs"), "Python".to_owned() => lang("Python", "py")]);
+        let path = std::env::temp_dir().join("mezura-test-shebang.rs");
+        fs::write(&path, "#!/usr/bin/env python3\nprint(1)\n").unwrap();
+
+        let mut config = Configuration::new(vec![]);
+        assert_eq!((Some("Rust".to_owned()), None), find_lang_of_file(&path, &languages, &config));
+
+        config.set_detect_shebangs(true);
+        let (lang_name, conflict) = find_lang_of_file(&path, &languages, &config);
+        assert_eq!(Some("Rust".to_owned()), lang_name);
+        assert_eq!(Some(DetectionConflict {path: path.clone(), extension_language: "Rust".to_owned(), shebang_identifier: "py".to_owned(),
+                shebang_language: Some("Python".to_owned()), chosen_language: "Rust".to_owned()}), conflict);
+
+        config.set_prefer_shebangs(true);
+        let (lang_name, conflict) = find_lang_of_file(&path, &languages, &config);
+        assert_eq!(Some("Python".to_owned()), lang_name);
+        assert_eq!("Python", conflict.unwrap().chosen_language);
+
+        fs::write(&path, "#!/bin/bash\n").unwrap();
+        let (lang_name, conflict) = find_lang_of_file(&path, &languages, &config);
+        assert_eq!(Some("Rust".to_owned()), lang_name);
+        assert_eq!(None, conflict.unwrap().shebang_language);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_find_lang_of_file_by_content() {
+        let lang = |name: &str, extension: &str| Language::new(name.to_owned(), vec![extension.to_owned()], vec![], vec![], vec![], vec![]);
+        let languages = Arc::new(hashmap!["C".to_owned() => lang("C", "c"), "C++".to_owned() => lang("C++", "h")]);
+        let path = std::env::temp_dir().join("mezura-test-disambiguate.h");
+        fs::write(&path, "#include <stdio.h>\ntypedef struct point {int x, y;} point;\n").unwrap();
+
+        let mut config = Configuration::new(vec![]);
+        assert_eq!(Some("C++".to_owned()), find_lang_of_file(&path, &languages, &config).0);
+        config.set_disambiguate(vec!["h".to_owned()]);
+        assert_eq!(Some("C".to_owned()), find_lang_of_file(&path, &languages, &config).0);
+        fs::write(&path, "namespace geometry {\nclass Point;\n}\n").unwrap();
+        assert_eq!(Some("C++".to_owned()), find_lang_of_file(&path, &languages, &config).0);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_queue_biggest_first() {
+        let files_injector = Injector::<ParsableFile>::new();
+        for (name, size) in [("a.rs", 10), ("b.rs", 300), ("c.rs", 10), ("d.rs", 0), ("e.rs", 2000)] {
+            files_injector.push(ParsableFile::from_archive_entry(PathBuf::from(name), "Rust".to_owned(), vec![b' '; size]));
+        }
+        assert_eq!(2320, queue_biggest_first(&files_injector));
+        let mut paths = Vec::new();
+        while let Steal::Success(file) = files_injector.steal() {
+            paths.push(file.path.to_string_lossy().to_string());
+        }
+        assert_eq!(vec!["e.rs", "b.rs", "a.rs", "c.rs", "d.rs"], paths);
+    }
+
+    #[test]
+    fn test_files_signal() {
+        let files_signal = Arc::new(FilesSignal::new());
+        let files_injector = Arc::new(Injector::<ParsableFile>::new());
+
+        let waiting_thread = {
+            let (files_signal, files_injector) = (files_signal.clone(), files_injector.clone());
+            std::thread::spawn(move || files_signal.wait_for_files(&files_injector))
+        };
+        files_injector.push(ParsableFile::new(PathBuf::from("a.rs"), "Rust".to_owned()));
+        files_signal.notify_new_files();
+        assert!(waiting_thread.join().unwrap());
+
+        files_injector.steal();
+        let waiting_thread = {
+            let (files_signal, files_injector) = (files_signal.clone(), files_injector.clone());
+            std::thread::spawn(move || files_signal.wait_for_files(&files_injector))
+        };
+        files_signal.finish();
+        assert!(!waiting_thread.join().unwrap());
+    }
+
+    #[test]
+    fn test_get_needed_consumers() {
+        assert_eq!(1, get_needed_consumers(0, 0, 8));
+        assert_eq!(1, get_needed_consumers(5, 0, 8));
+        assert_eq!(3, get_needed_consumers(QUEUED_FILES_PER_CONSUMER * 2, 0, 8));
+        assert_eq!(8, get_needed_consumers(10_000, 0, 8));
+        // The producers wait for the full queue to have space
+        assert_eq!(8, get_needed_consumers(16, 16, 8));
+    }
+
+    #[test]
+    fn test_parse_timings() {
+        let mut parse_timings = ParseTimings::default();
+        for i in 0..12 {
+            parse_timings.add_file(&PathBuf::from(format!("/p/{}.rs", i)), "Rust", Duration::from_millis(i));
+        }
+        parse_timings.add_file(Path::new("/p/Makefile"), "Makefile", Duration::from_millis(5));
+        assert_eq!(hashmap!["rs".to_owned() => (12, Duration::from_millis(66)), "".to_owned() => (1, Duration::from_millis(5))],
+                parse_timings.extensions);
+        assert_eq!(SLOWEST_FILES_NUM, parse_timings.slowest_files.len());
+        assert_eq!((PathBuf::from("/p/11.rs"), "Rust".to_owned(), Duration::from_millis(11)), parse_timings.slowest_files[0]);
+        assert_eq!(Duration::from_millis(3), parse_timings.slowest_files[SLOWEST_FILES_NUM - 1].2);
+
+        let mut other = ParseTimings::default();
+        other.add_file(Path::new("/q/main.c"), "C", Duration::from_millis(20));
+        parse_timings.add(&other);
+        assert_eq!(Duration::from_millis(91), parse_timings.total_duration());
+        assert_eq!(PathBuf::from("/q/main.c"), parse_timings.slowest_files[0].0);
+        assert_eq!(Duration::from_millis(4), parse_timings.slowest_files[SLOWEST_FILES_NUM - 1].2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_follow_links() {
+        let dir = std::env::temp_dir().join("mezura-test-follow-links");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("real/pkg")).unwrap();
+        fs::create_dir_all(dir.join("workspace")).unwrap();
+        fs::write(dir.join("real/pkg/a.rs"), "fn a() {}\n").unwrap();
+        fs::write(dir.join("real/b.rs"), "fn b() {}\n").unwrap();
+        std::os::unix::fs::symlink(dir.join("real/pkg"), dir.join("workspace/pkg")).unwrap();
+        std::os::unix::fs::symlink(dir.join("real/b.rs"), dir.join("workspace/b.rs")).unwrap();
+        std::os::unix::fs::symlink(dir.join("workspace"), dir.join("workspace/pkg_loop")).unwrap();
+
+        let lang = |name: &str, extension: &str| Language::new(name.to_owned(), vec![extension.to_owned()], vec![], vec![], vec![], vec![]);
+        let languages = || hashmap!["Rust".to_owned() => lang("Rust", "rs")];
+        let mut config = Configuration::new(vec![dir.join("workspace").to_str().unwrap().to_owned()]);
+        assert!(discover(config.clone(), languages()).is_empty());
+
+        config.set_follow_links(true);
+        let discovered_files = discover(config, languages()).into_iter().map(|x| x.path).collect::<Vec<_>>();
+        assert_eq!(vec![dir.join("workspace/b.rs"), dir.join("workspace/pkg/a.rs")], discovered_files);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_hidden() {
+        let dir = std::env::temp_dir().join("mezura-test-hidden");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join(".git/objects")).unwrap();
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join(".git/objects/a.rs"), "fn a() {}\n").unwrap();
+        fs::write(dir.join(".build.rs"), "fn b() {}\n").unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() {}\n").unwrap();
+
+        let lang = |name: &str, extension: &str| Language::new(name.to_owned(), vec![extension.to_owned()], vec![], vec![], vec![], vec![]);
+        let languages = || hashmap!["Rust".to_owned() => lang("Rust", "rs")];
+        let discovered_files = |config: Configuration| {
+            let mut paths = discover(config, languages()).into_iter().map(|x| x.path).collect::<Vec<_>>();
+            paths.sort();
+            paths
+        };
+        let mut config = Configuration::new(vec![dir.to_str().unwrap().to_owned()]);
+        assert_eq!(vec![dir.join("src/main.rs")], discovered_files(config.clone()));
+
+        config.set_hidden(true);
+        assert_eq!(vec![dir.join(".build.rs"), dir.join(".git/objects/a.rs"), dir.join("src/main.rs")], discovered_files(config));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_isolate_roots() {
+        let dir = std::env::temp_dir().join("mezura-test-isolate-roots");
+        let _ = fs::remove_dir_all(&dir);
+        for root in ["a", "b", "empty"] {
+            fs::create_dir_all(dir.join(root)).unwrap();
+        }
+        fs::write(dir.join("a/a.rs"), "fn a() {}\n").unwrap();
+        fs::write(dir.join("b/b.rs"), "fn b() {}\n// b\n").unwrap();
+
+        let lang = |name: &str, extension: &str| Language::new(name.to_owned(), vec![extension.to_owned()], vec!["\"".to_owned()],
+                vec!["//".to_owned()], vec![], vec![]);
+        let languages = || hashmap!["Rust".to_owned() => lang("Rust", "rs")];
+        let root = |x: &str| dir.join(x).to_str().unwrap().to_owned();
+
+        let mut config = Configuration::new(vec![root("a"), root("b"), root("empty")]);
+        config.set_isolate_roots(true);
+        assert!(run(config, languages()).is_ok());
+
+        let mut config = Configuration::new(vec![root("empty"), root("empty")]);
+        config.set_isolate_roots(true);
+        assert!(matches!(run(config, languages()), Err(ParseFilesError::NoRelevantFiles(_))));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_strict() {
+        let dir = std::env::temp_dir().join("mezura-test-strict");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a.rs"), "fn a() {}\n").unwrap();
+        fs::write(dir.join("b.rs"), b"fn b() {}\n// \xC3\x28\n").unwrap();
+
+        let languages = || hashmap!["Rust".to_owned() => Language::new("Rust".to_owned(), vec!["rs".to_owned()], vec!["\"".to_owned()],
+                vec!["//".to_owned()], vec![], vec![])];
+        let mut config = Configuration::new(vec![dir.to_str().unwrap().to_owned()]);
+        config.set_strict_encoding(true);
+        assert!(run(config.clone(), languages()).is_ok());
+
+        config.set_strict(true);
+        let error = run(config, languages()).unwrap_err();
+        assert!(matches!(error, ParseFilesError::FaultyFiles(1)));
+        assert_eq!(3, error.exit_code());
+        assert_eq!("1 faulty files with '--strict'", error.to_string());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_error_sources() {
+        use std::error::Error;
+
+        let error = ParseFilesError::InvalidKeywordPattern(RegexError::TrailingBackslash("a\\".to_owned()));
+        assert_eq!("Keyword pattern 'a\\' ends with a backslash.", error.to_string());
+        assert_eq!(Some(error.to_string()), error.source().map(|x| x.to_string()));
+
+        let error = ParseFilesError::UnavailableOutFile("out.json".to_owned(), std::io::Error::from(std::io::ErrorKind::AlreadyExists));
+        assert_eq!("'out.json' already exists. Use '--force' to overwrite it", error.to_string());
+        assert!(error.source().and_then(|x| x.downcast_ref::<std::io::Error>()).is_some());
+        assert!(ParseFilesError::Cancelled.source().is_none());
+
+        let parse = |args: &str| -> Result<Configuration, Box<dyn Error>> {Ok(config_manager::create_config_from_args(args)?)};
+        assert_eq!("The min size (10 bytes) is bigger than the max size (5 bytes).",
+                parse("./ --min-size 10 --max-size 5").unwrap_err().to_string());
+    }
+
+    #[derive(Default)]
+    struct RecordingObserver {
+        events: Arc<Mutex<Vec<String>>>
+    }
+
+    impl AnalysisObserver for RecordingObserver {
+        fn on_event(&self, event: &AnalysisEvent) {
+            let event = match event {
+                AnalysisEvent::PhaseChanged(x) => format!("{:?}", x),
+                AnalysisEvent::FileDiscovered {path, language} => format!("discovered {} ({})", path.file_name().unwrap().to_string_lossy(), language),
+                AnalysisEvent::FileParsed {path, stats, ..} => format!("parsed {} ({} lines)", path.file_name().unwrap().to_string_lossy(), stats.lines),
+                AnalysisEvent::FileFaulty(x) => format!("faulty {}", x.path.display()),
+                AnalysisEvent::FileSkipped {path, reason} | AnalysisEvent::DirSkipped {path, reason} =>
+                        format!("skipped {} ({})", path.file_name().unwrap().to_string_lossy(), reason.as_str())
+            };
+            self.events.lock().unwrap().push(event);
+        }
+    }
+
+    #[test]
+    fn test_run_with_observer() {
+        let dir = std::env::temp_dir().join("mezura-test-run-with-observer");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a.rs"), "fn a() {}\n").unwrap();
+        fs::write(dir.join("b.rs"), "fn b() {}\n// b\n").unwrap();
+
+        let language = Language::new("Rust".to_owned(), vec!["rs".to_owned()], vec!["\"".to_owned()], vec!["//".to_owned()], vec![], vec![]);
+        let observer = RecordingObserver::default();
+        let events = observer.events.clone();
+        let metrics = run_with_observer(Configuration::new(vec![dir.to_str().unwrap().to_owned()]), hashmap!["Rust".to_owned() => language], observer)
+                .unwrap().unwrap();
+        // However fast the run was
+        assert_eq!((1, 25), (metrics.durations.len(), metrics.total_bytes));
+        assert!(metrics.files_per_sec > 0 && metrics.lines_per_sec > 0 && metrics.bytes_per_sec > 0);
+        assert!(metrics.discovery_duration <= metrics.total_duration && !metrics.thread_utilizations.is_empty());
+
+        let events = events.lock().unwrap();
+        let phases = events.iter().filter(|x| !x.contains(' ')).collect::<Vec<_>>();
+        assert_eq!(vec!["Searching", "Parsing", "Summarizing", "Finished"], phases);
+        // The files are discovered and parsed concurrently, so only their own order is certain
+        let mut files = events.iter().filter(|x| x.contains(' ')).collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(vec!["discovered a.rs (Rust)", "discovered b.rs (Rust)", "parsed a.rs (1 lines)", "parsed b.rs (2 lines)"], files);
+        assert!(events.iter().position(|x| x == "discovered a.rs (Rust)") < events.iter().position(|x| x == "parsed a.rs (1 lines)"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    struct CancellingObserver {
+        cancel_flag: Arc<AtomicBool>
+    }
+
+    impl AnalysisObserver for CancellingObserver {
+        fn on_event(&self, event: &AnalysisEvent) {
+            if let AnalysisEvent::FileDiscovered {..} = event {
+                self.cancel_flag.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_run_cancellable() {
+        let dir = std::env::temp_dir().join("mezura-test-run-cancellable");
+        let _ = fs::remove_dir_all(&dir);
+        for sub_dir in ["a", "b"] {
+            fs::create_dir_all(dir.join(sub_dir)).unwrap();
+            fs::write(dir.join(sub_dir).join("main.rs"), "fn main() {}\n").unwrap();
+        }
+
+        let language = || hashmap!["Rust".to_owned() => Language::new("Rust".to_owned(), vec!["rs".to_owned()], vec!["\"".to_owned()],
+                vec!["//".to_owned()], vec![], vec![])];
+        let config = || Configuration::new(vec![dir.join("a").to_str().unwrap().to_owned(), dir.join("b").to_str().unwrap().to_owned()]);
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        assert!(run_cancellable(config(), language(), NoObserver, cancel_flag.clone()).is_ok());
+
+        cancel_flag.store(true, Ordering::Relaxed);
+        assert!(matches!(run_cancellable(config(), language(), NoObserver, cancel_flag), Err(ParseFilesError::Cancelled)));
+
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        let observer = CancellingObserver {cancel_flag: cancel_flag.clone()};
+        assert!(matches!(run_cancellable(config(), language(), observer, cancel_flag), Err(ParseFilesError::Cancelled)));
+
+        let mut isolated_config = config();
+        isolated_config.set_isolate_roots(true);
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        let observer = CancellingObserver {cancel_flag: cancel_flag.clone()};
+        assert!(matches!(run_cancellable(isolated_config, language(), observer, cancel_flag), Err(ParseFilesError::Cancelled)));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_depth_and_size_filters() {
+        let dir = std::env::temp_dir().join("mezura-test-depth-and-size");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("a/b")).unwrap();
+        fs::write(dir.join("top.rs"), "fn top() {}\n").unwrap();
+        fs::write(dir.join("a/small.rs"), "\n").unwrap();
+        fs::write(dir.join("a/b/big.rs"), "fn big() {}\n".repeat(100)).unwrap();
+
+        let lang = |name: &str, extension: &str| Language::new(name.to_owned(), vec![extension.to_owned()], vec![], vec![], vec![], vec![]);
+        let discovered = |config: &Configuration| discover(config.clone(), hashmap!["Rust".to_owned() => lang("Rust", "rs")])
+                .into_iter().map(|x| x.path.strip_prefix(&dir).unwrap().to_owned()).collect::<Vec<_>>();
+        let mut config = Configuration::new(vec![dir.to_str().unwrap().to_owned()]);
+        assert_eq!(vec![PathBuf::from("a/b/big.rs"), PathBuf::from("a/small.rs"), PathBuf::from("top.rs")], discovered(&config));
+
+        config.set_max_depth(Some(0));
+        assert_eq!(vec![PathBuf::from("top.rs")], discovered(&config));
+        config.set_max_depth(Some(1));
+        assert_eq!(vec![PathBuf::from("a/small.rs"), PathBuf::from("top.rs")], discovered(&config));
+
+        config.set_max_depth(None).set_min_size(Some(2)).set_max_size(Some(1024));
+        assert_eq!(vec![PathBuf::from("top.rs")], discovered(&config));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_faulty_reason() {
+        let error = File::open("test_dir/missing.rs").unwrap_err();
+        let reason = FaultyReason::from(error);
+        assert!(matches!(reason, FaultyReason::Io(std::io::ErrorKind::NotFound, _)));
+        assert_eq!(File::open("test_dir/missing.rs").unwrap_err().to_string(), reason.message());
+        assert_eq!("stream did not contain valid UTF-8", FaultyReason::InvalidEncoding.message());
+        assert_eq!("parsing took longer than 5 secs", FaultyReason::TimedOut(5).message());
+        assert_eq!("there is no language named 'Zig'", FaultyReason::UnknownLanguage("Zig".to_owned()).message());
+        assert_eq!("the notebook is not valid: unterminated string", FaultyReason::InvalidNotebook("unterminated string".to_owned()).message());
+        assert_eq!("the archive inflates to more than its limit", FaultyReason::OverInflated.message());
+    }
+
+    #[test]
+    fn test_reconciliation_discrepancies() {
+        let mut reconciliation = Reconciliation {found_files: 10, relevant_files: 6, excluded_files: 1, discovered_files: 6, discovered_bytes: 600,
+                parsed_files: 5, parsed_bytes: 550, faulty_files: 1, faulty_bytes: 50, skipped_files: 0, generated_files: 0,
+                minified_files: 0};
+        assert_eq!(3, reconciliation.unsupported_files());
+        assert!(reconciliation.discrepancies().is_empty());
+
+        reconciliation.parsed_files = 4;
+        reconciliation.parsed_bytes = 500;
+        assert_eq!(vec!["6 files of interest, but 4 parsed and 1 faulty".to_owned(), "600 bytes found, but 500 parsed and 50 faulty".to_owned()],
+                reconciliation.discrepancies());
+
+        reconciliation.excluded_files = 5;
+        reconciliation.discovered_files = 7;
+        assert_eq!(0, reconciliation.unsupported_files());
+        assert_eq!(vec!["6 files of interest and 5 excluded, but only 10 found".to_owned(), "6 files of interest, but 7 attributed to languages".to_owned()],
+                reconciliation.discrepancies()[..2].to_vec());
+    }
+
+    #[test]
+    fn test_resource_limits() {
+        let config = Configuration::new(vec!["a".to_owned()]);
+        assert_eq!(0, ResourceLimits::from_config(&config).max_queued_files);
+        assert_eq!(None, get_file_memory_budget(&config));
+
+        let mut config = Configuration::new(vec!["a".to_owned()]);
+        config.set_max_memory(2);
+        config.threads.consumers = 4;
+        assert_eq!(2048, ResourceLimits::from_config(&config).max_queued_files);
+        assert_eq!(Some(256 * 1024), get_file_memory_budget(&config));
+
+        let resource_limits = ResourceLimits::from_config(&config);
+        assert_eq!(1024 * 1024, resource_limits.max_buffered_bytes);
+        let file = ParsableFile::from_archive_entry(PathBuf::from("a.rs"), "Rust".to_owned(), vec![b' '; 300 * 1024]);
+        assert_eq!(256 * 1024, estimate_parse_memory(&file, &config));
+        let buffers = (0..4).map(|_| resource_limits.reserve_buffer(estimate_parse_memory(&file, &config))).collect::<Vec<_>>();
+        assert!(resource_limits.is_over_buffer_budget());
+        drop(buffers);
+        assert!(!resource_limits.is_over_buffer_budget());
+        let unlimited = ResourceLimits::new(0, 0);
+        let _buffer = unlimited.reserve_buffer(usize::MAX);
+        assert!(!unlimited.is_over_buffer_budget());
+        config.set_prescan(true);
+        assert_eq!(0, ResourceLimits::from_config(&config).max_queued_files);
+
+        let resource_limits = Arc::new(ResourceLimits::new(1, 0));
+        let slot = resource_limits.acquire_open_file();
+        let waiting_thread = {
+            let resource_limits = resource_limits.clone();
+            std::thread::spawn(move || {
+                let _slot = resource_limits.acquire_open_file();
+                let open_files = *resource_limits.open_files.lock().unwrap();
+                open_files
+            })
+        };
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(!waiting_thread.is_finished());
+        drop(slot);
+        assert_eq!(1, waiting_thread.join().unwrap());
+        assert_eq!(0, *resource_limits.open_files.lock().unwrap());
+    }
+
+    #[test]
+    fn test_merge_custom_languages() {
+        let mut language_map = hashmap![
+            "Java".to_owned() => Language::new("Java".to_owned(),vec!["java".to_owned()],vec![],vec!["\"".to_owned()],vec![],vec![]),
+            "C".to_owned() => Language::new("C".to_owned(),vec!["c".to_owned(),"h".to_owned()],vec![],vec!["\"".to_owned()],vec![],vec![]),
+            "C++".to_owned() => Language::new("C++".to_owned(),vec!["cpp".to_owned()],vec![],vec!["\"".to_owned()],vec![],vec![]),
+            "Go".to_owned() => Language::new("Go".to_owned(),vec!["go".to_owned(),"go2".to_owned()],vec![],vec!["\"".to_owned()],vec![],vec![])
+        ];
+        let custom_languages = hashmap![
+            "Go".to_owned() => Language::new("Go".to_owned(),vec!["go".to_owned()],vec![],vec!["//".to_owned()],vec![],vec![]),
+            "Java".to_owned() => Language::new("Java".to_owned(),vec!["java".to_owned(),"jav".to_owned()],vec![],vec!["\"".to_owned()],vec![],vec![]),
+            "Headers".to_owned() => Language::new("Headers".to_owned(),vec!["h".to_owned()],vec![],vec!["\"".to_owned()],vec![],vec![]),
+            "Cpp".to_owned() => Language::new("Cpp".to_owned(),vec!["cpp".to_owned()],vec![],vec!["\"".to_owned()],vec![],vec![])
+        ];
+        merge_custom_languages(&mut language_map, custom_languages);
+
+        assert_eq!(5, language_map.len());
+        assert_eq!(vec!["java".to_owned(),"jav".to_owned()], language_map.get("Java").unwrap().extensions);
+        // The definition is replaced, but the extensions that weren't given are kept
+        assert_eq!(vec!["go".to_owned(),"go2".to_owned()], language_map["Go"].extensions);
+        assert_eq!(vec!["//".to_owned()], language_map["Go"].comment_symbols);
+        assert_eq!(vec!["c".to_owned()], language_map.get("C").unwrap().extensions);
+        assert_eq!(vec!["h".to_owned()], language_map.get("Headers").unwrap().extensions);
+        assert!(language_map.get("C++").is_none());
+        assert!(language_map.get("Cpp").is_some());
+    }
+
+    #[test]
+    fn test_find_lang_with_this_identifier() {
+        let languages = Arc::new(hashmap![
+            "C++".to_owned() => Language::new("C++".to_owned(),vec!["cpp".to_owned(),"h".to_owned()],vec![],vec!["\"".to_owned()],vec![],vec![]),
+            "Headers".to_owned() => Language::new("Headers".to_owned(),vec!["h".to_owned()],vec![],vec!["\"".to_owned()],vec![],vec![])
+        ]);
+        assert_eq!(Some("C++".to_owned()), find_lang_with_this_identifier(&languages, "h", false));
+        assert_eq!(Some("C++".to_owned()), find_lang_with_this_identifier(&languages, "cpp", false));
+        assert_eq!(None, find_lang_with_this_identifier(&languages, "c", false));
+        assert_eq!(Some("C++".to_owned()), find_lang_with_this_identifier(&languages, "CPP", false));
+        assert_eq!(None, find_lang_with_this_identifier(&languages, "CPP", true));
+
+        let languages = Arc::new(hashmap![
+            "C".to_owned() => Language::new("C".to_owned(),vec!["c".to_owned()],vec![],vec!["\"".to_owned()],vec![],vec![]),
+            "C++".to_owned() => Language::new("C++".to_owned(),vec!["C".to_owned()],vec![],vec!["\"".to_owned()],vec![],vec![])
+        ]);
+        assert_eq!(Some("C".to_owned()), find_lang_with_this_identifier(&languages, "c", false));
+        assert_eq!(Some("C++".to_owned()), find_lang_with_this_identifier(&languages, "C", false));
+    }
+
+    #[test]
+    fn test_remove_faulty_files_stats() {
+        let mut languages_metadata_map = hashmap!["Rust".to_owned() => LanguageMetadata::new(1, 100)];
+        let faulty_files = vec![FaultyFile::new(PathBuf::from("a.rs"), "Rust".to_owned(), FaultyReason::InvalidEncoding, 150),
+                FaultyFile::new(PathBuf::from("b.RS"), "Rust".to_owned(), FaultyReason::InvalidEncoding, 10),
+                FaultyFile::new(PathBuf::from("c.py"), "Python".to_owned(), FaultyReason::InvalidEncoding, 10)];
+
+        let unaccounted_files = remove_faulty_files_stats(&faulty_files, &mut languages_metadata_map);
+        assert_eq!(LanguageMetadata::new(0, 0), languages_metadata_map["Rust"]);
+        assert_eq!(vec![&faulty_files[1], &faulty_files[2]], unaccounted_files);
+    }
+}
@@ -0,0 +1,343 @@
+// Compares the shares of the languages in bytes with the ones of the language bar of GitHub, which are made by linguist.
+// Linguist leaves out the vendored, generated and documentation files and the languages that are not programming or markup
+// ones, and the 'linguist-*' attributes of '.gitattributes' change that and the language of the files. Like with
+// '.mezuraignore', only the file at the root of every provided directory is read. The generated files are only the ones
+// of the attribute, since the contents of the files are not read.
+
+use crate::*;
+
+pub const GITATTRIBUTES_FILE_NAME : &str = ".gitattributes";
+
+// The dirs that linguist considers vendored at any level, an abridged 'vendor.yml'. Compared without the case
+const VENDORED_DIRS : [&str; 12] = ["node_modules", "vendor", "vendors", "third_party", "third-party", "thirdparty", "3rdparty",
+        "dependencies", "bower_components", "pods", "carthage", ".yarn"];
+const MINIFIED_SUFFIXES : [&str; 4] = [".min.js", "-min.js", ".min.css", "-min.css"];
+// The dirs and the names of the files before their first '.' that linguist considers documentation, an abridged 'documentation.yml'
+const ROOT_DOCUMENTATION_DIRS : [&str; 6] = ["docs", "doc", "examples", "samples", "sample", "man"];
+const DOCUMENTATION_DIRS : [&str; 3] = ["documentation", "javadoc", "groovydoc"];
+const DOCUMENTATION_FILES : [&str; 9] = ["readme", "changelog", "changes", "change", "contributing", "copying", "install", "license", "licence"];
+
+// The 'linguist-*' attributes of a file, None if they are not set
+#[derive(Debug,Default,PartialEq,Clone)]
+pub struct LinguistAttributes {
+    pub vendored: Option<bool>,
+    pub generated: Option<bool>,
+    pub documentation: Option<bool>,
+    pub detectable: Option<bool>,
+    pub language: Option<String>
+}
+
+#[derive(Debug,PartialEq)]
+struct AttributesPattern {
+    // Relative to the root, the patterns without a '/' start with '**', like the ones of '.mezuraignore'
+    segments: Vec<String>,
+    attributes: LinguistAttributes
+}
+
+// The roots of the provided directories, with the patterns of their '.gitattributes'
+#[derive(Debug,Default)]
+pub struct LinguistRules {
+    roots: Vec<(PathBuf, Vec<AttributesPattern>)>
+}
+
+// Why linguist leaves a file out of the language bar, in the order that it checks them
+#[derive(Debug,PartialEq,Eq,PartialOrd,Ord,Clone,Copy)]
+pub enum Exclusion {
+    Vendored,
+    Generated,
+    Documentation,
+    // The language is not a programming or markup one, or the file is 'linguist-detectable=false'
+    NotDetectable
+}
+
+#[derive(Debug,PartialEq)]
+pub struct LanguageShare {
+    pub language_name: String,
+    pub bytes: u64,
+    pub github_bytes: u64
+}
+
+#[derive(Debug,PartialEq)]
+pub struct LinguistComparison {
+    // The most bytes on GitHub first
+    pub languages: Vec<LanguageShare>,
+    // The files and the bytes of every reason
+    pub exclusions: Vec<(Exclusion, usize, u64)>,
+    // The language found and the one of 'linguist-language', with the files and the bytes
+    pub reassignments: Vec<(String, String, usize, u64)>
+}
+
+impl LinguistRules {
+    pub fn read(dirs: &[String]) -> Self {
+        let roots = dirs.iter().map(Path::new).filter(|x| x.is_dir())
+                .map(|dir| (dir.to_path_buf(), fs::read_to_string(dir.join(GITATTRIBUTES_FILE_NAME)).map_or(Vec::new(), |x| parse_patterns(&x))))
+                .collect();
+        LinguistRules {roots}
+    }
+
+    // The defaults of linguist for the path, and then every attribute is set by the last pattern that sets it, like with git
+    pub fn attributes_of(&self, path: &Path) -> LinguistAttributes {
+        let (relative_path, patterns) = self.roots.iter().find_map(|(root, patterns)| path.strip_prefix(root).ok().map(|x| (x, patterns.as_slice())))
+                .unwrap_or_else(|| (path.file_name().map_or(path, Path::new), &[]));
+        let segments = relative_path.components().map(|x| x.as_os_str().to_string_lossy()).collect::<Vec<_>>();
+        let segments = segments.iter().map(|x| x.as_ref()).collect::<Vec<_>>();
+
+        let mut attributes = LinguistAttributes {vendored: Some(is_vendored_by_default(&segments)), generated: None,
+                documentation: Some(is_documentation_by_default(&segments)), detectable: None, language: None};
+        for pattern in patterns.iter().filter(|x| ignore::matches_segments(&segments, &x.segments)) {
+            let x = &pattern.attributes;
+            attributes.vendored = x.vendored.or(attributes.vendored);
+            attributes.generated = x.generated.or(attributes.generated);
+            attributes.documentation = x.documentation.or(attributes.documentation);
+            attributes.detectable = x.detectable.or(attributes.detectable);
+            attributes.language = x.language.clone().or(attributes.language);
+        }
+        attributes
+    }
+}
+
+impl Exclusion {
+    fn label(&self) -> &'static str {
+        match self {
+            Exclusion::Vendored => "vendored",
+            Exclusion::Generated => "generated",
+            Exclusion::Documentation => "documentation",
+            Exclusion::NotDetectable => "not detectable"
+        }
+    }
+}
+
+// The files are counted with the language they were found as, and for GitHub with the one of 'linguist-language' if it is
+// set, matched with the names of the languages without the case.
+pub fn compare(files: &[DiscoveredFile], language_map: &HashMap<String, Language>, rules: &LinguistRules) -> LinguistComparison {
+    let mut languages : HashMap<String, (u64, u64)> = HashMap::new();
+    let mut exclusions : BTreeMap<Exclusion, (usize, u64)> = BTreeMap::new();
+    let mut reassignments : BTreeMap<(String, String), (usize, u64)> = BTreeMap::new();
+    for file in files {
+        languages.entry(file.language_name.clone()).or_default().0 += file.size;
+        let attributes = rules.attributes_of(&file.path);
+        let github_language = match &attributes.language {
+            Some(x) => find_language_name(language_map, x),
+            None => file.language_name.clone()
+        };
+        match get_exclusion(&attributes, language_map.get(&github_language)) {
+            Some(exclusion) => {
+                let entry = exclusions.entry(exclusion).or_default();
+                entry.0 += 1;
+                entry.1 += file.size;
+            },
+            None => languages.entry(github_language.clone()).or_default().1 += file.size
+        }
+        if github_language != file.language_name {
+            let entry = reassignments.entry((file.language_name.clone(), github_language)).or_default();
+            entry.0 += 1;
+            entry.1 += file.size;
+        }
+    }
+
+    let mut languages = languages.into_iter().map(|(language_name, (bytes, github_bytes))| LanguageShare {language_name, bytes, github_bytes})
+            .collect::<Vec<_>>();
+    languages.sort_by(|a, b| b.github_bytes.cmp(&a.github_bytes).then(b.bytes.cmp(&a.bytes)).then_with(|| a.language_name.cmp(&b.language_name)));
+    LinguistComparison {
+        languages,
+        exclusions: exclusions.into_iter().map(|(x, (files, bytes))| (x, files, bytes)).collect(),
+        reassignments: reassignments.into_iter().map(|((from, to), (files, bytes))| (from, to, files, bytes)).collect()
+    }
+}
+
+// e.g.
+//   Language    mezura             GitHub             Difference
+//   Rust        120.5 KBs  62.11%  120.5 KBs  80.28%      +18.17
+//   TOML          3.0 KBs   1.55%          -       -       -1.55
+//
+//   Left out by GitHub:
+//     vendored        12 files  1.2 MBs
+//   Languages changed by .gitattributes:
+//     C -> C++         4 files  20.0 KBs
+pub fn format_comparison(comparison: &LinguistComparison) -> String {
+    let (total, github_total) = comparison.languages.iter().fold((0, 0), |acc, x| (acc.0 + x.bytes, acc.1 + x.github_bytes));
+    let share = |bytes: u64, total: u64| if total == 0 {0f64} else {bytes as f64 / total as f64 * 100f64};
+    let format_share = |bytes: u64, total: u64| if bytes == 0 {("-".to_owned(), "-".to_owned())} else {
+        (format_size(bytes as usize), format!("{:.2}%", share(bytes, total)))
+    };
+
+    let mut rows = vec![["Language".to_owned(), "mezura".to_owned(), String::new(), "GitHub".to_owned(), String::new(), "Difference".to_owned()]];
+    for x in comparison.languages.iter() {
+        let ((size, percentage), (github_size, github_percentage)) = (format_share(x.bytes, total), format_share(x.github_bytes, github_total));
+        rows.push([x.language_name.clone(), size, percentage, github_size, github_percentage,
+                format!("{:+.2}", share(x.github_bytes, github_total) - share(x.bytes, total))]);
+    }
+    let widths = (0..6).map(|i| rows.iter().map(|x| x[i].chars().count()).max().unwrap_or(0)).collect::<Vec<_>>();
+    let mut text = rows.iter().enumerate().map(|(i, x)| if i == 0 {
+        format!("  {:<w0$}    {:<w1$}  {:<w2$}  {:<w3$}  {:<w4$}  {:>w5$}", x[0], x[1], x[2], x[3], x[4], x[5],
+                w0 = widths[0], w1 = widths[1], w2 = widths[2], w3 = widths[3], w4 = widths[4], w5 = widths[5])
+    } else {
+        format!("  {:<w0$}    {:>w1$}  {:>w2$}  {:>w3$}  {:>w4$}  {:>w5$}", x[0], x[1], x[2], x[3], x[4], x[5],
+                w0 = widths[0], w1 = widths[1], w2 = widths[2], w3 = widths[3], w4 = widths[4], w5 = widths[5])
+    }.trim_end().to_owned()).collect::<Vec<_>>().join("\n");
+
+    let explanations = [
+        ("Left out by GitHub:", comparison.exclusions.iter().map(|(x, files, bytes)| (x.label().to_owned(), *files, *bytes)).collect::<Vec<_>>()),
+        ("Languages changed by .gitattributes:", comparison.reassignments.iter().map(|(from, to, files, bytes)| (format!("{} -> {}", from, to), *files, *bytes))
+                .collect())
+    ];
+    for (title, rows) in explanations.iter().filter(|(_, rows)| !rows.is_empty()) {
+        let label_width = rows.iter().map(|x| x.0.chars().count()).max().unwrap_or(0);
+        let files_width = rows.iter().map(|x| with_seperators(x.1).len()).max().unwrap_or(0);
+        text += &format!("\n\n  {}", title);
+        for (label, files, bytes) in rows {
+            text += &format!("\n    {:<lw$}  {:>fw$} files  {}", label, with_seperators(*files), format_size(*bytes as usize), lw = label_width, fw = files_width);
+        }
+    }
+    text
+}
+
+// The names of linguist have '-' instead of the spaces in the attributes, but some of them have a '-' of their own, e.g. 'Objective-C'
+fn find_language_name(language_map: &HashMap<String, Language>, name: &str) -> String {
+    let with_spaces = name.replace('-', " ");
+    language_map.keys().find(|x| x.eq_ignore_ascii_case(name)).or_else(|| language_map.keys().find(|x| x.eq_ignore_ascii_case(&with_spaces)))
+            .map_or_else(|| name.to_owned(), |x| x.to_owned())
+}
+
+// Linguist leaves out a file if any of these hold, even if it is 'linguist-detectable'
+fn get_exclusion(attributes: &LinguistAttributes, language: Option<&Language>) -> Option<Exclusion> {
+    if attributes.vendored == Some(true) {
+        Some(Exclusion::Vendored)
+    } else if attributes.generated == Some(true) {
+        Some(Exclusion::Generated)
+    } else if attributes.documentation == Some(true) {
+        Some(Exclusion::Documentation)
+    // The languages of 'linguist-language' that are not known are taken as programming ones
+    } else if !attributes.detectable.unwrap_or_else(|| language.is_none_or(|x| matches!(x.category, FileCategory::Code | FileCategory::Markup))) {
+        Some(Exclusion::NotDetectable)
+    } else {
+        None
+    }
+}
+
+fn is_vendored_by_default(segments: &[&str]) -> bool {
+    let (name, dirs) = match segments.split_last() {
+        Some(x) => x,
+        None => return false
+    };
+    dirs.iter().any(|dir| VENDORED_DIRS.iter().any(|x| dir.eq_ignore_ascii_case(x)))
+            || MINIFIED_SUFFIXES.iter().any(|x| name.to_lowercase().ends_with(x))
+}
+
+fn is_documentation_by_default(segments: &[&str]) -> bool {
+    let (name, dirs) = match segments.split_last() {
+        Some(x) => x,
+        None => return false
+    };
+    let stem = name.split('.').next().unwrap_or(name);
+    dirs.first().is_some_and(|dir| ROOT_DOCUMENTATION_DIRS.iter().any(|x| dir.eq_ignore_ascii_case(x)))
+            || dirs.iter().any(|dir| DOCUMENTATION_DIRS.iter().any(|x| dir.eq_ignore_ascii_case(x)))
+            || DOCUMENTATION_FILES.iter().any(|x| stem.eq_ignore_ascii_case(x))
+}
+
+// e.g. 'vendor/** -linguist-vendored' or '*.h linguist-language=C++'. Empty lines, the ones starting with '#' and the
+// patterns without any 'linguist-*' attributes are skipped, and so are the ones ending with '/', which git doesn't apply to files.
+// An attribute is set with its name or '=true', unset with a leading '-' or '=false', and left as it was with a leading '!'.
+fn parse_patterns(contents: &str) -> Vec<AttributesPattern> {
+    contents.lines().filter_map(|line| {
+        let mut words = line.split_whitespace();
+        let pattern = words.next().filter(|x| !x.starts_with('#') && !x.starts_with('!') && !x.ends_with('/'))?;
+        let mut attributes = LinguistAttributes::default();
+        for word in words {
+            let (name, value) = match word.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (word, None)
+            };
+            let (name, is_set) = match name.strip_prefix('-') {
+                Some(x) => (x, false),
+                None => (name, value.is_none_or(|x| x != "false"))
+            };
+            match name {
+                "linguist-vendored" => attributes.vendored = Some(is_set),
+                "linguist-generated" => attributes.generated = Some(is_set),
+                "linguist-documentation" => attributes.documentation = Some(is_set),
+                "linguist-detectable" => attributes.detectable = Some(is_set),
+                "linguist-language" => attributes.language = value.filter(|x| !x.is_empty()).map(|x| x.to_owned()),
+                _ => ()
+            }
+        }
+        if attributes == LinguistAttributes::default() {
+            return None;
+        }
+
+        // Relative to the root if there is a '/' at the start or in the middle, like for '.gitignore'
+        let is_anchored = pattern.contains('/');
+        let mut segments = pattern.split('/').filter(|x| !x.is_empty()).map(|x| x.to_owned()).collect::<Vec<_>>();
+        if !is_anchored {
+            segments.insert(0, "**".to_owned());
+        }
+        Some(AttributesPattern {segments, attributes})
+    }).collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attributes_of() {
+        let patterns = parse_patterns("# overrides\n\nvendor/** -linguist-vendored\n*.h linguist-language=C++\nsrc/gen/*.rs linguist-generated=true\n\
+                docs/ linguist-documentation\ndata/*.json linguist-detectable\nREADME.md -linguist-documentation\n*.txt text eol=lf\n");
+        assert_eq!(5, patterns.len());
+        let rules = LinguistRules {roots: vec![(PathBuf::from("/p"), patterns)]};
+        let attributes_of = |path: &str| rules.attributes_of(Path::new(path));
+
+        assert_eq!(Some(false), attributes_of("/p/vendor/lib/a.c").vendored);
+        assert_eq!(Some(true), attributes_of("/p/src/node_modules/a.js").vendored);
+        assert_eq!(Some(true), attributes_of("/p/static/jquery.min.js").vendored);
+        assert_eq!(Some("C++".to_owned()), attributes_of("/p/include/a.h").language);
+        assert_eq!(None, attributes_of("/p/include/a.c").language);
+        assert_eq!(Some(true), attributes_of("/p/src/gen/a.rs").generated);
+        assert_eq!(None, attributes_of("/p/src/a.rs").generated);
+        assert_eq!(Some(true), attributes_of("/p/docs/guide/a.py").documentation);
+        assert_eq!(Some(false), attributes_of("/p/src/docs/a.py").documentation);
+        assert_eq!(Some(true), attributes_of("/p/src/Documentation/a.py").documentation);
+        assert_eq!(Some(false), attributes_of("/p/README.md").documentation);
+        assert_eq!(Some(true), attributes_of("/p/src/LICENSE.txt").documentation);
+        assert_eq!(Some(true), attributes_of("/p/data/a.json").detectable);
+        // Outside of the dirs, e.g. a file that was provided itself, only its name is taken into account
+        assert_eq!(Some(false), attributes_of("/other/vendor/a.c").vendored);
+        assert_eq!(Some(true), attributes_of("/other/README").documentation);
+    }
+
+    #[test]
+    fn test_compare() {
+        let language = |name: &str, category: FileCategory| {
+            let mut language = Language::new(name.to_owned(), vec![], vec![], vec![], vec![], vec![]);
+            language.category = category;
+            language
+        };
+        let language_map = hashmap!["C".to_owned() => language("C", FileCategory::Code), "C++".to_owned() => language("C++", FileCategory::Code),
+                "JSON".to_owned() => language("JSON", FileCategory::Data)];
+        let file = |path: &str, language_name: &str, size: u64| DiscoveredFile {path: PathBuf::from(path), extension: String::new(),
+                language_name: language_name.to_owned(), size};
+        let files = [file("/p/src/a.c", "C", 600), file("/p/include/a.h", "C", 200), file("/p/vendor/b.c", "C", 1000),
+                file("/p/package.json", "JSON", 200)];
+        let rules = LinguistRules {roots: vec![(PathBuf::from("/p"), parse_patterns("*.h linguist-language=c++\n"))]};
+
+        let comparison = compare(&files, &language_map, &rules);
+        assert_eq!(vec![LanguageShare {language_name: "C".to_owned(), bytes: 1800, github_bytes: 600},
+                LanguageShare {language_name: "C++".to_owned(), bytes: 0, github_bytes: 200},
+                LanguageShare {language_name: "JSON".to_owned(), bytes: 200, github_bytes: 0}], comparison.languages);
+        assert_eq!(vec![(Exclusion::Vendored, 1, 1000), (Exclusion::NotDetectable, 1, 200)], comparison.exclusions);
+        assert_eq!(vec![("C".to_owned(), "C++".to_owned(), 1, 200)], comparison.reassignments);
+
+        assert_eq!("  Language    mezura               GitHub               Difference\n\
+                    \x20 C               1.8 KBs  90.00%  600.0 Bytes  75.00%      -15.00\n\
+                    \x20 C++                   -       -  200.0 Bytes  25.00%      +25.00\n\
+                    \x20 JSON        200.0 Bytes  10.00%            -       -      -10.00\n\
+                    \n\
+                    \x20 Left out by GitHub:\n\
+                    \x20   vendored        1 files  1.0 KBs\n\
+                    \x20   not detectable  1 files  200.0 Bytes\n\
+                    \n\
+                    \x20 Languages changed by .gitattributes:\n\
+                    \x20   C -> C++  1 files  200.0 Bytes", format_comparison(&comparison));
+    }
+}
@@ -1,10 +1,10 @@
-use std::{collections::HashMap, time::Instant};
+use std::{collections::{HashMap, HashSet}, time::Instant};
 
 use colored::*;
 #[macro_use]
 extern crate include_dir;
 
-use mezura::{*, self, config_manager::{self, CHANGELOG, HELP, SHOW_CONFIGS, SHOW_LANGUAGES, VERSION_ID}, io_handler};
+use mezura::{*, self, badge::BadgeMetric, history, baseline::{self, DEF_CHECK_METRIC, MaxGrowth}, bench::{self, DEF_BENCH_ITERATIONS, MAX_BENCH_ITERATIONS, MIN_BENCH_ITERATIONS}, config_manager::{self, BASELINE, BENCH, CHANGELOG, CHECK, COLD, COMPLETIONS, DUMP_CONFIG, FILE, HELP, ITERATIONS, LISTEN, MAX_GROWTH, MERGE, METRIC, METRICS, SERVE, SHOW_CONFIGS, SNAPSHOT, TREND, QUIET, SHOW_LANGUAGES, SIGN_KEY, UPDATE_BASELINE, VERIFY_REPORT, VERSION_ID}, io_handler};
 
 
 fn main() {
@@ -12,7 +12,41 @@ fn main() {
     #[cfg(target_os = "windows")]
     control::set_virtual_terminal(true).unwrap();
 
-    println!("\n{}",VERSION_ID);
+    let args_str = match read_args_as_str() {
+        Some(args) => {
+            args
+        },
+        None => {
+            String::from("./")
+        }
+    };
+    let (args_str, dump_config) = strip_dump_config_arg(&args_str);
+
+    // Before the version is printed, so that the merged results can be saved as a file
+    match parse_merge_args(&args_str) {
+        Some(Some(paths)) => {
+            match mezura::merge_json_results(&paths) {
+                Ok(x) => println!("{}", x),
+                Err(x) => {
+                    println!("\n{}\n", x.formatted());
+                    std::process::exit(x.exit_code());
+                }
+            }
+            return;
+        },
+        Some(None) => {
+            message_printer::print_help_message_for_command(MERGE);
+            // The code of the arguments that aren't valid, as listed in the README
+            std::process::exit(6);
+        },
+        None => ()
+    }
+
+    // Checked before the configuration is parsed, since the version is printed first. The completions are read by the shells
+    // and the dumped configuration may be saved as a file
+    if !args_str.contains(&(String::from("--") + QUIET)) && !args_str.contains(&(String::from("--") + COMPLETIONS)) && !dump_config {
+        println!("\n{}",VERSION_ID);
+    }
 
     let mut language_map: HashMap<String, Language>;
 
@@ -27,43 +61,120 @@ fn main() {
     } else {
         match io_handler::parse_supported_languages_to_map(&PERSISTENT_APP_PATHS.languages_dir) {
             Ok((_language_map, faulty_files)) => {
-                if !faulty_files.is_empty() {
-                    let mut warn_msg = String::from("\nFormatting problems detected in language files: ");
-                    warn_msg.push_str(&faulty_files.join(", "));
-                    warn_msg.push_str(".\nThese files will not be taken into consideration.");
-                    println!("{}",warn_msg.yellow());
-                }
-
+                print_faulty_language_files_warning(&faulty_files);
                 language_map = _language_map;
             },
             Err(x) => {
                 println!("\n{}", x.formatted());
-                return;
+                std::process::exit(x.exit_code());
             }
         }
     }
 
-    let args_str = match read_args_as_str() {
-        Some(args) => {
-            args
-        },
-        None => {
-            String::from("./")
-        }
-    };
-
     if handle_message_only_command(&args_str, &language_map) {
         return;
     }
 
-    let config = match config_manager::create_config_from_args(&args_str) {
+    match parse_trend_args(&args_str) {
+        Some(Some(metric)) => {
+            if let Err(x) = history::print_trend(metric) {
+                println!("\n{}\n", x.formatted());
+                std::process::exit(x.exit_code());
+            }
+            return;
+        },
+        Some(None) => {
+            message_printer::print_help_message_for_command(TREND);
+            std::process::exit(6);
+        },
+        None => ()
+    }
+
+    let snapshot_args = match parse_snapshot_args(&args_str) {
+        Some(Some(SnapshotCommand::Diff(older, newer))) => {
+            match history::diff_snapshots(&older, &newer) {
+                Ok(x) => println!("\n{}", x),
+                Err(x) => {
+                    println!("\n{}\n", x.formatted());
+                    std::process::exit(x.exit_code());
+                }
+            }
+            return;
+        },
+        Some(Some(SnapshotCommand::Save(name, args))) => Some((name, args)),
+        Some(None) => {
+            message_printer::print_help_message_for_command(SNAPSHOT);
+            std::process::exit(6);
+        },
+        None => None
+    };
+    let bench_args = match parse_bench_args(&args_str) {
+        Some(Some(x)) => Some(x),
+        Some(None) => {
+            message_printer::print_help_message_for_command(BENCH);
+            std::process::exit(6);
+        },
+        None => None
+    };
+    let check_args = match parse_check_args(&args_str) {
+        Some(Some(x)) => Some(x),
+        Some(None) => {
+            message_printer::print_help_message_for_command(CHECK);
+            std::process::exit(6);
+        },
+        None => None
+    };
+    let file_args = match parse_file_args(&args_str) {
+        Some(Some(x)) => Some(x),
+        Some(None) => {
+            message_printer::print_help_message_for_command(FILE);
+            std::process::exit(6);
+        },
+        None => None
+    };
+    let serve_args = parse_serve_args(&args_str);
+    let args_of_config = match (&serve_args, &snapshot_args, &bench_args, &check_args, &file_args) {
+        (Some((_, x)), _, _, _, _) | (_, Some((_, x)), _, _, _) | (_, _, Some((_, _, x)), _, _) | (_, _, _, Some(CheckArgs {args: x, ..}), _)
+                | (_, _, _, _, Some((_, x))) => x,
+        _ => &args_str
+    };
+    let mut config = match config_manager::create_config_from_args(args_of_config) {
         Ok(config) => config,
         Err(x) => {
             println!("\n{}\n",x.formatted());
-            return;
+            std::process::exit(x.exit_code());
         } 
     };
 
+    if dump_config {
+        print!("{}", io_handler::config_builder_to_string(&config_manager::ConfigurationBuilder::from(&config)));
+        return;
+    }
+
+    // The snapshot is the report of the run
+    if let Some((name, _)) = &snapshot_args {
+        match history::prepare_snapshot_path(name) {
+            Ok(x) => config.report_file = Some(x),
+            Err(x) => {
+                println!("\n{}\n", x.formatted());
+                std::process::exit(x.exit_code());
+            }
+        }
+    }
+
+    if let Some(langs_dir) = &config.langs_dir {
+        match io_handler::parse_supported_languages_to_map(langs_dir) {
+            Ok((custom_languages, faulty_files)) => {
+                print_faulty_language_files_warning(&faulty_files);
+                merge_custom_languages(&mut language_map, custom_languages);
+            },
+            Err(x) => {
+                println!("\n{} ({})\n", x.formatted(), langs_dir);
+                std::process::exit(x.exit_code());
+            }
+        }
+    }
+
     if !config.languages_of_interest.is_empty() {
         match retain_only_languages_of_interest(&mut language_map, &config.languages_of_interest) {
             Ok(x) => {
@@ -73,26 +184,171 @@ fn main() {
             },
             Err(_) => {
                 println!("\n{}\n","Error: None of the provided language names map to valid supported languages".red());
-                return;
+                std::process::exit(6);
             }
         }
     }
 
+    if !config.extension_filters.is_empty() {
+        match retain_only_extensions_of_interest(&mut language_map, &config.extension_filters) {
+            Ok(x) => {
+                if let Some(msg) = x {
+                    println!("\n {}",msg);
+                }
+            },
+            Err(_) => {
+                println!("\n{}\n","Error: None of the provided extensions belong to the supported languages".red());
+                std::process::exit(6);
+            }
+        }
+    }
+
+    // Set either way, so that '--color always' also overrides the environment
+    control::set_override(config.uses_colors());
+
+    // The clone is removed when the checkout is dropped, at the end of the execution
+    let _remote_checkout = match &config.remote {
+        Some(url) => {
+            if !config.is_quiet() {
+                println!("\nCloning '{}'...", url);
+            }
+            match remote::clone_shallow(url) {
+                Ok(x) => {
+                    config.dirs = vec![x.path_str()];
+                    Some(x)
+                },
+                Err(x) => {
+                    println!("\n{}\n", format!("Unable to clone the repository: {}", x).red());
+                    // The code of git being unavailable, as listed in the README
+                    std::process::exit(5);
+                }
+            }
+        },
+        None => None
+    };
+
+    if let Some(source) = &config.files_from {
+        match io_handler::read_file_list(source) {
+            Ok(x) => config.dirs.extend(x),
+            Err(x) => {
+                println!("\n{}\n", format!("Unable to read the list of files: {}", x).red());
+                // The code of the files that couldn't be read, as listed in the README
+                drop(_remote_checkout);
+                std::process::exit(5);
+            }
+        }
+    }
+
+    if let Some((iterations, cold, _)) = bench_args {
+        match bench::run_bench(config, language_map, iterations, cold) {
+            Ok(x) => {
+                println!("\n{}", bench::format_bench(&x, bench::read_last_bench()));
+                if let Err(x) = bench::save_last_bench(bench::BenchSummary::new(&x.metrics)) {
+                    println!("{}", format!("Unable to keep the bench for the next one: {}", x).yellow());
+                }
+            },
+            Err(x) => {
+                println!("{}",x.formatted());
+                drop(_remote_checkout);
+                std::process::exit(x.exit_code());
+            }
+        }
+        return;
+    }
+
+    if let Some(check_args) = check_args {
+        match baseline::run_check(config, language_map, &check_args.baseline, &check_args.metric, check_args.max_growth, check_args.update) {
+            Ok(x) => {
+                let text = baseline::format_check(&x);
+                println!("\n{}\n", if x.passed() {text.bright_green()} else {text.red()});
+                if !x.passed() {
+                    drop(_remote_checkout);
+                    std::process::exit(x.exit_code());
+                }
+            },
+            Err(x) => {
+                println!("{}",x.formatted());
+                drop(_remote_checkout);
+                std::process::exit(x.exit_code());
+            }
+        }
+        return;
+    }
+
+    if let Some((path, _)) = file_args {
+        match single_file::analyze_file(&config, language_map, &path) {
+            Ok(x) => println!("\n{}\n", single_file::format_file_stats(&x, &config)),
+            Err(x) => {
+                println!("\n{}\n", x.formatted());
+                drop(_remote_checkout);
+                std::process::exit(x.exit_code());
+            }
+        }
+        return;
+    }
+
+    if let Some((listen, _)) = serve_args {
+        match listen {
+            Some(listen) => if let Err(x) = mezura::serve_with_reloader(&listen, config.clone(), language_map, Some(create_languages_reloader(config))) {
+                println!("\n{}\n", x.formatted());
+                drop(_remote_checkout);
+                std::process::exit(x.exit_code());
+            },
+            None => {
+                message_printer::print_help_message_for_command(SERVE);
+                drop(_remote_checkout);
+                std::process::exit(6);
+            }
+        }
+        return;
+    }
+
     let instant = Instant::now();
+    let is_quiet = config.is_quiet();
     match mezura::run(config, language_map) {
-        Ok(x) => {
-            let perf = format!("\nExec time: {:.2} secs ", instant.elapsed().as_secs_f32());
-            let metrics = match x {
-                Some(x) => format!("(Parsing {} files/s | {} lines/s)", with_seperators(x.files_per_sec), with_seperators(x.lines_per_sec)),
-                None => String::new()
-            };
-            println!("{}",perf + &metrics);
-        },
-        Err(x) => println!("{}",x.formatted())
+        Ok(_) if is_quiet => (),
+        Ok(Some(x)) => println!("\n{}", format_footer(&x, instant.elapsed())),
+        Ok(None) => println!("\nExec time: {:.2} secs", instant.elapsed().as_secs_f32()),
+        Err(x) => {
+            println!("{}",x.formatted());
+            // So that CI jobs can tell the failures apart. The clone isn't dropped by the exit
+            drop(_remote_checkout);
+            std::process::exit(x.exit_code());
+        }
     }
 }
 
 
+fn print_faulty_language_files_warning(faulty_files: &[String]) {
+    if !faulty_files.is_empty() {
+        let mut warn_msg = String::from("\nFormatting problems detected in language files: ");
+        warn_msg.push_str(&faulty_files.join(", "));
+        warn_msg.push_str(".\nThese files will not be taken into consideration.");
+        println!("{}",warn_msg.yellow());
+    }
+}
+
+// The language files of the persistent dir and of '--langs-dir', made into languages as at the start of the execution
+// whenever they change while serving. The warnings of the faulty files are not printed
+fn create_languages_reloader(config: Configuration) -> LanguagesReloader {
+    let dirs = std::iter::once(PERSISTENT_APP_PATHS.languages_dir.clone()).chain(config.langs_dir.clone()).collect();
+    let load = move || {
+        let mut language_map = io_handler::parse_supported_languages_to_map(&PERSISTENT_APP_PATHS.languages_dir).map_err(|x| x.to_string())?.0;
+        if let Some(langs_dir) = &config.langs_dir {
+            let custom_languages = io_handler::parse_supported_languages_to_map(langs_dir).map_err(|x| format!("{} ({})", x, langs_dir))?.0;
+            merge_custom_languages(&mut language_map, custom_languages);
+        }
+        if !config.languages_of_interest.is_empty() && retain_only_languages_of_interest(&mut language_map, &config.languages_of_interest).is_err() {
+            return Err("None of the provided language names map to valid supported languages".to_owned());
+        }
+        if !config.extension_filters.is_empty() && retain_only_extensions_of_interest(&mut language_map, &config.extension_filters).is_err() {
+            return Err("None of the provided extensions belong to the supported languages".to_owned());
+        }
+        Ok(language_map)
+    };
+    LanguagesReloader {dirs, load: Box::new(load)}
+}
+
 fn retain_only_languages_of_interest(language_map: &mut HashMap<String, Language>, languages_of_interest: &[String]) -> Result<Option<ColoredString>,()> 
 {
     language_map.retain(|s, _| languages_of_interest.iter().any(|x| x.to_lowercase() == s.to_lowercase()));
@@ -118,6 +374,53 @@ fn retain_only_languages_of_interest(language_map: &mut HashMap<String, Language
     }
 }
 
+// The filters of '--ext' are applied in order, starting from all the extensions if the first one removes some.
+// The languages that are left without extensions are removed.
+fn retain_only_extensions_of_interest(language_map: &mut HashMap<String, Language>, extension_filters: &[String]) -> Result<Option<ColoredString>,()> {
+    let all_extensions = language_map.values().flat_map(|x| x.extensions.iter().map(|x| x.to_lowercase())).collect::<HashSet<_>>();
+    let is_markup = |x: &String| domain::MARKUP_EXTENSIONS.contains(&x.as_str());
+    let get_group = |name: &str| match name {
+        "all" => Some(all_extensions.clone()),
+        "markup" => Some(all_extensions.iter().filter(|x| is_markup(x)).cloned().collect::<HashSet<_>>()),
+        "all-code" => Some(all_extensions.iter().filter(|x| !is_markup(x)).cloned().collect::<HashSet<_>>()),
+        _ => None
+    };
+
+    let mut extensions = if extension_filters.first().is_some_and(|x| x.starts_with('-')) {all_extensions.clone()} else {HashSet::new()};
+    let mut non_existant_extensions = Vec::new();
+    for filter in extension_filters {
+        let (is_removal, name) = match filter.strip_prefix('-') {
+            Some(x) => (true, x),
+            None => (false, filter.as_str())
+        };
+        let group = get_group(name).unwrap_or_else(|| {
+            if !all_extensions.contains(name) {
+                non_existant_extensions.push(name);
+            }
+            HashSet::from([name.to_owned()])
+        });
+        if is_removal {
+            extensions.retain(|x| !group.contains(x));
+        } else {
+            extensions.extend(group);
+        }
+    }
+
+    language_map.retain(|_, language| {
+        language.extensions.retain(|x| extensions.contains(&x.to_lowercase()));
+        !language.extensions.is_empty()
+    });
+    if language_map.is_empty() {
+        return Err(());
+    }
+
+    if !non_existant_extensions.is_empty() {
+        Ok(Some(format!("\nThese extensions don't belong to any language:\n {}", non_existant_extensions.join(" , ")).yellow()))
+    } else {
+        Ok(None)
+    }
+}
+
 
 fn read_baked_in_languages_dir() -> HashMap<String, Language> {
     let mut lang_files = HashMap::with_capacity(20);
@@ -162,6 +465,9 @@ fn handle_message_only_command(args_str: &str, language_map: &HashMap<String,Lan
     if args_str.contains(&(String::from("--") + HELP)) {
         message_printer::print_help_message_for_given_args(args_str);
         return true; 
+    } else if args_str.contains(&(String::from("--") + COMPLETIONS)) {
+        message_printer::print_completions(args_str);
+        return true;
     } else if args_str.contains(&(String::from("--") + CHANGELOG)) {
         message_printer::print_changelog();
         return true;
@@ -171,23 +477,302 @@ fn handle_message_only_command(args_str: &str, language_map: &HashMap<String,Lan
     } else if args_str.contains(&(String::from("--") + SHOW_CONFIGS)) {
         message_printer::print_existing_configs();
         return true;
+    } else if args_str.contains(&(String::from("--") + VERIFY_REPORT)) {
+        verify_report(args_str);
+        return true;
     }
 
     false
 }
 
+// 'serve --listen <address> <dirs and options>' -> the address and the arguments of the configuration.
+// None if it is not the 'serve' subcommand.
+fn parse_serve_args(args_str: &str) -> Option<(Option<String>, String)> {
+    let rest = args_str.strip_prefix(SERVE).filter(|x| x.is_empty() || x.starts_with(' '))?;
+    let mut commands = rest.split("--");
+    let (mut listen, mut args) = (None, commands.next().unwrap_or("").trim().to_owned());
+    for command in commands {
+        if let Some(x) = command.strip_prefix(LISTEN) {
+            listen = get_trimmed_if_not_empty(x);
+        } else {
+            args += &(String::from(" --") + command.trim_end());
+        }
+    }
+
+    // Like without 'serve', the current dir is analyzed if there are no arguments
+    Some((listen, if args.trim().is_empty() {String::from("./")} else {args.trim().to_owned()}))
+}
+
+// The arguments without '--dump-config', and whether it was given. The rest of the arguments are parsed as usual,
+// so that the configuration that is printed is the one that they would be run with.
+fn strip_dump_config_arg(args_str: &str) -> (String, bool) {
+    let mut commands = args_str.split("--");
+    let mut args = commands.next().unwrap_or("").trim().to_owned();
+    let mut dump_config = false;
+    for command in commands {
+        if command.trim() == DUMP_CONFIG {
+            dump_config = true;
+        } else {
+            args += &(String::from(" --") + command.trim_end());
+        }
+    }
+
+    if !dump_config {
+        return (args_str.to_owned(), false);
+    }
+    (if args.trim().is_empty() {String::from("./")} else {args.trim().to_owned()}, true)
+}
+
+// 'bench <dirs and options> --iterations <N> --cold' -> the iterations, whether the caches are dropped and the arguments
+// of the configuration. None if it is not the 'bench' subcommand, and Some(None) if the iterations are not valid.
+fn parse_bench_args(args_str: &str) -> Option<Option<(usize, bool, String)>> {
+    let rest = args_str.strip_prefix(BENCH).filter(|x| x.is_empty() || x.starts_with(' '))?;
+    let mut commands = rest.split("--");
+    let (mut iterations, mut cold, mut args) = (DEF_BENCH_ITERATIONS, false, commands.next().unwrap_or("").trim().to_owned());
+    for command in commands {
+        if let Some(x) = command.strip_prefix(ITERATIONS) {
+            match parse_usize_value(x, MIN_BENCH_ITERATIONS, MAX_BENCH_ITERATIONS) {
+                Some(x) => iterations = x,
+                None => return Some(None)
+            }
+        } else if command.trim() == COLD {
+            cold = true;
+        } else {
+            args += &(String::from(" --") + command.trim_end());
+        }
+    }
+
+    // Like without 'bench', the current dir is analyzed if there are no arguments
+    Some(Some((iterations, cold, if args.trim().is_empty() {String::from("./")} else {args.trim().to_owned()})))
+}
+
+#[derive(Debug, PartialEq)]
+struct CheckArgs {
+    baseline: String,
+    max_growth: MaxGrowth,
+    metric: String,
+    // The results are saved as the baseline instead of being compared with it
+    update: bool,
+    // The dirs and options of the run
+    args: String
+}
+
+// 'check <dirs and options> --baseline <file> --max-growth <N%|N> --metric <metric> --update-baseline'.
+// None if it is not the 'check' subcommand, and Some(None) if the baseline is missing or the growth or the metric are not valid.
+fn parse_check_args(args_str: &str) -> Option<Option<CheckArgs>> {
+    let rest = args_str.strip_prefix(CHECK).filter(|x| x.is_empty() || x.starts_with(' '))?;
+    let mut commands = rest.split("--");
+    let mut args = commands.next().unwrap_or("").trim().to_owned();
+    let (mut baseline, mut max_growth, mut metric, mut update) = (None, MaxGrowth::Absolute(0), DEF_CHECK_METRIC.to_owned(), false);
+    for command in commands {
+        if let Some(x) = command.strip_prefix(BASELINE) {
+            baseline = get_trimmed_if_not_empty(x);
+        } else if let Some(x) = command.strip_prefix(MAX_GROWTH) {
+            match MaxGrowth::parse(x) {
+                Some(x) => max_growth = x,
+                None => return Some(None)
+            }
+        // Before '--metric', which it starts with
+        } else if command.starts_with(METRICS) {
+            args += &(String::from(" --") + command.trim_end());
+        } else if let Some(x) = command.strip_prefix(METRIC) {
+            match get_trimmed_if_not_empty(x) {
+                Some(x) => metric = x,
+                None => return Some(None)
+            }
+        } else if command.trim() == UPDATE_BASELINE {
+            update = true;
+        } else {
+            args += &(String::from(" --") + command.trim_end());
+        }
+    }
+
+    let baseline = match baseline {
+        Some(x) => x,
+        None => return Some(None)
+    };
+    // Like without 'check', the current dir is analyzed if there are no arguments
+    Some(Some(CheckArgs {baseline, max_growth, metric, update, args: if args.trim().is_empty() {String::from("./")} else {args.trim().to_owned()}}))
+}
+
+// 'file <path> <options>' -> the path of the file and the arguments of the configuration, that start with the path.
+// None if it is not the 'file' subcommand, and Some(None) if the path is missing.
+fn parse_file_args(args_str: &str) -> Option<Option<(String, String)>> {
+    let rest = args_str.strip_prefix(FILE).filter(|x| x.is_empty() || x.starts_with(' '))?.trim();
+    match rest.split_whitespace().next() {
+        Some(x) if !x.starts_with("--") => Some(Some((x.to_owned(), rest.to_owned()))),
+        _ => Some(None)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum SnapshotCommand {
+    // The name, and the dirs and options of the run
+    Save(String, String),
+    // The older and the newer snapshot
+    Diff(String, String)
+}
+
+// 'snapshot save <name> <dirs and options>' or 'snapshot diff <older> <newer>'.
+// None if it is not the 'snapshot' subcommand, and Some(None) if its arguments are missing.
+fn parse_snapshot_args(args_str: &str) -> Option<Option<SnapshotCommand>> {
+    let rest = args_str.strip_prefix(SNAPSHOT).filter(|x| x.is_empty() || x.starts_with(' '))?;
+    let mut words = rest.split_whitespace();
+    let command = match (words.next(), words.next()) {
+        (Some("save"), Some(name)) => {
+            // Like without 'snapshot', the current dir is analyzed if there are no arguments
+            let args = words.collect::<Vec<_>>().join(" ");
+            Some(SnapshotCommand::Save(name.to_owned(), if args.is_empty() {String::from("./")} else {args}))
+        },
+        (Some("diff"), Some(older)) => match (words.next(), words.next()) {
+            (Some(newer), None) => Some(SnapshotCommand::Diff(older.to_owned(), newer.to_owned())),
+            _ => None
+        },
+        _ => None
+    };
+    Some(command)
+}
+
+// 'trend [metric]' -> the metric, which is 'lines' if it is not provided.
+// None if it is not the 'trend' subcommand, and Some(None) if the metric is not valid.
+fn parse_trend_args(args_str: &str) -> Option<Option<BadgeMetric>> {
+    let rest = args_str.strip_prefix(TREND).filter(|x| x.is_empty() || x.starts_with(' '))?;
+    match rest.trim() {
+        "" => Some(Some(BadgeMetric::Lines)),
+        x => Some(BadgeMetric::parse(x))
+    }
+}
+
+// 'merge <files>' -> the paths of the json files, separated by spaces.
+// None if it is not the 'merge' subcommand, and Some(None) if there are no files.
+fn parse_merge_args(args_str: &str) -> Option<Option<Vec<String>>> {
+    let rest = args_str.strip_prefix(MERGE).filter(|x| x.is_empty() || x.starts_with(' '))?;
+    let paths = rest.split_whitespace().map(|x| x.to_owned()).collect::<Vec<_>>();
+    Some(if paths.is_empty() {None} else {Some(paths)})
+}
+
+// Exits with a code other than 0 if the report can't be verified, so that it can be a step of a CI job
+fn verify_report(args_str: &str) {
+    let (mut path, mut key_path) = (None, None);
+    for command in args_str.split("--") {
+        if let Some(x) = command.strip_prefix(VERIFY_REPORT) {
+            path = get_trimmed_if_not_empty(x);
+        } else if let Some(x) = command.strip_prefix(SIGN_KEY) {
+            key_path = get_trimmed_if_not_empty(x);
+        }
+    }
+
+    let path = match path {
+        Some(x) => x,
+        None => {
+            message_printer::print_help_message_for_command(VERIFY_REPORT);
+            std::process::exit(6);
+        }
+    };
+    let result = match key_path {
+        Some(x) => io_handler::read_sign_key(&x).map_err(|_| io_handler::ReportVerificationError::UnreadableKey(x))
+                .and_then(|key| io_handler::verify_report(&path, Some(&key))),
+        None => io_handler::verify_report(&path, None)
+    };
+    match result {
+        Ok(true) => println!("\n{}\n", "The checksum and the signature of the report are valid.".bright_green()),
+        Ok(false) => println!("\n{}\n", "The checksum of the report is valid (provide '--sign-key' to also verify the signature).".bright_green()),
+        Err(x) => {
+            println!("\n{}\n", x.formatted());
+            std::process::exit(x.exit_code());
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use mezura::{Language, hashmap};
 
-    use crate::retain_only_languages_of_interest;
+    use mezura::{badge::BadgeMetric, baseline::MaxGrowth};
+
+    use crate::{CheckArgs, SnapshotCommand, parse_bench_args, parse_check_args, parse_file_args, parse_merge_args, parse_serve_args, parse_snapshot_args, parse_trend_args, retain_only_extensions_of_interest, retain_only_languages_of_interest};
+
+    #[test]
+    fn test_parse_serve_args() {
+        assert_eq!(Some((Some("127.0.0.1:8080".to_owned()), "./".to_owned())), parse_serve_args("serve --listen 127.0.0.1:8080"));
+        assert_eq!(Some((Some("0.0.0.0:80".to_owned()), "src, tests --exclude target --threads 4".to_owned())),
+                parse_serve_args("serve src, tests --exclude target --listen 0.0.0.0:80 --threads 4"));
+        assert_eq!(Some((None, "./".to_owned())), parse_serve_args("serve"));
+        assert_eq!(None, parse_serve_args("server --threads 4"));
+        assert_eq!(None, parse_serve_args("./ --threads 4"));
+    }
+
+    #[test]
+    fn test_parse_bench_args() {
+        assert_eq!(Some(Some((5, false, "./".to_owned()))), parse_bench_args("bench"));
+        assert_eq!(Some(Some((20, true, "src --exclude target".to_owned()))), parse_bench_args("bench src --iterations 20 --exclude target --cold"));
+        assert_eq!(Some(None), parse_bench_args("bench --iterations 0"));
+        assert_eq!(None, parse_bench_args("benches"));
+        assert_eq!(None, parse_bench_args("./ --threads 4"));
+    }
+
+    #[test]
+    fn test_parse_check_args() {
+        assert_eq!(Some(Some(CheckArgs {baseline: "base.json".to_owned(), max_growth: MaxGrowth::Absolute(0), metric: "code".to_owned(), update: false,
+                args: "./".to_owned()})), parse_check_args("check --baseline base.json"));
+        assert_eq!(Some(Some(CheckArgs {baseline: "base.json".to_owned(), max_growth: MaxGrowth::Percentage(5.0), metric: "rust.unsafe".to_owned(),
+                update: true, args: "src --metrics r = code / lines --exclude target".to_owned()})),
+                parse_check_args("check src --metrics r = code / lines --baseline base.json --max-growth 5% --metric rust.unsafe --exclude target --update-baseline"));
+        assert_eq!(Some(None), parse_check_args("check src"));
+        assert_eq!(Some(None), parse_check_args("check --baseline base.json --max-growth a lot"));
+        assert_eq!(None, parse_check_args("checks --baseline base.json"));
+        assert_eq!(None, parse_check_args("./ --threads 4"));
+    }
+
+    #[test]
+    fn test_parse_file_args() {
+        assert_eq!(Some(Some(("src/lib.rs".to_owned(), "src/lib.rs".to_owned()))), parse_file_args("file src/lib.rs"));
+        assert_eq!(Some(Some(("src/lib.rs".to_owned(), "src/lib.rs --zero-keywords --todos".to_owned()))),
+                parse_file_args("file src/lib.rs --zero-keywords --todos "));
+        assert_eq!(Some(None), parse_file_args("file"));
+        assert_eq!(Some(None), parse_file_args("file --todos"));
+        assert_eq!(None, parse_file_args("files src/lib.rs"));
+        assert_eq!(None, parse_file_args("./ --threads 4"));
+    }
+
+    #[test]
+    fn test_parse_snapshot_args() {
+        assert_eq!(Some(Some(SnapshotCommand::Save("v1.0".to_owned(), "./".to_owned()))), parse_snapshot_args("snapshot save v1.0"));
+        assert_eq!(Some(Some(SnapshotCommand::Save("v2.0".to_owned(), "src --exclude target".to_owned()))),
+                parse_snapshot_args("snapshot save v2.0 src --exclude target"));
+        assert_eq!(Some(Some(SnapshotCommand::Diff("v1.0".to_owned(), "v2.0".to_owned()))), parse_snapshot_args("snapshot diff v1.0 v2.0"));
+        assert_eq!(Some(None), parse_snapshot_args("snapshot diff v1.0"));
+        assert_eq!(Some(None), parse_snapshot_args("snapshot diff a b c"));
+        assert_eq!(Some(None), parse_snapshot_args("snapshot"));
+        assert_eq!(None, parse_snapshot_args("snapshots save v1.0"));
+        assert_eq!(None, parse_snapshot_args("./ --threads 4"));
+    }
+
+    #[test]
+    fn test_parse_trend_args() {
+        assert_eq!(Some(Some(BadgeMetric::Lines)), parse_trend_args("trend"));
+        assert_eq!(Some(Some(BadgeMetric::Code)), parse_trend_args("trend code"));
+        assert_eq!(Some(None), parse_trend_args("trend bytes"));
+        assert_eq!(None, parse_trend_args("trends"));
+        assert_eq!(None, parse_trend_args("./ --threads 4"));
+    }
+
+    #[test]
+    fn test_parse_merge_args() {
+        assert_eq!(Some(Some(vec!["a.json".to_owned(), "shards/b.json".to_owned()])), parse_merge_args("merge a.json  shards/b.json"));
+        assert_eq!(Some(None), parse_merge_args("merge"));
+        assert_eq!(None, parse_merge_args("merged a.json"));
+        assert_eq!(None, parse_merge_args("./ --threads 4"));
+    }
 
     #[test]
     fn test_retain_only_languages_of_interest() {
         let languages_of_interest = vec!["java".to_owned()];
         let mut language_map = hashmap![
-                "Java".to_owned() => Language::new("Java".to_owned(),vec![],vec![],vec!["\"".to_owned()],None,None,vec![]),
-                "C#".to_owned() => Language::new("C#".to_owned(),vec![],vec![],vec!["\"".to_owned()],None,None,vec![])];
+                "Java".to_owned() => Language::new("Java".to_owned(),vec![],vec![],vec!["\"".to_owned()],vec![],vec![]),
+                "C#".to_owned() => Language::new("C#".to_owned(),vec![],vec![],vec!["\"".to_owned()],vec![],vec![])];
 
         let result = retain_only_languages_of_interest(&mut language_map, &languages_of_interest);
         assert!(result.unwrap().is_none());
@@ -195,8 +780,8 @@ mod tests {
         
         let languages_of_interest = vec!["java".to_owned(),"c++".to_owned(),"Rust".to_owned()];
         let mut language_map = hashmap![
-                "Java".to_owned() => Language::new("Java".to_owned(),vec![],vec![],vec!["\"".to_owned()],None,None,vec![]),
-                "C#".to_owned() => Language::new("C#".to_owned(),vec![],vec![],vec!["\"".to_owned()],None,None,vec![])];
+                "Java".to_owned() => Language::new("Java".to_owned(),vec![],vec![],vec!["\"".to_owned()],vec![],vec![]),
+                "C#".to_owned() => Language::new("C#".to_owned(),vec![],vec![],vec!["\"".to_owned()],vec![],vec![])];
 
         let result = retain_only_languages_of_interest(&mut language_map, &languages_of_interest);
         assert!(result.unwrap().is_some());
@@ -204,11 +789,52 @@ mod tests {
         
         let languages_of_interest = vec!["c++".to_owned(),"Rust".to_owned()];
         let mut language_map = hashmap![
-                "Java".to_owned() => Language::new("Java".to_owned(),vec![],vec![],vec!["\"".to_owned()],None,None,vec![]),
-                "C#".to_owned() => Language::new("C#".to_owned(),vec![],vec![],vec!["\"".to_owned()],None,None,vec![])];
+                "Java".to_owned() => Language::new("Java".to_owned(),vec![],vec![],vec!["\"".to_owned()],vec![],vec![]),
+                "C#".to_owned() => Language::new("C#".to_owned(),vec![],vec![],vec!["\"".to_owned()],vec![],vec![])];
 
         let result = retain_only_languages_of_interest(&mut language_map, &languages_of_interest);
         assert!(result.is_err());
         assert!(language_map.len() == 0);
     }
+
+    #[test]
+    fn test_retain_only_extensions_of_interest() {
+        let language = |name: &str, extensions: &[&str]| Language::new(name.to_owned(), extensions.iter().map(|x| x.to_string()).collect(),
+                vec![], vec!["\"".to_owned()], vec![], vec![]);
+        let language_map = || hashmap![
+                "Rust".to_owned() => language("Rust", &["rs"]),
+                "JavaScript".to_owned() => language("JavaScript", &["js", "json"]),
+                "Markdown".to_owned() => language("Markdown", &["md", "markdown"])];
+        let filters = |x: &str| mezura::utils::parse_extension_filters(x);
+        let extensions_of = |language_map: &HashMap<String, Language>| {
+            let mut extensions = language_map.values().flat_map(|x| x.extensions.clone()).collect::<Vec<_>>();
+            extensions.sort();
+            extensions
+        };
+
+        let mut map = language_map();
+        assert!(retain_only_extensions_of_interest(&mut map, &filters("all,-json,-md")).unwrap().is_none());
+        assert_eq!(vec!["js", "markdown", "rs"], extensions_of(&map));
+
+        let mut map = language_map();
+        retain_only_extensions_of_interest(&mut map, &filters("-json")).unwrap();
+        assert_eq!(vec!["js", "markdown", "md", "rs"], extensions_of(&map));
+
+        let mut map = language_map();
+        retain_only_extensions_of_interest(&mut map, &filters("all-code")).unwrap();
+        assert_eq!(vec!["js", "rs"], extensions_of(&map));
+        assert!(!map.contains_key("Markdown"));
+
+        let mut map = language_map();
+        retain_only_extensions_of_interest(&mut map, &filters("markup,-md")).unwrap();
+        assert_eq!(vec!["json", "markdown"], extensions_of(&map));
+
+        let mut map = language_map();
+        assert!(retain_only_extensions_of_interest(&mut map, &filters("rs,py")).unwrap().is_some());
+        assert_eq!(vec!["rs"], extensions_of(&map));
+
+        let mut map = language_map();
+        assert!(retain_only_extensions_of_interest(&mut map, &filters("all,-all")).is_err());
+        assert!(map.is_empty());
+    }
 }
\ No newline at end of file
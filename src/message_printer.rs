@@ -17,6 +17,34 @@ pub const DIRS_HELP  :  &str =
     1) as the first arguments of the program directly
     2) if they are present in a configuration file (see '--save' and '--load' commands).
 
+    A .zip, .tar, .tar.gz or .tgz file is analyzed like a directory, reading its files in memory
+    without extracting them. Nothing is inflated to more than 100 times its compressed size (at
+    least 1 MiB), the '--max-memory' budget or the declared sizes of the files. A file that goes
+    over is counted as faulty, and a .tar.gz stops being read there.
+
+    A jupyter notebook (.ipynb) is counted as the language of its kernel, e.g. Python, but only its
+    code cells are parsed. Its code and markdown cells are counted in the 'Notebooks' section.
+
+"; 
+pub const REMOTE_HELP  :  &str = 
+"--remote
+    1 argument: the url of a git repository. Default: none
+
+    Clones the latest commit of the repository (e.g. https://github.com/user/project.git or
+    git@github.com:user/project.git) into a temporary directory, analyzes it like a local directory
+    and removes it afterwards. It can't be used along with target directories.
+    It needs 'git' to be installed.
+
+"; 
+pub const FILES_FROM_HELP  :  &str = 
+"--files-from
+    1 argument: the path of a file with a list of files, one per line, or - for the standard input.
+    Default: none
+
+    Analyzes the listed files instead of searching a directory, for example the output of
+    'git diff --name-only' or 'find'. The paths that don't exist are skipped. If target directories
+    are provided as well, the listed files are analyzed along with them. Never saved in configuration files.
+
 "; 
 pub const EXCLUDE_HELP  :  &str = 
 "--exclude 
@@ -28,6 +56,243 @@ pub const EXCLUDE_HELP  :  &str =
 
     The program will ignore these dirs.
 
+"; 
+pub const SKIP_BUILD_OUTPUTS_HELP  :  &str = 
+"--skip-build-outputs
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no 
+
+    Skips the directories with the outputs of the build tools of every project that is found:
+    the target dir of Cargo (as reported by 'cargo metadata'), 'node_modules', 'dist', 'build' etc and
+    the 'outDir' of tsconfig.json next to a package.json, 'build' of Gradle and 'target' of Maven.
+
+"; 
+pub const SORT_HELP  :  &str = 
+"--sort
+    1..2 arguments: files, lines, code, size or name, optionally followed by asc or desc, case-insensitive.
+    Default: by the number of files and the size. The numbers are sorted descending and the names
+    ascending, unless asc or desc is provided.
+
+    The order of the languages in the Details, the markdown tables and the overview, e.g. '--sort code'.
+    The overview keeps the first three languages in this order (see '--top'), and puts the rest in 'others'.
+
+"; 
+pub const COLUMNS_HELP  :  &str = 
+"--columns
+    1..n arguments: files, lines, code, comments (or extra), size, keywords and share, separated by
+    commas, case-insensitive. Default: all of them
+
+    The stats that are shown for every language in the Details and in the sum, in the text output,
+    e.g. '--columns files,lines,code' for a narrow terminal. 'comments' are the extra lines, which are
+    the comments and the blank lines. 'share' is the percentage of the total lines and code lines
+    that each language has, which is left out of the sum and when there is only one language.
+    The other outputs and the logs always have all of them.
+
+";
+pub const OVERVIEW_HELP  :  &str = 
+"--overview
+    1..n arguments: files, lines, code, comments (or extra), size and keywords, separated by commas,
+    case-insensitive. Default: files,lines,size
+
+    The lines of the overview and their order, e.g. '--overview code,files' to compare the languages
+    by their code lines first. 'comments' are the extra lines and 'keywords' the keyword occurrences.
+
+";
+pub const TOP_HELP  :  &str = 
+"--top
+    1 argument: a number from 1 to 1000. Default: all the languages in the Details and 3 in the overview
+
+    How many languages are shown individually in the Details and the overview, in the order of '--sort'.
+    The rest are summed up in 'others', without their keywords, unless there is only one of them.
+
+"; 
+pub const QUIET_HELP  :  &str = 
+"--quiet
+    No arguments. Default: no
+
+    Prints only the results and the errors: a summary line with the totals, or the tables with
+    '--output markdown'. The banners, the counts of the files and the saved files are not printed.
+    It can't be combined with '--verbose'.
+
+"; 
+pub const VERBOSE_HELP  :  &str = 
+"--verbose
+    No arguments. Default: no
+
+    Also prints every file as it is parsed, with its language and lines, the faulty files with their
+    errors, the directories that couldn't be read (whose number is among the warnings either way),
+    and how long each search and parser thread took. The progress bar is not shown.
+    It can't be combined with '--quiet'.
+
+"; 
+pub const LANG_HELP  :  &str = 
+"--lang
+    1 argument: en, de, fr or es, case-insensitive. Default: en
+
+    The language of the labels and the section titles of the printed results. The logs and the
+    reports keep their english keys, so that they can still be read back.
+
+"; 
+pub const OUTPUT_HELP  :  &str = 
+"--output
+    1 argument: text, markdown, prometheus, json, xml or yaml, case-insensitive. Default: text
+
+    With 'markdown' the Details and the sum are printed as tables of GitHub-flavored markdown and
+    nothing is colored, so that the results can be pasted in pull requests and READMEs. With
+    'prometheus' they are printed as gauges per language in the Prometheus text format (e.g.
+    'mezura_code_lines{language=\"Rust\"} 7065'), to be pushed to a Pushgateway. With 'json', 'xml'
+    and 'yaml' they are printed as a document with the totals and the files, lines, size and keywords
+    of each language. The json is the same as the one of 'serve', and the one that 'merge' reads.
+    The warnings of the run (e.g. the directories that couldn't be read or the unknown options of
+    the configuration file) are in its 'warnings' array, instead of in the 'Warnings' section that
+    is printed after the text results.
+    The messages of the run, e.g. the counts of the files and the lines of '--verbose', are printed
+    to stderr with all of them, and along with '--quiet' only the metrics or the document are printed. The visual overview is left out of all of them.
+
+"; 
+pub const COLOR_HELP  :  &str = 
+"--color
+    1 argument: auto, always or never, case-insensitive. Default: auto
+
+    Whether the output is colored. With 'auto' it is not colored when the NO_COLOR environment
+    variable is set, or when the output is not a terminal (e.g. redirected to a file). Without
+    colors, the languages are told apart in the bars of the overview by the symbol next to their names.
+
+"; 
+pub const NO_COLOR_HELP  :  &str = 
+"--no-color
+    No arguments. Default: no
+
+    The same as '--color never'.
+
+"; 
+pub const PATH_STYLE_HELP  :  &str = 
+"--path-style
+    1 argument: absolute, unix, os or relative, case-insensitive. Default: absolute
+
+    How the paths are printed (faulty files, conflicting shebangs, roots, directories and the report).
+    'absolute' prints them as they were found, 'unix' with '/' as the separator, 'os' with the separator
+    of the operating system and 'relative' relative to the working directory with '/' as the separator,
+    so that the outputs of different machines can be compared.
+
+"; 
+pub const SIZE_UNITS_HELP  :  &str = 
+"--size-units
+    1 argument: si, binary or bytes, case-insensitive. Default: si
+
+    How the sizes are printed. 'si' prints them in powers of 1000 (KBs, MBs), 'binary' in powers of
+    1024 (KiBs, MiBs) and 'bytes' the exact number of bytes, e.g. to audit the results.
+
+";
+pub const SIZE_PRECISION_HELP  :  &str = 
+"--size-precision
+    1 argument: the decimal places of the sizes, from 0 to 3. Default: 1
+
+    It has no effect with '--size-units bytes'.
+
+";
+pub const NUMBER_FORMAT_HELP  :  &str = 
+"--number-format
+    1 argument: comma, dot, space or none, case-insensitive. Default: comma
+
+    The thousands separator of the printed numbers (e.g. 1,234,567, 1.234.567, 1 234 567 or 1234567),
+    in the results, the overview and the summary. The decimals are always after a dot. The logs, the
+    report and the outputs that are meant for other programs (the json of '--serve' and prometheus)
+    never have separators.
+
+";
+pub const DIR_BREAKDOWN_HELP  :  &str = 
+"--dir-breakdown
+    0..1 arguments: a number of directory levels between 1 and 100. Default: none, or 1 if no argument
+
+    Also shows the stats per directory, as a tree below each of the provided directories that goes
+    this many levels deep. The stats of a directory include the ones of its subdirectories and the
+    biggest directories (in lines) are shown first, to find which component is the biggest.
+    With '--output json' the tree is also in the json as 'by_directory', each directory with its
+    'children', for treemaps. With '--top' the rest of the children of a directory are in 'others'.
+
+"; 
+pub const MAX_DEPTH_HELP  :  &str = 
+"--max-depth
+    1 argument: a number of directory levels between 0 and 10000. Default: none
+
+    Searches at most this many levels of subdirectories below the provided directories.
+    Providing 0 as argument will only search the files directly inside them.
+
+"; 
+pub const MIN_SIZE_HELP  :  &str = 
+"--min-size
+    1 argument: a size in bytes, optionally followed by a unit: KB, MB or GB. Default: none
+
+    Files smaller than this are skipped and counted as excluded.
+
+"; 
+pub const MAX_SIZE_HELP  :  &str = 
+"--max-size
+    1 argument: a size in bytes, optionally followed by a unit: KB, MB or GB. Default: none
+
+    Files bigger than this are skipped and counted as excluded, which is useful for ignoring huge
+    generated files.
+
+"; 
+pub const GIT_TRACKED_HELP  :  &str = 
+"--git-tracked
+    No arguments. Default: no
+
+    Only counts the files that are tracked by git, as listed in the index of the repository that
+    contains each provided directory, so build artifacts and untracked files never enter the stats.
+    The other filters (languages, excluded dirs etc) still apply. It needs 'git' to be installed.
+
+"; 
+pub const CHANGED_SINCE_HELP  :  &str = 
+"--changed-since
+    1 argument: a git revision, like a branch name or a commit. Default: none
+
+    Only counts the files that changed since the revision, including the uncommitted changes,
+    for example to see how big the changes of a branch are with '--changed-since main'.
+    The changes are taken from the common ancestor of the revision and the current commit,
+    and the deleted and untracked files are left out. The range is stated in the report.
+    It needs 'git' to be installed.
+
+"; 
+pub const ISOLATE_ROOTS_HELP  :  &str = 
+"--isolate-roots
+    No arguments. Default: no
+
+    When more than one directory is provided, analyzes each of them separately and concurrently,
+    printing a section with the results of each one, followed by a combined total. Useful when the
+    same extension means different things in different projects. The log, the report and the
+    notification get the combined results.
+
+"; 
+pub const INCLUDE_SUBMODULES_HELP  :  &str = 
+"--include-submodules
+    No arguments. Default: no
+
+    Searches the git submodules (as listed in the '.gitmodules' of the repository that contains each
+    provided directory, and in the ones of the submodules) as directories of their own, so that with
+    '--dir-breakdown' each one has its own section instead of being mixed with the repository.
+    It can't be combined with '--exclude-submodules'.
+
+"; 
+pub const EXCLUDE_SUBMODULES_HELP  :  &str = 
+"--exclude-submodules
+    No arguments. Default: no
+
+    Leaves the git submodules out of the search, so that only the files of the repository itself are
+    counted. Without it or '--include-submodules', the submodules are searched as any other directory.
+    It can't be combined with '--include-submodules'.
+
+"; 
+pub const DOCS_HELP  :  &str = 
+"--docs
+    No arguments. Default: no
+
+    Also counts the documentation files (Markdown, reStructuredText, AsciiDoc and plain text) as prose,
+    with their words, headings and code blocks, in a section of their own. They are not part of the
+    code stats, but a docs-to-code ratio is shown. A language file that claims one of their extensions
+    takes precedence.
+
 "; 
 pub const LANGUAGES_HELP  :  &str = 
 "--languages 
@@ -38,16 +303,51 @@ pub const LANGUAGES_HELP  :  &str =
 
     Only the languages specified here will be taken into account for the stats.
 
+"; 
+pub const EXT_HELP  :  &str = 
+"--ext
+    1..n extensions or groups of extensions separated by commas, case-insensitive. Default: all
+
+    Only the files with these extensions are taken into account for the stats. An extension or group
+    that starts with '-' is removed from the ones before it, or from all of them if it is the first,
+    e.g. '--ext all,-json,-md' or '--ext -json'. The groups are:
+      all        every extension of the supported languages
+      markup     the markup, data and documentation formats, like md, html, json and yaml
+      all-code   every extension apart from the markup ones
+
+"; 
+pub const MATCH_CASE_HELP  :  &str = 
+"--match-case
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    The extensions of the files only match the ones of the languages in the same case. Otherwise an
+    exact match is preferred, but the case is ignored when there is none, e.g. 'main.RS' is Rust.
+
+"; 
+pub const LANG_SCOPE_HELP  :  &str = 
+"--lang-scope
+    1..n arguments in the form '<language>: <dir1>, <dir2>', separated by ';'. 
+    If specified in a configuration file, use one language per line. Default: none
+
+    Restricts the files of the specified languages to the given directories, that are relative to
+    the searched directories. The rest of the files of those languages are counted as excluded.
+    The directories may use '*' to match any characters in a name, and '**' to match any number
+    of nested directories, for example: '--lang-scope rust: src, crates/*/src; java: app/**/main'.
+
 "; 
 pub const THREADS_HELP  :  &str = 
 "--threads
-    2 numbers: the first between 1 and 4 and the seconds between 1 and 12. 
+    2 numbers: the producers, between 1 and 4, and the consumers, between 1 and 12. Bigger numbers are
+    lowered to the maximums.
 
     This represents the number of the producers (threads that will traverse the given directories),
     and consumers (threads that will parse whatever files the producers found).
 
-    If this command is not provided, the numbers will be chosen based on the available threads
-    on your machine. Generally, a good ratio of producers-consumers is 1:3
+    If this command is not provided, the numbers will be chosen based on the available parallelism
+    of your machine (or the cpu limit of the container). Generally, a good ratio of producers-consumers is 1:3.
+    The consumers are started as the files are found, one for every 32 files waiting to be parsed,
+    so a few files are not parsed by a lot of threads.
     
 "; 
 pub const BRACES_AS_CODE_HELP  :  &str = 
@@ -61,6 +361,16 @@ pub const BRACES_AS_CODE_HELP  :  &str =
     and substance to be counted differently, according to the programer's code style.
     This helps to keep the stats clean when using code lines as a complexity and productivity metric.
 
+";
+pub const IGNORE_TRAILING_EMPTY_LINE_HELP  :  &str = 
+"--ignore-trailing-empty-line
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Leaves out the last line of a file if it is blank, e.g. the extra empty line that some editors add
+    at the end. The lines are counted the same with LF, CRLF and CR line endings, and the newline at
+    the end of the last line never adds a line of its own.
+
 "; 
 pub const SEARCH_IN_DOTTED_HELP  :  &str = 
 "--search-in-dotted
@@ -70,6 +380,25 @@ pub const SEARCH_IN_DOTTED_HELP  :  &str =
     Specifies whether the program should traverse directories that are prefixed with a dot,
     like .vscode or .git.
 
+"; 
+pub const HIDDEN_HELP  :  &str = 
+"--hidden
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no 
+
+    Includes the files and directories whose name starts with a dot, like .eslintrc.js or .github.
+    By default they are skipped, which also saves searching big directories like .git.
+
+"; 
+pub const FOLLOW_LINKS_HELP  :  &str = 
+"--follow-links
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no 
+
+    Follows the symbolic links to files and directories. By default they are skipped.
+    A directory that is reached more than once, e.g. through a link that points to one of its
+    parents, is only searched the first time.
+
 "; 
 pub const SHOW_FAULTY_FILES_HELP  :  &str = 
 "--show-faulty-files
@@ -83,6 +412,34 @@ pub const SHOW_FAULTY_FILES_HELP  :  &str =
     This flag specifies that their path, along with information about the exact error is displayed too.
     The most common reason for this error is if a file contains non UTF-8 characters. 
 
+"; 
+pub const SHOW_SKIPPED_HELP  :  &str = 
+"--show-skipped
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    The files that are left out for their size, with '--min-size' and '--max-size', are counted
+    after the found files. Specifies that their path and size are displayed too, with the limit
+    that they are outside of.
+
+";
+pub const SUMMARY_HELP  :  &str = 
+"--summary
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Prints only the total of the files, lines, code percentage and size, and the overview, without
+    the details of every language and the keywords, for a quick look at a project.
+
+"; 
+pub const STYLE_HELP  :  &str = 
+"--style
+    1 argument: ascii or unicode, case-insensitive. Default: ascii
+
+    The characters of the bars of the overview and the histogram, and of the line above the total.
+    Unicode draws them with block characters (e.g. █▓▒░), for a nicer look in the terminals and fonts
+    that have them, and ascii only uses the characters of ASCII, for the dumb terminals.
+
 "; 
 pub const NO_VISUAL_HELP  :  &str = 
 "--no-visual
@@ -93,6 +450,367 @@ pub const NO_VISUAL_HELP  :  &str =
     the vertical lines that reprisent the percentages.
 
 "; 
+pub const REGEX_KEYWORDS_HELP  :  &str = 
+"--regex-keywords
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no 
+
+    Specifies that the keyword aliases in the language files are regular expressions, instead of
+    plain words. For example '\\bclass\\b' only counts 'class' as a whole word, and 'unsafe\\s*\\{'
+    counts unsafe blocks. Supported: '.', '[a-z]', '[^a-z]', '\\s', '\\d', '\\w', '\\b', '(a|b)',
+    '^', '$', '*', '+', '?' and '{n,m}'. Since the aliases are separated by whitespace, use '\\s' for spaces.
+
+"; 
+pub const CO_OCCURRENCE_HELP  :  &str = 
+"--co-occurrence
+    0..n arguments: the names of the keywords (as they appear in the results), separated by commas.
+    If specified in a configuration file use 'true' or 'yes' for all the keywords, or their names.
+    Default: disabled
+
+    Shows a matrix for every language with how many files contain each pair of its keywords,
+    for example how many files use both 'unsafe' and 'transmute'. The diagonal is the number of
+    files that contain each keyword. If no keyword names are provided, all the keywords are shown.
+
+"; 
+pub const TODOS_HELP  :  &str = 
+"--todos
+    0..n arguments: the markers to look for, separated by commas. They are case sensitive.
+    If specified in a configuration file use 'true' or 'yes' for the default markers, or the markers.
+    Default: disabled
+
+    Scans the comments for the markers (TODO, FIXME and HACK if none are provided) and lists them
+    after the results, grouped by marker, with the file, the line and the text that follows them.
+
+"; 
+pub const TEST_PATTERN_HELP  :  &str = 
+"--test-pattern
+    0..n arguments: the patterns of the test files, separated by commas.
+    If specified in a configuration file use 'true' or 'yes' for the default patterns, or the patterns.
+    Default: disabled
+
+    Tells the test files apart from the production ones and compares them in a 'Tests' section after
+    the results, that is also added to the report. A pattern that ends with '/' matches a dir with that
+    name at any level (e.g. 'tests/' or 'src/test/'), the rest match the file names with '*' as a
+    wildcard (e.g. '*_test.go'). The default patterns are tests/, test/, __tests__/, *_test.*, test_*,
+    *.test.*, *.spec.*, *Test.* and *Tests.*
+
+"; 
+pub const LOGICAL_LINES_HELP  :  &str = 
+"--logical-lines
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Adds the logical lines to the Details, which are the statements of the code instead of its lines.
+    They are the occurrences of the 'Statement symbols' of a language file in the code, e.g. ';' and '{',
+    or the code lines for the languages without them, like Python.
+
+"; 
+pub const COMPLEXITY_HELP  :  &str = 
+"--complexity
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Adds the average complexity per file to the Details, which is the number of occurrences of the
+    'Branch keywords' of a language file in the code, e.g. 'if', 'for' and '&&', divided by the files.
+    It is a rough signal of how much branching the code has, not the cyclomatic complexity.
+    Without it the branch keywords are not counted, unless the complexity is a metric of '--fail-if',
+    '--metrics' or 'check'.
+
+"; 
+pub const HISTOGRAM_HELP  :  &str = 
+"--histogram
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Adds a line to the overview with the percentages of the files that have 0-50, 51-200, 201-1000
+    and more than 1000 lines, to spot the few huge files that hold most of the code.
+
+"; 
+pub const TIMINGS_HELP  :  &str = 
+"--timings
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Adds a section after the results with the time that was spent parsing the files of each extension
+    and the 10 slowest files, to find out what makes a run slow. The times of all the parser threads
+    are added up, so with more threads they are longer than the run itself.
+
+";
+pub const STREAM_HELP  :  &str = 
+"--stream
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Prints the files, lines and size of each language as soon as all of its files are parsed, while
+    the rest are still being parsed, and then the results as usual. Since more files of a language
+    could be found at any time, none is finished before the search of the dirs is over.
+    It has no effect when quiet, or with '--isolate-roots'.
+
+";
+pub const TOP_FILES_HELP  :  &str = 
+"--top-files
+    1 argument: a number from 1 to 1000. Default: disabled
+
+    Lists the files with the most lines and the biggest size after the results, for all the languages
+    together and for each of them, e.g. '--top-files 5'.
+
+"; 
+pub const INDENTATION_HELP  :  &str = 
+"--indentation
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Adds an 'Indentation' section with the percentages of the indented code lines of each extension
+    that start with tabs, with spaces or with both, and the most common width of the indentation
+    with spaces, e.g. '.rs -> 98.5% spaces (4 wide), 1.5% tabs, 0.0% mixed'.
+
+"; 
+pub const EMBEDDED_HELP  :  &str = 
+"--embedded
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Counts the regions of the files that are written in other languages as the lines of those
+    languages, e.g. the scripts and styles of HTML, Vue and Svelte files or the fenced code blocks
+    of markdown ('```rust'). The regions are set in the 'Embedded regions' of the language files,
+    and a 'lang' attribute, e.g. '<script lang=\"ts\">', picks the language of a region. Adds an
+    'Embedded code' section with the lines that each language got from the regions.
+
+";
+pub const SKIP_GENERATED_HELP  :  &str = 
+"--skip-generated
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Leaves out the files with a marker of generated code in their first lines, like '@generated',
+    'DO NOT EDIT' or the header of the protocol buffer compiler. Without it they are counted as usual,
+    and a 'Generated code' section shows how much of each language they are.
+
+"; 
+pub const SKIP_MINIFIED_HELP  :  &str = 
+"--skip-minified
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Leaves out the files with a line longer than 16 KiB, like the minified javascript and css files.
+    Without it they are counted as usual, and a 'Minified code' section shows how much of each language
+    they are. Either way such a line is read in chunks instead of whole, and counted as one line.
+
+";
+pub const AUTHORS_HELP  :  &str = 
+"--authors
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Adds an 'Authors' section with the authors that last changed the most lines, according to
+    'git blame', of all the files and of each extension. The files outside of git repositories and
+    the untracked ones are left out of it. It makes the run a lot slower, since git is run per file.
+
+"; 
+pub const ESTIMATE_HELP  :  &str = 
+"--estimate
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Adds an 'Estimate' section with the effort, the schedule, the developers and the cost that the
+    code lines would take to write, according to the basic COCOMO model (organic projects).
+    The cost is based on '--salary' and '--overhead'.
+
+"; 
+pub const SALARY_HELP  :  &str = 
+"--salary
+    1 argument: the yearly salary of a developer, in any currency, between 1 and 100000000. Default: 56286
+
+    Used for the cost of '--estimate'.
+
+"; 
+pub const OVERHEAD_HELP  :  &str = 
+"--overhead
+    1 argument: a percentage of the salary between 100 and 1000. Default: 240
+
+    What a developer costs in total, with the equipment, the offices etc, for the cost of '--estimate'.
+
+"; 
+pub const STRICT_HELP  :  &str = 
+"--strict
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Any faulty file fails the run with the exit code 3, before the results are printed, instead of
+    being left out of them.
+
+"; 
+pub const ZERO_KEYWORDS_HELP  :  &str = 
+"--zero-keywords
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Lists the keywords that don't occur in a language too, with 0. By default they are left out,
+    so that the small projects aren't cluttered with them.
+
+"; 
+pub const TOP_KEYWORDS_HELP  :  &str = 
+"--top-keywords
+    1 argument: a number from 1 to 1000. Default: disabled
+
+    Lists only the keywords that occur the most in each language and in total, the most first,
+    e.g. '--top-keywords 3'. By default all of them are listed, by name.
+
+"; 
+pub const KEYWORD_HOTSPOTS_HELP  :  &str = 
+"--keyword-hotspots
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Shows under the keywords of each language and of the total the file with the most occurrences
+    of each keyword, e.g. to find the file with the most 'unsafe' blocks or TODOs. Of the files with
+    as many occurrences, the first by path is shown.
+
+"; 
+pub const STATS_HELP  :  &str = 
+"--stats
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Prints how the run performed after the results: how long the search for the files took and how
+    much longer the parsing went on after it, the files, lines and bytes parsed per second, and how
+    busy each parser thread was while it was alive. Useful for tuning '--threads', e.g. on network
+    filesystems, where the search is usually the slow part.
+
+";
+pub const TUI_HELP  :  &str = 
+"--tui
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Draws the lines of the languages in the terminal while the files are parsed, in place of the
+    progress bar, and then the results can be browsed, in place of the printed ones. The languages
+    and the directories are listed in tables that can be scrolled with j/k or the arrows and sorted
+    by each column with 's' ('r' reverses the order). Tab switches between the languages and the
+    files, Enter opens a directory and Backspace goes back up, and 'q' quits. The results are
+    still saved. Works only in unix terminals.
+
+"; 
+pub const HYGIENE_HELP  :  &str = 
+"--hygiene
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Adds a 'Hygiene' section with the files that start with a byte order mark or a shebang ('#!'),
+    that end their lines with LF, CRLF or a mix of both, and that don't end with a newline.
+    The empty files are not counted as missing the final newline.
+
+"; 
+pub const LICENSES_HELP  :  &str = 
+"--licenses
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Looks for a license in the comments before the first code line of each file, either as an
+    'SPDX-License-Identifier' or as the usual header of Apache-2.0, MIT or GPL, and adds a 'Licenses'
+    section with the licensed and the unlicensed files of each extension.
+
+"; 
+pub const CLONES_HELP  :  &str = 
+"--clones
+    1 argument: a number from 3 to 1000. Default: disabled
+
+    Lists the blocks of code that are repeated in other files, with at least the given number of code
+    lines, e.g. '--clones 6'. The lines are compared without their comments and whitespace, and the
+    empty lines and the comment lines are skipped, so a block may be copied with different comments.
+    The 25 longest blocks are listed, followed by the total of all of them.
+
+"; 
+pub const DUPLICATES_HELP  :  &str = 
+"--duplicates
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Hashes the contents of the files while they are parsed, and lists the groups of identical files
+    after the results, with the lines and the size that the extra copies add to the stats. The groups
+    that waste the most bytes come first. Useful to find vendored copies of the same code.
+
+"; 
+pub const KEYWORD_DENSITY_HELP  :  &str = 
+"--keyword-density
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no 
+
+    Shows how many times each keyword occurs per 1000 lines of code (kloc) next to its count,
+    so that the keywords of codebases with very different sizes can be compared.
+
+"; 
+pub const KEYWORD_CATEGORIES_HELP  :  &str =
+"--keyword-categories
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    After the details, sums the occurrences of the keywords by the category that they have in the
+    language files (e.g. 'types' for the structs, classes, enums etc), across all the languages.
+    The keywords without a category are left out.
+
+";
+pub const KEYWORD_SCOPE_HELP  :  &str =
+"--keyword-scope
+    Where the occurrences of all the keywords are counted, overriding the 'SCOPE' of the keywords
+    in the language files. One of: code, comments, all. Default: the scope of each keyword
+
+    'code' leaves out the comments and the strings, 'comments' only counts the keywords written in
+    comments (e.g. FIXME or SAFETY:) and 'all' counts both.
+
+";
+pub const TYPICAL_MARKERS_HELP  :  &str = 
+"--typical-markers
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no 
+
+    Shows below every language how its percentage of extra lines and its lines per file compare to the
+    typical values of open source projects, marking them as 'typical', 'above typical' or 'below typical'.
+    The typical values are approximate and only meant to give some context.
+
+"; 
+pub const IGNORE_EMPTY_FILES_HELP  :  &str = 
+"--ignore-empty-files
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no 
+
+    Empty and whitespace-only files are always counted separately for every language and are displayed
+    next to its size. This flag specifies that they should also be left out when calculating the average size.
+
+"; 
+pub const DETECT_SHEBANGS_HELP  :  &str = 
+"--detect-shebangs
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no 
+
+    Files without an extension are normally skipped. This flag specifies that they should be detected
+    by their name if it is well-known (e.g. 'Makefile', 'Dockerfile', 'Rakefile'), or else by their
+    shebang line (e.g. '#!/usr/bin/env python3'). They are then counted under the language that supports
+    the corresponding extension, if any.
+    The shebang lines of files with an extension are checked too, and if they point to a different
+    language, the file is reported so that it can be renamed (see '--prefer-shebangs').
+
+"; 
+pub const PREFER_SHEBANGS_HELP  :  &str = 
+"--prefer-shebangs
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no 
+
+    Used along with '--detect-shebangs'. When the shebang line of a file points to a different language
+    than its extension, the file is counted under the language of the shebang instead, if it is supported.
+
+"; 
+pub const DISAMBIGUATE_HELP  :  &str = 
+"--disambiguate
+    1..n arguments: the extensions h, m and pl, separated by commas, case-insensitive
+
+    The files with these extensions are counted under the language that their first KB points to,
+    instead of the one of the extension: '.h' as Objective-C, C++ or else C, '.m' as Objective-C or
+    else Matlab, and '.pl' as Perl or Prolog, e.g. by '@interface', 'namespace' or 'use strict'.
+    Only the languages that are supported are chosen, and if none of them matches, the language of
+    the extension is kept.
+
+";
 pub const LOG_HELP  :  &str = 
 "--log 
     Can take 0..n words as arguments in the cmd.
@@ -114,6 +832,238 @@ pub const COMPRARE_LEVEL_HELP  :  &str =
 
     Providing 0 as argument will disable the progress report (comparison).
 
+"; 
+pub const LARGE_FILE_THRESHOLD_HELP  :  &str = 
+"--large-file-threshold
+    1 argument: a size in MBs between 0 and 100000. Default: 16
+
+    Files of at least this size are read in big blocks and their lines are scanned directly from them,
+    instead of being read line by line, which is faster for very large files.
+    Providing 0 as argument will read all the files line by line.
+
+"; 
+pub const SPLIT_THRESHOLD_HELP  :  &str = 
+"--split-threshold
+    1 argument: a size in MBs between 0 and 100000. Default: 0
+
+    Files of at least this size are cut at line boundaries into a segment for each parser thread,
+    which are parsed at the same time and then merged, so that a huge file like a database dump
+    doesn't keep a single thread busy for the whole run. A segment that starts inside a comment or
+    a string of the previous one is parsed again after it, so the stats are the same as without it.
+    Not used for the documentation files, with '--duplicates', '--max-memory' or plugins.
+    Providing 0 as argument parses every file with a single thread.
+
+"; 
+pub const MAX_LINE_LENGTH_HELP  :  &str = 
+"--max-line-length
+    1 argument: a number of characters between 1 and 100000. Default: 120
+
+    The lines that are longer are counted, and shown in the details next to the longest line of each
+    language, e.g. '(12 lines over 120, longest 240)'. Nothing is shown for the languages without any.
+
+"; 
+pub const MAX_OPEN_FILES_HELP  :  &str = 
+"--max-open-files
+    1 argument: the max number of files and directories that can be open at the same time, between 0 and 100000.
+    Default: 512, or 128 on macOS and 256 on Windows
+
+    Caps the file handles that the threads hold concurrently, so that the limits of restricted
+    environments like containers are not exceeded. Threads that would go over it wait for a handle to be released.
+    Providing 0 as argument sets no limit.
+
+"; 
+pub const MAX_MEMORY_HELP  :  &str = 
+"--max-memory
+    1 argument: an approximate memory budget in MBs, between 0 and 100000. Default: 0 
+
+    Keeps the memory usage of the analysis roughly inside the budget, by pausing the directory search
+    while too many files are waiting to be parsed or the files being parsed take up their half of it,
+    and by reading in small blocks the files that are too big for the budget. Useful in CI containers
+    with tight memory limits.
+    Providing 0 as argument sets no limit.
+
+"; 
+pub const PRESCAN_HELP  :  &str = 
+"--prescan
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Finds all the files and reads their sizes before the parsing starts, and prints how many bytes
+    are to be parsed. The biggest files are parsed first, so that they are spread among the threads
+    instead of one thread ending up with the big files found last, and the progress bar shows the
+    progress of the bytes. Ignores the queue limit of '--max-memory', since all the files are queued.
+
+"; 
+pub const TIMEOUT_HELP  :  &str = 
+"--timeout
+    1 argument: the max seconds of the search and the parsing, between 0 and 86400. Default: 0 
+
+    Stops the threads when the time is up and ends the run without results, so that a huge directory
+    can't hang a CI job. Providing 0 as argument sets no limit.
+
+"; 
+pub const FILE_TIMEOUT_HELP  :  &str = 
+"--file-timeout
+    1 argument: the max seconds of the parsing of a single file, between 0 and 86400. Default: 0 
+
+    A file that takes longer (e.g. a huge generated bundle) is abandoned and listed among the faulty files,
+    so it is left out of the results. Providing 0 as argument sets no limit.
+
+"; 
+pub const STRICT_ENCODING_HELP  :  &str = 
+"--strict-encoding
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no 
+
+    By default, files that start with a UTF-16 byte order mark are decoded as UTF-16 and invalid UTF-8
+    (e.g. Latin-1 files) is decoded lossily, so that legacy files are still counted.
+    With this command every file is read strictly as UTF-8 and the files that are not valid UTF-8
+    are reported as faulty instead.
+
+"; 
+pub const RESTRICT_TO_HELP  :  &str = 
+"--restrict-to
+    1 argument: the path of an existing directory. Default: none
+
+    Specifies a root directory that the search is not allowed to leave. Every provided directory
+    must be inside it, and any file or directory that resolves outside of it (for example through
+    a symlink or '..') is skipped and counted as excluded.
+
+"; 
+pub const LANGS_DIR_HELP  :  &str = 
+"--langs-dir
+    1 argument: the path of an existing directory. Default: none
+
+    Specifies a directory with extra language files, written in the same format as the ones in
+    'data/languages/' (not TOML, so that a language is written the same way wherever it is defined).
+    They are loaded on top of the supported languages: a file that claims the extensions of an
+    existing language takes them from it, and a file with the name of an existing language replaces
+    its definition, keeping the extensions of it that no file claims.
+
+"; 
+pub const REPORT_HELP  :  &str = 
+"--report
+    1 argument: the path of the file (it doesn't need to exist). Default: none
+
+    Saves the stats of the execution in a report file, followed by a SHA-256 checksum of its contents,
+    so that it can later be verified that the report has not been modified (see '--verify-report').
+
+"; 
+pub const OUT_HELP  :  &str = 
+"--out
+    1 argument: the path of the file (it doesn't need to exist). Default: none
+
+    Writes the results to the file instead of the terminal, in the format of '--output' and without
+    colors (unless '--color always'). The missing parent directories are created. An existing file
+    is not overwritten, unless '--force' is used as well.
+
+"; 
+pub const FORCE_HELP  :  &str = 
+"--force
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Allows '--out' to overwrite an existing file.
+
+"; 
+pub const EVENT_LOG_HELP  :  &str = 
+"--event-log
+    1 argument: the path of the file (it doesn't need to exist). Default: none
+
+    Writes a line of json for every file that is found, skipped (with the reason, e.g. excluded or
+    unsupported), parsed or faulty, and for every directory that is skipped, e.g.
+      {\"event\":\"skipped\",\"kind\":\"file\",\"path\":\"/p/a.min.js\",\"reason\":\"outside size range\"}
+    so that it can be seen why a file was or wasn't counted. An existing file is overwritten.
+
+"; 
+pub const SQLITE_HELP  :  &str = 
+"--sqlite
+    1 argument: the path of the database (it doesn't need to exist). Default: none
+
+    Appends the results of each run (the time, the dirs, the totals, and the stats and keywords of
+    every language) to the 'runs', 'languages' and 'keywords' tables of an SQLite database, so that
+    their history can be queried. The sqlite3 command line shell has to be installed.
+
+"; 
+pub const SIGN_KEY_HELP  :  &str = 
+"--sign-key
+    1 argument: the path of a file that contains the key. Default: none
+
+    Used along with '--report', to also sign the report with an HMAC-SHA256 of its contents using
+    the key of the file, or along with '--verify-report' to verify that signature.
+    The key is read from a file so that it doesn't show up in the process list or the shell history,
+    and it is never saved in configuration files.
+
+"; 
+pub const NOTIFY_HELP  :  &str = 
+"--notify
+    1 argument: 'slack:' or 'webhook:' followed by the url. Default: none
+
+    Posts a summary of the run (totals and the biggest changes since the previous log entry) when
+    it finishes, either as a Slack message through an incoming webhook, or as json to a generic webhook.
+    It needs 'curl' to be installed. A failed delivery is reported but doesn't fail the run.
+
+"; 
+pub const NOTIFY_TEMPLATE_HELP  :  &str = 
+"--notify-template
+    1 argument: the body to post to the webhook of '--notify'. Default: a json object of the stats
+
+    The placeholders {files}, {lines}, {code_lines}, {extra_lines}, {bytes} and {summary} are replaced
+    with the stats of the run. The summary is escaped, so it can be placed inside a json string.
+    Ignored for Slack targets.
+
+"; 
+pub const BADGE_HELP  :  &str = 
+"--badge
+    1..2 arguments separated by commas: the path of the svg file (it doesn't need to exist), and
+    optionally the metric: lines, code, extra or files, case-insensitive. The metric can be followed
+    by ':<language>' to count only the files of that language. Default: none, or lines as the metric
+
+    Writes a badge in the style of shields.io with the chosen metric, e.g. '--badge loc.svg, code:rust',
+    so that an up to date 'lines of code' badge can be generated in CI and embedded in READMEs.
+
+"; 
+pub const EXPORT_TREEMAP_HELP  :  &str = 
+"--export-treemap
+    1 argument: the path of the svg, dot or gv file (it doesn't need to exist). Default: none
+
+    Draws the directories by their lines, as an svg treemap or as a graphviz graph by the extension
+    of the path, e.g. '--export-treemap map.svg'. The directories of '--dir-breakdown' are drawn,
+    or 3 levels of them if it is not used, in which case they are not printed.
+
+"; 
+pub const PR_COMMENT_HELP  :  &str = 
+"--pr-comment
+    2 arguments separated by commas: the path of a report written with '--report' by a previous run,
+    and the path of the markdown file to write (it doesn't need to exist). Default: none
+
+    Writes a short markdown comment with the changes since the baseline report (files, lines, code
+    and size, in total and for the languages that changed), so that a CI bot can post it on the pull request.
+
+"; 
+pub const FAIL_IF_HELP  :  &str = 
+"--fail-if
+    1..n conditions separated by commas, each in the form '[<language>.]<metric> <operator> <number>'.
+    The metric is one of files, lines, code, extra, bytes, doc_lines, logical_lines, complexity,
+    blank_files, test_lines and test_code_lines (optionally prefixed with 'total_'), or else the name
+    of a keyword. The operators are >, >=, <, <=, == and !=. Default: none
+
+    The results are printed and saved as usual, but if any of the conditions holds the program
+    lists them and exits with the code 4, so that it can be used as a CI gate for size budgets,
+    e.g. '--fail-if total_lines > 100000, rust.unsafe > 20'. With '--pr-comment' they are also
+    listed in the comment.
+
+"; 
+pub const METRICS_HELP  :  &str = 
+"--metrics
+    1..n metrics separated by commas, each in the form '<name> = <expression>'. The expression
+    combines numbers and counters with +, -, *, / and parentheses. The counters are the metrics of
+    '--fail-if', optionally as '<language>.<counter>'. Default: none
+
+    Mostly meant for configuration files. The metrics are evaluated over the results and shown with
+    the total and in the json of '--output json', e.g. '--metrics extra_ratio = extra / lines,
+    test_density = test_lines / code'. A division by 0 has no value.
+
 "; 
 pub const SAVE_HELP  :  &str = 
 "--save
@@ -132,6 +1082,30 @@ pub const LOAD_HELP  :  &str =
 
     You can combine the '--load' and '--save' commands to modify a configuration file.
 
+"; 
+pub const PROFILE_HELP  :  &str = 
+"--profile
+    One argument as the name of a profile of the configuration file
+
+    Uses the options of a profile of the configuration of '--load', or else of the default one,
+    over the rest of its options. The options of the cmd are still used over the profile.
+    A profile starts with a '[profile.<name>]' line after the options of the file, and lasts
+    until the next profile, e.g. '--profile ci' with:
+        [profile.ci]
+        ===> threads
+        4 4
+    Saving a configuration with '--save' doesn't keep its profiles.
+
+"; 
+pub const DUMP_CONFIG_HELP  :  &str = 
+"--dump-config
+    No arguments.
+
+    Prints the options of the configuration that would be used, after the ones of the cmd, the
+    environment, the profile and the loaded or default configuration file are merged, in the
+    format of the configuration files, without analyzing anything. The output can be saved as
+    a starting configuration file, or used to see which option was taken from where.
+
 "; 
 pub const CHANGELOG_HELP  :  &str =
 "--changelog
@@ -149,6 +1123,108 @@ pub const SHOW_LANGUAGES_HELP  :  &str =
     all the supported languages that were detected in the persistent data path
     of the application, where you can add more. 
     
+";
+pub const VERIFY_REPORT_HELP  :  &str =
+"--verify-report
+    1 argument: the path of the report file
+
+    Verifies that the checksum of a report created with '--report' matches its contents.
+    If '--sign-key' is provided too, the signature of the report is also verified.
+    Exits with 4 if the report has been modified or is not signed, and with 5 if it can't be read.
+
+"; 
+pub const SERVE_HELP  :  &str =
+"serve --listen
+    1 argument: the address to listen on, e.g. 'serve --listen 127.0.0.1:8080'
+
+    Instead of a single run, keeps the configuration of the rest of the arguments and the languages
+    loaded and answers http GET requests until it is stopped:
+    '/analyze?path=<dir>' analyzes the dir (or else the dirs of the configuration) and returns the results as json,
+    '/metrics' analyzes the dirs of the configuration and returns them in the format of '--output prometheus'.
+    Nothing is logged, saved or sent for the requests. When the language files (also the ones of '--langs-dir')
+    change, the languages are loaded again before the next request, without restarting.
+
+";
+pub const SNAPSHOT_HELP  :  &str =
+"snapshot save, snapshot diff
+    'snapshot save <name> <dirs and options>': analyzes like without 'snapshot' and keeps the results
+    as a report named e.g. 'v1.0' in the '.mezura' dir of the current dir, replacing an older one of the same name.
+    'snapshot diff <older> <newer>': prints the changes between two saved snapshots, the total and the languages
+    that changed, e.g. 'snapshot diff v1.0 v2.0'.
+    The names can contain letters, digits, '.', '-' and '_'.
+
+";
+pub const BENCH_HELP  :  &str =
+"bench --iterations, --cold
+    'bench <dirs and options>': analyzes like without 'bench' as many times as '--iterations'
+    (a number from 1 to 1000, Default: 5), without printing the results, and prints the min, average,
+    p50, p90 and max times of the runs and the files and lines per second of the average one.
+    They are compared with the ones of the previous bench, that are kept in the '.mezura' dir of the
+    current dir. The runs after the first one read the files from the caches of the OS, unless '--cold'
+    is used, which drops them before every run and needs root on linux.
+
+";
+pub const CHECK_HELP  :  &str =
+"check --baseline, --max-growth, --metric, --update-baseline
+    'check <dirs and options> --baseline <file>': analyzes like without 'check', without printing the
+    results, and compares a metric of them with the one of the baseline, a json file of '--output json'.
+    If the metric grew more than '--max-growth' since the baseline, a percentage of it (e.g. '5%') or
+    a number (e.g. '2000'), the program exits with the code 4. Default: 0
+    The metric of '--metric' is one of files, lines, code, extra and bytes, or else the name of a keyword,
+    optionally as '<language>.<metric>' like for '--fail-if', e.g. 'rust.unsafe'. Default: code
+    '--update-baseline' saves the results as the baseline instead, e.g. after an accepted growth:
+    'check ./src --baseline baseline.json --max-growth 5%'.
+
+";
+pub const FILE_HELP  :  &str =
+"file
+    'file <path> <options>': parses just the file, with the language that a run would have found it as,
+    and prints its lines, code and extra lines, doc lines, complexity, size, longest line and keywords,
+    with the TODOs if '--todos' is used, e.g. 'file src/lib.rs --zero-keywords'.
+    The program exits with the code 2 if it is not a file of the supported languages, and 3 if it
+    couldn't be parsed.
+
+";
+pub const TREND_HELP  :  &str =
+"trend
+    1 optional argument: the metric, one of 'lines', 'code', 'extra' and 'files'. Default: lines
+
+    Prints the metric of the totals of every snapshot of 'snapshot save' in the '.mezura' dir of the current dir,
+    the oldest first, each with a bar in the style of the overview, e.g. 'trend code'.
+
+";
+pub const MERGE_HELP  :  &str =
+"merge
+    1..n arguments: the paths of json files of '--output json', separated by spaces
+
+    Prints the results of the files combined, in the same json format, e.g. of the shards of a monorepo
+    that were analyzed on different CI workers: 'merge a.json b.json c.json'. The counts of the same
+    language are summed, and its keywords are the ones of all the files, with their occurrences summed.
+
+";
+pub const LIST_ONLY_HELP  :  &str =
+"--list-only
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Only searches the dirs and prints the files that would be analyzed, with their language, extension
+    and size, without parsing them. Useful to check what the filters (excluded dirs, languages, scopes,
+    sizes etc) let through. With '--quiet' only the paths are printed.
+
+";
+pub const LINGUIST_COMPARE_HELP  :  &str =
+"--linguist-compare
+    No arguments in the cmd, but if specified in a configuration file use 'true' or 'yes' to enable,
+    or 'no' to disable. Default: no
+
+    Only searches the dirs, without parsing the files, and compares the share of every language in bytes
+    with the one of the language bar of GitHub. Like linguist, the vendored dirs (e.g. 'node_modules'),
+    the documentation (e.g. 'docs' and the READMEs) and the languages that are not programming or markup
+    ones are left out of the GitHub shares, and the '.gitattributes' at the root of the dirs applies its
+    'linguist-vendored', 'linguist-generated', 'linguist-documentation', 'linguist-detectable' and
+    'linguist-language' attributes. The files and bytes that were left out or changed language are listed
+    under the shares, to explain the differences.
+
 ";
 pub const SHOW_CONFIGS_HELP  :  &str =
 "--show-configs
@@ -161,25 +1237,42 @@ pub const SHOW_CONFIGS_HELP  :  &str =
 ";
 
 
+// The whole help message, in groups of related options. The commands, that don't analyze anything, are first
+const HELP_GROUPS : &[(&str, &[&str])] = &[
+    ("Commands", &[CHANGELOG_HELP, SHOW_LANGUAGES_HELP, SHOW_CONFIGS_HELP, VERIFY_REPORT_HELP, SERVE_HELP, SNAPSHOT_HELP,
+            TREND_HELP, MERGE_HELP, BENCH_HELP, CHECK_HELP,
+            FILE_HELP, LIST_ONLY_HELP, LINGUIST_COMPARE_HELP]),
+    ("Files", &[DIRS_HELP, REMOTE_HELP, FILES_FROM_HELP, EXCLUDE_HELP, SKIP_BUILD_OUTPUTS_HELP, MAX_DEPTH_HELP,
+            MIN_SIZE_HELP, MAX_SIZE_HELP, GIT_TRACKED_HELP, CHANGED_SINCE_HELP, ISOLATE_ROOTS_HELP, INCLUDE_SUBMODULES_HELP,
+            EXCLUDE_SUBMODULES_HELP, DOCS_HELP,
+            LANGUAGES_HELP, EXT_HELP, MATCH_CASE_HELP, LANG_SCOPE_HELP, SEARCH_IN_DOTTED_HELP, HIDDEN_HELP,
+            FOLLOW_LINKS_HELP, IGNORE_EMPTY_FILES_HELP, DETECT_SHEBANGS_HELP, PREFER_SHEBANGS_HELP, DISAMBIGUATE_HELP, RESTRICT_TO_HELP,
+            LANGS_DIR_HELP]),
+    ("Output", &[DIR_BREAKDOWN_HELP, PATH_STYLE_HELP, SIZE_UNITS_HELP, SIZE_PRECISION_HELP, NUMBER_FORMAT_HELP, OUTPUT_HELP,
+            COLOR_HELP, NO_COLOR_HELP, LANG_HELP, SORT_HELP, COLUMNS_HELP, OVERVIEW_HELP, TOP_HELP, QUIET_HELP, VERBOSE_HELP,
+            SHOW_FAULTY_FILES_HELP, SHOW_SKIPPED_HELP, SUMMARY_HELP, NO_VISUAL_HELP, STYLE_HELP, TIMINGS_HELP, STATS_HELP, STREAM_HELP, TUI_HELP]),
+    ("Analysis", &[BRACES_AS_CODE_HELP, IGNORE_TRAILING_EMPTY_LINE_HELP, TYPICAL_MARKERS_HELP, KEYWORD_DENSITY_HELP, KEYWORD_CATEGORIES_HELP,
+            KEYWORD_SCOPE_HELP, REGEX_KEYWORDS_HELP, CO_OCCURRENCE_HELP, ZERO_KEYWORDS_HELP, TOP_KEYWORDS_HELP, KEYWORD_HOTSPOTS_HELP, TODOS_HELP, TOP_FILES_HELP,
+            DUPLICATES_HELP, CLONES_HELP, LICENSES_HELP, HYGIENE_HELP, MAX_LINE_LENGTH_HELP, INDENTATION_HELP, EMBEDDED_HELP,
+            SKIP_GENERATED_HELP, SKIP_MINIFIED_HELP, AUTHORS_HELP, ESTIMATE_HELP, SALARY_HELP, OVERHEAD_HELP, LOGICAL_LINES_HELP,
+            COMPLEXITY_HELP, HISTOGRAM_HELP, TEST_PATTERN_HELP]),
+    ("Limits", &[THREADS_HELP, LARGE_FILE_THRESHOLD_HELP, SPLIT_THRESHOLD_HELP, MAX_OPEN_FILES_HELP, MAX_MEMORY_HELP, PRESCAN_HELP,
+            TIMEOUT_HELP, FILE_TIMEOUT_HELP, STRICT_ENCODING_HELP, STRICT_HELP]),
+    ("Results", &[LOG_HELP, COMPRARE_LEVEL_HELP, OUT_HELP, FORCE_HELP, REPORT_HELP, SIGN_KEY_HELP, SQLITE_HELP, EVENT_LOG_HELP,
+            NOTIFY_HELP, NOTIFY_TEMPLATE_HELP, BADGE_HELP, EXPORT_TREEMAP_HELP, PR_COMMENT_HELP, FAIL_IF_HELP, METRICS_HELP]),
+    ("Configurations", &[SAVE_HELP, LOAD_HELP, PROFILE_HELP, DUMP_CONFIG_HELP])
+];
+
 pub fn print_whole_help_message() {
     let mut msg = get_data_dir_str();
-    msg += "Format of arguments: <path_here> --optional_command1 --optional_commandN\n\nCOMMANDS:\n\n";
-
-    msg += CHANGELOG_HELP;
-    msg += SHOW_LANGUAGES_HELP;
-    msg += SHOW_CONFIGS_HELP;
-    msg += DIRS_HELP;
-    msg += EXCLUDE_HELP;
-    msg += LANGUAGES_HELP;
-    msg += THREADS_HELP;
-    msg += BRACES_AS_CODE_HELP;
-    msg += SEARCH_IN_DOTTED_HELP;
-    msg += SHOW_FAULTY_FILES_HELP;
-    msg += NO_VISUAL_HELP;
-    msg += LOG_HELP;
-    msg += COMPRARE_LEVEL_HELP;
-    msg += SAVE_HELP;
-    msg += LOAD_HELP;
+    msg += "Format of arguments: <path_here> --optional_command1 --optional_commandN\n\n";
+
+    for (name, helps) in HELP_GROUPS {
+        msg += &format!("{}:\n\n", name.to_uppercase());
+        for help in helps.iter() {
+            msg += help;
+        }
+    }
 
     println!("{}",msg);
 }
@@ -219,6 +1312,73 @@ pub fn print_help_message_for_command(arg: &str) {
     } 
 }
 
+// Used with the hidden '--completions <shell>', e.g. 'source <(mezura --completions bash)'
+pub fn print_completions(args_line: &str) {
+    let shell = args_line.split("--").find_map(|x| x.strip_prefix(COMPLETIONS)).map(str::trim).unwrap_or_default();
+    match get_completions_script(shell) {
+        Some(x) => print!("{}", x),
+        None => println!("\n{}\n", format!("'{}' is not a supported shell. Use bash, zsh or fish", shell).yellow())
+    }
+}
+
+// The options of the help groups as (name, takes arguments), from the first two lines of their help, e.g.
+// "--top-files\n    1 argument: ..." -> ("top-files", true). The subcommands, that don't start with '--', are left out.
+// Used for the completions and the environment variables.
+pub fn get_options() -> Vec<(&'static str, bool)> {
+    let mut options = vec![(HELP, false)];
+    for help in HELP_GROUPS.iter().flat_map(|(_, helps)| helps.iter()) {
+        let mut lines = help.lines();
+        if let Some(name) = lines.next().and_then(|x| x.trim().strip_prefix("--")) {
+            let takes_args = !lines.next().unwrap_or_default().trim_start().starts_with("No arguments");
+            options.push((name, takes_args));
+        }
+    }
+    options
+}
+
+// The arguments of the options are completed as paths, since most of them are, and the rest as directories
+fn get_completions_script(shell: &str) -> Option<String> {
+    let options = get_options();
+    match shell {
+        "bash" => {
+            let all_options = options.iter().map(|x| format!("--{}", x.0)).collect::<Vec<_>>().join(" ");
+            let arg_options = options.iter().filter(|x| x.1).map(|x| format!("--{}", x.0)).collect::<Vec<_>>().join("|");
+            Some(format!("_mezura() {{
+    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\"
+    local prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"
+    case \"$prev\" in
+        {})
+            COMPREPLY=($(compgen -f -- \"$cur\"))
+            return;;
+    esac
+    if [[ \"$cur\" == -* ]]; then
+        COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))
+    else
+        COMPREPLY=($(compgen -d -- \"$cur\"))
+    fi
+}}
+complete -o filenames -F _mezura mezura
+", arg_options, all_options))
+        },
+        "zsh" => {
+            let specs = options.iter().map(|(name, takes_args)| if *takes_args {format!("'--{}:value:_files'", name)} else {format!("'--{}'", name)})
+                    .collect::<Vec<_>>();
+            Some(format!("#compdef mezura
+
+_mezura() {{
+    _arguments \\
+        {} \\
+        '*:directory:_files -/'
+}}
+compdef _mezura mezura
+", specs.join(" \\\n        ")))
+        },
+        "fish" => Some(options.iter().map(|(name, takes_args)| format!("complete -c mezura -l {}{}\n", name, if *takes_args {" -r -F"} else {""}))
+                .collect()),
+        _ => None
+    }
+}
+
 pub fn print_changelog() {
     println!("\n{}\n", String::from_utf8_lossy(&CHANGELOG_BYTES));
 }
@@ -274,6 +1434,12 @@ fn get_help_msg_of_command(command: &str) -> Option<&str> {
         Some(EXCLUDE_HELP)
     } else if command == LANGUAGES {
         Some(LANGUAGES_HELP)
+    } else if command == EXT {
+        Some(EXT_HELP)
+    } else if command == MATCH_CASE {
+        Some(MATCH_CASE_HELP)
+    } else if command == LANG_SCOPE {
+        Some(LANG_SCOPE_HELP)
     } else if command == THREADS {
         Some(THREADS_HELP)
     } else if command == BRACES_AS_CODE {
@@ -284,14 +1450,224 @@ fn get_help_msg_of_command(command: &str) -> Option<&str> {
         Some(SHOW_FAULTY_FILES_HELP)
     } else if command == NO_VISUAL {
         Some(NO_VISUAL_HELP)
+    } else if command == REGEX_KEYWORDS {
+        Some(REGEX_KEYWORDS_HELP)
+    } else if command == PREFER_SHEBANGS {
+        Some(PREFER_SHEBANGS_HELP)
+    } else if command == DISAMBIGUATE {
+        Some(DISAMBIGUATE_HELP)
+    } else if command == SORT {
+        Some(SORT_HELP)
+    } else if command == COLUMNS {
+        Some(COLUMNS_HELP)
+    } else if command == TOP {
+        Some(TOP_HELP)
+    } else if command == QUIET {
+        Some(QUIET_HELP)
+    } else if command == VERBOSE {
+        Some(VERBOSE_HELP)
+    } else if command == LANG {
+        Some(LANG_HELP)
+    } else if command == OUTPUT {
+        Some(OUTPUT_HELP)
+    } else if command == COLOR {
+        Some(COLOR_HELP)
+    } else if command == NO_COLOR {
+        Some(NO_COLOR_HELP)
+    } else if command == PATH_STYLE {
+        Some(PATH_STYLE_HELP)
+    } else if command == SIZE_UNITS {
+        Some(SIZE_UNITS_HELP)
+    } else if command == SIZE_PRECISION {
+        Some(SIZE_PRECISION_HELP)
+    } else if command == NUMBER_FORMAT {
+        Some(NUMBER_FORMAT_HELP)
+    } else if command == DIR_BREAKDOWN {
+        Some(DIR_BREAKDOWN_HELP)
+    } else if command == MAX_DEPTH {
+        Some(MAX_DEPTH_HELP)
+    } else if command == MIN_SIZE {
+        Some(MIN_SIZE_HELP)
+    } else if command == MAX_SIZE {
+        Some(MAX_SIZE_HELP)
+    } else if command == DOCS {
+        Some(DOCS_HELP)
+    } else if command == ISOLATE_ROOTS {
+        Some(ISOLATE_ROOTS_HELP)
+    } else if command == INCLUDE_SUBMODULES {
+        Some(INCLUDE_SUBMODULES_HELP)
+    } else if command == EXCLUDE_SUBMODULES {
+        Some(EXCLUDE_SUBMODULES_HELP)
+    } else if command == GIT_TRACKED {
+        Some(GIT_TRACKED_HELP)
+    } else if command == CHANGED_SINCE {
+        Some(CHANGED_SINCE_HELP)
+    } else if command == HIDDEN {
+        Some(HIDDEN_HELP)
+    } else if command == FOLLOW_LINKS {
+        Some(FOLLOW_LINKS_HELP)
+    } else if command == MAX_MEMORY {
+        Some(MAX_MEMORY_HELP)
+    } else if command == PRESCAN {
+        Some(PRESCAN_HELP)
+    } else if command == TIMEOUT {
+        Some(TIMEOUT_HELP)
+    } else if command == FILE_TIMEOUT {
+        Some(FILE_TIMEOUT_HELP)
+    } else if command == MAX_OPEN_FILES {
+        Some(MAX_OPEN_FILES_HELP)
+    } else if command == STRICT_ENCODING {
+        Some(STRICT_ENCODING_HELP)
+    } else if command == TYPICAL_MARKERS {
+        Some(TYPICAL_MARKERS_HELP)
+    } else if command == KEYWORD_DENSITY {
+        Some(KEYWORD_DENSITY_HELP)
+    } else if command == KEYWORD_CATEGORIES {
+        Some(KEYWORD_CATEGORIES_HELP)
+    } else if command == KEYWORD_SCOPE {
+        Some(KEYWORD_SCOPE_HELP)
+    } else if command == SKIP_BUILD_OUTPUTS {
+        Some(SKIP_BUILD_OUTPUTS_HELP)
+    } else if command == LARGE_FILE_THRESHOLD {
+        Some(LARGE_FILE_THRESHOLD_HELP)
+    } else if command == SPLIT_THRESHOLD {
+        Some(SPLIT_THRESHOLD_HELP)
+    } else if command == MAX_LINE_LENGTH {
+        Some(MAX_LINE_LENGTH_HELP)
+    } else if command == DETECT_SHEBANGS {
+        Some(DETECT_SHEBANGS_HELP)
+    } else if command == CO_OCCURRENCE {
+        Some(CO_OCCURRENCE_HELP)
+    } else if command == TODOS {
+        Some(TODOS_HELP)
+    } else if command == TOP_FILES {
+        Some(TOP_FILES_HELP)
+    } else if command == DUPLICATES {
+        Some(DUPLICATES_HELP)
+    } else if command == CLONES {
+        Some(CLONES_HELP)
+    } else if command == LICENSES {
+        Some(LICENSES_HELP)
+    } else if command == HYGIENE {
+        Some(HYGIENE_HELP)
+    } else if command == INDENTATION {
+        Some(INDENTATION_HELP)
+    } else if command == SKIP_GENERATED {
+        Some(SKIP_GENERATED_HELP)
+    } else if command == SKIP_MINIFIED {
+        Some(SKIP_MINIFIED_HELP)
+    } else if command == KEYWORD_HOTSPOTS {
+        Some(KEYWORD_HOTSPOTS_HELP)
+    } else if command == AUTHORS {
+        Some(AUTHORS_HELP)
+    } else if command == ESTIMATE {
+        Some(ESTIMATE_HELP)
+    } else if command == SALARY {
+        Some(SALARY_HELP)
+    } else if command == OVERHEAD {
+        Some(OVERHEAD_HELP)
+    } else if command == STRICT {
+        Some(STRICT_HELP)
+    } else if command == ZERO_KEYWORDS {
+        Some(ZERO_KEYWORDS_HELP)
+    } else if command == TOP_KEYWORDS {
+        Some(TOP_KEYWORDS_HELP)
+    } else if command == STATS {
+        Some(STATS_HELP)
+    } else if command == EMBEDDED {
+        Some(EMBEDDED_HELP)
+    } else if command == TUI {
+        Some(TUI_HELP)
+    } else if command == LOGICAL_LINES {
+        Some(LOGICAL_LINES_HELP)
+    } else if command == COMPLEXITY {
+        Some(COMPLEXITY_HELP)
+    } else if command == HISTOGRAM {
+        Some(HISTOGRAM_HELP)
+    } else if command == LIST_ONLY {
+        Some(LIST_ONLY_HELP)
+    } else if command == LINGUIST_COMPARE {
+        Some(LINGUIST_COMPARE_HELP)
+    } else if command == TIMINGS {
+        Some(TIMINGS_HELP)
+    } else if command == STREAM {
+        Some(STREAM_HELP)
+    } else if command == TEST_PATTERN {
+        Some(TEST_PATTERN_HELP)
+    } else if command == IGNORE_TRAILING_EMPTY_LINE {
+        Some(IGNORE_TRAILING_EMPTY_LINE_HELP)
+    } else if command == SUMMARY {
+        Some(SUMMARY_HELP)
+    } else if command == STYLE {
+        Some(STYLE_HELP)
+    } else if command == OVERVIEW {
+        Some(OVERVIEW_HELP)
+    } else if command == SHOW_SKIPPED {
+        Some(SHOW_SKIPPED_HELP)
+    } else if command == IGNORE_EMPTY_FILES {
+        Some(IGNORE_EMPTY_FILES_HELP)
     } else if command == LOG {
         Some(LOG_HELP)
     } else if command == COMPRARE_LEVEL {
         Some(COMPRARE_LEVEL_HELP)
+    } else if command == RESTRICT_TO {
+        Some(RESTRICT_TO_HELP)
+    } else if command == REPORT {
+        Some(REPORT_HELP)
+    } else if command == OUT {
+        Some(OUT_HELP)
+    } else if command == FORCE {
+        Some(FORCE_HELP)
+    } else if command == SIGN_KEY {
+        Some(SIGN_KEY_HELP)
+    } else if command == SQLITE {
+        Some(SQLITE_HELP)
+    } else if command == EVENT_LOG {
+        Some(EVENT_LOG_HELP)
+    } else if command == REMOTE {
+        Some(REMOTE_HELP)
+    } else if command == FILES_FROM {
+        Some(FILES_FROM_HELP)
+    } else if command == FAIL_IF {
+        Some(FAIL_IF_HELP)
+    } else if command == METRICS {
+        Some(METRICS_HELP)
+    } else if command == PR_COMMENT {
+        Some(PR_COMMENT_HELP)
+    } else if command == BADGE {
+        Some(BADGE_HELP)
+    } else if command == EXPORT_TREEMAP {
+        Some(EXPORT_TREEMAP_HELP)
+    } else if command == NOTIFY {
+        Some(NOTIFY_HELP)
+    } else if command == NOTIFY_TEMPLATE {
+        Some(NOTIFY_TEMPLATE_HELP)
+    } else if command == VERIFY_REPORT {
+        Some(VERIFY_REPORT_HELP)
+    } else if command == BENCH || command == ITERATIONS || command == COLD {
+        Some(BENCH_HELP)
+    } else if command == CHECK || command == BASELINE || command == MAX_GROWTH || command == METRIC || command == UPDATE_BASELINE {
+        Some(CHECK_HELP)
+    } else if command == FILE {
+        Some(FILE_HELP)
+    } else if command == SERVE || command == LISTEN {
+        Some(SERVE_HELP)
+    } else if command == SNAPSHOT {
+        Some(SNAPSHOT_HELP)
+    } else if command == TREND {
+        Some(TREND_HELP)
+    } else if command == MERGE {
+        Some(MERGE_HELP)
+    } else if command == LANGS_DIR {
+        Some(LANGS_DIR_HELP)
     } else if command == SAVE {
         Some(SAVE_HELP)
     } else if command == LOAD {
         Some(LOAD_HELP)
+    } else if command == PROFILE {
+        Some(PROFILE_HELP)
+    } else if command == DUMP_CONFIG {
+        Some(DUMP_CONFIG_HELP)
     } else if command == CHANGELOG {
         Some(CHANGELOG_HELP)
     } else if command == SHOW_LANGUAGES {
@@ -301,4 +1677,31 @@ fn get_help_msg_of_command(command: &str) -> Option<&str> {
     } else {
         None
     }
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_options() {
+        let options = get_options();
+        assert!(options.contains(&(HELP, false)));
+        assert!(options.contains(&(TUI, false)));
+        assert!(options.contains(&(TOP_FILES, true)));
+        assert!(options.contains(&(DIRS, true)));
+        // The subcommands are not options
+        assert!(!options.iter().any(|x| x.0.starts_with(SERVE)));
+        assert_eq!(options.len(), HELP_GROUPS.iter().map(|x| x.1.len()).sum::<usize>() - 7 + 1);
+    }
+
+    #[test]
+    fn test_get_completions_script() {
+        let bash = get_completions_script("bash").unwrap();
+        assert!(bash.contains("--top-files|") && bash.contains(" --tui ") && !bash.contains("--tui|"));
+        assert!(get_completions_script("zsh").unwrap().contains("'--top-files:value:_files' \\\n        '--"));
+        assert!(get_completions_script("fish").unwrap().contains("complete -c mezura -l top-files -r -F\ncomplete"));
+        assert_eq!(get_completions_script("cmd"), None);
+    }
+}
@@ -0,0 +1,142 @@
+// Reads jupyter notebooks, so that their code cells are counted as the language of their kernel instead of as json.
+// Only what the notebooks of nbformat 4 use is supported: the cells with a 'source' that is a string or a list of
+// strings, and the language in 'metadata.language_info' or 'metadata.kernelspec'.
+
+use crate::{*, json::Json};
+
+pub const NOTEBOOK_EXTENSION : &str = "ipynb";
+
+
+#[derive(Debug,PartialEq)]
+pub struct Notebook {
+    // The name of the language, e.g. 'python'
+    pub kernel_language: Option<String>,
+    // The extension of the files of the language, without the dot, e.g. 'py'
+    pub kernel_extension: Option<String>,
+    pub code_cells: Vec<String>,
+    pub markdown_cells: Vec<String>
+}
+
+pub fn is_notebook(path: &Path) -> bool {
+    utils::get_file_extension(path).is_some_and(|x| x.eq_ignore_ascii_case(NOTEBOOK_EXTENSION))
+}
+
+// Unless a language claims the extension of the notebooks, in which case they are parsed like the rest of its files
+pub fn is_parsed_as_notebook(path: &Path, language: &Language) -> bool {
+    is_notebook(path) && !language.extensions.iter().any(|x| x.eq_ignore_ascii_case(NOTEBOOK_EXTENSION))
+}
+
+// The language of the kernel of the notebook, by the extension of its files or else by its name.
+// None if the notebook can't be read or its kernel has no language in the map.
+pub fn find_kernel_language(path: &Path, languages: &Arc<HashMap<String,Language>>, match_case: bool) -> Option<String> {
+    let notebook = parse_notebook(&fs::read_to_string(path).ok()?).ok()?;
+    let by_extension = notebook.kernel_extension.and_then(|x| find_lang_with_this_identifier(languages, &x, match_case));
+    let kernel_language = notebook.kernel_language;
+    by_extension.or_else(|| languages.keys().filter(|x| kernel_language.as_ref().is_some_and(|y| x.eq_ignore_ascii_case(y))).min().cloned())
+}
+
+// The code cells are parsed as a single file of the language, one after the other, and the markdown cells are only counted
+pub fn parse_notebook_file(path: &Path, lang_name: &str, buf: &mut String, language_map: Arc<HashMap<String,Language>>, config: &Configuration,
+        plugins: &[Box<dyn MetricPlugin>]) -> Result<FileStats,FaultyReason>
+{
+    let contents = fs::read(path)?;
+    let notebook = parse_notebook(&String::from_utf8_lossy(&contents)).map_err(FaultyReason::InvalidNotebook)?;
+    let code = notebook.code_cells.iter().map(|x| if x.ends_with('\n') {x.to_owned()} else {x.to_owned() + "\n"}).collect::<String>();
+    let mut file_stats = file_parser::parse_archive_entry(code.as_bytes(), path, lang_name, buf, language_map, config, plugins)?;
+    if config.duplicates {
+        file_stats.content_hash = Some(hashing::sha256(&contents));
+    }
+    file_stats.notebook = Some(NotebookStats {
+        notebooks: 1,
+        code_cells: notebook.code_cells.len(),
+        markdown_cells: notebook.markdown_cells.len(),
+        markdown_lines: notebook.markdown_cells.iter().map(|x| x.lines().count()).sum()
+    });
+    Ok(file_stats)
+}
+
+pub fn parse_notebook(contents: &str) -> Result<Notebook, String> {
+    let json = json::parse(contents)?;
+    let cells = match json.get("cells") {
+        Some(Json::Array(x)) => x,
+        _ => return Err("there are no cells".to_owned())
+    };
+
+    let mut notebook = Notebook {kernel_language: None, kernel_extension: None, code_cells: Vec::new(), markdown_cells: Vec::new()};
+    for cell in cells {
+        let source = match cell.get("source") {
+            Some(Json::String(x)) => x.to_owned(),
+            Some(Json::Array(x)) => x.iter().filter_map(Json::as_str).collect(),
+            _ => String::new()
+        };
+        match cell.get("cell_type").and_then(Json::as_str) {
+            Some("code") => notebook.code_cells.push(source),
+            Some("markdown") => notebook.markdown_cells.push(source),
+            // The raw cells are neither code nor prose
+            _ => ()
+        }
+    }
+
+    let metadata = json.get("metadata");
+    let language_info = metadata.and_then(|x| x.get("language_info"));
+    notebook.kernel_extension = language_info.and_then(|x| x.get("file_extension")).and_then(Json::as_str)
+            .map(|x| x.trim_start_matches('.').to_owned()).filter(|x| !x.is_empty());
+    notebook.kernel_language = metadata.and_then(|x| x.get("kernelspec")).and_then(|x| x.get("language"))
+            .or_else(|| language_info.and_then(|x| x.get("name"))).and_then(Json::as_str).map(|x| x.to_owned());
+    Ok(notebook)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTEBOOK : &str = r##"{
+ "cells": [
+  {"cell_type": "markdown", "metadata": {}, "source": ["# Analysis\n", "Loads the \"data\" é\n"]},
+  {"cell_type": "code", "execution_count": 1, "metadata": {}, "outputs": [], "source": ["import pandas as pd\n", "\n", "# The data\n", "df = pd.read_csv('a.csv')"]},
+  {"cell_type": "raw", "metadata": {}, "source": "raw text"},
+  {"cell_type": "code", "execution_count": null, "metadata": {}, "outputs": [], "source": "df.head()\n"}
+ ],
+ "metadata": {
+  "kernelspec": {"display_name": "Python 3", "language": "python", "name": "python3"},
+  "language_info": {"file_extension": ".py", "name": "python", "version": "3.11.4"}
+ },
+ "nbformat": 4,
+ "nbformat_minor": 5
+}"##;
+
+    #[test]
+    fn test_parse_notebook() {
+        assert_eq!(Ok(Notebook {kernel_language: Some("python".to_owned()), kernel_extension: Some("py".to_owned()),
+                code_cells: vec!["import pandas as pd\n\n# The data\ndf = pd.read_csv('a.csv')".to_owned(), "df.head()\n".to_owned()],
+                markdown_cells: vec!["# Analysis\nLoads the \"data\" é\n".to_owned()]}), parse_notebook(NOTEBOOK));
+
+        assert!(parse_notebook("{\"cells\": [}").is_err());
+        assert!(parse_notebook("{\"metadata\": {}}").is_err());
+        assert!(parse_notebook("[] []").is_err());
+    }
+
+    #[test]
+    fn test_parse_notebook_file() {
+        let dir = std::env::temp_dir().join("mezura-test-notebook");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("analysis.ipynb");
+        fs::write(&path, NOTEBOOK).unwrap();
+        let python = Language::new("Python".to_owned(), vec!["py".to_owned()], vec!["\"".to_owned()], vec!["#".to_owned()], vec![], vec![]);
+        let languages = Arc::new(hashmap!["Python".to_owned() => python.clone()]);
+
+        assert!(is_parsed_as_notebook(&path, &python));
+        assert_eq!(Some("Python".to_owned()), find_kernel_language(&path, &languages, true));
+        let file_stats = parse_notebook_file(&path, "Python", &mut String::new(), languages.clone(), &Configuration::new(vec![]), &[]).unwrap();
+        assert_eq!((5, 3), (file_stats.lines, file_stats.code_lines));
+        assert_eq!(Some(NotebookStats {notebooks: 1, code_cells: 2, markdown_cells: 1, markdown_lines: 2}), file_stats.notebook);
+
+        fs::write(&path, "{\"cells\": []").unwrap();
+        assert_eq!(None, find_kernel_language(&path, &languages, true));
+        assert!(matches!(parse_notebook_file(&path, "Python", &mut String::new(), languages, &Configuration::new(vec![]), &[]),
+                Err(FaultyReason::InvalidNotebook(_))));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
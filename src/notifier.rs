@@ -0,0 +1,161 @@
+use std::{io::Write, process::{Command, Stdio}};
+
+use crate::*;
+
+const SLACK_PREFIX   : &str = "slack:";
+const WEBHOOK_PREFIX : &str = "webhook:";
+const SLACK_TEMPLATE : &str = "{\"text\": \"{summary}\"}";
+pub const DEF_WEBHOOK_TEMPLATE : &str = "{\"files\": {files}, \"lines\": {lines}, \"code_lines\": {code_lines}, \"extra_lines\": {extra_lines}, \
+        \"bytes\": {bytes}, \"summary\": \"{summary}\"}";
+const SEND_TIMEOUT_SECS : &str = "30";
+
+
+#[derive(Debug,PartialEq)]
+pub enum NotifyTarget {
+    Slack(String),
+    Webhook(String)
+}
+
+// Accepts 'slack:<url>' or 'webhook:<url>', where the url has to be http(s)
+pub fn parse_target(s: &str) -> Option<NotifyTarget> {
+    let s = s.trim();
+    let target = if let Some(url) = s.strip_prefix(SLACK_PREFIX) {
+        NotifyTarget::Slack(url.trim().to_owned())
+    } else if let Some(url) = s.strip_prefix(WEBHOOK_PREFIX) {
+        NotifyTarget::Webhook(url.trim().to_owned())
+    } else {
+        return None;
+    };
+
+    match &target {
+        NotifyTarget::Slack(url) | NotifyTarget::Webhook(url) if url.starts_with("https://") || url.starts_with("http://") => Some(target),
+        _ => None
+    }
+}
+
+// Posts the summary of the run to the target of '--notify'. The previous entry of the log, if there is one,
+// is the baseline that the biggest changes are calculated against.
+pub fn send_notification(target: &NotifyTarget, final_stats: &FinalStats, existing_log_contents: &Option<String>, config: &Configuration)
+-> Result<(),String>
+{
+    let baseline = existing_log_contents.as_ref().and_then(|x| result_printer::parse_N_previous_entries(x, 1).into_iter().next()).map(|x| x.stats);
+    let summary = format_summary(final_stats, baseline.as_ref());
+
+    let (url, template) = match target {
+        NotifyTarget::Slack(url) => (url, SLACK_TEMPLATE),
+        NotifyTarget::Webhook(url) => (url, config.notify_template.as_deref().unwrap_or(DEF_WEBHOOK_TEMPLATE))
+    };
+    post_json(url, &render_template(template, final_stats, &summary))
+}
+
+// e.g. "mezura: 500 files, 208,267 lines (143,330 code, 64,937 extra), 7.0 MBs. Since the last run: code +2.5%, lines +1.8%"
+pub fn format_summary(final_stats: &FinalStats, baseline: Option<&FinalStats>) -> String {
+    let mut summary = format!("mezura: {} files, {} lines ({} code, {} extra), {} {}", with_seperators(final_stats.files),
+            with_seperators(final_stats.lines), with_seperators(final_stats.code_lines), with_seperators(final_stats.extra_lines),
+            final_stats.size, final_stats.size_measurement);
+
+    if let Some(baseline) = baseline {
+        let mut deltas = [("files", baseline.files, final_stats.files), ("lines", baseline.lines, final_stats.lines),
+                ("code", baseline.code_lines, final_stats.code_lines), ("extra", baseline.extra_lines, final_stats.extra_lines)]
+                .iter().map(|(name, older, newer)| (*name, get_change_percentage(*older, *newer)))
+                .filter(|(_, x)| *x != 0f64)
+                .collect::<Vec<_>>();
+        deltas.sort_by(|a, b| b.1.abs().partial_cmp(&a.1.abs()).unwrap());
+
+        if deltas.is_empty() {
+            summary += ". No changes since the last run";
+        } else {
+            summary += ". Since the last run: ";
+            summary += &deltas.iter().map(|(name, x)| format!("{} {:+.1}%", name, x)).collect::<Vec<_>>().join(", ");
+        }
+    }
+    summary
+}
+
+// The placeholders are {files}, {lines}, {code_lines}, {extra_lines}, {bytes} and {summary}.
+// The summary is escaped so that it can be placed inside a json string.
+pub fn render_template(template: &str, final_stats: &FinalStats, summary: &str) -> String {
+    template.replace("{files}", &final_stats.files.to_string())
+        .replace("{lines}", &final_stats.lines.to_string())
+        .replace("{code_lines}", &final_stats.code_lines.to_string())
+        .replace("{extra_lines}", &final_stats.extra_lines.to_string())
+        .replace("{bytes}", &final_stats.bytes_size.to_string())
+        .replace("{summary}", &escape_json_string(summary))
+}
+
+fn get_change_percentage(older: usize, newer: usize) -> f64 {
+    if older == 0 {
+        return if newer == 0 {0f64} else {100f64};
+    }
+    round_1((newer as f64 - older as f64) / older as f64 * 100f64)
+}
+
+pub fn escape_json_string(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            x if (x as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", x as u32)),
+            x => escaped.push(x)
+        }
+    }
+    escaped
+}
+
+// There is no http client among the dependencies, so curl does the sending
+fn post_json(url: &str, body: &str) -> Result<(),String> {
+    let mut child = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--max-time", SEND_TIMEOUT_SECS, "-X", "POST",
+                "-H", "Content-Type: application/json", "--data-binary", "@-", url])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|x| format!("could not run curl ({})", x))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(body.as_bytes()).map_err(|x| x.to_string())?;
+    }
+    let output = child.wait_with_output().map_err(|x| x.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_owned())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(Some(NotifyTarget::Slack("https://hooks.slack.com/services/a/b".to_owned())),
+                parse_target(" slack:https://hooks.slack.com/services/a/b "));
+        assert_eq!(Some(NotifyTarget::Webhook("http://localhost:8080/hook".to_owned())), parse_target("webhook: http://localhost:8080/hook"));
+        assert_eq!(None, parse_target("slack:hooks.slack.com"));
+        assert_eq!(None, parse_target("teams:https://a.b"));
+        assert_eq!(None, parse_target(""));
+    }
+
+    #[test]
+    fn test_format_summary_and_render_template() {
+        let final_stats = FinalStats::new_extended(110, 2000, 1500, 500, 3_500_000, 31_818);
+        assert_eq!("mezura: 110 files, 2,000 lines (1,500 code, 500 extra), 3.5 MBs", format_summary(&final_stats, None));
+
+        let baseline = FinalStats::new_extended(100, 2000, 1200, 800, 3_000_000, 30_000);
+        let summary = format_summary(&final_stats, Some(&baseline));
+        assert_eq!("mezura: 110 files, 2,000 lines (1,500 code, 500 extra), 3.5 MBs. Since the last run: extra -37.5%, code +25.0%, files +10.0%",
+                summary);
+        assert_eq!("mezura: 110 files, 2,000 lines (1,500 code, 500 extra), 3.5 MBs. No changes since the last run",
+                format_summary(&final_stats, Some(&final_stats)));
+
+        assert_eq!("{\"text\": \"a \\\"quoted\\\"\\nsummary\"}", render_template(SLACK_TEMPLATE, &final_stats, "a \"quoted\"\nsummary"));
+        assert_eq!("{\"files\": 110, \"lines\": 2000, \"code_lines\": 1500, \"extra_lines\": 500, \"bytes\": 3500000, \"summary\": \"s\"}",
+                render_template(DEF_WEBHOOK_TEMPLATE, &final_stats, "s"));
+    }
+}
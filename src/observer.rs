@@ -0,0 +1,95 @@
+use crate::*;
+
+
+// The phases of a run, in the order that they happen. With '--isolate-roots' the searching and the parsing
+// are reported for every root.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AnalysisPhase {
+    // The dirs are searched and the files that are found are parsed at the same time
+    Searching,
+    // The search is over and the remaining files are parsed
+    Parsing,
+    // The faulty files are removed and the totals are calculated
+    Summarizing,
+    // The results were printed and saved
+    Finished
+}
+
+// Why a file or a directory was left out by the search, or for a generated file after it was parsed
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SkipReason {
+    // A symbolic link, without '--follow-links'
+    Link,
+    BrokenLink,
+    // Starts with a '.', without '--hidden' (or '--search-in-dotted' for the directories)
+    Hidden,
+    // Not tracked by git, with '--git-tracked' or '--changed-since'
+    Untracked,
+    // Matched by the '.mezuraignore' of a provided directory
+    Ignored,
+    OutsideRestrictTo,
+    // None of the languages claims it
+    Unsupported,
+    OutsideLangScope,
+    Excluded,
+    OutsideSizeRange,
+    BeyondMaxDepth,
+    BuildOutput,
+    // With '--exclude-submodules', or with '--include-submodules' where it is searched as a directory of its own
+    Submodule,
+    // With '--skip-generated'
+    Generated,
+    // With '--skip-minified'
+    Minified
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AnalysisEvent<'a> {
+    PhaseChanged(AnalysisPhase),
+    // A file of interest that will be parsed
+    FileDiscovered {path: &'a Path, language: &'a str},
+    FileSkipped {path: &'a Path, reason: SkipReason},
+    // The files inside it are not searched at all
+    DirSkipped {path: &'a Path, reason: SkipReason},
+    FileParsed {path: &'a Path, language: &'a str, stats: &'a FileStats, bytes: u64},
+    FileFaulty(&'a FaultyFile)
+}
+
+// Implemented by the users of the library that need to follow a run as it happens (e.g. to show progress in a GUI).
+// Registered through 'run_with_observer'. The files are reported by the search and the parser threads,
+// so the events of different files can arrive in any order, and the observer should return quickly.
+pub trait AnalysisObserver: Send + Sync {
+    fn on_event(&self, event: &AnalysisEvent);
+}
+
+pub type ObserverRef = Arc<dyn AnalysisObserver>;
+
+// The observer of the runs that nobody follows
+#[derive(Debug)]
+pub struct NoObserver;
+
+impl AnalysisObserver for NoObserver {
+    fn on_event(&self, _event: &AnalysisEvent) {}
+}
+
+impl SkipReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Link => "link",
+            Self::BrokenLink => "broken link",
+            Self::Hidden => "hidden",
+            Self::Untracked => "untracked",
+            Self::Ignored => "ignored",
+            Self::OutsideRestrictTo => "outside restrict-to",
+            Self::Unsupported => "unsupported",
+            Self::OutsideLangScope => "outside lang-scope",
+            Self::Excluded => "excluded",
+            Self::OutsideSizeRange => "outside size range",
+            Self::BeyondMaxDepth => "beyond max-depth",
+            Self::BuildOutput => "build output",
+            Self::Submodule => "submodule",
+            Self::Generated => "generated",
+            Self::Minified => "minified"
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use std::mem;
+
+use crate::*;
+
+
+// What a plugin is told about a line, after it was parsed
+#[derive(Debug)]
+pub struct LineContext<'a> {
+    // Trimmed, with its comments and strings still in it
+    pub line: &'a str,
+    pub language: &'a Language,
+    pub is_code: bool,
+    pub is_doc: bool
+}
+
+// What a plugin is told about a file, after all of its lines were parsed
+#[derive(Debug)]
+pub struct FileContext<'a> {
+    pub path: &'a Path,
+    pub language: &'a Language,
+    pub stats: &'a FileStats
+}
+
+// Implemented by the users of the library to count their own metrics while the files are parsed, e.g. the SQL queries
+// or the lines that match a pattern of their company. The metrics are added to 'FileStats::plugin_metrics' by name,
+// summed per language and printed in the 'Plugins' section. Registered through 'run_with_plugins'.
+pub trait MetricPlugin: Send + Sync {
+    // Called for every line of the code files, but not the documentation ones
+    fn on_line(&self, _context: &LineContext, _metrics: &mut PluginMetrics) {}
+
+    fn on_file(&self, _context: &FileContext, _metrics: &mut PluginMetrics) {}
+}
+
+pub type PluginsRef = Arc<Vec<Box<dyn MetricPlugin>>>;
+
+// A plugin out of a closure, that counts the lines that it returns true for
+pub struct LineMetric<F> {
+    name: String,
+    is_match: F
+}
+
+// A plugin out of a closure, that adds what it returns for every file
+pub struct FileMetric<F> {
+    name: String,
+    count: F
+}
+
+impl<F: Fn(&LineContext) -> bool + Send + Sync> LineMetric<F> {
+    pub fn new(name: &str, is_match: F) -> Self {
+        LineMetric {name: name.to_owned(), is_match}
+    }
+}
+
+impl<F: Fn(&LineContext) -> bool + Send + Sync> MetricPlugin for LineMetric<F> {
+    fn on_line(&self, context: &LineContext, metrics: &mut PluginMetrics) {
+        if (self.is_match)(context) {
+            *metrics.entry(self.name.clone()).or_insert(0) += 1;
+        }
+    }
+}
+
+impl<F: Fn(&FileContext) -> usize + Send + Sync> FileMetric<F> {
+    pub fn new(name: &str, count: F) -> Self {
+        FileMetric {name: name.to_owned(), count}
+    }
+}
+
+impl<F: Fn(&FileContext) -> usize + Send + Sync> MetricPlugin for FileMetric<F> {
+    fn on_file(&self, context: &FileContext, metrics: &mut PluginMetrics) {
+        *metrics.entry(self.name.clone()).or_insert(0) += (self.count)(context);
+    }
+}
+
+pub fn run_file_plugins(plugins: &[Box<dyn MetricPlugin>], path: &Path, language: &Language, file_stats: &mut FileStats) {
+    if plugins.is_empty() {
+        return;
+    }
+    let mut metrics = mem::take(&mut file_stats.plugin_metrics);
+    let context = FileContext {path, language, stats: file_stats};
+    for plugin in plugins {
+        plugin.on_file(&context, &mut metrics);
+    }
+    file_stats.plugin_metrics = metrics;
+}
+
+// e.g.
+//
+// Plugins.
+//
+// SQL queries   ->  42 (Rust 40, Python 2)
+//
+// The languages of a metric are ordered by their count, the biggest first
+#[derive(Debug)]
+pub struct PluginSummary;
+
+impl ReportAnalyzer for PluginSummary {
+    fn analyze(&self, aggregate: &Aggregate) -> Option<ReportSection> {
+        let mut metrics = BTreeMap::<&str,Vec<(&str,usize)>>::new();
+        for (language, content_info) in aggregate.content_info_map.iter() {
+            for (name, count) in content_info.plugin_metrics.iter() {
+                metrics.entry(name).or_default().push((language, *count));
+            }
+        }
+        if metrics.is_empty() {
+            return None;
+        }
+
+        let mut section = ReportSection::new("Plugins");
+        for (name, mut counts) in metrics {
+            counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+            let total = counts.iter().map(|x| x.1).sum::<usize>();
+            let languages = counts.iter().map(|(language, count)| format!("{} {}", language, with_seperators(*count))).collect::<Vec<_>>();
+            section = section.with_entry(name, format!("{} ({})", with_seperators(total), languages.join(", ")));
+        }
+        Some(section)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plugins() {
+        let language = Language::new("Rust".to_owned(), vec!["rs".to_owned()], vec!["\"".to_owned()], vec!["//".to_owned()], vec![], vec![]);
+        let plugins : Vec<Box<dyn MetricPlugin>> = vec![
+            Box::new(LineMetric::new("SQL queries", |x: &LineContext| x.is_code && x.line.contains("SELECT "))),
+            Box::new(FileMetric::new("Long files", |x: &FileContext| usize::from(x.stats.lines > 2)))
+        ];
+        let mut file_stats = FileStats::default();
+        for (line, is_code) in [("query(\"SELECT * FROM a\");", true), ("// SELECT * FROM b", false), ("run();", true)] {
+            let context = LineContext {line, language: &language, is_code, is_doc: false};
+            plugins.iter().for_each(|x| x.on_line(&context, &mut file_stats.plugin_metrics));
+            file_stats.incr_lines();
+        }
+        run_file_plugins(&plugins, Path::new("a.rs"), &language, &mut file_stats);
+        assert_eq!(BTreeMap::from([("SQL queries".to_owned(), 1), ("Long files".to_owned(), 1)]), file_stats.plugin_metrics);
+    }
+
+    #[test]
+    fn test_plugin_summary() {
+        let mut rust = LanguageContentInfo::new(200, 150, HashMap::new());
+        rust.plugin_metrics = BTreeMap::from([("SQL queries".to_owned(), 1200), ("Unsafe blocks".to_owned(), 3)]);
+        let mut python = LanguageContentInfo::new(100, 50, HashMap::new());
+        python.plugin_metrics = BTreeMap::from([("SQL queries".to_owned(), 2)]);
+        let content_info_map = hashmap!["Rust".to_owned() => rust, "Python".to_owned() => python];
+        let languages_metadata_map = hashmap!["Rust".to_owned() => LanguageMetadata::new(4, 8000), "Python".to_owned() => LanguageMetadata::new(1, 900)];
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+
+        assert_eq!(Some(ReportSection::new("Plugins").with_entry("SQL queries", "1,202 (Rust 1,200, Python 2)").with_entry("Unsafe blocks", "3 (Rust 3)")),
+                PluginSummary.analyze(&Aggregate::new(&content_info_map, &languages_metadata_map, &[], &final_stats)));
+
+        let content_info_map = hashmap!["Rust".to_owned() => LanguageContentInfo::new(200, 150, HashMap::new())];
+        assert_eq!(None, PluginSummary.analyze(&Aggregate::new(&content_info_map, &languages_metadata_map, &[], &final_stats)));
+    }
+}
@@ -0,0 +1,140 @@
+use crate::{*, io_handler::ReportStats};
+
+const TITLE : &str = "### mezura";
+
+
+// Renders the changes since the baseline report as a markdown comment, that a CI bot can post on a pull request, e.g.
+//
+// ### mezura
+//
+// | Language | Files | Lines | Code | Size |
+// | :-- | --: | --: | --: | --: |
+// | **Total** | 120 (+3) | 15,000 (+250) | 10,000 (+200) | 1.2 MBs (+2.5%) |
+// | Rust | 100 (+3) | 12,500 (+250) | 8,800 (+200) | 1.0 MBs (+3.1%) |
+// | Python (removed) | 0 (-2) | 0 (-40) | 0 (-30) | 0.0 Bytes (-100.0%) |
+//
+// **Budgets exceeded**
+//
+// - `total_lines > 15000` (actual: 15,000)
+//
+// Only the languages that changed are listed, the ones with the biggest change in code lines first.
+pub fn render_pr_comment(baseline: &ReportStats, violations: &[BudgetViolation], content_info_map: &HashMap<String,LanguageContentInfo>,
+        languages_metadata_map: &HashMap<String,LanguageMetadata>, final_stats: &FinalStats) -> String
+{
+    let current = languages_metadata_map.iter().map(|(name, metadata)| {
+        let content_info = &content_info_map[name];
+        let stats = FinalStats::new_extended(metadata.files, content_info.lines, content_info.code_lines,
+                content_info.lines - content_info.code_lines, metadata.bytes, metadata.bytes / metadata.files.max(1));
+        (name.to_owned(), stats)
+    }).collect::<HashMap<_,_>>();
+    let empty_stats = FinalStats::new_extended(0, 0, 0, 0, 0, 0);
+    let changed = get_changed_languages(&baseline.languages, &current, &empty_stats);
+
+    let mut comment = format!("{}\n\n", TITLE);
+    if changed.is_empty() && has_same_stats(&baseline.total, final_stats) {
+        comment += "No changes since the baseline.\n";
+        return comment + &format_violations(violations);
+    }
+
+    comment += "| Language | Files | Lines | Code | Size |\n| :-- | --: | --: | --: | --: |\n";
+    comment += &format_row("**Total**", &baseline.total, final_stats);
+    for (name, older, newer) in changed {
+        let title = if !baseline.languages.contains_key(name) {
+            format!("{} (new)", name)
+        } else if !current.contains_key(name) {
+            format!("{} (removed)", name)
+        } else {
+            name.to_owned()
+        };
+        comment += &format_row(&title.replace('|', "\\|"), older, newer);
+    }
+    comment + &format_violations(violations)
+}
+
+// The languages whose stats differ, the ones with the biggest change in code lines first. A language that is missing
+// from one side gets the empty stats there.
+pub(crate) fn get_changed_languages<'a>(older: &'a HashMap<String,FinalStats>, newer: &'a HashMap<String,FinalStats>,
+        empty_stats: &'a FinalStats) -> Vec<(&'a String, &'a FinalStats, &'a FinalStats)>
+{
+    let mut names = newer.keys().chain(older.keys()).collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    let mut changed = names.into_iter().map(|name| {
+        (name, older.get(name).unwrap_or(empty_stats), newer.get(name).unwrap_or(empty_stats))
+    }).filter(|(_, older, newer)| !has_same_stats(older, newer)).collect::<Vec<_>>();
+    changed.sort_by_key(|(_, older, newer)| std::cmp::Reverse((newer.code_lines as i64 - older.code_lines as i64).abs()));
+    changed
+}
+
+fn format_violations(violations: &[BudgetViolation]) -> String {
+    if violations.is_empty() {
+        return String::new();
+    }
+
+    let mut text = String::from("\n**Budgets exceeded**\n\n");
+    for violation in violations {
+        text += &format!("- `{}` (actual: {})\n", violation.expression, with_seperators(violation.value));
+    }
+    text
+}
+
+pub(crate) fn has_same_stats(older: &FinalStats, newer: &FinalStats) -> bool {
+    older.files == newer.files && older.lines == newer.lines && older.code_lines == newer.code_lines && older.bytes_size == newer.bytes_size
+}
+
+fn format_row(title: &str, older: &FinalStats, newer: &FinalStats) -> String {
+    format!("| {} | {} | {} | {} | {} |\n", title, format_with_change(older.files, newer.files),
+            format_with_change(older.lines, newer.lines), format_with_change(older.code_lines, newer.code_lines),
+            format_size_with_change(older.bytes_size, newer.bytes_size))
+}
+
+// e.g. "1.0 MBs (+3.1%)", without the percentage if there was no size before
+pub(crate) fn format_size_with_change(older: usize, newer: usize) -> String {
+    if older > 0 {
+        format!("{} ({:+.1}%)", format_size(newer), (newer as f64 - older as f64) / older as f64 * 100f64)
+    } else {
+        format_size(newer)
+    }
+}
+
+// e.g. "12,500 (+250)", or only the value if it didn't change
+pub(crate) fn format_with_change(older: usize, newer: usize) -> String {
+    if newer >= older {
+        if newer == older {with_seperators(newer)} else {format!("{} (+{})", with_seperators(newer), with_seperators(newer - older))}
+    } else {
+        format!("{} (-{})", with_seperators(newer), with_seperators(older - newer))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_pr_comment() {
+        let baseline = ReportStats {
+            total: FinalStats::new_extended(6, 1200, 900, 300, 50_000, 8_333),
+            languages: hashmap!["Rust".to_owned() => FinalStats::new_extended(4, 1000, 800, 200, 40_000, 10_000),
+                    "Python".to_owned() => FinalStats::new_extended(2, 200, 100, 100, 10_000, 5_000)]
+        };
+        let content_info_map = hashmap!["Rust".to_owned() => LanguageContentInfo::new(1250, 1000, HashMap::new()),
+                "C".to_owned() => LanguageContentInfo::new(100, 80, HashMap::new())];
+        let languages_metadata_map = hashmap!["Rust".to_owned() => LanguageMetadata::new(5, 50_000),
+                "C".to_owned() => LanguageMetadata::new(1, 4_000)];
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+
+        assert_eq!("### mezura\n\n| Language | Files | Lines | Code | Size |\n| :-- | --: | --: | --: | --: |\n\
+                | **Total** | 6 | 1,350 (+150) | 1,080 (+180) | 54.0 KBs (+8.0%) |\n\
+                | Rust | 5 (+1) | 1,250 (+250) | 1,000 (+200) | 50.0 KBs (+25.0%) |\n\
+                | Python (removed) | 0 (-2) | 0 (-200) | 0 (-100) | 0.0 Bytes (-100.0%) |\n\
+                | C (new) | 1 (+1) | 100 (+100) | 80 (+80) | 4.0 KBs |\n",
+                render_pr_comment(&baseline, &[], &content_info_map, &languages_metadata_map, &final_stats));
+
+        let unchanged = ReportStats {total: FinalStats::calculate(&content_info_map, &languages_metadata_map, false), languages: HashMap::new()};
+        let no_languages = HashMap::new();
+        let violations = [BudgetViolation {expression: "total_lines > 1000".to_owned(), value: 1350}];
+        assert_eq!("### mezura\n\nNo changes since the baseline.\n\n**Budgets exceeded**\n\n- `total_lines > 1000` (actual: 1,350)\n",
+                render_pr_comment(&unchanged, &violations, &HashMap::new(), &no_languages, &final_stats));
+    }
+}
@@ -1,4 +1,4 @@
-use std::{fs::ReadDir, thread};
+use std::{fs::DirEntry, thread};
 
 use crossbeam_deque::Steal;
 
@@ -7,31 +7,49 @@ use crate::*;
 
 pub fn start_producer_thread(id: usize, files_injector: Arc<Injector<ParsableFile>>, dirs_injector: Arc<Injector<PathBuf>>, worker: Worker<PathBuf>,
         languages_metadata_map: MetadataMapMut, termination_states: Arc<Mutex<Vec<bool>>>, languages: Arc<HashMap<String,Language>>, config: Arc<Configuration>,
-        files_stats: Arc<Mutex<FilesPresent>>)
+        files_stats: Arc<Mutex<FilesPresent>>, files_signal: Arc<FilesSignal>, resource_limits: Arc<ResourceLimits>, visited_dirs: VisitedDirsMut,
+        tracked_files: Arc<Option<git::TrackedFiles>>, ignore_rules: Arc<ignore::IgnoreRules>, submodule_dirs: Arc<Vec<PathBuf>>, observer: ObserverRef)
 -> JoinHandle<()>
 {
     thread::Builder::new().name(id.to_string()).spawn(move || {
-        let (total_files, relevant_files, excluded_files) = 
-                search_for_files(id, files_injector, dirs_injector, worker, termination_states, languages, languages_metadata_map, config);
+        let started_instant = Instant::now();
+        let (total_files, relevant_files, excluded_files, detection_conflicts, skipped_files, unreadable_dirs) = 
+                search_for_files(id, files_injector, dirs_injector, worker, termination_states, languages, languages_metadata_map, config.clone(), &files_signal,
+                        &resource_limits, &visited_dirs, &tracked_files, &ignore_rules, &submodule_dirs, &*observer);
+        print_thread_colored_msg(&config, id, format!("Search thread {} finished in {} ms, having found {} files of interest.", id,
+                with_seperators(started_instant.elapsed().as_millis() as usize), with_seperators(relevant_files)));
         let mut file_stats_guard = files_stats.lock().unwrap(); 
         file_stats_guard.total_files += total_files;
         file_stats_guard.relevant_files += relevant_files;
         file_stats_guard.excluded_files += excluded_files;
+        file_stats_guard.detection_conflicts.extend(detection_conflicts);
+        file_stats_guard.skipped_files.extend(skipped_files);
+        file_stats_guard.unreadable_dirs.extend(unreadable_dirs);
 
     }).unwrap()
 }
 
 pub fn search_for_files(id: usize, files_injector: Arc<Injector<ParsableFile>>, dirs_injector: Arc<Injector<PathBuf>>, worker: Worker<PathBuf>, termination_states: Arc<Mutex<Vec<bool>>>,
-        languages: Arc<HashMap<String,Language>>, languages_metadata_map: MetadataMapMut, config: Arc<Configuration>) 
--> (usize,usize,usize) 
+        languages: Arc<HashMap<String,Language>>, languages_metadata_map: MetadataMapMut, config: Arc<Configuration>, files_signal: &FilesSignal,
+        resource_limits: &ResourceLimits, visited_dirs: &VisitedDirsMut, tracked_files: &Option<git::TrackedFiles>, ignore_rules: &ignore::IgnoreRules,
+        submodule_dirs: &[PathBuf], observer: &dyn AnalysisObserver) 
+-> (usize,usize,usize,Vec<DetectionConflict>,Vec<SkippedFile>,Vec<UnreadableDir>) 
 {
     let mut total_files = 0;
     let mut relevant_files = 0;
     let mut excluded_files = 0;
+    let mut detection_conflicts = Vec::new();
+    let mut skipped_files = Vec::new();
+    let mut unreadable_dirs = Vec::new();
+    // The sizes of the files are added up by each thread on its own, and to the shared map once the search is over
+    let mut local_metadata_map : HashMap<String,LanguageMetadata> = HashMap::new();
     let mut should_terminate = false;
     // let mut times_slept = 0;
 
     loop {
+        if files_signal.should_stop() {
+            break;
+        }
         let next_dir  = {
             if worker.is_empty() {
                 match dirs_injector.steal_batch_and_pop(&worker) {
@@ -49,9 +67,26 @@ pub fn search_for_files(id: usize, files_injector: Arc<Injector<ParsableFile>>,
                 termination_states.lock().unwrap()[id] = false;
             }
 
-            if let Ok(entries) = fs::read_dir(&dir) {
-                traverse_dir(&files_injector, entries, &dirs_injector, &languages, &config, &languages_metadata_map,
-                        &mut total_files, &mut relevant_files, &mut excluded_files)
+            if config.follow_links && !is_first_visit(dir, visited_dirs) {
+                continue;
+            }
+
+            // The entries are collected so that the directory is closed before its files are checked
+            let entries = {
+                let _open_file_slot = resource_limits.acquire_open_file();
+                fs::read_dir(&dir).map(|x| x.filter_map(|entry| entry.map_err(|x| warnings::add_io_error(dir, &x)).ok()).collect::<Vec<_>>())
+            };
+            match entries {
+                Err(x) => unreadable_dirs.push(UnreadableDir::new(dir, &x)),
+                Ok(entries) => {
+                    let previous_relevant_files = relevant_files;
+                    let build_output_dirs = if config.skip_build_outputs {build_outputs::find_build_output_dirs(dir)} else {Vec::new()};
+                    traverse_dir(&files_injector, entries, &dirs_injector, &languages, &config, &mut local_metadata_map, &build_output_dirs, submodule_dirs,
+                            files_signal, resource_limits, tracked_files, ignore_rules, observer, &mut total_files, &mut relevant_files, &mut excluded_files, &mut detection_conflicts, &mut skipped_files);
+                    if relevant_files != previous_relevant_files {
+                        files_signal.notify_new_files();
+                    }
+                }
             }
         } else {
             should_terminate = true;
@@ -67,57 +102,127 @@ pub fn search_for_files(id: usize, files_injector: Arc<Injector<ParsableFile>>,
         }
     }
 
+    let mut languages_metadata_guard = languages_metadata_map.lock().unwrap();
+    for (name, metadata) in local_metadata_map {
+        languages_metadata_guard.get_mut(&name).unwrap().add_metadata(&metadata);
+    }
+    drop(languages_metadata_guard);
+
     // print_thread_colored_msg(id, format!("Thread {} |  Exits with findings: {:?}",id,(total_files,relevant_files)));
     // print_thread_colored_msg(id, format!("Thread {} |  Slept {} times. ",id,times_slept));
 
-    (total_files,relevant_files,excluded_files)
+    (total_files,relevant_files,excluded_files,detection_conflicts,skipped_files,unreadable_dirs)
 }
 
-fn traverse_dir(files_injector: &Arc<Injector<ParsableFile>>, entries: ReadDir, dirs_injector: &Arc<Injector<PathBuf>>,
-        languages: &Arc<HashMap<String,Language>>, config: &Configuration, languages_metadata_map: &MetadataMapMut,
-        total_files: &mut usize, relevant_files: &mut usize, excluded_files: &mut usize)  
+fn traverse_dir(files_injector: &Arc<Injector<ParsableFile>>, entries: Vec<DirEntry>, dirs_injector: &Arc<Injector<PathBuf>>,
+        languages: &Arc<HashMap<String,Language>>, config: &Configuration, languages_metadata_map: &mut HashMap<String,LanguageMetadata>, build_output_dirs: &[PathBuf],
+        submodule_dirs: &[PathBuf], files_signal: &FilesSignal, resource_limits: &ResourceLimits, tracked_files: &Option<git::TrackedFiles>, ignore_rules: &ignore::IgnoreRules,
+        observer: &dyn AnalysisObserver, total_files: &mut usize, relevant_files: &mut usize, excluded_files: &mut usize, detection_conflicts: &mut Vec<DetectionConflict>,
+        skipped_files: &mut Vec<SkippedFile>)  
 {
     let mut local_total_files = 0;
     let mut local_relevant_files = 0;
     let mut local_excluded_files = 0;
-    for e in entries.flatten(){
-        if let Ok(ft) = e.file_type() {
+    for e in entries {
+        if let Ok(mut ft) = e.file_type() {
+            if ft.is_symlink() {
+                if !config.follow_links {
+                    observer.on_event(&AnalysisEvent::FileSkipped {path: &e.path(), reason: SkipReason::Link});
+                    continue;
+                }
+                ft = match fs::metadata(e.path()) {
+                    Ok(x) => x.file_type(),
+                    Err(_) => {
+                        observer.on_event(&AnalysisEvent::FileSkipped {path: &e.path(), reason: SkipReason::BrokenLink});
+                        continue
+                    }
+                };
+            }
+
+            // Like the dotted directories, the hidden files are not counted at all
+            if ft.is_file() && !config.hidden && e.file_name().to_str().is_some_and(|x| x.starts_with('.')) {
+                observer.on_event(&AnalysisEvent::FileSkipped {path: &e.path(), reason: SkipReason::Hidden});
+                continue;
+            }
+
+            // With '--git-tracked' the untracked files are not counted at all, as if they didn't exist
+            if let Some(tracked_files) = tracked_files {
+                let is_tracked = if ft.is_file() {tracked_files.contains_file(&e.path())} else {tracked_files.contains_dir(&e.path())};
+                if !is_tracked {
+                    skip(observer, &e.path(), !ft.is_file(), SkipReason::Untracked);
+                    continue;
+                }
+            }
+
+            if ignore_rules.is_ignored(&e.path(), !ft.is_file()) {
+                if ft.is_file() {
+                    local_total_files += 1;
+                    local_excluded_files += 1;
+                }
+                skip(observer, &e.path(), !ft.is_file(), SkipReason::Ignored);
+                continue;
+            }
+
+            if let Some(root) = &config.restrict_to {
+                if !utils::is_path_inside_root(&e.path(), root) {
+                    if ft.is_file() {
+                        local_total_files += 1;
+                        local_excluded_files += 1;
+                    }
+                    skip(observer, &e.path(), !ft.is_file(), SkipReason::OutsideRestrictTo);
+                    continue;
+                }
+            }
+
             if ft.is_file() { 
                 local_total_files += 1;
                 let path_buf = e.path();
-                let extension_name = match path_buf.extension() {
-                    Some(x) => {
-                        match x.to_str() {
-                                Some(x) => x.to_owned(),
-                                None => continue
-                            }
-                        },
-                        None => continue
-                };
-                if let Some(lang_name) = find_lang_with_this_identifier(languages, &extension_name) {
+                let (lang_name, conflict) = find_lang_of_file(&path_buf, languages, config);
+                detection_conflicts.extend(conflict);
+                if let Some(lang_name) = lang_name {
+                    if !is_inside_lang_scope(&path_buf, &lang_name, config) {
+                        local_excluded_files += 1;
+                        observer.on_event(&AnalysisEvent::FileSkipped {path: &path_buf, reason: SkipReason::OutsideLangScope});
+                        continue;
+                    }
                     if !config.exclude_dirs.is_empty() {
                         let full_path = &path_buf.to_str().unwrap_or("").replace('\\', "/");
                         if config.exclude_dirs.iter().any(|x| full_path.ends_with(x) || x == full_path) {
                             local_excluded_files += 1;
+                            observer.on_event(&AnalysisEvent::FileSkipped {path: &path_buf, reason: SkipReason::Excluded});
                             continue;
                         }
                     }
 
-                    local_relevant_files += 1;
                     let bytes = match path_buf.metadata() {
                         Ok(x) => x.len() as usize,
                         Err(_) => 0
                     };
+                    if let Some(reason) = get_size_skip_reason(bytes as u64, config) {
+                        local_excluded_files += 1;
+                        observer.on_event(&AnalysisEvent::FileSkipped {path: &path_buf, reason: SkipReason::OutsideSizeRange});
+                        skipped_files.push(SkippedFile {path: path_buf, language_name: lang_name, reason, bytes: bytes as u64});
+                        continue;
+                    }
 
-                    languages_metadata_map.lock().unwrap().get_mut(&lang_name).unwrap().add_file_meta(bytes);
+                    local_relevant_files += 1;
+
+                    languages_metadata_map.entry(lang_name.clone()).or_default().add_file_meta(bytes);
                     
+                    observer.on_event(&AnalysisEvent::FileDiscovered {path: &path_buf, language: &lang_name});
+                    wait_for_queue_space(files_injector, files_signal, resource_limits);
                     files_injector.push(ParsableFile::new(path_buf, lang_name));
+                } else {
+                    observer.on_event(&AnalysisEvent::FileSkipped {path: &path_buf, reason: SkipReason::Unsupported});
                 }
             } else { //is directory
                 let file_name = e.file_name();
                 let dir_name = match file_name.to_str() {
                     Some(x) => {
-                        if !config.should_search_in_dotted && x.starts_with('.') {continue;}
+                        if !config.should_search_in_dotted && !config.hidden && x.starts_with('.') {
+                            observer.on_event(&AnalysisEvent::DirSkipped {path: &e.path(), reason: SkipReason::Hidden});
+                            continue;
+                        }
                         else {x}
                     },
                     None => continue
@@ -126,8 +231,20 @@ fn traverse_dir(files_injector: &Arc<Injector<ParsableFile>>, entries: ReadDir,
                 let pathbuf = e.path();
                 let full_path = &pathbuf.to_str().unwrap_or("").replace('\\', "/");
         
-                if !config.exclude_dirs.iter().any(|x| x == dir_name || x == full_path) {
-                    dirs_injector.push(pathbuf);
+                let skip_reason = if config.exclude_dirs.iter().any(|x| x == dir_name || x == full_path) {
+                    Some(SkipReason::Excluded)
+                } else if !is_within_max_depth(&pathbuf, config) {
+                    Some(SkipReason::BeyondMaxDepth)
+                } else if build_outputs::is_build_output_dir(&pathbuf, build_output_dirs) {
+                    Some(SkipReason::BuildOutput)
+                } else if submodule_dirs.contains(&pathbuf) {
+                    Some(SkipReason::Submodule)
+                } else {
+                    None
+                };
+                match skip_reason {
+                    Some(reason) => observer.on_event(&AnalysisEvent::DirSkipped {path: &pathbuf, reason}),
+                    None => dirs_injector.push(pathbuf)
                 }
             }
         }
@@ -138,13 +255,160 @@ fn traverse_dir(files_injector: &Arc<Injector<ParsableFile>>, entries: ReadDir,
     *excluded_files += local_excluded_files;
 }
 
-#[cfg(debug_assertions)]
-fn print_thread_colored_msg(id: usize, msg: String) {
-    if id == 0 {
-        println!("{}",msg.truecolor(51, 167, 255));
+fn skip(observer: &dyn AnalysisObserver, path: &Path, is_dir: bool, reason: SkipReason) {
+    if is_dir {
+        observer.on_event(&AnalysisEvent::DirSkipped {path, reason});
+    } else {
+        observer.on_event(&AnalysisEvent::FileSkipped {path, reason});
+    }
+}
+
+// The depth of a directory is the number of levels below the provided directory that it is in
+fn is_within_max_depth(dir: &Path, config: &Configuration) -> bool {
+    let max_depth = match config.max_depth {
+        Some(x) => x,
+        None => return true
+    };
+    config.dirs.iter().filter_map(|root| dir.strip_prefix(root).ok()).any(|x| x.components().count() <= max_depth)
+}
+
+// None if the file is inside the size range of '--min-size' and '--max-size'
+fn get_size_skip_reason(bytes: u64, config: &Configuration) -> Option<SkippedReason> {
+    match (config.min_size, config.max_size) {
+        (Some(min), _) if bytes < min => Some(SkippedReason::BelowMinSize(min)),
+        (_, Some(max)) if bytes > max => Some(SkippedReason::AboveMaxSize(max)),
+        _ => None
+    }
+}
+
+// When following links the same directory can be reached through different paths, or even through a cycle.
+// The canonical paths of the visited directories are kept, so that each is searched only once.
+fn is_first_visit(dir: &Path, visited_dirs: &VisitedDirsMut) -> bool {
+    match fs::canonicalize(dir) {
+        Ok(x) => visited_dirs.lock().unwrap().insert(x),
+        Err(_) => false
+    }
+}
+
+// Pauses the search while the queued files, or the files that are being parsed, are at their limit of '--max-memory'
+fn wait_for_queue_space(files_injector: &Injector<ParsableFile>, files_signal: &FilesSignal, resource_limits: &ResourceLimits) {
+    if resource_limits.max_queued_files == 0 {
+        return;
+    }
+    // The consumers stop taking files when the run is cancelled or timed out
+    while (files_injector.len() >= resource_limits.max_queued_files || resource_limits.is_over_buffer_budget()) && !files_signal.should_stop() {
+        // The consumers may be waiting for the files that were pushed so far
+        files_signal.notify_new_files();
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+fn is_inside_lang_scope(file_path: &Path, lang_name: &str, config: &Configuration) -> bool {
+    let lang_name = lang_name.to_lowercase();
+    let scope = match config.lang_scopes.iter().find(|x| x.language == lang_name) {
+        Some(x) => x,
+        None => return true
+    };
+
+    let parent_dir = match file_path.parent() {
+        Some(x) => x,
+        None => return false
+    };
+    config.dirs.iter().any(|dir| {
+        match parent_dir.strip_prefix(dir) {
+            Ok(relative_dir) => scope.allows(&relative_dir.to_string_lossy()),
+            Err(_) => false
+        }
+    })
+}
+
+// A pattern that ends with '/' matches a dir with that name at any level below the provided dirs, e.g. 'tests/' or 'src/test/'.
+// The rest of the patterns match the name of the file, e.g. '*_test.go'.
+pub fn is_test_file(file_path: &Path, patterns: &[String], dirs: &[String]) -> bool {
+    let file_name = file_path.file_name().map(|x| x.to_string_lossy()).unwrap_or_default();
+    let parent_dir = file_path.parent().unwrap_or_else(|| Path::new(""));
+    let relative_dir = dirs.iter().find_map(|dir| parent_dir.strip_prefix(dir).ok()).unwrap_or(parent_dir).to_string_lossy();
+
+    patterns.iter().any(|pattern| match pattern.strip_suffix('/') {
+        Some(dir_pattern) => utils::is_dir_matching_pattern(&relative_dir, &format!("**/{}", dir_pattern)),
+        None => utils::is_name_matching_pattern(&file_name, pattern)
+    })
+}
+
+// Only printed with '--verbose'
+pub fn print_thread_colored_msg(config: &Configuration, id: usize, msg: String) {
+    let msg = if id == 0 {
+        msg.truecolor(51, 167, 255)
     } else if id == 1 {
-        println!("{}",msg.truecolor(255, 179, 71));
+        msg.truecolor(255, 179, 71)
     } else {
-        println!("{}",msg.truecolor(47, 171, 44));
+        msg.truecolor(47, 171, 44)
+    };
+    reporter::verbose(config, &msg.to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_test_file() {
+        let patterns = ["tests/".to_owned(), "src/test/".to_owned(), "*_test.go".to_owned()];
+        let dirs = ["/home/test/project".to_owned()];
+        let is_test = |path: &str| is_test_file(Path::new(path), &patterns, &dirs);
+
+        assert!(is_test("/home/test/project/tests/parse.rs"));
+        assert!(is_test("/home/test/project/crates/a/tests/common/mod.rs"));
+        assert!(is_test("/home/test/project/app/src/test/java/A.java"));
+        assert!(is_test("/home/test/project/cmd/main_test.go"));
+        assert!(!is_test("/home/test/project/src/lib.rs"));
+        assert!(!is_test("/home/test/project/app/test/a.rs"));
+        assert!(!is_test("/home/test/project/testsuite/a.rs"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_search_with_several_producers() {
+        let dir = std::env::temp_dir().join("mezura-test-several-producers");
+        let _ = fs::remove_dir_all(&dir);
+        for i in 0..20 {
+            let sub_dir = dir.join(format!("d{}/e{}", i, i % 3));
+            fs::create_dir_all(&sub_dir).unwrap();
+            fs::write(sub_dir.join("a.rs"), "fn a() {}\n").unwrap();
+            fs::write(sub_dir.join("b.rs"), "fn b() {}\n").unwrap();
+            fs::write(dir.join(format!("d{}/c.txt", i)), "c\n").unwrap();
+        }
+
+        let languages = Arc::new(hashmap!["Rust".to_owned() => Language::new("Rust".to_owned(), vec!["rs".to_owned()], vec!["\"".to_owned()],
+                vec!["//".to_owned()], vec![], vec![])]);
+        let config = Arc::new(Configuration::new(vec![dir.to_str().unwrap().to_owned()]));
+        let (files_injector, dirs_injector) = (Arc::new(Injector::new()), Arc::new(Injector::new()));
+        dirs_injector.push(dir.clone());
+        let languages_metadata_map = Arc::new(Mutex::new(make_language_metadata(&languages)));
+        let files_stats = Arc::new(Mutex::new(FilesPresent::default()));
+        let termination_states = Arc::new(Mutex::new(vec![false; 3]));
+
+        let handles = (0..3).map(|i| start_producer_thread(i, files_injector.clone(), dirs_injector.clone(), Worker::new_fifo(),
+                languages_metadata_map.clone(), termination_states.clone(), languages.clone(), config.clone(), files_stats.clone(),
+                Arc::new(FilesSignal::new()), Arc::new(ResourceLimits::new(0, 0)), Arc::new(Mutex::new(HashSet::new())), Arc::new(None),
+                Arc::new(ignore::IgnoreRules::default()), Arc::new(Vec::new()), Arc::new(NoObserver))).collect::<Vec<_>>();
+        handles.into_iter().for_each(|x| {x.join().unwrap();});
+
+        // Every file is found once, whichever thread found it
+        assert_eq!(40, files_injector.len());
+        assert_eq!((60, 40), {let x = files_stats.lock().unwrap(); (x.total_files, x.relevant_files)});
+        assert_eq!(LanguageMetadata::new(40, 400), languages_metadata_map.lock().unwrap()["Rust"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_get_size_skip_reason() {
+        let mut config = Configuration::new(vec![]);
+        assert_eq!(None, get_size_skip_reason(0, &config));
+        config.set_min_size(Some(2)).set_max_size(Some(1024));
+        assert_eq!(Some(SkippedReason::BelowMinSize(2)), get_size_skip_reason(1, &config));
+        assert_eq!(None, get_size_skip_reason(2, &config));
+        assert_eq!(None, get_size_skip_reason(1024, &config));
+        assert_eq!(Some(SkippedReason::AboveMaxSize(1024)), get_size_skip_reason(1025, &config));
+    }
+}
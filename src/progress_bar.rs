@@ -0,0 +1,90 @@
+use std::{io::{self, Write}, sync::mpsc::{Receiver, RecvTimeoutError}, thread};
+
+use crate::*;
+
+const BAR_WIDTH : usize = 30;
+const REDRAW_INTERVAL : Duration = Duration::from_millis(100);
+// Runs that finish faster than this never show the bar, so that it doesn't just flicker
+const INITIAL_DELAY : Duration = Duration::from_millis(300);
+
+
+// Every message on the channel is a file that a consumer finished with, with its bytes. The thread exits when all the
+// senders are dropped, meaning that all the consumers have finished. With the total bytes of '--prescan', which are
+// known before the parsing starts, the progress is the one of the bytes instead of the one of the files.
+pub fn start_progress_thread(receiver: Receiver<usize>, files_injector: Arc<Injector<ParsableFile>>, total_bytes: Option<usize>) -> JoinHandle<()> {
+    thread::Builder::new().name("progress".to_owned()).spawn(move || {
+        let started_instant = Instant::now();
+        let mut last_draw_instant : Option<Instant> = None;
+        let (mut files_done, mut bytes_done) = (0, 0);
+        loop {
+            match receiver.recv_timeout(REDRAW_INTERVAL) {
+                Ok(bytes) => {
+                    files_done += 1;
+                    bytes_done += bytes;
+                },
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => break
+            }
+
+            if started_instant.elapsed() >= INITIAL_DELAY && last_draw_instant.is_none_or(|x| x.elapsed() >= REDRAW_INTERVAL) {
+                // The queued files are the ones parsed plus the ones waiting, while the producers may still be adding more
+                let line = format_progress_line(files_done, files_done + files_injector.len(), total_bytes.map(|x| (bytes_done, x)),
+                        started_instant.elapsed());
+                reporter::hold(|| {
+                    print!("\r{}", line);
+                    io::stdout().flush();
+                });
+                last_draw_instant = Some(Instant::now());
+            }
+        }
+
+        if last_draw_instant.is_some() {
+            reporter::hold(|| {
+                print!("\r{}\r", " ".repeat(BAR_WIDTH + 50));
+                io::stdout().flush();
+            });
+        }
+    }).unwrap()
+}
+
+// [##########--------------------]  33%  1,000/3,000 files  ETA 12s
+fn format_progress_line(files_done: usize, files_queued: usize, bytes: Option<(usize, usize)>, elapsed: Duration) -> String {
+    let (done, total) = bytes.unwrap_or((files_done, files_queued));
+    let ratio = if total == 0 {0f64} else {(done as f64 / total as f64).min(1f64)};
+    let filled = ((ratio * BAR_WIDTH as f64) as usize).min(BAR_WIDTH);
+    let eta = if done == 0 {
+        "ETA --".to_owned()
+    } else {
+        let remaining_secs = elapsed.as_secs_f64() / done as f64 * total.saturating_sub(done) as f64;
+        format!("ETA {}", format_seconds(remaining_secs.ceil() as u64))
+    };
+
+    format!("[{}{}] {:>3}%  {}/{} files  {}", "#".repeat(filled), "-".repeat(BAR_WIDTH - filled), (ratio * 100f64) as usize,
+            with_seperators(files_done), with_seperators(files_queued), eta)
+}
+
+fn format_seconds(secs: u64) -> String {
+    if secs < 60 {
+        format!("{}s", secs)
+    } else {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_progress_line() {
+        assert_eq!("[------------------------------]   0%  0/0 files  ETA --", format_progress_line(0, 0, None, Duration::from_secs(1)));
+        assert_eq!("[##########--------------------]  33%  1,000/3,000 files  ETA 4s",
+                format_progress_line(1000, 3000, None, Duration::from_secs(2)));
+        assert_eq!("[##############################] 100%  10/10 files  ETA 0s", format_progress_line(10, 10, None, Duration::from_secs(5)));
+        assert_eq!("[###############---------------]  50%  1/2 files  ETA 1m 30s", format_progress_line(1, 2, None, Duration::from_secs(90)));
+        // A big file parsed first is most of the progress
+        assert_eq!("[########################------]  80%  1/10 files  ETA 1s",
+                format_progress_line(1, 10, Some((8000, 10_000)), Duration::from_secs(4)));
+    }
+}
@@ -0,0 +1,613 @@
+// A small regex engine, used for keyword aliases when '--regex-keywords' is enabled. The patterns are compiled to a program
+// that runs over the text once, advancing all of its possible states together, so that nothing recurses per char of the line.
+// Supported syntax: literals, '.', character classes ('[a-z_]', '[^0-9]'), the escapes \s \S \d \D \w \W \b \B \n \t,
+// groups with alternation ('(a|b)'), anchors ('^', '$') and the quantifiers '*', '+', '?', '{n}', '{n,}', '{n,m}',
+// optionally followed by '?' to make them lazy. A '{' that doesn't start a valid quantifier is treated as a literal.
+
+use colored::{ColoredString, Colorize};
+
+use crate::Formatted;
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regex {
+    source: String,
+    program: Vec<Inst>
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RegexError {
+    UnbalancedParenthesis(String),
+    UnterminatedClass(String),
+    NothingToRepeat(String),
+    TrailingBackslash(String)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Class(Vec<ClassItem>, bool),
+    Start,
+    End,
+    WordBoundary(bool),
+    Concat(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repeat(Box<Node>, usize, Option<usize>, bool)
+}
+
+// The instructions of the compiled program. The first target of a split is preferred over the second
+#[derive(Debug, Clone, PartialEq)]
+enum Inst {
+    Char(char),
+    Any,
+    Class(Vec<ClassItem>, bool),
+    Start,
+    End,
+    WordBoundary(bool),
+    Split(usize, usize),
+    Jump(usize),
+    Match
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ClassItem {
+    Range(char, char),
+    Digit(bool),
+    Word(bool),
+    Space(bool)
+}
+
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Regex, RegexError> {
+        Ok(Regex::compile(pattern, parse(pattern)?))
+    }
+
+    // The ascii letters of the pattern match both of their cases, e.g. for the keywords of SQL
+    pub fn new_case_insensitive(pattern: &str) -> Result<Regex, RegexError> {
+        Ok(Regex::compile(pattern, fold_case(parse(pattern)?)))
+    }
+
+    fn compile(pattern: &str, node: Node) -> Regex {
+        let mut program = Vec::new();
+        compile_node(&node, &mut program);
+        program.push(Inst::Match);
+        Regex {
+            source: pattern.to_owned(),
+            program
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.find(text).is_some()
+    }
+
+    // Returns the char indices [start, end) of the leftmost match
+    pub fn find(&self, text: &str) -> Option<(usize, usize)> {
+        let chars = text.chars().collect::<Vec<_>>();
+        self.find_in_chars(&chars, 0)
+    }
+
+    // Counts the non-overlapping, non-empty matches in the text
+    pub fn count_matches(&self, text: &str) -> usize {
+        let chars = text.chars().collect::<Vec<_>>();
+        let (mut count, mut start) = (0, 0);
+        while let Some((match_start, match_end)) = self.find_in_chars(&chars, start) {
+            if match_end > match_start {
+                count += 1;
+                start = match_end;
+            } else {
+                start = match_start + 1;
+            }
+        }
+        count
+    }
+
+    // The threads are kept in the order of their priority, so the first one to match is the one a backtracking engine would find.
+    // The threads of later starts come after the ones of earlier starts and are not created once a match is found.
+    fn find_in_chars(&self, chars: &[char], start: usize) -> Option<(usize, usize)> {
+        let mut current = Threads::new(self.program.len());
+        let mut next = Threads::new(self.program.len());
+        let mut found = None;
+        for pos in start..=chars.len() {
+            if found.is_none() {
+                self.add_thread(&mut current, 0, pos, pos, chars);
+            }
+            if current.list.is_empty() && found.is_some() {
+                break;
+            }
+
+            next.clear();
+            for &(pc, thread_start) in &current.list {
+                let consumes = match &self.program[pc] {
+                    Inst::Char(c) => pos < chars.len() && chars[pos] == *c,
+                    Inst::Any => pos < chars.len() && chars[pos] != '\n',
+                    Inst::Class(items, negated) => pos < chars.len() && items.iter().any(|x| class_item_matches(x, chars[pos])) != *negated,
+                    Inst::Match => {
+                        // The threads after this one have a lower priority
+                        found = Some((thread_start, pos));
+                        break;
+                    },
+                    _ => false
+                };
+                if consumes {
+                    self.add_thread(&mut next, pc + 1, thread_start, pos + 1, chars);
+                }
+            }
+            std::mem::swap(&mut current, &mut next);
+        }
+        found
+    }
+
+    // Follows the jumps, splits and assertions from 'pc' at 'pos', adding the instructions that they lead to in the order of their priority
+    fn add_thread(&self, threads: &mut Threads, pc: usize, start: usize, pos: usize, chars: &[char]) {
+        threads.stack.push(pc);
+        while let Some(pc) = threads.stack.pop() {
+            if threads.visited[pc] == threads.generation {
+                continue;
+            }
+            threads.visited[pc] = threads.generation;
+            match &self.program[pc] {
+                Inst::Jump(x) => threads.stack.push(*x),
+                Inst::Split(preferred, other) => {
+                    threads.stack.push(*other);
+                    threads.stack.push(*preferred);
+                },
+                Inst::Start => if pos == 0 {threads.stack.push(pc + 1)},
+                Inst::End => if pos == chars.len() {threads.stack.push(pc + 1)},
+                Inst::WordBoundary(positive) => {
+                    let before = pos > 0 && is_word_char(chars[pos - 1]);
+                    let after = pos < chars.len() && is_word_char(chars[pos]);
+                    if (before != after) == *positive {
+                        threads.stack.push(pc + 1);
+                    }
+                },
+                _ => threads.list.push((pc, start))
+            }
+        }
+    }
+}
+
+// The instructions that the threads are at, with the start of their match, for one position of the text
+struct Threads {
+    list: Vec<(usize, usize)>,
+    // The generation at which each instruction was last visited, so that clearing doesn't touch the whole program
+    visited: Vec<usize>,
+    generation: usize,
+    stack: Vec<usize>
+}
+
+impl Threads {
+    fn new(program_len: usize) -> Self {
+        Threads {list: Vec::new(), visited: vec![0; program_len], generation: 1, stack: Vec::new()}
+    }
+
+    fn clear(&mut self) {
+        self.list.clear();
+        self.generation += 1;
+    }
+}
+
+fn parse(pattern: &str) -> Result<Node, RegexError> {
+    let mut parser = Parser {
+        chars: pattern.chars().collect(),
+        pos: 0,
+        source: pattern
+    };
+    let node = parser.parse_alternation()?;
+    if parser.pos < parser.chars.len() {
+        return Err(RegexError::UnbalancedParenthesis(pattern.to_owned()));
+    }
+    Ok(node)
+}
+
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    source: &'a str
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn parse_alternation(&mut self) -> Result<Node, RegexError> {
+        let mut branches = vec![self.parse_concat()?];
+        while self.peek() == Some('|') {
+            self.pos += 1;
+            branches.push(self.parse_concat()?);
+        }
+
+        if branches.len() == 1 {
+            Ok(branches.pop().unwrap())
+        } else {
+            Ok(Node::Alternation(branches))
+        }
+    }
+
+    fn parse_concat(&mut self) -> Result<Node, RegexError> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {break;}
+            let atom = self.parse_atom()?;
+            nodes.push(self.parse_quantifier(atom)?);
+        }
+
+        match nodes.len() {
+            0 => Ok(Node::Empty),
+            1 => Ok(nodes.pop().unwrap()),
+            _ => Ok(Node::Concat(nodes))
+        }
+    }
+
+    fn parse_atom(&mut self) -> Result<Node, RegexError> {
+        let c = self.chars[self.pos];
+        self.pos += 1;
+        match c {
+            '.' => Ok(Node::Any),
+            '^' => Ok(Node::Start),
+            '$' => Ok(Node::End),
+            '(' => {
+                if self.chars[self.pos..].starts_with(&['?', ':']) {
+                    self.pos += 2;
+                }
+                let node = self.parse_alternation()?;
+                if self.peek() != Some(')') {
+                    return Err(RegexError::UnbalancedParenthesis(self.source.to_owned()));
+                }
+                self.pos += 1;
+                Ok(node)
+            },
+            '[' => self.parse_class(),
+            '\\' => self.parse_escape(),
+            '*' | '+' | '?' => Err(RegexError::NothingToRepeat(self.source.to_owned())),
+            _ => Ok(Node::Char(c))
+        }
+    }
+
+    fn parse_escape(&mut self) -> Result<Node, RegexError> {
+        let c = match self.peek() {
+            Some(x) => x,
+            None => return Err(RegexError::TrailingBackslash(self.source.to_owned()))
+        };
+        self.pos += 1;
+        Ok(match c {
+            'b' => Node::WordBoundary(true),
+            'B' => Node::WordBoundary(false),
+            _ => match class_item_of_escape(c) {
+                Some(item) => Node::Class(vec![item], false),
+                None => Node::Char(literal_of_escape(c))
+            }
+        })
+    }
+
+    fn parse_class(&mut self) -> Result<Node, RegexError> {
+        let mut items = Vec::new();
+        let negated = self.peek() == Some('^');
+        if negated {self.pos += 1;}
+
+        let mut is_first = true;
+        loop {
+            let c = match self.peek() {
+                Some(x) => x,
+                None => return Err(RegexError::UnterminatedClass(self.source.to_owned()))
+            };
+            self.pos += 1;
+            if c == ']' && !is_first {break;}
+            is_first = false;
+
+            let start = if c == '\\' {
+                let escaped = match self.peek() {
+                    Some(x) => x,
+                    None => return Err(RegexError::UnterminatedClass(self.source.to_owned()))
+                };
+                self.pos += 1;
+                if let Some(item) = class_item_of_escape(escaped) {
+                    items.push(item);
+                    continue;
+                }
+                literal_of_escape(escaped)
+            } else {
+                c
+            };
+
+            if self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|x| *x != ']') {
+                self.pos += 1;
+                let mut end = self.chars[self.pos];
+                self.pos += 1;
+                if end == '\\' {
+                    end = match self.peek() {
+                        Some(x) => literal_of_escape(x),
+                        None => return Err(RegexError::UnterminatedClass(self.source.to_owned()))
+                    };
+                    self.pos += 1;
+                }
+                items.push(ClassItem::Range(start, end));
+            } else {
+                items.push(ClassItem::Range(start, start));
+            }
+        }
+
+        Ok(Node::Class(items, negated))
+    }
+
+    fn parse_quantifier(&mut self, atom: Node) -> Result<Node, RegexError> {
+        let (min, max) = match self.peek() {
+            Some('*') => {self.pos += 1; (0, None)},
+            Some('+') => {self.pos += 1; (1, None)},
+            Some('?') => {self.pos += 1; (0, Some(1))},
+            Some('{') => match self.parse_braces_quantifier() {
+                Some(x) => x,
+                None => return Ok(atom)
+            },
+            _ => return Ok(atom)
+        };
+
+        if matches!(atom, Node::Start | Node::End | Node::WordBoundary(_) | Node::Empty) {
+            return Err(RegexError::NothingToRepeat(self.source.to_owned()));
+        }
+
+        let greedy = self.peek() != Some('?');
+        if !greedy {self.pos += 1;}
+
+        Ok(Node::Repeat(Box::new(atom), min, max, greedy))
+    }
+
+    // Returns None without consuming anything, if the '{' is not the start of a quantifier
+    fn parse_braces_quantifier(&mut self) -> Option<(usize, Option<usize>)> {
+        let close = self.chars[self.pos..].iter().position(|x| *x == '}')? + self.pos;
+        let inner = self.chars[self.pos+1..close].iter().collect::<String>();
+        let (min, max) = match inner.split_once(',') {
+            Some((min, max)) => {
+                let min = min.trim().parse::<usize>().ok()?;
+                let max = if max.trim().is_empty() {None} else {Some(max.trim().parse::<usize>().ok()?)};
+                (min, max)
+            },
+            None => {
+                let n = inner.trim().parse::<usize>().ok()?;
+                (n, Some(n))
+            }
+        };
+        if max.is_some_and(|x| x < min) {
+            return None;
+        }
+
+        self.pos = close + 1;
+        Some((min, max))
+    }
+}
+
+fn class_item_of_escape(c: char) -> Option<ClassItem> {
+    match c {
+        'd' => Some(ClassItem::Digit(true)),
+        'D' => Some(ClassItem::Digit(false)),
+        'w' => Some(ClassItem::Word(true)),
+        'W' => Some(ClassItem::Word(false)),
+        's' => Some(ClassItem::Space(true)),
+        'S' => Some(ClassItem::Space(false)),
+        _ => None
+    }
+}
+
+fn literal_of_escape(c: char) -> char {
+    match c {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        _ => c
+    }
+}
+
+// The letters become classes of both their cases, and the ranges of the classes get the ranges of the other case
+fn fold_case(node: Node) -> Node {
+    match node {
+        Node::Char(c) if c.is_ascii_alphabetic() => {
+            Node::Class(vec![ClassItem::Range(c.to_ascii_lowercase(), c.to_ascii_lowercase()),
+                    ClassItem::Range(c.to_ascii_uppercase(), c.to_ascii_uppercase())], false)
+        },
+        Node::Class(items, negated) => {
+            let mut folded = items.clone();
+            for item in items {
+                if let ClassItem::Range(start, end) = item {
+                    for (from, to) in [('a', 'z'), ('A', 'Z')] {
+                        let (start, end) = (start.max(from), end.min(to));
+                        if start <= end {
+                            folded.push(ClassItem::Range(swap_ascii_case(start), swap_ascii_case(end)));
+                        }
+                    }
+                }
+            }
+            Node::Class(folded, negated)
+        },
+        Node::Concat(nodes) => Node::Concat(nodes.into_iter().map(fold_case).collect()),
+        Node::Alternation(nodes) => Node::Alternation(nodes.into_iter().map(fold_case).collect()),
+        Node::Repeat(node, min, max, greedy) => Node::Repeat(Box::new(fold_case(*node)), min, max, greedy),
+        x => x
+    }
+}
+
+fn swap_ascii_case(c: char) -> char {
+    if c.is_ascii_lowercase() {c.to_ascii_uppercase()} else {c.to_ascii_lowercase()}
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn class_item_matches(item: &ClassItem, c: char) -> bool {
+    match item {
+        ClassItem::Range(start, end) => *start <= c && c <= *end,
+        ClassItem::Digit(positive) => c.is_ascii_digit() == *positive,
+        ClassItem::Word(positive) => is_word_char(c) == *positive,
+        ClassItem::Space(positive) => c.is_whitespace() == *positive
+    }
+}
+
+fn compile_node(node: &Node, program: &mut Vec<Inst>) {
+    match node {
+        Node::Empty => (),
+        Node::Char(c) => program.push(Inst::Char(*c)),
+        Node::Any => program.push(Inst::Any),
+        Node::Class(items, negated) => program.push(Inst::Class(items.clone(), *negated)),
+        Node::Start => program.push(Inst::Start),
+        Node::End => program.push(Inst::End),
+        Node::WordBoundary(positive) => program.push(Inst::WordBoundary(*positive)),
+        Node::Concat(nodes) => nodes.iter().for_each(|x| compile_node(x, program)),
+        Node::Alternation(branches) => {
+            let mut jumps_to_end = Vec::new();
+            for (i, branch) in branches.iter().enumerate() {
+                if i == branches.len() - 1 {
+                    compile_node(branch, program);
+                    break;
+                }
+                let split = program.len();
+                program.push(Inst::Split(split + 1, 0));
+                compile_node(branch, program);
+                jumps_to_end.push(program.len());
+                program.push(Inst::Jump(0));
+                program[split] = Inst::Split(split + 1, program.len());
+            }
+            let end = program.len();
+            for x in jumps_to_end {
+                program[x] = Inst::Jump(end);
+            }
+        },
+        Node::Repeat(inner, min, max, greedy) => {
+            for _ in 0..*min {
+                compile_node(inner, program);
+            }
+            let split_of = |body: usize, out: usize| if *greedy {Inst::Split(body, out)} else {Inst::Split(out, body)};
+            match max {
+                None => {
+                    let split = program.len();
+                    program.push(Inst::Split(0, 0));
+                    compile_node(inner, program);
+                    program.push(Inst::Jump(split));
+                    program[split] = split_of(split + 1, program.len());
+                },
+                Some(max) => {
+                    let mut splits = Vec::new();
+                    for _ in *min..*max {
+                        splits.push(program.len());
+                        program.push(Inst::Split(0, 0));
+                        compile_node(inner, program);
+                    }
+                    let end = program.len();
+                    for x in splits {
+                        program[x] = split_of(x + 1, end);
+                    }
+                }
+            }
+        }
+    }
+}
+
+
+impl Formatted for RegexError {
+    fn formatted(&self) -> ColoredString {
+        match self {
+            Self::UnbalancedParenthesis(x) => format!("Keyword pattern '{}' has unbalanced parenthesis.",x).red(),
+            Self::UnterminatedClass(x) => format!("Keyword pattern '{}' has an unterminated character class.",x).red(),
+            Self::NothingToRepeat(x) => format!("Keyword pattern '{}' has a quantifier with nothing to repeat.",x).red(),
+            Self::TrailingBackslash(x) => format!("Keyword pattern '{}' ends with a backslash.",x).red()
+        }
+    }
+}
+
+impl_display_from_formatted!(RegexError);
+
+impl std::error::Error for RegexError {}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regex_parsing() {
+        assert!(Regex::new("unsafe\\s*\\{").is_ok());
+        assert!(Regex::new("#\\[test\\]").is_ok());
+        assert!(Regex::new("a{2,3}").is_ok());
+        assert!(Regex::new("{").is_ok());
+
+        assert_eq!(Err(RegexError::UnbalancedParenthesis("(a".to_owned())), Regex::new("(a"));
+        assert_eq!(Err(RegexError::UnbalancedParenthesis("a)".to_owned())), Regex::new("a)"));
+        assert_eq!(Err(RegexError::UnterminatedClass("[ab".to_owned())), Regex::new("[ab"));
+        assert_eq!(Err(RegexError::NothingToRepeat("*a".to_owned())), Regex::new("*a"));
+        assert_eq!(Err(RegexError::NothingToRepeat("^*".to_owned())), Regex::new("^*"));
+        assert_eq!(Err(RegexError::TrailingBackslash("a\\".to_owned())), Regex::new("a\\"));
+    }
+
+    #[test]
+    fn test_regex_matching() {
+        let regex = Regex::new("unsafe\\s*\\{").unwrap();
+        assert!(regex.is_match("let x = unsafe {"));
+        assert!(regex.is_match("unsafe{"));
+        assert!(!regex.is_match("unsafe fn a() {"));
+
+        let regex = Regex::new("#\\[test\\]").unwrap();
+        assert!(regex.is_match("    #[test]"));
+        assert!(!regex.is_match("#[tests]"));
+
+        let regex = Regex::new("\\bclass\\b").unwrap();
+        assert!(regex.is_match("public class A {"));
+        assert!(!regex.is_match("let subclass = 1;"));
+        assert!(!regex.is_match("classes"));
+
+        assert_eq!(Some((0,3)), Regex::new("a+").unwrap().find("aaab"));
+        assert_eq!(Some((0,1)), Regex::new("a+?").unwrap().find("aaab"));
+        assert_eq!(Some((1,3)), Regex::new("(ab|cd)").unwrap().find("xcdab"));
+        assert_eq!(Some((2,5)), Regex::new("[0-9]{2,3}").unwrap().find("ab1234"));
+        assert_eq!(Some((0,2)), Regex::new("[^a-c]{2}").unwrap().find("xyz"));
+        assert_eq!(None, Regex::new("^b").unwrap().find("ab"));
+        assert_eq!(Some((1,2)), Regex::new("b$").unwrap().find("ab"));
+        assert_eq!(Some((0,2)), Regex::new("a{").unwrap().find("a{"));
+    }
+
+    #[test]
+    fn test_regex_count_matches() {
+        assert_eq!(2, Regex::new("\\bclass\\b").unwrap().count_matches("class A { class B {} } subclass"));
+        assert_eq!(3, Regex::new("a").unwrap().count_matches("banana"));
+        assert_eq!(1, Regex::new("an+a").unwrap().count_matches("banana"));
+        assert_eq!(0, Regex::new("x*").unwrap().count_matches("abc"));
+    }
+
+    #[test]
+    fn test_case_insensitive_regex() {
+        let regex = Regex::new_case_insensitive("\\bselect\\b").unwrap();
+        assert_eq!(3, regex.count_matches("SELECT a FROM (Select b FROM c) UNION select d"));
+        assert!(!regex.is_match("SELECTED"));
+        assert_eq!("\\bselect\\b", regex.as_str());
+
+        assert!(Regex::new_case_insensitive("[a-c]+_[^x]").unwrap().is_match("AbC_y"));
+        assert!(!Regex::new_case_insensitive("[^x]").unwrap().is_match("X"));
+        assert!(Regex::new_case_insensitive("[0-9A-F]{2}").unwrap().is_match("ff"));
+        assert!(Regex::new_case_insensitive("(begin|end);").unwrap().is_match("END;"));
+        assert!(!Regex::new("select").unwrap().is_match("SELECT"));
+    }
+
+    #[test]
+    fn test_regex_empty_repetitions() {
+        assert_eq!(Some((0,3)), Regex::new("(a*)*").unwrap().find("aaab"));
+        assert_eq!(Some((0,1)), Regex::new("(a?)+?").unwrap().find("aa"));
+        assert_eq!(Some((0,2)), Regex::new("(|a)+b").unwrap().find("ab"));
+    }
+
+    #[test]
+    fn test_regex_long_line_on_small_stack() {
+        let spaces = " ".repeat(100_000);
+        let matches = std::thread::Builder::new().stack_size(64 * 1024).spawn(move || {
+            let regex = Regex::new("unsafe\\s*\\{").unwrap();
+            (regex.is_match(&format!("unsafe{}{{", spaces)), regex.count_matches(&spaces), Regex::new("\\s+x").unwrap().is_match(&spaces))
+        }).unwrap().join().unwrap();
+        assert_eq!((true, 0, false), matches);
+    }
+}
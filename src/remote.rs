@@ -0,0 +1,104 @@
+use std::{process::Command, time::{SystemTime, UNIX_EPOCH}};
+
+use crate::*;
+
+
+const URL_SCHEMES : [&str; 5] = ["https://", "http://", "ssh://", "git://", "file://"];
+
+
+// A shallow clone of a remote repository in a temporary directory, that is removed when this is dropped
+#[derive(Debug)]
+pub struct RemoteCheckout {
+    path: PathBuf
+}
+
+impl RemoteCheckout {
+    pub fn path_str(&self) -> String {
+        self.path.to_string_lossy().replace('\\', "/")
+    }
+}
+
+impl Drop for RemoteCheckout {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+// Accepts the urls with a scheme that git understands, or the scp-like 'user@host:path' form
+pub fn is_valid_remote_url(url: &str) -> bool {
+    if url.is_empty() || url.starts_with('-') || url.contains(char::is_whitespace) {
+        return false;
+    }
+    if let Some(scheme) = URL_SCHEMES.iter().find(|x| url.starts_with(*x)) {
+        return url.len() > scheme.len();
+    }
+
+    match url.split_once(':') {
+        Some((user_host, path)) => user_host.contains('@') && !user_host.contains('/') && !path.is_empty(),
+        None => false
+    }
+}
+
+// Clones only the latest commit of the default branch. Git is not allowed to prompt for credentials,
+// since nobody would answer while the output is not shown.
+pub fn clone_shallow(url: &str) -> Result<RemoteCheckout, String> {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|x| x.subsec_nanos()).unwrap_or(0);
+    let checkout = RemoteCheckout {path: std::env::temp_dir().join(format!("mezura-remote-{}-{}", std::process::id(), nanos))};
+
+    let output = Command::new("git").args(["clone", "--depth", "1", "--quiet", "--no-tags", "--"]).arg(url).arg(&checkout.path)
+            .env("GIT_TERMINAL_PROMPT", "0").output()
+            .map_err(|x| format!("could not run git ({})", x))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_owned());
+    }
+
+    Ok(checkout)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_remote_url() {
+        assert!(is_valid_remote_url("https://github.com/tshepang/mezura.git"));
+        assert!(is_valid_remote_url("ssh://git@github.com/tshepang/mezura"));
+        assert!(is_valid_remote_url("git@github.com:tshepang/mezura.git"));
+        assert!(is_valid_remote_url("file:///tmp/repo"));
+        assert!(!is_valid_remote_url("https://"));
+        assert!(!is_valid_remote_url("--upload-pack=touch /tmp/x"));
+        assert!(!is_valid_remote_url("github.com/tshepang/mezura"));
+        assert!(!is_valid_remote_url("./some/dir"));
+        assert!(!is_valid_remote_url("C:/some/dir"));
+        assert!(!is_valid_remote_url(""));
+    }
+
+    #[test]
+    fn test_clone_shallow() {
+        let dir = std::env::temp_dir().join("mezura-test-remote");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+
+        let git = |args: &[&str]| Command::new("git").args(["-c", "user.name=mezura", "-c", "user.email=mezura@localhost"]).args(args)
+                .current_dir(&dir).output().map(|x| x.status.success()).unwrap_or(false);
+        if !git(&["init", "-q"]) {
+            // git is not installed
+            fs::remove_dir_all(&dir).unwrap();
+            return;
+        }
+        assert!(git(&["add", "main.rs"]));
+        assert!(git(&["commit", "-q", "-m", "first"]));
+
+        let checkout = clone_shallow(&format!("file://{}", dir.to_str().unwrap())).unwrap();
+        let checkout_path = PathBuf::from(checkout.path_str());
+        assert!(checkout_path.join("main.rs").is_file());
+        drop(checkout);
+        assert!(!checkout_path.exists());
+
+        assert!(clone_shallow(&format!("file://{}", dir.join("missing").to_str().unwrap())).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
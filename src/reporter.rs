@@ -0,0 +1,91 @@
+// The messages of a run, e.g. the banners, the counts of the files and the lines of '--verbose', are printed through a
+// single writer, so that the lines of the threads are not mixed with each other or with the progress bar. With an output
+// format other than text they go to stderr, so that the output can be read by other programs as it is.
+
+use std::io::{self, Write};
+
+use lazy_static::lazy_static;
+
+use crate::{*, config_manager::{OutputFormat, Verbosity}};
+
+lazy_static! {
+    static ref OUTPUT_LOCK : Mutex<()> = Mutex::new(());
+}
+
+#[derive(Debug,PartialEq,Clone,Copy)]
+pub enum Level {
+    // Printed however quiet the run is, e.g. a report that couldn't be saved
+    Warning,
+    // The progress of the run, left out with '--quiet'
+    Info,
+    // Every parsed file and the timings of the threads, only with '--verbose'
+    Verbose
+}
+
+impl Level {
+    pub fn is_shown(self, verbosity: Verbosity) -> bool {
+        match self {
+            Level::Warning => true,
+            Level::Info => verbosity != Verbosity::Quiet,
+            Level::Verbose => verbosity == Verbosity::Verbose
+        }
+    }
+}
+
+pub fn report(config: &Configuration, level: Level, msg: &str) {
+    report_with(config, level, |out| {
+        writeln!(out, "{}", msg);
+    });
+}
+
+pub fn info(config: &Configuration, msg: &str) {
+    report(config, Level::Info, msg);
+}
+
+pub fn verbose(config: &Configuration, msg: &str) {
+    report(config, Level::Verbose, msg);
+}
+
+pub fn warning(config: &Configuration, msg: &str) {
+    report(config, Level::Warning, msg);
+}
+
+// For a message of many lines, that are written at once
+pub fn report_with(config: &Configuration, level: Level, write: impl FnOnce(&mut dyn Write)) {
+    if !level.is_shown(config.verbosity) {
+        return;
+    }
+    hold(|| {
+        if config.output == OutputFormat::Text {
+            let mut out = io::stdout().lock();
+            write(&mut out);
+            out.flush();
+        } else {
+            let mut out = io::stderr().lock();
+            write(&mut out);
+            out.flush();
+        }
+    });
+}
+
+// Runs the printing while no message is printed, e.g. of the progress bar or of a language that was streamed.
+// Nothing in it may report a message, since the writer is not reentrant.
+pub fn hold<R>(print: impl FnOnce() -> R) -> R {
+    let _guard = OUTPUT_LOCK.lock().unwrap_or_else(|x| x.into_inner());
+    print()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_shown() {
+        assert!(Level::Warning.is_shown(Verbosity::Quiet));
+        assert!(!Level::Info.is_shown(Verbosity::Quiet));
+        assert!(Level::Info.is_shown(Verbosity::Normal));
+        assert!(!Level::Verbose.is_shown(Verbosity::Normal));
+        assert!(Level::Verbose.is_shown(Verbosity::Verbose));
+    }
+}
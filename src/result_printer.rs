@@ -1,13 +1,69 @@
-use std::cmp::max;
+use std::{cmp::max, io::Write};
 
-use crate::*;
+use colored::Color;
+
+use crate::{*, badge::BadgeMetric, config_manager::{Column, OutputFormat, SortKey, SortOrder}, warnings::RunWarning};
 
 //the total number of vertical lines ( | ) that appear in the [-|||...|-] in the overview section
 const NUM_OF_VERTICALS : usize = 50;
+// In a narrow terminal the bars of the overview are shortened down to this many verticals, and below it they go on a line of their own
+const MIN_NUM_OF_VERTICALS : usize = 10;
+// The '    [-' and '-]' around the verticals
+const VERTICALS_BLOCK_EXTRA_LEN : usize = 8;
+// The dates of 'trend', e.g. "2021-10-17"
+const TREND_DATE_LEN : usize = 10;
+
+// Without the length of the "files" label, that differs per language
+const KEYWORD_LINE_OFFSET : usize = 14;
+// The spaces between the columns of the keywords that don't fit in a line
+const KEYWORD_COLUMNS_GAP : usize = 3;
+// Without colors, the languages are told apart in the bars of the overview by these, that are shown next to their names
+const PLAIN_VERTICALS : [&str; 10] = ["|", "=", "+", ":", "#", "*", "~", "%", "o", "x"];
+// The same with '--style unicode'
+const PLAIN_BLOCKS : [&str; 10] = ["█", "▓", "▒", "░", "▚", "▞", "▙", "▟", "▛", "▜"];
+// How many languages are shown individually in the overview if '--top' is not used
+const DEF_OVERVIEW_TOP : usize = 3;
+// The upper limits of the lines of the files in each bar of '--histogram'
+const HISTOGRAM_BUCKETS : [(usize, &str); 4] = [(50, "0-50"), (200, "51-200"), (1000, "201-1000"), (usize::MAX, ">1000")];
+const OVERVIEW_COLORS : [Color; 4] = [Color::Cyan, Color::BrightMagenta, Color::BrightYellow, Color::TrueColor {r: 106, g: 217, b: 189}];
+const OTHERS_COLOR : Color = Color::TrueColor {r: 215, g: 201, b: 240};
+
+type OverviewLabel = fn(&labels::Labels) -> String;
+type OverviewValue = fn(&LanguageContentInfo, &LanguageMetadata) -> usize;
+// The lines that the overview can have, chosen with '--overview', with the label of each and the value of every language in it
+const OVERVIEW_METRICS : [(Column, OverviewLabel, OverviewValue); 6] = [
+    (Column::Files, |labels| labels::capitalized(labels.files), |_, metadata| metadata.files),
+    (Column::Lines, |labels| labels::capitalized(labels.lines), |content_info, _| content_info.lines),
+    (Column::Code, |labels| labels::capitalized(labels.code), |content_info, _| content_info.code_lines),
+    (Column::Comments, |labels| labels::capitalized(labels.extra), |content_info, _| content_info.lines - content_info.code_lines),
+    (Column::Size, |labels| labels.size.to_owned(), |_, metadata| metadata.bytes),
+    (Column::Keywords, |labels| labels::capitalized(labels.keywords), |content_info, _| content_info.keyword_occurences.values().sum())
+];
+// How many of the longest blocks of '--clones' are listed
+const MAX_LISTED_CLONES : usize = 25;
+
+// The names of the languages to be shown, along with their stats
+type GroupedLanguages = (Vec<String>, HashMap<String, LanguageContentInfo>, HashMap<String, LanguageMetadata>);
+// How much a value can differ from the typical one (as a fraction of it) and still be considered typical
+const TYPICAL_TOLERANCE : f64 = 0.25;
+
+// With '--out' the results are written to the file instead of stdout. Set once, before anything is printed
+static OUT_FILE : Mutex<Option<File>> = Mutex::new(None);
+
+// In place of 'print!' and 'println!' for everything that is part of the results
+macro_rules! out {
+    ($($arg:tt)*) => {write_out(&format!($($arg)*))};
+}
+
+macro_rules! outln {
+    () => {write_out("\n")};
+    ($($arg:tt)*) => {write_out(&format!("{}\n", format_args!($($arg)*)))};
+}
 
-const KEYWORD_LINE_OFFSET : usize = 19;
-const STANDARD_LINE_STATS_LEN : usize = 33;
-const DASH_LINE_OFFSET : usize = 47;
+lazy_static! {
+    // None if the output is not a terminal or its width is unknown, in which case nothing is fitted to it
+    static ref TERMINAL_WIDTH : Option<usize> = get_terminal_width();
+}
 
 //log file keys
 const FILES         : &str  = "Files:";
@@ -17,18 +73,114 @@ const EXTRA         : &str  = "Extra:";
 const TOTAL_SIZE    : &str  = "Total Size:";
 const AVERAGE_SIZE  : &str  = "Average Size:";
 
+#[allow(clippy::too_many_arguments)]
 pub fn format_and_print_results(content_info_map: &mut HashMap<String, LanguageContentInfo>, languages_metadata_map: &mut HashMap<String, LanguageMetadata>,
-        final_stats: &FinalStats, existing_log_content: &Option<String>, datetime_now: &DateTime<Local>, config: &Configuration) 
+        final_stats: &FinalStats, file_categories_map: &BTreeMap<FileCategory,DirStats>, dir_tree: &[(usize, String, DirStats)],
+        warnings: &[RunWarning], existing_log_content: &Option<String>, datetime_now: &DateTime<Local>, config: &Configuration) 
 {
-    let mut sorted_language_names = get_language_names_as_sorted_vec_according_to_how_much_they_appeared(languages_metadata_map);
-    let biggest_prefix_standard_spaces = get_biggest_prefix_standard_spaces(&sorted_language_names, languages_metadata_map);
+    let mut sorted_language_names = get_language_names_as_sorted_vec(content_info_map, languages_metadata_map, config.sort);
+    let is_text = config.output == OutputFormat::Text;
+    let metrics = derived_metrics::evaluate_derived_metrics(&config.metrics, content_info_map, languages_metadata_map, final_stats);
+
+    // When quiet, only what can be read by other programs is printed
+    if config.is_quiet() {
+        match config.output {
+            OutputFormat::Markdown => outln!("{}", get_markdown_tables(&sorted_language_names, content_info_map, languages_metadata_map,
+                    final_stats, config)),
+            OutputFormat::Prometheus => out!("{}", get_prometheus_metrics(&sorted_language_names, content_info_map, languages_metadata_map, config)),
+            OutputFormat::Json => outln!("{}", export::Results::new(&config.dirs, content_info_map, languages_metadata_map, final_stats)
+                    .with_directories(dir_tree, config.top).with_warnings(warnings).with_metrics(&metrics).to_json()),
+            OutputFormat::Xml => out!("{}", export::Results::new(&config.dirs, content_info_map, languages_metadata_map, final_stats).to_xml()),
+            OutputFormat::Yaml => out!("{}", export::Results::new(&config.dirs, content_info_map, languages_metadata_map, final_stats).to_yaml()),
+            OutputFormat::Text => print_summary_line(final_stats)
+        }
+        return;
+    }
+
+    if config.output == OutputFormat::Markdown {
+        outln!("{}", get_markdown_tables(&sorted_language_names, content_info_map, languages_metadata_map, final_stats, config));
+    } else if config.output == OutputFormat::Prometheus {
+        out!("{}", get_prometheus_metrics(&sorted_language_names, content_info_map, languages_metadata_map, config));
+    } else if config.output == OutputFormat::Json {
+        outln!("{}", export::Results::new(&config.dirs, content_info_map, languages_metadata_map, final_stats)
+                    .with_directories(dir_tree, config.top).with_warnings(warnings).with_metrics(&metrics).to_json());
+    } else if config.output == OutputFormat::Xml {
+        out!("{}", export::Results::new(&config.dirs, content_info_map, languages_metadata_map, final_stats).to_xml());
+    } else if config.output == OutputFormat::Yaml {
+        out!("{}", export::Results::new(&config.dirs, content_info_map, languages_metadata_map, final_stats).to_yaml());
+    } else {
+        // With '--top', the languages after the first ones are summed up in a single row
+        let grouped = config.top.and_then(|top| group_rest_as_others(&sorted_language_names, content_info_map, languages_metadata_map, top));
+        let (names, details_content_info_map, details_metadata_map) = match &grouped {
+            Some((names, grouped_content_info_map, grouped_metadata_map)) => (names, grouped_content_info_map, grouped_metadata_map),
+            None => (&sorted_language_names, &*content_info_map, &*languages_metadata_map)
+        };
+        let biggest_prefix_standard_spaces = get_biggest_prefix_standard_spaces(names, details_metadata_map);
+        // With '--summary' only the total is printed, even for a single language
+        if !config.summary {
+            print_individually(names, details_content_info_map, details_metadata_map, biggest_prefix_standard_spaces, config);
+        }
+
+        if languages_metadata_map.len() > 1 || config.summary {
+            print_sum(content_info_map, final_stats, file_categories_map, &metrics, biggest_prefix_standard_spaces, config);
+        } else if let Some(line) = get_derived_metrics_line(&metrics, biggest_prefix_standard_spaces) {
+            // A single language is its own total
+            outln!("{}", line);
+        }
+    }
 
-    print_individually(&sorted_language_names, content_info_map, languages_metadata_map, biggest_prefix_standard_spaces, !config.no_keywords);
+    if config.co_occurrence.is_some() && !config.no_keywords && !config.summary {
+        print_keyword_co_occurrences(&sorted_language_names, content_info_map);
+    }
 
-    if languages_metadata_map.len() > 1 {
-        print_sum(content_info_map, final_stats, biggest_prefix_standard_spaces, !config.no_keywords);
+    // Made before the overview puts the less relevant languages in 'others', which have no lines per file
+    let histogram_line = if config.histogram && is_text {get_histogram_line(content_info_map, config)} else {None};
+
+    // The overview is made of colors, so it has no place in markdown or metrics
+    let has_overview = languages_metadata_map.len() > 1 && is_text;
+    if has_overview {
         print_visual_overview(&mut sorted_language_names, content_info_map, languages_metadata_map, final_stats, config);
     }
+    if let Some(line) = histogram_line {
+        if !has_overview {
+            outln!("\n{}.\n", labels::get().overview.underline().bold());
+        }
+        outln!("{}\n", line);
+    }
+
+    if let Some(content) = existing_log_content {
+        if config.compare_level != 0 {
+            print_comparison_to_previous_runs(final_stats, content,  config.compare_level, datetime_now);
+        }    
+    }
+}
+
+// Used with '--isolate-roots', after the sections of the roots
+//
+// Combined total.
+//
+// /home/user/project-a   ->   120 files  |  lines 15,000 {10,000 code + 5,000 extra}  |  1.2 MBs
+// /home/user/project-b   ->    35 files  |  lines  2,000 {1,500 code + 500 extra}  |  80.5 KBs
+// Total                  ->   155 files  |  lines 17,000 {11,500 code + 5,500 extra}  |  1.3 MBs
+pub fn print_combined_total(roots_final_stats: &[(String, FinalStats)], final_stats: &FinalStats, existing_log_content: &Option<String>,
+        datetime_now: &DateTime<Local>, config: &Configuration)
+{
+    let labels = labels::get();
+    outln!("\n{}.\n", labels.combined_total.underline().bold());
+
+    let total_title = labels::capitalized(labels.total);
+    let rows = roots_final_stats.iter().map(|(root, stats)| (root, stats)).chain(std::iter::once((&total_title, final_stats))).collect::<Vec<_>>();
+    let max_title_len = rows.iter().map(|(title, _)| title.chars().count()).max().unwrap_or(0);
+    let max_files_len = rows.iter().map(|(_, stats)| with_seperators(stats.files).len()).max().unwrap_or(0);
+    let max_lines_len = rows.iter().map(|(_, stats)| with_seperators(stats.lines).len()).max().unwrap_or(0);
+
+    for (title, stats) in rows {
+        outln!("{}{}   ->  {:>files_width$} {}  |  {} {:>lines_width$} {{{} {} + {} {}}}  |  {:.precision$} {}", title.bold(),
+                " ".repeat(max_title_len - title.chars().count()), with_seperators(stats.files), colored_word(labels.files),
+                colored_word(labels.lines), with_seperators(stats.lines), with_seperators(stats.code_lines), labels.code,
+                with_seperators(stats.extra_lines), labels.extra, stats.size, colored_word(&stats.size_measurement),
+                files_width = max_files_len, lines_width = max_lines_len, precision = get_size_precision());
+    }
 
     if let Some(content) = existing_log_content {
         if config.compare_level != 0 {
@@ -37,106 +189,892 @@ pub fn format_and_print_results(content_info_map: &mut HashMap<String, LanguageC
     }
 }
 
+// Used with '--quiet', instead of the results. It has no colors, so that it can be parsed.
+//
+// Total   ->  155 files  |  lines 17,000 {11,500 code + 5,500 extra}  |  1.3 MBs
+pub fn print_summary_line(final_stats: &FinalStats) {
+    outln!("{}", get_summary_line(final_stats));
+}
+
+fn get_summary_line(final_stats: &FinalStats) -> String {
+    get_stats_line(&labels::capitalized(labels::get().total), final_stats)
+}
+
+// Used with '--stream', while the files are parsed. The languages are printed in the order they are finished, under a heading
+// that comes before the first one.
+//
+// Finished languages.
+//
+// Python   ->  40 files  |  lines 6,200 {4,900 code + 1,300 extra}  |  210.5 KBs
+// Rust   ->  120 files  |  lines 15,000 {10,000 code + 5,000 extra}  |  1.2 MBs
+// The names are padded to the longest name of the languages that were found, so that the arrows are aligned.
+pub fn print_finished_language(name: &str, final_stats: &FinalStats, is_first: bool, name_width: usize) {
+    if is_first {
+        outln!("\n{}.\n", labels::get().finished_languages.underline().bold());
+    }
+    outln!("{}", get_stats_line(&format!("{:width$}", name, width = name_width), final_stats));
+}
+
+fn get_stats_line(title: &str, final_stats: &FinalStats) -> String {
+    let labels = labels::get();
+    format!("{}   ->  {} {}  |  {} {} {{{} {} + {} {}}}  |  {}", title, with_seperators(final_stats.files),
+            labels.files, labels.lines, with_seperators(final_stats.lines), with_seperators(final_stats.code_lines), labels.code,
+            with_seperators(final_stats.extra_lines), labels.extra, format_size(final_stats.bytes_size))
+}
+
+// Used with '--docs', after the code results
+//
+// Documentation.
+//
+// Markdown           ->  12 files  |  lines 1,200  |  words 9,350  |  headings 85  |  code blocks 30
+// reStructuredText   ->   3 files  |  lines   400  |  words 2,100  |  headings 20  |  code blocks 4
+// Docs to code ratio: 0.15 (1,600 documentation lines for 10,500 code lines)
+pub fn print_documentation(content_info_map: &HashMap<String,LanguageContentInfo>, languages_metadata_map: &HashMap<String,LanguageMetadata>,
+        final_stats: &FinalStats)
+{
+    let labels = labels::get();
+    outln!("\n{}.\n", labels.documentation.underline().bold());
+
+    let mut names = languages_metadata_map.iter().filter(|(_, x)| x.files > 0).map(|(name, _)| name).collect::<Vec<_>>();
+    names.sort_by(|a, b| content_info_map[*b].lines.cmp(&content_info_map[*a].lines).then(a.cmp(b)));
+    let max_name_len = names.iter().map(|x| x.chars().count()).max().unwrap_or(0);
+    let max_files_len = names.iter().map(|x| with_seperators(languages_metadata_map[*x].files).len()).max().unwrap_or(0);
+    let max_lines_len = names.iter().map(|x| with_seperators(content_info_map[*x].lines).len()).max().unwrap_or(0);
+    let max_words_len = names.iter().map(|x| with_seperators(content_info_map[*x].prose.words).len()).max().unwrap_or(0);
+
+    let mut documentation_lines = 0;
+    for name in names {
+        let (content_info, metadata) = (&content_info_map[name], &languages_metadata_map[name]);
+        documentation_lines += content_info.lines;
+        outln!("{}{}   ->  {:>files_width$} {}  |  {} {:>lines_width$}  |  {} {:>words_width$}  |  {} {}  |  {} {}", name.bold(),
+                " ".repeat(max_name_len - name.chars().count()), with_seperators(metadata.files), colored_word(labels.files),
+                colored_word(labels.lines), with_seperators(content_info.lines), colored_word(labels.words), with_seperators(content_info.prose.words),
+                colored_word(labels.headings), with_seperators(content_info.prose.headings), colored_word(labels.code_blocks),
+                with_seperators(content_info.prose.code_blocks), files_width = max_files_len, lines_width = max_lines_len, words_width = max_words_len);
+    }
+
+    if final_stats.code_lines > 0 {
+        outln!("{} {:.2} ({} documentation lines for {} code lines)", "Docs to code ratio:".bold(),
+                documentation_lines as f64 / final_stats.code_lines as f64, with_seperators(documentation_lines), with_seperators(final_stats.code_lines));
+    }
+}
+
+// Used with '--dir-breakdown'
+//
+// Directories.
+//
+// /home/user/project   ->  120 files  |  lines 15,000 {10,000 code + 5,000 extra}  |  1.2 MBs total
+//   src                ->  100 files  |  lines 12,500 {8,800 code + 3,700 extra}  |  1.0 MBs total
+//   tests              ->   20 files  |  lines  2,500 {1,200 code + 1,300 extra}  |  200.0 KBs total
+pub fn print_dir_breakdown(rows: &[(usize, String, DirStats)], config: &Configuration) {
+    let labels = labels::get();
+    outln!("\n{}.\n", labels.directories.underline().bold());
+
+    let titles = rows.iter().map(|(level, name, _)| {
+        if *level == 0 {config.path_style.format(name)} else {format!("{}{}", "  ".repeat(*level), name)}
+    }).collect::<Vec<_>>();
+    let max_title_len = titles.iter().map(|x| x.chars().count()).max().unwrap_or(0);
+    let max_files_len = rows.iter().map(|(_, _, stats)| with_seperators(stats.files).len()).max().unwrap_or(0);
+    let max_lines_len = rows.iter().map(|(_, _, stats)| with_seperators(stats.lines).len()).max().unwrap_or(0);
+
+    for (title, (level, _, stats)) in titles.iter().zip(rows) {
+        let title = if *level == 0 {title.bold()} else {title.normal()};
+        let (size, size_desc) = get_size_and_formatted_size_text(stats.bytes, labels.total);
+        outln!("{}{}   ->  {:>files_width$} {}  |  {} {:>lines_width$} {{{} {} + {} {}}}  |  {:.precision$} {}", title,
+                " ".repeat(max_title_len - title.chars().count()), with_seperators(stats.files), colored_word(labels.files),
+                colored_word(labels.lines), with_seperators(stats.lines), with_seperators(stats.code_lines), labels.code,
+                with_seperators(stats.lines - stats.code_lines), labels.extra, size, size_desc, files_width = max_files_len,
+                lines_width = max_lines_len, precision = get_size_precision());
+    }
+}
+
+// The sections of the registered analyzers, e.g.
+//
+// Maintainability.
+//
+// Score         ->  B
+// Large files   ->  3
+pub fn print_report_sections(report_sections: &[ReportSection]) {
+    for section in report_sections {
+        outln!("\n{}.\n", section.title.underline().bold());
+        let max_key_len = section.entries.iter().map(|(key, _)| key.chars().count()).max().unwrap_or(0);
+        for (key, value) in section.entries.iter() {
+            outln!("{}{}   ->  {}", key.bold(), " ".repeat(max_key_len - key.chars().count()), value);
+        }
+    }
+}
+
+// Used with '--todos', after the report sections, e.g.
+//
+// TODOs.
+//
+// TODO (2)
+//   src/cache.rs:41  evict the old entries
+//   src/main.rs:7    split this
+//
+// FIXME (1)
+//   src/lib.rs:120   the loop below never ends
+// After everything else, unless they are in the json results. Without the section for the formats that are read by other
+// programs, e.g.
+//
+// Warnings.
+//
+// -- permission denied: '/project/secrets': Permission denied (os error 13)
+// -- unknown config key: 'colour' is not an option of the configuration files, it is ignored.
+pub fn print_warnings(warnings: &[RunWarning], config: &Configuration) {
+    if warnings.is_empty() || config.output == OutputFormat::Json {
+        return;
+    }
+    if config.output != OutputFormat::Text || config.is_quiet() {
+        for warning in warnings {
+            reporter::warning(config, &format!("Warning: {}: {}", warning.kind.as_str(), warning.message));
+        }
+        return;
+    }
+
+    outln!("\n{}.\n", labels::get().warnings.underline().bold());
+    for warning in warnings {
+        outln!("-- {}: {}", warning.kind.as_str().yellow(), warning.message);
+    }
+}
+
+pub fn print_todos(content_info_map: &HashMap<String,LanguageContentInfo>, config: &Configuration) {
+    let markers = config.get_todo_markers();
+    let groups = group_todos(content_info_map, &markers);
+    if groups.is_empty() {
+        return;
+    }
+
+    outln!("\n{}.", labels::get().todos.underline().bold());
+    for (marker, todos) in groups {
+        outln!("\n{} ({})", marker.bold(), todos.len());
+        let locations = todos.iter().map(|x| format!("{}:{}", config.path_style.format(&x.path.to_string_lossy()), x.line)).collect::<Vec<_>>();
+        let max_len = locations.iter().map(|x| x.chars().count()).max().unwrap_or(0);
+        for (location, todo) in locations.iter().zip(todos) {
+            let line = format!("  {}{}  {}", location, " ".repeat(max_len - location.chars().count()), todo.text);
+            outln!("{}", line.trim_end());
+        }
+    }
+}
+
+// The categories of '--keyword-categories', each with its sum and the languages that it comes from, the biggest first, e.g.
+//
+// Keyword categories.
+//
+//   types        135  (Rust: 120 , C: 15)
+//   concurrency   40  (Rust: 40)
+pub fn print_keyword_categories(categories_map: &BTreeMap<String,BTreeMap<String,usize>>) {
+    if categories_map.is_empty() {
+        return;
+    }
+
+    outln!("\n{}.\n", labels::get().keyword_categories.underline().bold());
+    for line in get_keyword_categories_lines(categories_map) {
+        outln!("{}", line);
+    }
+}
+
+fn get_keyword_categories_lines(categories_map: &BTreeMap<String,BTreeMap<String,usize>>) -> Vec<String> {
+    let mut categories = categories_map.iter().map(|(category, languages)| {
+        let mut languages = languages.iter().collect::<Vec<_>>();
+        languages.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        (category, languages.iter().map(|(_, x)| **x).sum::<usize>(), languages)
+    }).collect::<Vec<_>>();
+    categories.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+    let name_width = categories.iter().map(|(category, _, _)| category.chars().count()).max().unwrap_or(0);
+    let sum_width = categories.iter().map(|(_, sum, _)| with_seperators(*sum).chars().count()).max().unwrap_or(0);
+    categories.iter().map(|(category, sum, languages)| {
+        let languages = languages.iter().map(|(name, x)| format!("{}: {}", name, with_seperators(**x))).collect::<Vec<_>>().join(" , ");
+        format!("  {}{}  {:>sum_width$}  ({})", colored_word(category), " ".repeat(name_width - category.chars().count()), with_seperators(*sum),
+                languages, sum_width = sum_width)
+    }).collect()
+}
+
+// The markers without any items are left out. The items of each marker are sorted by path and line.
+fn group_todos<'a>(content_info_map: &'a HashMap<String,LanguageContentInfo>, markers: &'a [String]) -> Vec<(&'a str, Vec<&'a TodoItem>)> {
+    markers.iter().filter_map(|marker| {
+        let mut todos = content_info_map.values().flat_map(|x| x.todos.iter()).filter(|x| x.marker == *marker).collect::<Vec<_>>();
+        todos.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+        if todos.is_empty() {None} else {Some((marker.as_str(), todos))}
+    }).collect()
+}
+
+// The files of each language, that are listed with '--top-files'. Empty if it isn't used.
+pub fn take_file_summaries(content_info_map: &mut HashMap<String,LanguageContentInfo>) -> HashMap<String,Vec<FileSummary>> {
+    content_info_map.iter_mut().map(|(name, x)| (name.to_owned(), std::mem::take(&mut x.file_summaries))).collect()
+}
+
+// The files of all the languages, that are compared with '--duplicates'. Empty if it isn't used.
+pub fn take_file_hashes(content_info_map: &mut HashMap<String,LanguageContentInfo>) -> Vec<FileHash> {
+    content_info_map.values_mut().flat_map(|x| std::mem::take(&mut x.file_hashes)).collect()
+}
+
+// Used with '--duplicates', after the top files. The groups of identical files are ordered by the bytes that their extra copies
+// waste, the most first, and the files of a group by path. The empty files are all identical, so they are left out.
+//
+// Duplicate files.
+//
+// 3 copies  |  wasted: 2,400 lines, 80.0 KBs
+//   /home/user/project/third_party/lib.js
+//   /home/user/project/vendor/a/lib.js
+//   /home/user/project/vendor/b/lib.js
+//
+// 1 groups  |  wasted: 2,400 lines, 80.0 KBs
+pub fn print_duplicate_files(file_hashes: &[FileHash], config: &Configuration) {
+    if !config.duplicates {
+        return;
+    }
+    let labels = labels::get();
+    outln!("\n{}.\n", labels.duplicate_files.underline().bold());
+    outln!("{}", format_duplicate_files(&find_duplicate_groups(file_hashes), config));
+}
+
+// The files of each group, with the lines and the bytes of one of them
+fn find_duplicate_groups(file_hashes: &[FileHash]) -> Vec<(Vec<&FileSummary>, usize, usize)> {
+    let mut files_by_hash = HashMap::<&[u8; 32],Vec<&FileSummary>>::new();
+    for file_hash in file_hashes.iter().filter(|x| x.summary.bytes > 0) {
+        files_by_hash.entry(&file_hash.hash).or_default().push(&file_hash.summary);
+    }
+    let mut groups = files_by_hash.into_values().filter(|x| x.len() > 1).map(|mut files| {
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        let (lines, bytes) = (files[0].lines, files[0].bytes);
+        (files, lines, bytes)
+    }).collect::<Vec<_>>();
+    groups.sort_by(|(a, _, a_bytes), (b, _, b_bytes)| (b_bytes * (b.len() - 1)).cmp(&(a_bytes * (a.len() - 1))).then(a[0].path.cmp(&b[0].path)));
+    groups
+}
+
+fn format_duplicate_files(groups: &[(Vec<&FileSummary>, usize, usize)], config: &Configuration) -> String {
+    let labels = labels::get();
+    let mut text = String::new();
+    let (mut wasted_lines, mut wasted_bytes) = (0, 0);
+    for (files, lines, bytes) in groups {
+        let extra_copies = files.len() - 1;
+        wasted_lines += lines * extra_copies;
+        wasted_bytes += bytes * extra_copies;
+        text += &format!("{} {}  |  {}: {} {}, {}\n", files.len(), labels.copies, labels.wasted, with_seperators(lines * extra_copies), labels.lines,
+                format_size(bytes * extra_copies));
+        for file in files {
+            text += &format!("  {}\n", config.path_style.format(&file.path.to_string_lossy()));
+        }
+        text += "\n";
+    }
+    text += &format!("{} {}  |  {}: {} {}, {}", with_seperators(groups.len()), labels.groups, labels.wasted, with_seperators(wasted_lines), labels.lines,
+            format_size(wasted_bytes));
+    text
+}
+
+// The code lines of all the languages, that are compared with '--clones'. Empty if it isn't used.
+pub fn take_file_code_lines(content_info_map: &mut HashMap<String,LanguageContentInfo>) -> Vec<FileCodeLines> {
+    content_info_map.values_mut().flat_map(|x| std::mem::take(&mut x.file_code_lines)).collect()
+}
+
+// Used with '--clones', after the duplicate files. Only the longest blocks are listed, but the total is of all of them.
+//
+// Clones.
+//
+// 40 code lines
+//   /home/user/project/src/a.rs:10-62
+//   /home/user/project/src/b.rs:5-57
+//
+// total: 1  |  40 code lines
+pub fn print_clones(file_code_lines: &[FileCodeLines], config: &Configuration) {
+    let min_lines = match config.clones {
+        Some(x) => x,
+        None => return
+    };
+    let labels = labels::get();
+    outln!("\n{}.\n", labels.clones.underline().bold());
+    outln!("{}", format_clones(&clones::find_clones(file_code_lines, min_lines), config));
+}
+
+fn format_clones(clones: &[clones::CodeClone], config: &Configuration) -> String {
+    let labels = labels::get();
+    let mut text = String::new();
+    for clone in clones.iter().take(MAX_LISTED_CLONES) {
+        text += &format!("{} {}\n", with_seperators(clone.code_lines), labels.code_lines);
+        for location in [&clone.first, &clone.second] {
+            text += &format!("  {}:{}-{}\n", config.path_style.format(&location.path.to_string_lossy()), location.start_line, location.end_line);
+        }
+        text += "\n";
+    }
+    text += &format!("{}: {}  |  {} {}", labels.total, with_seperators(clones.len()), with_seperators(clones.iter().map(|x| x.code_lines).sum()),
+            labels.code_lines);
+    text
+}
+
+// Used with '--top-files', after the TODOs. The languages are listed by name, after all of them together if there are more than one.
+//
+// Top files.
+//
+// All languages
+//   Most lines:
+//        2,230  /home/user/project/src/lib.rs (Rust)
+//          950  /home/user/project/src/parser.c (C)
+//   Biggest size:
+//     98.1 KBs  /home/user/project/src/lib.rs (Rust)
+//     30.5 KBs  /home/user/project/src/parser.c (C)
+//
+// C
+//   ...
+pub fn print_top_files(file_summaries_map: &HashMap<String,Vec<FileSummary>>, config: &Configuration) {
+    let top = match config.top_files {
+        Some(x) => x,
+        None => return
+    };
+    let mut names = file_summaries_map.iter().filter(|(_, x)| !x.is_empty()).map(|(name, _)| name).collect::<Vec<_>>();
+    if names.is_empty() {
+        return;
+    }
+    names.sort();
+
+    let labels = labels::get();
+    outln!("\n{}.", labels.top_files.underline().bold());
+    if names.len() > 1 {
+        let files = names.iter().flat_map(|name| file_summaries_map[*name].iter().map(move |x| (name.as_str(), x))).collect::<Vec<_>>();
+        outln!("\n{}\n{}", labels.all_languages.bold(), format_top_files(&files, top, true, config));
+    }
+    for name in names {
+        let files = file_summaries_map[name].iter().map(|x| (name.as_str(), x)).collect::<Vec<_>>();
+        outln!("\n{}\n{}", name.bold(), format_top_files(&files, top, false, config));
+    }
+}
+
+// With '--list-only'. When quiet, only the paths are printed, so that they can be piped to other programs.
+pub fn print_discovered_files(discovered_files: &[DiscoveredFile], config: &Configuration) {
+    if config.is_quiet() {
+        discovered_files.iter().for_each(|x| outln!("{}", config.path_style.format(&x.path.to_string_lossy())));
+        return;
+    }
+    outln!("\n{}.\n", "Files to analyze".underline().bold());
+    if !discovered_files.is_empty() {
+        outln!("{}", format_discovered_files(discovered_files, config));
+    }
+    outln!("{} files  |  {}", with_seperators(discovered_files.len()), format_size(discovered_files.iter().map(|x| x.size as usize).sum()));
+}
+
+// Used with '--linguist-compare', instead of the results
+pub fn print_linguist_comparison(comparison: &linguist::LinguistComparison) {
+    outln!("\n{}.\n", "Linguist comparison".underline().bold());
+    outln!("{}", linguist::format_comparison(comparison));
+}
+
+// e.g. 'src/lib.rs    Rust (rs)    85.2 KBs'
+fn format_discovered_files(discovered_files: &[DiscoveredFile], config: &Configuration) -> String {
+    let rows = discovered_files.iter().map(|x| {
+        let language = if x.extension.is_empty() {x.language_name.clone()} else {format!("{} ({})", x.language_name, x.extension)};
+        (config.path_style.format(&x.path.to_string_lossy()), language, format_size(x.size as usize))
+    }).collect::<Vec<_>>();
+    let path_width = rows.iter().map(|(path, _, _)| path.chars().count()).max().unwrap_or(0);
+    let language_width = rows.iter().map(|(_, language, _)| language.chars().count()).max().unwrap_or(0);
+
+    let mut text = String::new();
+    for (path, language, size) in rows {
+        text += &format!("{:path_width$}    {:language_width$}    {}\n", path, language, size, path_width = path_width, language_width = language_width);
+    }
+    text
+}
+
+// Used with '--timings', at the end. The extensions are ordered by the time spent on them, which is the time of all the
+// parser threads added up.
+//
+// Timings.
+//
+//   By extension:
+//     rs        120 files    1.25 secs  (62.5%)
+//     c          40 files    720.00 ms  (36.0%)
+//                2 files    30.00 ms   (1.5%)
+//   Slowest files:
+//     410.50 ms  /home/user/project/src/generated.rs (Rust)
+//     ...
+pub fn print_parse_timings(parse_timings: &ParseTimings, config: &Configuration) {
+    if parse_timings.extensions.is_empty() {
+        return;
+    }
+    outln!("\n{}.\n\n{}", labels::get().timings.underline().bold(), format_parse_timings(parse_timings, config));
+}
+
+fn format_parse_timings(parse_timings: &ParseTimings, config: &Configuration) -> String {
+    let labels = labels::get();
+    let total_secs = parse_timings.total_duration().as_secs_f64();
+    let mut extensions = parse_timings.extensions.iter().collect::<Vec<_>>();
+    extensions.sort_by(|(a_name, (_, a)), (b_name, (_, b))| b.cmp(a).then(a_name.cmp(b_name)));
+
+    let rows = extensions.iter().map(|(extension, (files, duration))| {
+        let percentage = if total_secs == 0f64 {0f64} else {duration.as_secs_f64() / total_secs * 100f64};
+        (extension.as_str(), format!("{} {}", with_seperators(*files), labels.files), format_duration(*duration), format!("({:.1}%)", percentage))
+    }).collect::<Vec<_>>();
+    let extension_width = rows.iter().map(|(extension, _, _, _)| extension.chars().count()).max().unwrap_or(0);
+    let files_width = rows.iter().map(|(_, files, _, _)| files.chars().count()).max().unwrap_or(0);
+    let duration_width = rows.iter().map(|(_, _, duration, _)| duration.chars().count()).max().unwrap_or(0);
+
+    let mut text = format!("  {}:\n", labels.by_extension);
+    for (extension, files, duration, percentage) in rows {
+        text += &format!("    {:extension_width$}    {:>files_width$}    {:duration_width$}  {}\n", extension, files, duration, percentage,
+                extension_width = extension_width, files_width = files_width, duration_width = duration_width);
+    }
+
+    let durations = parse_timings.slowest_files.iter().map(|(_, _, duration)| format_duration(*duration)).collect::<Vec<_>>();
+    let max_len = durations.iter().map(|x| x.chars().count()).max().unwrap_or(0);
+    text += &format!("  {}:\n", labels.slowest_files);
+    for (duration, (path, language_name, _)) in durations.iter().zip(&parse_timings.slowest_files) {
+        text += &format!("    {:>width$}  {} ({})\n", duration, config.path_style.format(&path.to_string_lossy()), language_name, width = max_len);
+    }
+    text.trim_end().to_owned()
+}
+
+// With '--stats', e.g.
+// Performance.
+//
+//   Discovery : 120.00 ms
+//   Parsing   : 1.25 secs
+//   Speed     : 789 files/s | 223,684 lines/s | 1.2 MBs/s
+//   Threads   : 0 92% | 1 88% | 2 75%
+pub fn print_performance_stats(metrics: &Metrics) {
+    outln!("\n{}.\n\n{}", labels::get().performance.underline().bold(), format_performance_stats(metrics));
+}
+
+fn format_performance_stats(metrics: &Metrics) -> String {
+    let labels = labels::get();
+    let rows = [
+        (labels.discovery, format_duration(metrics.discovery_duration)),
+        (labels.parsing, format_duration(metrics.parse_duration)),
+        (labels.speed, format!("{} files/s | {} lines/s | {}/s", with_seperators(metrics.files_per_sec), with_seperators(metrics.lines_per_sec),
+                format_size(metrics.bytes_per_sec))),
+        (labels.threads, metrics.thread_utilizations.iter().enumerate().map(|(i, x)| format!("{} {:.0}%", i, x * 100f64))
+                .collect::<Vec<_>>().join(" | "))
+    ];
+    let width = rows.iter().map(|(label, _)| label.chars().count()).max().unwrap_or(0);
+    rows.iter().map(|(label, value)| format!("  {:width$} : {}", label, value, width = width)).collect::<Vec<_>>().join("\n")
+}
+
+// The line that ends the results, with the whole time of the run, e.g.
+// Analyzed 12,345 files (1.2M lines, 312.0 MBs) in 4.70 secs - 2,626 files/s, 255k lines/s
+pub fn format_footer(metrics: &Metrics, elapsed: Duration) -> String {
+    let text = format!("Analyzed {} files ({} lines, {}) in {}", with_seperators(metrics.files), format_compact_number(metrics.lines),
+            format_size(metrics.total_bytes), format_duration(elapsed));
+    // The speeds of the shorter runs aren't telling
+    if metrics.total_duration <= Duration::from_secs(1) {
+        return text;
+    }
+    format!("{} - {} files/s, {} lines/s", text, with_seperators(metrics.files_per_sec), format_compact_number(metrics.lines_per_sec))
+}
+
+// With a suffix from a thousand on, and a decimal below a hundred of it, e.g. '950', '12.3k', '255k' and '1.2M'
+fn format_compact_number(number: usize) -> String {
+    let (value, suffix) = match number {
+        x if x >= 1_000_000_000 => (x as f64 / 1e9, "B"),
+        x if x >= 1_000_000 => (x as f64 / 1e6, "M"),
+        x if x >= 1000 => (x as f64 / 1e3, "k"),
+        x => return x.to_string()
+    };
+    if value < 100f64 {format!("{:.1}{}", value, suffix)} else {format!("{:.0}{}", value, suffix)}
+}
+
+// In milliseconds below a second, e.g. '0.35 ms' and '1.25 secs'
+fn format_duration(duration: Duration) -> String {
+    if duration.as_millis() < 1000 {
+        format!("{:.2} ms", duration.as_secs_f64() * 1000f64)
+    } else {
+        format!("{:.2} secs", duration.as_secs_f64())
+    }
+}
+
+// The files with the most lines, followed by the biggest ones. The ties are ordered by path.
+fn format_top_files(files: &[(&str, &FileSummary)], top: usize, with_language: bool, config: &Configuration) -> String {
+    let labels = labels::get();
+    let mut text = String::new();
+    for (title, by_size) in [(labels.most_lines, false), (labels.biggest_size, true)] {
+        let mut sorted = files.to_vec();
+        sorted.sort_by(|(_, a), (_, b)| {
+            let (a_value, b_value) = if by_size {(a.bytes, b.bytes)} else {(a.lines, b.lines)};
+            b_value.cmp(&a_value).then(a.path.cmp(&b.path))
+        });
+        sorted.truncate(top);
+
+        let values = sorted.iter().map(|(_, x)| {
+            if by_size {
+                format_size(x.bytes)
+            } else {
+                with_seperators(x.lines)
+            }
+        }).collect::<Vec<_>>();
+        let max_len = values.iter().map(|x| x.chars().count()).max().unwrap_or(0);
+
+        text += &format!("  {}:\n", title);
+        for (value, (language, file)) in values.iter().zip(sorted) {
+            let language = if with_language {format!(" ({})", language)} else {String::new()};
+            text += &format!("    {:>width$}  {}{}\n", value, config.path_style.format(&file.path.to_string_lossy()), language, width = max_len);
+        }
+    }
+    text.trim_end().to_owned()
+}
+
+// Used with '--output prometheus', in place of the Details and the sum. There is a gauge for each stat, with a sample
+// for each language, e.g.
+//
+// # HELP mezura_files The number of files of each language.
+// # TYPE mezura_files gauge
+// mezura_files{language="Rust"} 20
+// ...
+// # HELP mezura_keywords The occurrences of each keyword of each language.
+// # TYPE mezura_keywords gauge
+// mezura_keywords{language="Rust",keyword="structs"} 37
+pub fn get_prometheus_metrics(sorted_languages: &[String], content_info_map: &HashMap<String,LanguageContentInfo>,
+        languages_metadata_map: &HashMap<String,LanguageMetadata>, config: &Configuration) -> String
+{
+    // The backslashes, double quotes and line feeds of a label value are escaped
+    fn escape(value: &str) -> String {
+        value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+    }
+
+    type GetValue = fn(&LanguageContentInfo, &LanguageMetadata) -> usize;
+    let gauges : [(&str, &str, GetValue); 5] = [
+        ("files", "The number of files of each language.", |_, metadata| metadata.files),
+        ("lines", "The lines of each language.", |content_info, _| content_info.lines),
+        ("code_lines", "The code lines of each language.", |content_info, _| content_info.code_lines),
+        ("extra_lines", "The extra lines (comments and blank lines) of each language.", |content_info, _| content_info.lines - content_info.code_lines),
+        ("bytes", "The size in bytes of the files of each language.", |_, metadata| metadata.bytes)
+    ];
+
+    let mut metrics = String::new();
+    for (name, help, get_value) in gauges.iter() {
+        metrics += &format!("# HELP mezura_{} {}\n# TYPE mezura_{} gauge\n", name, help, name);
+        for language in sorted_languages {
+            metrics += &format!("mezura_{}{{language=\"{}\"}} {}\n", name, escape(language),
+                    get_value(&content_info_map[language], &languages_metadata_map[language]));
+        }
+    }
+
+    if !config.no_keywords {
+        metrics += "# HELP mezura_keywords The occurrences of each keyword of each language.\n# TYPE mezura_keywords gauge\n";
+        for language in sorted_languages {
+            for (keyword, occurrences) in content_info_map[language].keyword_occurences.iter() {
+                metrics += &format!("mezura_keywords{{language=\"{}\",keyword=\"{}\"}} {}\n", escape(language), escape(keyword), occurrences);
+            }
+        }
+    }
+    metrics
+}
+
+// Used with '--output markdown', in place of the Details and the sum, e.g.
+//
+// ### Details
+//
+// | Language | Files | Lines | Code | Extra | Code % | Total size | Average size | Keywords |
+// | :-- | --: | --: | --: | --: | --: | --: | --: | :-- |
+// | Rust | 20 | 9,767 | 7,065 | 2,702 | 72.34% | 435.1 KBs | 21.8 KBs | enums: 11, structs: 37 |
+// | **Total** | ... |
+//
+// A 'Doc lines' column is added after 'Code %' if there are any documentation comments.
+fn get_markdown_tables(sorted_languages: &[String], content_info_map: &HashMap<String,LanguageContentInfo>,
+        languages_metadata_map: &HashMap<String,LanguageMetadata>, final_stats: &FinalStats, config: &Configuration) -> String
+{
+    fn format_keywords(keyword_occurences: &KeywordOccurences) -> String {
+        keyword_occurences.iter().map(|(name, x)| format!("{}: {}", name, with_seperators(*x))).collect::<Vec<_>>().join(", ")
+    }
+    fn format_row(title: &str, files: usize, lines: usize, code_lines: usize, doc_lines: Option<usize>, sizes: (String, String),
+            keywords: Option<String>) -> String
+    {
+        let code_lines_percentage = if lines > 0 {code_lines as f64 / lines as f64 * 100f64} else {0f64};
+        let mut row = format!("| {} | {} | {} | {} | {} | {:.2}% |", title, with_seperators(files), with_seperators(lines),
+                with_seperators(code_lines), with_seperators(lines - code_lines), code_lines_percentage);
+        if let Some(doc_lines) = doc_lines {
+            row += &format!(" {} |", with_seperators(doc_lines));
+        }
+        row += &format!(" {} | {} |", sizes.0, sizes.1);
+        if let Some(keywords) = keywords {
+            row += &format!(" {} |", keywords);
+        }
+        row + "\n"
+    }
+
+    let labels = labels::get();
+    let should_show_keywords = !config.no_keywords;
+    let should_show_doc_lines = get_doc_lines_sum(content_info_map) > 0;
+    let mut tables = format!("### {}\n\n| {} | {} | {} | {} | {} | {} % |", labels.details, labels.language,
+            labels::capitalized(labels.files), labels::capitalized(labels.lines), labels::capitalized(labels.code),
+            labels::capitalized(labels.extra), labels::capitalized(labels.code));
+    if should_show_doc_lines {
+        tables += &format!(" {} |", labels::capitalized(labels.doc_lines));
+    }
+    tables += &format!(" {} | {} |", labels.total_size, labels.average_size);
+    tables += if should_show_keywords {" Keywords |\n"} else {"\n"};
+    tables += "| :-- | --: | --: | --: | --: | --: | --: | --: |";
+    if should_show_doc_lines {
+        tables += " --: |";
+    }
+    tables += if should_show_keywords {" :-- |\n"} else {"\n"};
+
+    for lang_name in sorted_languages {
+        let (content_info, metadata) = (&content_info_map[lang_name], &languages_metadata_map[lang_name]);
+        let files_for_average = if config.ignore_empty_files {metadata.files.saturating_sub(content_info.blank_files())} else {metadata.files}.max(1);
+        let keywords = if should_show_keywords {Some(format_keywords(&content_info.keyword_occurences))} else {None};
+        let doc_lines = if should_show_doc_lines {Some(content_info.doc_lines)} else {None};
+        tables += &format_row(&lang_name.replace('|', "\\|"), metadata.files, content_info.lines, content_info.code_lines, doc_lines,
+                (format_size(metadata.bytes), format_size(metadata.bytes / files_for_average)), keywords);
+    }
+
+    if languages_metadata_map.len() > 1 {
+        let keywords = if should_show_keywords {Some(format_keywords(&create_keyword_sum_map(content_info_map)))} else {None};
+        let doc_lines = if should_show_doc_lines {Some(get_doc_lines_sum(content_info_map))} else {None};
+        tables += &format_row(&format!("**{}**", labels::capitalized(labels.total)), final_stats.files, final_stats.lines, final_stats.code_lines,
+                doc_lines, (format_size(final_stats.bytes_size), format_size(final_stats.bytes_average_size)), keywords);
+    }
+    tables
+}
 
 fn print_individually(sorted_languages: &[String], content_info_map: &HashMap<String,LanguageContentInfo>,
-     languages_metadata_map: &HashMap<String, LanguageMetadata>, biggest_prefix_standard_spaces: usize, should_print_keywords: bool)
+     languages_metadata_map: &HashMap<String, LanguageMetadata>, biggest_prefix_standard_spaces: usize, config: &Configuration)
 {
-    fn get_size_text(metadata: &LanguageMetadata) -> String {
-        let (size, size_desc) = get_size_and_formatted_size_text(metadata.bytes, "total");
+    fn get_size_text(metadata: &LanguageMetadata, content_info: &LanguageContentInfo, config: &Configuration) -> String {
+        let files_for_average = if config.ignore_empty_files {
+            metadata.files.saturating_sub(content_info.blank_files()).max(1)
+        } else {
+            // Zero for a language that is only embedded in the files of others
+            metadata.files.max(1)
+        };
+        let extra_text = get_empty_files_text(content_info.empty_files, content_info.whitespace_only_files) +
+                &get_doc_lines_text(content_info.doc_lines, content_info.lines) + &get_complexity_text(content_info.complexity, files_for_average, config) +
+                &get_logical_lines_text(content_info.logical_lines, config) +
+                &get_long_lines_text(content_info.long_lines, content_info.max_line_length, config.max_line_length);
+        if !config.shows_column(Column::Size) {
+            return extra_text.trim_start().to_owned();
+        }
+        let (size, size_desc) = get_size_and_formatted_size_text(metadata.bytes, labels::get().total);
         let (average_size, average_size_desc) = get_size_and_formatted_size_text(
-                metadata.bytes / metadata.files, "average");
+                metadata.bytes / files_for_average, labels::get().average);
 
-        format!("{:.1} {} - {:.1} {}",size, size_desc, average_size, average_size_desc)
+        format!("{:.precision$} {} - {:.precision$} {}{}",size, size_desc, average_size, average_size_desc, extra_text,
+                precision = get_size_precision())
     }
 
+    let should_print_keywords = !config.no_keywords && config.shows_column(Column::Keywords);
+
+    #[allow(clippy::too_many_arguments)]
     fn reconstruct_line(i: usize, max_line_stats_len: usize, titles_vec: &[String], lines_stats_vec: &[String],
-         lines_stats_len_vec: &[usize], size_stats_vec: &[String], keywords_stats_vec: &[String]) -> String
+         lines_stats_len_vec: &[usize], size_stats_vec: &[String], per_file_stats_vec: &[String], keywords_stats_vec: &[String]) -> String
     {
-        let spaces = max_line_stats_len+1 - lines_stats_len_vec[i];
-        let mut line = titles_vec[i].clone() + &lines_stats_vec[i] + &" ".repeat(spaces) + " |  " + &size_stats_vec[i];
-        //if run with --no-keywords
-        if !keywords_stats_vec.is_empty(){
+        let mut line = titles_vec[i].clone() + &lines_stats_vec[i];
+        if !size_stats_vec[i].is_empty() {
+            let spaces = max_line_stats_len+1 - lines_stats_len_vec[i];
+            line = line + &" ".repeat(spaces) + " |  " + &size_stats_vec[i];
+        }
+        if !per_file_stats_vec[i].is_empty() {
+            line = line + "\n" + &per_file_stats_vec[i];
+        }
+        //if run with --no-keywords, or if none of the keywords of the language is listed
+        if !keywords_stats_vec.is_empty() && !keywords_stats_vec[i].is_empty() {
             line = line + "\n" + &keywords_stats_vec[i];
         } 
         line
     }
 
-    println!("{}.\n", "Details".underline().bold());
+    let labels = labels::get();
+    outln!("{}.\n", labels.details.underline().bold());
     
-    let mut max_line_stats_len = STANDARD_LINE_STATS_LEN;
-    let (mut titles_vec, mut lines_stats_vec, mut lines_stats_len_vec, mut size_stats_vec,
-            mut keywords_stats_vec) = (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    let mut max_line_stats_len = 0;
+    let (mut titles_vec, mut lines_stats_vec, mut lines_stats_len_vec, mut size_stats_vec, mut per_file_stats_vec,
+            mut keywords_stats_vec, mut typical_stats_vec) = (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    let references = if config.typical_markers {
+        io_handler::parse_language_references(&String::from_utf8_lossy(&LANGUAGE_REFERENCES_BYTES))
+    } else {
+        HashMap::new()
+    };
 
     for lang_name in sorted_languages {
         let content_info = content_info_map.get(lang_name).unwrap();
         let metadata = languages_metadata_map.get(lang_name).unwrap();
 
-        let files_str = with_seperators(metadata.files);
-        let prefix_standard_spaces = lang_name.chars().count() + metadata.files.to_string().chars().count() +
-                 utils::num_of_seperators(metadata.files); 
-        let title = format!("{}   {}{} {}  -> ",lang_name.bold(), " ".repeat(biggest_prefix_standard_spaces - prefix_standard_spaces),
-                 files_str, colored_word("files"));
-        titles_vec.push(title);
-
-        let code_lines_percentage = if content_info.lines > 0 {content_info.code_lines as f64 / content_info.lines as f64 * 100f64} else {0f64};
-        let lines_str = with_seperators(content_info.lines);
-        let code_lines_str = with_seperators(content_info.code_lines);
-        let extra_lines_str = with_seperators(content_info.lines - content_info.code_lines);
-        let curr_line_stats_len = STANDARD_LINE_STATS_LEN + lines_str.len() + code_lines_str.len() + extra_lines_str.len();
+        titles_vec.push(get_row_title(lang_name, metadata.files, biggest_prefix_standard_spaces, config));
+
+        let lines_stats = get_lines_stats_text(content_info.lines, content_info.code_lines, config);
+        let curr_line_stats_len = get_printed_len(&lines_stats);
         lines_stats_len_vec.push(curr_line_stats_len); 
         if max_line_stats_len < curr_line_stats_len {
             max_line_stats_len = curr_line_stats_len;
         }
         
-        lines_stats_vec.push(format!("{} {} {{{} code ({:.2}%) + {} extra}}", colored_word("lines"), lines_str, code_lines_str,
-                 code_lines_percentage, extra_lines_str));
-        size_stats_vec.push(get_size_text(metadata));
+        lines_stats_vec.push(lines_stats);
+        size_stats_vec.push(get_size_text(metadata, content_info, config));
+        if config.shows_column(Column::Lines) || config.shows_column(Column::Code) {
+            per_file_stats_vec.push(get_per_file_text(&content_info.lines_distribution, biggest_prefix_standard_spaces, config));
+        } else {
+            per_file_stats_vec.push(String::new());
+        }
         
         if should_print_keywords {
-            keywords_stats_vec.push(get_keywords_as_str(&content_info.keyword_occurences, biggest_prefix_standard_spaces));
+            let code_lines = if config.keyword_density {Some(content_info.code_lines)} else {None};
+            let mut keywords_stats = get_keywords_as_str(&content_info.keyword_occurences, code_lines, biggest_prefix_standard_spaces, config);
+            if config.keyword_hotspots {
+                add_keyword_hotspots_line(&mut keywords_stats, &content_info.keyword_occurences, &content_info.keyword_hotspots,
+                        biggest_prefix_standard_spaces, config);
+            }
+            keywords_stats_vec.push(keywords_stats);
+        }
+        if let Some(reference) = references.get(&lang_name.to_lowercase()) {
+            typical_stats_vec.push(get_typical_stats_text(content_info, metadata, reference, biggest_prefix_standard_spaces));
+        } else {
+            typical_stats_vec.push(String::new());
         }
     }
 
-    for i in 0..lines_stats_vec.len() {
-        let line = reconstruct_line(i, max_line_stats_len, &titles_vec, &lines_stats_vec,
-                &lines_stats_len_vec, &size_stats_vec, &keywords_stats_vec);
+    // After the lines, so that the shares of the rows are aligned. A single row would have all of the total
+    if config.shows_column(Column::Share) && sorted_languages.len() > 1 {
+        let (total_lines, total_code_lines) = sorted_languages.iter().map(|x| &content_info_map[x])
+                .fold((0, 0), |(lines, code_lines), x| (lines + x.lines, code_lines + x.code_lines));
+        for (i, lang_name) in sorted_languages.iter().enumerate() {
+            let content_info = &content_info_map[lang_name];
+            let share_text = get_share_text(content_info.lines, content_info.code_lines, total_lines, total_code_lines);
+            lines_stats_vec[i] = if lines_stats_vec[i].is_empty() {share_text} else {
+                format!("{}{}  |  {}", lines_stats_vec[i], " ".repeat(max_line_stats_len - lines_stats_len_vec[i]), share_text)
+            };
+            lines_stats_len_vec[i] = get_printed_len(&lines_stats_vec[i]);
+        }
+        max_line_stats_len = lines_stats_len_vec.iter().copied().max().unwrap_or(0);
+    }
+
+    for (i, typical_stats) in typical_stats_vec.iter().enumerate() {
+        let mut line = reconstruct_line(i, max_line_stats_len, &titles_vec, &lines_stats_vec,
+                &lines_stats_len_vec, &size_stats_vec, &per_file_stats_vec, &keywords_stats_vec);
+        if !typical_stats.is_empty() {
+            line = line + "\n" + typical_stats;
+        }
                 
         if i == lines_stats_len_vec.len() - 1 {
-            println!("{}",line);
+            outln!("{}",line);
         } else {
-            println!("{}\n",line);
+            outln!("{}\n",line);
         }
     }
 }
 
 
-fn print_sum(content_info_map: &HashMap<String,LanguageContentInfo>, final_stats: &FinalStats, biggest_prefix_standard_spaces: usize,
-        should_print_keywords: bool) 
+fn print_sum(content_info_map: &HashMap<String,LanguageContentInfo>, final_stats: &FinalStats, file_categories_map: &BTreeMap<FileCategory,DirStats>,
+        metrics: &[(String, Option<f64>)], biggest_prefix_standard_spaces: usize, config: &Configuration) 
 {
-    let (total_files_str, total_lines_str, total_code_lines_str, total_extra_lines_str) = 
-            (with_seperators(final_stats.files),with_seperators(final_stats.lines),with_seperators(final_stats.code_lines), with_seperators(final_stats.extra_lines)); 
-
     let keywords_sum_map = create_keyword_sum_map(content_info_map);
-    let keywords_line = get_keywords_as_str(&keywords_sum_map, biggest_prefix_standard_spaces);
-
-    let spaces = biggest_prefix_standard_spaces - (5 + total_files_str.len());
-    let title = format!("{}   {}{} {}  -> ","Total".bold()," ".repeat(spaces),total_files_str,colored_word("files"));
-    let code_lines_percentage = if final_stats.lines > 0 {final_stats.code_lines as f64 / final_stats.lines as f64 * 100f64} else {0f64};
-    let size_text = format!("{} {} - {} {}",final_stats.size, colored_word(&format!("{} total", final_stats.size_measurement)),
-            final_stats.average_size,colored_word(&format!("{} average", final_stats.average_size_measurement)));
+    let code_lines = if config.keyword_density {Some(final_stats.code_lines)} else {None};
+    let mut keywords_line = get_keywords_as_str(&keywords_sum_map, code_lines, biggest_prefix_standard_spaces, config);
+    if config.keyword_hotspots {
+        let mut hotspots_sum_map = KeywordHotspots::new();
+        for (name, (num, path)) in content_info_map.values().flat_map(|x| x.keyword_hotspots.iter()) {
+            domain::add_keyword_hotspot(&mut hotspots_sum_map, name, *num, path);
+        }
+        add_keyword_hotspots_line(&mut keywords_line, &keywords_sum_map, &hotspots_sum_map, biggest_prefix_standard_spaces, config);
+    }
 
-    let line_len = STANDARD_LINE_STATS_LEN + total_files_str.len() + total_code_lines_str.len() + total_extra_lines_str.len() +
-            final_stats.size.to_string().len() + final_stats.average_size.to_string().len() + DASH_LINE_OFFSET;
-    println!("{} ","-".repeat(line_len));
+    let labels = labels::get();
+    let title = get_row_title(&labels::capitalized(labels.total), final_stats.files, biggest_prefix_standard_spaces, config);
+    let (mut empty_files, mut whitespace_only_files) = (0, 0);
+    content_info_map.values().for_each(|c| {empty_files += c.empty_files; whitespace_only_files += c.whitespace_only_files});
+    let extra_text = get_empty_files_text(empty_files, whitespace_only_files) + &get_doc_lines_text(get_doc_lines_sum(content_info_map), final_stats.lines) +
+            &get_logical_lines_text(content_info_map.values().map(|x| x.logical_lines).sum(), config) +
+            &get_long_lines_text(content_info_map.values().map(|x| x.long_lines).sum(), content_info_map.values().map(|x| x.max_line_length).max()
+                    .unwrap_or(0), config.max_line_length);
+    let size_text = if config.shows_column(Column::Size) {
+        format!("{} {} - {} {}{}",final_stats.size, colored_word(&format!("{} {}", final_stats.size_measurement, labels.total)),
+                final_stats.average_size,colored_word(&format!("{} {}", final_stats.average_size_measurement, labels.average)), extra_text)
+    } else {
+        extra_text.trim_start().to_owned()
+    };
 
-    let info = format!("{} {} {{{} code ({:.2}%) + {} extra}}  |  {}\n",colored_word("lines"), total_lines_str,total_code_lines_str,
-            code_lines_percentage, total_extra_lines_str, size_text);
+    let mut info = get_lines_stats_text(final_stats.lines, final_stats.code_lines, config);
+    if !size_text.is_empty() {
+        info += &format!("  |  {}", size_text);
+    }
+    // The line separates the total from the details above it
+    if !config.summary {
+        outln!("{} ", get_renderer(config.style).separator().repeat(get_printed_len(&title) + get_printed_len(&info)));
+    }
+    info += "\n";
+    if let Some(line) = get_file_categories_line(file_categories_map, biggest_prefix_standard_spaces) {
+        info += &format!("{}\n", line);
+    }
+    if let Some(line) = get_derived_metrics_line(metrics, biggest_prefix_standard_spaces) {
+        info += &format!("{}\n", line);
+    }
 
-    if should_print_keywords {
-        println!("{}", format!("{}{}{}\n",title,info,keywords_line));
+    if !config.no_keywords && !config.summary && config.shows_column(Column::Keywords) {
+        outln!("{}", format!("{}{}{}\n",title,info,keywords_line));
     } else {
-        println!("{}", format!("{}{}",title,info));
+        outln!("{}", format!("{}{}",title,info));
+    }
+}
+
+// The lines of each category, the most first, under the total, e.g.
+// "lines by category: code 12,000 (80.0%) , config 2,250 (15.0%) , markup 750 (5.0%)".
+// None if all the languages are of the same category.
+fn get_file_categories_line(file_categories_map: &BTreeMap<FileCategory,DirStats>, max_files_num_size: usize) -> Option<String> {
+    if file_categories_map.len() < 2 {
+        return None;
+    }
+
+    let total_lines = file_categories_map.values().map(|x| x.lines).sum::<usize>();
+    let mut categories = file_categories_map.iter().collect::<Vec<_>>();
+    categories.sort_by(|a, b| b.1.lines.cmp(&a.1.lines).then(a.0.cmp(b.0)));
+    let categories = categories.iter().map(|(category, stats)| {
+        let percentage = if total_lines == 0 {0f64} else {stats.lines as f64 / total_lines as f64 * 100f64};
+        format!("{} {} ({:.1}%)", colored_word(category.as_str()), with_seperators(stats.lines), percentage)
+    }).collect::<Vec<_>>();
+    Some(format!("{}{}: {}", " ".repeat(get_keyword_line_offset() + max_files_num_size), labels::get().lines_by_category,
+            categories.join(" , ")))
+}
+
+// The metrics of '--metrics' in the order they were defined, e.g. "metrics: extra_ratio 0.31 , test_density -",
+// where '-' is a metric without a value. None if there are no metrics.
+fn get_derived_metrics_line(metrics: &[(String, Option<f64>)], max_files_num_size: usize) -> Option<String> {
+    if metrics.is_empty() {
+        return None;
+    }
+
+    let mut items = metrics.iter().map(|(name, value)| format!("{} {}", colored_word(name), get_derived_metric_text(*value)))
+            .collect::<Vec<_>>();
+    items[0] = format!("{}: {}", labels::get().metrics, items[0]);
+    Some(join_wrapped(&items, get_keyword_line_offset() + max_files_num_size, *TERMINAL_WIDTH))
+}
+
+// Whole numbers without decimals, e.g. of 'files - 1', and the rest with 2
+fn get_derived_metric_text(value: Option<f64>) -> String {
+    match value {
+        Some(x) if x.fract() == 0.0 && x.abs() < 1e15 => format!("{}", x as i64),
+        Some(x) => format!("{:.2}", x),
+        None => "-".to_owned()
+    }
+}
+
+//                          KEYWORD CO-OCCURRENCE
+//
+// Rust
+//                 transmute   unsafe
+//   transmute             4        3
+//   unsafe                3       12
+fn print_keyword_co_occurrences(sorted_languages: &[String], content_info_map: &HashMap<String,LanguageContentInfo>) {
+    outln!("\n{}.", labels::get().keyword_co_occurrence.underline().bold());
+
+    for lang_name in sorted_languages {
+        let co_occurrences = &content_info_map.get(lang_name).unwrap().keyword_co_occurrences;
+        let mut keywords = co_occurrences.keys().filter(|(a,b)| a == b).map(|x| x.0.as_str()).collect::<Vec<_>>();
+        if keywords.is_empty() {continue;}
+        keywords.sort_unstable();
+
+        let column_width = keywords.iter().map(|x| x.chars().count()).max().unwrap_or(0) + 3;
+        let mut matrix = format!("\n{}\n  {}", lang_name.bold(), " ".repeat(column_width));
+        for keyword in &keywords {
+            matrix += &format!("{:>width$}", keyword, width = column_width);
+        }
+        for first in &keywords {
+            matrix += &format!("\n  {}{}", colored_word(first), " ".repeat(column_width - first.chars().count()));
+            for second in &keywords {
+                let pair = if first <= second {(first.to_string(), second.to_string())} else {(second.to_string(), first.to_string())};
+                let num = co_occurrences.get(&pair).copied().unwrap_or(0);
+                matrix += &format!("{:>width$}", with_seperators(num), width = column_width);
+            }
+        }
+        outln!("{}", matrix);
     }
+    outln!();
 }
 
 //                                    OVERVIEW
@@ -149,56 +1087,72 @@ fn print_sum(content_info_map: &HashMap<String,LanguageContentInfo>, final_stats
 fn print_visual_overview(sorted_language_vec: &mut Vec<String>, content_info_map: &mut HashMap<String, LanguageContentInfo>,
         languages_metadata_map: &mut HashMap<String, LanguageMetadata>, final_stats: &FinalStats, config: &Configuration) 
 {
-    fn make_cyan(str: &str) -> String {
-        str.cyan().to_string()
-    }
-    fn make_magenta(str: &str) -> String {
-        str.bright_magenta().to_string()
-    }
-    fn make_yellow(str: &str) -> String {
-        str.bright_yellow().to_string()
-    }
-    fn make_fourth_color(str: &str) -> String {
-        str.truecolor(106, 217, 189).to_string()
-    }
-    fn make_color_for_others(str: &str) -> String {
-        str.truecolor(215, 201, 240).to_string()
+    // A single language is not grouped as "others"
+    let top = config.top.unwrap_or(DEF_OVERVIEW_TOP);
+    if content_info_map.len() > top + 1 {
+        retain_most_relevant_and_add_others_field_for_rest(sorted_language_vec, content_info_map, languages_metadata_map, final_stats, top);
     }
 
-    if content_info_map.len() > 4 {
-        retain_most_relevant_and_add_others_field_for_rest(sorted_language_vec, content_info_map, languages_metadata_map, final_stats);
-    }
+    let labels = labels::get();
+    outln!("{}.\n", labels.overview.underline().bold());
 
-    println!("{}.\n", "Overview".underline().bold());
+    let colors = get_overview_colors(sorted_language_vec.len(), sorted_language_vec[sorted_language_vec.len()-1] == "others");
 
-    let color_func_vec : Vec<fn(&str) -> String> = {
-        if sorted_language_vec[sorted_language_vec.len()-1] == "others" {
-            vec![make_cyan, make_magenta, make_yellow, make_color_for_others]
-        } else {
-            vec![make_cyan, make_magenta, make_yellow, make_fourth_color]
-        }
-    };
+    let metrics = config.overview.iter().filter_map(|x| OVERVIEW_METRICS.iter().find(|(column, _, _)| column == x)).collect::<Vec<_>>();
+    // The colons are aligned, e.g. "Files:", "Lines:", "Size :"
+    let prefixes = metrics.iter().map(|(_, get_label, _)| get_label(labels)).collect::<Vec<_>>();
+    let max_prefix_len = prefixes.iter().map(|x| x.chars().count()).max().unwrap_or(0);
+    let prefixes = prefixes.iter().map(|x| format!("{}{}:", x, " ".repeat(max_prefix_len - x.chars().count()))).collect::<Vec<_>>();
 
-    let files_percentages = get_files_percentages(languages_metadata_map, sorted_language_vec);
-    let lines_percentages = get_lines_percentages(content_info_map, sorted_language_vec);
-    let sizes_percentages = get_sizes_percentages(languages_metadata_map, sorted_language_vec);
+    let lines = metrics.iter().zip(prefixes).map(|((_, _, get_value), prefix)| {
+        let values = sorted_language_vec.iter().map(|x| get_value(&content_info_map[x], &languages_metadata_map[x])).collect::<Vec<_>>();
+        create_overview_line(&prefix, &get_percentages(&values), sorted_language_vec, &colors, config)
+    }).collect::<Vec<_>>();
 
-    let files_verticals = if config.no_visual {vec![]} else{get_num_of_verticals(&files_percentages)};
-    let lines_verticals = if config.no_visual {vec![]} else{get_num_of_verticals(&lines_percentages)};
-    let size_verticals = if config.no_visual {vec![]} else{get_num_of_verticals(&sizes_percentages)};
+    outln!("{}\n", lines.join("\n\n"));
+}
 
-    let files_line = create_overview_line("Files:", &files_percentages, &files_verticals,
-            sorted_language_vec, &color_func_vec, config);
-    let lines_line = create_overview_line("Lines:", &lines_percentages, &lines_verticals,
-            sorted_language_vec, &color_func_vec, config);
-    let size_line = create_overview_line("Size :", &sizes_percentages, &size_verticals,
-            sorted_language_vec, &color_func_vec, config);
+// The metric of the snapshots of 'trend', the oldest first, e.g.
+//
+// Trend of lines.
+//
+// v1.0   2021-10-17   12,000    [-||||||||||||||||||||||||||||||||||||||||          -]
+// v2.0   2022-01-03   15,000    [-||||||||||||||||||||||||||||||||||||||||||||||||||-]
+pub fn print_trend(snapshots: &[history::Snapshot], metric: BadgeMetric) {
+    outln!("\n{}.\n", format!("Trend of {}", metric.label()).underline().bold());
+
+    let rows = snapshots.iter().map(|x| (x.name.as_str(), x.datetime.format("%Y-%m-%d").to_string(), history::get_metric_value(metric, &x.stats.total)))
+            .collect::<Vec<_>>();
+    let vertical = "|".color(get_overview_colors(1, false)[0]).to_string();
+    for line in create_trend_lines(&rows, *TERMINAL_WIDTH, &vertical) {
+        outln!("{}", line);
+    }
+    outln!();
+}
 
-    println!("{}\n\n{}\n\n{}\n",files_line, lines_line, size_line);
+// The bars are as long as their share of the biggest value, so that the growth can be seen at a glance
+fn create_trend_lines(rows: &[(&str, String, usize)], terminal_width: Option<usize>, vertical: &str) -> Vec<String> {
+    let name_width = rows.iter().map(|(name, _, _)| name.chars().count()).max().unwrap_or(0);
+    let values = rows.iter().map(|(_, _, value)| with_seperators(*value)).collect::<Vec<_>>();
+    let value_width = values.iter().map(|x| x.chars().count()).max().unwrap_or(0);
+    let max_value = rows.iter().map(|(_, _, value)| *value).max().unwrap_or(0);
+
+    // The name, the date and the value, with the spaces between them
+    let text_len = name_width + 3 + TREND_DATE_LEN + 3 + value_width;
+    let total_verticals = terminal_width.map_or(NUM_OF_VERTICALS,
+            |x| x.saturating_sub(text_len + VERTICALS_BLOCK_EXTRA_LEN).clamp(MIN_NUM_OF_VERTICALS, NUM_OF_VERTICALS));
+
+    rows.iter().zip(values).map(|((name, date, value), value_str)| {
+        let verticals = if max_value == 0 {0} else {(*value as f64 / max_value as f64 * total_verticals as f64).round() as usize};
+        // A value that is not 0 always shows
+        let verticals = if *value > 0 {verticals.max(1)} else {0};
+        format!("{:<name_width$}   {}   {:>value_width$}    [-{}{}-]", name, date, value_str, vertical.repeat(verticals),
+                " ".repeat(total_verticals - verticals), name_width = name_width, value_width = value_width)
+    }).collect()
 }
 
 fn print_comparison_to_previous_runs(final_stats: &FinalStats, log_content: &str, num_of_entries: usize, datetime_now: &DateTime<Local>) {
-    println!("\n{}.\n", "Progress".underline().bold());
+    outln!("\n{}.\n", labels::get().progress.underline().bold());
 
     let log_entries = parse_N_previous_entries(log_content, num_of_entries);
 
@@ -219,7 +1173,7 @@ fn print_comparison_to_previous_runs(final_stats: &FinalStats, log_content: &str
                 with_seperators(entry.stats.extra_lines), color_percentage(&difference_as_signed_percentage_str_of_usize(entry.stats.extra_lines, final_stats.extra_lines)),
         ));
     }
-    print!("{}", comparison_str);
+    out!("{}", comparison_str);
 
     fn color_percentage(percentage: &str) -> ColoredString {
         if percentage.starts_with('+') {
@@ -268,13 +1222,13 @@ fn difference_as_signed_percentage_str_of_f64(older: f64, newer: f64) -> String
 }
 
 #[derive(Debug)]
-struct LogEntry {
+pub(crate) struct LogEntry {
     name: Option<String>,
-    stats: FinalStats,
+    pub(crate) stats: FinalStats,
     datetime: DateTime<Local>,
 }
 
-fn parse_N_previous_entries(log_content: &str, n: usize) -> Vec<LogEntry> {
+pub(crate) fn parse_N_previous_entries(log_content: &str, n: usize) -> Vec<LogEntry> {
     let mut log_entries = Vec::with_capacity(15);
     let (mut files, mut lines, mut code_lines, mut extra_lines, mut bytes_size) = (0, 0, 0, 0, 0);
     let mut counter = 0;
@@ -321,25 +1275,107 @@ fn parse_N_previous_entries(log_content: &str, n: usize) -> Vec<LogEntry> {
     log_entries
 } 
 
-fn get_keywords_as_str(keyword_occurencies: &HashMap<String,usize>, max_files_num_size: usize) -> String {
-    let mut keyword_info = String::new();
-    if !keyword_occurencies.is_empty() {
-        let mut keyword_iter = keyword_occurencies.iter();
-        let first_keyword = keyword_iter.next().unwrap();
-        keyword_info.push_str(&format!("{}{}: {}"," ".repeat(KEYWORD_LINE_OFFSET + max_files_num_size),
-                colored_word(first_keyword.0),with_seperators(*first_keyword.1)));
-        for (keyword_name,occurancies) in keyword_iter {
-            keyword_info.push_str(&format!(" , {}: {}",colored_word(keyword_name),with_seperators(*occurancies)));
+// With '--keyword-density' the code lines are provided, to show the occurrences per 1000 of them as well, e.g. 'unsafe: 12 (3.4/kloc)'.
+// The keywords that don't fit in the width of the terminal are laid out in aligned columns, with the same indentation.
+fn get_keywords_as_str(keyword_occurencies: &KeywordOccurences, code_lines: Option<usize>, max_files_num_size: usize, config: &Configuration) -> String {
+    let get_density_text = |occurancies: usize| match code_lines {
+        Some(x) => format!(" ({:.1}/kloc)", occurancies as f64 * 1000f64 / x.max(1) as f64),
+        None => String::new()
+    };
+
+    let keywords = select_keywords(keyword_occurencies, config).into_iter().map(|(keyword_name, occurancies)| {
+        format!("{}: {}{}", colored_word(keyword_name), with_seperators(*occurancies), get_density_text(*occurancies))
+    }).collect::<Vec<_>>();
+    join_in_columns(&keywords, get_keyword_line_offset() + max_files_num_size, *TERMINAL_WIDTH)
+}
+
+// The items on a single line separated by ' , ' if they fit in the width, otherwise in as many columns as fit, each as wide
+// as the widest item, filled row by row, e.g.
+//   enums: 12      structs: 140   traits: 9
+//   unsafe: 3      where: 27
+fn join_in_columns(items: &[String], indentation: usize, width: Option<usize>) -> String {
+    let lens = items.iter().map(|x| get_printed_len(x)).collect::<Vec<_>>();
+    let single_line_len = indentation + lens.iter().sum::<usize>() + 3 * items.len().saturating_sub(1);
+    let width = match width {
+        Some(x) if single_line_len > x => x,
+        _ => return join_wrapped(items, indentation, None)
+    };
+    let column_width = lens.iter().max().unwrap_or(&0) + KEYWORD_COLUMNS_GAP;
+    let columns = ((width.saturating_sub(indentation) + KEYWORD_COLUMNS_GAP) / column_width).max(1);
+    let cells = items.iter().zip(&lens).collect::<Vec<_>>();
+    cells.chunks(columns).map(|row| {
+        let mut line = " ".repeat(indentation);
+        for (i, (item, len)) in row.iter().enumerate() {
+            line += item;
+            if i + 1 < row.len() {
+                line += &" ".repeat(column_width - **len);
+            }
+        }
+        line
+    }).collect::<Vec<_>>().join("\n")
+}
+
+// The keywords that are listed, by name, or the most occurring first with '--top-keywords'
+fn select_keywords<'a>(keyword_occurencies: &'a KeywordOccurences, config: &Configuration) -> Vec<(&'a String,&'a usize)> {
+    let mut keyword_occurencies = keyword_occurencies.iter().filter(|(_, x)| config.zero_keywords || **x != 0).collect::<Vec<_>>();
+    if let Some(top) = config.top_keywords {
+        // Stable, so the ties stay ordered by name
+        keyword_occurencies.sort_by(|a, b| b.1.cmp(a.1));
+        keyword_occurencies.truncate(top);
+    }
+    keyword_occurencies
+}
+
+// The file with the most occurrences of each listed keyword, in the same order, on the lines under the keywords,
+// e.g. 'hotspots: unsafe 12 in src/ffi.rs , TODO 8 in src/main.rs'
+fn add_keyword_hotspots_line(keywords_line: &mut String, keyword_occurencies: &KeywordOccurences, keyword_hotspots: &KeywordHotspots,
+        max_files_num_size: usize, config: &Configuration)
+{
+    let hotspots_line = get_keyword_hotspots_as_str(keyword_occurencies, keyword_hotspots, max_files_num_size, config);
+    if !keywords_line.is_empty() && !hotspots_line.is_empty() {
+        *keywords_line += &format!("\n{}", hotspots_line);
+    }
+}
+
+fn get_keyword_hotspots_as_str(keyword_occurencies: &KeywordOccurences, keyword_hotspots: &KeywordHotspots, max_files_num_size: usize,
+        config: &Configuration) -> String
+{
+    let mut hotspots = select_keywords(keyword_occurencies, config).into_iter()
+            .filter_map(|(name, _)| keyword_hotspots.get(name).map(|(num, path)| format!("{} {} in {}", colored_word(name),
+                    with_seperators(*num), config.path_style.format(&path.to_string_lossy()))))
+            .collect::<Vec<_>>();
+    if hotspots.is_empty() {
+        return String::new();
+    }
+    hotspots[0] = format!("{}: {}", colored_word(labels::get().hotspots), hotspots[0]);
+    join_wrapped(&hotspots, get_keyword_line_offset() + max_files_num_size, *TERMINAL_WIDTH)
+}
+
+// The items separated by ' , ' after the indentation. An item that would go past the width starts a new line with the same indentation.
+fn join_wrapped(items: &[String], indentation: usize, width: Option<usize>) -> String {
+    let mut text = String::new();
+    let mut line_len = indentation;
+    for (i, item) in items.iter().enumerate() {
+        let item_len = get_printed_len(item);
+        if i == 0 {
+            text += &" ".repeat(indentation);
+        } else if width.is_none_or(|x| line_len + 3 + item_len <= x) {
+            text += " , ";
+            line_len += 3;
+        } else {
+            text += &format!(" ,\n{}", " ".repeat(indentation));
+            line_len = indentation;
         }
+        text += item;
+        line_len += item_len;
     }
-    keyword_info
+    text
 }
 
-fn create_keyword_sum_map(content_info_map: &HashMap<String,LanguageContentInfo>) -> HashMap<String,usize> {
-    let mut collective_keywords_map : HashMap<String,usize> = HashMap::new();
+fn create_keyword_sum_map(content_info_map: &HashMap<String,LanguageContentInfo>) -> KeywordOccurences {
+    let mut collective_keywords_map = KeywordOccurences::new();
     for content_info in content_info_map.values() {
         for keyword in &content_info.keyword_occurences {
-            if *keyword.1 == 0 {continue;}
             if let Some(x) = collective_keywords_map.get_mut(keyword.0) {
                 *x += *keyword.1;
             } else {
@@ -352,12 +1388,202 @@ fn create_keyword_sum_map(content_info_map: &HashMap<String,LanguageContentInfo>
 }
 
 fn get_size_and_formatted_size_text(value: usize, suffix: &str) -> (f64,ColoredString) {
-    if value > 1000000 
-        {(value as f64 / 1000000f64, colored_word(&("MBs ".to_owned() + suffix)))}
-    else if value > 1000 
-        {(value as f64 / 1000f64, colored_word(&("KBs ".to_owned() + suffix)))}
-    else
-        {(value as f64, colored_word(&("Bytes ".to_owned() + suffix)))}
+    let (size, measurement) = get_size_and_measurement(value);
+    (size, colored_word(&format!("{} {}", measurement, suffix)))
+}
+
+// e.g. "per file -> lines: 120.5 average , 98 median  |  code: 80.2 average , 64 median". Empty if no files were parsed.
+// With '--ignore-empty-files', of the files that are not empty or whitespace-only, like the average size.
+fn get_per_file_text(lines_distribution: &LinesDistribution, max_files_num_size: usize, config: &Configuration) -> String {
+    let without_blank_files;
+    let lines_distribution = if config.ignore_empty_files {
+        without_blank_files = lines_distribution.without_blank_files();
+        &without_blank_files
+    } else {
+        lines_distribution
+    };
+    let labels = labels::get();
+    let stats = (lines_distribution.average_lines(), lines_distribution.median_lines(), lines_distribution.average_code_lines(),
+            lines_distribution.median_code_lines());
+    match stats {
+        (Some(average_lines), Some(median_lines), Some(average_code_lines), Some(median_code_lines)) =>
+            format!("{}{} -> {}: {:.1} {} , {} {}  |  {}: {:.1} {} , {} {}", " ".repeat(get_keyword_line_offset() + max_files_num_size),
+                    colored_word(labels.per_file), labels.lines, average_lines, labels.average, median_lines, labels.median,
+                    labels.code, average_code_lines, labels.average, median_code_lines, labels.median),
+        _ => String::new()
+    }
+}
+
+fn get_typical_stats_text(content_info: &LanguageContentInfo, metadata: &LanguageMetadata, reference: &LanguageReference,
+        max_files_num_size: usize) -> String 
+{
+    fn colored_marker(value: f64, typical: f64) -> ColoredString {
+        match get_typical_marker(value, typical) {
+            x @ "typical" => x.bright_green(),
+            x => x.yellow()
+        }
+    }
+
+    let extra_lines_percentage = if content_info.lines > 0 {
+        (content_info.lines - content_info.code_lines) as f64 / content_info.lines as f64 * 100f64
+    } else {
+        0f64
+    };
+    let lines_per_file = content_info.lines / metadata.files.max(1);
+
+    format!("{}{}: {:.1}% ({} {}%) , {}: {} ({} {})", " ".repeat(get_keyword_line_offset() + max_files_num_size),
+            colored_word("extra lines"), extra_lines_percentage,
+            colored_marker(extra_lines_percentage, reference.extra_lines_percentage), reference.extra_lines_percentage,
+            colored_word("lines per file"), with_seperators(lines_per_file),
+            colored_marker(lines_per_file as f64, reference.lines_per_file as f64), with_seperators(reference.lines_per_file))
+}
+
+fn get_typical_marker(value: f64, typical: f64) -> &'static str {
+    if value > typical * (1.0 + TYPICAL_TOLERANCE) {
+        "above typical"
+    } else if value < typical * (1.0 - TYPICAL_TOLERANCE) {
+        "below typical"
+    } else {
+        "typical"
+    }
+}
+
+fn get_empty_files_text(empty_files: usize, whitespace_only_files: usize) -> String {
+    if empty_files == 0 && whitespace_only_files == 0 {
+        return String::new();
+    }
+
+    format!("  ({} {}, {} {})", with_seperators(empty_files), colored_word(labels::get().empty),
+            with_seperators(whitespace_only_files), colored_word(labels::get().whitespace_only))
+}
+
+// e.g. "  (1,200 doc lines, 12.3%)", with the percentage of all the lines
+fn get_doc_lines_text(doc_lines: usize, lines: usize) -> String {
+    if doc_lines == 0 {
+        return String::new();
+    }
+
+    format!("  ({} {}, {:.1}%)", with_seperators(doc_lines), colored_word(labels::get().doc_lines),
+            doc_lines as f64 / lines.max(1) as f64 * 100f64)
+}
+
+// Zero for the languages without branch keywords, so nothing is shown for them
+fn get_complexity_text(complexity: usize, files: usize, config: &Configuration) -> String {
+    if !config.complexity || complexity == 0 {
+        return String::new();
+    }
+
+    format!("  ({:.1} {})", complexity as f64 / files.max(1) as f64, colored_word(labels::get().complexity_per_file))
+}
+
+// e.g. "  (12 lines over 120, longest 240)", only for the languages with lines longer than '--max-line-length'
+fn get_long_lines_text(long_lines: usize, max_line_length: usize, limit: usize) -> String {
+    if long_lines == 0 {
+        return String::new();
+    }
+
+    let labels = labels::get();
+    format!("  ({} {} {}, {} {})", with_seperators(long_lines), colored_word(labels.lines_over), with_seperators(limit),
+            colored_word(labels.longest), with_seperators(max_line_length))
+}
+
+// e.g. "  (950 logical lines)", only with '--logical-lines'
+fn get_logical_lines_text(logical_lines: usize, config: &Configuration) -> String {
+    if !config.logical_lines {
+        return String::new();
+    }
+
+    format!("  ({} {})", with_seperators(logical_lines), colored_word(labels::get().logical_lines))
+}
+
+fn get_doc_lines_sum(content_info_map: &HashMap<String,LanguageContentInfo>) -> usize {
+    content_info_map.values().map(|x| x.doc_lines).sum()
+}
+
+fn get_keyword_line_offset() -> usize {
+    KEYWORD_LINE_OFFSET + labels::get().files.chars().count()
+}
+
+// e.g. 'Rust    120 files  -> ', or 'Rust    -> ' without the files. The arrows are aligned to the widest title.
+fn get_row_title(name: &str, files: usize, biggest_prefix_standard_spaces: usize, config: &Configuration) -> String {
+    let name_len = name.chars().count();
+    if !config.shows_column(Column::Files) {
+        return format!("{}   {}-> ", name.bold(), " ".repeat(biggest_prefix_standard_spaces.saturating_sub(name_len)));
+    }
+    let files_str = with_seperators(files);
+    format!("{}   {}{} {}  -> ", name.bold(), " ".repeat(biggest_prefix_standard_spaces.saturating_sub(name_len + files_str.chars().count())),
+            files_str, colored_word(labels::get().files))
+}
+
+// The lines of a row of the Details or the sum, with the columns of '--columns', e.g.
+// 'lines 6,200 {4,900 code (79.03%) + 1,300 extra}', 'lines 6,200' or '4,900 code (79.03%)'
+fn get_lines_stats_text(lines: usize, code_lines: usize, config: &Configuration) -> String {
+    let labels = labels::get();
+    let mut parts = Vec::new();
+    if config.shows_column(Column::Code) {
+        let code_lines_percentage = if lines > 0 {code_lines as f64 / lines as f64 * 100f64} else {0f64};
+        parts.push(format!("{} {} ({:.2}%)", with_seperators(code_lines), labels.code, code_lines_percentage));
+    }
+    if config.shows_column(Column::Comments) {
+        parts.push(format!("{} {}", with_seperators(lines - code_lines), labels.extra));
+    }
+    let parts = parts.join(" + ");
+    if !config.shows_column(Column::Lines) {
+        return parts;
+    }
+
+    let lines_text = format!("{} {}", colored_word(labels.lines), with_seperators(lines));
+    if parts.is_empty() {lines_text} else {format!("{} {{{}}}", lines_text, parts)}
+}
+
+// The part of the total lines and code lines of a row of the Details, e.g. "share 62.50% lines , 75.00% code"
+fn get_share_text(lines: usize, code_lines: usize, total_lines: usize, total_code_lines: usize) -> String {
+    let labels = labels::get();
+    let percentage = |x: usize, total: usize| if total == 0 {0f64} else {x as f64 / total as f64 * 100f64};
+    format!("{} {:.2}% {} , {:.2}% {}", colored_word(labels.share), percentage(lines, total_lines), labels.lines,
+            percentage(code_lines, total_code_lines), labels.code)
+}
+
+pub fn set_out_file(file: File) {
+    *OUT_FILE.lock().unwrap() = Some(file);
+}
+
+// A failed write is not reported, the same as the failed prints of the terminal
+fn write_out(text: &str) {
+    match OUT_FILE.lock().unwrap().as_mut() {
+        Some(x) => {let _ = x.write_all(text.as_bytes());},
+        None => print!("{}", text)
+    }
+}
+
+// The number of columns of the terminal that the results are printed in. The COLUMNS variable is exported by some shells,
+// otherwise it is asked from the terminal with 'stty', which is not available on Windows.
+fn get_terminal_width() -> Option<usize> {
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    if let Some(x) = std::env::var("COLUMNS").ok().and_then(|x| x.trim().parse::<usize>().ok()).filter(|x| *x > 0) {
+        return Some(x);
+    }
+    let tty = File::open("/dev/tty").ok()?;
+    let output = std::process::Command::new("stty").arg("size").stdin(tty).stderr(std::process::Stdio::null()).output().ok()?;
+    // 'rows columns'
+    String::from_utf8_lossy(&output.stdout).split_whitespace().nth(1)?.parse::<usize>().ok().filter(|x| *x > 0)
+}
+
+// The number of characters that are printed, without the escape codes of the colors
+fn get_printed_len(s: &str) -> usize {
+    let mut is_escape_code = false;
+    s.chars().filter(|x| {
+        if *x == '\x1b' {
+            is_escape_code = true;
+        } else if is_escape_code {
+            is_escape_code = *x != 'm';
+        } else {
+            return true;
+        }
+        false
+    }).count()
 }
 
 fn colored_word(word: &str) -> ColoredString {
@@ -365,24 +1591,40 @@ fn colored_word(word: &str) -> ColoredString {
 }
 
 
-fn get_language_names_as_sorted_vec_according_to_how_much_they_appeared(
-   languages_metadata_map: &HashMap<String, LanguageMetadata>) -> Vec<String> 
+// Without a sort order, the languages are sorted according to how much they appeared (files and size)
+pub fn get_language_names_as_sorted_vec(content_info_map: &HashMap<String, LanguageContentInfo>,
+        languages_metadata_map: &HashMap<String, LanguageMetadata>, sort: Option<SortOrder>) -> Vec<String> 
 {
     let mut value_map = HashMap::<String,usize>::new();
     let mut sorted_languages_vec = Vec::new();
     for (ext_name,metadata) in languages_metadata_map.iter() {
-        value_map.insert(ext_name.to_owned(), metadata.files * 10 + metadata.bytes as usize);
+        let content_info = content_info_map.get(ext_name);
+        let value = match sort.map(|x| x.key) {
+            None => metadata.files * 10 + metadata.bytes,
+            Some(SortKey::Files) => metadata.files,
+            Some(SortKey::Lines) => content_info.map_or(0, |x| x.lines),
+            Some(SortKey::Code) => content_info.map_or(0, |x| x.code_lines),
+            Some(SortKey::Size) => metadata.bytes,
+            Some(SortKey::Name) => 0
+        };
+        value_map.insert(ext_name.to_owned(), value);
         sorted_languages_vec.push(ext_name.to_owned());
     }
 
+    let ascending = sort.is_some_and(|x| x.ascending);
     sorted_languages_vec.sort_by(|a,b| {
-        value_map.get(b).unwrap().cmp(value_map.get(a).unwrap())
+        let ordering = match sort.map(|x| x.key) {
+            Some(SortKey::Name) => a.to_lowercase().cmp(&b.to_lowercase()),
+            _ => value_map.get(a).unwrap().cmp(value_map.get(b).unwrap())
+        };
+        // The names break the ties, so that the order doesn't change between runs
+        (if ascending {ordering} else {ordering.reverse()}).then_with(|| a.cmp(b))
     });
 
     sorted_languages_vec
 }
 
-fn get_num_of_verticals(percentages: &[f64]) -> Vec<usize> {
+fn get_num_of_verticals(percentages: &[f64], total_verticals: usize) -> Vec<usize> {
     let mut verticals = Vec::<usize>::with_capacity(4);
     let mut sum = 0;
     for files_percent in percentages.iter(){
@@ -390,7 +1632,7 @@ fn get_num_of_verticals(percentages: &[f64]) -> Vec<usize> {
         if *files_percent == 0f64 {
             0
         } else {
-            let mut num_of_verticals = (files_percent/2.0).round() as usize;
+            let mut num_of_verticals = (files_percent * total_verticals as f64 / 100.0).round() as usize;
             if num_of_verticals == 0 {
                 num_of_verticals = 1;
             }
@@ -400,8 +1642,9 @@ fn get_num_of_verticals(percentages: &[f64]) -> Vec<usize> {
         sum += num_of_verticals;
     }
 
-    if sum != NUM_OF_VERTICALS {
-        normalize_to_NUM_OF_VERTICALS(&mut verticals, sum);
+    // An empty bar is left empty
+    if sum != total_verticals && sum != 0 {
+        normalize_to_NUM_OF_VERTICALS(&mut verticals, sum, total_verticals);
     }
 
     verticals
@@ -409,7 +1652,7 @@ fn get_num_of_verticals(percentages: &[f64]) -> Vec<usize> {
 
 // A not very precise attempt to normalize the sum of verticals to the proper number that should appear 
 // in the [-|||...|-] block, but is it good enough.
-fn normalize_to_NUM_OF_VERTICALS(verticals: &mut Vec<usize>, sum: usize) {
+fn normalize_to_NUM_OF_VERTICALS(verticals: &mut Vec<usize>, sum: usize, total_verticals: usize) {
     let mut sorted_verticals = Vec::new();
     for i in verticals.iter_mut() {
         sorted_verticals.push(i);
@@ -418,8 +1661,8 @@ fn normalize_to_NUM_OF_VERTICALS(verticals: &mut Vec<usize>, sum: usize) {
     let comparator = |a: &&mut usize,b: &&mut usize| b.cmp(a);
     sorted_verticals.sort_by(comparator);
 
-    let is_over = sum > NUM_OF_VERTICALS;
-    let mut difference = if is_over {sum - NUM_OF_VERTICALS} else {NUM_OF_VERTICALS - sum}; 
+    let is_over = sum > total_verticals;
+    let mut difference = if is_over {sum - total_verticals} else {total_verticals - sum}; 
 
     let same_num_of_verticals_indices = {
         let mut temp = Vec::new();
@@ -481,18 +1724,59 @@ fn normalize_to_NUM_OF_VERTICALS(verticals: &mut Vec<usize>, sum: usize) {
     }
 }
 
-fn create_overview_line(prefix: &str, percentages: &[f64], verticals: &[usize], languages_name: &[String],
-        color_func_vec: &[fn(&str) -> String], config: &Configuration) -> String 
-{
+// Draws the bars and the separators of the results with the characters of '--style'
+trait BarRenderer {
+    // The character of the i-th language in the bars without colors, that is also shown next to its name
+    fn plain_symbol(&self, i: usize) -> &'static str;
+    // The character of every language in the bars, when they are told apart by their colors
+    fn colored_symbol(&self) -> &'static str;
+    // Both are as long as the ascii ones, which VERTICALS_BLOCK_EXTRA_LEN counts
+    fn bar_start(&self) -> &'static str;
+    fn bar_end(&self) -> &'static str;
+    // Of the line between the details and the total
+    fn separator(&self) -> &'static str;
+}
+
+struct AsciiRenderer;
+struct UnicodeRenderer;
+
+impl BarRenderer for AsciiRenderer {
+    fn plain_symbol(&self, i: usize) -> &'static str {PLAIN_VERTICALS[i % PLAIN_VERTICALS.len()]}
+    fn colored_symbol(&self) -> &'static str {"|"}
+    fn bar_start(&self) -> &'static str {"[-"}
+    fn bar_end(&self) -> &'static str {"-]"}
+    fn separator(&self) -> &'static str {"-"}
+}
+
+impl BarRenderer for UnicodeRenderer {
+    fn plain_symbol(&self, i: usize) -> &'static str {PLAIN_BLOCKS[i % PLAIN_BLOCKS.len()]}
+    fn colored_symbol(&self) -> &'static str {"█"}
+    fn bar_start(&self) -> &'static str {"│ "}
+    fn bar_end(&self) -> &'static str {" │"}
+    fn separator(&self) -> &'static str {"─"}
+}
+
+fn get_renderer(style: config_manager::Style) -> &'static dyn BarRenderer {
+    match style {
+        config_manager::Style::Ascii => &AsciiRenderer,
+        config_manager::Style::Unicode => &UnicodeRenderer
+    }
+}
+
+fn create_overview_line(prefix: &str, percentages: &[f64], languages_name: &[String], colors: &[Color], config: &Configuration) -> String {
+    let is_plain = !config.uses_colors();
+    let renderer = get_renderer(config.style);
     let mut line = String::with_capacity(150);
     line.push_str(&format!("{}    ",prefix));
     for (i,percent) in percentages.iter().enumerate() {
         let str_perc = format!("{:.2}",percent);
-        line.push_str(&format!("{}{}% ", " ".repeat(5-str_perc.len()), str_perc));
+        line.push_str(&format!("{}{}% ", " ".repeat(5usize.saturating_sub(str_perc.len())), str_perc));
         if config.no_visual {
             line.push_str(&languages_name[i]);
+        } else if is_plain {
+            line.push_str(&format!("{} ({})", languages_name[i], renderer.plain_symbol(i)));
         } else {
-            line.push_str(&color_func_vec[i](&languages_name[i]));
+            line.push_str(&languages_name[i].color(colors[i]).to_string());
         }
         if i < percentages.len() - 1{
             line.push_str(" - ")
@@ -500,87 +1784,162 @@ fn create_overview_line(prefix: &str, percentages: &[f64], verticals: &[usize],
     }
     
     if !config.no_visual {
-        add_verticals_str(&mut line, verticals, color_func_vec);
+        let total_verticals = get_total_verticals(get_printed_len(&line), prefix.chars().count(), percentages.len(), *TERMINAL_WIDTH, &mut line);
+        add_verticals_str(&mut line, &get_num_of_verticals(percentages, total_verticals), colors, is_plain, renderer);
     }
 
     line
 }
 
-fn add_verticals_str(line: &mut String, files_verticals: &[usize], color_func_vec: &[fn(&str) -> String]) {
-    line.push_str("    [-");
+// The bar is as long as what is left of the width of the terminal after the text of the line, up to NUM_OF_VERTICALS.
+// If that is too short, the bar goes on the next line, below the percentages. Every language needs at least one vertical.
+fn get_total_verticals(line_len: usize, prefix_len: usize, languages_num: usize, terminal_width: Option<usize>, line: &mut String) -> usize {
+    let width = match terminal_width {
+        Some(x) => x,
+        None => return NUM_OF_VERTICALS
+    };
+    let mut total_verticals = width.saturating_sub(line_len + VERTICALS_BLOCK_EXTRA_LEN);
+    if total_verticals < MIN_NUM_OF_VERTICALS {
+        line.push('\n');
+        line.push_str(&" ".repeat(prefix_len));
+        total_verticals = width.saturating_sub(prefix_len + VERTICALS_BLOCK_EXTRA_LEN);
+    }
+    total_verticals.clamp(MIN_NUM_OF_VERTICALS.max(languages_num), NUM_OF_VERTICALS.max(languages_num))
+}
+
+fn add_verticals_str(line: &mut String, files_verticals: &[usize], colors: &[Color], is_plain: bool, renderer: &dyn BarRenderer) {
+    line.push_str("    ");
+    line.push_str(renderer.bar_start());
     for (i,verticals) in files_verticals.iter().enumerate() {
-        line.push_str(&color_func_vec[i]("|").repeat(*verticals));
+        let vertical = if is_plain {renderer.plain_symbol(i).to_owned()} else {renderer.colored_symbol().color(colors[i]).to_string()};
+        line.push_str(&vertical.repeat(*verticals));
     }
-    line.push_str("-]");
+    line.push_str(renderer.bar_end());
 }
 
 fn retain_most_relevant_and_add_others_field_for_rest(sorted_language_names: &mut Vec<String>,
         content_info_map: &mut HashMap<String, LanguageContentInfo>,
         languages_metadata_map: &mut HashMap<String, LanguageMetadata>,
-        final_stats: &FinalStats) 
+        final_stats: &FinalStats, top: usize) 
 {
     fn get_files_lines_size(content_info_map: &HashMap<String, LanguageContentInfo>,
-        languages_metadata_map: &HashMap<String, LanguageMetadata>) -> (usize,usize,usize) 
+        languages_metadata_map: &HashMap<String, LanguageMetadata>) -> (usize,usize,usize,usize) 
    {
-       let (mut files, mut lines, mut size) = (0,0,0);
-       content_info_map.iter().for_each(|x| lines += x.1.lines);
+       let (mut files, mut lines, mut code_lines, mut size) = (0,0,0,0);
+       content_info_map.iter().for_each(|x| {lines += x.1.lines; code_lines += x.1.code_lines});
        languages_metadata_map.iter().for_each(|x| {files += x.1.files; size += x.1.bytes});
-       (files, lines, size as usize) 
+       (files, lines, code_lines, size as usize) 
    }
 
-    if sorted_language_names.len() > 4 {
-        for _ in 3..sorted_language_names.len() {
-             sorted_language_names.remove(sorted_language_names.len()-1);
-        }
+    // The final stats have no keywords, so the ones of the languages that are left out are summed up here
+    let mut other_keywords = KeywordOccurences::new();
+    if sorted_language_names.len() > top + 1 {
+        sorted_language_names.truncate(top);
         sorted_language_names.push("others".to_owned());
 
+        for (_, content_info) in content_info_map.iter().filter(|(x, _)| !sorted_language_names.contains(x)) {
+            for (keyword, occurrences) in content_info.keyword_occurences.iter() {
+                *other_keywords.entry(keyword.clone()).or_insert(0) += occurrences;
+            }
+        }
         content_info_map.retain(|x,_| sorted_language_names.contains(x));
         languages_metadata_map.retain(|x,_| sorted_language_names.contains(x));
     }
     
-    let (relevant_files, relevant_lines, relevant_size) = get_files_lines_size(content_info_map, languages_metadata_map);
-    let (other_files, other_lines, other_size) = 
-        (final_stats.files - relevant_files, final_stats.lines - relevant_lines,
+    let (relevant_files, relevant_lines, relevant_code_lines, relevant_size) = get_files_lines_size(content_info_map, languages_metadata_map);
+    let (other_files, other_lines, other_code_lines, other_size) = 
+        (final_stats.files - relevant_files, final_stats.lines - relevant_lines, final_stats.code_lines - relevant_code_lines,
          final_stats.bytes_size - relevant_size);
 
-    //We only care about the total lines of code for the "others" field, this is the only field involved with calculations
-    content_info_map.insert("others".to_string(), LanguageContentInfo::dummy(other_lines));
+    // The "others" field only has the totals that the lines of the overview are made of
+    let mut others = LanguageContentInfo::dummy(other_lines);
+    others.code_lines = other_code_lines;
+    others.keyword_occurences = other_keywords;
+    content_info_map.insert("others".to_string(), others);
     languages_metadata_map.insert("others".to_string(), LanguageMetadata::new(other_files, other_size));
 }
 
 
-fn get_files_percentages(languages_metadata_map: &HashMap<String,LanguageMetadata>, sorted_language_names: &[String]) -> Vec<f64> {
-    let mut language_files = [0].repeat(languages_metadata_map.len());
-    languages_metadata_map.iter().for_each(|e| {
-        let pos = sorted_language_names.iter().position(|name| name == e.0).unwrap();
-        language_files[pos] = e.1.files;
-    });
-    
-    get_percentages(&language_files)
+// The languages after the first 'top' ones summed up as "others", without their keywords, since they differ per language.
+// None if there are less than 2 of them.
+fn group_rest_as_others(sorted_language_names: &[String], content_info_map: &HashMap<String, LanguageContentInfo>,
+        languages_metadata_map: &HashMap<String, LanguageMetadata>, top: usize) -> Option<GroupedLanguages>
+{
+    if sorted_language_names.len() <= top + 1 {
+        return None;
+    }
+
+    let (shown, rest) = sorted_language_names.split_at(top);
+    let mut grouped_content_info_map = shown.iter().map(|x| (x.to_owned(), content_info_map[x].clone())).collect::<HashMap<_,_>>();
+    let mut grouped_metadata_map = shown.iter().map(|x| (x.to_owned(), languages_metadata_map[x].clone())).collect::<HashMap<_,_>>();
+    let (mut others_content_info, mut others_metadata) = (LanguageContentInfo::dummy(0), LanguageMetadata::new(0, 0));
+    for name in rest {
+        others_content_info.add_content_info(&LanguageContentInfo {keyword_occurences: KeywordOccurences::new(), keyword_co_occurrences: HashMap::new(),
+                keyword_hotspots: KeywordHotspots::new(), todos: Vec::new(), ..content_info_map[name].clone()});
+        others_metadata.add_metadata(&languages_metadata_map[name]);
+    }
+    grouped_content_info_map.insert("others".to_owned(), others_content_info);
+    grouped_metadata_map.insert("others".to_owned(), others_metadata);
+
+    let mut names = shown.to_vec();
+    names.push("others".to_owned());
+    Some((names, grouped_content_info_map, grouped_metadata_map))
 }
 
-fn get_lines_percentages(content_info_map: &HashMap<String,LanguageContentInfo>, languages_name: &[String]) -> Vec<f64> {
-    let mut language_lines = [0].repeat(content_info_map.len());
-    content_info_map.iter().for_each(|e| {
-        let pos = languages_name.iter().position(|name| name == e.0).unwrap();
-        language_lines[pos] = e.1.lines;
-    });
+// The first colors are fixed and the rest are generated. The "others" are always last, in a pale color.
+fn get_overview_colors(len: usize, has_others: bool) -> Vec<Color> {
+    let individual = if has_others {len - 1} else {len};
+    let mut colors = (0..individual).map(|i| OVERVIEW_COLORS.get(i).copied().unwrap_or_else(|| get_generated_color(i))).collect::<Vec<_>>();
+    if has_others {
+        colors.push(OTHERS_COLOR);
+    }
+    colors
+}
 
-    get_percentages(&language_lines)
+// The hues are spread around the circle by the golden angle, so that the neighbouring languages differ
+fn get_generated_color(i: usize) -> Color {
+    let hue = (i as f64 * 137.5) % 360.0 / 60.0;
+    let (value, chroma) = (0.95, 0.95 * 0.55);
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as usize {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x)
+    };
+    let to_u8 = |c: f64| ((c + value - chroma) * 255.0).round() as u8;
+    Color::TrueColor {r: to_u8(r), g: to_u8(g), b: to_u8(b)}
 }
 
-fn get_sizes_percentages(languages_metadata_map: &HashMap<String,LanguageMetadata>, languages_name: &[String]) -> Vec<f64> {
-    let mut language_size = [0].repeat(languages_metadata_map.len());
-    languages_metadata_map.iter().for_each(|e| {
-        let pos = languages_name.iter().position(|name| name == e.0).unwrap();
-        language_size[pos] = e.1.bytes;
-    });
-    
-    get_percentages(&language_size)
+// With '--histogram', the files of all the languages by how many lines they have, e.g.
+// Lines per file:    62.50% 0-50 (|) - 25.00% 51-200 (=) - 12.50% 201-1000 (+) -  0.00% >1000 (:)    [-||||...-]
+// None if there are no parsed files.
+fn get_histogram_line(content_info_map: &HashMap<String,LanguageContentInfo>, config: &Configuration) -> Option<String> {
+    let percentages = get_histogram_percentages(content_info_map)?;
+    let names = HISTOGRAM_BUCKETS.iter().map(|(_, name)| name.to_string()).collect::<Vec<_>>();
+    let labels = labels::get();
+    let prefix = format!("{} {}:", labels::capitalized(labels.lines), labels.per_file);
+    Some(create_overview_line(&prefix, &percentages, &names, &get_overview_colors(names.len(), false), config))
+}
+
+fn get_histogram_percentages(content_info_map: &HashMap<String,LanguageContentInfo>) -> Option<Vec<f64>> {
+    let mut bucket_files = [0].repeat(HISTOGRAM_BUCKETS.len());
+    for lines in content_info_map.values().flat_map(|x| x.lines_distribution.lines.iter()) {
+        let pos = HISTOGRAM_BUCKETS.iter().position(|(max_lines, _)| lines <= max_lines).unwrap();
+        bucket_files[pos] += 1;
+    }
+
+    if bucket_files.iter().all(|x| *x == 0) {None} else {Some(get_percentages(&bucket_files))}
 }
 
 fn get_percentages(numbers: &[usize]) -> Vec<f64> {
     let total_files :usize = numbers.iter().sum();
+    // e.g. the keywords of languages that have none
+    if total_files == 0 {
+        return vec![0f64; numbers.len()];
+    }
     let mut language_percentages = Vec::with_capacity(4);
     let mut sum = 0.0;
     for (counter,files) in numbers.iter().enumerate() {
@@ -603,7 +1962,7 @@ fn get_percentages(numbers: &[usize]) -> Vec<f64> {
 
 fn get_biggest_prefix_standard_spaces(sorted_language_names: &[String], languages_metadata_map: &HashMap<String, LanguageMetadata>) -> usize {
     let longest_lang_name = sorted_language_names.iter().map(|x| x.chars().count()).max().unwrap();
-    let longest_lang_name = max(longest_lang_name,5);
+    let longest_lang_name = max(longest_lang_name, labels::capitalized(labels::get().total).chars().count());
     let total_files: usize = languages_metadata_map.iter().map(|meta| meta.1.files).sum();
     let total_files_digits = total_files.to_string().chars().count();
 
@@ -622,107 +1981,85 @@ mod tests {
     #[test]
     fn test_normalize() {
         let mut verticals = vec![18,15,19,1];
-        normalize_to_NUM_OF_VERTICALS(&mut verticals, 53);
+        normalize_to_NUM_OF_VERTICALS(&mut verticals, 53, NUM_OF_VERTICALS);
         assert_eq!(vec![16,15,18,1], verticals);
         assert!(verticals.iter().sum::<usize>() == 50);
         
         let mut verticals = vec![17,17,18,1];
-        normalize_to_NUM_OF_VERTICALS(&mut verticals, 53);
+        normalize_to_NUM_OF_VERTICALS(&mut verticals, 53, NUM_OF_VERTICALS);
         assert_eq!(vec![16,16,17,1], verticals);
         assert!(verticals.iter().sum::<usize>() == 50);
     
         let mut verticals = vec![16,15,16,1];
-        normalize_to_NUM_OF_VERTICALS(&mut verticals, 48);
+        normalize_to_NUM_OF_VERTICALS(&mut verticals, 48, NUM_OF_VERTICALS);
         assert_eq!(vec![17,15,17,1], verticals);
         assert!(verticals.iter().sum::<usize>() == 50);
     
         let mut verticals = vec![18,16,17];
-        normalize_to_NUM_OF_VERTICALS(&mut verticals, 51);
+        normalize_to_NUM_OF_VERTICALS(&mut verticals, 51, NUM_OF_VERTICALS);
         assert_eq!(vec![17,16,17], verticals);
         assert!(verticals.iter().sum::<usize>() == 50);
     
         let mut verticals = vec![25,26];
-        normalize_to_NUM_OF_VERTICALS(&mut verticals, 51);
+        normalize_to_NUM_OF_VERTICALS(&mut verticals, 51, NUM_OF_VERTICALS);
         assert_eq!(vec![25,25], verticals);
         assert!(verticals.iter().sum::<usize>() == 50);
     }
 
     #[test]
-    fn test_get_lines_percentages() {
-        let ext_names = ["py".to_string(),"java".to_string(),"cs".to_string()];
-
-        let content_info_map = hashmap!("cs".to_string() => LanguageContentInfo::dummy(100),
-            "java".to_string() => LanguageContentInfo::dummy(100), "py".to_string() => LanguageContentInfo::dummy(0));
-        assert_eq!(vec![0f64,50f64,50f64], get_lines_percentages(&content_info_map, &ext_names));
-        let content_info_map = hashmap!("cs".to_string() => LanguageContentInfo::dummy(0),
-        "java".to_string() => LanguageContentInfo::dummy(0), "py".to_string() => LanguageContentInfo::dummy(1));
-        assert_eq!(vec![100f64,0f64,0f64], get_lines_percentages(&content_info_map, &ext_names));
-        let content_info_map = hashmap!("cs".to_string() => LanguageContentInfo::dummy(20),
-        "java".to_string() => LanguageContentInfo::dummy(20), "py".to_string() => LanguageContentInfo::dummy(20));
-        assert_eq!(vec![33.33f64,33.33f64,33.34f64], get_lines_percentages(&content_info_map, &ext_names));
-        
-        let ext_names = ["py".to_string(),"java".to_string(),"cs".to_string(),"rs".to_string()];
+    fn test_get_percentages() {
+        assert_eq!(vec![0f64,50f64,50f64], get_percentages(&[0, 100, 100]));
+        assert_eq!(vec![100f64,0f64,0f64], get_percentages(&[1, 0, 0]));
+        assert_eq!(vec![33.33f64,33.33f64,33.34f64], get_percentages(&[20, 20, 20]));
 
-        let content_info_map = hashmap!("cs".to_string() => LanguageContentInfo::dummy(100),
-            "java".to_string() => LanguageContentInfo::dummy(100), "py".to_string() => LanguageContentInfo::dummy(0),
-            "rs".to_string() => LanguageContentInfo::dummy(0));
-        assert_eq!(vec![0f64,50f64,50f64,0f64], get_lines_percentages(&content_info_map, &ext_names));
-        let content_info_map = hashmap!("cs".to_string() => LanguageContentInfo::dummy(100),
-            "java".to_string() => LanguageContentInfo::dummy(100), "py".to_string() => LanguageContentInfo::dummy(100),
-            "rs".to_string() => LanguageContentInfo::dummy(0));
-        assert_eq!(vec![33.33,33.33,33.33,0.01], get_lines_percentages(&content_info_map, &ext_names));
-        let content_info_map = hashmap!("cs".to_string() => LanguageContentInfo::dummy(201),
-            "java".to_string() => LanguageContentInfo::dummy(200), "py".to_string() => LanguageContentInfo::dummy(200),
-            "rs".to_string() => LanguageContentInfo::dummy(0));
-        assert_eq!(vec![33.28,33.28,33.44,0.0], get_lines_percentages(&content_info_map, &ext_names));
+        assert_eq!(vec![0f64,50f64,50f64,0f64], get_percentages(&[0, 100, 100, 0]));
+        assert_eq!(vec![33.33,33.33,33.33,0.01], get_percentages(&[100, 100, 100, 0]));
+        assert_eq!(vec![33.28,33.28,33.44,0.0], get_percentages(&[200, 200, 201, 0]));
 
-        let ext_names = ["py".to_string(),"java".to_string(),"cs".to_string(),"rs".to_string(),"cpp".to_string()];
-
-        let content_info_map = hashmap!("cs".to_string() => LanguageContentInfo::dummy(100),
-            "java".to_string() => LanguageContentInfo::dummy(100), "py".to_string() => LanguageContentInfo::dummy(0),
-            "rs".to_string() => LanguageContentInfo::dummy(0), "cpp".to_string() => LanguageContentInfo::dummy(0));
-        assert_eq!(vec![0.0,50f64,50f64,0f64,0f64], get_lines_percentages(&content_info_map, &ext_names));
+        assert_eq!(vec![0.0,50f64,50f64,0f64,0f64], get_percentages(&[0, 100, 100, 0, 0]));
+        assert_eq!(vec![0f64,0f64], get_percentages(&[0, 0]));
     }
 
     #[test]
     fn test_get_num_of_verticals() {
         let percentages = vec![49.6,50.4];
-        let verticals = get_num_of_verticals(&percentages);
+        let verticals = get_num_of_verticals(&percentages, NUM_OF_VERTICALS);
         assert!(verticals.iter().sum::<usize>() == 50);
         assert_eq!(vec![25,25], verticals);
+        assert_eq!(vec![0,0], get_num_of_verticals(&[0.0, 0.0], NUM_OF_VERTICALS));
 
         let percentages = vec![0.0,100.0];
-        let verticals = get_num_of_verticals(&percentages);
+        let verticals = get_num_of_verticals(&percentages, NUM_OF_VERTICALS);
         assert!(verticals.iter().sum::<usize>() == 50);
         assert_eq!(vec![0,50], verticals);
 
 
         let percentages = vec![33.33,33.33,33.34];
-        assert_eq!(vec![16,17,17], get_num_of_verticals(&percentages));
+        assert_eq!(vec![16,17,17], get_num_of_verticals(&percentages, NUM_OF_VERTICALS));
 
         let percentages = vec![0.3,65.67,34.3];
-        let verticals = get_num_of_verticals(&percentages);
+        let verticals = get_num_of_verticals(&percentages, NUM_OF_VERTICALS);
         assert!(verticals.iter().sum::<usize>() == 50);
         assert_eq!(vec![1,32,17], verticals);
         
         let percentages = vec![0.0,0.0,100.0];
-        let verticals = get_num_of_verticals(&percentages);
+        let verticals = get_num_of_verticals(&percentages, NUM_OF_VERTICALS);
         assert!(verticals.iter().sum::<usize>() == 50);
         assert_eq!(vec![0,0,50], verticals);
 
         let percentages = vec![0.2,49.9,49.9];
-        let verticals = get_num_of_verticals(&percentages);
+        let verticals = get_num_of_verticals(&percentages, NUM_OF_VERTICALS);
         assert!(verticals.iter().sum::<usize>() == 50);
         assert_eq!(vec![1,24,25], verticals);
 
 
         let percentages = vec![12.5,50.0,25.0,12.5];
-        let verticals = get_num_of_verticals(&percentages);
+        let verticals = get_num_of_verticals(&percentages, NUM_OF_VERTICALS);
         assert!(verticals.iter().sum::<usize>() == 50);
         assert_eq!(vec![6,25,13,6], verticals);
 
         let percentages = vec![0.1,0.1,49.9,49.9];
-        let verticals = get_num_of_verticals(&percentages);
+        let verticals = get_num_of_verticals(&percentages, NUM_OF_VERTICALS);
         assert!(verticals.iter().sum::<usize>() == 50);
         assert_eq!(vec![1,1,24,24], verticals);
     }
@@ -746,13 +2083,13 @@ mod tests {
         ];
         let final_stats = FinalStats::new(40, 4000, 3000, 200000);
 
-        retain_most_relevant_and_add_others_field_for_rest(&mut sorted_language_names, &mut content_info_map, &mut languages_metadata_map, &final_stats);
+        retain_most_relevant_and_add_others_field_for_rest(&mut sorted_language_names, &mut content_info_map, &mut languages_metadata_map, &final_stats, 3);
 
         assert_eq!(hashmap![
             "a".to_owned() => LanguageContentInfo::new(1000, 800, hashmap![]),
             "b".to_owned() => LanguageContentInfo::new(900, 700, hashmap![]),
             "c".to_owned() => LanguageContentInfo::new(800, 600, hashmap![]),
-            "others".to_owned() => LanguageContentInfo::new(1300, 0, hashmap![])
+            "others".to_owned() => LanguageContentInfo::new(1300, 900, hashmap![])
             ], content_info_map);
         
         assert_eq!(hashmap![
@@ -763,6 +2100,441 @@ mod tests {
             ], languages_metadata_map);
     }
 
+    #[test]
+    fn test_group_rest_as_others() {
+        let sorted_language_names = vec!["a".to_owned(), "b".to_owned(), "c".to_owned()];
+        let content_info_map = hashmap![
+            "a".to_owned() => LanguageContentInfo::new(1000, 800, hashmap!["x".to_owned() => 1]),
+            "b".to_owned() => LanguageContentInfo::new(900, 700, hashmap!["y".to_owned() => 2]),
+            "c".to_owned() => LanguageContentInfo::new(800, 600, hashmap!["z".to_owned() => 3])
+        ];
+        let languages_metadata_map = hashmap![
+            "a".to_owned() => LanguageMetadata::new(10, 60000),
+            "b".to_owned() => LanguageMetadata::new(9, 50000),
+            "c".to_owned() => LanguageMetadata::new(8, 40000)
+        ];
+
+        let (names, grouped_content_info_map, grouped_metadata_map) =
+                group_rest_as_others(&sorted_language_names, &content_info_map, &languages_metadata_map, 1).unwrap();
+        assert_eq!(vec!["a".to_owned(), "others".to_owned()], names);
+        assert_eq!(content_info_map["a"], grouped_content_info_map["a"]);
+        assert_eq!((1700, 1300), (grouped_content_info_map["others"].lines, grouped_content_info_map["others"].code_lines));
+        assert!(grouped_content_info_map["others"].keyword_occurences.is_empty());
+        assert_eq!(LanguageMetadata::new(17, 90000), grouped_metadata_map["others"]);
+
+        assert!(group_rest_as_others(&sorted_language_names, &content_info_map, &languages_metadata_map, 2).is_none());
+    }
+
+    #[test]
+    fn test_get_histogram_percentages() {
+        let content_info = |lines: &[usize]| {
+            let mut content_info = LanguageContentInfo::dummy(lines.iter().sum());
+            lines.iter().for_each(|x| content_info.lines_distribution.add_file(*x, *x, *x == 0));
+            content_info
+        };
+        let content_info_map = hashmap!["Rust".to_owned() => content_info(&[0, 50, 51, 1500]), "C".to_owned() => content_info(&[1000])];
+        assert_eq!(Some(vec![40.0, 20.0, 20.0, 20.0]), get_histogram_percentages(&content_info_map));
+
+        let content_info_map = hashmap!["Rust".to_owned() => LanguageContentInfo::dummy(0)];
+        assert_eq!(None, get_histogram_percentages(&content_info_map));
+    }
+
+    #[test]
+    fn test_get_overview_colors() {
+        assert_eq!(OVERVIEW_COLORS[..3].iter().copied().chain(std::iter::once(OTHERS_COLOR)).collect::<Vec<_>>(), get_overview_colors(4, true));
+        let colors = get_overview_colors(8, false);
+        assert_eq!(OVERVIEW_COLORS.to_vec(), colors[..4]);
+        for (i, color) in colors.iter().enumerate() {
+            assert!(!colors[i + 1..].contains(color));
+        }
+    }
+
+    #[test]
+    fn test_get_language_names_as_sorted_vec() {
+        let content_info_map = hashmap!["Rust".to_owned() => LanguageContentInfo::new(100, 90, HashMap::new()),
+                "C".to_owned() => LanguageContentInfo::new(300, 150, HashMap::new()),
+                "Java".to_owned() => LanguageContentInfo::new(200, 160, HashMap::new())];
+        let languages_metadata_map = hashmap!["Rust".to_owned() => LanguageMetadata::new(5, 4_000),
+                "C".to_owned() => LanguageMetadata::new(2, 9_000), "Java".to_owned() => LanguageMetadata::new(2, 3_000)];
+        let sorted = |sort| get_language_names_as_sorted_vec(&content_info_map, &languages_metadata_map, sort);
+
+        assert_eq!(vec!["C", "Rust", "Java"], sorted(None));
+        assert_eq!(vec!["Rust", "C", "Java"], sorted(SortOrder::parse("files")));
+        assert_eq!(vec!["C", "Java", "Rust"], sorted(SortOrder::parse("files asc")));
+        assert_eq!(vec!["Java", "C", "Rust"], sorted(SortOrder::parse("code")));
+        assert_eq!(vec!["Rust", "Java", "C"], sorted(SortOrder::parse("lines asc")));
+        assert_eq!(vec!["C", "Java", "Rust"], sorted(SortOrder::parse("name")));
+        assert_eq!(vec!["Rust", "Java", "C"], sorted(SortOrder::parse("name desc")));
+
+        // The ties are ordered by name, whatever the order of the maps
+        for names in [["Go", "C", "Zig", "Ada"], ["Zig", "Ada", "Go", "C"]] {
+            let content_info_map = names.iter().map(|x| (x.to_string(), LanguageContentInfo::new(10, 5, HashMap::new()))).collect();
+            let languages_metadata_map = names.iter().map(|x| (x.to_string(), LanguageMetadata::new(1, 100))).collect();
+            assert_eq!(vec!["Ada", "C", "Go", "Zig"], get_language_names_as_sorted_vec(&content_info_map, &languages_metadata_map, None));
+            assert_eq!(vec!["Ada", "C", "Go", "Zig"], get_language_names_as_sorted_vec(&content_info_map, &languages_metadata_map,
+                    SortOrder::parse("files asc")));
+        }
+    }
+
+    #[test]
+    fn test_get_keywords_as_str() {
+        let mut config = Configuration::new(Vec::new());
+        let keyword_occurences = KeywordOccurences::from([("unsafe".to_owned(), 3)]);
+        assert!(get_keywords_as_str(&keyword_occurences, None, 0, &config).ends_with(": 3"));
+        assert!(get_keywords_as_str(&keyword_occurences, Some(2000), 0, &config).ends_with(": 3 (1.5/kloc)"));
+        assert!(get_keywords_as_str(&keyword_occurences, Some(0), 0, &config).ends_with(": 3 (3000.0/kloc)"));
+        assert!(get_keywords_as_str(&KeywordOccurences::new(), Some(2000), 0, &config).is_empty());
+
+        // The keywords are always in the same order, whatever the order they were counted in
+        let keyword_occurences = ["unsafe", "traits", "enums", "structs"].iter().map(|x| (x.to_string(), 1)).collect::<KeywordOccurences>();
+        assert_eq!("enums: 1 , structs: 1 , traits: 1 , unsafe: 1", get_keywords_as_str(&keyword_occurences, None, 0, &config).trim_start());
+
+        let keyword_occurences = KeywordOccurences::from([("enums".to_owned(), 2), ("structs".to_owned(), 0), ("traits".to_owned(), 5),
+                ("unsafe".to_owned(), 2)]);
+        assert_eq!("enums: 2 , traits: 5 , unsafe: 2", get_keywords_as_str(&keyword_occurences, None, 0, &config).trim_start());
+        config.set_top_keywords(Some(2));
+        assert_eq!("traits: 5 , enums: 2", get_keywords_as_str(&keyword_occurences, None, 0, &config).trim_start());
+        config.set_zero_keywords(true).set_top_keywords(None);
+        assert_eq!("enums: 2 , structs: 0 , traits: 5 , unsafe: 2", get_keywords_as_str(&keyword_occurences, None, 0, &config).trim_start());
+    }
+
+    #[test]
+    fn test_join_in_columns() {
+        let items = ["enums: 12", "structs: 140", "traits: 9", "unsafe: 3", "where: 27"].iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        assert_eq!("  enums: 12 , structs: 140 , traits: 9 , unsafe: 3 , where: 27", join_in_columns(&items, 2, None));
+        assert_eq!("  enums: 12 , structs: 140 , traits: 9 , unsafe: 3 , where: 27", join_in_columns(&items, 2, Some(80)));
+        assert_eq!("  enums: 12      structs: 140   traits: 9\n  unsafe: 3      where: 27", join_in_columns(&items, 2, Some(47)));
+        assert_eq!("  enums: 12\n  structs: 140\n  traits: 9\n  unsafe: 3\n  where: 27", join_in_columns(&items, 2, Some(10)));
+        assert_eq!("", join_in_columns(&[], 2, Some(10)));
+    }
+
+    #[test]
+    fn test_get_derived_metrics_line() {
+        assert_eq!(None, get_derived_metrics_line(&[], 0));
+        let metrics = [("extra_ratio".to_owned(), Some(0.3125)), ("per_test_line".to_owned(), None), ("rest".to_owned(), Some(-12.0))];
+        assert_eq!("metrics: extra_ratio 0.31 , per_test_line - , rest -12", get_derived_metrics_line(&metrics, 0).unwrap().trim_start());
+    }
+
+    #[test]
+    fn test_get_keyword_hotspots_as_str() {
+        let mut config = Configuration::new(Vec::new());
+        let keyword_occurences = KeywordOccurences::from([("TODO".to_owned(), 9), ("traits".to_owned(), 0), ("unsafe".to_owned(), 14)]);
+        let keyword_hotspots = KeywordHotspots::from([("TODO".to_owned(), (8, PathBuf::from("/p/main.rs"))),
+                ("unsafe".to_owned(), (12, PathBuf::from("/p/ffi.rs")))]);
+        assert_eq!("hotspots: TODO 8 in /p/main.rs , unsafe 12 in /p/ffi.rs",
+                get_keyword_hotspots_as_str(&keyword_occurences, &keyword_hotspots, 0, &config).trim_start());
+        config.set_top_keywords(Some(1));
+        assert_eq!("hotspots: unsafe 12 in /p/ffi.rs", get_keyword_hotspots_as_str(&keyword_occurences, &keyword_hotspots, 0, &config).trim_start());
+        assert!(get_keyword_hotspots_as_str(&keyword_occurences, &KeywordHotspots::new(), 0, &config).is_empty());
+    }
+
+    #[test]
+    fn test_join_wrapped() {
+        let items = ["enums: 1", "structs: 12", "traits: 3"].iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        assert_eq!("  enums: 1 , structs: 12 , traits: 3", join_wrapped(&items, 2, None));
+        assert_eq!("  enums: 1 , structs: 12 , traits: 3", join_wrapped(&items, 2, Some(36)));
+        assert_eq!("  enums: 1 , structs: 12 ,\n  traits: 3", join_wrapped(&items, 2, Some(35)));
+        assert_eq!("  enums: 1 ,\n  structs: 12 ,\n  traits: 3", join_wrapped(&items, 2, Some(10)));
+        assert!(join_wrapped(&[], 2, Some(10)).is_empty());
+    }
+
+    #[test]
+    fn test_get_total_verticals() {
+        let mut line = "Files:  50.00% Rust - 50.00% C".to_owned();
+        assert_eq!(NUM_OF_VERTICALS, get_total_verticals(line.len(), 6, 2, None, &mut line));
+        assert_eq!(NUM_OF_VERTICALS, get_total_verticals(line.len(), 6, 2, Some(200), &mut line));
+        assert_eq!(20, get_total_verticals(line.len(), 6, 2, Some(line.len() + 28), &mut line));
+        assert!(!line.contains('\n'));
+
+        // Too narrow for the bar, which goes on the next line
+        assert_eq!(22, get_total_verticals(line.len(), 6, 2, Some(36), &mut line));
+        assert!(line.ends_with("C\n      "));
+        // At least one vertical for every language
+        let mut line = String::new();
+        assert_eq!(12, get_total_verticals(0, 0, 12, Some(15), &mut line));
+    }
+
+    #[test]
+    fn test_create_trend_lines() {
+        let rows = [("v1.0", "2021-10-17".to_owned(), 12_000), ("v2.0-rc", "2022-01-03".to_owned(), 15_000), ("empty", "2022-02-01".to_owned(), 0)];
+        assert_eq!(vec![
+                format!("v1.0      2021-10-17   12,000    [-{}{}-]", "|".repeat(40), " ".repeat(10)),
+                format!("v2.0-rc   2022-01-03   15,000    [-{}-]", "|".repeat(50)),
+                format!("empty     2022-02-01        0    [-{}-]", " ".repeat(50))],
+                create_trend_lines(&rows, None, "|"));
+
+        // Shortened to what is left of the terminal
+        let lines = create_trend_lines(&rows, Some(60), "|");
+        assert!(lines.iter().all(|x| x.chars().count() == 60));
+        assert!(lines[1].ends_with(&format!("[-{}-]", "|".repeat(23))));
+        let lines = create_trend_lines(&[("a", "2022-01-03".to_owned(), 1), ("b", "2022-01-04".to_owned(), 1000)], Some(20), "|");
+        assert_eq!(format!("a   2022-01-03       1    [-|{}-]", " ".repeat(MIN_NUM_OF_VERTICALS - 1)), lines[0]);
+    }
+
+    #[test]
+    fn test_get_keyword_categories_lines() {
+        let categories_map = BTreeMap::from([("concurrency".to_owned(), BTreeMap::from([("Rust".to_owned(), 40)])),
+                ("types".to_owned(), BTreeMap::from([("C".to_owned(), 15), ("Rust".to_owned(), 1_120), ("Go".to_owned(), 15)]))]);
+        assert_eq!(vec!["  types        1,150  (Rust: 1,120 , C: 15 , Go: 15)", "  concurrency     40  (Rust: 40)"],
+                get_keyword_categories_lines(&categories_map));
+    }
+
+    #[test]
+    fn test_get_file_categories_line() {
+        let stats = |lines: usize| DirStats {files: 1, lines, code_lines: lines, bytes: 10};
+        let mut categories_map = BTreeMap::from([(FileCategory::Code, stats(12_000))]);
+        assert_eq!(None, get_file_categories_line(&categories_map, 0));
+
+        categories_map.insert(FileCategory::Markup, stats(750));
+        categories_map.insert(FileCategory::Config, stats(2_250));
+        let line = get_file_categories_line(&categories_map, 0).unwrap();
+        assert_eq!("lines by category: code 12,000 (80.0%) , config 2,250 (15.0%) , markup 750 (5.0%)", line.trim_start());
+    }
+
+    #[test]
+    fn test_group_todos() {
+        let todo = |marker: &str, path: &str, line: usize| TodoItem {marker: marker.to_owned(), path: PathBuf::from(path), line, text: String::new()};
+        let mut rust = LanguageContentInfo::dummy(100);
+        rust.todos = vec![todo("TODO", "b.rs", 3), todo("FIXME", "a.rs", 9), todo("TODO", "a.rs", 12)];
+        let mut c = LanguageContentInfo::dummy(50);
+        c.todos = vec![todo("TODO", "a.c", 40)];
+        let content_info_map = hashmap!["Rust".to_owned() => rust, "C".to_owned() => c];
+
+        let markers = vec!["FIXME".to_owned(), "HACK".to_owned(), "TODO".to_owned()];
+        let groups = group_todos(&content_info_map, &markers);
+        assert_eq!(vec!["FIXME", "TODO"], groups.iter().map(|x| x.0).collect::<Vec<_>>());
+        assert_eq!(vec![&todo("TODO", "a.c", 40), &todo("TODO", "a.rs", 12), &todo("TODO", "b.rs", 3)], groups[1].1);
+        assert!(group_todos(&content_info_map, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_format_discovered_files() {
+        let discovered_files = vec![DiscoveredFile {path: PathBuf::from("/a/src/main.rs"), extension: "rs".to_owned(), language_name: "Rust".to_owned(), size: 2500},
+                DiscoveredFile {path: PathBuf::from("/a/Makefile"), extension: String::new(), language_name: "Makefile".to_owned(), size: 300}];
+
+        assert_eq!("/a/src/main.rs    Rust (rs)    2.5 KBs\n\
+                    /a/Makefile       Makefile     300.0 Bytes\n",
+                format_discovered_files(&discovered_files, &Configuration::new(vec![])));
+    }
+
+    #[test]
+    fn test_format_parse_timings() {
+        let mut parse_timings = ParseTimings::default();
+        parse_timings.add_file(Path::new("/p/a.rs"), "Rust", Duration::from_millis(1250));
+        parse_timings.add_file(Path::new("/p/b.rs"), "Rust", Duration::from_millis(500));
+        parse_timings.add_file(Path::new("/p/Makefile"), "Makefile", Duration::from_millis(250));
+
+        assert_eq!("  By extension:\n\
+                    \x20   rs    2 files    1.75 secs  (87.5%)\n\
+                    \x20         1 files    250.00 ms  (12.5%)\n\
+                    \x20 Slowest files:\n\
+                    \x20   1.25 secs  /p/a.rs (Rust)\n\
+                    \x20   500.00 ms  /p/b.rs (Rust)\n\
+                    \x20   250.00 ms  /p/Makefile (Makefile)", format_parse_timings(&parse_timings, &Configuration::new(vec![])));
+    }
+
+    #[test]
+    fn test_format_performance_stats() {
+        let mut metrics = Metrics::from_durations(120, 34_000, 1_125, vec![Duration::from_millis(1250)]);
+        metrics.discovery_duration = Duration::from_millis(120);
+        metrics.parse_duration = Duration::from_millis(1130);
+        metrics.thread_utilizations = vec![0.92, 0.875];
+
+        assert_eq!("  Discovery : 120.00 ms\n\
+                    \x20 Parsing   : 1.13 secs\n\
+                    \x20 Speed     : 96 files/s | 27,200 lines/s | 900.0 Bytes/s\n\
+                    \x20 Threads   : 0 92% | 1 88%", format_performance_stats(&metrics));
+    }
+
+    #[test]
+    fn test_format_footer() {
+        let metrics = Metrics::from_durations(12_345, 1_200_000, 1_125, vec![Duration::from_millis(4700)]);
+        assert_eq!("Analyzed 12,345 files (1.2M lines, 1.1 KBs) in 4.80 secs - 2,626 files/s, 255k lines/s",
+                format_footer(&metrics, Duration::from_millis(4800)));
+        let metrics = Metrics::from_durations(3, 950, 0, vec![Duration::from_millis(40)]);
+        assert_eq!("Analyzed 3 files (950 lines, 0.0 Bytes) in 50.00 ms", format_footer(&metrics, Duration::from_millis(50)));
+        assert_eq!("12.3k", format_compact_number(12_345));
+        assert_eq!("2.0B", format_compact_number(1_999_999_999));
+    }
+
+    #[test]
+    fn test_format_top_files() {
+        let file = |path: &str, lines: usize, bytes: usize| FileSummary {path: PathBuf::from(path), lines, bytes};
+        let (a, b, c) = (file("/p/a.rs", 1200, 900), file("/p/b.rs", 80, 2048), file("/p/c.c", 80, 100));
+        let files = vec![("Rust", &a), ("Rust", &b), ("C", &c)];
+        let config = Configuration::new(vec![]);
+
+        assert_eq!("  Most lines:\n    1,200  /p/a.rs (Rust)\n       80  /p/b.rs (Rust)\n  Biggest size:\n        \
+                2.0 KBs  /p/b.rs (Rust)\n    900.0 Bytes  /p/a.rs (Rust)", format_top_files(&files, 2, true, &config));
+        assert_eq!("  Most lines:\n    80  /p/c.c\n  Biggest size:\n    100.0 Bytes  /p/c.c", format_top_files(&files[2..], 5, false, &config));
+    }
+
+    #[test]
+    fn test_format_duplicate_files() {
+        let file = |path: &str, hash: u8, lines: usize, bytes: usize| FileHash {hash: [hash; 32], summary: FileSummary {path: PathBuf::from(path), lines, bytes}};
+        let file_hashes = vec![file("/p/vendor/b/lib.js", 1, 1200, 40_000), file("/p/a.rs", 2, 50, 3000), file("/p/vendor/a/lib.js", 1, 1200, 40_000),
+                file("/p/b.rs", 2, 50, 3000), file("/p/c.rs", 3, 50, 3000), file("/p/vendor/c/lib.js", 1, 1200, 40_000),
+                file("/p/empty_a.rs", 4, 0, 0), file("/p/empty_b.rs", 4, 0, 0)];
+        let config = Configuration::new(vec![]);
+
+        assert_eq!("3 copies  |  wasted: 2,400 lines, 80.0 KBs\n  /p/vendor/a/lib.js\n  /p/vendor/b/lib.js\n  /p/vendor/c/lib.js\n\n\
+                2 copies  |  wasted: 50 lines, 3.0 KBs\n  /p/a.rs\n  /p/b.rs\n\n\
+                2 groups  |  wasted: 2,450 lines, 83.0 KBs", format_duplicate_files(&find_duplicate_groups(&file_hashes), &config));
+        assert_eq!("0 groups  |  wasted: 0 lines, 0.0 Bytes", format_duplicate_files(&find_duplicate_groups(&file_hashes[4..]), &config));
+    }
+
+    #[test]
+    fn test_format_clones() {
+        let location = |path: &str, start_line: usize, end_line: usize| clones::CloneLocation {path: PathBuf::from(path), start_line, end_line};
+        let clones = vec![clones::CodeClone {code_lines: 1200, first: location("/p/a.rs", 10, 1500), second: location("/p/b.rs", 5, 1495)},
+                clones::CodeClone {code_lines: 6, first: location("/p/a.rs", 2000, 2008), second: location("/p/c.rs", 1, 6)}];
+        let config = Configuration::new(vec![]);
+
+        assert_eq!("1,200 code lines\n  /p/a.rs:10-1500\n  /p/b.rs:5-1495\n\n6 code lines\n  /p/a.rs:2000-2008\n  /p/c.rs:1-6\n\ntotal: 2  |  1,206 code lines",
+                format_clones(&clones, &config));
+        assert_eq!("total: 0  |  0 code lines", format_clones(&[], &config));
+    }
+
+    #[test]
+    fn test_get_markdown_tables() {
+        let content_info_map = hashmap!["Rust".to_owned() => LanguageContentInfo::new(1500, 1200, hashmap!["structs".to_owned() => 4, "enums".to_owned() => 2]),
+                "C".to_owned() => LanguageContentInfo::new(500, 300, hashmap!["structs".to_owned() => 1])];
+        let languages_metadata_map = hashmap!["Rust".to_owned() => LanguageMetadata::new(3, 60_000), "C".to_owned() => LanguageMetadata::new(1, 800)];
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+        let sorted_languages = ["Rust".to_owned(), "C".to_owned()];
+        let mut config = Configuration::new(vec![]);
+
+        assert_eq!("### Details\n\n\
+                | Language | Files | Lines | Code | Extra | Code % | Total size | Average size | Keywords |\n\
+                | :-- | --: | --: | --: | --: | --: | --: | --: | :-- |\n\
+                | Rust | 3 | 1,500 | 1,200 | 300 | 80.00% | 60.0 KBs | 20.0 KBs | enums: 2, structs: 4 |\n\
+                | C | 1 | 500 | 300 | 200 | 60.00% | 800.0 Bytes | 800.0 Bytes | structs: 1 |\n\
+                | **Total** | 4 | 2,000 | 1,500 | 500 | 75.00% | 60.8 KBs | 15.2 KBs | enums: 2, structs: 5 |\n",
+                get_markdown_tables(&sorted_languages, &content_info_map, &languages_metadata_map, &final_stats, &config));
+
+        config.set_should_not_count_keywords(true);
+        let tables = get_markdown_tables(&sorted_languages[..1], &content_info_map, &hashmap!["Rust".to_owned() => LanguageMetadata::new(3, 60_000)],
+                &final_stats, &config);
+        assert!(tables.ends_with("| :-- | --: | --: | --: | --: | --: | --: | --: |\n| Rust | 3 | 1,500 | 1,200 | 300 | 80.00% | 60.0 KBs | 20.0 KBs |\n"));
+
+        let mut content_info_map = content_info_map;
+        content_info_map.get_mut("Rust").unwrap().doc_lines = 250;
+        let tables = get_markdown_tables(&sorted_languages[..1], &content_info_map, &hashmap!["Rust".to_owned() => LanguageMetadata::new(3, 60_000)],
+                &final_stats, &config);
+        assert!(tables.ends_with("| Code % | Doc lines | Total size | Average size |\n| :-- | --: | --: | --: | --: | --: | --: | --: | --: |\n\
+                | Rust | 3 | 1,500 | 1,200 | 300 | 80.00% | 250 | 60.0 KBs | 20.0 KBs |\n"));
+    }
+
+    #[test]
+    fn test_get_prometheus_metrics() {
+        let content_info_map = hashmap!["Rust".to_owned() => LanguageContentInfo::new(1500, 1200, hashmap!["structs".to_owned() => 4]),
+                "C\"".to_owned() => LanguageContentInfo::new(500, 300, HashMap::new())];
+        let languages_metadata_map = hashmap!["Rust".to_owned() => LanguageMetadata::new(3, 60_000), "C\"".to_owned() => LanguageMetadata::new(1, 800)];
+        let sorted_languages = ["Rust".to_owned(), "C\"".to_owned()];
+        let mut config = Configuration::new(vec![]);
+
+        let metrics = get_prometheus_metrics(&sorted_languages, &content_info_map, &languages_metadata_map, &config);
+        assert!(metrics.starts_with("# HELP mezura_files The number of files of each language.\n# TYPE mezura_files gauge\n\
+                mezura_files{language=\"Rust\"} 3\nmezura_files{language=\"C\\\"\"} 1\n# HELP mezura_lines"));
+        assert!(metrics.contains("mezura_extra_lines{language=\"Rust\"} 300\n"));
+        assert!(metrics.ends_with("# TYPE mezura_keywords gauge\nmezura_keywords{language=\"Rust\",keyword=\"structs\"} 4\n"));
+
+        config.set_should_not_count_keywords(true);
+        assert!(get_prometheus_metrics(&sorted_languages, &content_info_map, &languages_metadata_map, &config)
+                .ends_with("# TYPE mezura_bytes gauge\nmezura_bytes{language=\"Rust\"} 60000\nmezura_bytes{language=\"C\\\"\"} 800\n"));
+    }
+
+    #[test]
+    fn test_create_plain_overview_line() {
+        let mut config = Configuration::new(vec![]);
+        config.set_color(config_manager::ColorPolicy::Never);
+        let languages = ["Rust".to_owned(), "C".to_owned(), "Java".to_owned()];
+
+        assert_eq!(format!("Files:    50.00% Rust (|) - 30.00% C (=) - 20.00% Java (+)    [-{}{}{}-]", "|".repeat(25), "=".repeat(15), "+".repeat(10)),
+                create_overview_line("Files:", &[50.0, 30.0, 20.0], &languages, &OVERVIEW_COLORS[..3], &config));
+        config.set_style(config_manager::Style::Unicode);
+        assert_eq!(format!("Files:    50.00% Rust (█) - 30.00% C (▓) - 20.00% Java (▒)    │ {}{}{} │", "█".repeat(25), "▓".repeat(15), "▒".repeat(10)),
+                create_overview_line("Files:", &[50.0, 30.0, 20.0], &languages, &OVERVIEW_COLORS[..3], &config));
+        config.set_should_enable_visuals(true);
+        assert_eq!("Files:    50.00% Rust - 30.00% C - 20.00% Java",
+                create_overview_line("Files:", &[50.0, 30.0, 20.0], &languages, &OVERVIEW_COLORS[..3], &config));
+    }
+
+    #[test]
+    fn test_get_summary_line() {
+        let content_info_map = hashmap!["Rust".to_owned() => LanguageContentInfo::new(15_000, 11_500, HashMap::new())];
+        let languages_metadata_map = hashmap!["Rust".to_owned() => LanguageMetadata::new(155, 60_000)];
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+
+        assert_eq!("Total   ->  155 files  |  lines 15,000 {11,500 code + 3,500 extra}  |  60.0 KBs", get_summary_line(&final_stats));
+    }
+
+    #[test]
+    fn test_get_lines_stats_text() {
+        let mut config = Configuration::new(vec![]);
+        config.set_columns(Some(vec![Column::Code, Column::Comments]));
+        assert_eq!("1,500 code (75.00%) + 500 extra", get_lines_stats_text(2000, 1500, &config));
+        config.set_columns(Some(vec![Column::Comments, Column::Size]));
+        assert_eq!("500 extra", get_lines_stats_text(2000, 1500, &config));
+        config.set_columns(Some(vec![Column::Size]));
+        assert_eq!("", get_lines_stats_text(2000, 1500, &config));
+
+        // The colors are not counted in the length
+        config.set_columns(None);
+        assert_eq!("lines 2,000 {1,500 code (75.00%) + 500 extra}".len(), get_printed_len(&get_lines_stats_text(2000, 1500, &config)));
+        config.set_columns(Some(vec![Column::Lines]));
+        assert_eq!("lines 2,000".len(), get_printed_len(&get_lines_stats_text(2000, 1500, &config)));
+        assert_eq!(5, get_printed_len("\x1b[3;38;2;181;169;138mlines\x1b[0m"));
+    }
+
+    #[test]
+    fn test_get_complexity_text() {
+        let mut config = Configuration::new(vec![]);
+        // The default output has no complexity
+        assert_eq!("", get_complexity_text(30, 4, &config));
+        config.set_complexity(true);
+        assert!(get_complexity_text(30, 4, &config).contains("7.5"));
+        assert_eq!("", get_complexity_text(0, 4, &config));
+    }
+
+    #[test]
+    fn test_get_share_text() {
+        assert_eq!("share 62.50% lines , 75.00% code".len(), get_printed_len(&get_share_text(500, 300, 800, 400)));
+        assert!(get_share_text(500, 300, 800, 400).contains("62.50% lines , 75.00% code"));
+        assert!(get_share_text(0, 0, 0, 0).contains("0.00% lines , 0.00% code"));
+    }
+
+    #[test]
+    fn test_get_row_title_with_long_names() {
+        let mut config = Configuration::new(vec![]);
+        config.set_color(config_manager::ColorPolicy::Never);
+        let names = ["C".to_owned(), "TypeScript React".to_owned()];
+        let languages_metadata_map = hashmap!["C".to_owned() => LanguageMetadata::new(1200, 8000),
+                "TypeScript React".to_owned() => LanguageMetadata::new(3, 400)];
+        let biggest_prefix_standard_spaces = get_biggest_prefix_standard_spaces(&names, &languages_metadata_map);
+        assert_eq!(21, biggest_prefix_standard_spaces);
+
+        let titles = [get_row_title("C", 1200, biggest_prefix_standard_spaces, &config),
+                get_row_title("TypeScript React", 3, biggest_prefix_standard_spaces, &config),
+                get_row_title("Total", 1203, biggest_prefix_standard_spaces, &config)];
+        assert_eq!("TypeScript React       3 files  -> ", titles[1]);
+        assert!(titles.iter().all(|x| get_printed_len(x) == get_printed_len(&titles[1])));
+
+        // Never less than the name, even if the spaces are underestimated
+        let name = "x".repeat(40);
+        config.set_columns(Some(vec![Column::Lines]));
+        assert_eq!(format!("{}   -> ", name), get_row_title(&name, 2, 0, &config));
+    }
+
+    #[test]
+    fn test_get_typical_marker() {
+        assert_eq!("typical", get_typical_marker(100.0, 100.0));
+        assert_eq!("typical", get_typical_marker(125.0, 100.0));
+        assert_eq!("typical", get_typical_marker(75.0, 100.0));
+        assert_eq!("above typical", get_typical_marker(126.0, 100.0));
+        assert_eq!("below typical", get_typical_marker(74.0, 100.0));
+    }
+
     #[test]
     fn test_time_split_from_minutes() {
         assert_eq!((0,0,0),split_minutes_to_D_H_M(0));
@@ -0,0 +1,235 @@
+use std::{io::{BufRead, BufReader, Write}, net::{TcpListener, TcpStream}, time::{Duration, SystemTime}};
+
+use crate::*;
+
+const JSON_TYPE       : &str = "application/json";
+const PROMETHEUS_TYPE : &str = "text/plain; version=0.0.4";
+// So that a client that stops sending or reading doesn't block the requests after it
+const READ_TIMEOUT    : Duration = Duration::from_secs(10);
+const WRITE_TIMEOUT   : Duration = Duration::from_secs(30);
+
+
+// The dirs of the language files and how the languages are made from them again, so that the changes of the files are
+// picked up without restarting the server. Checked before every request
+pub struct LanguagesReloader {
+    pub dirs: Vec<String>,
+    pub load: Box<dyn Fn() -> Result<HashMap<String, Language>, String>>
+}
+
+// The path, the modification time and the size of each file of the dirs, ordered by path
+type FilesState = Vec<(PathBuf, Option<SystemTime>, u64)>;
+
+#[derive(Debug,PartialEq)]
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: String
+}
+
+// The requests are answered one at a time, since every analysis already uses all the threads of the configuration
+pub fn serve(listen: &str, config: Arc<Configuration>, mut language_map_ref: Arc<HashMap<String, Language>>, reloader: Option<LanguagesReloader>)
+-> Result<(), ParseFilesError>
+{
+    let listener = TcpListener::bind(listen).map_err(|x| ParseFilesError::UnavailableAddress(format!("{} ({})", listen, x)))?;
+    println!("\nListening on http://{} ('/analyze?path=<dir>' and '/metrics').", listen);
+
+    let mut files_state = reloader.as_ref().map_or_else(Vec::new, |x| get_files_state(&x.dirs));
+    // A failed connection or a client that went away doesn't stop the server
+    for stream in listener.incoming().flatten() {
+        if let Some(reloader) = &reloader {
+            reload_if_changed(reloader, &mut files_state, &config, &mut language_map_ref);
+        }
+        handle_connection(stream, &config, &language_map_ref);
+    }
+    Ok(())
+}
+
+// The languages are kept as they were if the new ones can't be made, e.g. while a file is half written.
+// They are made again only after the next change
+fn reload_if_changed(reloader: &LanguagesReloader, files_state: &mut FilesState, config: &Configuration,
+        language_map_ref: &mut Arc<HashMap<String, Language>>)
+{
+    let new_state = get_files_state(&reloader.dirs);
+    if new_state == *files_state {
+        return;
+    }
+    *files_state = new_state;
+
+    match (reloader.load)() {
+        Ok(mut language_map) => match prepare_language_map(config, &mut language_map) {
+            Ok(_) => {
+                println!("\nThe language files changed, {} languages were loaded.", language_map.len());
+                *language_map_ref = Arc::new(language_map);
+            },
+            Err(x) => println!("\nThe language files changed, but the old languages are kept: {}", x)
+        },
+        Err(x) => println!("\nThe language files changed, but the old languages are kept: {}", x)
+    }
+}
+
+fn get_files_state(dirs: &[String]) -> FilesState {
+    let mut files_state = dirs.iter().filter_map(|x| fs::read_dir(x).ok()).flatten().flatten()
+            .filter_map(|x| x.metadata().ok().filter(|x| x.is_file()).map(|metadata| (x.path(), metadata.modified().ok(), metadata.len())))
+            .collect::<Vec<_>>();
+    files_state.sort_by(|a, b| a.0.cmp(&b.0));
+    files_state
+}
+
+fn handle_connection(mut stream: TcpStream, config: &Arc<Configuration>, language_map_ref: &Arc<HashMap<String, Language>>) {
+    if let Err(x) = stream.set_read_timeout(Some(READ_TIMEOUT)).and_then(|_| stream.set_write_timeout(Some(WRITE_TIMEOUT))) {
+        println!("\nUnable to set the timeouts of a connection: {}", x);
+        return;
+    }
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    // The headers are read, but none of them is needed
+    let mut header = String::new();
+    while reader.read_line(&mut header).map(|x| x > 0).unwrap_or(false) && !header.trim().is_empty() {
+        header.clear();
+    }
+
+    let response = respond(&request_line, config, language_map_ref);
+    if let Err(x) = stream.write_all(format!("HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            response.status, response.content_type, response.body.len(), response.body).as_bytes())
+    {
+        println!("\nUnable to send the response to '{}': {}", request_line.trim(), x);
+    }
+}
+
+// e.g. 'GET /analyze?path=/home/user/project HTTP/1.1'
+fn respond(request_line: &str, config: &Arc<Configuration>, language_map_ref: &Arc<HashMap<String, Language>>) -> Response {
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method, target),
+        _ => return error_response("400 Bad Request", "malformed request")
+    };
+    if method != "GET" {
+        return error_response("405 Method Not Allowed", "only GET is supported");
+    }
+
+    let (route, query) = target.split_once('?').unwrap_or((target, ""));
+    match route {
+        "/analyze" => {
+            let mut request_config = (**config).clone();
+            if let Some(path) = get_query_value(query, "path") {
+                if !Path::new(&path).exists() {
+                    return error_response("404 Not Found", &format!("'{}' doesn't exist", path));
+                }
+                request_config.dirs = vec![path];
+            }
+            match analyze_silently(Arc::new(request_config.clone()), language_map_ref.clone()) {
+                Ok((analysis, final_stats)) => Response {status: "200 OK", content_type: JSON_TYPE,
+                        body: export::Results::new(&request_config.dirs, &analysis.content_info_map, &analysis.languages_metadata_map, &final_stats)
+                                .with_warnings(&warnings::take()).to_json()},
+                Err(x) => error_response("422 Unprocessable Entity", &x.formatted().to_string())
+            }
+        },
+        "/metrics" => {
+            match analyze_silently(config.clone(), language_map_ref.clone()) {
+                Ok((analysis, _)) => {
+                    let sorted_language_names = result_printer::get_language_names_as_sorted_vec(&analysis.content_info_map,
+                            &analysis.languages_metadata_map, config.sort);
+                    Response {status: "200 OK", content_type: PROMETHEUS_TYPE, body: result_printer::get_prometheus_metrics(&sorted_language_names,
+                            &analysis.content_info_map, &analysis.languages_metadata_map, config)}
+                },
+                Err(x) => error_response("422 Unprocessable Entity", &x.formatted().to_string())
+            }
+        },
+        _ => error_response("404 Not Found", &format!("no such endpoint '{}'", route))
+    }
+}
+
+fn error_response(status: &'static str, message: &str) -> Response {
+    Response {status, content_type: JSON_TYPE, body: format!("{{\"error\": \"{}\"}}", notifier::escape_json_string(message.trim()))}
+}
+
+// The value of the first 'key=value' pair of the query with this key, percent-decoded
+fn get_query_value(query: &str, key: &str) -> Option<String> {
+    query.split('&').find_map(|pair| {
+        let (name, value) = pair.split_once('=')?;
+        if name == key {get_trimmed_if_not_empty(&percent_decode(value))} else {None}
+    })
+}
+
+// '+' is a space and '%xx' is a byte, so that the non-ascii characters are decoded from their utf-8 bytes
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let hex_digit = |i: usize| bytes.get(i).and_then(|x| (*x as char).to_digit(16));
+    let mut i = 0;
+    while i < bytes.len() {
+        match (bytes[i], hex_digit(i + 1), hex_digit(i + 2)) {
+            (b'+', _, _) => decoded.push(b' '),
+            (b'%', Some(high), Some(low)) => {
+                decoded.push((high * 16 + low) as u8);
+                i += 2;
+            },
+            (x, _, _) => decoded.push(x)
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_query_value() {
+        assert_eq!(Some("/home/user/my project".to_owned()), get_query_value("path=%2Fhome%2Fuser%2Fmy+project", "path"));
+        assert_eq!(Some("/tmp/ü".to_owned()), get_query_value("other=1&path=/tmp/%C3%BC", "path"));
+        assert_eq!(Some("100%".to_owned()), get_query_value("path=100%", "path"));
+        assert_eq!(None, get_query_value("path=", "path"));
+        assert_eq!(None, get_query_value("paths=/tmp", "path"));
+    }
+
+    #[test]
+    fn test_respond_to_unknown_requests() {
+        let (config, language_map_ref) = (Arc::new(Configuration::new(vec![])), Arc::new(HashMap::new()));
+        assert_eq!("405 Method Not Allowed", respond("POST /analyze HTTP/1.1", &config, &language_map_ref).status);
+        assert_eq!("404 Not Found", respond("GET /stats HTTP/1.1", &config, &language_map_ref).status);
+        assert_eq!("404 Not Found", respond("GET /analyze?path=/non/existent/dir HTTP/1.1", &config, &language_map_ref).status);
+        assert_eq!("400 Bad Request", respond("\r\n", &config, &language_map_ref).status);
+    }
+
+    #[test]
+    fn test_reload_if_changed() {
+        let dir = std::env::temp_dir().join("mezura-test-reload-languages");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("Rust.txt");
+        fs::write(&file, "rs").unwrap();
+
+        let load_dir = dir.clone();
+        let reloader = LanguagesReloader {dirs: vec![dir.to_str().unwrap().to_owned()], load: Box::new(move || {
+            let extensions = fs::read_to_string(load_dir.join("Rust.txt")).map_err(|x| x.to_string())?;
+            if extensions.is_empty() {
+                return Err("no extensions".to_owned());
+            }
+            let language = Language::new("Rust".to_owned(), extensions.split_whitespace().map(|x| x.to_owned()).collect(), vec![], vec![], vec![], vec![]);
+            Ok(hashmap!["Rust".to_owned() => language])
+        })};
+        let config = Configuration::new(vec![]);
+        let mut files_state = get_files_state(&reloader.dirs);
+        let mut language_map_ref = Arc::new(HashMap::new());
+
+        reload_if_changed(&reloader, &mut files_state, &config, &mut language_map_ref);
+        assert!(language_map_ref.is_empty());
+
+        fs::write(&file, "rs rlib").unwrap();
+        reload_if_changed(&reloader, &mut files_state, &config, &mut language_map_ref);
+        assert_eq!(vec!["rs".to_owned(), "rlib".to_owned()], language_map_ref["Rust"].extensions);
+
+        // A file that can't be loaded keeps the languages as they were
+        fs::write(&file, "").unwrap();
+        reload_if_changed(&reloader, &mut files_state, &config, &mut language_map_ref);
+        assert_eq!(2, language_map_ref["Rust"].extensions.len());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,110 @@
+use crate::*;
+
+
+// The stats of the file of 'file', which is parsed on its own instead of being searched for
+#[derive(Debug,PartialEq)]
+pub struct SingleFileStats {
+    pub path: PathBuf,
+    pub language_name: String,
+    pub bytes: u64,
+    pub file_stats: FileStats
+}
+
+// Parses the file with the language that the search would have found it as, by its extension, name or shebang.
+// The notebooks are parsed by their code cells, as in a run.
+pub fn analyze_file(config: &Configuration, mut language_map: HashMap<String, Language>, path: &str) -> Result<SingleFileStats, ParseFilesError> {
+    // The complexity is always among the stats of a single file
+    let config = &Configuration {complexity: true, ..config.clone()};
+    prepare_language_map(config, &mut language_map)?;
+    let (path, language_map) = (PathBuf::from(path), Arc::new(language_map));
+    let language_name = match find_lang_of_file(&path, &language_map, config).0 {
+        Some(x) if path.is_file() => x,
+        _ => return Err(ParseFilesError::UnsupportedFile(path.to_string_lossy().to_string()))
+    };
+
+    let mut buf = String::new();
+    let file_stats = match language_map.get(&language_name) {
+        Some(x) if notebook::is_parsed_as_notebook(&path, x) =>
+                notebook::parse_notebook_file(&path, &language_name, &mut buf, language_map.clone(), config, &[]),
+        _ => file_parser::parse_file(&path, &language_name, &mut buf, language_map.clone(), config, &[])
+    }.map_err(|x| ParseFilesError::UnparsableFile(path.to_string_lossy().to_string(), x))?;
+    let bytes = path.metadata().map_or(0, |x| x.len());
+    Ok(SingleFileStats {path, language_name, bytes, file_stats})
+}
+
+// e.g.
+// src/ffi.rs (Rust)
+//
+//   Lines      : 1,200
+//   Code       : 900 (75.00%)
+//   Extra      : 300 (25.00%)
+//   Doc lines  : 120
+//   Complexity : 85
+//   Size       : 39.1 KBs
+//   Longest    : 118
+//   Keywords   : structs: 12 , unsafe: 3
+pub fn format_file_stats(stats: &SingleFileStats, config: &Configuration) -> String {
+    let (labels, file_stats) = (labels::get(), &stats.file_stats);
+    let percentage = |x: usize| if file_stats.lines == 0 {0f64} else {x as f64 / file_stats.lines as f64 * 100f64};
+    let extra_lines = file_stats.lines - file_stats.code_lines;
+
+    let mut rows = vec![
+        (labels.lines, with_seperators(file_stats.lines)),
+        (labels.code, format!("{} ({:.2}%)", with_seperators(file_stats.code_lines), percentage(file_stats.code_lines))),
+        (labels.extra, format!("{} ({:.2}%)", with_seperators(extra_lines), percentage(extra_lines))),
+        (labels.doc_lines, with_seperators(file_stats.doc_lines))
+    ];
+    if config.logical_lines {
+        rows.push((labels.logical_lines, with_seperators(file_stats.logical_lines)));
+    }
+    rows.push((labels.complexity, with_seperators(file_stats.complexity)));
+    rows.push((labels.size, format_size(stats.bytes as usize)));
+    rows.push((labels.longest, with_seperators(file_stats.max_line_length)));
+    let keywords = file_stats.keyword_occurences.iter().filter(|(_, x)| config.zero_keywords || **x != 0)
+            .map(|(name, x)| format!("{}: {}", name, with_seperators(*x))).collect::<Vec<_>>();
+    if !keywords.is_empty() {
+        rows.push((labels.keywords, keywords.join(" , ")));
+    }
+    if config.todos.is_some() {
+        rows.push((labels.todos, with_seperators(file_stats.todos.len())));
+    }
+
+    let width = rows.iter().map(|(label, _)| label.chars().count()).max().unwrap_or(0);
+    let rows = rows.iter().map(|(label, value)| format!("  {:width$} : {}", labels::capitalized(label), value, width = width)).collect::<Vec<_>>();
+    format!("{} ({})\n\n{}", config.path_style.format(&stats.path.to_string_lossy()).bold(), stats.language_name, rows.join("\n"))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_file_stats() {
+        let rust = Language::new("Rust".to_owned(), vec!["rs".to_owned()], vec!["\"".to_owned()], vec!["//".to_owned()], vec![], vec![]);
+        let file_stats = FileStats {lines: 1200, code_lines: 900, doc_lines: 120, complexity: 85, max_line_length: 118,
+                keyword_occurences: KeywordOccurences::from([("structs".to_owned(), 12), ("traits".to_owned(), 0), ("unsafe".to_owned(), 3)]),
+                ..FileStats::default()};
+        let stats = SingleFileStats {path: PathBuf::from("/p/ffi.rs"), language_name: rust.name.clone(), bytes: 39_117, file_stats};
+        colored::control::set_override(false);
+        assert_eq!("/p/ffi.rs (Rust)\n\n\
+                    \x20 Lines      : 1,200\n\
+                    \x20 Code       : 900 (75.00%)\n\
+                    \x20 Extra      : 300 (25.00%)\n\
+                    \x20 Doc lines  : 120\n\
+                    \x20 Complexity : 85\n\
+                    \x20 Size       : 39.1 KBs\n\
+                    \x20 Longest    : 118\n\
+                    \x20 Keywords   : structs: 12 , unsafe: 3", format_file_stats(&stats, &Configuration::new(vec![])));
+
+        let path = std::env::temp_dir().join("mezura-test-single-file.rs");
+        fs::write(&path, "// a comment\nfn main() {}\n").unwrap();
+        let stats = analyze_file(&Configuration::new(vec![]), hashmap!["Rust".to_owned() => rust.clone()], &path.to_string_lossy()).unwrap();
+        assert_eq!(("Rust", 2, 1, 26), (stats.language_name.as_str(), stats.file_stats.lines, stats.file_stats.code_lines, stats.bytes));
+        assert!(matches!(analyze_file(&Configuration::new(vec![]), hashmap!["Rust".to_owned() => rust.clone()], "Cargo.toml"),
+                Err(ParseFilesError::UnsupportedFile(_))));
+        assert!(matches!(analyze_file(&Configuration::new(vec![]), hashmap!["Rust".to_owned() => rust], "src"),
+                Err(ParseFilesError::UnsupportedFile(_))));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,101 @@
+use std::{io::Write, process::{Command, Stdio}};
+
+use crate::*;
+
+// The tables are created by the first run, so the database doesn't need to exist beforehand
+const SCHEMA : &str = "CREATE TABLE IF NOT EXISTS runs (id INTEGER PRIMARY KEY, timestamp TEXT NOT NULL, target TEXT NOT NULL, \
+files INTEGER NOT NULL, lines INTEGER NOT NULL, code_lines INTEGER NOT NULL, extra_lines INTEGER NOT NULL, bytes INTEGER NOT NULL);
+CREATE TABLE IF NOT EXISTS languages (run_id INTEGER NOT NULL REFERENCES runs(id), language TEXT NOT NULL, \
+files INTEGER NOT NULL, lines INTEGER NOT NULL, code_lines INTEGER NOT NULL, bytes INTEGER NOT NULL);
+CREATE TABLE IF NOT EXISTS keywords (run_id INTEGER NOT NULL REFERENCES runs(id), language TEXT NOT NULL, \
+keyword TEXT NOT NULL, occurrences INTEGER NOT NULL);
+";
+// The rows of the languages and the keywords belong to the run that was inserted last, in the same transaction
+const LAST_RUN_ID : &str = "(SELECT max(id) FROM runs)";
+
+
+// Appends the run to the database of '--sqlite', so that the history of the results can be queried, e.g.
+// SELECT timestamp, code_lines FROM runs WHERE target = '/home/user/project' ORDER BY timestamp
+pub fn append_run(db_path: &str, content_info_map: &HashMap<String,LanguageContentInfo>, languages_metadata_map: &HashMap<String,LanguageMetadata>,
+        final_stats: &FinalStats, datetime_now: &DateTime<Local>, config: &Configuration) -> Result<(),String>
+{
+    let target = config.remote.clone().unwrap_or_else(|| config.dirs.join(", "));
+    run_sqlite(db_path, &make_insert_script(&target, content_info_map, languages_metadata_map, final_stats, datetime_now))
+}
+
+// A single transaction, so that a failed run leaves nothing behind. The languages and the keywords are in order of name.
+pub fn make_insert_script(target: &str, content_info_map: &HashMap<String,LanguageContentInfo>,
+        languages_metadata_map: &HashMap<String,LanguageMetadata>, final_stats: &FinalStats, datetime_now: &DateTime<Local>) -> String
+{
+    let mut script = String::from("BEGIN;\n");
+    script += SCHEMA;
+    script += &format!("INSERT INTO runs (timestamp, target, files, lines, code_lines, extra_lines, bytes) VALUES ({}, {}, {}, {}, {}, {}, {});\n",
+            quote(&datetime_now.format("%Y-%m-%d %H:%M:%S").to_string()), quote(target), final_stats.files, final_stats.lines,
+            final_stats.code_lines, final_stats.extra_lines, final_stats.bytes_size);
+
+    let mut names = languages_metadata_map.keys().filter(|x| content_info_map.contains_key(*x)).collect::<Vec<_>>();
+    names.sort();
+    for name in names {
+        let (content_info, metadata) = (&content_info_map[name], &languages_metadata_map[name]);
+        script += &format!("INSERT INTO languages VALUES ({}, {}, {}, {}, {}, {});\n", LAST_RUN_ID, quote(name), metadata.files,
+                content_info.lines, content_info.code_lines, metadata.bytes);
+        for (keyword, occurrences) in content_info.keyword_occurences.iter() {
+            script += &format!("INSERT INTO keywords VALUES ({}, {}, {}, {});\n", LAST_RUN_ID, quote(name), quote(keyword), occurrences);
+        }
+    }
+    script + "COMMIT;\n"
+}
+
+// A string literal of SQL, where a quote is escaped by doubling it
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+// There is no SQLite library among the dependencies, so the sqlite3 shell runs the script
+fn run_sqlite(db_path: &str, script: &str) -> Result<(),String> {
+    let mut child = Command::new("sqlite3")
+        .args(["-bail", db_path])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|x| format!("could not run sqlite3 ({})", x))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(script.as_bytes()).map_err(|x| x.to_string())?;
+    }
+    let output = child.wait_with_output().map_err(|x| x.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_owned())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_make_insert_script() {
+        let content_info_map = hashmap!["Rust".to_owned() => LanguageContentInfo::new(1000, 800, hashmap!["unsafe".to_owned() => 3]),
+                "C".to_owned() => LanguageContentInfo::new(500, 300, HashMap::new())];
+        let languages_metadata_map = hashmap!["Rust".to_owned() => LanguageMetadata::new(10, 40_000),
+                "C".to_owned() => LanguageMetadata::new(4, 10_000)];
+        let final_stats = FinalStats::calculate(&content_info_map, &languages_metadata_map, false);
+        let datetime: DateTime<Local> = DateTime::from_str("2021-10-17 09:30:00 +00:00").unwrap();
+
+        let script = make_insert_script("/home/user/it's", &content_info_map, &languages_metadata_map, &final_stats, &datetime);
+        assert!(script.starts_with(&format!("BEGIN;\n{}", SCHEMA)));
+        // The timestamp is in the local time zone
+        assert!(script.ends_with(&format!("INSERT INTO runs (timestamp, target, files, lines, code_lines, extra_lines, bytes) \
+                VALUES ('{}', '/home/user/it''s', 14, 1500, 1100, 400, 50000);\n\
+                INSERT INTO languages VALUES ((SELECT max(id) FROM runs), 'C', 4, 500, 300, 10000);\n\
+                INSERT INTO languages VALUES ((SELECT max(id) FROM runs), 'Rust', 10, 1000, 800, 40000);\n\
+                INSERT INTO keywords VALUES ((SELECT max(id) FROM runs), 'Rust', 'unsafe', 3);\n\
+                COMMIT;\n", datetime.format("%Y-%m-%d %H:%M:%S"))));
+    }
+}
@@ -0,0 +1,117 @@
+use crate::*;
+
+
+// Used with '--stream', to print the stats of each language as soon as it is finished, while the rest are parsed.
+// More files of a language could be found at any time, so a language is finished when the search is over and all of its
+// files were parsed or found faulty. The events are passed on to the observer of the run.
+pub struct StreamingObserver {
+    inner: ObserverRef,
+    state: Mutex<StreamState>
+}
+
+#[derive(Debug, Default)]
+struct StreamState {
+    is_search_over: bool,
+    printed_languages: usize,
+    languages: HashMap<String, LanguageProgress>
+}
+
+#[derive(Debug, Default)]
+struct LanguageProgress {
+    // Found, but neither parsed nor faulty yet
+    outstanding_files: usize,
+    // Of the parsed files only
+    stats: DirStats,
+    is_finished: bool
+}
+
+impl StreamingObserver {
+    pub fn new(inner: ObserverRef) -> Self {
+        StreamingObserver {inner, state: Mutex::new(StreamState::default())}
+    }
+}
+
+impl AnalysisObserver for StreamingObserver {
+    fn on_event(&self, event: &AnalysisEvent) {
+        {
+            // The lock is held while printing, so that the lines of different threads are not mixed
+            let mut state = self.state.lock().unwrap();
+            for (name, stats) in state.update(event) {
+                let is_first = state.printed_languages == 0;
+                state.printed_languages += 1;
+                reporter::hold(|| result_printer::print_finished_language(&name, &FinalStats::new(stats.files, stats.lines, stats.code_lines,
+                        stats.bytes), is_first, state.get_longest_name_len()));
+            }
+        }
+        self.inner.on_event(event);
+    }
+}
+
+impl StreamState {
+    // Returns the languages that were finished by the event, in order of name. The ones whose files were all faulty are left out.
+    fn update(&mut self, event: &AnalysisEvent) -> Vec<(String, DirStats)> {
+        match event {
+            AnalysisEvent::FileDiscovered {language, ..} => {
+                self.languages.entry((*language).to_owned()).or_default().outstanding_files += 1;
+            },
+            AnalysisEvent::FileParsed {language, stats, bytes, ..} => {
+                let progress = self.languages.entry((*language).to_owned()).or_default();
+                progress.outstanding_files = progress.outstanding_files.saturating_sub(1);
+                progress.stats.add_file_stats(stats, *bytes as usize);
+            },
+            AnalysisEvent::FileFaulty(x) => {
+                let progress = self.languages.entry(x.language_name.clone()).or_default();
+                progress.outstanding_files = progress.outstanding_files.saturating_sub(1);
+            },
+            AnalysisEvent::PhaseChanged(AnalysisPhase::Parsing) => self.is_search_over = true,
+            AnalysisEvent::PhaseChanged(_) | AnalysisEvent::FileSkipped {..} | AnalysisEvent::DirSkipped {..} => ()
+        }
+        if !self.is_search_over {
+            return Vec::new();
+        }
+
+        let mut finished = self.languages.iter_mut().filter(|(_, x)| !x.is_finished && x.outstanding_files == 0).map(|(name, x)| {
+            x.is_finished = true;
+            (name.clone(), x.stats)
+        }).filter(|(_, stats)| stats.files != 0).collect::<Vec<_>>();
+        finished.sort_by(|(a, _), (b, _)| a.cmp(b));
+        finished
+    }
+
+    // All the languages are known once the search is over, which is before any of them is printed
+    fn get_longest_name_len(&self) -> usize {
+        self.languages.keys().map(|x| x.chars().count()).max().unwrap_or(0)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_state() {
+        let file_stats = |lines: usize| FileStats {lines, code_lines: lines / 2, keyword_occurences: BTreeMap::new(), has_content: true, doc_lines: 0,
+                complexity: 0, logical_lines: 0, todos: Vec::new(), prose: ProseStats::default(),
+                plugin_metrics: PluginMetrics::new(), content_hash: None,
+                code_line_hashes: Vec::new(), license: None, hygiene: None,
+                max_line_length: 0, long_lines: 0, indentation: None, is_generated: false, is_minified: false, notebook: None};
+        let (a, b, c) = (Path::new("/p/a.rs"), Path::new("/p/b.rs"), Path::new("/p/c.py"));
+        let mut state = StreamState::default();
+
+        for (path, language) in [(a, "Rust"), (b, "Rust"), (c, "Python")] {
+            assert!(state.update(&AnalysisEvent::FileDiscovered {path, language}).is_empty());
+        }
+        // Nothing is finished before the search is over
+        assert!(state.update(&AnalysisEvent::FileParsed {path: c, language: "Python", stats: &file_stats(10), bytes: 100}).is_empty());
+        assert_eq!(vec![("Python".to_owned(), DirStats {files: 1, lines: 10, code_lines: 5, bytes: 100})],
+                state.update(&AnalysisEvent::PhaseChanged(AnalysisPhase::Parsing)));
+
+        assert!(state.update(&AnalysisEvent::FileParsed {path: a, language: "Rust", stats: &file_stats(20), bytes: 300}).is_empty());
+        let faulty_file = FaultyFile::new(b.to_path_buf(), "Rust".to_owned(), FaultyReason::InvalidEncoding, 50);
+        assert_eq!(vec![("Rust".to_owned(), DirStats {files: 1, lines: 20, code_lines: 10, bytes: 300})],
+                state.update(&AnalysisEvent::FileFaulty(&faulty_file)));
+        assert!(state.update(&AnalysisEvent::PhaseChanged(AnalysisPhase::Summarizing)).is_empty());
+        assert_eq!(6, state.get_longest_name_len());
+    }
+}
@@ -0,0 +1,171 @@
+// '--export-treemap <path>': the directories of the breakdown drawn by their lines, as an svg treemap or as a graphviz
+// graph, chosen by the extension of the path. A directory is split among its subdirectories in proportion to their lines,
+// and the rest of its area is taken by its own files.
+
+use crate::*;
+
+const WIDTH : f64 = 1200.0;
+const HEIGHT : f64 = 800.0;
+// The space above the subdirectories of a directory, for its name
+const HEADER_HEIGHT : f64 = 16.0;
+const PADDING : f64 = 2.0;
+// The smallest rectangle that gets its name written in it
+const MIN_LABELED_WIDTH : f64 = 40.0;
+// The colors of the levels, starting from the provided directories, repeated for the deeper ones
+const LEVEL_COLORS : [&str; 5] = ["#e8eef7", "#c6d7ee", "#a3c0e4", "#7fa8da", "#5b91d0"];
+
+
+#[derive(Debug,PartialEq,Clone,Copy)]
+enum TreemapFormat {
+    Svg,
+    Dot
+}
+
+// The path has to end with '.svg', '.dot' or '.gv' and can't be a directory
+pub fn parse_treemap_path(s: &str) -> Option<String> {
+    let path = utils::get_trimmed_if_not_empty(s.trim_matches('"'))?;
+    if get_format(&path).is_none() || Path::new(&path).is_dir() {
+        return None;
+    }
+    Some(path)
+}
+
+fn get_format(path: &str) -> Option<TreemapFormat> {
+    match Path::new(path).extension()?.to_str()?.to_lowercase().as_str() {
+        "svg" => Some(TreemapFormat::Svg),
+        "dot" | "gv" => Some(TreemapFormat::Dot),
+        _ => None
+    }
+}
+
+pub fn write_treemap(path: &str, dir_tree: &[(usize, String, DirStats)]) -> Result<(), String> {
+    let nodes = make_dir_nodes(dir_tree);
+    if nodes.iter().all(|x| x.stats.lines == 0) {
+        return Err("no lines were counted".to_owned());
+    }
+    let contents = match get_format(path).ok_or_else(|| format!("'{}' is neither svg nor dot", path))? {
+        TreemapFormat::Svg => make_svg(&nodes),
+        TreemapFormat::Dot => make_dot(&nodes)
+    };
+    fs::write(path, contents).map_err(|x| x.to_string())
+}
+
+// The provided directories side by side, each split in the other direction than its parent
+fn make_svg(nodes: &[DirNode]) -> String {
+    let mut svg = format!(concat!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" ",
+            "viewBox=\"0 0 {width} {height}\" font-family=\"Verdana,Geneva,DejaVu Sans,sans-serif\" font-size=\"11\">\n"),
+            width = WIDTH, height = HEIGHT);
+    let total_lines = nodes.iter().map(|x| x.stats.lines).sum::<usize>();
+    add_svg_rects(&mut svg, nodes, total_lines, (0.0, 0.0, WIDTH, HEIGHT), 0, "");
+    svg + "</svg>\n"
+}
+
+fn add_svg_rects(svg: &mut String, nodes: &[DirNode], parent_lines: usize, (x, y, width, height): (f64, f64, f64, f64), level: usize,
+        parent_path: &str)
+{
+    let is_horizontal = level.is_multiple_of(2);
+    let mut offset = 0.0;
+    for node in nodes.iter().filter(|x| x.stats.lines != 0) {
+        let fraction = node.stats.lines as f64 / parent_lines as f64;
+        let (rect_x, rect_y, rect_width, rect_height) = if is_horizontal {
+            (x + offset, y, width * fraction, height)
+        } else {
+            (x, y + offset, width, height * fraction)
+        };
+        offset += if is_horizontal {rect_width} else {rect_height};
+
+        let path = if parent_path.is_empty() {node.name.clone()} else {format!("{}/{}", parent_path, node.name)};
+        *svg += &format!("<g><title>{} ({} lines, {} files)</title><rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\" stroke=\"#fff\"/>",
+                escape_xml(&path), with_seperators(node.stats.lines), with_seperators(node.stats.files), rect_x, rect_y, rect_width, rect_height,
+                LEVEL_COLORS[level % LEVEL_COLORS.len()]);
+        if rect_width >= MIN_LABELED_WIDTH && rect_height >= HEADER_HEIGHT {
+            *svg += &format!("<text x=\"{:.1}\" y=\"{:.1}\">{}</text>", rect_x + PADDING * 2.0, rect_y + HEADER_HEIGHT - 4.0, escape_xml(&node.name));
+        }
+        *svg += "</g>\n";
+
+        let inner = (rect_x + PADDING, rect_y + HEADER_HEIGHT, rect_width - 2.0 * PADDING, rect_height - HEADER_HEIGHT - PADDING);
+        if !node.children.is_empty() && inner.2 > 0.0 && inner.3 > 0.0 {
+            add_svg_rects(svg, &node.children, node.stats.lines, inner, level + 1, &path);
+        }
+    }
+}
+
+// A box per directory, pointing to its subdirectories, that is bigger the more lines it has
+fn make_dot(nodes: &[DirNode]) -> String {
+    let mut dot = String::from("digraph treemap {\n  rankdir=LR;\n  node [shape=box, style=filled, fillcolor=\"#c6d7ee\"];\n");
+    let max_lines = nodes.iter().map(|x| x.stats.lines).max().unwrap_or(0).max(1);
+    let mut next_id = 0;
+    for node in nodes {
+        add_dot_node(&mut dot, node, None, max_lines, &mut next_id);
+    }
+    dot + "}\n"
+}
+
+fn add_dot_node(dot: &mut String, node: &DirNode, parent_id: Option<usize>, max_lines: usize, next_id: &mut usize) {
+    let id = *next_id;
+    *next_id += 1;
+    // By the square root, so that the area of the box is in proportion to the lines
+    let scale = (node.stats.lines as f64 / max_lines as f64).sqrt();
+    *dot += &format!("  n{} [label=\"{}\\n{} lines\", width={:.2}, height={:.2}];\n", id, notifier::escape_json_string(&node.name),
+            with_seperators(node.stats.lines), 0.75 + 3.0 * scale, 0.5 + 2.0 * scale);
+    if let Some(parent_id) = parent_id {
+        *dot += &format!("  n{} -> n{};\n", parent_id, id);
+    }
+    for child in &node.children {
+        add_dot_node(dot, child, Some(id), max_lines, next_id);
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_dir_tree() -> Vec<(usize, String, DirStats)> {
+        let stats = |files: usize, lines: usize| DirStats {files, lines, code_lines: lines, bytes: lines * 10};
+        vec![(0, "/p".to_owned(), stats(4, 1000)), (1, "src".to_owned(), stats(3, 750)), (2, "a<b".to_owned(), stats(1, 250)),
+                (1, "tests".to_owned(), stats(1, 250))]
+    }
+
+    #[test]
+    fn test_parse_treemap_path() {
+        assert_eq!(Some("map.svg".to_owned()), parse_treemap_path(" map.svg "));
+        assert_eq!(Some("docs/map.GV".to_owned()), parse_treemap_path("\"docs/map.GV\""));
+        assert_eq!(Some("map.dot".to_owned()), parse_treemap_path("map.dot"));
+        assert_eq!(None, parse_treemap_path("map.png"));
+        assert_eq!(None, parse_treemap_path(""));
+    }
+
+    #[test]
+    fn test_make_svg() {
+        let svg = make_svg(&make_dir_nodes(&get_dir_tree()));
+        assert!(svg.contains("<title>/p (1,000 lines, 4 files)</title><rect x=\"0.0\" y=\"0.0\" width=\"1200.0\" height=\"800.0\""));
+        // Split vertically in the rest of the root, below its name
+        assert!(svg.contains("<title>/p/src (750 lines, 3 files)</title><rect x=\"2.0\" y=\"16.0\" width=\"1196.0\" height=\"586.5\""));
+        assert!(svg.contains("<title>/p/tests (250 lines, 1 files)</title><rect x=\"2.0\" y=\"602.5\" width=\"1196.0\" height=\"195.5\""));
+        // Split horizontally again, taking a third of the width of 'src'
+        assert!(svg.contains("<title>/p/src/a&lt;b (250 lines, 1 files)</title><rect x=\"4.0\" y=\"32.0\" width=\"397.3\""));
+        assert!(svg.contains(">a&lt;b</text>"));
+    }
+
+    #[test]
+    fn test_make_dot() {
+        let dot = make_dot(&make_dir_nodes(&get_dir_tree()));
+        assert!(dot.contains("  n0 [label=\"/p\\n1,000 lines\", width=3.75, height=2.50];\n"));
+        assert!(dot.contains("  n2 [label=\"a<b\\n250 lines\", width=2.25, height=1.50];\n  n1 -> n2;\n"));
+        assert!(dot.contains("  n0 -> n3;\n"));
+    }
+
+    #[test]
+    fn test_write_treemap() {
+        let path = std::env::temp_dir().join("mezura-test-treemap.dot").to_str().unwrap().to_owned();
+        write_treemap(&path, &get_dir_tree()).unwrap();
+        assert!(fs::read_to_string(&path).unwrap().starts_with("digraph treemap {"));
+        assert_eq!(Err("no lines were counted".to_owned()), write_treemap(&path, &[]));
+        fs::remove_file(&path).unwrap();
+    }
+}
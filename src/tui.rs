@@ -0,0 +1,486 @@
+use std::{fs::{File, OpenOptions}, io::{self, Read, Write}, process::{Command, Stdio}};
+
+use crate::*;
+
+const BAR_WIDTH : usize = 30;
+const REDRAW_INTERVAL : Duration = Duration::from_millis(100);
+// The lines of the screen that are not rows: the title, the location, the header, a blank one, the position and the keys
+const NON_ROW_LINES : usize = 6;
+const KEYS_LINE : &str = "j/k move  enter open  backspace up  tab view  s sort  r reverse  q quit";
+
+
+// Used with '--tui'. The terminal is read a key at a time and drawn on the alternate screen, until dropped,
+// when it is restored as it was. The mode is set with 'stty', so it only works on unix terminals.
+pub struct Terminal {
+    tty: File,
+    saved_mode: String
+}
+
+impl Terminal {
+    pub fn enter() -> io::Result<Terminal> {
+        let tty = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+        let saved_mode = run_stty(&tty, &["-g"])?;
+        run_stty(&tty, &["-icanon", "-echo", "-isig", "min", "1", "time", "0"])?;
+        let mut terminal = Terminal {tty, saved_mode};
+        terminal.tty.write_all(b"\x1b[?1049h\x1b[?25l\x1b[H\x1b[2J")?;
+        Ok(terminal)
+    }
+
+    pub fn writer(&self) -> io::Result<File> {
+        self.tty.try_clone()
+    }
+
+    // As (width, height), or 80x24 if 'stty' can't tell
+    fn size(&self) -> (usize, usize) {
+        let size = run_stty(&self.tty, &["size"]).ok().and_then(|x| utils::parse_two_usize_values(&x.replace(' ', ","), 1, 10_000, 1, 10_000));
+        size.map(|(rows, cols)| (cols, rows)).unwrap_or((80, 24))
+    }
+
+    fn read_key(&mut self) -> io::Result<Key> {
+        // The escape sequences of the special keys arrive in a single read
+        let mut buf = [0u8; 8];
+        let len = self.tty.read(&mut buf)?;
+        Ok(parse_key(&buf[..len]))
+    }
+
+    fn draw(&mut self, lines: &[String]) -> io::Result<()> {
+        self.tty.write_all(format!("\x1b[H\x1b[2J{}", lines.join("\r\n")).as_bytes())?;
+        self.tty.flush()
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = self.tty.write_all(b"\x1b[?25h\x1b[?1049l");
+        let _ = run_stty(&self.tty, &[self.saved_mode.as_str()]);
+    }
+}
+
+fn run_stty(tty: &File, args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty").args(args).stdin(tty.try_clone()?).stderr(Stdio::piped()).output()?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    } else {
+        Err(io::Error::other(String::from_utf8_lossy(&output.stderr).trim().to_owned()))
+    }
+}
+
+// Draws the lines of the languages as the files are parsed, in place of the progress bar and the overview.
+// The events are passed on to the observer of the run.
+pub struct LiveChartObserver {
+    inner: ObserverRef,
+    state: Mutex<LiveChart>
+}
+
+struct LiveChart {
+    writer: File,
+    files: usize,
+    languages_lines: HashMap<String, usize>,
+    last_draw_instant: Option<Instant>
+}
+
+impl LiveChartObserver {
+    pub fn new(inner: ObserverRef, writer: File) -> Self {
+        LiveChartObserver {inner, state: Mutex::new(LiveChart {writer, files: 0, languages_lines: HashMap::new(), last_draw_instant: None})}
+    }
+}
+
+impl AnalysisObserver for LiveChartObserver {
+    fn on_event(&self, event: &AnalysisEvent) {
+        if let AnalysisEvent::FileParsed {language, stats, ..} = event {
+            let mut chart = self.state.lock().unwrap();
+            chart.files += 1;
+            *chart.languages_lines.entry(language.to_string()).or_insert(0) += stats.lines;
+            if chart.last_draw_instant.is_none_or(|x| x.elapsed() >= REDRAW_INTERVAL) {
+                let lines = format_live_chart(chart.files, &chart.languages_lines);
+                let _ = chart.writer.write_all(format!("\x1b[H\x1b[2J{}", lines.join("\r\n")).as_bytes());
+                let _ = chart.writer.flush();
+                chart.last_draw_instant = Some(Instant::now());
+            }
+        }
+        self.inner.on_event(event);
+    }
+}
+
+// The languages with the most lines first, e.g.
+// Analyzing... 120 files
+//
+// Rust        [##############################] 12,000
+// Python      [#####-------------------------]  2,000
+fn format_live_chart(files: usize, languages_lines: &HashMap<String, usize>) -> Vec<String> {
+    let mut languages = languages_lines.iter().collect::<Vec<_>>();
+    languages.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    let max_lines = languages.first().map(|x| *x.1).unwrap_or(0);
+    let max_name_len = languages.iter().map(|x| x.0.chars().count()).max().unwrap_or(0);
+
+    let mut lines = vec![format!("{}... {} files", labels::get().analyzing_directories, with_seperators(files)), String::new()];
+    for (name, language_lines) in languages {
+        lines.push(format!("{} {} {}", pad(name, max_name_len), format_bar(*language_lines, max_lines), with_seperators(*language_lines)));
+    }
+    lines
+}
+
+fn format_bar(value: usize, max: usize) -> String {
+    let filled = if max == 0 {0} else {(value as f64 / max as f64 * BAR_WIDTH as f64).round() as usize};
+    format!("[{}{}]", "#".repeat(filled), "-".repeat(BAR_WIDTH - filled))
+}
+
+fn pad(s: &str, len: usize) -> String {
+    format!("{}{}", s, " ".repeat(len.saturating_sub(s.chars().count())))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Key {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Enter,
+    Back,
+    SwitchView,
+    Sort,
+    Reverse,
+    Quit,
+    Other
+}
+
+fn parse_key(bytes: &[u8]) -> Key {
+    match bytes {
+        b"\x1b[A" | b"k" => Key::Up,
+        b"\x1b[B" | b"j" => Key::Down,
+        b"\x1b[5~" => Key::PageUp,
+        b"\x1b[6~" | b" " => Key::PageDown,
+        b"\r" | b"\n" | b"\x1b[C" | b"l" => Key::Enter,
+        b"\x7f" | b"\x08" | b"\x1b[D" | b"h" => Key::Back,
+        b"\t" => Key::SwitchView,
+        b"s" => Key::Sort,
+        b"r" => Key::Reverse,
+        b"q" | b"\x1b" | b"\x03" => Key::Quit,
+        _ => Key::Other
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum View {
+    Languages,
+    Files
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SortColumn {
+    Name,
+    Files,
+    Lines,
+    Code,
+    Size
+}
+
+impl SortColumn {
+    fn next(self) -> SortColumn {
+        match self {
+            Self::Name => Self::Files,
+            Self::Files => Self::Lines,
+            Self::Lines => Self::Code,
+            Self::Code => Self::Size,
+            Self::Size => Self::Name
+        }
+    }
+}
+
+// A language, or a directory or a file. The files have no code lines
+#[derive(Debug, Clone, PartialEq)]
+struct Row {
+    name: String,
+    files: usize,
+    lines: usize,
+    code_lines: Option<usize>,
+    bytes: usize,
+    is_dir: bool
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct DirNode {
+    files: usize,
+    lines: usize,
+    bytes: usize,
+    dirs: BTreeMap<String, DirNode>,
+    file_rows: Vec<Row>
+}
+
+impl DirNode {
+    fn rows(&self) -> Vec<Row> {
+        let dir_rows = self.dirs.iter().map(|(name, x)| Row {name: name.to_owned(), files: x.files, lines: x.lines, code_lines: None,
+                bytes: x.bytes, is_dir: true});
+        dir_rows.chain(self.file_rows.iter().cloned()).collect()
+    }
+
+    fn get(&self, path: &[String]) -> Option<&DirNode> {
+        path.iter().try_fold(self, |node, name| node.dirs.get(name))
+    }
+}
+
+// The directories of the files, where every directory has the sums of all the files under it. With a single root the tree
+// starts inside it, and with more the roots are the first directories.
+fn make_dir_node(file_summaries_map: &HashMap<String, Vec<FileSummary>>, dirs: &[String]) -> DirNode {
+    let mut root_node = DirNode::default();
+    for summary in file_summaries_map.values().flatten() {
+        let mut names = Vec::new();
+        let relative_path = match find_breakdown_root(&summary.path, dirs) {
+            Some(root) => {
+                if dirs.len() > 1 {
+                    names.push(root.to_string_lossy().into_owned());
+                }
+                summary.path.strip_prefix(root).unwrap_or(&summary.path)
+            },
+            None => &summary.path
+        };
+        names.extend(relative_path.iter().map(|x| x.to_string_lossy().into_owned()));
+        let file_name = match names.pop() {
+            Some(x) => x,
+            None => continue
+        };
+
+        let mut node = &mut root_node;
+        for name in names {
+            node.files += 1;
+            node.lines += summary.lines;
+            node.bytes += summary.bytes;
+            node = node.dirs.entry(name).or_default();
+        }
+        node.files += 1;
+        node.lines += summary.lines;
+        node.bytes += summary.bytes;
+        node.file_rows.push(Row {name: file_name, files: 1, lines: summary.lines, code_lines: None, bytes: summary.bytes, is_dir: false});
+    }
+    root_node
+}
+
+fn make_language_rows(content_info_map: &HashMap<String, LanguageContentInfo>, languages_metadata_map: &HashMap<String, LanguageMetadata>)
+        -> Vec<Row>
+{
+    content_info_map.iter().filter_map(|(name, x)| {
+        let metadata = languages_metadata_map.get(name)?;
+        Some(Row {name: name.to_owned(), files: metadata.files, lines: x.lines, code_lines: Some(x.code_lines), bytes: metadata.bytes,
+                is_dir: false})
+    }).collect()
+}
+
+// The names ascending and the numbers descending, unless reversed. The ties are ordered by name
+fn sort_rows(rows: &mut [Row], column: SortColumn, reversed: bool) {
+    rows.sort_by(|a, b| {
+        let ordering = match column {
+            SortColumn::Name => a.name.cmp(&b.name),
+            SortColumn::Files => b.files.cmp(&a.files),
+            SortColumn::Lines => b.lines.cmp(&a.lines),
+            SortColumn::Code => b.code_lines.cmp(&a.code_lines),
+            SortColumn::Size => b.bytes.cmp(&a.bytes)
+        };
+        let ordering = ordering.then_with(|| a.name.cmp(&b.name));
+        if reversed {ordering.reverse()} else {ordering}
+    });
+}
+
+struct Browser {
+    view: View,
+    sort: SortColumn,
+    reversed: bool,
+    selected: usize,
+    offset: usize,
+    // The directories opened in the files view, from the root
+    dir_path: Vec<String>
+}
+
+impl Browser {
+    fn new() -> Self {
+        Browser {view: View::Languages, sort: SortColumn::Lines, reversed: false, selected: 0, offset: 0, dir_path: Vec::new()}
+    }
+
+    fn rows(&self, language_rows: &[Row], root_node: &DirNode) -> Vec<Row> {
+        let mut rows = match self.view {
+            View::Languages => language_rows.to_vec(),
+            View::Files => root_node.get(&self.dir_path).map(DirNode::rows).unwrap_or_default()
+        };
+        sort_rows(&mut rows, self.sort, self.reversed);
+        rows
+    }
+
+    // Returns false when the browser should be closed
+    fn handle_key(&mut self, key: Key, language_rows: &[Row], root_node: &DirNode, page: usize) -> bool {
+        let rows = self.rows(language_rows, root_node);
+        match key {
+            Key::Up => self.selected = self.selected.saturating_sub(1),
+            Key::Down => self.selected = (self.selected + 1).min(rows.len().saturating_sub(1)),
+            Key::PageUp => self.selected = self.selected.saturating_sub(page),
+            Key::PageDown => self.selected = (self.selected + page).min(rows.len().saturating_sub(1)),
+            Key::Enter => match rows.get(self.selected) {
+                Some(row) if self.view == View::Files && row.is_dir => {
+                    self.dir_path.push(row.name.clone());
+                    self.selected = 0;
+                },
+                _ => ()
+            },
+            Key::Back => {
+                // The directory that is left is selected in its parent
+                if let Some(name) = self.dir_path.pop() {
+                    self.selected = self.rows(language_rows, root_node).iter().position(|x| x.name == name).unwrap_or(0);
+                }
+            },
+            Key::SwitchView => {
+                self.view = if self.view == View::Languages {View::Files} else {View::Languages};
+                self.selected = 0;
+            },
+            Key::Sort => self.sort = self.sort.next(),
+            Key::Reverse => self.reversed = !self.reversed,
+            Key::Quit => return false,
+            Key::Other => ()
+        }
+        true
+    }
+
+    // The selected row is kept on the screen, scrolling as little as possible
+    fn scroll(&mut self, page: usize) {
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if page != 0 && self.selected >= self.offset + page {
+            self.offset = self.selected + 1 - page;
+        }
+    }
+
+    fn render(&self, rows: &[Row], dirs: &[String], width: usize, height: usize) -> Vec<String> {
+        let page = height.saturating_sub(NON_ROW_LINES);
+        let views = [(View::Languages, "Languages"), (View::Files, "Files")].iter()
+                .map(|(view, name)| if *view == self.view {format!("[{}]", name)} else {format!(" {} ", name)}).collect::<Vec<_>>();
+        let sort_name = format!("{:?}", self.sort).to_lowercase();
+        let direction = if self.reversed {"reversed"} else {"default"};
+        let location = match self.view {
+            View::Languages => "All languages".to_owned(),
+            View::Files => {
+                let root = if dirs.len() == 1 {dirs[0].trim_end_matches('/')} else {""};
+                format!("{}/{}", root, self.dir_path.join("/"))
+            }
+        };
+
+        let max_lines = rows.iter().map(|x| x.lines).max().unwrap_or(0);
+        let name_len = rows.iter().map(|x| x.name.chars().count() + if x.is_dir {1} else {0}).max().unwrap_or(0).clamp(4, 40);
+        let mut lines = vec![
+            format!("mezura  {}  sort: {} ({})", views.join(" "), sort_name, direction),
+            location,
+            format!("  {} {:>8} {:>12} {:>12} {:>10}  Lines", pad("Name", name_len), "Files", "Lines", "Code", "Size")
+        ];
+        for (i, row) in rows.iter().enumerate().skip(self.offset).take(page) {
+            let name = if row.is_dir {format!("{}/", row.name)} else {row.name.clone()};
+            let name = if name.chars().count() > name_len {name.chars().take(name_len).collect()} else {name};
+            let code_lines = row.code_lines.map(with_seperators).unwrap_or_else(|| "-".to_owned());
+            let line = format!("{} {} {:>8} {:>12} {:>12} {:>10}  {}", if i == self.selected {">"} else {" "}, pad(&name, name_len),
+                    with_seperators(row.files), with_seperators(row.lines), code_lines, utils::format_size(row.bytes),
+                    format_bar(row.lines, max_lines));
+            lines.push(if i == self.selected {format!("\x1b[7m{}\x1b[0m", truncate(&line, width))} else {truncate(&line, width)});
+        }
+        lines.resize(page + 4, String::new());
+        lines.push(format!("{} of {}", if rows.is_empty() {0} else {self.selected + 1}, rows.len()));
+        lines.push(KEYS_LINE.to_owned());
+        lines.iter().map(|x| if x.starts_with('\x1b') {x.to_owned()} else {truncate(x, width)}).collect()
+    }
+}
+
+fn truncate(s: &str, width: usize) -> String {
+    s.chars().take(width).collect()
+}
+
+// Shows the results until the browser is closed. The languages are listed first, and the files view opens directories
+// down from the roots.
+pub fn browse(terminal: &mut Terminal, content_info_map: &HashMap<String, LanguageContentInfo>,
+        languages_metadata_map: &HashMap<String, LanguageMetadata>, file_summaries_map: &HashMap<String, Vec<FileSummary>>,
+        config: &Configuration) -> io::Result<()>
+{
+    let language_rows = make_language_rows(content_info_map, languages_metadata_map);
+    let root_node = make_dir_node(file_summaries_map, &config.dirs);
+    let mut browser = Browser::new();
+    loop {
+        let (width, height) = terminal.size();
+        let page = height.saturating_sub(NON_ROW_LINES);
+        browser.scroll(page);
+        let rows = browser.rows(&language_rows, &root_node);
+        terminal.draw(&browser.render(&rows, &config.dirs, width, height))?;
+
+        let key = terminal.read_key()?;
+        if !browser.handle_key(key, &language_rows, &root_node, page) {
+            return Ok(());
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(path: &str, lines: usize, bytes: usize) -> FileSummary {
+        FileSummary {path: PathBuf::from(path), lines, bytes}
+    }
+
+    #[test]
+    fn test_make_dir_node() {
+        let file_summaries_map = hashmap!["Rust".to_owned() => vec![summary("/project/src/main.rs", 10, 100), summary("/project/src/a/b.rs", 5, 50)],
+                "Python".to_owned() => vec![summary("/project/setup.py", 2, 20)]];
+        let root_node = make_dir_node(&file_summaries_map, &["/project".to_owned()]);
+        assert_eq!((root_node.files, root_node.lines, root_node.bytes), (3, 17, 170));
+
+        let mut rows = root_node.rows();
+        sort_rows(&mut rows, SortColumn::Lines, false);
+        assert_eq!(rows, vec![Row {name: "src".to_owned(), files: 2, lines: 15, code_lines: None, bytes: 150, is_dir: true},
+                Row {name: "setup.py".to_owned(), files: 1, lines: 2, code_lines: None, bytes: 20, is_dir: false}]);
+        let src_node = root_node.get(&["src".to_owned()]).unwrap();
+        assert_eq!(src_node.rows().iter().map(|x| x.name.as_str()).collect::<Vec<_>>(), vec!["a", "main.rs"]);
+        assert_eq!(root_node.get(&["src".to_owned(), "a".to_owned()]).unwrap().lines, 5);
+
+        // With more roots, the roots are the first directories
+        let root_node = make_dir_node(&file_summaries_map, &["/project/src".to_owned(), "/project".to_owned()]);
+        assert_eq!(root_node.dirs.keys().collect::<Vec<_>>(), vec!["/project", "/project/src"]);
+        assert_eq!(root_node.dirs["/project/src"].lines, 15);
+    }
+
+    #[test]
+    fn test_browser() {
+        let file_summaries_map = hashmap!["Rust".to_owned() => vec![summary("/project/src/main.rs", 10, 100), summary("/project/b.rs", 20, 10)]];
+        let root_node = make_dir_node(&file_summaries_map, &["/project".to_owned()]);
+        let language_rows = vec![Row {name: "Rust".to_owned(), files: 2, lines: 30, code_lines: Some(25), bytes: 110, is_dir: false}];
+        let mut browser = Browser::new();
+
+        // The files are not opened in the languages view
+        assert!(browser.handle_key(Key::Enter, &language_rows, &root_node, 10));
+        assert!(browser.dir_path.is_empty());
+
+        browser.handle_key(parse_key(b"\t"), &language_rows, &root_node, 10);
+        assert_eq!(browser.rows(&language_rows, &root_node).iter().map(|x| x.name.as_str()).collect::<Vec<_>>(), vec!["b.rs", "src"]);
+        browser.handle_key(parse_key(b"r"), &language_rows, &root_node, 10);
+        assert_eq!(browser.rows(&language_rows, &root_node)[0].name, "src");
+        browser.handle_key(parse_key(b"\r"), &language_rows, &root_node, 10);
+        assert_eq!(browser.dir_path, vec!["src".to_owned()]);
+        assert_eq!(browser.rows(&language_rows, &root_node)[0].name, "main.rs");
+
+        // Going up selects the directory that was left
+        browser.handle_key(parse_key(b"\x1b[A"), &language_rows, &root_node, 10);
+        browser.handle_key(parse_key(b"\x7f"), &language_rows, &root_node, 10);
+        assert!(browser.dir_path.is_empty());
+        assert_eq!(browser.selected, 0);
+        browser.handle_key(Key::Down, &language_rows, &root_node, 10);
+        browser.handle_key(Key::Down, &language_rows, &root_node, 10);
+        assert_eq!(browser.selected, 1);
+
+        let rows = browser.rows(&language_rows, &root_node);
+        let lines = browser.render(&rows, &["/project".to_owned()], 200, 10);
+        assert_eq!(lines.len(), 10);
+        assert_eq!(lines[1], "/project/");
+        assert!(lines[4].starts_with("\x1b[7m> b.rs"));
+        assert_eq!(lines[8], "2 of 2");
+        assert!(!browser.handle_key(parse_key(b"q"), &language_rows, &root_node, 10));
+    }
+
+    #[test]
+    fn test_format_live_chart() {
+        let lines = format_live_chart(3, &hashmap!["Rust".to_owned() => 100, "Go".to_owned() => 50]);
+        assert_eq!(lines[2], format!("Rust [{}] 100", "#".repeat(30)));
+        assert_eq!(lines[3], format!("Go   [{}{}] 50", "#".repeat(15), "-".repeat(15)));
+    }
+}
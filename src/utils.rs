@@ -1,4 +1,5 @@
 use crate::*;
+use config_manager::{NumberFormat, SizeUnits};
 
 
 #[macro_export]
@@ -20,6 +21,68 @@ pub fn round_2(num: f64) -> f64 {
     (num * 100.0).round() / 100.0
 }
 
+// How the sizes are printed. It is set once at startup from '--size-units' and '--size-precision'
+static SIZE_FORMAT : Mutex<(SizeUnits, usize)> = Mutex::new((SizeUnits::Si, 1));
+
+pub fn set_size_format(units: SizeUnits, precision: usize) {
+    *SIZE_FORMAT.lock().unwrap() = (units, precision);
+}
+
+// The decimal places of the sizes, which are whole numbers in bytes
+pub fn get_size_precision() -> usize {
+    match *SIZE_FORMAT.lock().unwrap() {
+        (SizeUnits::Bytes, _) => 0,
+        (_, precision) => precision
+    }
+}
+
+pub fn get_size_and_measurement(value: usize) -> (f64, String) {
+    get_size_and_measurement_in(value, SIZE_FORMAT.lock().unwrap().0)
+}
+
+fn get_size_and_measurement_in(value: usize, units: SizeUnits) -> (f64, String) {
+    match units {
+        SizeUnits::Si if value >= 1_000_000 => (value as f64 / 1_000_000f64, "MBs".to_owned()),
+        SizeUnits::Si if value >= 1000 => (value as f64 / 1000f64, "KBs".to_owned()),
+        SizeUnits::Binary if value >= 1 << 20 => (value as f64 / (1 << 20) as f64, "MiBs".to_owned()),
+        SizeUnits::Binary if value >= 1 << 10 => (value as f64 / (1 << 10) as f64, "KiBs".to_owned()),
+        _ => (value as f64, "Bytes".to_owned())
+    }
+}
+
+// Rounded to the decimal places that it is printed with
+pub fn round_size(size: f64) -> f64 {
+    let factor = 10f64.powi(get_size_precision() as i32);
+    (size * factor).round() / factor
+}
+
+// e.g. '85.2 KBs', or '85,213 Bytes' in bytes
+pub fn format_size(value: usize) -> String {
+    let (units, precision) = *SIZE_FORMAT.lock().unwrap();
+    format_size_in(value, units, precision)
+}
+
+fn format_size_in(value: usize, units: SizeUnits, precision: usize) -> String {
+    if units == SizeUnits::Bytes {
+        return format!("{} Bytes", with_seperators(value));
+    }
+    let (size, measurement) = get_size_and_measurement_in(value, units);
+    format!("{:.*} {}", precision, size, measurement)
+}
+
+
+// The extensions and groups of '--ext', in lowercase and without a leading dot, e.g. 'all, -.JSON' to 'all' and '-json'
+pub fn parse_extension_filters(s: &str) -> Vec<String> {
+    s.split(',').filter_map(|x| {
+        let x = x.trim().to_lowercase();
+        let (sign, extension) = match x.strip_prefix('-') {
+            Some(rest) => ("-", rest.trim()),
+            None => ("", x.as_str())
+        };
+        let extension = extension.strip_prefix('.').unwrap_or(extension);
+        if extension.is_empty() {None} else {Some(format!("{}{}", sign, extension))}
+    }).collect()
+}
 
 pub fn parse_languages_to_vec(s: &str) -> Vec<String> {
     fn remove_dot_prefix(str: &str) -> &str {
@@ -35,6 +98,19 @@ pub fn parse_languages_to_vec(s: &str) -> Vec<String> {
     .collect::<Vec<_>>()
 }
 
+pub fn parse_keyword_names_to_vec(s: &str) -> Vec<String> {
+    s.split(',')
+    .filter_map(|x| get_trimmed_if_not_empty(&x.to_lowercase()))
+    .collect::<Vec<_>>()
+}
+
+// Unlike the keyword names, the markers keep their case, since they are matched exactly
+pub fn parse_todo_markers_to_vec(s: &str) -> Vec<String> {
+    s.split(',')
+    .filter_map(get_trimmed_if_not_empty)
+    .collect::<Vec<_>>()
+}
+
 pub fn parse_paths_to_vec(s: &str) -> Vec<String> {
     s.split(',')
     .filter_map(|x| {
@@ -44,6 +120,18 @@ pub fn parse_paths_to_vec(s: &str) -> Vec<String> {
     .collect::<Vec<_>>()
 }
 
+// The dir is canonicalized, so that every visited path can be compared against it after resolving
+// symlinks and '..' components.
+pub fn parse_canonical_dir(s: &str) -> Option<String> {
+    let s = s.trim();
+    let s = s.strip_prefix('"').unwrap_or(s).strip_suffix('"').unwrap_or(s);
+    if s.is_empty() || !Path::new(s).is_dir() {
+        return None;
+    }
+
+    canonicalize_to_str(Path::new(s))
+}
+
 pub fn parse_usize_value(s: &str, min: usize, max: usize) -> Option<usize> {
     if let Ok(num) = s.trim().parse::<usize>() {
         if num <= max && num >= min {
@@ -56,6 +144,16 @@ pub fn parse_usize_value(s: &str, min: usize, max: usize) -> Option<usize> {
     }
 }
 
+// A size in bytes, optionally followed by a unit: B, KB, MB or GB (case insensitive, 1KB = 1024 bytes)
+pub fn parse_size_value(s: &str) -> Option<u64> {
+    let s = s.trim().to_lowercase();
+    let s = s.strip_suffix('s').unwrap_or(&s);
+    let (number, multiplier) = [("kb", 1 << 10), ("mb", 1 << 20), ("gb", 1 << 30), ("b", 1)].iter()
+            .find_map(|(unit, multiplier)| s.strip_suffix(unit).map(|x| (x, *multiplier)))
+            .unwrap_or((s, 1));
+    number.trim().parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
 pub fn parse_two_usize_values(s: &str, min1: usize, max1: usize, min2: usize, max2: usize) -> Option<(usize,usize)> {
     let elements = s.split_whitespace().filter_map(|x| get_trimmed_if_not_empty(x)).collect::<Vec<_>>();
     if elements.len() != 2 {
@@ -103,33 +201,117 @@ pub fn extract_file_contents(file_path: &str) -> Option<String> {
     }
 }
 
-pub fn get_file_extension(path: &Path) -> Option<&str> {
+// Reads at most 'max_bytes' bytes, so that big files without line breaks are not read whole
+pub fn read_first_line(path: &Path, max_bytes: usize) -> Option<String> {
+    let mut bytes = Vec::with_capacity(max_bytes);
+    File::open(path).ok()?.take(max_bytes as u64).read_to_end(&mut bytes).ok()?;
+    let line = bytes.split(|x| *x == b'\n').next()?;
+    Some(String::from_utf8_lossy(line).trim_end().to_owned())
+}
+
+// Like convert_to_absolute, strips the "\\?\" prefix that windows puts on canonicalized paths and uses forward slashes.
+pub fn canonicalize_to_str(path: &Path) -> Option<String> {
+    let buf = fs::canonicalize(path).ok()?;
+    let str_path = buf.to_str()?;
+    Some(str_path.strip_prefix(r"\\?\").unwrap_or(str_path).replace('\\', "/"))
+}
+
+// Resolves symlinks and '..' components before comparing, so nothing can escape the root through them.
+pub fn is_path_inside_root(path: &Path, root: &str) -> bool {
+    match canonicalize_to_str(path) {
+        Some(x) => Path::new(&x).starts_with(root),
+        None => false
+    }
+}
+
+// The path as seen from the base directory, going up with '..' if needed. None if they have different roots
+// (e.g. different drives on windows). Both paths are expected to be absolute.
+pub fn get_relative_path(path: &Path, base: &Path) -> Option<PathBuf> {
+    let (mut path_components, mut base_components) = (path.components().peekable(), base.components().peekable());
+    if path_components.next() != base_components.next() {
+        return None;
+    }
+    while path_components.peek().is_some() && path_components.peek() == base_components.peek() {
+        path_components.next();
+        base_components.next();
+    }
+
+    let mut relative_path = base_components.map(|_| "..").collect::<PathBuf>();
+    relative_path.extend(path_components);
+    if relative_path.as_os_str().is_empty() {
+        relative_path.push(".");
+    }
+    Some(relative_path)
+}
+
+// The pattern matches if it matches the start of the dir, so "src" also matches "src/a/b". A '**' segment matches
+// any number of dirs and a '*' inside a segment matches any number of characters.
+pub fn is_dir_matching_pattern(dir: &str, pattern: &str) -> bool {
+    fn matches_segments(dir_segments: &[&str], pattern_segments: &[&str]) -> bool {
+        match pattern_segments.split_first() {
+            None => true,
+            Some((&"**", rest)) => (0..=dir_segments.len()).any(|i| matches_segments(&dir_segments[i..], rest)),
+            Some((first, rest)) => match dir_segments.split_first() {
+                Some((dir_first, dir_rest)) => is_name_matching_pattern(dir_first, first) && matches_segments(dir_rest, rest),
+                None => false
+            }
+        }
+    }
+
+    let split = |x: &str| x.replace('\\', "/").split('/').filter(|x| !x.is_empty() && *x != ".").map(|x| x.to_owned()).collect::<Vec<_>>();
+    let (dir_segments, pattern_segments) = (split(dir), split(pattern));
+    matches_segments(&dir_segments.iter().map(|x| x.as_str()).collect::<Vec<_>>(),
+            &pattern_segments.iter().map(|x| x.as_str()).collect::<Vec<_>>())
+}
+
+// The whole name has to match, with a '*' matching any number of characters, e.g. "parser_test.go" and "*_test.*"
+pub fn is_name_matching_pattern(name: &str, pattern: &str) -> bool {
+    match pattern.split_once('*') {
+        None => name == pattern,
+        Some((prefix, rest)) => {
+            match name.strip_prefix(prefix) {
+                Some(remaining) => (0..=remaining.len()).filter(|i| remaining.is_char_boundary(*i))
+                        .any(|i| is_name_matching_pattern(&remaining[i..], rest)),
+                None => false
+            }
+        }
+    }
+}
+
+// In lowercase, so that e.g. the timings of 'a.RS' and 'b.rs' are summed together
+pub fn get_file_extension(path: &Path) -> Option<String> {
     match path.extension() {
-        Some(x) => x.to_str(),
+        Some(x) => x.to_str().map(str::to_ascii_lowercase),
         None => None
     }
 }
 
 
+// The thousands separator of the printed numbers. It is set once at startup from '--number-format'
+static SEPERATOR : Mutex<Option<char>> = Mutex::new(Some(','));
+
+pub fn set_number_format(number_format: NumberFormat) {
+    *SEPERATOR.lock().unwrap() = number_format.separator();
+}
+
 pub fn with_seperators(i: usize) -> String {
-    let mut s = String::new();
-    let i_str = i.to_string();
-    let a = i_str.chars().rev().enumerate();
-    for (idx, val) in a {
-        if idx != 0 && idx % 3 == 0 {
-            s.insert(0, ',');
-        }
-        s.insert(0, val);
-    }
-    s
+    with_seperators_str(&i.to_string())
 }
 
 pub fn with_seperators_str(i_str: &str) -> String {
+    with_seperator(i_str, *SEPERATOR.lock().unwrap())
+}
+
+fn with_seperator(i_str: &str, seperator: Option<char>) -> String {
+    let seperator = match seperator {
+        Some(x) => x,
+        None => return i_str.to_owned()
+    };
     let mut s = String::new();
     let a = i_str.chars().rev().enumerate();
     for (idx, val) in a {
         if idx != 0 && idx % 3 == 0 {
-            s.insert(0, ',');
+            s.insert(0, seperator);
         }
         s.insert(0, val);
     }
@@ -137,6 +319,9 @@ pub fn with_seperators_str(i_str: &str) -> String {
 }
 
 pub fn num_of_seperators(i: usize) -> usize {
+    if SEPERATOR.lock().unwrap().is_none() {
+        return 0;
+    }
     let mut input = i;
     let mut commas = 0;
     loop {
@@ -173,6 +358,14 @@ mod Tests{
         assert_eq!("1,234,567",with_seperators(1234567));
     }
 
+    #[test]
+    pub fn test_with_seperator() {
+        assert_eq!("1.234.567",with_seperator("1234567", NumberFormat::Dot.separator()));
+        assert_eq!("12 345",with_seperator("12345", NumberFormat::Space.separator()));
+        assert_eq!("123",with_seperator("123", NumberFormat::Space.separator()));
+        assert_eq!("1234567",with_seperator("1234567", NumberFormat::Plain.separator()));
+    }
+
     #[test]
     pub fn test_parse_languages_to_vec() {
         assert_eq!(Vec::<String>::new(), parse_languages_to_vec(","));
@@ -190,6 +383,61 @@ mod Tests{
         assert_eq!(vec!["a".to_owned(),"b/b".to_owned()], parse_paths_to_vec(" a  ,  b\\b "));
     }
 
+    #[test]
+    pub fn test_is_path_inside_root() {
+        let root = canonicalize_to_str(Path::new("./src")).unwrap();
+        assert!(is_path_inside_root(Path::new("./src"), &root));
+        assert!(is_path_inside_root(Path::new("./src/lib.rs"), &root));
+        assert!(is_path_inside_root(Path::new("./src/../src/utils.rs"), &root));
+
+        assert!(!is_path_inside_root(Path::new("./src/.."), &root));
+        assert!(!is_path_inside_root(Path::new("./src/../Cargo.toml"), &root));
+        assert!(!is_path_inside_root(Path::new("./src/non_existant"), &root));
+    }
+
+    #[test]
+    pub fn test_get_relative_path() {
+        let base = Path::new("/home/user/project");
+        assert_eq!(Some(PathBuf::from("src/lib.rs")), get_relative_path(Path::new("/home/user/project/src/lib.rs"), base));
+        assert_eq!(Some(PathBuf::from(".")), get_relative_path(base, base));
+        assert_eq!(Some(PathBuf::from("../other/a.rs")), get_relative_path(Path::new("/home/user/other/a.rs"), base));
+        assert_eq!(Some(PathBuf::from("../../..")), get_relative_path(Path::new("/"), base));
+        assert_eq!(None, get_relative_path(Path::new("relative/a.rs"), base));
+
+        let working_dir = canonicalize_to_str(Path::new(".")).unwrap();
+        let path = format!("{}/src/lib.rs", working_dir);
+        assert_eq!("src/lib.rs", config_manager::PathStyle::Relative.format(&path));
+        assert_eq!(path, config_manager::PathStyle::Absolute.format(&path));
+        assert_eq!("C:/project/src/lib.rs", config_manager::PathStyle::Unix.format("C:\\project\\src/lib.rs"));
+    }
+
+    #[test]
+    pub fn test_is_dir_matching_pattern() {
+        assert!(is_dir_matching_pattern("src", "src"));
+        assert!(is_dir_matching_pattern("src/a/b", "src/"));
+        assert!(is_dir_matching_pattern("crates/a/src", "crates/**"));
+        assert!(is_dir_matching_pattern("crates", "crates/**"));
+        assert!(is_dir_matching_pattern("crates/a/src", "crates/*/src"));
+        assert!(is_dir_matching_pattern("a/b/src/c", "**/src"));
+        assert!(is_dir_matching_pattern("lib-core/x", "lib-*"));
+        assert!(is_dir_matching_pattern("src\\a", "./src/a"));
+
+        assert!(!is_dir_matching_pattern("", "src"));
+        assert!(!is_dir_matching_pattern("testdata/src", "src"));
+        assert!(!is_dir_matching_pattern("srcs", "src"));
+        assert!(!is_dir_matching_pattern("crates/a/tests", "crates/*/src"));
+        assert!(!is_dir_matching_pattern("core-lib", "lib-*"));
+    }
+
+    #[test]
+    pub fn test_is_name_matching_pattern() {
+        assert!(is_name_matching_pattern("parser_test.go", "*_test.*"));
+        assert!(is_name_matching_pattern("app.spec.ts", "*.spec.*"));
+        assert!(is_name_matching_pattern("conftest.py", "conftest.py"));
+        assert!(!is_name_matching_pattern("test_parser.py", "*_test.*"));
+        assert!(!is_name_matching_pattern("latest.rs", "test*"));
+    }
+
     #[test]
     pub fn test_parse_usize_values() {
         assert_eq!(None,parse_usize_value("0", 1, 8));
@@ -214,4 +462,31 @@ mod Tests{
         assert_eq!(Some((4,12)),parse_two_usize_values("4 12", 1, 4, 1, 12));
         assert_eq!(Some((2,6)),parse_two_usize_values("2 6", 1, 4, 1, 12));
     }
+
+    #[test]
+    pub fn test_parse_size_value() {
+        assert_eq!(Some(0),parse_size_value("0"));
+        assert_eq!(Some(512),parse_size_value(" 512 "));
+        assert_eq!(Some(512),parse_size_value("512B"));
+        assert_eq!(Some(10 * 1024),parse_size_value("10KB"));
+        assert_eq!(Some(10 * 1024),parse_size_value("10 kbs"));
+        assert_eq!(Some(3 * 1024 * 1024),parse_size_value("3mb"));
+        assert_eq!(Some(2 * 1024 * 1024 * 1024),parse_size_value("2GB"));
+        assert_eq!(None,parse_size_value(""));
+        assert_eq!(None,parse_size_value("KB"));
+        assert_eq!(None,parse_size_value("-1"));
+        assert_eq!(None,parse_size_value("1.5MB"));
+        assert_eq!(None,parse_size_value("1TB"));
+    }
+
+    #[test]
+    pub fn test_format_size_in() {
+        assert_eq!("900.0 Bytes",format_size_in(900, SizeUnits::Si, 1));
+        assert_eq!("1.0 KBs",format_size_in(1000, SizeUnits::Si, 1));
+        assert_eq!("1.23 MBs",format_size_in(1_234_567, SizeUnits::Si, 2));
+        assert_eq!("1000 Bytes",format_size_in(1000, SizeUnits::Binary, 0));
+        assert_eq!("1.5 KiBs",format_size_in(1536, SizeUnits::Binary, 1));
+        assert_eq!("1.177 MiBs",format_size_in(1_234_567, SizeUnits::Binary, 3));
+        assert_eq!("1,234,567 Bytes",format_size_in(1_234_567, SizeUnits::Bytes, 2));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,117 @@
+// The problems of a run that don't stop it, e.g. an option of the configuration file that is not known or a directory
+// that couldn't be read. They are collected while the configuration is read and the directories are searched, by any
+// thread, and shown together after the results instead of in between the other messages.
+
+use lazy_static::lazy_static;
+
+use crate::*;
+
+lazy_static! {
+    static ref WARNINGS : Mutex<Vec<RunWarning>> = Mutex::new(Vec::new());
+}
+
+#[derive(Debug,PartialEq,Clone,Copy)]
+pub enum WarningKind {
+    // An option of a configuration file that no version knows, e.g. misspelled
+    UnknownConfigKey,
+    // A directory of the search that couldn't be listed, for another reason than its permissions
+    UnreadableDir,
+    PermissionDenied,
+    // The threads of '--threads' that were more than the maximum
+    ClampedThreads
+}
+
+#[derive(Debug,PartialEq,Clone)]
+pub struct RunWarning {
+    pub kind: WarningKind,
+    pub message: String
+}
+
+impl WarningKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WarningKind::UnknownConfigKey => "unknown config key",
+            WarningKind::UnreadableDir => "unreadable directory",
+            WarningKind::PermissionDenied => "permission denied",
+            WarningKind::ClampedThreads => "clamped threads"
+        }
+    }
+}
+
+impl RunWarning {
+    pub fn to_json(&self) -> String {
+        format!("{{\"kind\": \"{}\", \"message\": \"{}\"}}", self.kind.as_str(), notifier::escape_json_string(&self.message))
+    }
+}
+
+// The same warning is kept once, e.g. of a configuration file that is read again by 'serve'
+pub fn add(kind: WarningKind, message: String) {
+    let warning = RunWarning {kind, message};
+    let mut warnings = WARNINGS.lock().unwrap_or_else(|x| x.into_inner());
+    if !warnings.contains(&warning) {
+        warnings.push(warning);
+    }
+}
+
+// The ones of an io error of a path, told apart by whether it was for the permissions
+pub fn add_io_error(path: &Path, error: &std::io::Error) {
+    let kind = if error.kind() == std::io::ErrorKind::PermissionDenied {WarningKind::PermissionDenied} else {WarningKind::UnreadableDir};
+    add(kind, format!("'{}': {}", path.display(), error));
+}
+
+// One for the directories that were skipped for their permissions and one for the rest, with their number instead of
+// their paths, which are listed with '--verbose'
+pub fn add_unreadable_dirs(unreadable_dirs: &[UnreadableDir]) {
+    for warning in make_unreadable_dirs_warnings(unreadable_dirs) {
+        add(warning.kind, warning.message);
+    }
+}
+
+fn make_unreadable_dirs_warnings(unreadable_dirs: &[UnreadableDir]) -> Vec<RunWarning> {
+    let permission_denied = unreadable_dirs.iter().filter(|x| x.permission_denied).count();
+    let unreadable = unreadable_dirs.len() - permission_denied;
+    let dirs_str = |x: usize| format!("{} {}", with_seperators(x), if x == 1 {"directory"} else {"directories"});
+    let mut warnings = Vec::new();
+    if permission_denied != 0 {
+        warnings.push(RunWarning {kind: WarningKind::PermissionDenied,
+                message: format!("{} skipped due to permissions, the results are incomplete.", dirs_str(permission_denied))});
+    }
+    if unreadable != 0 {
+        warnings.push(RunWarning {kind: WarningKind::UnreadableDir,
+                message: format!("{} couldn't be read, the results are incomplete.", dirs_str(unreadable))});
+    }
+    warnings
+}
+
+// Empties the warnings, so that the next run of the process starts without them
+pub fn take() -> Vec<RunWarning> {
+    std::mem::take(&mut *WARNINGS.lock().unwrap_or_else(|x| x.into_inner()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_warning_to_json() {
+        let warning = RunWarning {kind: WarningKind::UnknownConfigKey, message: "'colour' is not an option".to_owned()};
+        assert_eq!("{\"kind\": \"unknown config key\", \"message\": \"'colour' is not an option\"}", warning.to_json());
+        let warning = RunWarning {kind: WarningKind::PermissionDenied, message: "'C:\\secret' \"denied\"".to_owned()};
+        assert_eq!("{\"kind\": \"permission denied\", \"message\": \"'C:\\\\secret' \\\"denied\\\"\"}", warning.to_json());
+    }
+
+    #[test]
+    fn test_make_unreadable_dirs_warnings() {
+        assert!(make_unreadable_dirs_warnings(&[]).is_empty());
+
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        let gone = std::io::Error::from(std::io::ErrorKind::NotFound);
+        let dirs = [UnreadableDir::new(Path::new("/p/a"), &denied), UnreadableDir::new(Path::new("/p/b"), &gone),
+                UnreadableDir::new(Path::new("/p/c"), &denied), UnreadableDir::new(Path::new("/p/d"), &denied)];
+        assert!(dirs[0].permission_denied && !dirs[1].permission_denied);
+        assert_eq!(vec![RunWarning {kind: WarningKind::PermissionDenied, message: "3 directories skipped due to permissions, the results are incomplete.".to_owned()},
+                RunWarning {kind: WarningKind::UnreadableDir, message: "1 directory couldn't be read, the results are incomplete.".to_owned()}],
+                make_unreadable_dirs_warnings(&dirs));
+    }
+}
@@ -0,0 +1,16 @@
+// The entry point of the archived project
+use std::env;
+
+fn main() {
+    let args = env::args().collect::<Vec<_>>();
+    if args.len() > 1 {
+        println!("Hello, {}!", args[1]);
+    } else {
+        println!("Hello, world!");
+    }
+
+    for (i, arg) in args.iter().enumerate() {
+        // TODO: print the arguments in columns
+        println!("{}: {}", i, arg);
+    }
+}
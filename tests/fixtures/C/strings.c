@@ -0,0 +1,15 @@
+#include <stdio.h>
+
+// struct Commented;
+struct point {
+    int x;
+    int y;
+};
+
+int main(void) {
+    printf("// not a comment\n");
+    printf("/* not a comment either */\n");
+    /* a comment
+       that spans lines */
+    return 0;
+}
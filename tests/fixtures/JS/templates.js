@@ -0,0 +1,7 @@
+// a comment
+const html = `<div>
+  // not a comment ${user.name /* but this is */}
+  ${items.map(x => `<li>${x} /* nor this */</li>`)}
+</div>`;
+
+class Page {}
@@ -0,0 +1,8 @@
+# a comment
+class Greeter:
+    """A docstring with a # inside"""
+
+    def greet(self):
+        print("# not a comment")
+        print('# nor this one')  # but this is
+        return 'done'
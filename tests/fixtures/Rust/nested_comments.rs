@@ -0,0 +1,9 @@
+/* outer
+   /* inner */
+   struct StillComment;
+*/
+enum Shape {
+    Circle, /* trailing */
+}
+
+trait Area {}
@@ -0,0 +1,13 @@
+/// A url is not a comment
+struct Link {
+    url: &'static str
+}
+
+fn main() {
+    let url = "https://example.com"; // a trailing comment
+    let block = "/* not a comment */";
+    let raw = r#"// still a "string" "#;
+
+    // struct Comment;
+    /* struct Hidden; */
+}
@@ -0,0 +1,11 @@
+# a comment
+cat <<EOF > out.txt
+# not a comment
+function inside_the_heredoc
+EOF
+
+function greet {
+	cat <<-'END'
+	# nor this one
+	END
+}
@@ -1,5 +1,4 @@
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex};
+use std::{collections::{HashMap, HashSet}, sync::{Arc, Mutex}};
 use crossbeam_deque::{Injector, Worker};
 use mezura::*;
 use mezura::config_manager::Threads;
@@ -18,9 +17,8 @@ fn test_whole_workflow () {
     let config = Arc::new(config);
     let mut files_present = FilesPresent::default();
     let faulty_files_ref : FaultyFilesListMut  = Arc::new(Mutex::new(Vec::new()));
-    let finish_condition_ref = Arc::new(AtomicBool::new(false));
+    let files_signal_ref = Arc::new(FilesSignal::new());
     let language_map = Arc::new(language_map);
-    let languages_content_info_ref = Arc::new(Mutex::new(make_language_stats(language_map.clone())));
     let files_injector = Arc::new(Injector::new());
     let dirs_injector = Arc::new(Injector::new());
     let producer_termination_states = Arc::new(Mutex::new(vec![false]));
@@ -28,22 +26,23 @@ fn test_whole_workflow () {
 
     assert!(languages_metadata_map.lock().unwrap().len() == language_map_len);
 
-    calculate_single_file_stats_or_add_to_injector(&config, &dirs_injector, &files_injector, &mut files_present, &language_map, &languages_metadata_map);
+    calculate_single_file_stats_or_add_to_injector(&config, &dirs_injector, &files_injector, &mut files_present, &language_map, &languages_metadata_map, &NoObserver);
 
-    let (total_files_num, relevant_files_num, _) = producer::search_for_files(0, files_injector.clone(), dirs_injector.clone(),
-         Worker::new_fifo(), producer_termination_states, language_map.clone(), languages_metadata_map.clone(), config.clone());
+    let (total_files_num, relevant_files_num, _, _, _, _) = producer::search_for_files(0, files_injector.clone(), dirs_injector.clone(),
+         Worker::new_fifo(), producer_termination_states, language_map.clone(), languages_metadata_map.clone(), config.clone(), &files_signal_ref,
+         &ResourceLimits::new(0, 0), &Arc::new(Mutex::new(HashSet::new())), &None, &ignore::IgnoreRules::default(), &[], &NoObserver);
 
-    finish_condition_ref.store(true, Ordering::Relaxed);
-    consumer::start_parsing_files(0, files_injector, faulty_files_ref.clone(), finish_condition_ref, languages_content_info_ref.clone(),
-         language_map.clone(), config);
-    
-    let mut content_info_map_guard = languages_content_info_ref.lock();
-    let content_info_map = content_info_map_guard.as_deref_mut().unwrap();
+    files_signal_ref.finish();
+    let parser_context = consumer::ParserContext {files_injector, faulty_files: faulty_files_ref.clone(), files_signal: files_signal_ref,
+         dirs_stats: Arc::new(Mutex::new(HashMap::new())), parse_timings: Arc::new(Mutex::new(ParseTimings::default())),
+         language_map: language_map.clone(), config, progress_sender: None, resource_limits: Arc::new(ResourceLimits::new(0, 0)),
+         observer: Arc::new(NoObserver), plugins: Arc::new(Vec::new())};
+    let mut content_info_map = consumer::start_parsing_files(0, &parser_context);
 
     let mut languages_metadata_map_guard = languages_metadata_map.lock();
     let languages_metadata_map = languages_metadata_map_guard.as_deref_mut().unwrap();
 
-    remove_languages_with_0_files(content_info_map, languages_metadata_map);
+    remove_languages_with_0_files(&mut content_info_map, languages_metadata_map);
     
     assert!(relevant_files_num != 0 && total_files_num != 0);
     let first_lang_metadata = languages_metadata_map.iter().next().unwrap().1;
@@ -57,3 +56,88 @@ fn test_whole_workflow () {
     assert!(keyword_num != 0);
 }
 
+
+#[test]
+fn test_discover() {
+    let current_dir = env!("CARGO_MANIFEST_DIR").replace("\\", "/");
+    let config = config_manager::create_config_from_args(&format!("{}/src --threads 2 1 --exclude consumer.rs",current_dir)).unwrap();
+    let language_map = io_handler::parse_supported_languages_to_map(&LOCAL_APP_PATHS.languages_dir).unwrap().0;
+
+    let discovered_files = discover(config, language_map);
+    let rust_files_num = std::fs::read_dir(format!("{}/src", current_dir)).unwrap()
+            .filter(|x| x.as_ref().unwrap().path().extension().is_some_and(|x| x == "rs")).count();
+
+    assert_eq!(rust_files_num - 1, discovered_files.len());
+    assert!(discovered_files.iter().all(|x| x.extension == "rs" && x.language_name == "Rust" && x.size != 0));
+    assert!(!discovered_files.iter().any(|x| x.path.ends_with("consumer.rs")));
+    assert!(discovered_files.windows(2).all(|x| x[0].path < x[1].path));
+}
+
+// Every file of 'tests/fixtures/<language name>/' is parsed and compared with the counts of the '.expected' file next to it,
+// e.g. 'strings.rs.expected' for 'strings.rs'. The expected file has a 'name value' pair per line, for 'lines', 'code'
+// and 'docs' and for every keyword that occurs at least once.
+#[test]
+fn test_fixtures() {
+    let fixtures_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let language_map = io_handler::parse_supported_languages_to_map(&LOCAL_APP_PATHS.languages_dir).unwrap().0;
+
+    let mut fixtures_num = 0;
+    for lang_dir in std::fs::read_dir(&fixtures_dir).unwrap().flatten() {
+        let lang_name = lang_dir.file_name().to_string_lossy().into_owned();
+        let language = language_map.get(&lang_name).unwrap_or_else(|| panic!("No language named '{}' for its fixtures", lang_name));
+        for file in std::fs::read_dir(lang_dir.path()).unwrap().flatten() {
+            let path = file.path();
+            if path.extension().is_some_and(|x| x == "expected") {
+                continue;
+            }
+            let expected_path = format!("{}.expected", path.display());
+            let expected = std::fs::read_to_string(&expected_path).unwrap_or_else(|_| panic!("Missing '{}'", expected_path));
+            let expected = expected.lines().filter(|x| !x.trim().is_empty()).map(|x| {
+                let (name, value) = x.trim().rsplit_once(' ').unwrap();
+                (name.trim().to_owned(), value.parse::<usize>().unwrap())
+            }).collect::<std::collections::BTreeMap<_,_>>();
+
+            let file_stats = file_parser::parse_file_for_tests(&path, language).unwrap();
+            let mut actual = std::collections::BTreeMap::from([("lines".to_owned(), file_stats.lines),
+                    ("code".to_owned(), file_stats.code_lines), ("docs".to_owned(), file_stats.doc_lines)]);
+            actual.extend(file_stats.keyword_occurences.into_iter().filter(|x| x.1 != 0));
+
+            assert_eq!(expected, actual, "{}", path.display());
+            fixtures_num += 1;
+        }
+    }
+    assert!(fixtures_num != 0);
+}
+
+#[test]
+fn test_exit_codes() {
+    let run = |args: &[&str]| std::process::Command::new(env!("CARGO_BIN_EXE_mezura")).args(args).output().unwrap().status.code();
+
+    assert_eq!(Some(6), run(&["./", "--no-such-option"]));
+    assert_eq!(Some(6), run(&["./no/such/dir"]));
+    assert_eq!(Some(6), run(&["merge"]));
+    assert_eq!(Some(5), run(&["merge", "./no/such/results.json"]));
+    assert_eq!(Some(5), run(&["snapshot", "diff", "no-such-snapshot", "no-such-snapshot"]));
+}
+
+#[test]
+fn test_archive_over_inflation_limit() {
+    let current_dir = env!("CARGO_MANIFEST_DIR").replace("\\", "/");
+    let config = config_manager::create_config_from_args(&format!("{}/test_dir/archives/bomb.tar.gz", current_dir)).unwrap();
+    let language_map = Arc::new(io_handler::parse_supported_languages_to_map(&LOCAL_APP_PATHS.languages_dir).unwrap().0);
+    let languages_metadata_map = Arc::new(Mutex::new(make_language_metadata(&language_map)));
+    let files_injector = Arc::new(Injector::new());
+    let mut files_present = FilesPresent::default();
+
+    calculate_single_file_stats_or_add_to_injector(&config, &Arc::new(Injector::new()), &files_injector, &mut files_present, &language_map,
+            &languages_metadata_map, &NoObserver);
+
+    // The entry after the one over the limit is not reached
+    assert_eq!((2, 2), (files_present.total_files, files_present.relevant_files));
+    assert_eq!(1, files_injector.len());
+    assert_eq!(1, files_present.faulty_archive_entries.len());
+    let faulty_file = &files_present.faulty_archive_entries[0];
+    assert!(faulty_file.path.ends_with("bomb.tar.gz/bomb/b.rs"));
+    assert_eq!((FaultyReason::OverInflated, 8 * 1024 * 1024), (faulty_file.error.clone(), faulty_file.bytes));
+    assert_eq!(2, languages_metadata_map.lock().unwrap()["Rust"].files);
+}